@@ -0,0 +1,170 @@
+//! Interactive expression evaluator.
+//!
+//! Evaluates expressions against the cells of a CSV file,
+//! useful for debugging the formula language.
+//!
+//! # Usage
+//! `cargo run -p hermes-core --example repl [-- <data.csv>]`
+//!
+//! Each line is evaluated as an expression, e.g. `a1 + b2 * 2`.
+//! Lines starting with `:` are commands, see `:help`.
+use hermes_core::{data, expr};
+use std::{
+    env,
+    io::{self, BufRead, Write},
+    path::Path,
+    process::ExitCode,
+};
+
+const PROMPT: &str = "> ";
+const HELP: &str = "\
+Enter an expression to evaluate it.
+Cells of the loaded CSV can be referenced as `a1`, `$b$2`, etc.
+
+Commands:
+  :help          Show this message.
+  :at <cell>     Set the cell expressions are evaluated in. Defaults to `a1`.
+  :size          Show the number of rows and columns of the loaded data.
+  :quit, :q      Exit.";
+
+fn main() -> ExitCode {
+    let sheet = match env::args_os().nth(1) {
+        None => Sheet::default(),
+        Some(path) => match Sheet::from_path(&path) {
+            Ok(sheet) => sheet,
+            Err(err) => {
+                eprintln!("could not load `{}`: {err}", path.to_string_lossy());
+                return ExitCode::FAILURE;
+            }
+        },
+    };
+
+    let mut origin = data::CellPath {
+        sheet: 0,
+        row: 0,
+        col: 0,
+    };
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+    let mut line = String::new();
+    loop {
+        print!("{PROMPT}");
+        stdout.flush().ok();
+
+        line.clear();
+        match stdin.lock().read_line(&mut line) {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!("could not read input: {err}");
+                return ExitCode::FAILURE;
+            }
+        }
+
+        let input = line.trim();
+        if input.is_empty() {
+            continue;
+        }
+
+        if let Some(command) = input.strip_prefix(':') {
+            let (command, arg) = match command.split_once(char::is_whitespace) {
+                Some((command, arg)) => (command, arg.trim()),
+                None => (command, ""),
+            };
+
+            match command {
+                "q" | "quit" => break,
+                "help" => println!("{HELP}"),
+                "size" => {
+                    let (rows, cols) = sheet.size();
+                    println!("{rows} rows, {cols} columns");
+                }
+                "at" => match data::CellRef::from_str(arg) {
+                    Some(cell) => {
+                        origin.row = cell.row;
+                        origin.col = cell.col;
+                    }
+                    None => eprintln!("invalid cell `{arg}`"),
+                },
+                _ => eprintln!("unknown command `{command}`, see `:help`"),
+            }
+            continue;
+        }
+
+        match expr::eval(input, &sheet, &origin) {
+            Ok(value) => println!("{value}"),
+            Err(err) => eprintln!("error: {err:?}"),
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Cells of a CSV file, indexed by row then column.
+#[derive(Default)]
+struct Sheet {
+    rows: Vec<Vec<expr::Value>>,
+}
+
+impl Sheet {
+    fn from_path(path: impl AsRef<Path>) -> Result<Self, csv::Error> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .from_path(path)?;
+
+        let rows = reader
+            .records()
+            .map(|record| record.map(|record| record.iter().map(parse_value).collect()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { rows })
+    }
+
+    /// # Returns
+    /// `(rows, columns)`
+    fn size(&self) -> (usize, usize) {
+        let cols = self.rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        (self.rows.len(), cols)
+    }
+}
+
+impl expr::Context for &Sheet {
+    fn cell_value(
+        self,
+        cell_ref: &data::CellRef,
+        _origin: &data::CellPath,
+    ) -> Result<expr::Value, expr::ContextError> {
+        match &cell_ref.sheet {
+            data::SheetRef::Relative | data::SheetRef::Absolute(data::SheetIndex::Index(0)) => {}
+            data::SheetRef::Absolute(_) => return Err(expr::ContextError::CellRefDoesNotExist),
+        }
+
+        let value = self
+            .rows
+            .get(cell_ref.row as usize)
+            .and_then(|row| row.get(cell_ref.col as usize))
+            .cloned()
+            .unwrap_or(expr::Value::Empty);
+
+        Ok(value)
+    }
+}
+
+/// Interpret the raw contents of a CSV field.
+fn parse_value(value: &str) -> expr::Value {
+    let value = value.trim();
+    if value.is_empty() {
+        expr::Value::Empty
+    } else if let Ok(value) = value.parse::<i64>() {
+        expr::Value::Int(value)
+    } else if let Ok(value) = value.parse::<f64>() {
+        expr::Value::Float(value)
+    } else if value.eq_ignore_ascii_case("true") {
+        expr::Value::Bool(true)
+    } else if value.eq_ignore_ascii_case("false") {
+        expr::Value::Bool(false)
+    } else {
+        expr::Value::String(value.to_string())
+    }
+}
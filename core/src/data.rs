@@ -4,31 +4,154 @@ use std::fmt;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+/// Index type for sheet rows, columns, and sheets.
+/// `u32` by default, or `u16` if the `index-u16` feature is enabled.
+#[cfg(not(feature = "index-u16"))]
+pub type IndexType = u32;
+#[cfg(feature = "index-u16")]
 pub type IndexType = u16;
+
+/// Maximum number of rows or columns a sheet may contain.
+/// Sizes must be representable by [`IndexType`],
+/// so the largest valid index is `MAX_SHEET_SIZE - 1`.
+pub const MAX_SHEET_SIZE: IndexType = IndexType::MAX;
 pub const SHEET_DELIMETER: char = '!';
+/// Delimeter of sheet labels which can not be written bare.
+/// Within a quoted label, the quote is escaped by doubling it.
+/// e.g. `'My Sheet'!A1`, `'Bob''s'!A1`
+pub const SHEET_QUOTE: char = '\'';
 pub const REF_MODE_SIGIL: char = '$';
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum RefMode {
     Relative,
     Absolute,
 }
 
+/// Serialized as its string form, e.g. `"2"`, `"sheet"`, `"'My Sheet'"`.
 #[derive(Clone, Debug, derive_more::From, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SheetIndex {
     Index(IndexType),
     Label(String),
 }
 
+/// Serialized as the string form of the sheet if absolute, or `null` if relative.
 #[derive(Clone, Debug, derive_more::From, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SheetRef {
     Relative,
     Absolute(SheetIndex),
 }
 
+impl SheetIndex {
+    /// Parse a string.
+    /// Integers are sheet indices, anything else is a label.
+    /// Labels may be quoted, in which case they are always labels.
+    /// e.g. `2`, `sheet`, `'My Sheet'`, `'2'`.
+    ///
+    /// # Returns
+    /// `None` if the string is empty or a quoted label is invalid.
+    pub fn from_str(value: impl AsRef<str>) -> Option<Self> {
+        let value = value.as_ref();
+        if value.starts_with(SHEET_QUOTE) {
+            let (label, rest) = Self::split_quoted_label(value)?;
+            return rest.is_empty().then_some(Self::Label(label));
+        }
+
+        if value.is_empty() {
+            None
+        } else if let Ok(idx) = value.parse::<IndexType>() {
+            Some(Self::Index(idx))
+        } else {
+            Some(Self::Label(value.to_string()))
+        }
+    }
+
+    /// Split a quoted label from the start of a string.
+    /// e.g. `'Bob''s'!a1` is split into `Bob's` and `!a1`.
+    ///
+    /// # Returns
+    /// `(label, rest)` with the label unescaped,
+    /// or `None` if the string does not start with a closed quoted label.
+    fn split_quoted_label(value: &str) -> Option<(String, &str)> {
+        let mut label = String::new();
+        let mut chars = value.char_indices().skip(1).peekable();
+        while let Some((idx, ch)) = chars.next() {
+            if ch != SHEET_QUOTE {
+                label.push(ch);
+                continue;
+            }
+
+            if chars.next_if(|(_, ch)| *ch == SHEET_QUOTE).is_some() {
+                label.push(ch);
+            } else {
+                return Some((label, &value[idx + ch.len_utf8()..]));
+            }
+        }
+
+        None
+    }
+
+    /// Whether a sheet label must be quoted to be used in a cell reference.
+    /// Labels may be written bare if they start with a letter and
+    /// only contain letters, digits, and underscores (`_`).
+    pub fn label_requires_quotes(label: impl AsRef<str>) -> bool {
+        let mut chars = label.as_ref().chars();
+        let Some(first) = chars.next() else {
+            return true;
+        };
+
+        !first.is_alphabetic() || !chars.all(|ch| ch.is_alphanumeric() || ch == '_')
+    }
+}
+
+impl fmt::Display for SheetIndex {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Index(idx) => write!(f, "{idx}"),
+            Self::Label(label) => {
+                if Self::label_requires_quotes(label) {
+                    let escaped = label.replace(SHEET_QUOTE, &SHEET_QUOTE.to_string().repeat(2));
+                    write!(f, "{SHEET_QUOTE}{escaped}{SHEET_QUOTE}")
+                } else {
+                    write!(f, "{label}")
+                }
+            }
+        }
+    }
+}
+
+impl SheetRef {
+    /// Resolve the referenced sheet to its index.
+    ///
+    /// # Arguments
+    /// + `origin`: Index of the sheet the reference originates from,
+    ///     used if the reference is relative.
+    /// + `labels`: Label of each sheet, in order.
+    ///
+    /// # Returns
+    /// `None` if the sheet does not exist.
+    pub fn resolve<I, S>(&self, origin: IndexType, labels: I) -> Option<IndexType>
+    where
+        I: IntoIterator<Item = S>,
+        I::IntoIter: ExactSizeIterator,
+        S: AsRef<str>,
+    {
+        let mut labels = labels.into_iter();
+        let idx = match self {
+            Self::Relative => origin,
+            Self::Absolute(SheetIndex::Index(idx)) => *idx,
+            Self::Absolute(SheetIndex::Label(label)) => {
+                let idx = labels.position(|sheet| sheet.as_ref() == label)?;
+                return IndexType::try_from(idx).ok();
+            }
+        };
+
+        ((idx as usize) < labels.len()).then_some(idx)
+    }
+}
+
 impl From<Option<SheetIndex>> for SheetRef {
     fn from(value: Option<SheetIndex>) -> Self {
         match value {
@@ -38,8 +161,49 @@ impl From<Option<SheetIndex>> for SheetRef {
     }
 }
 
+/// Notation used to label and reference cells.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ReferenceStyle {
+    /// Columns are labeled alphabetically and rows numerically.
+    /// e.g. `B3`
+    #[default]
+    A1,
+
+    /// Rows and columns are both labeled numerically.
+    /// e.g. `R3C2`
+    R1C1,
+}
+
+impl ReferenceStyle {
+    /// Label of a column.
+    /// e.g. `B` in A1 style, `2` in R1C1 style.
+    pub fn col_label(&self, col: IndexType) -> String {
+        match self {
+            Self::A1 => utils::index_to_col(col),
+            Self::R1C1 => utils::index_to_row(col),
+        }
+    }
+
+    /// Label of a cell.
+    /// e.g. `B3` in A1 style, `R3C2` in R1C1 style.
+    pub fn cell_label(&self, cell: &CellIndex) -> String {
+        match self {
+            Self::A1 => cell.to_string(),
+            Self::R1C1 => format!(
+                "R{}C{}",
+                utils::index_to_row(cell.row),
+                utils::index_to_row(cell.col)
+            ),
+        }
+    }
+}
+
 /// Absolute path to a cell within a workbook.
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct CellPath {
     pub sheet: IndexType,
     pub row: IndexType,
@@ -47,8 +211,8 @@ pub struct CellPath {
 }
 
 /// Path to a cell via reference.
+/// Serialized as its string form, e.g. `"sheet!$A1"`.
 #[derive(Clone, Debug, PartialEq, Eq)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CellRef {
     pub sheet: SheetRef,
     pub row: IndexType,
@@ -157,23 +321,14 @@ impl CellRef {
 
 impl CellRef {
     /// Parse a string.
-    /// Valid cell indexes have the form `[<sheet>!]<a-z>[<a-z>]\d+`.
-    /// e.g. `a1`, `b5`, `d40`, `bf300`, `sheet1!s4`, `my_sheet!bf232`, `0!a4`.
+    /// Valid cell indexes have the form `[<sheet>!]<a-z>+\d+`.
+    /// e.g. `a1`, `b5`, `d40`, `bf300`, `acb24`, `sheet1!s4`, `my_sheet!bf232`, `0!a4`.
+    /// Sheet labels may be quoted, with embedded quotes doubled.
+    /// e.g. `'My Sheet'!a1`, `'Bob''s'!a1`, `'0'!a1`.
+    /// Quoted sheets are always labels.
     /// Sheet labels and letters are case insensitive.
     pub fn from_str(value: impl AsRef<str>) -> Option<Self> {
-        let value = value.as_ref();
-        let (sheet, cell) = match value.split_once(SHEET_DELIMETER) {
-            Some((sheet, cell)) => (Some(sheet), cell),
-            None => (None, value),
-        };
-
-        let sheet = sheet.map(|sheet| {
-            if let Some(sheet) = sheet.parse::<IndexType>().ok() {
-                SheetIndex::Index(sheet)
-            } else {
-                SheetIndex::Label(sheet.to_string())
-            }
-        });
+        let (sheet, cell) = Self::split_sheet(value.as_ref())?;
 
         let mut col = vec![];
         let mut row = vec![];
@@ -211,7 +366,7 @@ impl CellRef {
             .into_iter()
             .collect::<String>()
             .parse::<IndexType>()
-            .expect("row is a valid index");
+            .ok()?;
         let col = utils::col_to_index(col)?;
         let row = utils::row_to_index(row)?;
 
@@ -225,6 +380,143 @@ impl CellRef {
     }
 }
 
+impl CellRef {
+    /// Parse a string in R1C1 style.
+    /// Valid references have the form `[<sheet>!]R[<row>]C[<col>]`,
+    /// where the row and column are either a 1-based absolute index, e.g. `R3C2`,
+    /// an offset from `origin` in brackets, e.g. `R[-1]C[2]`,
+    /// or omitted for an offset of zero, e.g. `RC[1]`.
+    /// Sheets follow the same rules as [`CellRef::from_str`].
+    /// Markers are case insensitive.
+    ///
+    /// # Arguments
+    /// + `origin`: Cell relative references are resolved from.
+    pub fn from_r1c1(value: impl AsRef<str>, origin: &CellIndex) -> Option<Self> {
+        let (sheet, cell) = Self::split_sheet(value.as_ref())?;
+        let (row, row_mode, rest) = Self::parse_r1c1_component(cell, 'R', origin.row)?;
+        let (col, col_mode, rest) = Self::parse_r1c1_component(rest, 'C', origin.col)?;
+        if !rest.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            sheet: sheet.into(),
+            row,
+            col,
+            row_mode,
+            col_mode,
+        })
+    }
+
+    /// Format the reference in R1C1 style.
+    ///
+    /// # Arguments
+    /// + `origin`: Cell relative references are written as offsets from.
+    pub fn to_r1c1(&self, origin: &CellIndex) -> String {
+        let mut value = String::new();
+        if let SheetRef::Absolute(sheet) = &self.sheet {
+            value.push_str(&format!("{sheet}{SHEET_DELIMETER}"));
+        }
+
+        value.push_str(&Self::format_r1c1_component(
+            'R',
+            self.row,
+            self.row_mode,
+            origin.row,
+        ));
+        value.push_str(&Self::format_r1c1_component(
+            'C',
+            self.col,
+            self.col_mode,
+            origin.col,
+        ));
+        value
+    }
+
+    /// Split the sheet from a reference.
+    ///
+    /// # Returns
+    /// `(sheet, cell)`, or `None` if a quoted sheet label is invalid.
+    fn split_sheet(value: &str) -> Option<(Option<SheetIndex>, &str)> {
+        if value.starts_with(SHEET_QUOTE) {
+            let (label, rest) = SheetIndex::split_quoted_label(value)?;
+            let cell = rest.strip_prefix(SHEET_DELIMETER)?;
+            return Some((Some(SheetIndex::Label(label)), cell));
+        }
+
+        let split = match value.split_once(SHEET_DELIMETER) {
+            Some((sheet, cell)) => {
+                let sheet = if let Some(sheet) = sheet.parse::<IndexType>().ok() {
+                    SheetIndex::Index(sheet)
+                } else {
+                    SheetIndex::Label(sheet.to_string())
+                };
+                (Some(sheet), cell)
+            }
+            None => (None, value),
+        };
+        Some(split)
+    }
+
+    /// Parse the row or column component of an R1C1 reference.
+    ///
+    /// # Arguments
+    /// + `marker`: `R` for rows, `C` for columns.
+    /// + `origin`: Index offsets are relative to.
+    ///
+    /// # Returns
+    /// `(index, mode, rest)` where `rest` is the remaining input.
+    fn parse_r1c1_component(
+        value: &str,
+        marker: char,
+        origin: IndexType,
+    ) -> Option<(IndexType, RefMode, &str)> {
+        let first = value.chars().next()?;
+        if !first.eq_ignore_ascii_case(&marker) {
+            return None;
+        }
+        let value = &value[first.len_utf8()..];
+
+        if let Some(value) = value.strip_prefix('[') {
+            let (offset, rest) = value.split_once(']')?;
+            let offset = offset.parse::<i64>().ok()?;
+            let idx = i64::from(origin).checked_add(offset)?;
+            let idx = IndexType::try_from(idx).ok()?;
+            return Some((idx, RefMode::Relative, rest));
+        }
+
+        let digits = value
+            .find(|ch: char| !ch.is_ascii_digit())
+            .unwrap_or(value.len());
+        if digits == 0 {
+            return Some((origin, RefMode::Relative, value));
+        }
+
+        let idx = value[..digits].parse::<IndexType>().ok()?;
+        let idx = utils::row_to_index(idx)?;
+        Some((idx, RefMode::Absolute, &value[digits..]))
+    }
+
+    fn format_r1c1_component(
+        marker: char,
+        idx: IndexType,
+        mode: RefMode,
+        origin: IndexType,
+    ) -> String {
+        match mode {
+            RefMode::Absolute => format!("{marker}{}", utils::index_to_row(idx)),
+            RefMode::Relative => {
+                let offset = i64::from(idx) - i64::from(origin);
+                if offset == 0 {
+                    marker.to_string()
+                } else {
+                    format!("{marker}[{offset}]")
+                }
+            }
+        }
+    }
+}
+
 impl fmt::Display for CellRef {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let Self {
@@ -235,11 +527,7 @@ impl fmt::Display for CellRef {
             row_mode,
         } = self;
         if let SheetRef::Absolute(sheet) = sheet {
-            match sheet {
-                SheetIndex::Index(idx) => write!(f, "{idx}")?,
-                SheetIndex::Label(idx) => write!(f, "{idx}")?,
-            }
-            write!(f, "{SHEET_DELIMETER}")?;
+            write!(f, "{sheet}{SHEET_DELIMETER}")?;
         }
 
         if matches!(col_mode, RefMode::Absolute) {
@@ -371,12 +659,12 @@ impl<'de> Deserialize<'de> for CellIndex {
                 let row = row
                     .trim()
                     .parse::<IndexType>()
-                    .map_err(|err| E::custom("could not parse row: {err:?}"))?;
+                    .map_err(|err| E::custom(format!("could not parse row: {err:?}")))?;
 
                 let col = col
                     .trim()
                     .parse::<IndexType>()
-                    .map_err(|err| E::custom("could not parse col: {err:?}"))?;
+                    .map_err(|err| E::custom(format!("could not parse col: {err:?}")))?;
 
                 Ok(CellIndex { row, col })
             }
@@ -393,8 +681,8 @@ impl<'de> Deserialize<'de> for CellIndex {
     }
 }
 
-#[derive(Clone, Debug)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+/// Serialized as its string form, e.g. `"A,C"`, `"1,3"`, `"A1:B3"`.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Range {
     /// Unbounded columnar input.
     Cols(Vec<IndexType>),
@@ -406,6 +694,297 @@ pub enum Range {
     Rect { start: CellIndex, end: CellIndex },
 }
 
+impl Range {
+    /// Parse a string.
+    /// Valid ranges are comma separated columns, e.g. `a,c`,
+    /// comma separated rows, e.g. `1,3`,
+    /// or two cells separated by a colon, e.g. `a1:b3`.
+    /// An empty string is an empty list of columns.
+    pub fn from_str(value: impl AsRef<str>) -> Option<Self> {
+        let value = value.as_ref().trim();
+        if let Some((start, end)) = value.split_once(':') {
+            let cell = |value: &str| {
+                let cell = CellRef::from_str(value.trim())?;
+                matches!(cell.sheet, SheetRef::Relative).then(|| CellIndex::new(cell.row, cell.col))
+            };
+            return Some(Self::Rect {
+                start: cell(start)?,
+                end: cell(end)?,
+            });
+        }
+
+        if value.is_empty() {
+            return Some(Self::Cols(vec![]));
+        }
+
+        let labels = value.split(',').map(|label| label.trim());
+        if let Some(cols) = labels
+            .clone()
+            .map(utils::col_to_index)
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(Self::Cols(cols))
+        } else {
+            labels
+                .map(|label| utils::row_to_index(label.parse::<IndexType>().ok()?))
+                .collect::<Option<Vec<_>>>()
+                .map(Self::Rows)
+        }
+    }
+}
+
+impl fmt::Display for Range {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cols(cols) => {
+                let cols = cols
+                    .iter()
+                    .map(|col| utils::index_to_col(*col))
+                    .collect::<Vec<_>>();
+                write!(f, "{}", cols.join(","))
+            }
+            Self::Rows(rows) => {
+                let rows = rows
+                    .iter()
+                    .map(|row| utils::index_to_row(*row))
+                    .collect::<Vec<_>>();
+                write!(f, "{}", rows.join(","))
+            }
+            Self::Rect { start, end } => write!(f, "{start}:{end}"),
+        }
+    }
+}
+
+/// Cell or rectangular range of cells, possibly in another sheet.
+/// e.g. `bf300`, `sheet2!c10`, `a1:b3`, `'My Sheet'!a1:b3`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeRef {
+    pub sheet: SheetRef,
+
+    /// Top left cell of the range.
+    pub start: CellIndex,
+
+    /// Bottom right cell of the range.
+    /// Equal to `start` for a single cell.
+    pub end: CellIndex,
+}
+
+impl RangeRef {
+    /// Parse a string.
+    /// Valid references are a cell as accepted by [`CellRef::from_str`],
+    /// optionally followed by a colon and a second cell without a sheet.
+    /// e.g. `bf300`, `sheet2!c10`, `a1:b3`, `sheet2!$a$1:c10`.
+    /// Absolute markers are accepted but ignored.
+    /// The corners of the range may be given in any order.
+    pub fn from_str(value: impl AsRef<str>) -> Option<Self> {
+        let value = value.as_ref().trim();
+        // A sheet given only on the end cell, e.g. `a1:sheet!b2`, would otherwise
+        // be read as a bare sheet label containing a colon.
+        if !value.starts_with(SHEET_QUOTE)
+            && value
+                .split_once(SHEET_DELIMETER)
+                .is_some_and(|(sheet, _)| sheet.contains(':'))
+        {
+            return None;
+        }
+
+        let (sheet, cells) = CellRef::split_sheet(value)?;
+        let cell = |value: &str| {
+            let cell = CellRef::from_str(value.trim())?;
+            matches!(cell.sheet, SheetRef::Relative).then(|| CellIndex::new(cell.row, cell.col))
+        };
+
+        let (start, end) = match cells.split_once(':') {
+            Some((start, end)) => (cell(start)?, cell(end)?),
+            None => {
+                let cell = cell(cells)?;
+                (cell.clone(), cell)
+            }
+        };
+
+        Some(Self {
+            sheet: sheet.into(),
+            start: CellIndex::new(start.row.min(end.row), start.col.min(end.col)),
+            end: CellIndex::new(start.row.max(end.row), start.col.max(end.col)),
+        })
+    }
+
+    /// Whether the reference is to a single cell.
+    pub fn is_cell(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl fmt::Display for RangeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let SheetRef::Absolute(sheet) = &self.sheet {
+            write!(f, "{sheet}{SHEET_DELIMETER}")?;
+        }
+
+        if self.is_cell() {
+            write!(f, "{}", self.start)
+        } else {
+            write!(f, "{}:{}", self.start, self.end)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for SheetIndex {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for SheetIndex {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        Self::from_str(&value)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid sheet `{value}`")))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for SheetRef {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match self {
+            Self::Relative => serializer.serialize_none(),
+            Self::Absolute(sheet) => serializer.serialize_some(sheet),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for SheetRef {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Option::<SheetIndex>::deserialize(deserializer).map(Self::from)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for CellRef {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for CellRef {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        Self::from_str(&value)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid cell reference `{value}`")))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Range {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Range {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        Self::from_str(&value)
+            .ok_or_else(|| serde::de::Error::custom(format!("invalid range `{value}`")))
+    }
+}
+
+#[cfg(feature = "schema")]
+mod schema {
+    //! Schemas of the types serialized as strings.
+    use super::{CellIndex, CellRef, Range, SheetIndex, SheetRef};
+    use schemars::{JsonSchema, Schema, SchemaGenerator, json_schema};
+    use std::borrow::Cow;
+
+    fn string_schema(description: &str) -> Schema {
+        json_schema!({
+            "type": "string",
+            "description": description,
+        })
+    }
+
+    impl JsonSchema for CellIndex {
+        fn schema_name() -> Cow<'static, str> {
+            "CellIndex".into()
+        }
+
+        fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+            let mut schema = string_schema("`(<row>,<col>)`, e.g. `(0,2)`.");
+            schema.insert("pattern".into(), r"^\(\s*\d+\s*,\s*\d+\s*\)$".into());
+            schema
+        }
+    }
+
+    impl JsonSchema for SheetIndex {
+        fn schema_name() -> Cow<'static, str> {
+            "SheetIndex".into()
+        }
+
+        fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+            string_schema("Sheet index or label, e.g. `2`, `sheet`, `'My Sheet'`.")
+        }
+    }
+
+    impl JsonSchema for SheetRef {
+        fn schema_name() -> Cow<'static, str> {
+            "SheetRef".into()
+        }
+
+        fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+            generator.subschema_for::<Option<SheetIndex>>()
+        }
+    }
+
+    impl JsonSchema for CellRef {
+        fn schema_name() -> Cow<'static, str> {
+            "CellRef".into()
+        }
+
+        fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+            string_schema("Cell reference, e.g. `A1`, `$B$2`, `'My Sheet'!C3`.")
+        }
+    }
+
+    impl JsonSchema for Range {
+        fn schema_name() -> Cow<'static, str> {
+            "Range".into()
+        }
+
+        fn json_schema(_generator: &mut SchemaGenerator) -> Schema {
+            string_schema("Range, e.g. `A,C`, `1,3`, `A1:B3`.")
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -442,6 +1021,16 @@ mod test {
                 row_mode: RefMode::Relative,
             })
         );
+        assert_eq!(
+            CellRef::from_str("acb24"),
+            Some(CellRef {
+                sheet: SheetRef::Relative,
+                col: 755,
+                row: 23,
+                col_mode: RefMode::Relative,
+                row_mode: RefMode::Relative,
+            })
+        );
         assert_eq!(
             CellRef::from_str("ac24"),
             Some(CellRef {
@@ -617,15 +1206,221 @@ mod test {
             })
         );
 
+        for (label, col) in [("aaa", 702), ("AZZ", 1377), ("zzz", 18_277)] {
+            assert_eq!(
+                CellRef::from_str(format!("{label}12")),
+                Some(CellRef {
+                    sheet: SheetRef::Relative,
+                    col,
+                    row: 11,
+                    col_mode: RefMode::Relative,
+                    row_mode: RefMode::Relative,
+                })
+            );
+        }
+        let max = utils::index_to_col(IndexType::MAX);
+        assert_eq!(
+            CellRef::from_str(format!("{max}1")).map(|cell| cell.col),
+            Some(IndexType::MAX)
+        );
+        assert!(CellRef::from_str(format!("{max}A1")).is_none());
+
+        assert_eq!(
+            CellRef::from_str("'My Sheet'!a1"),
+            Some(CellRef {
+                sheet: SheetRef::Absolute(SheetIndex::Label("My Sheet".to_string())),
+                col: 0,
+                row: 0,
+                col_mode: RefMode::Relative,
+                row_mode: RefMode::Relative,
+            })
+        );
+        assert_eq!(
+            CellRef::from_str("'Bob''s data!'!$b$2"),
+            Some(CellRef {
+                sheet: SheetRef::Absolute(SheetIndex::Label("Bob's data!".to_string())),
+                col: 1,
+                row: 1,
+                col_mode: RefMode::Absolute,
+                row_mode: RefMode::Absolute,
+            })
+        );
+        assert_eq!(
+            CellRef::from_str("'0'!a1").map(|cell| cell.sheet),
+            Some(SheetRef::Absolute(SheetIndex::Label("0".to_string())))
+        );
+
+        assert!(CellRef::from_str("'sheet'a1").is_none());
+        assert!(CellRef::from_str("'sheet!a1").is_none());
+        assert!(CellRef::from_str("'sheet'!a1!").is_none());
+        assert!(CellRef::from_str("'sheet'!").is_none());
         assert!(CellRef::from_str("4").is_none());
         assert!(CellRef::from_str("a").is_none());
-        assert!(CellRef::from_str("acb24").is_none());
+        assert!(CellRef::from_str("zzzzzzzz1").is_none());
         assert!(CellRef::from_str("a2c").is_none());
         assert!(CellRef::from_str("$$a2").is_none());
         assert!(CellRef::from_str("a2$").is_none());
         assert!(CellRef::from_str("a2!sheet").is_none());
         assert!(CellRef::from_str("sheet!a2!").is_none());
         assert!(CellRef::from_str("sheet$a2").is_none());
+        assert!(CellRef::from_str(format!("a{}", u64::from(IndexType::MAX) + 2)).is_none());
+    }
+
+    #[test]
+    fn range_from_str() {
+        assert_eq!(Range::from_str("a, c"), Some(Range::Cols(vec![0, 2])));
+        assert_eq!(Range::from_str("1,3"), Some(Range::Rows(vec![0, 2])));
+        assert_eq!(
+            Range::from_str("a1:b3"),
+            Some(Range::Rect {
+                start: CellIndex::new(0u16, 0u16),
+                end: CellIndex::new(2u16, 1u16),
+            })
+        );
+        assert_eq!(Range::from_str(""), Some(Range::Cols(vec![])));
+
+        assert!(Range::from_str("a,1").is_none());
+        assert!(Range::from_str("0").is_none());
+        assert!(Range::from_str("sheet!a1:b2").is_none());
+        assert!(Range::from_str("a1:").is_none());
+
+        for value in ["A,C", "1,3", "A1:B3", "AB12:ZZZ100"] {
+            let range = Range::from_str(value).expect("valid range");
+            assert_eq!(range.to_string(), value);
+        }
+    }
+
+    #[test]
+    fn range_ref_from_str() {
+        assert_eq!(
+            RangeRef::from_str("bf300"),
+            Some(RangeRef {
+                sheet: SheetRef::Relative,
+                start: CellIndex::new(299u16, 57u16),
+                end: CellIndex::new(299u16, 57u16),
+            })
+        );
+        assert_eq!(
+            RangeRef::from_str("sheet2!c10"),
+            Some(RangeRef {
+                sheet: SheetRef::Absolute(SheetIndex::Label("sheet2".to_string())),
+                start: CellIndex::new(9u16, 2u16),
+                end: CellIndex::new(9u16, 2u16),
+            })
+        );
+        assert_eq!(
+            RangeRef::from_str("'My Sheet'!$c$3:a1"),
+            Some(RangeRef {
+                sheet: SheetRef::Absolute(SheetIndex::Label("My Sheet".to_string())),
+                start: CellIndex::new(0u16, 0u16),
+                end: CellIndex::new(2u16, 2u16),
+            })
+        );
+        assert_eq!(
+            RangeRef::from_str("1!a1:b2").map(|range| range.sheet),
+            Some(SheetRef::Absolute(SheetIndex::Index(1)))
+        );
+
+        assert!(RangeRef::from_str("").is_none());
+        assert!(RangeRef::from_str("a1:").is_none());
+        assert!(RangeRef::from_str("a1:sheet!b2").is_none());
+        assert!(RangeRef::from_str("a,c").is_none());
+
+        for value in ["A1", "A1:B3", "sheet2!C10", "'My Sheet'!A1:B3"] {
+            let range = RangeRef::from_str(value).expect("valid range");
+            assert_eq!(range.to_string(), value);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_string_form() {
+        let cell = CellRef::from_str("'My Sheet'!$b2").unwrap();
+        let json = serde_json::to_string(&cell).unwrap();
+        assert_eq!(json, r#""'My Sheet'!$B2""#);
+        assert_eq!(serde_json::from_str::<CellRef>(&json).unwrap(), cell);
+        assert!(serde_json::from_str::<CellRef>(r#""b""#).is_err());
+
+        for (sheet, json) in [
+            (SheetRef::Relative, "null"),
+            (SheetRef::Absolute(SheetIndex::Index(2)), r#""2""#),
+            (
+                SheetRef::Absolute(SheetIndex::Label("2".to_string())),
+                r#""'2'""#,
+            ),
+            (
+                SheetRef::Absolute(SheetIndex::Label("sheet".to_string())),
+                r#""sheet""#,
+            ),
+        ] {
+            assert_eq!(serde_json::to_string(&sheet).unwrap(), json);
+            assert_eq!(serde_json::from_str::<SheetRef>(json).unwrap(), sheet);
+        }
+
+        let range = Range::from_str("a1:c4").unwrap();
+        let json = serde_json::to_string(&range).unwrap();
+        assert_eq!(json, r#""A1:C4""#);
+        assert_eq!(serde_json::from_str::<Range>(&json).unwrap(), range);
+
+        let mode = serde_json::to_string(&RefMode::Absolute).unwrap();
+        assert_eq!(
+            serde_json::from_str::<RefMode>(&mode).unwrap(),
+            RefMode::Absolute
+        );
+    }
+
+    #[test]
+    fn cell_ref_r1c1() {
+        let origin = CellIndex::new(4u16, 3u16);
+        assert_eq!(
+            CellRef::from_r1c1("R3C2", &origin),
+            Some(CellRef {
+                sheet: SheetRef::Relative,
+                col: 1,
+                row: 2,
+                col_mode: RefMode::Absolute,
+                row_mode: RefMode::Absolute,
+            })
+        );
+        assert_eq!(
+            CellRef::from_r1c1("r[-1]c[2]", &origin),
+            Some(CellRef {
+                sheet: SheetRef::Relative,
+                col: 5,
+                row: 3,
+                col_mode: RefMode::Relative,
+                row_mode: RefMode::Relative,
+            })
+        );
+        assert_eq!(
+            CellRef::from_r1c1("'My Sheet'!RC1", &origin),
+            Some(CellRef {
+                sheet: SheetRef::Absolute(SheetIndex::Label("My Sheet".to_string())),
+                col: 0,
+                row: 4,
+                col_mode: RefMode::Absolute,
+                row_mode: RefMode::Relative,
+            })
+        );
+
+        assert!(CellRef::from_r1c1("R0C1", &origin).is_none());
+        assert!(CellRef::from_r1c1("R[-5]C1", &origin).is_none());
+        assert!(CellRef::from_r1c1("R[]C1", &origin).is_none());
+        assert!(CellRef::from_r1c1("R1C1x", &origin).is_none());
+        assert!(CellRef::from_r1c1("C1R1", &origin).is_none());
+        assert!(CellRef::from_r1c1("a1", &origin).is_none());
+
+        for value in ["R3C2", "R[-1]C[2]", "RC", "2!R[4]C1", "'My Sheet'!R1C[-3]"] {
+            let cell = CellRef::from_r1c1(value, &origin).expect("valid reference");
+            assert_eq!(cell.to_r1c1(&origin), value);
+        }
+
+        let cell = CellRef::from_str("b3").unwrap();
+        assert_eq!(cell.to_r1c1(&origin), "R[-2]C[-2]");
+        assert_eq!(ReferenceStyle::R1C1.cell_label(&origin), "R5C4");
+        assert_eq!(ReferenceStyle::A1.cell_label(&origin), "D5");
+        assert_eq!(ReferenceStyle::R1C1.col_label(27), "28");
+        assert_eq!(ReferenceStyle::A1.col_label(27), "AB");
     }
 
     #[test]
@@ -809,5 +1604,57 @@ mod test {
             row_mode: RefMode::Absolute,
         };
         assert_eq!(format!("{cell}"), "2!$AC$24");
+
+        let cell = CellRef {
+            sheet: SheetRef::Absolute(SheetIndex::Label("My Sheet".to_string())),
+            col: 0,
+            row: 0,
+            col_mode: RefMode::Relative,
+            row_mode: RefMode::Relative,
+        };
+        assert_eq!(format!("{cell}"), "'My Sheet'!A1");
+        assert_eq!(CellRef::from_str(cell.to_string()), Some(cell));
+
+        let cell = CellRef {
+            sheet: SheetRef::Absolute(SheetIndex::Label("Bob's".to_string())),
+            col: 0,
+            row: 0,
+            col_mode: RefMode::Relative,
+            row_mode: RefMode::Relative,
+        };
+        assert_eq!(format!("{cell}"), "'Bob''s'!A1");
+        assert_eq!(CellRef::from_str(cell.to_string()), Some(cell));
+
+        let cell = CellRef {
+            sheet: SheetRef::Absolute(SheetIndex::Label("2024".to_string())),
+            col: 0,
+            row: 0,
+            col_mode: RefMode::Relative,
+            row_mode: RefMode::Relative,
+        };
+        assert_eq!(format!("{cell}"), "'2024'!A1");
+    }
+
+    #[test]
+    fn sheet_ref_resolve() {
+        let labels = ["data", "results"];
+        assert_eq!(SheetRef::Relative.resolve(1, labels), Some(1));
+        assert_eq!(SheetRef::Relative.resolve(2, labels), None);
+        assert_eq!(
+            SheetRef::Absolute(SheetIndex::Index(0)).resolve(1, labels),
+            Some(0)
+        );
+        assert_eq!(
+            SheetRef::Absolute(SheetIndex::Index(2)).resolve(0, labels),
+            None
+        );
+        assert_eq!(
+            SheetRef::Absolute(SheetIndex::Label("results".to_string())).resolve(0, labels),
+            Some(1)
+        );
+        assert_eq!(
+            SheetRef::Absolute(SheetIndex::Label("other".to_string())).resolve(0, labels),
+            None
+        );
     }
 }
@@ -6,8 +6,10 @@ pub enum Expr {
     Empty,
     Literal(ExprLiteral),
     Binary(ExprBinary),
+    Comparison(ExprComparison),
     Unary(ExprUnary),
     Group(ExprGroup),
+    Call(ExprCall),
 }
 
 #[derive(derive_more::From, Clone, Debug, PartialEq, Eq)]
@@ -45,6 +47,17 @@ pub struct ExprBinary {
     pub right: Box<Expr>,
 }
 
+/// Chained comparisons, e.g. `1 < A1 < 10`.
+/// Holds if each operand compares to the next,
+/// with every operand evaluated once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExprComparison {
+    pub first: Box<Expr>,
+
+    /// Comparison operators, each with the operand to its right.
+    pub rest: Vec<(OpBinary, Expr)>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum OpBinary {
     Add,
@@ -64,6 +77,19 @@ pub enum OpBinary {
 }
 
 impl OpBinary {
+    /// If the operator compares its operands.
+    pub fn is_comparison(&self) -> bool {
+        matches!(
+            self,
+            Self::Equal
+                | Self::NotEqual
+                | Self::Greater
+                | Self::GreaterEqual
+                | Self::Less
+                | Self::LessEqual
+        )
+    }
+
     pub fn from_token(token: &token::Kind) -> Option<Self> {
         match token {
             token::Kind::BangEqual => Some(Self::NotEqual),
@@ -94,6 +120,8 @@ pub struct ExprUnary {
 pub enum OpUnary {
     Not,
     Minus,
+    /// Postfix `%`, dividing by 100.
+    Percent,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -104,7 +132,57 @@ pub struct ExprGroup {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum GroupDelimeter {
     /// `(...)`
     Parenthesis,
 }
+
+impl GroupDelimeter {
+    /// Character closing the group.
+    pub fn close(&self) -> char {
+        match self {
+            Self::Parenthesis => ')',
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExprCall {
+    pub function: Function,
+    pub args: Vec<Expr>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    /// `iferror(value, fallback)`: `value`, or `fallback` if evaluating `value` errors.
+    IfError,
+    /// `iserror(value)`: Whether evaluating `value` errors.
+    IsError,
+}
+
+impl Function {
+    /// Case insensitive lookup of a function by name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "iferror" => Some(Self::IfError),
+            "iserror" => Some(Self::IsError),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::IfError => "iferror",
+            Self::IsError => "iserror",
+        }
+    }
+
+    /// Number of arguments the function takes.
+    pub fn arity(&self) -> usize {
+        match self {
+            Self::IfError => 2,
+            Self::IsError => 1,
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use super::{ast, lex, parse};
 use crate::data;
-use std::{cmp, fmt, time};
+use std::{cell::RefCell, cmp, fmt, time};
 
 /// Provides the context to evaluate an expression in.
 pub trait Context: Copy {
@@ -15,19 +15,235 @@ pub trait Context: Copy {
         cell_ref: &data::CellRef,
         origin: &data::CellPath,
     ) -> Result<Value, ContextError>;
+
+    /// # Returns
+    /// Options to evaluate expressions with.
+    fn options(self) -> Options {
+        Options::default()
+    }
+}
+
+/// Options controlling how expressions are evaluated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct Options {
+    pub empty_cells: EmptyCells,
+    pub empty_comparison: EmptyComparison,
+    pub overflow: Overflow,
+    pub budget: Budget,
+}
+
+/// Limits on the work done evaluating an expression.
+/// Evaluation exceeding the budget errors with [`Error::Timeout`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Budget {
+    /// Maximum number of expression nodes to visit.
+    pub max_nodes: Option<usize>,
+
+    /// Maximum time to evaluate for.
+    /// Not enforced on `wasm32` targets, which have no clock.
+    pub max_time: Option<time::Duration>,
+}
+
+impl Budget {
+    pub const UNLIMITED: Self = Self {
+        max_nodes: None,
+        max_time: None,
+    };
 }
 
-#[derive(Clone)]
+impl Default for Budget {
+    fn default() -> Self {
+        Self {
+            max_nodes: Some(1_000_000),
+            max_time: Some(time::Duration::from_secs(1)),
+        }
+    }
+}
+
+/// Tracks the budget spent evaluating an expression.
+struct Meter {
+    budget: Budget,
+    nodes: usize,
+    #[cfg(not(target_arch = "wasm32"))]
+    start: time::Instant,
+}
+
+impl Meter {
+    /// Number of nodes visited between checks of the elapsed time.
+    const TIME_CHECK_INTERVAL: usize = 256;
+
+    fn new(budget: Budget) -> Self {
+        Self {
+            budget,
+            nodes: 0,
+            #[cfg(not(target_arch = "wasm32"))]
+            start: time::Instant::now(),
+        }
+    }
+
+    /// Spend the budget to visit a node.
+    fn visit(&mut self) -> Result<(), Error> {
+        self.nodes += 1;
+        if self.budget.max_nodes.is_some_and(|max| self.nodes > max) {
+            return Err(Error::Timeout);
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.nodes % Self::TIME_CHECK_INTERVAL == 0
+            && self
+                .budget
+                .max_time
+                .is_some_and(|max| self.start.elapsed() > max)
+        {
+            return Err(Error::Timeout);
+        }
+
+        Ok(())
+    }
+}
+
+/// How references to empty cells evaluate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum EmptyCells {
+    /// Empty cells are [empty values](Value::Empty).
+    #[default]
+    Empty,
+    /// Empty cells are `0`.
+    Zero,
+    /// Referencing an empty cell is an [error](Error::EmptyCell).
+    Error,
+}
+
+/// What empty values are equal to when compared.
+/// Empty values always equal each other,
+/// and are unequal to values they are not comparable with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum EmptyComparison {
+    /// Empty values are only equal to other empty values.
+    Strict,
+    /// Empty values compare as `""` with strings.
+    String,
+    /// Empty values compare as `0` with numbers.
+    Zero,
+    /// Empty values compare as `""` with strings and `0` with numbers, as in spreadsheets.
+    #[default]
+    Blank,
+}
+
+impl EmptyComparison {
+    /// # Returns
+    /// Value an empty value compares as against `other`,
+    /// or `None` if they are not comparable.
+    fn compare_as(self, other: &Value) -> Option<Value> {
+        match (self, other) {
+            (Self::String | Self::Blank, Value::String(_)) => Some(Value::String(String::new())),
+            (Self::Zero | Self::Blank, Value::Int(_) | Value::Float(_)) => Some(Value::Int(0)),
+            _ => None,
+        }
+    }
+}
+
+/// How integer arithmetic exceeding the range of `Int` evaluates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Overflow {
+    /// The result is promoted to a `Float`.
+    #[default]
+    Promote,
+    /// Overflowing is an [error](Error::Overflow).
+    Error,
+}
+
+impl Overflow {
+    /// # Returns
+    /// Result of an overflowing operation, computed as a float by `value`.
+    fn resolve(self, value: impl FnOnce() -> f64) -> Result<Value, Error> {
+        match self {
+            Self::Promote => Ok(Value::Float(value())),
+            Self::Error => Err(Error::Overflow),
+        }
+    }
+}
+
+/// Evaluates with `options` instead of those of the inner context.
+#[derive(Clone, Copy, Debug)]
+pub struct WithOptions<T> {
+    pub ctx: T,
+    pub options: Options,
+}
+
+impl<T> Context for WithOptions<T>
+where
+    T: Context,
+{
+    fn cell_value(
+        self,
+        cell_ref: &data::CellRef,
+        origin: &data::CellPath,
+    ) -> Result<Value, ContextError> {
+        self.ctx.cell_value(cell_ref, origin)
+    }
+
+    fn options(self) -> Options {
+        self.options
+    }
+}
+
+/// Records the cells read from the inner context, e.g. to track a value's provenance.
+/// Each cell is recorded once, in the order it was first read.
+#[derive(Clone, Copy, Debug)]
+pub struct Recording<'a, T> {
+    pub ctx: T,
+    pub reads: &'a RefCell<Vec<data::CellRef>>,
+}
+
+impl<T> Context for Recording<'_, T>
+where
+    T: Context,
+{
+    fn cell_value(
+        self,
+        cell_ref: &data::CellRef,
+        origin: &data::CellPath,
+    ) -> Result<Value, ContextError> {
+        {
+            let mut reads = self.reads.borrow_mut();
+            if !reads.contains(cell_ref) {
+                reads.push(cell_ref.clone());
+            }
+        }
+        self.ctx.cell_value(cell_ref, origin)
+    }
+
+    fn options(self) -> Options {
+        self.ctx.options()
+    }
+}
+
+#[derive(Clone, Debug, thiserror::Error)]
 pub enum ContextError {
     /// A referenced cell does not exist.
     /// e.g. The sheet does not exist, or the cell index is out of bounds.
+    #[error("referenced cell does not exist")]
     CellRefDoesNotExist,
     /// The referenced cell contains a [value error](Error).
+    #[error("referenced cell contains an error: {0}")]
     CellRefValueError(Error),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Value {
     Empty,
     String(String),
@@ -98,83 +314,59 @@ impl fmt::Display for Value {
             Value::Int(value) => write!(f, "{value}"),
             Value::Float(value) => write!(f, "{value}"),
             Value::Bool(value) => write!(f, "{value}"),
-            Value::DateTime(date_time) => todo!(),
-            Value::Duration(duration) => todo!(),
-        }
-    }
-}
-
-#[cfg(feature = "calamine")]
-impl TryFrom<calamine::Data> for Value {
-    type Error = Error;
-    fn try_from(value: calamine::Data) -> Result<Self, Self::Error> {
-        use calamine::Data;
-
-        match value {
-            Data::Int(data) => Ok(Self::Int(data)),
-            Data::Float(data) => Ok(Self::Float(data)),
-            Data::String(data) => Ok(Self::String(data)),
-            Data::Bool(data) => Ok(Self::Bool(data)),
-            Data::DateTime(data) => todo!(),
-            Data::DateTimeIso(data) => todo!(),
-            Data::DurationIso(data) => todo!(),
-            Data::Error(err) => Err(err.into()),
-            Data::Empty => Ok(Self::Empty),
-        }
-    }
-}
-
-#[cfg(feature = "calamine")]
-impl Into<calamine::Data> for Value {
-    fn into(self) -> calamine::Data {
-        use calamine::Data;
-
-        match self {
-            Value::Empty => Data::Empty,
-            Value::String(data) => Data::String(data),
-            Value::Int(data) => Data::Int(data),
-            Value::Float(data) => Data::Float(data),
-            Value::Bool(data) => Data::Bool(data),
-            Value::DateTime(data) => todo!(),
-            Value::Duration(data) => todo!(),
+            Value::DateTime(date_time) => write!(f, "{}", date_time.to_rfc3339()),
+            Value::Duration(duration) => write!(f, "{duration:?}"),
         }
     }
 }
 
 /// Error value.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, thiserror::Error)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Error {
     /// Invalid syntax.
+    #[error("syntax error: {0}")]
     Tokenize(lex::error::Kind),
     /// Invalid expression.
+    #[error("invalid formula: {0}")]
     Parse(parse::error::Kind),
     /// Divide by 0.
+    #[error("division by zero")]
     Div0,
     /// Could not parse a string as a number.
+    #[error("value is not a valid number")]
     InvalidNumber,
     /// Could not evaluate operation due to invalid arguments.
+    #[error("{0}")]
     InvalidOperation(String),
     /// Number overflow.
+    #[error("number is too large")]
     Overflow,
     /// Invalid cell reference.
+    #[error("invalid cell reference `{0}`")]
     InvalidCellRef(data::CellRef),
+    /// Referenced cell is empty, and [empty cells are errors](EmptyCells::Error).
+    #[error("referenced cell `{0}` is empty")]
+    EmptyCell(data::CellRef),
+    /// Evaluation exceeded its [budget](Budget).
+    #[error("formula took too long to evaluate")]
+    Timeout,
 }
 
-#[cfg(feature = "calamine")]
-impl From<calamine::CellErrorType> for Error {
-    fn from(value: calamine::CellErrorType) -> Self {
-        use calamine::CellErrorType;
-
-        match value {
-            CellErrorType::Div0 => Self::Div0,
-            CellErrorType::NA => todo!(),
-            CellErrorType::Name => todo!(),
-            CellErrorType::Null => todo!(),
-            CellErrorType::Num => Self::InvalidNumber,
-            CellErrorType::Ref => todo!(),
-            CellErrorType::Value => todo!(),
-            CellErrorType::GettingData => todo!(),
+impl Error {
+    /// # Returns
+    /// Spreadsheet style error code of the error.
+    /// e.g. `#DIV/0!`, `#REF!`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Tokenize(_) | Self::Parse(_) => "#ERROR!",
+            Self::Div0 => "#DIV/0!",
+            Self::InvalidNumber | Self::Overflow => "#NUM!",
+            Self::InvalidOperation(_) => "#VALUE!",
+            Self::InvalidCellRef(_) => "#REF!",
+            Self::EmptyCell(_) => "#N/A",
+            Self::Timeout => "#CALC!",
         }
     }
 }
@@ -184,12 +376,28 @@ pub fn eval<T>(expr: ast::Expr, ctx: T, origin: &data::CellPath) -> Result<Value
 where
     T: Context,
 {
+    let mut meter = Meter::new(ctx.options().budget);
+    eval_expr(expr, ctx, origin, &mut meter)
+}
+
+fn eval_expr<T>(
+    expr: ast::Expr,
+    ctx: T,
+    origin: &data::CellPath,
+    meter: &mut Meter,
+) -> Result<Value, Error>
+where
+    T: Context,
+{
+    meter.visit()?;
     match expr {
         ast::Expr::Empty => Ok(Value::Empty),
         ast::Expr::Literal(value) => eval_literal(value, ctx, origin),
-        ast::Expr::Binary(value) => eval_binary(value, ctx, origin),
-        ast::Expr::Unary(value) => eval_unary(value, ctx, origin),
-        ast::Expr::Group(value) => eval(*value.expr, ctx, origin),
+        ast::Expr::Binary(value) => eval_binary(value, ctx, origin, meter),
+        ast::Expr::Comparison(value) => eval_comparison(value, ctx, origin, meter),
+        ast::Expr::Unary(value) => eval_unary(value, ctx, origin, meter),
+        ast::Expr::Group(value) => eval_expr(*value.expr, ctx, origin, meter),
+        ast::Expr::Call(value) => eval_call(value, ctx, origin, meter),
     }
 }
 
@@ -213,30 +421,105 @@ where
             }
         }
         ast::ExprLiteral::CellRef(value) => {
-            ctx.cell_value(&value.value, origin)
+            let cell = ctx
+                .cell_value(&value.value, origin)
                 .map_err(|err| match err {
                     ContextError::CellRefDoesNotExist => Error::InvalidCellRef(value.value.clone()),
                     ContextError::CellRefValueError(error) => error,
-                })
+                })?;
+
+            match (cell, ctx.options().empty_cells) {
+                (Value::Empty, EmptyCells::Zero) => Ok(Value::Int(0)),
+                (Value::Empty, EmptyCells::Error) => Err(Error::EmptyCell(value.value)),
+                (cell, _) => Ok(cell),
+            }
         }
     }
 }
 
 #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, level = "trace"))]
-fn eval_binary<T>(expr: ast::ExprBinary, ctx: T, origin: &data::CellPath) -> Result<Value, Error>
+fn eval_call<T>(
+    expr: ast::ExprCall,
+    ctx: T,
+    origin: &data::CellPath,
+    meter: &mut Meter,
+) -> Result<Value, Error>
 where
     T: Context,
 {
-    let left = eval(*expr.left, ctx, origin)?;
-    let right = eval(*expr.right, ctx, origin)?;
-    match expr.op {
+    let function = expr.function;
+    let mut args = expr.args.into_iter();
+    match (function, args.next(), args.next(), args.next()) {
+        (ast::Function::IfError, Some(value), Some(fallback), None) => {
+            // Exceeding the budget is not caught, so the whole evaluation is aborted.
+            match eval_expr(value, ctx, origin, meter) {
+                Err(Error::Timeout) => Err(Error::Timeout),
+                Err(_) => eval_expr(fallback, ctx, origin, meter),
+                value => value,
+            }
+        }
+        (ast::Function::IsError, Some(value), None, None) => {
+            match eval_expr(value, ctx, origin, meter) {
+                Err(Error::Timeout) => Err(Error::Timeout),
+                value => Ok(Value::Bool(value.is_err())),
+            }
+        }
+        _ => Err(Error::InvalidOperation(format!(
+            "`{}` takes {} argument(s)",
+            function.name(),
+            function.arity()
+        ))),
+    }
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, level = "trace"))]
+fn eval_binary<T>(
+    expr: ast::ExprBinary,
+    ctx: T,
+    origin: &data::CellPath,
+    meter: &mut Meter,
+) -> Result<Value, Error>
+where
+    T: Context,
+{
+    // Operations nest to the right, e.g. `1 + 2 + 3` is `1 + (2 + 3)`,
+    // so long chains are unwound instead of recursing once per operation.
+    let mut operations = vec![];
+    let mut expr = expr;
+    let right = loop {
+        let left = eval_expr(*expr.left, ctx, origin, meter)?;
+        operations.push((expr.op, left));
+        match *expr.right {
+            ast::Expr::Binary(right) => {
+                meter.visit()?;
+                expr = right;
+            }
+            right => break right,
+        }
+    };
+
+    let mut value = eval_expr(right, ctx, origin, meter)?;
+    for (op, left) in operations.into_iter().rev() {
+        value = binary(op, left, value, ctx.options())?;
+    }
+    Ok(value)
+}
+
+/// Apply a binary operator to its evaluated operands.
+fn binary(op: ast::OpBinary, left: Value, right: Value, options: Options) -> Result<Value, Error> {
+    let Options {
+        overflow,
+        empty_comparison: empty,
+        ..
+    } = options;
+    match op {
         ast::OpBinary::Add => {
             if let Value::Int(left) = left
                 && let Value::Int(right) = right
             {
                 match left.checked_add(right) {
                     Some(value) => Ok(Value::Int(value)),
-                    None => Err(Error::Overflow),
+                    None => overflow.resolve(|| left as f64 + right as f64),
                 }
             } else if left.is_number() && right.is_number() {
                 let left = left.as_number().unwrap();
@@ -252,7 +535,7 @@ where
             {
                 match left.checked_sub(right) {
                     Some(value) => Ok(Value::Int(value)),
-                    None => Err(Error::Overflow),
+                    None => overflow.resolve(|| left as f64 - right as f64),
                 }
             } else if left.is_number() && right.is_number() {
                 let left = left.as_number().unwrap();
@@ -270,7 +553,7 @@ where
             {
                 match left.checked_mul(right) {
                     Some(value) => Ok(Value::Int(value)),
-                    None => Err(Error::Overflow),
+                    None => overflow.resolve(|| left as f64 * right as f64),
                 }
             } else if left.is_number() && right.is_number() {
                 let left = left.as_number().unwrap();
@@ -289,7 +572,7 @@ where
                 if left % right == 0 {
                     match left.checked_div(right) {
                         Some(value) => Ok(Value::Int(value)),
-                        None => Err(Error::Overflow),
+                        None => overflow.resolve(|| left as f64 / right as f64),
                     }
                 } else {
                     let left = left as f64;
@@ -331,22 +614,22 @@ where
                 if let Ok(pow) = u32::try_from(right) {
                     match left.checked_pow(pow) {
                         Some(value) => Ok(Value::Int(value)),
-                        None => Err(Error::Overflow),
+                        None => overflow.resolve(|| (left as f64).powf(right as f64)),
                     }
                 } else if let Ok(pow) = i32::try_from(right) {
                     let base = left as f64;
                     Ok(Value::Float(base.powi(pow)))
                 } else {
-                    return Err(Error::Overflow);
+                    overflow.resolve(|| (left as f64).powf(right as f64))
                 }
             } else if left.is_number()
                 && let Value::Int(right) = right
             {
                 let left = left.as_number().unwrap();
-                let Ok(pow) = i32::try_from(right) else {
-                    return Err(Error::Overflow);
-                };
-                Ok(Value::Float(left.powi(pow)))
+                match i32::try_from(right) {
+                    Ok(pow) => Ok(Value::Float(left.powi(pow))),
+                    Err(_) => overflow.resolve(|| left.powf(right as f64)),
+                }
             } else if left.is_number()
                 && let Value::Float(right) = right
             {
@@ -358,47 +641,83 @@ where
                 ))
             }
         }
-        ast::OpBinary::Equal => match value_eq(&left, &right) {
-            Some(value) => Ok(Value::Bool(value)),
-            None => Err(Error::InvalidOperation("can not compare types".to_string())),
-        },
-        ast::OpBinary::NotEqual => match value_eq(&left, &right) {
-            Some(value) => Ok(Value::Bool(!value)),
-            None => Err(Error::InvalidOperation("can not compare types".to_string())),
-        },
-        ast::OpBinary::Greater => match value_ord(&left, &right) {
-            Some(ord) => Ok(Value::Bool(matches!(ord, cmp::Ordering::Greater))),
-            None => Err(Error::InvalidOperation("can not compare types".to_string())),
-        },
-        ast::OpBinary::GreaterEqual => match value_ord(&left, &right) {
-            Some(ord) => Ok(Value::Bool(matches!(
-                ord,
-                cmp::Ordering::Greater | cmp::Ordering::Equal
-            ))),
-            None => Err(Error::InvalidOperation("can not compare types".to_string())),
+        ast::OpBinary::Equal
+        | ast::OpBinary::NotEqual
+        | ast::OpBinary::Greater
+        | ast::OpBinary::GreaterEqual
+        | ast::OpBinary::Less
+        | ast::OpBinary::LessEqual => Ok(Value::Bool(compare(op, &left, &right, empty)?)),
+        ast::OpBinary::And => match (left, right) {
+            (Value::Bool(left), Value::Bool(right)) => Ok(Value::Bool(left && right)),
+            _ => Err(Error::InvalidOperation(
+                "can only `and` booleans".to_string(),
+            )),
         },
-        ast::OpBinary::Less => match value_ord(&left, &right) {
-            Some(ord) => Ok(Value::Bool(matches!(ord, cmp::Ordering::Less))),
-            None => Err(Error::InvalidOperation("can not compare types".to_string())),
+        ast::OpBinary::Or => match (left, right) {
+            (Value::Bool(left), Value::Bool(right)) => Ok(Value::Bool(left || right)),
+            _ => Err(Error::InvalidOperation(
+                "can only `or` booleans".to_string(),
+            )),
         },
-        ast::OpBinary::LessEqual => match value_ord(&left, &right) {
-            Some(ord) => Ok(Value::Bool(matches!(
-                ord,
-                cmp::Ordering::Less | cmp::Ordering::Equal
-            ))),
-            None => Err(Error::InvalidOperation("can not compare types".to_string())),
-        },
-        ast::OpBinary::And => todo!(),
-        ast::OpBinary::Or => todo!(),
     }
 }
 
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, level = "trace"))]
+fn eval_comparison<T>(
+    expr: ast::ExprComparison,
+    ctx: T,
+    origin: &data::CellPath,
+    meter: &mut Meter,
+) -> Result<Value, Error>
+where
+    T: Context,
+{
+    let empty = ctx.options().empty_comparison;
+    let mut left = eval_expr(*expr.first, ctx, origin, meter)?;
+    let mut holds = true;
+    for (op, right) in expr.rest {
+        let right = eval_expr(right, ctx, origin, meter)?;
+        holds &= compare(op, &left, &right, empty)?;
+        left = right;
+    }
+
+    Ok(Value::Bool(holds))
+}
+
+/// Compare two values with a comparison operator.
+///
+/// # Errors
+/// + [`Error::InvalidOperation`] if the values can not be compared.
+fn compare(
+    op: ast::OpBinary,
+    left: &Value,
+    right: &Value,
+    empty: EmptyComparison,
+) -> Result<bool, Error> {
+    let holds = match op {
+        ast::OpBinary::Equal => value_eq(left, right, empty),
+        ast::OpBinary::NotEqual => value_eq(left, right, empty).map(|value| !value),
+        ast::OpBinary::Greater => value_ord(left, right, empty).map(|ord| ord.is_gt()),
+        ast::OpBinary::GreaterEqual => value_ord(left, right, empty).map(|ord| ord.is_ge()),
+        ast::OpBinary::Less => value_ord(left, right, empty).map(|ord| ord.is_lt()),
+        ast::OpBinary::LessEqual => value_ord(left, right, empty).map(|ord| ord.is_le()),
+        op => unreachable!("{op:?} is not a comparison"),
+    };
+
+    holds.ok_or_else(|| Error::InvalidOperation("can not compare types".to_string()))
+}
+
 /// Compare two values for equality.
 /// `Int` and `Float` are compared as values.
+/// `Empty` is compared as determined by `empty`.
 /// `None` if types can not be compared.
-fn value_eq(left: &Value, right: &Value) -> Option<bool> {
+fn value_eq(left: &Value, right: &Value, empty: EmptyComparison) -> Option<bool> {
     match (left, right) {
         (Value::Empty, Value::Empty) => Some(true),
+        (Value::Empty, other) | (other, Value::Empty) => match empty.compare_as(other) {
+            Some(left) => value_eq(&left, other, empty),
+            None => Some(false),
+        },
         (Value::String(left), Value::String(right)) => Some(left == right),
         (Value::Bool(left), Value::Bool(right)) => Some(left == right),
         (Value::Int(left), Value::Int(right)) => Some(left == right),
@@ -415,9 +734,13 @@ fn value_eq(left: &Value, right: &Value) -> Option<bool> {
 /// `Int`` and `Float` are compared as values.
 /// If `Float` is `NaN` returns `None`.
 /// `String` is compared lexicographically.
+/// `Empty` is compared as determined by `empty`.
 /// `None` if the types can't be ordered.
-fn value_ord(left: &Value, right: &Value) -> Option<cmp::Ordering> {
+fn value_ord(left: &Value, right: &Value, empty: EmptyComparison) -> Option<cmp::Ordering> {
     match (left, right) {
+        (Value::Empty, Value::Empty) => Some(cmp::Ordering::Equal),
+        (Value::Empty, right) => value_ord(&empty.compare_as(right)?, right, empty),
+        (left, Value::Empty) => value_ord(left, &empty.compare_as(left)?, empty),
         (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
         (Value::Int(left), Value::Int(right)) => Some(left.cmp(right)),
         (Value::Float(left), Value::Float(right)) => {
@@ -452,11 +775,16 @@ fn value_ord(left: &Value, right: &Value) -> Option<cmp::Ordering> {
 }
 
 #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, level = "trace"))]
-fn eval_unary<T>(expr: ast::ExprUnary, ctx: T, origin: &data::CellPath) -> Result<Value, Error>
+fn eval_unary<T>(
+    expr: ast::ExprUnary,
+    ctx: T,
+    origin: &data::CellPath,
+    meter: &mut Meter,
+) -> Result<Value, Error>
 where
     T: Context,
 {
-    let value = eval(*expr.expr, ctx, origin)?;
+    let value = eval_expr(*expr.expr, ctx, origin, meter)?;
     match expr.op {
         ast::OpUnary::Not => {
             let Value::Bool(value) = value else {
@@ -470,19 +798,34 @@ where
             if let Value::Float(value) = value {
                 Ok(Value::Float(-value))
             } else if let Value::Int(value) = value {
-                Ok(Value::Int(-value))
+                match value.checked_neg() {
+                    Some(value) => Ok(Value::Int(value)),
+                    None => ctx.options().overflow.resolve(|| -(value as f64)),
+                }
             } else {
                 Err(Error::InvalidOperation(
                     "can not subtract non-numeric types".to_string(),
                 ))
             }
         }
+        ast::OpUnary::Percent => {
+            if let Value::Float(value) = value {
+                Ok(Value::Float(value / 100.0))
+            } else if let Value::Int(value) = value {
+                Ok(Value::Float(value as f64 / 100.0))
+            } else {
+                Err(Error::InvalidOperation(
+                    "can not take percent of non-numeric types".to_string(),
+                ))
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use std::assert_matches::assert_matches;
 
     #[derive(Clone, Copy)]
     struct CtxEmpty;
@@ -532,6 +875,30 @@ mod test {
         };
         assert_eq!(res, Value::Float(5.0));
 
+        let src = ".5";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let Ok(res) = eval(ast, ctx, &origin) else {
+            panic!("invalid input");
+        };
+        assert_eq!(res, Value::Float(0.5));
+
+        let src = "2.5E6";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let Ok(res) = eval(ast, ctx, &origin) else {
+            panic!("invalid input");
+        };
+        assert_eq!(res, Value::Float(2.5e6));
+
+        let src = "-1e-3";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let Ok(res) = eval(ast, ctx, &origin) else {
+            panic!("invalid input");
+        };
+        assert_eq!(res, Value::Float(-1e-3));
+
         // bool
         let src = "true";
         let lex = lex::tokenize(src);
@@ -1025,6 +1392,74 @@ mod test {
             panic!("invalid input");
         };
         assert_eq!(res, Value::Float(4.0));
+
+        // unary +
+        let src = "+2 * +-3";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let Ok(res) = eval(ast, ctx, &origin) else {
+            panic!("invalid input");
+        };
+        assert_eq!(res, Value::Int(-6));
+    }
+
+    #[test]
+    fn eval_percent() {
+        let ctx = CtxEmpty;
+        let origin = data::CellPath {
+            sheet: 0,
+            row: 0,
+            col: 0,
+        };
+
+        let src = "5%";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let Ok(res) = eval(ast, ctx, &origin) else {
+            panic!("invalid input");
+        };
+        assert_eq!(res, Value::Float(0.05));
+
+        let src = "-50%";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let Ok(res) = eval(ast, ctx, &origin) else {
+            panic!("invalid input");
+        };
+        assert_eq!(res, Value::Float(-0.5));
+
+        let src = "50% * 4";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let Ok(res) = eval(ast, ctx, &origin) else {
+            panic!("invalid input");
+        };
+        assert_eq!(res, Value::Float(2.0));
+
+        let src = "200 * 5%";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let Ok(res) = eval(ast, ctx, &origin) else {
+            panic!("invalid input");
+        };
+        assert_eq!(res, Value::Float(10.0));
+
+        let src = "1 + 50%";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let Ok(res) = eval(ast, ctx, &origin) else {
+            panic!("invalid input");
+        };
+        assert_eq!(res, Value::Float(1.5));
+
+        // remainder
+        let src = "12%5";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let Ok(res) = eval(ast, ctx, &origin) else {
+            panic!("invalid input");
+        };
+        assert_eq!(res, Value::Int(2));
     }
 
     #[test]
@@ -1374,4 +1809,449 @@ mod test {
         };
         assert_eq!(res, Value::Bool(false));
     }
+
+    #[test]
+    fn eval_logical() {
+        let ctx = CtxEmpty;
+        let origin = data::CellPath {
+            sheet: 0,
+            row: 0,
+            col: 0,
+        };
+
+        let binary = |op, left: bool, right: bool| -> ast::Expr {
+            let left: ast::ExprLiteral = ast::LitBool { value: left }.into();
+            let right: ast::ExprLiteral = ast::LitBool { value: right }.into();
+            ast::Expr::Binary(ast::ExprBinary {
+                op,
+                left: Box::new(left.into()),
+                right: Box::new(right.into()),
+            })
+        };
+
+        let ast = binary(ast::OpBinary::And, true, false);
+        let Ok(res) = eval(ast, ctx, &origin) else {
+            panic!("invalid input");
+        };
+        assert_eq!(res, Value::Bool(false));
+
+        let ast = binary(ast::OpBinary::Or, true, false);
+        let Ok(res) = eval(ast, ctx, &origin) else {
+            panic!("invalid input");
+        };
+        assert_eq!(res, Value::Bool(true));
+
+        let ast = ast::Expr::Binary(ast::ExprBinary {
+            op: ast::OpBinary::And,
+            left: Box::new(ast::Expr::Empty),
+            right: Box::new(ast::Expr::Empty),
+        });
+        assert_matches!(eval(ast, ctx, &origin), Err(Error::InvalidOperation(_)));
+    }
+
+    #[test]
+    fn eval_call() {
+        let ctx = CtxEmpty;
+        let origin = data::CellPath {
+            sheet: 0,
+            row: 0,
+            col: 0,
+        };
+
+        let src = "iferror('a' + 1, 'none')";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let res = eval(ast, ctx, &origin).expect("fallback to be valid");
+        assert_eq!(res, Value::String("none".to_string()));
+
+        let src = "iferror(1 + 1, 'none')";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let res = eval(ast, ctx, &origin).expect("value to be valid");
+        assert_eq!(res, Value::Int(2));
+
+        let src = "iferror(a1, 'a' + 1)";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let err = eval(ast, ctx, &origin).expect_err("fallback to error");
+        assert_matches!(err, Error::InvalidOperation(_));
+
+        let src = "iserror(a1)";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let res = eval(ast, ctx, &origin).expect("input to be valid");
+        assert_eq!(res, Value::Bool(true));
+
+        let src = "!iserror(1)";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let res = eval(ast, ctx, &origin).expect("input to be valid");
+        assert_eq!(res, Value::Bool(true));
+    }
+
+    #[test]
+    fn eval_empty_cells() {
+        #[derive(Clone, Copy)]
+        struct Ctx;
+        impl Context for Ctx {
+            fn cell_value(
+                self,
+                cell_ref: &data::CellRef,
+                origin: &data::CellPath,
+            ) -> Result<Value, ContextError> {
+                Ok(Value::Empty)
+            }
+        }
+
+        let origin = data::CellPath {
+            sheet: 0,
+            row: 0,
+            col: 0,
+        };
+        let with_empty_cells = |empty_cells| WithOptions {
+            ctx: Ctx,
+            options: Options {
+                empty_cells,
+                ..Default::default()
+            },
+        };
+
+        let src = "a1";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let res = eval(ast.clone(), Ctx, &origin).expect("input to be valid");
+        assert_eq!(res, Value::Empty);
+        let res = eval(ast.clone(), with_empty_cells(EmptyCells::Zero), &origin)
+            .expect("input to be valid");
+        assert_eq!(res, Value::Int(0));
+        let err = eval(ast, with_empty_cells(EmptyCells::Error), &origin)
+            .expect_err("empty cell to be an error");
+        assert_matches!(err, Error::EmptyCell(_));
+        assert_eq!(err.code(), "#N/A");
+
+        let src = "a1 + 1";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        assert_matches!(
+            eval(ast.clone(), Ctx, &origin),
+            Err(Error::InvalidOperation(_))
+        );
+        let res =
+            eval(ast, with_empty_cells(EmptyCells::Zero), &origin).expect("input to be valid");
+        assert_eq!(res, Value::Int(1));
+
+        let src = "iferror(a1, 'blank')";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let res =
+            eval(ast, with_empty_cells(EmptyCells::Error), &origin).expect("input to be valid");
+        assert_eq!(res, Value::String("blank".to_string()));
+    }
+
+    #[test]
+    fn eval_empty_comparison() {
+        #[derive(Clone, Copy)]
+        struct Ctx;
+        impl Context for Ctx {
+            fn cell_value(
+                self,
+                cell_ref: &data::CellRef,
+                origin: &data::CellPath,
+            ) -> Result<Value, ContextError> {
+                Ok(Value::Empty)
+            }
+        }
+
+        let origin = data::CellPath {
+            sheet: 0,
+            row: 0,
+            col: 0,
+        };
+        let with_empty_comparison = |empty_comparison| WithOptions {
+            ctx: Ctx,
+            options: Options {
+                empty_comparison,
+                ..Default::default()
+            },
+        };
+
+        let cases = [
+            ("a1 == b1", [true, true, true, true]),
+            ("a1 == ''", [false, true, false, true]),
+            ("a1 != 'a'", [true, true, true, true]),
+            ("a1 == 0", [false, false, true, true]),
+            ("a1 == 0.0", [false, false, true, true]),
+            ("a1 == false", [false, false, false, false]),
+        ];
+        let modes = [
+            EmptyComparison::Strict,
+            EmptyComparison::String,
+            EmptyComparison::Zero,
+            EmptyComparison::Blank,
+        ];
+        for (src, expected) in cases {
+            let lex = lex::tokenize(src);
+            let ast = parse::parse(&lex.tokens).expect("input to be valid");
+            for (mode, expected) in modes.iter().zip(expected) {
+                let res = eval(ast.clone(), with_empty_comparison(*mode), &origin)
+                    .expect("values to be comparable");
+                assert_eq!(res, Value::Bool(expected), "{src} with {mode:?}");
+            }
+        }
+
+        let src = "a1 < 1";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let res = eval(ast.clone(), Ctx, &origin).expect("values to be comparable");
+        assert_eq!(res, Value::Bool(true));
+        assert_matches!(
+            eval(ast, with_empty_comparison(EmptyComparison::Strict), &origin),
+            Err(Error::InvalidOperation(_))
+        );
+
+        let src = "a1 >= b1";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let res = eval(ast, with_empty_comparison(EmptyComparison::Strict), &origin)
+            .expect("values to be comparable");
+        assert_eq!(res, Value::Bool(true));
+
+        let src = "'' < a1";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let res = eval(ast, Ctx, &origin).expect("values to be comparable");
+        assert_eq!(res, Value::Bool(false));
+    }
+
+    #[test]
+    fn eval_comparison_chain() {
+        let ctx = CtxEmpty;
+        let origin = data::CellPath {
+            sheet: 0,
+            row: 0,
+            col: 0,
+        };
+
+        let cases = [
+            ("1 < 5 < 10", true),
+            ("1 < 10 < 10", false),
+            ("1 < 5 <= 5", true),
+            ("3 > 2 > 1", true),
+            ("1 == 1.0 != 2", true),
+            ("1 < 5 > 10", false),
+        ];
+        for (src, expected) in cases {
+            let lex = lex::tokenize(src);
+            let ast = parse::parse(&lex.tokens).expect("input to be valid");
+            let res = eval(ast, ctx, &origin).expect("input to be valid");
+            assert_eq!(res, Value::Bool(expected), "{src}");
+        }
+
+        // operands are evaluated once
+        #[derive(Clone, Copy)]
+        struct Ctx<'a> {
+            reads: &'a std::cell::Cell<usize>,
+        }
+        impl Context for Ctx<'_> {
+            fn cell_value(
+                self,
+                _cell_ref: &data::CellRef,
+                _origin: &data::CellPath,
+            ) -> Result<Value, ContextError> {
+                self.reads.set(self.reads.get() + 1);
+                Ok(Value::Int(5))
+            }
+        }
+
+        let reads = std::cell::Cell::new(0);
+        let src = "1 < A1 < 10";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let res = eval(ast, Ctx { reads: &reads }, &origin).expect("input to be valid");
+        assert_eq!(res, Value::Bool(true));
+        assert_eq!(reads.get(), 1);
+    }
+
+    #[test]
+    fn eval_budget() {
+        let origin = data::CellPath {
+            sheet: 0,
+            row: 0,
+            col: 0,
+        };
+        let with_budget = |budget| WithOptions {
+            ctx: CtxEmpty,
+            options: Options {
+                budget,
+                ..Default::default()
+            },
+        };
+
+        let src = "1 + 2 + 3";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let budget = Budget {
+            max_nodes: Some(5),
+            max_time: None,
+        };
+        let res = eval(ast.clone(), with_budget(budget), &origin).expect("input to be valid");
+        assert_eq!(res, Value::Int(6));
+        let budget = Budget {
+            max_nodes: Some(4),
+            max_time: None,
+        };
+        let err = eval(ast, with_budget(budget), &origin).expect_err("budget to be exceeded");
+        assert_matches!(err, Error::Timeout);
+        assert_eq!(err.code(), "#CALC!");
+
+        // not caught
+        let src = "iferror(1 + 2 + 3, 0)";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let err = eval(ast, with_budget(budget), &origin).expect_err("budget to be exceeded");
+        assert_matches!(err, Error::Timeout);
+
+        let src = "iserror(1 + 2 + 3)";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let err = eval(ast, with_budget(budget), &origin).expect_err("budget to be exceeded");
+        assert_matches!(err, Error::Timeout);
+
+        // time
+        let src = vec!["1"; 1_000].join(" + ");
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let res =
+            eval(ast.clone(), with_budget(Budget::UNLIMITED), &origin).expect("input to be valid");
+        assert_eq!(res, Value::Int(1_000));
+        let budget = Budget {
+            max_nodes: None,
+            max_time: Some(time::Duration::ZERO),
+        };
+        let err = eval(ast, with_budget(budget), &origin).expect_err("budget to be exceeded");
+        assert_matches!(err, Error::Timeout);
+    }
+
+    #[test]
+    fn eval_overflow() {
+        let origin = data::CellPath {
+            sheet: 0,
+            row: 0,
+            col: 0,
+        };
+        let with_overflow = |overflow| WithOptions {
+            ctx: CtxEmpty,
+            options: Options {
+                overflow,
+                ..Default::default()
+            },
+        };
+
+        let cases = [
+            ("9223372036854775807 + 1", 9223372036854775807.0 + 1.0),
+            ("-9223372036854775807 - 2", -9223372036854775807.0 - 2.0),
+            ("9223372036854775807 * 2", 9223372036854775807.0 * 2.0),
+            ("10 ** 19", 1e19),
+            ("2 ** 4294967296", f64::INFINITY),
+        ];
+        for (src, expected) in cases {
+            let lex = lex::tokenize(src);
+            let ast = parse::parse(&lex.tokens).expect("input to be valid");
+            let res = eval(ast.clone(), CtxEmpty, &origin).expect("overflow to be promoted");
+            assert_eq!(res, Value::Float(expected), "{src}");
+            let res = eval(ast.clone(), with_overflow(Overflow::Promote), &origin)
+                .expect("overflow to be promoted");
+            assert_eq!(res, Value::Float(expected), "{src}");
+            let err = eval(ast, with_overflow(Overflow::Error), &origin)
+                .expect_err("overflow to be an error");
+            assert_matches!(err, Error::Overflow, "{src}");
+        }
+
+        let src = "9223372036854775806 + 1";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let res = eval(ast, with_overflow(Overflow::Error), &origin).expect("input to be valid");
+        assert_eq!(res, Value::Int(i64::MAX));
+    }
+
+    #[test]
+    fn error_display() {
+        use crate::expr::token;
+
+        let lex = lex::tokenize("'hi");
+        let err = Error::Tokenize(lex.errors[0].value);
+        assert_eq!(err.to_string(), "syntax error: string is not closed");
+
+        let err = Error::Parse(parse::error::Kind::UnclosedGroup {
+            expeted: ast::GroupDelimeter::Parenthesis,
+        });
+        assert_eq!(err.to_string(), "invalid formula: missing closing `)`");
+
+        let err = Error::Parse(parse::error::Kind::UnexpectedToken {
+            expected: vec![token::Kind::Number, token::Kind::CellRef],
+            found: token::Kind::Comma,
+        });
+        assert_eq!(
+            err.to_string(),
+            "invalid formula: unexpected `,`, expected number or cell reference"
+        );
+
+        assert_eq!(Error::Div0.to_string(), "division by zero");
+        let err = Error::InvalidCellRef(data::CellRef::from_str("'Sheet 1'!A1").unwrap());
+        assert_eq!(err.to_string(), "invalid cell reference `'Sheet 1'!A1`");
+
+        let err = ContextError::CellRefValueError(Error::Overflow);
+        assert_eq!(
+            err.to_string(),
+            "referenced cell contains an error: number is too large"
+        );
+    }
+
+    #[test]
+    fn error_code() {
+        assert_eq!(Error::Div0.code(), "#DIV/0!");
+        assert_eq!(Error::Overflow.code(), "#NUM!");
+        assert_eq!(Error::InvalidOperation("bad".to_string()).code(), "#VALUE!");
+        let err = Error::InvalidCellRef(data::CellRef::from_str("a1").unwrap());
+        assert_eq!(err.code(), "#REF!");
+    }
+
+    #[test]
+    fn eval_recording() {
+        #[derive(Clone, Copy)]
+        struct Ctx;
+        impl Context for Ctx {
+            fn cell_value(
+                self,
+                cell_ref: &data::CellRef,
+                origin: &data::CellPath,
+            ) -> Result<Value, ContextError> {
+                Ok(Value::Int(1))
+            }
+        }
+
+        let origin = data::CellPath {
+            sheet: 0,
+            row: 0,
+            col: 0,
+        };
+        let reads = RefCell::new(vec![]);
+        let ctx = Recording {
+            ctx: Ctx,
+            reads: &reads,
+        };
+
+        let src = "a1 + b2 + a1";
+        let lex = lex::tokenize(src);
+        let ast = parse::parse(&lex.tokens).expect("input to be valid");
+        let res = eval(ast, ctx, &origin).expect("input to be valid");
+        assert_eq!(res, Value::Int(3));
+        assert_eq!(
+            reads.into_inner(),
+            vec![
+                data::CellRef::from_str("a1").unwrap(),
+                data::CellRef::from_str("b2").unwrap(),
+            ]
+        );
+    }
 }
@@ -1,5 +1,5 @@
 use super::{
-    position::{Span, WithSpan},
+    position::{Position, WithSpan},
     token::{self, Token},
 };
 use crate::data;
@@ -23,24 +23,25 @@ impl Lex {
     }
 }
 
+#[derive(Clone)]
 struct Scanner<'a> {
     /// Iterator over src characters.
-    iter: iter::Peekable<iter::Enumerate<std::str::Chars<'a>>>,
+    iter: iter::Peekable<std::str::Chars<'a>>,
 
-    /// Cursor position.
-    pos: usize,
+    /// Position of the last consumed character.
+    pos: Position,
 
-    /// If the iterator has been fully consumed.
-    complete: bool,
+    /// Position following the last consumed character.
+    end: Position,
 }
 
 impl<'a> Scanner<'a> {
     pub fn new(src: &'a str) -> Self {
-        let iter = src.chars().enumerate().peekable();
+        let iter = src.chars().peekable();
         Self {
             iter,
-            pos: 0,
-            complete: false,
+            pos: Position::default(),
+            end: Position::default(),
         }
     }
 }
@@ -48,7 +49,7 @@ impl<'a> Scanner<'a> {
 impl<'a> Scanner<'a> {
     /// Peek at the next character without consuming it.
     pub fn peek(&mut self) -> Option<&<Self as Iterator>::Item> {
-        self.iter.peek().map(|(_, char)| char)
+        self.iter.peek()
     }
 
     /// Consume the next character if it is equal to the expected one.
@@ -56,40 +57,33 @@ impl<'a> Scanner<'a> {
         &mut self,
         expected: &<Self as Iterator>::Item,
     ) -> Option<<Self as Iterator>::Item> {
-        if let Some((idx, char)) = self.iter.next_if(|(_, char)| expected == char) {
-            #[cfg(feature = "tracing")]
-            tracing::debug!(?char);
+        let char = self.iter.next_if_eq(expected)?;
+        self.consumed(char);
+        Some(char)
+    }
 
-            self.pos = idx;
-            Some(char)
-        } else {
-            if !self.complete {
-                self.pos += 1;
-                self.complete = true;
-            }
+    /// # Returns
+    /// Position following the last consumed character.
+    /// Used as the exclusive end of spans.
+    pub fn end(&self) -> Position {
+        self.end
+    }
 
-            None
-        }
+    fn consumed(&mut self, char: char) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(?char);
+
+        self.pos = self.end;
+        self.end = self.end.advance(char);
     }
 }
 
 impl<'a> iter::Iterator for Scanner<'a> {
     type Item = char;
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some((idx, char)) = self.iter.next() {
-            #[cfg(feature = "tracing")]
-            tracing::debug!(?char);
-
-            self.pos = idx;
-            Some(char)
-        } else {
-            if !self.complete {
-                self.pos += 1;
-                self.complete = true;
-            }
-
-            None
-        }
+        let char = self.iter.next()?;
+        self.consumed(char);
+        Some(char)
     }
 }
 
@@ -119,15 +113,17 @@ impl<'a> Lexer<'a> {
     }
 
     /// Validates if the character is valid within an identifier.
-    /// Valid characters are alphabetic (`a-z`, `A-Z`) and underscore (`_`).
+    /// Valid characters are unicode alphabetic (e.g. `a-z`, `A-Z`, `é`) and underscore (`_`).
     fn is_valid_ident_char(ch: &char) -> bool {
-        ch.is_ascii_alphabetic() || *ch == '_'
+        ch.is_alphabetic() || *ch == '_'
     }
 
     /// Validates if the character is valid within a cell reference.
-    /// Valid characters are alphanumeric (`a-z`, `A-Z`, `0 - 9`), exclamation (`!`), and dollar (`$`).
+    /// Valid characters are unicode alphanumeric (e.g. `a-z`, `A-Z`, `0 - 9`, `é`),
+    /// exclamation (`!`), and dollar (`$`).
+    /// Non-ASCII characters are only valid in sheet labels.
     fn is_valid_cell_ref_char(ch: &char) -> bool {
-        ch.is_ascii_alphanumeric() || *ch == '!' || *ch == '$'
+        ch.is_alphanumeric() || *ch == '!' || *ch == '$'
     }
 
     /// Validates if the character is valid within an identifier or cell reference.
@@ -169,61 +165,46 @@ impl<'a> Lexer<'a> {
         let pos_start = self.it.pos;
 
         let token = match char {
-            ':' => Ok(WithSpan::at(Token::Colon, pos_start)),
-            ',' => Ok(WithSpan::at(Token::Comma, pos_start)),
-            '-' => Ok(WithSpan::at(Token::Minus, pos_start)),
-            '(' => Ok(WithSpan::at(Token::ParenLeft, pos_start)),
-            ')' => Ok(WithSpan::at(Token::ParenRight, pos_start)),
-            '%' => Ok(WithSpan::at(Token::Percent, pos_start)),
-            '+' => Ok(WithSpan::at(Token::Plus, pos_start)),
-            '/' => Ok(WithSpan::at(Token::SlashForward, pos_start)),
+            ':' => Ok(WithSpan::new(Token::Colon, pos_start, self.it.end())),
+            ',' => Ok(WithSpan::new(Token::Comma, pos_start, self.it.end())),
+            '-' => Ok(WithSpan::new(Token::Minus, pos_start, self.it.end())),
+            '(' => Ok(WithSpan::new(Token::ParenLeft, pos_start, self.it.end())),
+            ')' => Ok(WithSpan::new(Token::ParenRight, pos_start, self.it.end())),
+            '%' => Ok(WithSpan::new(Token::Percent, pos_start, self.it.end())),
+            '+' => Ok(WithSpan::new(Token::Plus, pos_start, self.it.end())),
+            '/' => Ok(WithSpan::new(Token::SlashForward, pos_start, self.it.end())),
 
             '*' => {
                 let token = self.next_if_else('*', Token::StarStar, Token::Star);
-                Ok(WithSpan::new(token, pos_start, self.it.pos))
+                Ok(WithSpan::new(token, pos_start, self.it.end()))
             }
 
             '!' => {
                 let token = self.next_if_else('=', Token::BangEqual, Token::Bang);
-                Ok(WithSpan::new(token, pos_start, self.it.pos))
+                Ok(WithSpan::new(token, pos_start, self.it.end()))
             }
 
             '<' => {
                 let token = self.next_if_else('=', Token::LessEqual, Token::Less);
-                Ok(WithSpan::new(token, pos_start, self.it.pos))
+                Ok(WithSpan::new(token, pos_start, self.it.end()))
             }
 
             '>' => {
                 let token = self.next_if_else('=', Token::GreaterEqual, Token::Greater);
-                Ok(WithSpan::new(token, pos_start, self.it.pos))
+                Ok(WithSpan::new(token, pos_start, self.it.end()))
             }
 
             '=' => {
                 let token = self.next_if_else('=', Token::EqualEqual, Token::Equal);
-                Ok(WithSpan::new(token, pos_start, self.it.pos))
+                Ok(WithSpan::new(token, pos_start, self.it.end()))
             }
 
-            '\'' | '"' => {
-                let value = self.next_while(|ch| ch != char).into_iter().collect();
-                if let Some(ch) = self.it.next() {
-                    assert_eq!(ch, char, "delimeters should match");
-                    Ok(WithSpan::new(
-                        Token::String {
-                            value,
-                            delimeter: token::StringDelimeter::from_char(ch)
-                                .expect("string delimeter is valid"),
-                        },
-                        pos_start,
-                        self.it.pos + 1,
-                    ))
-                } else {
-                    Err(WithSpan::new(
-                        error::Kind::UnterminatedString,
-                        pos_start,
-                        self.it.pos,
-                    ))
-                }
-            }
+            data::SHEET_QUOTE => match self.match_quoted_cell_ref(pos_start) {
+                Some(token) => Ok(token),
+                None => self.match_string(char, pos_start),
+            },
+
+            '"' => self.match_string(char, pos_start),
 
             '$' => {
                 let rest = self.next_while(|ch| Self::is_valid_cell_ref_char(&ch));
@@ -232,51 +213,42 @@ impl<'a> Lexer<'a> {
                     Ok(WithSpan::new(
                         Token::CellRef(cell),
                         pos_start,
-                        self.it.pos + 1,
+                        self.it.end(),
                     ))
                 } else {
                     Err(WithSpan::new(
                         error::Kind::InvalidCellRef,
                         pos_start,
-                        self.it.pos,
+                        self.it.end(),
                     ))
                 }
             }
 
-            char if char.is_ascii_digit() => {
-                let rest = self.next_while(|ch| ch.is_ascii_digit() || ch == '.');
-                let value = iter::once(char).chain(rest).collect::<String>();
+            char if char.is_ascii_digit() => match self.match_indexed_cell_ref(char, pos_start) {
+                Some(token) => Ok(token),
+                None => self.match_number(char, pos_start),
+            },
 
-                if value
-                    .chars()
-                    .last()
-                    .expect("at least one character in value")
-                    == '.'
-                {
-                    Err(WithSpan::new(
-                        error::Kind::RadixTerminator,
-                        pos_start,
-                        self.it.pos,
-                    ))
-                } else if value.chars().filter(|ch| *ch == '.').count() > 1 {
-                    Err(WithSpan::new(
-                        error::Kind::MultipleRadixPoints,
+            '.' => {
+                if self.it.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+                    self.match_number(char, pos_start)
+                } else {
+                    Ok(WithSpan::new(
+                        Token::Unknown(char),
                         pos_start,
-                        self.it.pos,
+                        self.it.end(),
                     ))
-                } else {
-                    Ok(WithSpan::new(Token::Number(value), pos_start, self.it.pos))
                 }
             }
 
-            char if char.is_ascii_alphabetic() => {
+            char if char.is_alphabetic() => {
                 let rest = self.next_while(|ch| Self::is_valid_ident_or_cell_ref_char(&ch));
                 let value = iter::once(char).chain(rest).collect::<String>();
                 if let Some(cell) = data::CellRef::from_str(&value) {
                     Ok(WithSpan::new(
                         Token::CellRef(cell),
                         pos_start,
-                        self.it.pos + 1,
+                        self.it.end(),
                     ))
                 } else {
                     let value_lower = value.to_lowercase();
@@ -284,25 +256,274 @@ impl<'a> Lexer<'a> {
                         Ok(WithSpan::new(
                             Token::Keyword(word),
                             pos_start,
-                            self.it.pos + 1,
+                            self.it.end(),
                         ))
                     } else {
                         Ok(WithSpan::new(
                             Token::Identifier(value),
                             pos_start,
-                            self.it.pos + 1,
+                            self.it.end(),
                         ))
                     }
                 }
             }
 
-            char => Ok(WithSpan::at(Token::Unknown(char), pos_start)),
+            char => Ok(WithSpan::new(
+                Token::Unknown(char),
+                pos_start,
+                self.it.end(),
+            )),
         };
 
         Some(token)
     }
 }
 
+impl<'a> Lexer<'a> {
+    /// Match a number literal.
+    /// Numbers may start with a radix point and end with an exponent.
+    /// e.g. `12`, `1.5`, `.5`, `2.5E6`, `1e-3`
+    ///
+    /// # Arguments
+    /// + `first`: First character of the number, already consumed.
+    /// + `pos_start`: Position of `first`.
+    fn match_number(
+        &mut self,
+        first: char,
+        pos_start: Position,
+    ) -> Result<WithSpan<Token>, WithSpan<error::Kind>> {
+        let rest = self.next_while(|ch| ch.is_ascii_digit() || ch == '.');
+        let mut value = iter::once(first).chain(rest).collect::<String>();
+
+        if value
+            .chars()
+            .last()
+            .expect("at least one character in value")
+            == '.'
+        {
+            return Err(WithSpan::new(
+                error::Kind::RadixTerminator,
+                pos_start,
+                self.it.end(),
+            ));
+        } else if value.chars().filter(|ch| *ch == '.').count() > 1 {
+            return Err(WithSpan::new(
+                error::Kind::MultipleRadixPoints,
+                pos_start,
+                self.it.end(),
+            ));
+        }
+
+        if let Some(marker) = self
+            .it
+            .peek()
+            .copied()
+            .filter(|ch| *ch == 'e' || *ch == 'E')
+        {
+            self.it.next();
+            value.push(marker);
+            if let Some(sign) = self
+                .it
+                .peek()
+                .copied()
+                .filter(|ch| *ch == '+' || *ch == '-')
+            {
+                self.it.next();
+                value.push(sign);
+            }
+
+            let exponent = self.next_while(|ch| ch.is_ascii_digit());
+            if exponent.is_empty() {
+                return Err(WithSpan::new(
+                    error::Kind::MissingExponent,
+                    pos_start,
+                    self.it.end(),
+                ));
+            }
+            value.extend(exponent);
+        }
+
+        Ok(WithSpan::new(
+            Token::Number(value),
+            pos_start,
+            self.it.end(),
+        ))
+    }
+}
+
+impl<'a> Lexer<'a> {
+    /// Match a cell reference with a quoted sheet label.
+    /// e.g. `'My Sheet'!A1`
+    ///
+    /// # Arguments
+    /// + `pos_start`: Position of the opening quote, already consumed.
+    ///
+    /// # Returns
+    /// `None` without consuming any input if the input is not a valid quoted cell reference.
+    fn match_quoted_cell_ref(&mut self, pos_start: Position) -> Option<WithSpan<Token>> {
+        let mut it = self.it.clone();
+        let mut value = data::SHEET_QUOTE.to_string();
+        loop {
+            let ch = it.next()?;
+            value.push(ch);
+            if ch == data::SHEET_QUOTE {
+                match it.peek().copied() {
+                    Some(data::SHEET_QUOTE) => value.push(it.next()?),
+                    Some(data::SHEET_DELIMETER) => break,
+                    _ => return None,
+                }
+            }
+        }
+
+        while let Some(ch) = it.peek().copied() {
+            if !Self::is_valid_cell_ref_char(&ch) {
+                break;
+            }
+            value.push(ch);
+            it.next();
+        }
+
+        let cell = data::CellRef::from_str(&value)?;
+        let end = it.end();
+        self.it = it;
+        Some(WithSpan::new(Token::CellRef(cell), pos_start, end))
+    }
+
+    /// Match a cell reference with a sheet index.
+    /// e.g. `1!A1`
+    ///
+    /// # Arguments
+    /// + `first`: First digit of the sheet index, already consumed.
+    /// + `pos_start`: Position of `first`.
+    ///
+    /// # Returns
+    /// `None` without consuming any input if the input is not a valid indexed cell reference.
+    fn match_indexed_cell_ref(
+        &mut self,
+        first: char,
+        pos_start: Position,
+    ) -> Option<WithSpan<Token>> {
+        let mut it = self.it.clone();
+        let mut value = first.to_string();
+        while let Some(ch) = it.peek().copied().filter(|ch| ch.is_ascii_digit()) {
+            value.push(ch);
+            it.next();
+        }
+
+        // `1!=A1` is a comparison
+        value.push(it.next_if_eq(&data::SHEET_DELIMETER)?);
+        if it.peek().is_none_or(|ch| *ch == '=') {
+            return None;
+        }
+
+        while let Some(ch) = it.peek().copied() {
+            if !Self::is_valid_cell_ref_char(&ch) {
+                break;
+            }
+            value.push(ch);
+            it.next();
+        }
+
+        let cell = data::CellRef::from_str(&value)?;
+        let end = it.end();
+        self.it = it;
+        Some(WithSpan::new(Token::CellRef(cell), pos_start, end))
+    }
+
+    /// Match a string literal.
+    /// Supported escape sequences are `\'`, `\"`, `\\`, `\n`, `\r`, `\t`,
+    /// and unicode escapes of the form `\u{1F600}`.
+    ///
+    /// # Arguments
+    /// + `delimeter`: Opening delimeter, already consumed.
+    /// + `pos_start`: Position of `delimeter`.
+    fn match_string(
+        &mut self,
+        delimeter: char,
+        pos_start: Position,
+    ) -> Result<WithSpan<Token>, WithSpan<error::Kind>> {
+        let mut value = String::new();
+        let mut invalid_escape = None;
+        loop {
+            let Some(ch) = self.it.next() else {
+                return Err(WithSpan::new(
+                    error::Kind::UnterminatedString,
+                    pos_start,
+                    self.it.end(),
+                ));
+            };
+
+            if ch == delimeter {
+                break;
+            } else if ch == '\\' {
+                let escape_start = self.it.pos;
+                match self.match_escape() {
+                    Some(ch) => value.push(ch),
+                    None => {
+                        invalid_escape.get_or_insert((escape_start, self.it.end()));
+                    }
+                }
+            } else {
+                value.push(ch);
+            }
+        }
+
+        if let Some((start, end)) = invalid_escape {
+            return Err(WithSpan::new(error::Kind::InvalidEscape, start, end));
+        }
+
+        Ok(WithSpan::new(
+            Token::String {
+                value,
+                delimeter: token::StringDelimeter::from_char(delimeter)
+                    .expect("string delimeter is valid"),
+            },
+            pos_start,
+            self.it.end(),
+        ))
+    }
+
+    /// Match the remainder of an escape sequence, after the backslash (`\`).
+    ///
+    /// # Returns
+    /// The escaped character, or `None` if the sequence is invalid.
+    fn match_escape(&mut self) -> Option<char> {
+        let ch = *self.it.peek()?;
+        let ch = match ch {
+            '\'' | '"' | '\\' => ch,
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            'u' => {
+                self.it.next();
+                if *self.it.peek()? != '{' {
+                    return None;
+                }
+                self.it.next();
+                let digits = self.next_while(|ch| ch.is_ascii_hexdigit());
+                if *self.it.peek()? != '}' {
+                    return None;
+                }
+                self.it.next();
+                if digits.is_empty() || digits.len() > 6 {
+                    return None;
+                }
+
+                let code = u32::from_str_radix(&digits.into_iter().collect::<String>(), 16)
+                    .expect("valid hex digits");
+                return char::from_u32(code);
+            }
+            _ => {
+                self.it.next();
+                return None;
+            }
+        };
+
+        self.it.next();
+        Some(ch)
+    }
+}
+
 impl<'a> Into<Lex> for Lexer<'a> {
     fn into(self) -> Lex {
         Lex {
@@ -313,22 +534,37 @@ impl<'a> Into<Lex> for Lexer<'a> {
 }
 
 pub mod error {
-    #[derive(Debug, Clone, Copy)]
+    #[derive(Debug, Clone, Copy, thiserror::Error)]
     #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
     pub enum Kind {
         /// An invalid charater was encountered.
+        #[error("expected `{expected}`, found `{found}`")]
         UnexpectedCharacter { expected: char, found: char },
         /// A string was opened, but not closed before the end of the input.
+        #[error("string is not closed")]
         UnterminatedString,
         /// Number contains multiple radix points.
         /// e.g. `1.2.3`
+        #[error("number has more than one decimal point")]
         MultipleRadixPoints,
         /// Number ends with a radix point.
         /// e.g. `123.`
+        #[error("number ends with a decimal point")]
         RadixTerminator,
+        /// Number has an exponent marker without exponent digits.
+        /// e.g. `1e`, `2.5E+`
+        #[error("number is missing its exponent")]
+        MissingExponent,
+        /// String contains an invalid escape sequence.
+        /// e.g. `'\q'`, `'\u{110000}'`
+        #[error("invalid escape sequence in string")]
+        InvalidEscape,
         /// Could not parse string as a cell reference.
+        #[error("invalid cell reference")]
         InvalidCellRef,
         /// Input ended unexpectedly.
+        #[error("unexpected end of input")]
         EndOfInput,
     }
 }
@@ -356,7 +592,7 @@ mod test {
         let lex = tokenize(input);
         assert_eq!(lex.tokens.len(), 1);
         let token = &lex.tokens[0];
-        assert_eq!(token.span, Span::new(0, 2));
+        assert_eq!(token.span.chars(), 0..2);
         let Token::String { value, delimeter } = &token.value else {
             panic!("incorrect token kind")
         };
@@ -367,7 +603,7 @@ mod test {
         let lex = tokenize(input);
         assert_eq!(lex.tokens.len(), 1);
         let token = &lex.tokens[0];
-        assert_eq!(token.span, Span::new(0, 2));
+        assert_eq!(token.span.chars(), 0..2);
         let Token::String { value, delimeter } = &token.value else {
             panic!("incorrect token kind")
         };
@@ -383,7 +619,7 @@ mod test {
         assert_eq!(lex.tokens.len(), 1);
         assert!(lex.errors.is_empty());
         let token = &lex.tokens[0];
-        assert_eq!(token.span, Span::new(0, 6));
+        assert_eq!(token.span.chars(), 0..6);
         let Token::String { value, delimeter } = &token.value else {
             panic!("incorrect token kind")
         };
@@ -396,7 +632,7 @@ mod test {
         assert_eq!(lex.tokens.len(), 1);
         assert!(lex.errors.is_empty());
         let token = &lex.tokens[0];
-        assert_eq!(token.span, Span::new(0, 6));
+        assert_eq!(token.span.chars(), 0..6);
         let Token::String { value, delimeter } = &token.value else {
             panic!("incorrect token kind")
         };
@@ -411,7 +647,7 @@ mod test {
         assert!(lex.tokens.is_empty());
         assert_eq!(lex.errors.len(), 1);
         let err = &lex.errors[0];
-        assert_eq!(err.span, Span::new(0, 1));
+        assert_eq!(err.span.chars(), 0..1);
         assert_matches!(err.value, error::Kind::UnterminatedString);
 
         let input = "\"";
@@ -419,7 +655,7 @@ mod test {
         assert!(lex.tokens.is_empty());
         assert_eq!(lex.errors.len(), 1);
         let err = &lex.errors[0];
-        assert_eq!(err.span, Span::new(0, 1));
+        assert_eq!(err.span.chars(), 0..1);
         assert_matches!(err.value, error::Kind::UnterminatedString);
 
         let input = "'test";
@@ -427,7 +663,7 @@ mod test {
         assert!(lex.tokens.is_empty());
         assert_eq!(lex.errors.len(), 1);
         let err = &lex.errors[0];
-        assert_eq!(err.span, Span::new(0, 5));
+        assert_eq!(err.span.chars(), 0..5);
         assert_matches!(err.value, error::Kind::UnterminatedString);
 
         let input = "\"test";
@@ -435,10 +671,85 @@ mod test {
         assert!(lex.tokens.is_empty());
         assert_eq!(lex.errors.len(), 1);
         let err = &lex.errors[0];
-        assert_eq!(err.span, Span::new(0, 5));
+        assert_eq!(err.span.chars(), 0..5);
         assert_matches!(err.value, error::Kind::UnterminatedString);
     }
 
+    #[test]
+    fn tokenize_string_escapes() {
+        let input = r#"'it\'s'"#;
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 1);
+        assert!(lex.errors.is_empty());
+        let token = &lex.tokens[0];
+        assert_eq!(token.span.chars(), 0..7);
+        let Token::String { value, .. } = &token.value else {
+            panic!("incorrect token kind")
+        };
+        assert_eq!(value, "it's");
+
+        let input = r#""a \"b\" \\ c\nd\t\u{e9}\u{1F600}""#;
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 1);
+        assert!(lex.errors.is_empty());
+        let Token::String { value, .. } = &lex.tokens[0].value else {
+            panic!("incorrect token kind")
+        };
+        assert_eq!(value, "a \"b\" \\ c\nd\té😀");
+
+        let input = "'é ü'";
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 1);
+        let Token::String { value, .. } = &lex.tokens[0].value else {
+            panic!("incorrect token kind")
+        };
+        assert_eq!(value, "é ü");
+
+        for input in [r"'\q'", r"'\u{110000}'", r"'\u{}'", r"'\u{zz}'"] {
+            let lex = tokenize(input);
+            assert!(lex.tokens.is_empty(), "{input}");
+            assert_eq!(lex.errors.len(), 1, "{input}");
+            let err = &lex.errors[0];
+            assert_matches!(err.value, error::Kind::InvalidEscape);
+        }
+
+        let input = r"'\q' + 1";
+        let lex = tokenize(input);
+        assert_eq!(lex.errors.len(), 1);
+        assert_eq!(lex.errors[0].span.chars(), 1..3);
+        assert_eq!(lex.tokens.len(), 2);
+
+        let input = r"'a\'";
+        let lex = tokenize(input);
+        assert!(lex.tokens.is_empty());
+        assert_eq!(lex.errors.len(), 1);
+        assert_matches!(lex.errors[0].value, error::Kind::UnterminatedString);
+    }
+
+    #[test]
+    fn tokenize_multibyte_spans() {
+        let input = "'é' +\n  ü";
+        let lex = tokenize(input);
+        assert!(lex.errors.is_empty());
+        assert_eq!(lex.tokens.len(), 3);
+
+        let string = &lex.tokens[0].span;
+        assert_eq!(string.chars(), 0..3);
+        assert_eq!(string.bytes(), 0..4);
+        assert_eq!(&input[string.bytes()], "'é'");
+
+        let plus = &lex.tokens[1].span;
+        assert_eq!(plus.chars(), 4..5);
+        assert_eq!(plus.bytes(), 5..6);
+
+        let ident = &lex.tokens[2];
+        assert_eq!(ident.value, Token::Identifier("ü".to_string()));
+        assert_eq!(ident.span.chars(), 8..9);
+        assert_eq!(ident.span.bytes(), 9..11);
+        assert_eq!(ident.span.start.line, 1);
+        assert_eq!(ident.span.start.column, 2);
+    }
+
     #[test]
     fn tokenize_number() {
         let input = "3";
@@ -454,6 +765,14 @@ mod test {
         assert!(lex.errors.is_empty());
         let token = &lex.tokens[0];
         assert_eq!(token.value, Token::Number("3.0".to_string()));
+        assert_eq!(token.span.chars(), 0..3);
+
+        let input = "5%";
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 2);
+        assert_eq!(lex.tokens[0].span.chars(), 0..1);
+        assert_eq!(lex.tokens[1].value, Token::Percent);
+        assert_eq!(lex.tokens[1].span.chars(), 1..2);
     }
 
     #[test]
@@ -488,6 +807,61 @@ mod test {
         assert_matches!(err.value, error::Kind::RadixTerminator);
     }
 
+    #[test]
+    fn tokenize_number_leading_radix_point() {
+        let input = ".5";
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 1);
+        assert!(lex.errors.is_empty());
+        let token = &lex.tokens[0];
+        assert_eq!(token.value, Token::Number(".5".to_string()));
+
+        let input = ".5.2";
+        let lex = tokenize(input);
+        assert!(lex.tokens.is_empty());
+        assert_eq!(lex.errors.len(), 1);
+        let err = &lex.errors[0];
+        assert_matches!(err.value, error::Kind::MultipleRadixPoints);
+
+        let input = ".";
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 1);
+        assert!(lex.errors.is_empty());
+        assert_eq!(lex.tokens[0].value, Token::Unknown('.'));
+    }
+
+    #[test]
+    fn tokenize_number_exponent() {
+        for input in ["1e3", "1e-3", "2.5E6", "2.5E+6", ".5e2"] {
+            let lex = tokenize(input);
+            assert_eq!(lex.tokens.len(), 1);
+            assert!(lex.errors.is_empty());
+            let token = &lex.tokens[0];
+            assert_eq!(token.value, Token::Number(input.to_string()));
+        }
+
+        let input = "1e3+2";
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 3);
+        assert!(lex.errors.is_empty());
+        assert_eq!(lex.tokens[0].value, Token::Number("1e3".to_string()));
+        assert_eq!(lex.tokens[1].value, Token::Plus);
+
+        for input in ["1e", "1E-", "2.5e+ "] {
+            let lex = tokenize(input);
+            assert!(lex.tokens.is_empty());
+            assert_eq!(lex.errors.len(), 1);
+            let err = &lex.errors[0];
+            assert_matches!(err.value, error::Kind::MissingExponent);
+        }
+
+        let input = "1.e3";
+        let lex = tokenize(input);
+        assert_eq!(lex.errors.len(), 1);
+        let err = &lex.errors[0];
+        assert_matches!(err.value, error::Kind::RadixTerminator);
+    }
+
     #[test]
     fn tokenize_cell_index() {
         let col = 0;
@@ -563,6 +937,81 @@ mod test {
         );
     }
 
+    #[test]
+    fn tokenize_indexed_sheet_cell_ref() {
+        let input = "1!$b2 + 12";
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 3);
+        assert!(lex.errors.is_empty());
+        assert_eq!(lex.tokens[0].span.chars(), 0..5);
+        assert_eq!(
+            lex.tokens[0].value,
+            Token::CellRef(data::CellRef {
+                sheet: data::SheetRef::Absolute(data::SheetIndex::Index(1)),
+                col: 1,
+                row: 1,
+                col_mode: data::RefMode::Absolute,
+                row_mode: data::RefMode::Relative
+            })
+        );
+        assert_eq!(lex.tokens[2].value, Token::Number("12".to_string()));
+
+        let input = "1!=a1";
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 3);
+        assert_eq!(lex.tokens[0].value, Token::Number("1".to_string()));
+        assert_eq!(lex.tokens[1].value, Token::BangEqual);
+
+        let input = "1.5!a1";
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens[0].value, Token::Number("1.5".to_string()));
+        assert_eq!(lex.tokens[1].value, Token::Bang);
+    }
+
+    #[test]
+    fn tokenize_quoted_sheet_cell_ref() {
+        let input = "'My Sheet'!$b2";
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 1);
+        assert!(lex.errors.is_empty());
+        let token = &lex.tokens[0];
+        assert_eq!(token.span.chars(), 0..14);
+        assert_eq!(
+            token.value,
+            Token::CellRef(data::CellRef {
+                sheet: data::SheetRef::Absolute(data::SheetIndex::Label("My Sheet".to_string())),
+                col: 1,
+                row: 1,
+                col_mode: data::RefMode::Absolute,
+                row_mode: data::RefMode::Relative
+            })
+        );
+
+        let input = "'Bob''s'!a1 + 1";
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 3);
+        assert!(lex.errors.is_empty());
+        let Token::CellRef(cell) = &lex.tokens[0].value else {
+            panic!("incorrect token kind");
+        };
+        assert_eq!(
+            cell.sheet,
+            data::SheetRef::Absolute(data::SheetIndex::Label("Bob's".to_string()))
+        );
+
+        // strings
+        let input = "'My Sheet'";
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 1);
+        assert_matches!(lex.tokens[0].value, Token::String { .. });
+
+        let input = "'My Sheet'!";
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 2);
+        assert_matches!(lex.tokens[0].value, Token::String { .. });
+        assert_eq!(lex.tokens[1].value, Token::Bang);
+    }
+
     #[test]
     fn tokenize_ident() {
         let input = "a";
@@ -580,6 +1029,30 @@ mod test {
         assert_eq!(token.value, Token::Identifier(input.to_string()));
     }
 
+    #[test]
+    fn tokenize_unicode() {
+        let input = "größe";
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 1);
+        assert!(lex.errors.is_empty());
+        assert_eq!(lex.tokens[0].value, Token::Identifier(input.to_string()));
+
+        let input = "données!b2";
+        let lex = tokenize(input);
+        assert_eq!(lex.tokens.len(), 1);
+        assert!(lex.errors.is_empty());
+        assert_eq!(
+            lex.tokens[0].value,
+            Token::CellRef(data::CellRef {
+                sheet: data::SheetRef::Absolute(data::SheetIndex::Label("données".to_string())),
+                col: 1,
+                row: 1,
+                col_mode: data::RefMode::Relative,
+                row_mode: data::RefMode::Relative
+            })
+        );
+    }
+
     #[test]
     fn tokenize_keyword() {
         let reserved = [
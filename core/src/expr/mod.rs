@@ -6,19 +6,32 @@
 mod ast;
 mod eval;
 mod lex;
+mod normalize;
 mod parse;
 mod position;
+mod reference;
 mod token;
+mod version;
 
-pub use eval::{Context, ContextError, Error, Value};
+pub use eval::{
+    Budget, Context, ContextError, EmptyCells, EmptyComparison, Error, Options, Overflow,
+    Recording, Value, WithOptions,
+};
+pub use normalize::normalize;
+pub use position::{Position, Span, WithSpan};
+pub use reference::{Reference, references};
+pub use version::{Compatibility, UNVERSIONED, VERSION, compatibility};
 
 /// Validate the input can be parsed.
-pub fn parse(input: impl AsRef<str>) -> Result<(), Error> {
-    let lex = lex::tokenize(input);
+///
+/// # Returns
+/// The first error with its location in the input.
+pub fn parse(input: impl AsRef<str>) -> Result<(), WithSpan<Error>> {
+    let mut lex = lex::tokenize(input);
     if !lex.errors.is_empty() {
-        return Err(Error::Tokenize(lex.errors[0].value));
+        return Err(lex.errors.swap_remove(0).map(Error::Tokenize));
     }
-    parse::parse(&lex.tokens).map_err(|err| Error::Parse(err.value))?;
+    parse::parse(&lex.tokens).map_err(|err| err.map(Error::Parse))?;
     Ok(())
 }
 
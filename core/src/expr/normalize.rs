@@ -0,0 +1,141 @@
+//! Normalize expressions so equivalent sources can be compared.
+use super::{
+    lex,
+    position::WithSpan,
+    token::{Keyword, Token},
+};
+use std::fmt::Write;
+
+/// Normalize the text of an expression.
+/// Whitespace is removed where it does not separate tokens,
+/// cell references are upper cased, keywords are lower cased,
+/// and strings are double quoted.
+/// e.g. `SUM( a1:b2 ) + 'x'` and `sum(A1:B2)+"x"` both normalize to `sum(A1:B2)+"x"`.
+///
+/// # Returns
+/// The trimmed input if it can not be tokenized.
+pub fn normalize(input: impl AsRef<str>) -> String {
+    let input = input.as_ref();
+    let lex = lex::tokenize(input);
+    if !lex.errors.is_empty() {
+        return input.trim().to_string();
+    }
+
+    let mut normalized = String::with_capacity(input.len());
+    for (idx, token) in lex.tokens.iter().enumerate() {
+        if idx > 0 && separated(&lex.tokens[idx - 1], token, lex.tokens.get(idx + 1)) {
+            normalized.push(' ');
+        }
+
+        match &token.value {
+            Token::Bang => normalized.push('!'),
+            Token::BangEqual => normalized.push_str("!="),
+            Token::CellRef(cell) => write!(normalized, "{cell}").unwrap(),
+            Token::Colon => normalized.push(':'),
+            Token::Comma => normalized.push(','),
+            Token::Equal => normalized.push('='),
+            Token::EqualEqual => normalized.push_str("=="),
+            Token::Greater => normalized.push('>'),
+            Token::GreaterEqual => normalized.push_str(">="),
+            Token::Identifier(value) => normalized.push_str(value),
+            Token::Less => normalized.push('<'),
+            Token::LessEqual => normalized.push_str("<="),
+            Token::Minus => normalized.push('-'),
+            Token::Number(value) => normalized.push_str(value),
+            Token::ParenLeft => normalized.push('('),
+            Token::ParenRight => normalized.push(')'),
+            Token::Percent => normalized.push('%'),
+            Token::Plus => normalized.push('+'),
+            Token::Keyword(word) => normalized.push_str(word.as_str()),
+            Token::SlashForward => normalized.push('/'),
+            Token::Star => normalized.push('*'),
+            Token::StarStar => normalized.push_str("**"),
+            Token::String { value, .. } => write!(normalized, "{value:?}").unwrap(),
+            Token::Unknown(ch) => normalized.push(*ch),
+        }
+    }
+
+    normalized
+}
+
+/// # Returns
+/// Whether a space must separate two consecutive tokens
+/// for them to be tokenized and parsed the same way.
+///
+/// # Arguments
+/// + `after`: Token following `next`.
+fn separated(
+    previous: &WithSpan<Token>,
+    next: &WithSpan<Token>,
+    after: Option<&WithSpan<Token>>,
+) -> bool {
+    match (&previous.value, &next.value) {
+        (
+            Token::CellRef(_)
+            | Token::Identifier(_)
+            | Token::Keyword(_)
+            | Token::Number(_)
+            | Token::Unknown(_),
+            Token::CellRef(_)
+            | Token::Identifier(_)
+            | Token::Keyword(_)
+            | Token::Number(_)
+            | Token::Unknown(_),
+        ) => true,
+
+        (
+            Token::Bang | Token::Equal | Token::Greater | Token::Less,
+            Token::Equal | Token::EqualEqual,
+        ) => true,
+
+        (Token::Star, Token::Star | Token::StarStar) => true,
+
+        // A percent immediately following a number is postfix unless followed by an operand,
+        // so a remainder without a right operand must stay separated.
+        (Token::Number(_), Token::Percent) => {
+            previous.span.end != next.span.start && !after.is_some_and(is_operand)
+        }
+
+        _ => false,
+    }
+}
+
+fn is_operand(token: &WithSpan<Token>) -> bool {
+    matches!(
+        token.value,
+        Token::Bang
+            | Token::CellRef(_)
+            | Token::Identifier(_)
+            | Token::Keyword(Keyword::True | Keyword::False | Keyword::Sum)
+            | Token::Number(_)
+            | Token::ParenLeft
+            | Token::String { .. }
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn normalize_whitespace_and_case() {
+        assert_eq!(normalize("SUM( a1:b2 ) + 'x'"), "sum(A1:B2)+\"x\"");
+        assert_eq!(normalize("sum(A1:B2)+\"x\""), "sum(A1:B2)+\"x\"");
+        assert_eq!(normalize("a1 AND true"), "A1 and true");
+        assert_eq!(normalize("2 * * 3"), "2* *3");
+        assert_eq!(normalize("  a1 +  "), "A1+");
+    }
+
+    #[test]
+    fn normalize_percent() {
+        assert_eq!(normalize("5% * 2"), "5%*2");
+        assert_eq!(normalize("5 % 2"), "5%2");
+        assert_eq!(normalize("5%2"), "5%2");
+        assert_eq!(normalize("5 %"), "5 %");
+    }
+
+    #[test]
+    fn normalize_invalid() {
+        assert_eq!(normalize(" 'unterminated "), "'unterminated");
+    }
+}
@@ -1,6 +1,6 @@
 use super::{
     ast, lex,
-    position::WithSpan,
+    position::{Span, WithSpan},
     token::{self, Token},
 };
 use crate::data;
@@ -30,7 +30,7 @@ enum Precedence {
 impl Precedence {
     pub fn of(token: &token::Kind) -> Self {
         match token {
-            token::Kind::ParenRight => Self::None,
+            token::Kind::ParenRight | token::Kind::Comma => Self::None,
 
             token::Kind::Bang
             | token::Kind::BangEqual
@@ -46,19 +46,16 @@ impl Precedence {
 
             token::Kind::StarStar => Self::Exponent,
 
-            token::Kind::Keyword(keyword) => todo!(),
-
             token::Kind::Equal => Self::Prefix,
 
             token::Kind::CellRef
             | token::Kind::Colon
-            | token::Kind::Comma
             | token::Kind::Identifier
+            | token::Kind::Keyword(_)
             | token::Kind::Number
             | token::Kind::ParenLeft
-            | token::Kind::String => Self::Unambiguous,
-
-            token::Kind::Unknown => todo!(),
+            | token::Kind::String
+            | token::Kind::Unknown => Self::Unambiguous,
         }
     }
 }
@@ -78,8 +75,19 @@ impl<'a> Parser<'a> {
         }
     }
 
-    pub fn idx(&self) -> usize {
-        self.cursor
+    /// # Returns
+    /// Span of the current token,
+    /// or an empty span at the end of the last token if all tokens were consumed.
+    pub fn span(&self) -> Span {
+        match self.tokens.get(self.cursor) {
+            Some(token) => token.span,
+            None => Span::at(
+                self.tokens
+                    .last()
+                    .map(|token| token.span.end)
+                    .unwrap_or_default(),
+            ),
+        }
     }
 }
 
@@ -116,7 +124,12 @@ pub fn parse(tokens: &Vec<WithSpan<Token>>) -> Result<ast::Expr, WithSpan<error:
     }
 
     let mut parser = Parser::new(tokens);
-    parse_expr(&mut parser, Precedence::None)
+    let expr = parse_expr(&mut parser, Precedence::None)?;
+    if matches!(parser.peek(), Some(token::Kind::Comma)) {
+        // Commas only separate function arguments.
+        return parse_infix(&mut parser, expr);
+    }
+    Ok(expr)
 }
 
 #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
@@ -139,31 +152,30 @@ fn parse_expr<'a>(
 }
 
 fn parse_prefix<'a>(parser: &mut Parser<'a>) -> Result<ast::Expr, WithSpan<error::Kind>> {
-    static VALID_PREFIX_TOKENS: &'static [token::Kind] = &[
-        token::Kind::Bang,
-        token::Kind::CellRef,
-        token::Kind::Identifier,
-        token::Kind::Minus,
+    /// Tokens suggested when an expression can not start with the token found.
+    static SUGGESTED_PREFIX_TOKENS: &'static [token::Kind] = &[
         token::Kind::Number,
-        token::Kind::ParenLeft,
-        token::Kind::Keyword(token::Keyword::True),
-        token::Kind::Keyword(token::Keyword::False),
-        token::Kind::Keyword(token::Keyword::Sum),
         token::Kind::String,
+        token::Kind::CellRef,
+        token::Kind::ParenLeft,
     ];
 
     let Some(token) = parser.peek() else {
-        return Err(WithSpan::at(
+        return Err(WithSpan::with_span(
             error::Kind::UnexpectedEndOfInut,
-            parser.cursor,
+            parser.span(),
         ));
     };
 
     match token {
-        token::Kind::String | token::Kind::CellRef | token::Kind::Number => {
-            Ok(parse_literal(parser)?.into())
+        token::Kind::String | token::Kind::CellRef => Ok(parse_literal(parser)?.into()),
+        token::Kind::Number => {
+            let tokens = parser.tokens;
+            let number = &tokens[parser.cursor];
+            let expr = parse_literal(parser)?.into();
+            Ok(parse_percent(parser, expr, &number.span))
         }
-        token::Kind::Bang | token::Kind::Minus => parse_unary(parser),
+        token::Kind::Bang | token::Kind::Minus | token::Kind::Plus => parse_unary(parser),
         token::Kind::BangEqual
         | token::Kind::Colon
         | token::Kind::Comma
@@ -174,29 +186,46 @@ fn parse_prefix<'a>(parser: &mut Parser<'a>) -> Result<ast::Expr, WithSpan<error
         | token::Kind::Less
         | token::Kind::LessEqual
         | token::Kind::Percent
-        | token::Kind::Plus
         | token::Kind::SlashForward
         | token::Kind::Star
-        | token::Kind::StarStar => {
-            let idx = parser.idx();
-            Err(WithSpan::at(error::Kind::InvalidPrefix, idx))
-        }
+        | token::Kind::StarStar => Err(WithSpan::with_span(
+            error::Kind::InvalidPrefix {
+                found: token,
+                expected: SUGGESTED_PREFIX_TOKENS.to_vec(),
+            },
+            parser.span(),
+        )),
         token::Kind::Keyword(word) => match word {
             token::Keyword::True | token::Keyword::False => Ok(parse_literal(parser)?.into()),
-            token::Keyword::And => todo!(),
-            token::Keyword::Or => todo!(),
-            token::Keyword::Sum => todo!(),
+            token::Keyword::And | token::Keyword::Or | token::Keyword::Sum => {
+                Err(WithSpan::with_span(
+                    error::Kind::Unsupported(token::Kind::Keyword(word)),
+                    parser.span(),
+                ))
+            }
         },
-        token::Kind::Identifier => todo!(),
+        token::Kind::Identifier => {
+            let call = parser
+                .tokens
+                .get(parser.cursor + 1)
+                .is_some_and(|token| token.value == Token::ParenLeft);
+            if call {
+                Ok(parse_call(parser)?.into())
+            } else {
+                Err(WithSpan::with_span(
+                    error::Kind::Unsupported(token::Kind::Identifier),
+                    parser.span(),
+                ))
+            }
+        }
         token::Kind::ParenLeft => Ok(parse_group(parser)?.into()),
-        token::Kind::ParenRight => Err(WithSpan::at(
+        token::Kind::ParenRight | token::Kind::Unknown => Err(WithSpan::with_span(
             error::Kind::UnexpectedToken {
-                expected: VALID_PREFIX_TOKENS.to_vec(),
-                found: token::Kind::ParenRight,
+                expected: SUGGESTED_PREFIX_TOKENS.to_vec(),
+                found: token,
             },
-            parser.cursor,
+            parser.span(),
         )),
-        token::Kind::Unknown => todo!(),
     }
 }
 
@@ -235,31 +264,32 @@ fn parse_infix<'a>(
         | token::Kind::Plus
         | token::Kind::SlashForward
         | token::Kind::Star
-        | token::Kind::StarStar => Ok(parse_binary(parser, lhs)?.into()),
+        | token::Kind::StarStar => parse_binary(parser, lhs),
 
-        token::Kind::ParenLeft => todo!(),
-        token::Kind::ParenRight => todo!(),
         token::Kind::Keyword(word) => match word {
-            token::Keyword::And => todo!(),
-            token::Keyword::Or => todo!(),
-            token::Keyword::Sum => todo!(),
+            token::Keyword::And | token::Keyword::Or | token::Keyword::Sum => {
+                return Err(WithSpan::with_span(
+                    error::Kind::Unsupported(token::Kind::Keyword(word)),
+                    parser.span(),
+                ));
+            }
             keyword => {
-                return Err(WithSpan::at(
+                return Err(WithSpan::with_span(
                     error::Kind::UnexpectedToken {
                         expected: VALID_TOKEN_KINDS.to_vec(),
                         found: token::Kind::Keyword(keyword),
                     },
-                    parser.cursor,
+                    parser.span(),
                 ));
             }
         },
         token => {
-            return Err(WithSpan::at(
+            return Err(WithSpan::with_span(
                 error::Kind::UnexpectedToken {
                     expected: VALID_TOKEN_KINDS.to_vec(),
                     found: token,
                 },
-                parser.cursor,
+                parser.span(),
             ));
         }
     }
@@ -267,7 +297,6 @@ fn parse_infix<'a>(
 
 #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
 fn parse_group<'a>(parser: &mut Parser<'a>) -> Result<ast::ExprGroup, WithSpan<error::Kind>> {
-    let pos_start = parser.cursor;
     let open_delimeter = parser.next().expect("tokens to exist");
     let expr = match parse_expr(parser, Precedence::None) {
         Ok(expr) => expr,
@@ -287,8 +316,8 @@ fn parse_group<'a>(parser: &mut Parser<'a>) -> Result<ast::ExprGroup, WithSpan<e
     let Some(close_delimeter) = parser.next() else {
         return Err(WithSpan::new(
             error::Kind::UnexpectedEndOfInut,
-            pos_start,
-            parser.cursor,
+            open_delimeter.span.start,
+            parser.span().end,
         ));
     };
 
@@ -302,26 +331,159 @@ fn parse_group<'a>(parser: &mut Parser<'a>) -> Result<ast::ExprGroup, WithSpan<e
             error::Kind::UnclosedGroup {
                 expeted: ast::GroupDelimeter::Parenthesis,
             },
-            pos_start,
-            parser.cursor,
+            open_delimeter.span.start,
+            close_delimeter.span.end,
         ))
     }
 }
 
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+fn parse_call<'a>(parser: &mut Parser<'a>) -> Result<ast::ExprCall, WithSpan<error::Kind>> {
+    let name = parser.next().expect("tokens to exist");
+    let Token::Identifier(value) = &name.value else {
+        unreachable!("invalid function token");
+    };
+    let Some(function) = ast::Function::from_name(value) else {
+        return Err(WithSpan::with_span(
+            error::Kind::UnknownFunction(value.clone()),
+            name.span,
+        ));
+    };
+    let open_delimeter = parser.next().expect("tokens to exist");
+    assert_eq!(open_delimeter.value, Token::ParenLeft);
+
+    let mut args = vec![];
+    let close_delimeter = loop {
+        if matches!(parser.peek(), Some(token::Kind::ParenRight)) && args.is_empty() {
+            break parser.next().expect("tokens to exist");
+        }
+
+        args.push(parse_expr(parser, Precedence::None)?);
+        let Some(next) = parser.next() else {
+            return Err(WithSpan::new(
+                error::Kind::UnexpectedEndOfInut,
+                name.span.start,
+                parser.span().end,
+            ));
+        };
+        match next.value {
+            Token::Comma => continue,
+            Token::ParenRight => break next,
+            _ => {
+                return Err(WithSpan::with_span(
+                    error::Kind::UnexpectedToken {
+                        expected: vec![token::Kind::Comma, token::Kind::ParenRight],
+                        found: token::Kind::from_token(&next.value),
+                    },
+                    next.span,
+                ));
+            }
+        }
+    };
+
+    if args.len() != function.arity() {
+        return Err(WithSpan::new(
+            error::Kind::Arity {
+                function: function.name().to_string(),
+                expected: function.arity(),
+                found: args.len(),
+            },
+            name.span.start,
+            close_delimeter.span.end,
+        ));
+    }
+
+    Ok(ast::ExprCall { function, args })
+}
+
 #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
 fn parse_binary<'a>(
     parser: &mut Parser<'a>,
     lhs: ast::Expr,
-) -> Result<ast::ExprBinary, WithSpan<error::Kind>> {
+) -> Result<ast::Expr, WithSpan<error::Kind>> {
     let op_token = parser.next().expect("tokens still exist");
     let op = ast::OpBinary::from_token(&token::Kind::from_token(&op_token.value))
         .expect(&format!("invalid token kind {op_token:?}"));
-    let rhs = parse_expr(parser, Precedence::None)?;
-    Ok(ast::ExprBinary {
-        op,
-        left: Box::new(lhs),
-        right: Box::new(rhs),
-    })
+    if op.is_comparison() {
+        return parse_comparison(parser, op, lhs);
+    }
+
+    // Operations nest to the right, e.g. `1 + 2 + 3` is `1 + (2 + 3)`,
+    // so the operands of a chain are collected before nesting them
+    // instead of recursing once per operation.
+    let mut operations = vec![(lhs, op)];
+    let mut rhs = parse_prefix(parser)?;
+    while let Some(next) = parser.peek() {
+        if Precedence::None >= Precedence::of(&next) {
+            break;
+        }
+
+        match next {
+            token::Kind::Minus
+            | token::Kind::Percent
+            | token::Kind::Plus
+            | token::Kind::SlashForward
+            | token::Kind::Star
+            | token::Kind::StarStar => {
+                let op_token = parser.next().expect("tokens still exist");
+                let op = ast::OpBinary::from_token(&token::Kind::from_token(&op_token.value))
+                    .expect(&format!("invalid token kind {op_token:?}"));
+                operations.push((rhs, op));
+                rhs = parse_prefix(parser)?;
+            }
+            _ => rhs = parse_infix(parser, rhs)?,
+        }
+    }
+
+    let expr = operations.into_iter().rev().fold(rhs, |rhs, (lhs, op)| {
+        ast::ExprBinary {
+            op,
+            left: Box::new(lhs),
+            right: Box::new(rhs),
+        }
+        .into()
+    });
+    Ok(expr)
+}
+
+/// Parse a comparison, which may be chained.
+/// A single comparison is a [binary expression](ast::ExprBinary),
+/// a chain is a [comparison expression](ast::ExprComparison).
+/// e.g. `1 < A1 < 10` holds if `1 < A1` and `A1 < 10`.
+///
+/// # Arguments
+/// + `op`: Comparison operator, already consumed.
+/// + `lhs`: Left side of the comparison.
+fn parse_comparison<'a>(
+    parser: &mut Parser<'a>,
+    op: ast::OpBinary,
+    lhs: ast::Expr,
+) -> Result<ast::Expr, WithSpan<error::Kind>> {
+    let mut rest = vec![(op, parse_expr(parser, Precedence::Compare)?)];
+    while let Some(op) = parser
+        .peek()
+        .and_then(|token| ast::OpBinary::from_token(&token))
+        .filter(|op| op.is_comparison())
+    {
+        parser.next();
+        rest.push((op, parse_expr(parser, Precedence::Compare)?));
+    }
+
+    if rest.len() == 1 {
+        let (op, rhs) = rest.pop().expect("comparison to exist");
+        return Ok(ast::ExprBinary {
+            op,
+            left: Box::new(lhs),
+            right: Box::new(rhs),
+        }
+        .into());
+    }
+
+    Ok(ast::ExprComparison {
+        first: Box::new(lhs),
+        rest,
+    }
+    .into())
 }
 
 #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
@@ -333,9 +495,9 @@ fn parse_unary<'a>(parser: &mut Parser<'a>) -> Result<ast::Expr, WithSpan<error:
     match &next.value {
         Token::Minus => {
             let Some(token) = parser.peek() else {
-                return Err(WithSpan::at(
+                return Err(WithSpan::with_span(
                     error::Kind::UnexpectedEndOfInut,
-                    parser.cursor,
+                    parser.span(),
                 ));
             };
 
@@ -345,12 +507,13 @@ fn parse_unary<'a>(parser: &mut Parser<'a>) -> Result<ast::Expr, WithSpan<error:
                     unreachable!();
                 };
 
-                Ok(ast::Expr::Literal(
+                let expr = ast::Expr::Literal(
                     ast::LitNumber {
                         value: format!("-{value}"),
                     }
                     .into(),
-                ))
+                );
+                Ok(parse_percent(parser, expr, &token.span))
             } else {
                 let expr = parse_expr(parser, Precedence::Prefix)?;
                 Ok(ast::ExprUnary {
@@ -361,6 +524,9 @@ fn parse_unary<'a>(parser: &mut Parser<'a>) -> Result<ast::Expr, WithSpan<error:
             }
         }
 
+        // `+` has no effect, but is commonly typed out of spreadsheet habit, e.g. `+A1 + B1`.
+        Token::Plus => parse_expr(parser, Precedence::Prefix),
+
         Token::Bang => {
             let expr = parse_expr(parser, Precedence::Prefix)?;
             Ok(ast::ExprUnary {
@@ -374,6 +540,48 @@ fn parse_unary<'a>(parser: &mut Parser<'a>) -> Result<ast::Expr, WithSpan<error:
     }
 }
 
+/// Apply a postfix percent to a number literal.
+///
+/// A `%` is a percent, rather than the remainder operator, if it immediately follows
+/// the number and is not followed by a right operand.
+/// e.g. `5%` and `5% * 2` are percents, `5 % 2` and `5%2` are remainders.
+///
+/// # Arguments
+/// + `expr`: Number literal expression.
+/// + `number`: Span of the number literal.
+fn parse_percent<'a>(parser: &mut Parser<'a>, expr: ast::Expr, number: &Span) -> ast::Expr {
+    let Some(percent) = parser.tokens.get(parser.cursor) else {
+        return expr;
+    };
+    if percent.value != Token::Percent || percent.span.start != number.end {
+        return expr;
+    }
+
+    let operand = parser
+        .tokens
+        .get(parser.cursor + 1)
+        .map(|token| token::Kind::from_token(&token.value));
+    if let Some(
+        token::Kind::Bang
+        | token::Kind::CellRef
+        | token::Kind::Identifier
+        | token::Kind::Keyword(token::Keyword::True | token::Keyword::False | token::Keyword::Sum)
+        | token::Kind::Number
+        | token::Kind::ParenLeft
+        | token::Kind::String,
+    ) = operand
+    {
+        return expr;
+    }
+
+    parser.next();
+    ast::ExprUnary {
+        op: ast::OpUnary::Percent,
+        expr: Box::new(expr),
+    }
+    .into()
+}
+
 #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
 fn parse_literal<'a>(input: &mut Parser<'a>) -> Result<ast::ExprLiteral, WithSpan<error::Kind>> {
     let next = input.next().expect("non-empty token stream");
@@ -410,34 +618,74 @@ pub mod error {
     use super::{ast, token};
     use crate::expr::parse::ast::GroupDelimeter;
 
-    #[derive(Debug, derive_more::From, Clone)]
+    #[derive(Debug, derive_more::From, Clone, thiserror::Error)]
     #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
     pub enum Kind {
         /// The input was unexpectedly empty.
+        #[error("formula ended unexpectedly")]
         UnexpectedEndOfInut,
 
         /// An unexpected kind of token was found.
+        #[error("unexpected {found}, expected {}", expected_list(.expected))]
         UnexpectedToken {
             expected: Vec<token::Kind>,
             found: token::Kind,
         },
 
-        /// The token is not valid as a prefix.
-        InvalidPrefix,
+        /// An expression can not start with the token.
+        /// Holds suggestions of tokens that can.
+        #[error("unexpected {found}, expected {}", expected_list(.expected))]
+        InvalidPrefix {
+            found: token::Kind,
+            expected: Vec<token::Kind>,
+        },
 
         /// A group wasn't closed.
-        UnclosedGroup {
-            expeted: ast::GroupDelimeter,
+        #[error("missing closing `{}`", .expeted.close())]
+        UnclosedGroup { expeted: ast::GroupDelimeter },
+
+        /// The function does not exist.
+        #[error("unknown function `{0}`")]
+        UnknownFunction(String),
+
+        /// A function was called with the wrong number of arguments.
+        #[error("`{function}` takes {expected} argument(s), found {found}")]
+        Arity {
+            function: String,
+            expected: usize,
+            found: usize,
         },
 
+        /// The token is valid, but not yet supported.
+        #[error("{0} is not supported yet")]
+        Unsupported(token::Kind),
+
+        #[error("{0}")]
         Binary(KindBinary),
     }
 
-    #[derive(Debug, Clone, Copy)]
+    #[derive(Debug, Clone, Copy, thiserror::Error)]
     #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
     pub enum KindBinary {
+        #[error("operator is missing its right side")]
         InvalidRhs,
     }
+
+    /// Formats a list of token kinds as `a`, `a or b`, or `a, b, or c`.
+    fn expected_list(kinds: &[token::Kind]) -> String {
+        let kinds = kinds
+            .iter()
+            .map(|kind| kind.to_string())
+            .collect::<Vec<_>>();
+        match kinds.as_slice() {
+            [] => "nothing".to_string(),
+            [kind] => kind.clone(),
+            [a, b] => format!("{a} or {b}"),
+            [rest @ .., last] => format!("{}, or {last}", rest.join(", ")),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -718,6 +966,65 @@ mod test {
         assert_eq!(right, "2");
     }
 
+    #[test]
+    fn parse_comparison_chain() {
+        let number = |value: &str| -> ast::Expr {
+            ast::ExprLiteral::from(ast::LitNumber {
+                value: value.to_string(),
+            })
+            .into()
+        };
+
+        let src = "1 < 2";
+        let lex = lex::tokenize(src);
+        let expr = parse(&lex.tokens).expect("input to be valid");
+        assert_eq!(
+            expr,
+            ast::ExprBinary {
+                op: ast::OpBinary::Less,
+                left: Box::new(number("1")),
+                right: Box::new(number("2")),
+            }
+            .into()
+        );
+
+        let src = "1 < 2 <= 3";
+        let lex = lex::tokenize(src);
+        let expr = parse(&lex.tokens).expect("input to be valid");
+        assert_eq!(
+            expr,
+            ast::ExprComparison {
+                first: Box::new(number("1")),
+                rest: vec![
+                    (ast::OpBinary::Less, number("2")),
+                    (ast::OpBinary::LessEqual, number("3")),
+                ],
+            }
+            .into()
+        );
+
+        let src = "1 < 2 == 2 > 0";
+        let lex = lex::tokenize(src);
+        let expr = parse(&lex.tokens).expect("input to be valid");
+        assert_eq!(
+            expr,
+            ast::ExprComparison {
+                first: Box::new(number("1")),
+                rest: vec![
+                    (ast::OpBinary::Less, number("2")),
+                    (ast::OpBinary::Equal, number("2")),
+                    (ast::OpBinary::Greater, number("0")),
+                ],
+            }
+            .into()
+        );
+
+        let src = "1 < 2 <";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input to be invalid");
+        assert_matches!(err.value, error::Kind::UnexpectedEndOfInut);
+    }
+
     #[test]
     fn parse_unary_test() {
         // -
@@ -761,8 +1068,40 @@ mod test {
         assert_matches!(op, ast::OpUnary::Not);
         assert_eq!(
             *expr_num,
+            ast::Expr::Literal(ast::ExprLiteral::CellRef(ast::LitCellRef {
+                value: cell.clone()
+            }))
+        );
+
+        // +
+        let src = format!("+{cell}");
+        let lex = lex::tokenize(src);
+        let expr = parse(&lex.tokens).expect("input to be valid");
+        assert_eq!(
+            expr,
             ast::Expr::Literal(ast::ExprLiteral::CellRef(ast::LitCellRef { value: cell }))
         );
+
+        let src = "+1 + +2";
+        let lex = lex::tokenize(src);
+        let expr = parse(&lex.tokens).expect("input to be valid");
+        let ast::Expr::Binary(ast::ExprBinary { op, left, right }) = expr else {
+            panic!("invalid expression");
+        };
+        assert_matches!(op, ast::OpBinary::Add);
+        assert_matches!(
+            *left,
+            ast::Expr::Literal(ast::ExprLiteral::Number(ast::LitNumber { .. }))
+        );
+        assert_matches!(
+            *right,
+            ast::Expr::Literal(ast::ExprLiteral::Number(ast::LitNumber { .. }))
+        );
+
+        let src = "+";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input to be invalid");
+        assert_matches!(err.value, error::Kind::UnexpectedEndOfInut);
     }
 
     #[test]
@@ -871,6 +1210,115 @@ mod test {
             panic!("invalid expression");
         };
         assert_eq!(value, src);
+
+        for src in [".5", "1e-3", "2.5E6"] {
+            let lex = lex::tokenize(src);
+            assert_eq!(lex.tokens.len(), 1);
+            let expr = parse(&lex.tokens).expect("input to be valid");
+            let ast::Expr::Literal(ast::ExprLiteral::Number(ast::LitNumber { value })) = expr
+            else {
+                panic!("invalid expression");
+            };
+            assert_eq!(value, src);
+        }
+
+        let src = "-.5e2";
+        let lex = lex::tokenize(src);
+        assert_eq!(lex.tokens.len(), 2);
+        let expr = parse(&lex.tokens).expect("input to be valid");
+        let ast::Expr::Literal(ast::ExprLiteral::Number(ast::LitNumber { value })) = expr else {
+            panic!("invalid expression");
+        };
+        assert_eq!(value, src);
+    }
+
+    #[test]
+    fn parse_percent_test() {
+        let src = "5%";
+        let lex = lex::tokenize(src);
+        let expr = parse(&lex.tokens).expect("input to be valid");
+        let ast::Expr::Unary(ast::ExprUnary { op, expr }) = expr else {
+            panic!("invalid expression");
+        };
+        assert_matches!(op, ast::OpUnary::Percent);
+        let ast::Expr::Literal(ast::ExprLiteral::Number(ast::LitNumber { value })) = *expr else {
+            panic!("invalid expression");
+        };
+        assert_eq!(value, "5");
+
+        let src = "-2.5%";
+        let lex = lex::tokenize(src);
+        let expr = parse(&lex.tokens).expect("input to be valid");
+        let ast::Expr::Unary(ast::ExprUnary { op, expr }) = expr else {
+            panic!("invalid expression");
+        };
+        assert_matches!(op, ast::OpUnary::Percent);
+        let ast::Expr::Literal(ast::ExprLiteral::Number(ast::LitNumber { value })) = *expr else {
+            panic!("invalid expression");
+        };
+        assert_eq!(value, "-2.5");
+
+        // binds to the number before binary operators
+        let src = "5% * 2";
+        let lex = lex::tokenize(src);
+        let expr = parse(&lex.tokens).expect("input to be valid");
+        let ast::Expr::Binary(ast::ExprBinary { op, left, right }) = expr else {
+            panic!("invalid expression");
+        };
+        assert_matches!(op, ast::OpBinary::Multiply);
+        assert_matches!(
+            *left,
+            ast::Expr::Unary(ast::ExprUnary {
+                op: ast::OpUnary::Percent,
+                ..
+            })
+        );
+        assert_matches!(*right, ast::Expr::Literal(_));
+
+        let src = "2 * 5%";
+        let lex = lex::tokenize(src);
+        let expr = parse(&lex.tokens).expect("input to be valid");
+        let ast::Expr::Binary(ast::ExprBinary { op, left, right }) = expr else {
+            panic!("invalid expression");
+        };
+        assert_matches!(op, ast::OpBinary::Multiply);
+        assert_matches!(*left, ast::Expr::Literal(_));
+        assert_matches!(
+            *right,
+            ast::Expr::Unary(ast::ExprUnary {
+                op: ast::OpUnary::Percent,
+                ..
+            })
+        );
+
+        let src = "(5%)";
+        let lex = lex::tokenize(src);
+        let expr = parse(&lex.tokens).expect("input to be valid");
+        let ast::Expr::Group(ast::ExprGroup { expr, .. }) = expr else {
+            panic!("invalid expression");
+        };
+        assert_matches!(
+            *expr,
+            ast::Expr::Unary(ast::ExprUnary {
+                op: ast::OpUnary::Percent,
+                ..
+            })
+        );
+
+        // remainder
+        for src in ["5%2", "5 % 2", "5 %2", "5%(2)"] {
+            let lex = lex::tokenize(src);
+            let expr = parse(&lex.tokens).expect("input to be valid");
+            let ast::Expr::Binary(ast::ExprBinary { op, .. }) = expr else {
+                panic!("invalid expression");
+            };
+            assert_matches!(op, ast::OpBinary::Remainder);
+        }
+
+        let src = "5 %";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input should be invalid");
+        assert_matches!(err.value, error::Kind::UnexpectedEndOfInut);
     }
 
     #[test]
@@ -926,4 +1374,143 @@ mod test {
         let expr = parse(&lex.tokens).expect("empty token list to be valid");
         assert_matches!(expr, ast::Expr::Empty);
     }
+
+    #[test]
+    fn parse_unsupported() {
+        let src = "sum";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input should be unsupported");
+        assert_matches!(
+            err.value,
+            error::Kind::Unsupported(token::Kind::Keyword(token::Keyword::Sum))
+        );
+
+        let src = "true and false";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input should be unsupported");
+        assert_matches!(
+            err.value,
+            error::Kind::Unsupported(token::Kind::Keyword(token::Keyword::And))
+        );
+
+        let src = "foo";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input should be unsupported");
+        assert_matches!(err.value, error::Kind::Unsupported(token::Kind::Identifier));
+
+        let src = "1 (2)";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input should be invalid");
+        assert_matches!(err.value, error::Kind::UnexpectedToken { .. });
+    }
+
+    #[test]
+    fn parse_call() {
+        let src = "iferror(1 / 0, 'none')";
+        let lex = lex::tokenize(src);
+        let expr = parse(&lex.tokens).expect("input to be valid");
+        let ast::Expr::Call(ast::ExprCall { function, args }) = expr else {
+            panic!("invalid expression");
+        };
+        assert_matches!(function, ast::Function::IfError);
+        assert_eq!(args.len(), 2);
+        assert_matches!(args[0], ast::Expr::Binary(_));
+        assert_matches!(
+            args[1],
+            ast::Expr::Literal(ast::ExprLiteral::String(ast::LitString { .. }))
+        );
+
+        let src = "ISERROR(a1) == false";
+        let lex = lex::tokenize(src);
+        let expr = parse(&lex.tokens).expect("input to be valid");
+        let ast::Expr::Binary(ast::ExprBinary { op, left, .. }) = expr else {
+            panic!("invalid expression");
+        };
+        assert_matches!(op, ast::OpBinary::Equal);
+        assert_matches!(
+            *left,
+            ast::Expr::Call(ast::ExprCall {
+                function: ast::Function::IsError,
+                ..
+            })
+        );
+
+        let src = "iferror(iserror(a1), (1, 2))";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input should be invalid");
+        assert_matches!(err.value, error::Kind::UnclosedGroup { .. });
+
+        // err: unknown
+        let src = "foo(1)";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input should be invalid");
+        assert_matches!(err.value, error::Kind::UnknownFunction(name) if name == "foo");
+        assert_eq!(err.span.chars(), 0..3);
+
+        // err: arity
+        let src = "iserror(1, 2)";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input should be invalid");
+        assert_matches!(
+            err.value,
+            error::Kind::Arity {
+                expected: 1,
+                found: 2,
+                ..
+            }
+        );
+        assert_eq!(err.span.chars(), 0..13);
+
+        let src = "iferror()";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input should be invalid");
+        assert_matches!(err.value, error::Kind::Arity { found: 0, .. });
+
+        // err: eof
+        let src = "iferror(1,";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input should be invalid");
+        assert_matches!(err.value, error::Kind::UnexpectedEndOfInut);
+
+        // err: comma outside of call
+        let src = "1, 2";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input should be invalid");
+        assert_matches!(err.value, error::Kind::UnexpectedToken { .. });
+    }
+
+    #[test]
+    fn parse_error_span() {
+        let src = "'é' + ü";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input should be unsupported");
+        assert_matches!(err.value, error::Kind::Unsupported(token::Kind::Identifier));
+        assert_eq!(err.span.chars(), 6..7);
+        assert_eq!(&src[err.span.bytes()], "ü");
+
+        let src = "1 +\n  *";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input should be invalid");
+        assert_matches!(
+            err.value,
+            error::Kind::InvalidPrefix {
+                found: token::Kind::Star,
+                ..
+            }
+        );
+        assert_eq!(err.span.start.line, 1);
+        assert_eq!(err.span.start.column, 2);
+        assert_eq!(err.span.chars(), 6..7);
+        assert_eq!(
+            err.value.to_string(),
+            "unexpected `*`, expected number, string, cell reference, or `(`"
+        );
+
+        let src = "('é'";
+        let lex = lex::tokenize(src);
+        let err = parse(&lex.tokens).expect_err("input should be invalid");
+        assert_matches!(err.value, error::Kind::UnexpectedEndOfInut);
+        assert_eq!(err.span.chars(), 0..4);
+        assert_eq!(err.span.bytes(), 0..5);
+    }
 }
@@ -1,35 +1,83 @@
-#[derive(Debug, Copy, Clone, PartialEq, Eq, derive_more::Deref, derive_more::From)]
-pub struct BytePos(pub usize);
+use std::{fmt, ops::Range};
 
-impl std::ops::Add<usize> for BytePos {
-    type Output = Self;
-    fn add(self, rhs: usize) -> Self::Output {
-        Self(self.0 + rhs)
+/// Location in the source.
+/// Tracks both byte and character offsets so consumers indexing either
+/// `str` bytes or `char`s (e.g. editors) can map the location back to the source.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct Position {
+    /// Byte offset.
+    pub byte: usize,
+
+    /// Character offset.
+    pub char: usize,
+
+    /// Line, 0-based.
+    pub line: usize,
+
+    /// Character offset within the line, 0-based.
+    pub column: usize,
+}
+
+impl Position {
+    /// # Returns
+    /// Position following `ch` if `ch` is at this position.
+    pub fn advance(self, ch: char) -> Self {
+        if ch == '\n' {
+            Self {
+                byte: self.byte + ch.len_utf8(),
+                char: self.char + 1,
+                line: self.line + 1,
+                column: 0,
+            }
+        } else {
+            Self {
+                byte: self.byte + ch.len_utf8(),
+                char: self.char + 1,
+                line: self.line,
+                column: self.column + 1,
+            }
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    /// Formats as `line:column`, 1-based.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line + 1, self.column + 1)
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Range of the source, end exclusive.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct Span {
-    pub start: BytePos,
-    pub end: BytePos,
+    pub start: Position,
+    pub end: Position,
 }
 
 impl Span {
-    pub fn new(start: impl Into<BytePos>, end: impl Into<BytePos>) -> Self {
-        Self {
-            start: start.into(),
-            end: end.into(),
-        }
+    pub fn new(start: Position, end: Position) -> Self {
+        Self { start, end }
     }
 
-    /// Span a single position.
-    pub fn at(position: impl Into<BytePos>) -> Self {
-        let pos = position.into();
+    /// Empty span at a position.
+    pub fn at(position: Position) -> Self {
         Self {
-            start: pos,
-            end: pos + 1,
+            start: position,
+            end: position,
         }
     }
+
+    /// # Returns
+    /// Byte range of the span.
+    pub fn bytes(&self) -> Range<usize> {
+        self.start.byte..self.end.byte
+    }
+
+    /// # Returns
+    /// Character range of the span.
+    pub fn chars(&self) -> Range<usize> {
+        self.start.char..self.end.char
+    }
 }
 
 #[derive(Debug)]
@@ -39,18 +87,55 @@ pub struct WithSpan<T> {
 }
 
 impl<T> WithSpan<T> {
-    pub fn new(value: T, start: impl Into<BytePos>, end: impl Into<BytePos>) -> Self {
+    pub fn new(value: T, start: Position, end: Position) -> Self {
         Self {
             value,
             span: Span::new(start, end),
         }
     }
 
-    /// Span a single position.
-    pub fn at(value: T, pos: impl Into<BytePos>) -> Self {
-        Self {
-            value,
-            span: Span::at(pos),
+    pub fn with_span(value: T, span: Span) -> Self {
+        Self { value, span }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> WithSpan<U> {
+        WithSpan {
+            value: f(self.value),
+            span: self.span,
+        }
+    }
+}
+
+impl<T> fmt::Display for WithSpan<T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at {}", self.value, self.span.start)
+    }
+}
+
+impl<T> std::error::Error for WithSpan<T> where T: std::error::Error {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn position_advance() {
+        let mut pos = Position::default();
+        for ch in "aé\n😀b".chars() {
+            pos = pos.advance(ch);
         }
+        assert_eq!(
+            pos,
+            Position {
+                byte: 9,
+                char: 5,
+                line: 1,
+                column: 2,
+            }
+        );
+        assert_eq!(pos.to_string(), "2:3");
     }
 }
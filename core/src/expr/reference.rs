@@ -0,0 +1,102 @@
+//! Extract cell references from expressions.
+use super::{lex, position::Span, token::Token};
+use crate::data;
+
+/// Cell or range referenced by an expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Reference {
+    pub start: data::CellRef,
+
+    /// End of the range, if the reference is a range.
+    pub end: Option<data::CellRef>,
+
+    /// Location of the reference in the source.
+    pub span: Span,
+}
+
+impl Reference {
+    /// # Returns
+    /// `((min row, min col), (max row, max col))` of the cells covered by the reference.
+    pub fn bounds(
+        &self,
+    ) -> (
+        (data::IndexType, data::IndexType),
+        (data::IndexType, data::IndexType),
+    ) {
+        let end = self.end.as_ref().unwrap_or(&self.start);
+        (
+            (self.start.row.min(end.row), self.start.col.min(end.col)),
+            (self.start.row.max(end.row), self.start.col.max(end.col)),
+        )
+    }
+}
+
+/// Extract the cell and range references of an expression.
+/// The input does not need to be a complete or valid expression,
+/// allowing references to be found while an expression is being edited.
+///
+/// # Returns
+/// References in the order they appear in the source.
+pub fn references(input: impl AsRef<str>) -> Vec<Reference> {
+    let lex = lex::tokenize(input);
+    let mut references = vec![];
+    let mut tokens = lex.tokens.iter().peekable();
+    while let Some(token) = tokens.next() {
+        let Token::CellRef(start) = &token.value else {
+            continue;
+        };
+
+        let mut reference = Reference {
+            start: start.clone(),
+            end: None,
+            span: token.span,
+        };
+
+        if let Some(colon) = tokens.next_if(|token| matches!(token.value, Token::Colon)) {
+            reference.span.end = colon.span.end;
+            if let Some(end) = tokens.next_if(|token| matches!(token.value, Token::CellRef(_))) {
+                let Token::CellRef(cell) = &end.value else {
+                    unreachable!("token is a cell ref");
+                };
+                reference.end = Some(cell.clone());
+                reference.span.end = end.span.end;
+            }
+        }
+
+        references.push(reference);
+    }
+
+    references
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn references_cells_and_ranges() {
+        let refs = references("a1 + sum(b2:c5)");
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].start, data::CellRef::dynamic(0u8, 0u8));
+        assert_eq!(refs[0].end, None);
+        assert_eq!(refs[0].span.chars(), 0..2);
+        assert_eq!(refs[1].start, data::CellRef::dynamic(1u8, 1u8));
+        assert_eq!(refs[1].end, Some(data::CellRef::dynamic(4u8, 2u8)));
+        assert_eq!(refs[1].span.chars(), 9..14);
+        assert_eq!(refs[1].bounds(), ((1, 1), (4, 2)));
+    }
+
+    #[test]
+    fn references_partial_input() {
+        let refs = references("b2:");
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].end, None);
+        assert_eq!(refs[0].span.chars(), 0..3);
+
+        let refs = references("(c5:a1 +");
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].bounds(), ((0, 0), (4, 2)));
+
+        assert!(references("'a1' + 1").is_empty());
+    }
+}
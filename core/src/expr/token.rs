@@ -1,7 +1,9 @@
 use crate::data;
+use std::fmt;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Keyword {
     True,
     False,
@@ -86,6 +88,7 @@ impl StringDelimeter {
 /// Should match the variants in [`Token`].
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Kind {
     Bang,
     BangEqual,
@@ -143,3 +146,34 @@ impl Kind {
         }
     }
 }
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bang => write!(f, "`!`"),
+            Self::BangEqual => write!(f, "`!=`"),
+            Self::CellRef => write!(f, "cell reference"),
+            Self::Colon => write!(f, "`:`"),
+            Self::Comma => write!(f, "`,`"),
+            Self::Equal => write!(f, "`=`"),
+            Self::EqualEqual => write!(f, "`==`"),
+            Self::Greater => write!(f, "`>`"),
+            Self::GreaterEqual => write!(f, "`>=`"),
+            Self::Identifier => write!(f, "name"),
+            Self::Less => write!(f, "`<`"),
+            Self::LessEqual => write!(f, "`<=`"),
+            Self::Minus => write!(f, "`-`"),
+            Self::Number => write!(f, "number"),
+            Self::ParenLeft => write!(f, "`(`"),
+            Self::ParenRight => write!(f, "`)`"),
+            Self::Percent => write!(f, "`%`"),
+            Self::Plus => write!(f, "`+`"),
+            Self::Keyword(word) => write!(f, "`{}`", word.as_str()),
+            Self::SlashForward => write!(f, "`/`"),
+            Self::Star => write!(f, "`*`"),
+            Self::StarStar => write!(f, "`**`"),
+            Self::String => write!(f, "string"),
+            Self::Unknown => write!(f, "unknown character"),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Versioning of the expression language.
+//! Formulas are saved with the version they were written under,
+//! so changes to how they parse or evaluate can be reported when they are opened.
+
+/// Current version of the expression grammar and semantics.
+/// Incremented whenever a formula may parse or evaluate differently than before.
+pub const VERSION: u32 = 4;
+
+/// Version of formulas saved before versions were recorded.
+pub const UNVERSIONED: u32 = 1;
+
+/// Changes to the language, with the version that introduced them.
+/// Each describes how formulas written under an earlier version may behave differently.
+const CHANGES: &[(u32, &str)] = &[
+    (
+        2,
+        "Integer arithmetic that overflows evaluates to a decimal number instead of an error by default.",
+    ),
+    (
+        3,
+        "Empty values compare as `\"\"` with strings and `0` with numbers by default, instead of being an error.",
+    ),
+    (
+        3,
+        "Comparisons can be chained, e.g. `1 < A1 < 10` is `1 < A1` and `A1 < 10`.",
+    ),
+    (
+        4,
+        "A leading `+` has no effect, e.g. `+A1` is `A1` instead of an error.",
+    ),
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Written under the current version.
+    Current,
+
+    /// Written under an earlier version.
+    /// Holds the changes made since, which may alter the results of its formulas.
+    Older(Vec<&'static str>),
+
+    /// Written under a later version,
+    /// so its formulas may use features this version does not support.
+    Newer,
+}
+
+impl Compatibility {
+    /// # Returns
+    /// Whether formulas behave as they did when they were written.
+    pub fn is_compatible(&self) -> bool {
+        match self {
+            Self::Current => true,
+            Self::Older(changes) => changes.is_empty(),
+            Self::Newer => false,
+        }
+    }
+}
+
+/// # Returns
+/// Compatibility of formulas written under `version` with the current version.
+pub fn compatibility(version: u32) -> Compatibility {
+    compatibility_with(version, VERSION, CHANGES)
+}
+
+fn compatibility_with(
+    version: u32,
+    current: u32,
+    changes: &[(u32, &'static str)],
+) -> Compatibility {
+    if version == current {
+        Compatibility::Current
+    } else if version > current {
+        Compatibility::Newer
+    } else {
+        Compatibility::Older(
+            changes
+                .iter()
+                .filter(|(introduced, _)| *introduced > version)
+                .map(|(_, change)| *change)
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn compatibility_test() {
+        assert_eq!(compatibility(VERSION), Compatibility::Current);
+        assert_eq!(compatibility(VERSION + 1), Compatibility::Newer);
+        assert_eq!(
+            compatibility(UNVERSIONED),
+            Compatibility::Older(CHANGES.iter().map(|(_, change)| *change).collect())
+        );
+
+        let changes = [(2, "a"), (3, "b")];
+        assert_eq!(
+            compatibility_with(1, 3, &changes),
+            Compatibility::Older(vec!["a", "b"])
+        );
+        assert_eq!(
+            compatibility_with(2, 3, &changes),
+            Compatibility::Older(vec!["b"])
+        );
+        assert!(!compatibility_with(2, 3, &changes).is_compatible());
+    }
+}
@@ -1,34 +1,52 @@
+//! Conversions between sheet indices and their labels.
+//!
+//! Rows are labeled by their 1-based position, and columns alphabetically,
+//! i.e. `A`, ..., `Z`, `AA`, ..., `ZZ`, `AAA`, ....
+//! Conversions are defined for the full range of [`IndexType`](data::IndexType),
+//! and parsing a label that does not fit returns `None`.
+//!
+//! # Examples
+//! ```
+//! use hermes_core::utils;
+//!
+//! assert_eq!(utils::index_to_col(27), "AB");
+//! assert_eq!(utils::col_to_index("AB"), Some(27));
+//! assert_eq!(utils::index_to_row(0), "1");
+//! assert_eq!(utils::row_to_index(1), Some(0));
+//! ```
 use crate::data;
 
-const ALPHABET: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
-const ALPHABET_LEN: usize = 26;
+const ALPHABET: &[u8; 26] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+const ALPHABET_LEN: u64 = 26;
 
 /// Convert an index into its cell row value.
 /// e.g. `0` -> `1`, `1` -> `2`.
 pub fn index_to_row(idx: data::IndexType) -> String {
-    (idx + 1).to_string()
+    (u64::from(idx) + 1).to_string()
 }
 
 /// Convert a numerical index into its
 /// cell column index -- which is alphabetic.
-/// e.g. `0` -> `"A"`, `1` -> `"B"`.
+/// e.g. `0` -> `"A"`, `1` -> `"B"`, `26` -> `"AA"`.
 pub fn index_to_col(idx: data::IndexType) -> String {
-    let idx = idx as usize;
-    let scale = idx / ALPHABET_LEN;
-    if scale == 0 {
-        ALPHABET[idx..idx + 1].to_string()
-    } else if scale <= ALPHABET_LEN {
-        let second = idx % ALPHABET_LEN;
-        let first = ALPHABET[scale - 1..scale].to_string();
-        let second = ALPHABET[second..second + 1].to_string();
-        format!("{first}{second}")
-    } else {
-        unreachable!("wasn't expecting an index this big");
+    // Bijective base 26, computed in `u64` so `idx + 1` can not overflow.
+    let mut value = u64::from(idx) + 1;
+    let mut label = Vec::new();
+    while value > 0 {
+        value -= 1;
+        label.push(ALPHABET[(value % ALPHABET_LEN) as usize]);
+        value /= ALPHABET_LEN;
     }
+
+    label.reverse();
+    String::from_utf8(label).expect("label is ascii")
 }
 
 /// Convert an index into its cell row value.
 /// e.g. `1` -> `0`, `2` -> `1`.
+///
+/// # Returns
+/// `None` if `row` is `0`.
 pub fn row_to_index(row: data::IndexType) -> Option<data::IndexType> {
     row.checked_sub(1)
 }
@@ -37,34 +55,60 @@ pub fn row_to_index(row: data::IndexType) -> Option<data::IndexType> {
 /// into its numerical index.
 /// e.g. `"a"` -> `0`, `"b"` -> `1`.
 /// Letters are case insensitive.
+///
+/// # Returns
+/// `None` if `col` is empty, contains characters other than ascii letters,
+/// or its index does not fit in [`IndexType`](data::IndexType).
+/// See [`try_col_to_index`] for the reason.
 pub fn col_to_index(col: impl AsRef<str>) -> Option<data::IndexType> {
-    let chars = col.as_ref().chars().collect::<Vec<_>>();
-    match chars[..] {
-        [c1] => {
-            let c1 = c1.to_ascii_uppercase();
+    try_col_to_index(col).ok()
+}
 
-            let Some(idx) = ALPHABET.chars().position(|ch| ch == c1) else {
-                return None;
-            };
+/// Convert a cell column index -- which is alphabetic --
+/// into its numerical index.
+/// Letters are case insensitive.
+///
+/// # Errors
+/// + [`error::ColLabel::Empty`] if `col` is empty.
+/// + [`error::ColLabel::InvalidCharacter`] if `col` contains characters other than ascii letters.
+/// + [`error::ColLabel::Overflow`] if the index does not fit in [`IndexType`](data::IndexType).
+pub fn try_col_to_index(col: impl AsRef<str>) -> Result<data::IndexType, error::ColLabel> {
+    let col = col.as_ref();
+    if col.is_empty() {
+        return Err(error::ColLabel::Empty);
+    }
+    if let Some(ch) = col.chars().find(|ch| !ch.is_ascii_alphabetic()) {
+        return Err(error::ColLabel::InvalidCharacter(ch));
+    }
 
-            let idx = idx as data::IndexType;
-            Some(idx)
-        }
-        [c1, c0] => {
-            let c0 = c0.to_ascii_uppercase();
-            let c1 = c1.to_ascii_uppercase();
-
-            let Some(a0) = ALPHABET.chars().position(|ch| ch == c0) else {
-                return None;
-            };
-            let Some(a1) = ALPHABET.chars().position(|ch| ch == c1) else {
-                return None;
-            };
-
-            let idx = ((a1 + 1) * ALPHABET_LEN + a0) as data::IndexType;
-            Some(idx)
-        }
-        _ => None,
+    let mut value: u64 = 0;
+    for ch in col.bytes() {
+        let digit = u64::from(ch.to_ascii_uppercase() - b'A') + 1;
+        value = value
+            .checked_mul(ALPHABET_LEN)
+            .and_then(|value| value.checked_add(digit))
+            .ok_or(error::ColLabel::Overflow)?;
+    }
+
+    data::IndexType::try_from(value - 1).map_err(|_| error::ColLabel::Overflow)
+}
+
+pub mod error {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    pub enum ColLabel {
+        /// Label is empty.
+        #[error("column label is empty")]
+        Empty,
+
+        /// Label contains a character other than an ascii letter.
+        #[error("column label contains `{0}`, expected only letters")]
+        InvalidCharacter(char),
+
+        /// Label's index is larger than the maximum [`IndexType`](crate::data::IndexType).
+        #[error("column label is too large")]
+        Overflow,
     }
 }
 
@@ -87,6 +131,16 @@ mod test {
         assert_eq!(index_to_col(701), "ZZ".to_string());
     }
 
+    #[test]
+    fn index_to_row_label() {
+        assert_eq!(index_to_row(0), "1".to_string());
+        assert_eq!(index_to_row(9), "10".to_string());
+        assert_eq!(
+            index_to_row(data::IndexType::MAX),
+            (u64::from(data::IndexType::MAX) + 1).to_string()
+        );
+    }
+
     #[test]
     fn col_label_to_index() {
         assert_eq!(col_to_index("A"), Some(0));
@@ -112,5 +166,98 @@ mod test {
         assert_eq!(col_to_index("yz"), Some(675));
         assert_eq!(col_to_index("za"), Some(676));
         assert_eq!(col_to_index("zz"), Some(701));
+
+        assert_eq!(col_to_index("AAA"), Some(702));
+        assert_eq!(col_to_index(""), None);
+        assert_eq!(col_to_index("A1"), None);
+        assert_eq!(col_to_index("Ä"), None);
+        assert_eq!(col_to_index("Z".repeat(64)), None);
+    }
+
+    #[test]
+    fn col_label_to_index_errors() {
+        assert_eq!(try_col_to_index("ZZZ"), Ok(18_277));
+        assert_eq!(try_col_to_index(""), Err(error::ColLabel::Empty));
+        assert_eq!(
+            try_col_to_index("A1"),
+            Err(error::ColLabel::InvalidCharacter('1'))
+        );
+        assert_eq!(
+            try_col_to_index("AÄ"),
+            Err(error::ColLabel::InvalidCharacter('Ä'))
+        );
+        assert_eq!(
+            try_col_to_index("Z".repeat(64)),
+            Err(error::ColLabel::Overflow)
+        );
+
+        let next = index_to_col(data::IndexType::MAX) + "A";
+        assert_eq!(try_col_to_index(next), Err(error::ColLabel::Overflow));
+    }
+
+    #[test]
+    fn col_label_bounds() {
+        let max = index_to_col(data::IndexType::MAX);
+        assert_eq!(col_to_index(&max), Some(data::IndexType::MAX));
+
+        // Next label after the maximum.
+        let mut next = max.into_bytes();
+        let mut idx = next.len();
+        loop {
+            if idx == 0 {
+                next.insert(0, b'A');
+                break;
+            }
+            idx -= 1;
+            if next[idx] == b'Z' {
+                next[idx] = b'A';
+            } else {
+                next[idx] += 1;
+                break;
+            }
+        }
+        let next = String::from_utf8(next).unwrap();
+        assert_eq!(col_to_index(&next), None);
+        assert_eq!(try_col_to_index(next), Err(error::ColLabel::Overflow));
+    }
+
+    #[test]
+    fn col_label_round_trip_exhaustive() {
+        let max = data::IndexType::MAX.min(u16::MAX.into());
+        let mut prev = None::<String>;
+        for idx in 0..=max {
+            let label = index_to_col(idx);
+            assert_eq!(col_to_index(&label), Some(idx), "{label}");
+            assert_eq!(col_to_index(label.to_ascii_lowercase()), Some(idx));
+            if let Some(prev) = prev {
+                assert!(
+                    (prev.len(), &prev) < (label.len(), &label),
+                    "{prev} < {label}"
+                );
+            }
+            prev = Some(label);
+        }
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn col_label_round_trip(idx: data::IndexType) {
+            let label = index_to_col(idx);
+            proptest::prop_assert!(label.bytes().all(|ch| ch.is_ascii_uppercase()));
+            proptest::prop_assert_eq!(col_to_index(&label), Some(idx));
+        }
+
+        #[test]
+        fn col_label_parse_any(input: String) {
+            if let Some(idx) = col_to_index(&input) {
+                proptest::prop_assert_eq!(index_to_col(idx), input.to_ascii_uppercase());
+            }
+        }
+
+        #[test]
+        fn row_label_round_trip(idx in 0..data::IndexType::MAX) {
+            let label = index_to_row(idx).parse::<data::IndexType>().unwrap();
+            proptest::prop_assert_eq!(row_to_index(label), Some(idx));
+        }
     }
 }
@@ -0,0 +1,14 @@
+//! Print the JSON schema of the commands and events
+//! passed between the desktop ui and backend.
+//!
+//! # Usage
+//! `cargo run -p hermes-desktop-lib --example schema --features schema > schema.json`
+use hermes_desktop_lib::ipc;
+
+fn main() {
+    let schema = ipc::schema();
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&schema).expect("schema is valid json")
+    );
+}
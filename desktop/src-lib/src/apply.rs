@@ -0,0 +1,201 @@
+//! Apply formulas to a dataset without the desktop app,
+//! e.g. to process files from another tool.
+//! Formulas are evaluated as they are in the app,
+//! in dependency order so formulas may reference the results of others.
+//!
+//! # Examples
+//! ```no_run
+//! use hermes_core::data::CellIndex;
+//! use hermes_desktop_lib::{self as hermes, apply::Formula};
+//!
+//! let formulas = [Formula::new(CellIndex::new(1u8, 2u8), "A2 * B2")];
+//! let changes = hermes::apply(&formulas, "data.csv", "results.csv").unwrap();
+//! ```
+use crate::formula;
+#[cfg(feature = "fs")]
+use crate::{audit, data, formula::error::WorkspaceOrder};
+use hermes_core as core;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+/// Formula whose value is written to a cell.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Formula {
+    pub cell: core::data::CellIndex,
+    pub value: String,
+
+    /// How the value is written if the cell is not empty.
+    #[serde(default)]
+    pub mode: formula::UpdateMode,
+}
+
+impl Formula {
+    pub fn new(cell: core::data::CellIndex, value: impl Into<String>) -> Self {
+        Self {
+            cell,
+            value: value.into(),
+            mode: formula::UpdateMode::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Options {
+    /// Options used to read the input file.
+    pub import: crate::data::ImportOptions,
+    pub eval: core::expr::Options,
+}
+
+/// Apply formulas to a csv file with the default options.
+/// See [`apply_with_options`].
+#[cfg(feature = "fs")]
+pub fn apply(
+    formulas: &[Formula],
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+) -> Result<Vec<audit::CellChange>, error::Apply> {
+    apply_with_options(formulas, input, output, &Options::default())
+}
+
+/// Apply formulas to a csv file.
+/// The input file is read, the formulas evaluated against it,
+/// and the result saved to the output file, which may be the input file.
+/// Nothing is written if any formula fails.
+///
+/// # Returns
+/// Change made to the cell of each formula, in evaluation order.
+#[cfg(feature = "fs")]
+pub fn apply_with_options(
+    formulas: &[Formula],
+    input: impl AsRef<Path>,
+    output: impl AsRef<Path>,
+    options: &Options,
+) -> Result<Vec<audit::CellChange>, error::Apply> {
+    let input = input.as_ref();
+    let mut csv = match data::read_dataset(input, options.import.clone())? {
+        data::Dataset::Csv(csv) => csv,
+        data::Dataset::Workbook(_) => return Err(WorkspaceOrder::Unsupported.into()),
+    };
+
+    let name = input
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let cell = |cell: &core::data::CellIndex| core::data::CellPath {
+        sheet: 0,
+        row: cell.row(),
+        col: cell.col(),
+    };
+    let dependencies = formula::Dependencies::new(
+        formulas
+            .iter()
+            .map(|formula| (cell(&formula.cell), formula.value.as_str())),
+        |sheet, origin| sheet.resolve(origin, [&name]),
+    );
+    let order = dependencies.all();
+
+    let evaluate = formula::Evaluate {
+        sheets: vec![formula::EvaluateSheet {
+            name: name.clone(),
+            cells: csv.sheet.cells().clone(),
+        }],
+        formulas: order
+            .iter()
+            .map(|idx| {
+                (
+                    dependencies.cell(*idx).clone(),
+                    formulas[*idx].value.clone(),
+                )
+            })
+            .collect(),
+        options: options.eval,
+    };
+
+    let mut changes = Vec::with_capacity(formulas.len());
+    for (idx, traced) in order.into_iter().zip(evaluate.run_traced()) {
+        let formula = &formulas[idx];
+        let value = traced.result.map_err(|error| error::Apply::Formula {
+            cell: formula.cell.clone(),
+            error,
+        })?;
+
+        let update = formula::UpdateCsv {
+            row: formula.cell.row(),
+            col: formula.cell.col(),
+            value,
+            formula: Some(formula.value.clone()),
+            provenance: Some(audit::Provenance::now(&formula.value, traced.inputs)),
+            mode: formula.mode,
+        };
+        changes.push(update.apply(&mut csv.sheet)?);
+    }
+
+    csv.save(output)?;
+    Ok(changes)
+}
+
+pub mod error {
+    use crate::{data, formula};
+    use hermes_core as core;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone, derive_more::From)]
+    pub enum Apply {
+        #[error("could not read input: {0}")]
+        Load(data::error::Load),
+
+        #[error("formula of cell {cell:?} failed: {error}")]
+        #[from(ignore)]
+        Formula {
+            cell: core::data::CellIndex,
+            error: core::expr::Error,
+        },
+
+        #[error("could not apply formulas: {0:?}")]
+        Order(formula::error::WorkspaceOrder),
+
+        #[error("could not save output: {0}")]
+        Save(data::error::SaveCsv),
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn apply_in_dependency_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("data.csv");
+        let output = dir.path().join("results.csv");
+        std::fs::write(&input, "a,b,c,d\n2,3,,\n").unwrap();
+
+        let formulas = [
+            Formula::new(core::data::CellIndex::new(1u8, 3u8), "C2 + 1"),
+            Formula::new(core::data::CellIndex::new(1u8, 2u8), "A2 * B2"),
+        ];
+        let changes = apply(&formulas, &input, &output).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].new, core::expr::Value::Int(6));
+        assert_eq!(changes[1].new, core::expr::Value::Int(7));
+        assert!(
+            changes
+                .iter()
+                .all(|change| change.old == core::expr::Value::Empty)
+        );
+
+        let content = std::fs::read_to_string(&output).unwrap();
+        assert_eq!(content.lines().collect::<Vec<_>>(), ["a,b,c,d", "2,3,6,7"]);
+        assert_eq!(std::fs::read_to_string(&input).unwrap(), "a,b,c,d\n2,3,,\n");
+
+        let formulas = [Formula::new(
+            core::data::CellIndex::new(1u8, 2u8),
+            "'a' + 1",
+        )];
+        assert!(matches!(
+            apply(&formulas, &input, &output),
+            Err(error::Apply::Formula { .. })
+        ));
+    }
+}
@@ -0,0 +1,202 @@
+//! Record of the changes made to files by workspace orders.
+//! The log is stored in the project root as [JSON lines](https://jsonlines.org),
+//! one [`Entry`] per executed order, and is only ever appended to.
+use hermes_core as core;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Name of the audit log file, relative to the project root.
+pub const AUDIT_FILE: &str = ".hermes-audit.jsonl";
+
+/// An executed order.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Entry {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+
+    /// Path of the file written to, relative to the project root.
+    pub path: PathBuf,
+    pub kind: OrderKind,
+    pub changes: Vec<CellChange>,
+}
+
+impl Entry {
+    /// Create an entry for an order executed now.
+    pub fn now(path: PathBuf, kind: OrderKind, changes: Vec<CellChange>) -> Self {
+        Self {
+            timestamp: chrono::Utc::now(),
+            path,
+            kind,
+            changes,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum OrderKind {
+    /// A file was created, replacing any existing file.
+    Create,
+
+    /// Cells of an existing file were set.
+    Update,
+}
+
+/// Change to the value of a single cell.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CellChange {
+    /// Sheet index for workbooks.
+    /// `None` for csv files.
+    #[serde(default)]
+    pub sheet: Option<core::data::IndexType>,
+
+    pub cell: core::data::CellIndex,
+    pub old: core::expr::Value,
+    pub new: core::expr::Value,
+
+    /// Formula the new value was computed from.
+    #[serde(default)]
+    pub formula: Option<String>,
+
+    /// How the new value was computed.
+    #[serde(default)]
+    pub provenance: Option<Provenance>,
+
+    #[serde(default)]
+    pub outcome: CellOutcome,
+}
+
+/// How a formula's value was computed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Provenance {
+    pub computed: chrono::DateTime<chrono::Utc>,
+
+    /// Formula the value was computed from.
+    pub formula: String,
+
+    /// [Version](core::expr::VERSION) of the expression language the formula was evaluated with.
+    pub expr_version: u32,
+
+    /// Cells read to compute the value, as referenced by the formula.
+    pub inputs: Vec<core::data::CellRef>,
+}
+
+impl Provenance {
+    /// Create the provenance of a value computed now.
+    pub fn now(formula: impl Into<String>, inputs: Vec<core::data::CellRef>) -> Self {
+        Self {
+            computed: chrono::Utc::now(),
+            formula: formula.into(),
+            expr_version: core::expr::VERSION,
+            inputs,
+        }
+    }
+}
+
+/// Result of applying a change to a cell.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum CellOutcome {
+    /// The new value was written.
+    #[default]
+    Written,
+
+    /// The cell already contained a value, so the new value was not written.
+    Skipped,
+}
+
+/// Filter for audit log entries.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct Query {
+    /// Only include entries for this file, relative to the project root.
+    pub path: Option<PathBuf>,
+
+    /// Maximum number of entries to return.
+    pub limit: Option<usize>,
+}
+
+impl Query {
+    /// # Returns
+    /// Matching entries, most recent first.
+    pub fn apply(&self, entries: impl DoubleEndedIterator<Item = Entry>) -> Vec<Entry> {
+        let entries = entries.rev().filter(|entry| self.matches(&entry.path));
+        match self.limit {
+            Some(limit) => entries.take(limit).collect(),
+            None => entries.collect(),
+        }
+    }
+
+    fn matches(&self, path: impl AsRef<Path>) -> bool {
+        self.path
+            .as_ref()
+            .map(|query| query == path.as_ref())
+            .unwrap_or(true)
+    }
+}
+
+pub mod error {
+    use serde::{Deserialize, Serialize};
+    use std::io;
+
+    #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    pub enum Audit {
+        #[error("{0}")]
+        Io(
+            #[serde(with = "io_error_serde::ErrorKind")]
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
+            io::ErrorKind,
+        ),
+
+        /// A line of the log could not be parsed.
+        #[error("invalid entry on line {line}")]
+        Invalid { line: usize },
+    }
+
+    impl From<io::Error> for Audit {
+        fn from(value: io::Error) -> Self {
+            Self::Io(value.kind())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn query_apply() {
+        let entry = |path: &str, idx: u8| Entry {
+            timestamp: chrono::DateTime::UNIX_EPOCH,
+            path: PathBuf::from(path),
+            kind: OrderKind::Update,
+            changes: vec![CellChange {
+                sheet: None,
+                cell: core::data::CellIndex::new(idx, 0u8),
+                old: core::expr::Value::Empty,
+                new: core::expr::Value::Int(idx.into()),
+                formula: Some("1".to_string()),
+                provenance: None,
+                outcome: CellOutcome::Written,
+            }],
+        };
+        let entries = vec![entry("a.csv", 0), entry("b.csv", 1), entry("a.csv", 2)];
+
+        let all = Query::default().apply(entries.clone().into_iter());
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0], entries[2]);
+
+        let query = Query {
+            path: Some(PathBuf::from("a.csv")),
+            limit: Some(1),
+        };
+        assert_eq!(
+            query.apply(entries.clone().into_iter()),
+            vec![entries[2].clone()]
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Apply a template to many files in a single run.
+use crate::{audit, data, formula, template};
+use hermes_core as core;
+use serde::{Deserialize, Serialize};
+use std::{cell::RefCell, path::PathBuf};
+
+#[cfg(feature = "fs")]
+use crate::fs::FileKind;
+#[cfg(feature = "fs")]
+use std::{fs, path::Path};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Batch {
+    /// Glob matching the files to process, relative to the project root.
+    /// A folder matches every file it contains.
+    pub pattern: String,
+    pub template: template::Template,
+
+    /// Options files are imported with.
+    #[serde(default)]
+    pub options: data::ImportOptions,
+
+    /// How formulas evaluate references to empty cells.
+    #[serde(default)]
+    pub empty_cells: core::expr::EmptyCells,
+
+    /// What formulas compare empty values as.
+    #[serde(default)]
+    pub empty_comparison: core::expr::EmptyComparison,
+
+    /// How formulas evaluate integer arithmetic that overflows.
+    #[serde(default)]
+    pub overflow: core::expr::Overflow,
+}
+
+impl Batch {
+    /// Evaluate the template's formulas against a csv.
+    /// Formulas are evaluated in order, so later formulas may reference the results of earlier ones.
+    ///
+    /// # Returns
+    /// Updates for the template's cells.
+    /// Cells already containing data are skipped.
+    pub fn updates_csv(&self, csv: &data::Csv) -> Result<Vec<formula::UpdateCsv>, error::File> {
+        self.template.check(&template::Shape::Csv {
+            cols: csv.sheet.size().1,
+        })?;
+
+        let mut sheet = csv.sheet.clone();
+        let mut updates = Vec::with_capacity(self.template.formulas.len());
+        for formula in self.template.formulas.iter() {
+            if sheet.contains_key(&formula.cell) {
+                continue;
+            }
+
+            let origin = core::data::CellPath {
+                sheet: 0,
+                row: formula.cell.row(),
+                col: formula.cell.col(),
+            };
+            let inputs = RefCell::new(vec![]);
+            let ctx = core::expr::WithOptions {
+                ctx: core::expr::Recording {
+                    ctx: &sheet,
+                    reads: &inputs,
+                },
+                options: core::expr::Options {
+                    empty_cells: self.empty_cells,
+                    empty_comparison: self.empty_comparison,
+                    overflow: self.overflow,
+                    ..Default::default()
+                },
+            };
+            let value = core::expr::eval(&formula.value, ctx, &origin).map_err(|error| {
+                error::File::Evaluate {
+                    cell: formula.cell.clone(),
+                    error,
+                }
+            })?;
+
+            sheet.set(formula.cell.clone(), value.clone());
+            updates.push(formula::UpdateCsv {
+                row: formula.cell.row(),
+                col: formula.cell.col(),
+                value,
+                formula: Some(formula.value.clone()),
+                provenance: Some(audit::Provenance::now(&formula.value, inputs.into_inner())),
+                mode: formula::UpdateMode::InsertOnly,
+            });
+        }
+
+        Ok(updates)
+    }
+}
+
+#[cfg(feature = "fs")]
+impl Batch {
+    /// # Returns
+    /// Data files matching the pattern, relative to `root`, in order.
+    /// Ignored files are excluded.
+    pub fn files(&self, root: impl AsRef<Path>) -> Result<Vec<PathBuf>, error::Files> {
+        let root = root.as_ref();
+        let pattern = self.pattern.trim().trim_matches('/');
+        let pattern = if pattern.is_empty() {
+            "**".to_string()
+        } else if root.join(pattern).is_dir() {
+            format!("{pattern}/**")
+        } else {
+            pattern.to_string()
+        };
+
+        let glob = globset::GlobBuilder::new(&pattern)
+            .literal_separator(true)
+            .build()
+            .map_err(|err| error::Files::InvalidPattern(err.to_string()))?
+            .compile_matcher();
+        let matcher = crate::fs::ignore::matcher(root);
+
+        let mut files = vec![];
+        let mut dirs = vec![root.to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in fs::read_dir(&dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                let file_type = entry.file_type()?;
+                if crate::fs::ignore::is_ignored(&matcher, &path, file_type.is_dir()) {
+                    continue;
+                }
+
+                if file_type.is_dir() {
+                    dirs.push(path);
+                } else if file_type.is_file() && FileKind::from_path(&path) != FileKind::Other {
+                    let rel_path = path
+                        .strip_prefix(root)
+                        .expect("path should be within root")
+                        .to_path_buf();
+                    if glob.is_match(&rel_path) {
+                        files.push(rel_path);
+                    }
+                }
+            }
+        }
+
+        files.sort();
+        Ok(files)
+    }
+
+    /// Create the order updating a file.
+    ///
+    /// # Arguments
+    /// + `path`: Path of the file, relative to `root`.
+    pub fn order(
+        &self,
+        root: impl AsRef<Path>,
+        path: impl AsRef<Path>,
+    ) -> Result<formula::WorkspaceOrder, error::File> {
+        let path = root.as_ref().join(path);
+        if FileKind::from_path(&path) != FileKind::Csv {
+            return Err(error::File::Unsupported);
+        }
+
+        let csv = data::Csv::load_from_path_with_options(&path, self.options.clone())?;
+        let updates = self.updates_csv(&csv)?;
+        Ok(formula::WorkspaceOrder::Update(formula::Update {
+            path,
+            updates: updates.into(),
+            options: self.options.clone(),
+        }))
+    }
+}
+
+/// Order for a single file of a batch.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FileOrder {
+    /// Path of the file, relative to the project root.
+    pub path: PathBuf,
+    pub order: Result<formula::WorkspaceOrder, error::File>,
+}
+
+pub mod error {
+    use crate::{data, template};
+    use hermes_core as core;
+    use serde::{Deserialize, Serialize};
+    use std::io;
+
+    #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    pub enum Files {
+        #[error("invalid pattern: {0}")]
+        InvalidPattern(String),
+
+        #[error("{0}")]
+        Io(
+            #[serde(with = "io_error_serde::ErrorKind")]
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
+            io::ErrorKind,
+        ),
+    }
+
+    impl From<io::Error> for Files {
+        fn from(value: io::Error) -> Self {
+            Self::Io(value.kind())
+        }
+    }
+
+    /// A file of a batch could not be processed.
+    #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone, derive_more::From)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    pub enum File {
+        #[error("only csv files are supported")]
+        Unsupported,
+
+        /// The file is protected from modification.
+        #[error("file is protected")]
+        Protected,
+
+        #[error("could not load file: {0}")]
+        Load(data::error::LoadCsv),
+
+        #[error("{0}")]
+        Incompatible(template::Incompatible),
+
+        #[error("could not evaluate formula in {cell}: {error}")]
+        Evaluate {
+            cell: core::data::CellIndex,
+            error: core::expr::Error,
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn batch_updates_csv() {
+        let mut cells = data::CellMap::new();
+        cells.insert((0u8, 0u8).into(), core::expr::Value::Int(1));
+        cells.insert((0u8, 1u8).into(), core::expr::Value::Int(2));
+        let csv = data::Csv {
+            sheet: data::Spreadsheet::from_cells(cells),
+            columns: Default::default(),
+            options: Default::default(),
+        };
+
+        let formula = |col: u8, value: &str| template::TemplateFormula {
+            sheet: None,
+            cell: core::data::CellIndex::new(0u8, col),
+            value: value.to_string(),
+        };
+        let batch = Batch {
+            pattern: "**/*.csv".to_string(),
+            template: template::Template {
+                name: "sum".to_string(),
+                shape: template::Shape::Csv { cols: 2 },
+                formulas: vec![
+                    formula(1, "A1"),
+                    formula(2, "A1 + B1"),
+                    formula(3, "C1 + 1"),
+                ],
+                expr_version: core::expr::VERSION,
+            },
+            options: Default::default(),
+            empty_cells: Default::default(),
+            empty_comparison: Default::default(),
+            overflow: Default::default(),
+        };
+
+        let updates = batch.updates_csv(&csv).unwrap();
+        assert_eq!(updates.len(), 2);
+        assert_eq!((updates[0].row, updates[0].col), (0, 2));
+        assert_eq!(updates[0].value, core::expr::Value::Int(3));
+        assert_eq!((updates[1].row, updates[1].col), (0, 3));
+        assert_eq!(updates[1].value, core::expr::Value::Int(4));
+
+        let batch = Batch {
+            template: template::Template {
+                shape: template::Shape::Csv { cols: 3 },
+                ..batch.template
+            },
+            ..batch
+        };
+        assert!(matches!(
+            batch.updates_csv(&csv),
+            Err(error::File::Incompatible(_))
+        ));
+    }
+}
@@ -0,0 +1,549 @@
+//! Dataset level cleaning operations.
+//! Operations act on whole rows or columns of a sheet rather than individual cells.
+use crate::{
+    data::{self, CellMap, Data},
+    settings::DecimalSeparator,
+};
+use hermes_core as core;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Operation {
+    /// Remove rows identical to an earlier row.
+    DropDuplicates {
+        /// Columns compared. All columns if empty.
+        columns: Vec<core::data::IndexType>,
+    },
+
+    /// Remove rows with a missing value.
+    DropMissing {
+        /// Columns checked. All columns if empty.
+        columns: Vec<core::data::IndexType>,
+    },
+
+    /// Replace missing values.
+    FillMissing {
+        /// Columns filled. All columns if empty.
+        columns: Vec<core::data::IndexType>,
+        fill: Fill,
+    },
+
+    /// Convert values to a type.
+    /// Values that can not be converted are left unchanged and reported as [failures](Failure).
+    Convert {
+        /// Columns converted. All columns if empty.
+        columns: Vec<core::data::IndexType>,
+        to: Conversion,
+    },
+
+    /// Add columns derived from the values of a column after the last column.
+    /// An invalid method adds no columns.
+    Extract {
+        col: core::data::IndexType,
+        method: data::ExtractMethod,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Fill {
+    /// Fill with a fixed value.
+    Constant(Data),
+
+    /// Fill with the closest preceding value in the column.
+    /// Values missing at the start of the column remain missing.
+    Forward,
+
+    /// Fill with the mean of the column's numeric values.
+    /// Columns without numeric values remain missing.
+    Mean,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Conversion {
+    /// Parse text as numbers, e.g. `"1.5"` becomes `1.5`.
+    Number { decimal: DecimalSeparator },
+
+    /// Parse text as dates using a [`chrono` format](chrono::format::strftime), e.g. `%Y-%m-%d`.
+    /// Dates without a time are taken at midnight, and dates without a time zone as UTC.
+    Date { format: String },
+
+    /// Convert values to text.
+    Text,
+}
+
+impl Conversion {
+    /// # Returns
+    /// The converted value, or `None` if the value can not be converted.
+    /// Missing values remain missing.
+    pub fn convert(&self, value: &Data) -> Option<Data> {
+        if is_missing(value) {
+            return Some(value.clone());
+        }
+
+        match self {
+            Self::Number { decimal } => match value {
+                Data::Int(_) | Data::Float(_) => Some(value.clone()),
+                Data::String(text) => {
+                    let text = text.trim();
+                    let text = match decimal {
+                        DecimalSeparator::Point => text.to_string(),
+                        DecimalSeparator::Comma if text.contains('.') => return None,
+                        DecimalSeparator::Comma => text.replace(',', "."),
+                    };
+                    if let Ok(value) = text.parse::<i64>() {
+                        Some(Data::Int(value))
+                    } else {
+                        text.parse::<f64>().ok().map(Data::Float)
+                    }
+                }
+                _ => None,
+            },
+
+            Self::Date { format } => match value {
+                Data::DateTime(_) => Some(value.clone()),
+                Data::String(text) => parse_date(text.trim(), format).map(Data::DateTime),
+                _ => None,
+            },
+
+            Self::Text => Some(Data::String(value.to_string())),
+        }
+    }
+}
+
+fn parse_date(text: &str, format: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+    if let Ok(date) = DateTime::parse_from_str(text, format) {
+        Some(date.with_timezone(&Utc))
+    } else if let Ok(date) = NaiveDateTime::parse_from_str(text, format) {
+        Some(date.and_utc())
+    } else {
+        NaiveDate::parse_from_str(text, format)
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|date| date.and_utc())
+    }
+}
+
+/// Value that could not be converted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Failure {
+    /// Cell of the value in the original sheet.
+    pub cell: core::data::CellIndex,
+    pub value: Data,
+    pub to: Conversion,
+}
+
+/// Sheet resulting from cleaning operations.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cleaned {
+    pub cells: CellMap,
+    pub failures: Vec<Failure>,
+}
+
+/// Apply operations to the cells of a sheet, in order.
+///
+/// # Arguments
+/// + `header`: Whether the first row holds column headers.
+///     The header row is never removed, filled, or converted.
+///
+/// # Returns
+/// Cells of the resulting sheet.
+pub fn apply(cells: &CellMap, header: bool, operations: &[Operation]) -> CellMap {
+    clean(cells, header, operations).cells
+}
+
+/// Apply operations to the cells of a sheet, in order,
+/// collecting the values that could not be converted.
+///
+/// # Arguments
+/// + `header`: Whether the first row holds column headers.
+///     The header row is never removed, filled, or converted.
+pub fn clean(cells: &CellMap, header: bool, operations: &[Operation]) -> Cleaned {
+    let (rows, mut cols) = cells.keys().fold((0, 0), |(rows, cols), idx| {
+        (rows.max(idx.row() + 1), cols.max(idx.col() + 1))
+    });
+    // Rows are kept with their index in the original sheet to report failures.
+    let mut rows = (0..rows)
+        .map(|row| {
+            let values = (0..cols)
+                .map(|col| {
+                    cells
+                        .get(&core::data::CellIndex::new(row, col))
+                        .cloned()
+                        .unwrap_or(Data::Empty)
+                })
+                .collect::<Vec<_>>();
+            (row, values)
+        })
+        .collect::<Vec<_>>();
+
+    let mut header_row = if header && !rows.is_empty() {
+        Some(rows.remove(0))
+    } else {
+        None
+    };
+
+    let mut failures = vec![];
+    for operation in operations {
+        match operation {
+            Operation::DropDuplicates { columns } => {
+                let columns = selected_columns(columns, cols);
+                let mut seen = HashSet::new();
+                rows.retain(|(_, row)| {
+                    let key = columns
+                        .iter()
+                        .map(|col| row[*col].to_string())
+                        .collect::<Vec<_>>();
+                    seen.insert(key)
+                });
+            }
+
+            Operation::DropMissing { columns } => {
+                let columns = selected_columns(columns, cols);
+                rows.retain(|(_, row)| !columns.iter().any(|col| is_missing(&row[*col])));
+            }
+
+            Operation::FillMissing { columns, fill } => {
+                for col in selected_columns(columns, cols) {
+                    fill_column(&mut rows, col, fill);
+                }
+            }
+
+            Operation::Convert { columns, to } => {
+                for col in selected_columns(columns, cols) {
+                    for (row, values) in rows.iter_mut() {
+                        match to.convert(&values[col]) {
+                            Some(value) => values[col] = value,
+                            None => failures.push(Failure {
+                                cell: core::data::CellIndex::new(
+                                    *row,
+                                    col as core::data::IndexType,
+                                ),
+                                value: values[col].clone(),
+                                to: to.clone(),
+                            }),
+                        }
+                    }
+                }
+            }
+
+            Operation::Extract { col, method } => {
+                if *col >= cols {
+                    continue;
+                }
+
+                let extract = data::Extract {
+                    col: *col,
+                    method: method.clone(),
+                    header,
+                };
+                let source_header = header_row
+                    .as_ref()
+                    .map(|(_, values)| values[*col as usize].to_string())
+                    .unwrap_or_default();
+                let Ok(columns) = extract.extract(
+                    &source_header,
+                    rows.iter().map(|(_, values)| values[*col as usize].clone()),
+                ) else {
+                    continue;
+                };
+
+                if let Some((_, values)) = header_row.as_mut() {
+                    values.extend(
+                        columns
+                            .iter()
+                            .map(|column| Data::String(column.header.clone())),
+                    );
+                }
+                for (idx, (_, values)) in rows.iter_mut().enumerate() {
+                    values.extend(columns.iter().map(|column| column.values[idx].clone()));
+                }
+                cols += columns.len() as core::data::IndexType;
+            }
+        }
+    }
+
+    failures.sort_by(|a, b| a.cell.cmp(&b.cell));
+    let cells = header_row
+        .into_iter()
+        .chain(rows)
+        .map(|(_, values)| values)
+        .enumerate()
+        .flat_map(|(row, values)| {
+            values.into_iter().enumerate().map(move |(col, value)| {
+                (
+                    core::data::CellIndex::new(
+                        row as core::data::IndexType,
+                        col as core::data::IndexType,
+                    ),
+                    value,
+                )
+            })
+        })
+        .collect();
+
+    Cleaned { cells, failures }
+}
+
+fn fill_column(rows: &mut [(core::data::IndexType, Vec<Data>)], col: usize, fill: &Fill) {
+    match fill {
+        Fill::Constant(value) => {
+            for (_, row) in rows.iter_mut() {
+                if is_missing(&row[col]) {
+                    row[col] = value.clone();
+                }
+            }
+        }
+
+        Fill::Forward => {
+            let mut previous: Option<Data> = None;
+            for (_, row) in rows.iter_mut() {
+                if is_missing(&row[col]) {
+                    if let Some(previous) = previous.as_ref() {
+                        row[col] = previous.clone();
+                    }
+                } else {
+                    previous = Some(row[col].clone());
+                }
+            }
+        }
+
+        Fill::Mean => {
+            let (sum, count) = rows
+                .iter()
+                .filter_map(|(_, row)| match &row[col] {
+                    Data::Int(value) => Some(*value as f64),
+                    Data::Float(value) if value.is_finite() => Some(*value),
+                    _ => None,
+                })
+                .fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+            if count == 0 {
+                return;
+            }
+
+            let mean = Data::Float(sum / count as f64);
+            for (_, row) in rows.iter_mut() {
+                if is_missing(&row[col]) {
+                    row[col] = mean.clone();
+                }
+            }
+        }
+    }
+}
+
+/// # Returns
+/// Indices of the selected columns within the sheet, or all columns if none are selected.
+fn selected_columns(columns: &[core::data::IndexType], cols: core::data::IndexType) -> Vec<usize> {
+    if columns.is_empty() {
+        (0..cols as usize).collect()
+    } else {
+        columns
+            .iter()
+            .filter(|col| **col < cols)
+            .map(|col| *col as usize)
+            .collect()
+    }
+}
+
+fn is_missing(value: &Data) -> bool {
+    match value {
+        Data::Empty => true,
+        Data::String(value) => value.trim().is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cells(rows: &[&[Data]]) -> CellMap {
+        let mut cells = CellMap::new();
+        for (row, values) in rows.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                cells.insert(
+                    core::data::CellIndex::new(row as u16, col as u16),
+                    value.clone(),
+                );
+            }
+        }
+        cells
+    }
+
+    #[test]
+    fn clean_apply() {
+        let text = |value: &str| Data::String(value.to_string());
+        let input = cells(&[
+            &[text("a"), text("b")],
+            &[Data::Int(1), Data::Int(2)],
+            &[Data::Int(1), Data::Int(2)],
+            &[text(""), Data::Int(4)],
+            &[Data::Int(3), Data::Empty],
+        ]);
+
+        let output = apply(
+            &input,
+            true,
+            &[Operation::DropDuplicates { columns: vec![] }],
+        );
+        assert_eq!(
+            output,
+            cells(&[
+                &[text("a"), text("b")],
+                &[Data::Int(1), Data::Int(2)],
+                &[text(""), Data::Int(4)],
+                &[Data::Int(3), Data::Empty],
+            ])
+        );
+
+        let output = apply(&input, true, &[Operation::DropMissing { columns: vec![1] }]);
+        assert_eq!(output.len(), 8);
+
+        let output = apply(
+            &input,
+            true,
+            &[
+                Operation::FillMissing {
+                    columns: vec![0],
+                    fill: Fill::Forward,
+                },
+                Operation::FillMissing {
+                    columns: vec![1],
+                    fill: Fill::Mean,
+                },
+            ],
+        );
+        let value = |row: u16, col: u16| output.get(&core::data::CellIndex::new(row, col));
+        assert_eq!(value(3, 0), Some(&Data::Int(1)));
+        assert_eq!(value(4, 1), Some(&Data::Float(8.0 / 3.0)));
+        assert_eq!(value(0, 0), Some(&text("a")));
+
+        let output = apply(
+            &input,
+            false,
+            &[Operation::FillMissing {
+                columns: vec![],
+                fill: Fill::Constant(Data::Int(0)),
+            }],
+        );
+        assert_eq!(
+            output.get(&core::data::CellIndex::new(3u16, 0u16)),
+            Some(&Data::Int(0))
+        );
+    }
+
+    #[test]
+    fn clean_convert() {
+        let text = |value: &str| Data::String(value.to_string());
+        let input = cells(&[
+            &[text("value"), text("date")],
+            &[text(" 1 "), text("2024-01-31")],
+            &[text("1,5"), text("31/01/2024")],
+            &[text("abc"), Data::Empty],
+        ]);
+
+        let cleaned = clean(
+            &input,
+            true,
+            &[
+                Operation::Convert {
+                    columns: vec![0],
+                    to: Conversion::Number {
+                        decimal: DecimalSeparator::Comma,
+                    },
+                },
+                Operation::Convert {
+                    columns: vec![1],
+                    to: Conversion::Date {
+                        format: "%Y-%m-%d".to_string(),
+                    },
+                },
+            ],
+        );
+        let value = |row: u16, col: u16| cleaned.cells.get(&core::data::CellIndex::new(row, col));
+        assert_eq!(value(0, 0), Some(&text("value")));
+        assert_eq!(value(1, 0), Some(&Data::Int(1)));
+        assert_eq!(value(2, 0), Some(&Data::Float(1.5)));
+        assert_eq!(value(3, 0), Some(&text("abc")));
+        assert!(matches!(value(1, 1), Some(Data::DateTime(_))));
+        assert_eq!(value(3, 1), Some(&Data::Empty));
+
+        let failed = cleaned
+            .failures
+            .iter()
+            .map(|failure| (failure.cell.row(), failure.cell.col()))
+            .collect::<Vec<_>>();
+        assert_eq!(failed, [(2, 1), (3, 0)]);
+    }
+
+    #[test]
+    fn clean_convert_reports_original_row() {
+        let text = |value: &str| Data::String(value.to_string());
+        let input = cells(&[&[Data::Empty], &[text("x")]]);
+
+        let cleaned = clean(
+            &input,
+            false,
+            &[
+                Operation::DropMissing { columns: vec![] },
+                Operation::Convert {
+                    columns: vec![],
+                    to: Conversion::Number {
+                        decimal: DecimalSeparator::Point,
+                    },
+                },
+            ],
+        );
+        assert_eq!(cleaned.failures.len(), 1);
+        assert_eq!(
+            cleaned.failures[0].cell,
+            core::data::CellIndex::new(1u16, 0u16)
+        );
+        assert_eq!(
+            cleaned.cells.get(&core::data::CellIndex::new(0u16, 0u16)),
+            Some(&text("x"))
+        );
+    }
+
+    #[test]
+    fn clean_extract() {
+        let text = |value: &str| Data::String(value.to_string());
+        let input = cells(&[
+            &[text("sample")],
+            &[text("s1_t0")],
+            &[text("s2_t12")],
+            &[text("s3")],
+        ]);
+
+        let cleaned = apply(
+            &input,
+            true,
+            &[
+                Operation::Extract {
+                    col: 0,
+                    method: data::ExtractMethod::Split {
+                        delimiter: "_".to_string(),
+                    },
+                },
+                Operation::Extract {
+                    col: 2,
+                    method: data::ExtractMethod::Regex(r"t(?<hours>\d+)".to_string()),
+                },
+            ],
+        );
+        let value = |row: u16, col: u16| cleaned.get(&core::data::CellIndex::new(row, col));
+        assert_eq!(value(0, 1), Some(&text("sample_1")));
+        assert_eq!(value(0, 2), Some(&text("sample_2")));
+        assert_eq!(value(0, 3), Some(&text("hours")));
+        assert_eq!(value(1, 1), Some(&text("s1")));
+        assert_eq!(value(2, 3), Some(&Data::Int(12)));
+        assert_eq!(value(3, 2), Some(&Data::Empty));
+        assert_eq!(value(3, 3), Some(&Data::Empty));
+    }
+}
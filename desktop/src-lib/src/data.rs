@@ -1,18 +1,71 @@
 use hermes_core as core;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::atomic::{AtomicU64, Ordering},
+};
 
 #[cfg(feature = "fs")]
-use std::{fs, path::Path};
+use std::{fs, io, path::Path, sync::atomic::AtomicBool};
 
+/// Cell data.
+/// Owned by hermes so readers' types do not leak past loading,
+/// see [`Workbook::load_from_path`].
 pub type Data = core::expr::Value;
 pub type CellMap = BTreeMap<core::data::CellIndex, Data>;
+pub type CellErrorMap = BTreeMap<core::data::CellIndex, CellError>;
+
+/// Error stored in a cell of a workbook, e.g. `#DIV/0!`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum CellError {
+    Div0,
+    NotAvailable,
+    Name,
+    Null,
+    Num,
+    Ref,
+    Value,
+    GettingData,
+}
+
+impl CellError {
+    /// # Returns
+    /// Spreadsheet style error code of the error.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Div0 => "#DIV/0!",
+            Self::NotAvailable => "#N/A",
+            Self::Name => "#NAME?",
+            Self::Null => "#NULL!",
+            Self::Num => "#NUM!",
+            Self::Ref => "#REF!",
+            Self::Value => "#VALUE!",
+            Self::GettingData => "#DATA!",
+        }
+    }
+}
+
+impl From<CellError> for core::expr::Error {
+    fn from(value: CellError) -> Self {
+        match value {
+            CellError::Div0 => Self::Div0,
+            CellError::Num => Self::InvalidNumber,
+            _ => Self::InvalidOperation(format!("cell contains {}", value.code())),
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, derive_more::Deref)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Spreadsheet {
     #[deref]
     cells: CellMap,
 
+    /// Cells containing an error instead of data.
+    #[serde(default)]
+    errors: CellErrorMap,
+
     /// Number of (rows, cols).
     /// Each is the max index value of their respective value contained.
     size: (core::data::IndexType, core::data::IndexType),
@@ -40,7 +93,11 @@ impl Spreadsheet {
             (max_row + 1, max_col + 1)
         };
 
-        Self { cells, size }
+        Self {
+            cells,
+            errors: CellErrorMap::new(),
+            size,
+        }
     }
 
     /// Number of (rows, cols).
@@ -52,6 +109,10 @@ impl Spreadsheet {
         &self.cells
     }
 
+    pub fn errors(&self) -> &CellErrorMap {
+        &self.errors
+    }
+
     pub fn is_empty(&self) -> bool {
         (0, 0) == self.size
     }
@@ -65,16 +126,19 @@ impl Spreadsheet {
     /// Sets the value of a cell.
     /// If a value already existed in the cell it is overwritten.
     pub fn set(&mut self, idx: core::data::CellIndex, value: Data) {
-        if idx.row() >= self.size.0 {
-            self.size.0 = idx.row() + 1;
-        }
-        if idx.col() >= self.size.1 {
-            self.size.1 = idx.col() + 1;
-        }
-
+        self.grow_to(&idx);
+        self.errors.remove(&idx);
         self.cells.insert(idx, value);
     }
 
+    /// Sets a cell to contain an error.
+    /// If a value already existed in the cell it is removed.
+    pub fn set_error(&mut self, idx: core::data::CellIndex, error: CellError) {
+        self.grow_to(&idx);
+        self.cells.remove(&idx);
+        self.errors.insert(idx, error);
+    }
+
     /// Inserts a value into a cell.
     /// If a value already exists at that location the insert fails.
     pub fn insert(
@@ -82,19 +146,51 @@ impl Spreadsheet {
         idx: core::data::CellIndex,
         value: Data,
     ) -> Result<(), error::CellNotEmpty> {
-        if self.cells.contains_key(&idx) {
+        if self.cells.contains_key(&idx) || self.errors.contains_key(&idx) {
             return Err(error::CellNotEmpty);
         }
 
+        self.grow_to(&idx);
+        self.cells.insert(idx, value);
+        Ok(())
+    }
+
+    /// Grow the size of the sheet to contain the cell.
+    fn grow_to(&mut self, idx: &core::data::CellIndex) {
         if idx.row() >= self.size.0 {
             self.size.0 = idx.row() + 1;
         }
         if idx.col() >= self.size.1 {
             self.size.1 = idx.col() + 1;
         }
+    }
+}
 
-        self.cells.insert(idx, value);
-        Ok(())
+impl core::expr::Context for &Spreadsheet {
+    /// The spreadsheet is unnamed, so it can only be referenced relatively or by index.
+    fn cell_value(
+        self,
+        cell_ref: &core::data::CellRef,
+        _origin: &core::data::CellPath,
+    ) -> Result<core::expr::Value, core::expr::ContextError> {
+        match cell_ref.sheet {
+            core::data::SheetRef::Relative
+            | core::data::SheetRef::Absolute(core::data::SheetIndex::Index(0)) => {}
+            core::data::SheetRef::Absolute(_) => {
+                return Err(core::expr::ContextError::CellRefDoesNotExist);
+            }
+        }
+
+        let idx = core::data::CellIndex::new(cell_ref.row, cell_ref.col);
+        if let Some(error) = self.errors.get(&idx) {
+            return Err(core::expr::ContextError::CellRefValueError((*error).into()));
+        }
+
+        Ok(self
+            .cells
+            .get(&idx)
+            .cloned()
+            .unwrap_or(core::expr::Value::Empty))
     }
 }
 
@@ -136,73 +232,376 @@ impl<'a> std::iter::Iterator for SpreadsheetRowIter<'a> {
     }
 }
 
+/// Kind of information held in a metadata row.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum MetadataKind {
+    Units,
+    Description,
+}
+
+/// Metadata describing a column.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ColumnMetadata {
+    pub units: Option<String>,
+    pub description: Option<String>,
+}
+
+impl ColumnMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.units.is_none() && self.description.is_none()
+    }
+
+    fn get(&self, kind: MetadataKind) -> Option<&String> {
+        match kind {
+            MetadataKind::Units => self.units.as_ref(),
+            MetadataKind::Description => self.description.as_ref(),
+        }
+    }
+
+    fn set(&mut self, kind: MetadataKind, value: String) {
+        match kind {
+            MetadataKind::Units => self.units = Some(value),
+            MetadataKind::Description => self.description = Some(value),
+        }
+    }
+}
+
+pub type ColumnMetadataMap = BTreeMap<core::data::IndexType, ColumnMetadata>;
+
+/// Options used when importing a dataset.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ImportOptions {
+    /// Rows containing column metadata rather than data.
+    /// Rows are indexed by their position in the file.
+    pub metadata_rows: Vec<(usize, MetadataKind)>,
+
+    #[serde(default)]
+    pub dialect: Dialect,
+}
+
+/// Format of a csv file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct Dialect {
+    /// Field delimiter, as an ASCII character.
+    pub delimiter: u8,
+
+    /// Quote character, as an ASCII character.
+    pub quote: u8,
+}
+
+impl Default for Dialect {
+    fn default() -> Self {
+        Self {
+            delimiter: b',',
+            quote: b'"',
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl Dialect {
+    /// # Returns
+    /// Reader builder for the dialect.
+    fn reader(&self) -> csv::ReaderBuilder {
+        let mut builder = csv::ReaderBuilder::new();
+        builder
+            .has_headers(false)
+            .flexible(true)
+            .delimiter(self.delimiter)
+            .quote(self.quote);
+        builder
+    }
+
+    /// # Returns
+    /// Writer builder for the dialect.
+    fn writer(&self) -> csv::WriterBuilder {
+        let mut builder = csv::WriterBuilder::new();
+        builder.delimiter(self.delimiter).quote(self.quote);
+        builder
+    }
+}
+
+impl ImportOptions {
+    fn metadata_kind(&self, row: usize) -> Option<MetadataKind> {
+        self.metadata_rows
+            .iter()
+            .find_map(|(idx, kind)| (*idx == row).then_some(*kind))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Csv {
     pub sheet: Spreadsheet,
+
+    /// Metadata captured from metadata rows, keyed by column.
+    #[serde(default)]
+    pub columns: ColumnMetadataMap,
+
+    /// Options the csv was imported with.
+    /// Used to restore metadata rows when saving.
+    #[serde(default)]
+    pub options: ImportOptions,
 }
 
-#[cfg(feature = "fs")]
 impl Csv {
-    pub fn from_csv_reader(reader: csv::Reader<fs::File>) -> Result<Self, error::LoadCsv> {
-        reader.try_into()
+    /// # Returns
+    /// Units of the column, if set.
+    pub fn units(&self, col: core::data::IndexType) -> Option<&String> {
+        self.columns
+            .get(&col)
+            .and_then(|column| column.units.as_ref())
     }
 
-    pub fn load_from_path(path: impl AsRef<Path>) -> Result<Self, error::LoadCsv> {
-        let mut cells = CellMap::new();
-        let mut reader = csv::ReaderBuilder::new()
-            .has_headers(false)
-            .from_path(path)?;
+    /// Interleave data records with the csv's metadata rows,
+    /// placing the metadata rows at their original positions.
+    ///
+    /// # Returns
+    /// Records in file order.
+    pub fn restore_metadata_rows(&self, records: Vec<Vec<String>>) -> Vec<Vec<String>> {
+        let cols = records
+            .iter()
+            .map(|record| record.len())
+            .max()
+            .unwrap_or(0)
+            .max(self.sheet.size().1 as usize);
 
-        reader.try_into()
-    }
+        let mut rows = records.into_iter();
+        let mut restored = vec![];
+        let mut file_row = 0;
+        loop {
+            if let Some(kind) = self.options.metadata_kind(file_row) {
+                let record = (0..cols)
+                    .map(|col| {
+                        self.columns
+                            .get(&(col as core::data::IndexType))
+                            .and_then(|column| column.get(kind))
+                            .cloned()
+                            .unwrap_or_default()
+                    })
+                    .collect();
 
-    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), error::SaveCsv> {
-        let tmp_file =
-            tempfile::NamedTempFile::new().map_err(|err| error::SaveCsv::Io(err.kind()))?;
-        let mut wtr = csv::Writer::from_path(tmp_file.path())?;
-        for row in self.sheet.iter_rows() {
-            let row_str = row
-                .into_iter()
-                .map(|value| value.to_string())
-                .collect::<Vec<_>>();
+                restored.push(record);
+            } else if let Some(row) = rows.next() {
+                restored.push(row);
+            } else {
+                break;
+            }
 
-            wtr.write_record(row_str)?;
+            file_row += 1;
         }
 
-        fs::rename(tmp_file.path(), path).map_err(|err| error::SaveCsv::Io(err.kind()))?;
-        Ok(())
+        restored
     }
 }
 
 #[cfg(feature = "fs")]
-impl TryFrom<csv::Reader<fs::File>> for Csv {
-    type Error = error::LoadCsv;
+impl Csv {
+    pub fn from_csv_reader<R: io::Read>(reader: csv::Reader<R>) -> Result<Self, error::LoadCsv> {
+        reader.try_into()
+    }
+
+    /// Create a csv, extracting metadata rows as described by `options`.
+    pub fn from_csv_reader_with_options<R: io::Read>(
+        reader: csv::Reader<R>,
+        options: ImportOptions,
+    ) -> Result<Self, error::LoadCsv> {
+        Self::read_records(reader, options, None)
+    }
+
+    /// Create a csv from only the first `rows` data rows of the reader.
+    /// Metadata rows are extracted as described by `options` and do not count towards `rows`.
+    pub fn from_csv_reader_head<R: io::Read>(
+        reader: csv::Reader<R>,
+        options: ImportOptions,
+        rows: usize,
+    ) -> Result<Self, error::LoadCsv> {
+        Self::read_records(reader, options, Some(rows))
+    }
+
+    /// Create a csv, reporting progress to `control` and stopping if it is cancelled.
+    ///
+    /// # Arguments
+    /// + `max_rows`: Maximum number of data rows to read.
+    ///   `None` to read all rows.
+    pub fn from_csv_reader_with_control<R: io::Read>(
+        reader: csv::Reader<R>,
+        options: ImportOptions,
+        max_rows: Option<usize>,
+        control: &LoadControl,
+    ) -> Result<Self, error::LoadCsv> {
+        Self::read_records_with_control(reader, options, max_rows, Some(control))
+    }
 
-    fn try_from(mut reader: csv::Reader<fs::File>) -> Result<Self, Self::Error> {
+    /// # Arguments
+    /// + `max_rows`: Maximum number of data rows to read.
+    ///   `None` to read all rows.
+    fn read_records<R: io::Read>(
+        reader: csv::Reader<R>,
+        options: ImportOptions,
+        max_rows: Option<usize>,
+    ) -> Result<Self, error::LoadCsv> {
+        Self::read_records_with_control(reader, options, max_rows, None)
+    }
+
+    fn read_records_with_control<R: io::Read>(
+        mut reader: csv::Reader<R>,
+        options: ImportOptions,
+        max_rows: Option<usize>,
+        control: Option<&LoadControl>,
+    ) -> Result<Self, error::LoadCsv> {
         let mut cells = CellMap::new();
-        for (row, result) in reader.records().enumerate() {
-            let record = result.expect("result is valid");
-            if row > core::data::IndexType::MAX.into() {
-                return Err(error::LoadCsv::DataTooLarge);
+        let mut columns = ColumnMetadataMap::new();
+        let mut data_row = 0;
+        let mut record = csv::StringRecord::new();
+        for file_row in 0.. {
+            if !reader.read_record(&mut record)? {
+                break;
             }
 
-            for (col, value) in record.into_iter().enumerate() {
-                if col > core::data::IndexType::MAX.into() {
-                    return Err(error::LoadCsv::DataTooLarge);
+            if let Some(control) = control {
+                if control.is_cancelled() {
+                    return Err(error::LoadCsv::Cancelled);
+                }
+                if file_row % PROGRESS_INTERVAL == 0 {
+                    control.report(LoadProgress {
+                        bytes: reader.position().byte(),
+                        rows: file_row + 1,
+                    });
+                }
+            }
+
+            if let Some(kind) = options.metadata_kind(file_row) {
+                for (col, value) in record.iter().enumerate() {
+                    let value = value.trim();
+                    if value.is_empty() {
+                        continue;
+                    }
+
+                    let col = sheet_index(col)?;
+                    columns.entry(col).or_default().set(kind, value.to_string());
                 }
 
-                let idx = (row as core::data::IndexType, col as core::data::IndexType);
+                continue;
+            }
+
+            if max_rows.is_some_and(|max_rows| data_row >= max_rows) {
+                break;
+            }
+
+            let row = sheet_index(data_row)?;
+            for (col, value) in record.iter().enumerate() {
+                let col = sheet_index(col)?;
+                let idx = (row, col);
                 let value = str_value_to_data(value);
                 let _ = cells.insert(idx.into(), value);
             }
+            data_row += 1;
         }
 
         let sheet = Spreadsheet::from_cells(cells);
-        Ok(Self { sheet })
+        Ok(Self {
+            sheet,
+            columns,
+            options,
+        })
+    }
+
+    pub fn load_from_path(path: impl AsRef<Path>) -> Result<Self, error::LoadCsv> {
+        Self::load_from_path_with_options(path, ImportOptions::default())
+    }
+
+    pub fn load_from_path_with_options(
+        path: impl AsRef<Path>,
+        options: ImportOptions,
+    ) -> Result<Self, error::LoadCsv> {
+        let reader = options.dialect.reader().from_path(path)?;
+        Self::from_csv_reader_with_options(reader, options)
+    }
+
+    /// Load a csv, reporting progress to `control` and stopping if it is cancelled.
+    ///
+    /// # Arguments
+    /// + `max_rows`: Maximum number of data rows to read.
+    ///   `None` to read all rows.
+    pub fn load_from_path_with_control(
+        path: impl AsRef<Path>,
+        options: ImportOptions,
+        max_rows: Option<usize>,
+        control: &LoadControl,
+    ) -> Result<Self, error::LoadCsv> {
+        let reader = options.dialect.reader().from_path(path)?;
+        Self::from_csv_reader_with_control(reader, options, max_rows, control)
+    }
+
+    /// Load only the first `rows` data rows of a csv.
+    /// Used to preview files too large to load entirely.
+    pub fn load_head_from_path_with_options(
+        path: impl AsRef<Path>,
+        options: ImportOptions,
+        rows: usize,
+    ) -> Result<Self, error::LoadCsv> {
+        let reader = options.dialect.reader().from_path(path)?;
+        Self::from_csv_reader_head(reader, options, rows)
+    }
+
+    /// Load a csv contained in an archive.
+    ///
+    /// # Arguments
+    /// + `archive`: Path to the archive.
+    /// + `member`: Path of the csv within the archive.
+    pub fn load_from_archive(
+        archive: impl AsRef<Path>,
+        member: impl AsRef<Path>,
+        options: ImportOptions,
+    ) -> Result<Self, error::LoadCsv> {
+        let content = crate::fs::archive::read_member(archive, member)
+            .map_err(|err| error::LoadCsv::Io(err.kind()))?;
+        let reader = options
+            .dialect
+            .reader()
+            .from_reader(io::Cursor::new(content));
+
+        Self::from_csv_reader_with_options(reader, options)
+    }
+
+    /// Save the csv.
+    /// Metadata rows are written back to their original positions.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), error::SaveCsv> {
+        let tmp_file =
+            tempfile::NamedTempFile::new().map_err(|err| error::SaveCsv::Io(err.kind()))?;
+        let mut wtr = self.options.dialect.writer().from_path(tmp_file.path())?;
+        let records = self
+            .sheet
+            .iter_rows()
+            .map(|row| row.into_iter().map(|value| value.to_string()).collect())
+            .collect();
+
+        for record in self.restore_metadata_rows(records) {
+            wtr.write_record(record)?;
+        }
+
+        fs::rename(tmp_file.path(), path).map_err(|err| error::SaveCsv::Io(err.kind()))?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "fs")]
+impl<R: io::Read> TryFrom<csv::Reader<R>> for Csv {
+    type Error = error::LoadCsv;
+
+    fn try_from(reader: csv::Reader<R>) -> Result<Self, Self::Error> {
+        Self::from_csv_reader_with_options(reader, ImportOptions::default())
     }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Workbook {
     sheets: Vec<(String, Spreadsheet)>,
 }
@@ -231,22 +630,955 @@ impl Workbook {
 
 #[cfg(feature = "fs")]
 impl Workbook {
+    /// Load a workbook, converting its cells into [`Data`].
     pub fn load_from_path(path: impl AsRef<Path>) -> Result<Self, error::LoadExcel> {
-        // TODO: currently just a placeholder
-        let cells = CellMap::new();
-        let sheet = Spreadsheet::from_cells(cells);
-        Ok(Self {
-            sheets: vec![("".into(), sheet)],
-        })
+        use calamine::Reader;
+
+        let mut workbook = calamine::open_workbook_auto(path)?;
+        let mut sheets = vec![];
+        for name in workbook.sheet_names() {
+            let range = workbook.worksheet_range(&name)?;
+            sheets.push((name, excel::spreadsheet(&range)?));
+        }
+
+        Ok(Self { sheets })
+    }
+}
+
+/// Boundary with [`calamine`].
+/// Its types are converted into hermes' own as soon as they are read.
+#[cfg(feature = "fs")]
+mod excel {
+    use super::{CellError, Data, Spreadsheet, error};
+    use hermes_core as core;
+
+    /// Convert a worksheet into a spreadsheet.
+    pub fn spreadsheet(
+        range: &calamine::Range<calamine::Data>,
+    ) -> Result<Spreadsheet, error::LoadExcel> {
+        let (row_offset, col_offset) = range.start().unwrap_or((0, 0));
+        let mut sheet = Spreadsheet::new();
+        for (row, col, data) in range.used_cells() {
+            let row = sheet_index(row_offset as usize + row)?;
+            let col = sheet_index(col_offset as usize + col)?;
+            let idx = core::data::CellIndex::new(row, col);
+            match data_from(data.clone()) {
+                Ok(Data::Empty) => {}
+                Ok(value) => sheet.set(idx, value),
+                Err(error) => sheet.set_error(idx, error),
+            }
+        }
+
+        Ok(sheet)
+    }
+
+    /// Convert cell data without losing information.
+    /// Dates and durations which can not be represented are kept as their serial value,
+    /// ISO 8601 text which can not be parsed is kept as text.
+    ///
+    /// # Returns
+    /// `Err` if the cell contains an error.
+    pub fn data_from(data: calamine::Data) -> Result<Data, CellError> {
+        let value = match data {
+            calamine::Data::Empty => Data::Empty,
+            calamine::Data::Int(value) => Data::Int(value),
+            calamine::Data::Float(value) => Data::Float(value),
+            calamine::Data::String(value) => Data::String(value),
+            calamine::Data::Bool(value) => Data::Bool(value),
+            calamine::Data::DateTime(value) => {
+                let converted = if value.is_duration() {
+                    value
+                        .as_duration()
+                        .and_then(|duration| duration.to_std().ok())
+                        .map(Data::Duration)
+                } else {
+                    value
+                        .as_datetime()
+                        .map(|date_time| Data::DateTime(date_time.and_utc()))
+                };
+                converted.unwrap_or(Data::Float(value.as_f64()))
+            }
+            calamine::Data::DateTimeIso(value) => match parse_iso_date_time(&value) {
+                Some(date_time) => Data::DateTime(date_time),
+                None => Data::String(value),
+            },
+            calamine::Data::DurationIso(value) => Data::String(value),
+            calamine::Data::Error(error) => return Err(error.into()),
+        };
+
+        Ok(value)
+    }
+
+    /// Parse an ISO 8601 date time, assuming UTC if it has no offset.
+    fn parse_iso_date_time(value: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+        if let Ok(date_time) = chrono::DateTime::parse_from_rfc3339(value) {
+            return Some(date_time.to_utc());
+        }
+        if let Ok(date_time) = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+        {
+            return Some(date_time.and_utc());
+        }
+        chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()
+            .and_then(|date| date.and_hms_opt(0, 0, 0))
+            .map(|date_time| date_time.and_utc())
+    }
+
+    fn sheet_index(idx: usize) -> Result<core::data::IndexType, error::LoadExcel> {
+        core::data::IndexType::try_from(idx)
+            .ok()
+            .filter(|idx| *idx < core::data::MAX_SHEET_SIZE)
+            .ok_or(error::LoadExcel::SheetTooLarge)
+    }
+
+    impl From<calamine::CellErrorType> for CellError {
+        fn from(value: calamine::CellErrorType) -> Self {
+            match value {
+                calamine::CellErrorType::Div0 => Self::Div0,
+                calamine::CellErrorType::NA => Self::NotAvailable,
+                calamine::CellErrorType::Name => Self::Name,
+                calamine::CellErrorType::Null => Self::Null,
+                calamine::CellErrorType::Num => Self::Num,
+                calamine::CellErrorType::Ref => Self::Ref,
+                calamine::CellErrorType::Value => Self::Value,
+                calamine::CellErrorType::GettingData => Self::GettingData,
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod test {
+        use super::*;
+
+        #[test]
+        fn data_from_calamine() {
+            assert_eq!(data_from(calamine::Data::Int(1)).unwrap(), Data::Int(1));
+            assert_eq!(
+                data_from(calamine::Data::String("a".to_string())).unwrap(),
+                Data::String("a".to_string())
+            );
+            assert_eq!(
+                data_from(calamine::Data::Error(calamine::CellErrorType::Div0)).unwrap_err(),
+                CellError::Div0
+            );
+            assert!(matches!(
+                data_from(calamine::Data::DateTimeIso(
+                    "2024-01-02T03:04:05".to_string()
+                )),
+                Ok(Data::DateTime(_))
+            ));
+            assert_eq!(
+                data_from(calamine::Data::DurationIso("PT1H".to_string())).unwrap(),
+                Data::String("PT1H".to_string())
+            );
+
+            let date =
+                calamine::ExcelDateTime::new(45000.5, calamine::ExcelDateTimeType::DateTime, false);
+            let Ok(Data::DateTime(date_time)) = data_from(calamine::Data::DateTime(date)) else {
+                panic!("expected date time");
+            };
+            assert_eq!(date_time.to_rfc3339(), "2023-03-15T12:00:00+00:00");
+        }
+
+        #[test]
+        fn spreadsheet_offset_and_errors() {
+            let mut range = calamine::Range::new((1, 2), (1, 3));
+            range.set_value((1, 2), calamine::Data::Float(1.5));
+            range.set_value((1, 3), calamine::Data::Error(calamine::CellErrorType::NA));
+            let sheet = spreadsheet(&range).unwrap();
+            assert_eq!(
+                sheet.cells().get(&core::data::CellIndex::new(1u8, 2u8)),
+                Some(&Data::Float(1.5))
+            );
+            assert_eq!(
+                sheet.errors().get(&core::data::CellIndex::new(1u8, 3u8)),
+                Some(&CellError::NotAvailable)
+            );
+            assert_eq!(sheet.size(), (2, 4));
+        }
     }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, derive_more::From)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Dataset {
     Csv(Csv),
     Workbook(Workbook),
 }
 
+/// Read a dataset from a file.
+/// Paths into an [archive](crate::fs::archive) read the archive member.
+#[cfg(feature = "fs")]
+pub fn read_dataset(
+    path: impl AsRef<Path>,
+    options: ImportOptions,
+) -> Result<Dataset, error::Load> {
+    read_dataset_with_control(path, options, None, None)
+}
+
+/// Read a dataset from a file.
+/// Paths into an [archive](crate::fs::archive) read the archive member, which is not controlled.
+/// Files whose kind is not known are read as a csv, then as a workbook.
+///
+/// # Arguments
+/// + `max_rows`: Maximum number of data rows of a csv to read.
+///   `None` to read all rows.
+/// + `control`: Follows and cancels reading a csv.
+#[cfg(feature = "fs")]
+pub fn read_dataset_with_control(
+    path: impl AsRef<Path>,
+    options: ImportOptions,
+    max_rows: Option<usize>,
+    control: Option<&LoadControl>,
+) -> Result<Dataset, error::Load> {
+    let path = path.as_ref();
+    if let Some((archive, member)) = crate::fs::archive::split_path(path) {
+        return read_archive_dataset(archive, member, options);
+    }
+
+    let load_csv = |options| match (control, max_rows) {
+        (Some(control), max_rows) => {
+            Csv::load_from_path_with_control(path, options, max_rows, control)
+        }
+        (None, Some(rows)) => Csv::load_head_from_path_with_options(path, options, rows),
+        (None, None) => Csv::load_from_path_with_options(path, options),
+    };
+
+    match DataKind::from_path(path) {
+        DataKind::Csv => load_csv(options)
+            .map(|csv| csv.into())
+            .map_err(|err| err.into()),
+        DataKind::Workbook => Workbook::load_from_path(path)
+            .map(|workbook| workbook.into())
+            .map_err(|err| err.into()),
+        DataKind::Unknown => match load_csv(options) {
+            Ok(csv) => Ok(csv.into()),
+            Err(csv_err) => match csv_err {
+                error::LoadCsv::Io(_) | error::LoadCsv::Cancelled => Err(csv_err.into()),
+                _ => match Workbook::load_from_path(path) {
+                    Ok(workbook) => Ok(workbook.into()),
+                    Err(_) => Err(error::Load::InvalidFileType),
+                },
+            },
+        },
+    }
+}
+
+/// Read a dataset from a file contained in an archive.
+/// Workbooks are not supported within archives.
+#[cfg(feature = "fs")]
+fn read_archive_dataset(
+    archive: impl AsRef<Path>,
+    member: impl AsRef<Path>,
+    options: ImportOptions,
+) -> Result<Dataset, error::Load> {
+    match DataKind::from_path(member.as_ref()) {
+        DataKind::Csv | DataKind::Unknown => Csv::load_from_archive(archive, member, options)
+            .map(|csv| csv.into())
+            .map_err(|err| err.into()),
+        DataKind::Workbook => Err(error::Load::InvalidFileType),
+    }
+}
+
+/// # Returns
+/// `Err` if the file is larger than `limit` bytes.
+/// Files whose size can not be read are not checked.
+#[cfg(feature = "fs")]
+pub fn check_dataset_size(path: impl AsRef<Path>, limit: u64) -> Result<(), error::Load> {
+    let path = path.as_ref();
+    let Ok(metadata) = fs::metadata(path) else {
+        return Ok(());
+    };
+
+    let size = metadata.len();
+    if size <= limit {
+        return Ok(());
+    }
+
+    match DataKind::from_path(path) {
+        DataKind::Workbook => Err(error::LoadExcel::DataTooLarge { size, limit }.into()),
+        DataKind::Csv | DataKind::Unknown => {
+            Err(error::LoadCsv::DataTooLarge { size, limit }.into())
+        }
+    }
+}
+
+/// # Returns
+/// Whether the file is read as a workbook.
+pub fn is_workbook_path(path: impl AsRef<std::path::Path>) -> bool {
+    matches!(DataKind::from_path(path.as_ref()), DataKind::Workbook)
+}
+
+/// How a file is read, detected from its extension.
+#[derive(Debug)]
+enum DataKind {
+    Csv,
+    Workbook,
+    Unknown,
+}
+
+impl DataKind {
+    fn from_path(path: &std::path::Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("csv" | "tsv") => Self::Csv,
+            Some("xlsx" | "xls") => Self::Workbook,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// A dataset loaded from a file.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Loaded {
+    pub dataset: Dataset,
+
+    /// Whether the file can not be written to.
+    /// e.g. It is read-only, locked by another process, or within an archive.
+    pub read_only: bool,
+
+    /// Whether only the first rows of the file were loaded.
+    /// See [`Limits`](crate::settings::Limits).
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// Event emitted to the ui with the [progress](LoadProgressEvent) of loading a dataset.
+pub const LOAD_PROGRESS_EVENT: &str = "load-progress";
+
+/// Number of records read between progress reports.
+#[cfg(feature = "fs")]
+const PROGRESS_INTERVAL: usize = 10_000;
+
+/// Identifies a dataset load so its progress can be followed and it can be cancelled.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct LoadId(u64);
+impl LoadId {
+    /// # Returns
+    /// An id not yet used by this process.
+    pub fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// Progress of loading a dataset.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LoadProgress {
+    /// Bytes of the file parsed.
+    pub bytes: u64,
+
+    /// Rows of the file parsed, including metadata rows.
+    pub rows: usize,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LoadProgressEvent {
+    pub id: LoadId,
+    pub progress: LoadProgress,
+}
+
+/// Follows and cancels loading a dataset.
+#[cfg(feature = "fs")]
+pub struct LoadControl<'a> {
+    cancelled: &'a AtomicBool,
+    on_progress: &'a dyn Fn(LoadProgress),
+}
+
+#[cfg(feature = "fs")]
+impl<'a> LoadControl<'a> {
+    /// # Arguments
+    /// + `cancelled`: Loading stops once set.
+    /// + `on_progress`: Called periodically while loading.
+    pub fn new(cancelled: &'a AtomicBool, on_progress: &'a dyn Fn(LoadProgress)) -> Self {
+        Self {
+            cancelled,
+            on_progress,
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    fn report(&self, progress: LoadProgress) {
+        (self.on_progress)(progress)
+    }
+}
+
+/// Join the rows of two sheets on key columns.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Join {
+    pub kind: JoinKind,
+
+    /// Key columns of the left sheet.
+    pub left_keys: Vec<core::data::IndexType>,
+
+    /// Key columns of the right sheet, matched in order with `left_keys`.
+    pub right_keys: Vec<core::data::IndexType>,
+
+    /// Whether the first row of each sheet holds column headers rather than data.
+    #[serde(default)]
+    pub header: bool,
+}
+
+impl Join {
+    /// Join two sheets.
+    /// Keys are compared by their written representation.
+    /// Rows with an empty key never match.
+    /// Left rows are kept in order, each followed by its matches in right row order.
+    ///
+    /// # Returns
+    /// Every column of the left sheet followed by the non-key columns of the right sheet.
+    pub fn apply(&self, left: &CellMap, right: &CellMap) -> Vec<crate::formula::OutputColumn> {
+        let first_row = if self.header { 1 } else { 0 };
+        let (left_rows, left_cols) = cells_size(left);
+        let (right_rows, right_cols) = cells_size(right);
+        let right_columns = (0..right_cols)
+            .filter(|col| !self.right_keys.contains(col))
+            .collect::<Vec<_>>();
+
+        let mut right_index = HashMap::<Vec<String>, Vec<core::data::IndexType>>::new();
+        for row in first_row..right_rows {
+            if let Some(key) = row_key(right, row, &self.right_keys) {
+                right_index.entry(key).or_default().push(row);
+            }
+        }
+
+        let header = |cells: &CellMap, col| {
+            let label = if self.header {
+                cell_value(cells, 0, col).to_string()
+            } else {
+                String::new()
+            };
+            if label.is_empty() {
+                core::utils::index_to_col(col)
+            } else {
+                label
+            }
+        };
+        let mut columns = (0..left_cols)
+            .map(|col| header(left, col))
+            .chain(right_columns.iter().map(|col| header(right, *col)))
+            .map(|header| crate::formula::OutputColumn {
+                header,
+                values: vec![],
+            })
+            .collect::<Vec<_>>();
+
+        for row in first_row..left_rows {
+            let matches = row_key(left, row, &self.left_keys)
+                .and_then(|key| right_index.get(&key))
+                .map(|rows| rows.iter().map(|row| Some(*row)).collect::<Vec<_>>())
+                .unwrap_or_default();
+            let matches = match self.kind {
+                JoinKind::Inner => matches,
+                JoinKind::Left if matches.is_empty() => vec![None],
+                JoinKind::Left => matches,
+            };
+
+            for right_row in matches {
+                let values = (0..left_cols).map(|col| cell_value(left, row, col)).chain(
+                    right_columns.iter().map(|col| match right_row {
+                        Some(right_row) => cell_value(right, right_row, *col),
+                        None => Data::Empty,
+                    }),
+                );
+                for (column, value) in columns.iter_mut().zip(values) {
+                    column.values.push(value);
+                }
+            }
+        }
+
+        columns
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum JoinKind {
+    /// Only rows with a match in both sheets.
+    #[default]
+    Inner,
+
+    /// Every row of the left sheet, with empty values if there is no match.
+    Left,
+}
+
+impl JoinKind {
+    pub const ALL: &[Self] = &[Self::Inner, Self::Left];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Inner => "inner",
+            Self::Left => "left",
+        }
+    }
+}
+
+/// Derive new columns from the values of an existing column.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Extract {
+    /// Column values are taken from.
+    pub col: core::data::IndexType,
+    pub method: ExtractMethod,
+
+    /// Whether the first row holds column headers rather than data.
+    #[serde(default)]
+    pub header: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ExtractMethod {
+    /// Each capture group of a regular expression becomes a column,
+    /// or the whole match if the expression has no groups.
+    /// Values that do not match leave the new cells empty.
+    Regex(String),
+
+    /// Each part of the value split on a delimiter becomes a column,
+    /// e.g. `sampleID_timepoint` split on `_`.
+    Split { delimiter: String },
+}
+
+impl Extract {
+    /// Extract new columns from the cells of a sheet.
+    ///
+    /// # Returns
+    /// New columns with a value for each data row.
+    /// Headers are taken from named capture groups, or numbered after the source column.
+    pub fn apply(
+        &self,
+        cells: &CellMap,
+    ) -> Result<Vec<crate::formula::OutputColumn>, error::Extract> {
+        let (rows, _) = cells_size(cells);
+        let first_row = if self.header { 1 } else { 0 };
+        let header = if self.header {
+            cell_value(cells, 0, self.col).to_string()
+        } else {
+            String::new()
+        };
+
+        self.extract(
+            &header,
+            (first_row..rows).map(|row| cell_value(cells, row, self.col)),
+        )
+    }
+
+    /// Extract new columns from the values of the source column.
+    ///
+    /// # Arguments
+    /// + `header`: Header of the source column. The column letter is used if empty.
+    pub(crate) fn extract(
+        &self,
+        header: &str,
+        values: impl IntoIterator<Item = Data>,
+    ) -> Result<Vec<crate::formula::OutputColumn>, error::Extract> {
+        let header = if header.is_empty() {
+            core::utils::index_to_col(self.col)
+        } else {
+            header.to_string()
+        };
+        let numbered = |idx: usize| format!("{header}_{}", idx + 1);
+
+        let (headers, parts) = match &self.method {
+            ExtractMethod::Regex(pattern) => {
+                let regex = regex::Regex::new(pattern)
+                    .map_err(|err| error::Extract::InvalidRegex(err.to_string()))?;
+                let groups = regex
+                    .capture_names()
+                    .enumerate()
+                    .skip(if regex.captures_len() > 1 { 1 } else { 0 })
+                    .map(|(group, name)| (group, name.map(|name| name.to_string())))
+                    .collect::<Vec<_>>();
+
+                let headers = groups
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, (_, name))| name.clone().unwrap_or_else(|| numbered(idx)))
+                    .collect::<Vec<_>>();
+                let parts = values
+                    .into_iter()
+                    .map(|value| {
+                        let value = value.to_string();
+                        match regex.captures(&value) {
+                            None => vec![],
+                            Some(captures) => groups
+                                .iter()
+                                .map(|(group, _)| {
+                                    captures
+                                        .get(*group)
+                                        .map(|part| str_value_to_data(part.as_str()))
+                                        .unwrap_or(Data::Empty)
+                                })
+                                .collect(),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                (headers, parts)
+            }
+
+            ExtractMethod::Split { delimiter } => {
+                if delimiter.is_empty() {
+                    return Err(error::Extract::EmptyDelimiter);
+                }
+
+                let parts = values
+                    .into_iter()
+                    .map(|value| match value {
+                        Data::Empty => vec![],
+                        value => value
+                            .to_string()
+                            .split(delimiter.as_str())
+                            .map(str_value_to_data)
+                            .collect::<Vec<_>>(),
+                    })
+                    .collect::<Vec<_>>();
+                let count = parts.iter().map(|parts| parts.len()).max().unwrap_or(0);
+                let headers = (0..count).map(numbered).collect::<Vec<_>>();
+
+                (headers, parts)
+            }
+        };
+
+        let mut columns = headers
+            .into_iter()
+            .map(|header| crate::formula::OutputColumn {
+                header,
+                values: Vec::with_capacity(parts.len()),
+            })
+            .collect::<Vec<_>>();
+        for parts in parts {
+            let mut parts = parts.into_iter();
+            for column in columns.iter_mut() {
+                column.values.push(parts.next().unwrap_or(Data::Empty));
+            }
+        }
+
+        Ok(columns)
+    }
+}
+
+/// Select a subset of the rows of a sheet,
+/// e.g. to prototype formulas on a large file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Subset {
+    pub rows: SubsetRows,
+
+    /// Whether the first row holds column headers rather than data.
+    /// The header row is always kept.
+    #[serde(default)]
+    pub header: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum SubsetRows {
+    /// Random rows, kept in their original order.
+    /// The same seed always selects the same rows.
+    Sample { count: usize, seed: u64 },
+
+    /// First rows.
+    First(usize),
+
+    /// Last rows.
+    Last(usize),
+
+    /// Rows for which an expression evaluates to `true`.
+    /// The expression is written for the first data row.
+    /// Relative references move with the row being tested, as when filling a formula down,
+    /// while absolute references do not.
+    Matching(String),
+}
+
+impl Subset {
+    /// Select rows of a sheet.
+    ///
+    /// # Returns
+    /// Every column of the sheet with the values of the selected rows.
+    /// `Err` if the expression of [`SubsetRows::Matching`] is invalid,
+    /// or evaluates to something other than a boolean.
+    pub fn apply(
+        &self,
+        cells: &CellMap,
+    ) -> Result<Vec<crate::formula::OutputColumn>, error::Subset> {
+        let first_row = if self.header { 1 } else { 0 };
+        let (rows, cols) = cells_size(cells);
+        let data_rows = first_row..rows.max(first_row);
+
+        let selected = match &self.rows {
+            SubsetRows::Sample { count, seed } => {
+                let mut rows = data_rows.collect::<Vec<_>>();
+                let count = (*count).min(rows.len());
+                let mut rng = SplitMix64(*seed);
+                // Partial Fisher-Yates shuffle.
+                for idx in 0..count {
+                    let swap = idx + (rng.next() % (rows.len() - idx) as u64) as usize;
+                    rows.swap(idx, swap);
+                }
+                rows.truncate(count);
+                rows.sort();
+                rows
+            }
+            SubsetRows::First(count) => data_rows.take(*count).collect(),
+            SubsetRows::Last(count) => {
+                let skip = data_rows.len().saturating_sub(*count);
+                data_rows.skip(skip).collect()
+            }
+            SubsetRows::Matching(expression) => {
+                core::expr::parse(expression)
+                    .map_err(|err| error::Subset::InvalidExpression(err.value.to_string()))?;
+
+                let mut selected = vec![];
+                for row in data_rows {
+                    let ctx = RowContext {
+                        cells,
+                        offset: row - first_row,
+                    };
+                    let origin = core::data::CellPath {
+                        sheet: 0,
+                        row: first_row,
+                        col: 0,
+                    };
+                    match core::expr::eval(expression, ctx, &origin) {
+                        Ok(Data::Bool(true)) => selected.push(row),
+                        Ok(Data::Bool(false)) => {}
+                        Ok(_) => return Err(error::Subset::NotBoolean { row }),
+                        Err(error) => {
+                            return Err(error::Subset::Evaluate {
+                                row,
+                                error: error.to_string(),
+                            });
+                        }
+                    }
+                }
+                selected
+            }
+        };
+
+        let header = |col| {
+            let label = if self.header {
+                cell_value(cells, 0, col).to_string()
+            } else {
+                String::new()
+            };
+            if label.is_empty() {
+                core::utils::index_to_col(col)
+            } else {
+                label
+            }
+        };
+        Ok((0..cols)
+            .map(|col| crate::formula::OutputColumn {
+                header: header(col),
+                values: selected
+                    .iter()
+                    .map(|row| cell_value(cells, *row, col))
+                    .collect(),
+            })
+            .collect())
+    }
+}
+
+/// Context evaluating an expression written for the first data row against a later row.
+#[derive(Clone, Copy)]
+struct RowContext<'a> {
+    cells: &'a CellMap,
+
+    /// Rows below the first data row.
+    offset: core::data::IndexType,
+}
+
+impl core::expr::Context for RowContext<'_> {
+    fn cell_value(
+        self,
+        cell_ref: &core::data::CellRef,
+        _origin: &core::data::CellPath,
+    ) -> Result<core::expr::Value, core::expr::ContextError> {
+        match cell_ref.sheet {
+            core::data::SheetRef::Relative
+            | core::data::SheetRef::Absolute(core::data::SheetIndex::Index(0)) => {}
+            core::data::SheetRef::Absolute(_) => {
+                return Err(core::expr::ContextError::CellRefDoesNotExist);
+            }
+        }
+
+        let row = match cell_ref.row_mode {
+            core::data::RefMode::Relative => cell_ref
+                .row
+                .checked_add(self.offset)
+                .ok_or(core::expr::ContextError::CellRefDoesNotExist)?,
+            core::data::RefMode::Absolute => cell_ref.row,
+        };
+        Ok(cell_value(self.cells, row, cell_ref.col))
+    }
+}
+
+/// Small seedable pseudo-random number generator.
+/// See <https://prng.di.unimi.it/splitmix64.c>.
+struct SplitMix64(u64);
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// # Returns
+/// Number of (rows, cols) spanned by the cells.
+fn cells_size(cells: &CellMap) -> (core::data::IndexType, core::data::IndexType) {
+    cells.keys().fold((0, 0), |(rows, cols), idx| {
+        (rows.max(idx.row() + 1), cols.max(idx.col() + 1))
+    })
+}
+
+fn cell_value(cells: &CellMap, row: core::data::IndexType, col: core::data::IndexType) -> Data {
+    cells
+        .get(&core::data::CellIndex::new(row, col))
+        .cloned()
+        .unwrap_or(Data::Empty)
+}
+
+/// # Returns
+/// Key of a row, or `None` if any of the key values are empty.
+fn row_key(
+    cells: &CellMap,
+    row: core::data::IndexType,
+    keys: &[core::data::IndexType],
+) -> Option<Vec<String>> {
+    keys.iter()
+        .map(|col| {
+            let value = cell_value(cells, row, *col).to_string();
+            (!value.is_empty()).then_some(value)
+        })
+        .collect()
+}
+
+/// Summary statistics of a column.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ColumnStats {
+    /// Number of cells with a value.
+    pub count: usize,
+
+    /// Number of empty cells.
+    pub missing: usize,
+
+    /// Number of cells without a usable value, e.g. formulas with errors.
+    pub skipped: usize,
+
+    /// Number of numeric values.
+    /// `mean`, `std_dev`, `min`, `max`, and `histogram` are computed from these.
+    pub numeric: usize,
+
+    pub mean: Option<f64>,
+
+    /// Sample standard deviation.
+    /// `None` if there are fewer than two numeric values.
+    pub std_dev: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+
+    /// Counts of numeric values in equal width bins spanning `min` to `max`.
+    pub histogram: Vec<usize>,
+}
+
+impl ColumnStats {
+    /// Compute statistics over the values of a column.
+    ///
+    /// # Arguments
+    /// + `values`: Value of each cell. `None` for cells without a usable value,
+    ///     which are skipped.
+    /// + `bins`: Number of histogram bins.
+    pub fn compute<'a>(values: impl IntoIterator<Item = Option<&'a Data>>, bins: usize) -> Self {
+        let mut stats = Self::default();
+        let mut numbers = vec![];
+        let mut mean = 0.0;
+        let mut m2 = 0.0;
+        for value in values {
+            let number = match value {
+                None => {
+                    stats.skipped += 1;
+                    continue;
+                }
+                Some(Data::Empty) => {
+                    stats.missing += 1;
+                    continue;
+                }
+                Some(Data::String(value)) if value.is_empty() => {
+                    stats.missing += 1;
+                    continue;
+                }
+                Some(Data::Int(value)) => *value as f64,
+                Some(Data::Float(value)) if value.is_finite() => *value,
+                Some(_) => {
+                    stats.count += 1;
+                    continue;
+                }
+            };
+
+            stats.count += 1;
+            numbers.push(number);
+
+            // Welford's online algorithm.
+            let delta = number - mean;
+            mean += delta / numbers.len() as f64;
+            m2 += delta * (number - mean);
+        }
+
+        stats.numeric = numbers.len();
+        if numbers.is_empty() {
+            return stats;
+        }
+
+        let min = numbers.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = numbers.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        stats.mean = Some(mean);
+        stats.std_dev = (numbers.len() > 1).then(|| (m2 / (numbers.len() - 1) as f64).sqrt());
+        stats.min = Some(min);
+        stats.max = Some(max);
+
+        if bins > 0 {
+            let mut histogram = vec![0; bins];
+            let width = (max - min) / bins as f64;
+            for number in numbers {
+                let bin = if width > 0.0 {
+                    (((number - min) / width) as usize).min(bins - 1)
+                } else {
+                    0
+                };
+                histogram[bin] += 1;
+            }
+            stats.histogram = histogram;
+        }
+
+        stats
+    }
+}
+
+/// Convert a row or column position into a sheet index.
+///
+/// # Returns
+/// `Err` if the position exceeds the sheet size limit.
+#[cfg(feature = "fs")]
+fn sheet_index(idx: usize) -> Result<core::data::IndexType, error::LoadCsv> {
+    core::data::IndexType::try_from(idx)
+        .ok()
+        .filter(|idx| *idx < core::data::MAX_SHEET_SIZE)
+        .ok_or(error::LoadCsv::SheetTooLarge)
+}
+
 fn str_value_to_data(value: &str) -> Data {
     if let Ok(value) = value.parse::<i64>() {
         Data::Int(value)
@@ -269,6 +1601,7 @@ pub mod error {
     pub struct CellNotEmpty;
 
     #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone, derive_more::From)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
     pub enum Save {
         #[error("error saving csv: {0}")]
         Csv(SaveCsv),
@@ -277,9 +1610,20 @@ pub mod error {
     }
 
     #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone, derive_more::From)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
     pub enum SaveCsv {
         #[error("{0}")]
-        Io(#[serde(with = "io_error_serde::ErrorKind")] io::ErrorKind),
+        Io(
+            #[serde(with = "io_error_serde::ErrorKind")]
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
+            io::ErrorKind,
+        ),
+        /// A record has a different number of fields than the ones before it.
+        #[error("record has {len} fields, expected {expected_len}")]
+        UnequalLengths { expected_len: u64, len: u64 },
+        /// Any other error raised by the csv writer.
+        #[error("{0}")]
+        Write(String),
     }
 
     #[cfg(feature = "fs")]
@@ -289,27 +1633,53 @@ pub mod error {
 
             match value.kind() {
                 ErrorKind::Io(error) => Self::Io(error.kind()),
-                ErrorKind::Utf8 { pos, err } => todo!(),
                 ErrorKind::UnequalLengths {
-                    pos,
-                    expected_len,
-                    len,
-                } => todo!(),
-                ErrorKind::Seek => todo!(),
-                ErrorKind::Serialize(_) => todo!(),
-                ErrorKind::Deserialize { pos, err } => todo!(),
-                _ => todo!(),
+                    expected_len, len, ..
+                } => Self::UnequalLengths {
+                    expected_len: *expected_len,
+                    len: *len,
+                },
+                _ => Self::Write(value.to_string()),
             }
         }
     }
 
     #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone, derive_more::From)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
     pub enum SaveExcel {
         #[error("{0}")]
-        Io(#[serde(with = "io_error_serde::ErrorKind")] io::ErrorKind),
+        Io(
+            #[serde(with = "io_error_serde::ErrorKind")]
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
+            io::ErrorKind,
+        ),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone, PartialEq)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    pub enum Subset {
+        #[error("invalid expression: {0}")]
+        InvalidExpression(String),
+        #[error("expression does not evaluate to true or false on row {}", row + 1)]
+        NotBoolean { row: hermes_core::data::IndexType },
+        #[error("error evaluating expression on row {}: {error}", row + 1)]
+        Evaluate {
+            row: hermes_core::data::IndexType,
+            error: String,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone, PartialEq)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    pub enum Extract {
+        #[error("invalid regular expression: {0}")]
+        InvalidRegex(String),
+        #[error("delimiter can not be empty")]
+        EmptyDelimiter,
     }
 
     #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone, derive_more::From)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
     pub enum Load {
         #[error("invalid file type")]
         InvalidFileType,
@@ -320,11 +1690,36 @@ pub mod error {
     }
 
     #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone, derive_more::From)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
     pub enum LoadCsv {
         #[error("{0}")]
-        Io(#[serde(with = "io_error_serde::ErrorKind")] io::ErrorKind),
-        #[error("data is too large")]
-        DataTooLarge,
+        Io(
+            #[serde(with = "io_error_serde::ErrorKind")]
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
+            io::ErrorKind,
+        ),
+        /// File is larger than the configured [limit](crate::settings::Limits).
+        #[error("file is {size} bytes, larger than the limit of {limit} bytes")]
+        DataTooLarge { size: u64, limit: u64 },
+        #[error(
+            "data is too large, sheets are limited to {} rows and columns",
+            hermes_core::data::MAX_SHEET_SIZE
+        )]
+        SheetTooLarge,
+        #[error("loading was cancelled")]
+        Cancelled,
+        /// A field is not valid UTF-8.
+        #[error("file is not valid UTF-8")]
+        #[from(ignore)]
+        InvalidUtf8 { line: Option<u64> },
+        /// A record has a different number of fields than the ones before it.
+        #[error("record has {len} fields, expected {expected_len}")]
+        #[from(ignore)]
+        UnequalLengths { expected_len: u64, len: u64 },
+        /// Any other error raised by the csv reader.
+        #[error("{0}")]
+        #[from(ignore)]
+        Read(String),
     }
 
     #[cfg(feature = "fs")]
@@ -334,23 +1729,376 @@ pub mod error {
 
             match value.kind() {
                 ErrorKind::Io(error) => Self::Io(error.kind()),
-                ErrorKind::Utf8 { pos, err } => todo!(),
+                ErrorKind::Utf8 { pos, .. } => Self::InvalidUtf8 {
+                    line: pos.as_ref().map(|pos| pos.line()),
+                },
                 ErrorKind::UnequalLengths {
-                    pos,
-                    expected_len,
-                    len,
-                } => todo!(),
-                ErrorKind::Seek => todo!(),
-                ErrorKind::Serialize(_) => todo!(),
-                ErrorKind::Deserialize { pos, err } => todo!(),
-                _ => todo!(),
+                    expected_len, len, ..
+                } => Self::UnequalLengths {
+                    expected_len: *expected_len,
+                    len: *len,
+                },
+                _ => Self::Read(value.to_string()),
             }
         }
     }
 
     #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone, derive_more::From)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
     pub enum LoadExcel {
         #[error("{0}")]
-        Io(#[serde(with = "io_error_serde::ErrorKind")] io::ErrorKind),
+        Io(
+            #[serde(with = "io_error_serde::ErrorKind")]
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
+            io::ErrorKind,
+        ),
+        /// File is larger than the configured [limit](crate::settings::Limits).
+        #[error("file is {size} bytes, larger than the limit of {limit} bytes")]
+        DataTooLarge { size: u64, limit: u64 },
+        #[error(
+            "data is too large, sheets are limited to {} rows and columns",
+            hermes_core::data::MAX_SHEET_SIZE
+        )]
+        SheetTooLarge,
+        /// The file could not be read as a workbook.
+        #[error("invalid workbook: {0}")]
+        #[from(ignore)]
+        Invalid(String),
+    }
+
+    #[cfg(feature = "fs")]
+    impl From<calamine::Error> for LoadExcel {
+        fn from(value: calamine::Error) -> Self {
+            match value {
+                calamine::Error::Io(error) => Self::Io(error.kind()),
+                _ => Self::Invalid(value.to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn join() {
+        fn cells(rows: &[&[&str]]) -> CellMap {
+            let mut cells = CellMap::new();
+            for (row, values) in rows.iter().enumerate() {
+                for (col, value) in values.iter().enumerate() {
+                    let idx = core::data::CellIndex::new(row as u16, col as u16);
+                    cells.insert(idx, str_value_to_data(value));
+                }
+            }
+            cells
+        }
+
+        let left = cells(&[
+            &["id", "name"],
+            &["1", "a"],
+            &["2", "b"],
+            &["", "c"],
+            &["3", "d"],
+        ]);
+        let right = cells(&[&["value", "id"], &["10", "1"], &["30", "3"], &["31", "3"]]);
+
+        let mut join = Join {
+            kind: JoinKind::Inner,
+            left_keys: vec![0],
+            right_keys: vec![1],
+            header: true,
+        };
+        let columns = join.apply(&left, &right);
+        let headers = columns
+            .iter()
+            .map(|column| column.header.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(headers, vec!["id", "name", "value"]);
+        assert_eq!(
+            columns[0].values,
+            vec![Data::Int(1), Data::Int(3), Data::Int(3)]
+        );
+        assert_eq!(
+            columns[2].values,
+            vec![Data::Int(10), Data::Int(30), Data::Int(31)]
+        );
+
+        join.kind = JoinKind::Left;
+        let columns = join.apply(&left, &right);
+        assert_eq!(columns[1].values.len(), 5);
+        assert_eq!(columns[2].values[1], Data::Empty);
+        assert_eq!(columns[2].values[2], Data::Empty);
+
+        join.header = false;
+        let columns = join.apply(&left, &right);
+        assert_eq!(columns[2].header, "A");
+        assert_eq!(columns[2].values[0], Data::String("value".to_string()));
+    }
+
+    #[test]
+    fn extract() {
+        let mut cells = CellMap::new();
+        for (row, value) in ["id", "a-1", "b-22", "c"].into_iter().enumerate() {
+            cells.insert(
+                core::data::CellIndex::new(row as u16, 1u16),
+                Data::String(value.to_string()),
+            );
+        }
+
+        let mut extract = Extract {
+            col: 1,
+            method: ExtractMethod::Regex(r"^(\w)-(\d+)$".to_string()),
+            header: true,
+        };
+        let columns = extract.apply(&cells).unwrap();
+        let headers = columns
+            .iter()
+            .map(|column| column.header.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(headers, vec!["id_1", "id_2"]);
+        assert_eq!(
+            columns[1].values,
+            vec![Data::Int(1), Data::Int(22), Data::Empty]
+        );
+
+        extract.method = ExtractMethod::Split {
+            delimiter: "-".to_string(),
+        };
+        extract.header = false;
+        let columns = extract.apply(&cells).unwrap();
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[0].header, "B_1");
+        assert_eq!(columns[0].values[0], Data::String("id".to_string()));
+        assert_eq!(columns[1].values[3], Data::Empty);
+
+        extract.method = ExtractMethod::Regex("(".to_string());
+        assert!(matches!(
+            extract.apply(&cells),
+            Err(error::Extract::InvalidRegex(_))
+        ));
+    }
+
+    #[test]
+    fn subset() {
+        let mut cells = CellMap::new();
+        cells.insert(
+            core::data::CellIndex::new(0u16, 0u16),
+            Data::String("value".to_string()),
+        );
+        for row in 1..=10u16 {
+            cells.insert(core::data::CellIndex::new(row, 0u16), Data::Int(row as i64));
+        }
+        let values = |rows| {
+            Subset { rows, header: true }
+                .apply(&cells)
+                .unwrap()
+                .swap_remove(0)
+                .values
+        };
+
+        assert_eq!(
+            values(SubsetRows::First(2)),
+            vec![Data::Int(1), Data::Int(2)]
+        );
+        assert_eq!(
+            values(SubsetRows::Last(2)),
+            vec![Data::Int(9), Data::Int(10)]
+        );
+        assert_eq!(values(SubsetRows::Last(20)).len(), 10);
+        assert_eq!(
+            values(SubsetRows::Matching("A2 > 8".to_string())),
+            vec![Data::Int(9), Data::Int(10)]
+        );
+        assert_eq!(
+            values(SubsetRows::Matching("A2 > A$3".to_string())),
+            (3..=10).map(Data::Int).collect::<Vec<_>>()
+        );
+
+        let sample = values(SubsetRows::Sample { count: 4, seed: 7 });
+        assert_eq!(sample.len(), 4);
+        assert!(
+            sample
+                .windows(2)
+                .all(|pair| pair[0].as_int() < pair[1].as_int())
+        );
+        assert_eq!(sample, values(SubsetRows::Sample { count: 4, seed: 7 }));
+
+        let err = Subset {
+            rows: SubsetRows::Matching("A2 + 1".to_string()),
+            header: true,
+        }
+        .apply(&cells);
+        assert!(matches!(err, Err(error::Subset::NotBoolean { row: 1 })));
+    }
+
+    #[test]
+    fn column_stats() {
+        let values = [
+            Data::Int(1),
+            Data::Float(2.0),
+            Data::Empty,
+            Data::Int(3),
+            Data::String("x".to_string()),
+            Data::Int(6),
+        ];
+        let stats =
+            ColumnStats::compute(values.iter().map(Some).chain([None, Some(&Data::Empty)]), 2);
+
+        assert_eq!(stats.count, 5);
+        assert_eq!(stats.missing, 2);
+        assert_eq!(stats.skipped, 1);
+        assert_eq!(stats.numeric, 4);
+        assert_eq!(stats.mean, Some(3.0));
+        assert!((stats.std_dev.unwrap() - (14.0_f64 / 3.0).sqrt()).abs() < 1e-12);
+        assert_eq!(stats.min, Some(1.0));
+        assert_eq!(stats.max, Some(6.0));
+        assert_eq!(stats.histogram, vec![3, 1]);
+
+        let stats = ColumnStats::compute([Some(&Data::Int(4))], 3);
+        assert_eq!(stats.std_dev, None);
+        assert_eq!(stats.histogram, vec![1, 0, 0]);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn csv_metadata_rows() {
+        let content = "time,conc\ns,mg/mL\n0,1.5\n1,2.5\n";
+        let reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(content.as_bytes());
+        let options = ImportOptions {
+            metadata_rows: vec![(1, MetadataKind::Units)],
+            ..Default::default()
+        };
+
+        let csv = Csv::from_csv_reader_with_options(reader, options.clone()).unwrap();
+        assert_eq!(csv.sheet.size(), (3, 2));
+        assert_eq!(csv.units(0), Some(&"s".to_string()));
+        assert_eq!(csv.units(1), Some(&"mg/mL".to_string()));
+        assert!(csv.columns.get(&0).unwrap().description.is_none());
+        let idx = core::data::CellIndex::new(1u8, 1u8);
+        assert!(matches!(
+            csv.sheet.get(&idx),
+            Some(Data::Float(value)) if *value == 1.5
+        ));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn csv_restore_metadata_rows() {
+        let content = "time,conc\ns,mg/mL\n0,1.5\n";
+        let reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(content.as_bytes());
+        let options = ImportOptions {
+            metadata_rows: vec![(1, MetadataKind::Units)],
+            ..Default::default()
+        };
+
+        let csv = Csv::from_csv_reader_with_options(reader, options).unwrap();
+        let records = vec![
+            vec!["time".to_string(), "conc".to_string(), "total".to_string()],
+            vec!["0".to_string(), "1.5".to_string(), "#DIV/0!".to_string()],
+        ];
+        assert_eq!(
+            csv.restore_metadata_rows(records),
+            vec![
+                vec!["time", "conc", "total"],
+                vec!["s", "mg/mL", ""],
+                vec!["0", "1.5", "#DIV/0!"],
+            ]
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn csv_dialect() {
+        let content = "a;'b;c'\n1;2\n";
+        let options = ImportOptions {
+            dialect: Dialect {
+                delimiter: b';',
+                quote: b'\'',
+            },
+            ..Default::default()
+        };
+        let reader = options.dialect.reader().from_reader(content.as_bytes());
+
+        let csv = Csv::from_csv_reader_with_options(reader, options).unwrap();
+        assert_eq!(csv.sheet.size(), (2, 2));
+        let idx = core::data::CellIndex::new(0u8, 1u8);
+        assert!(matches!(
+            csv.sheet.get(&idx),
+            Some(Data::String(value)) if value == "b;c"
+        ));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn csv_invalid_utf8() {
+        let content: &[u8] = b"a,b\n1,\xff\n";
+        let reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(content);
+
+        assert!(matches!(
+            Csv::from_csv_reader(reader),
+            Err(error::LoadCsv::InvalidUtf8 { line: Some(2) })
+        ));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn csv_load_control() {
+        use std::cell::RefCell;
+
+        let content = "a,b\n1,2\n3,4\n";
+        let reader = || {
+            csv::ReaderBuilder::new()
+                .has_headers(false)
+                .from_reader(content.as_bytes())
+        };
+
+        let cancelled = AtomicBool::new(false);
+        let reports = RefCell::new(vec![]);
+        let on_progress = |progress: LoadProgress| reports.borrow_mut().push(progress);
+        let control = LoadControl::new(&cancelled, &on_progress);
+        let csv =
+            Csv::from_csv_reader_with_control(reader(), ImportOptions::default(), None, &control)
+                .unwrap();
+        assert_eq!(csv.sheet.size(), (3, 2));
+        assert_eq!(
+            reports.borrow().first(),
+            Some(&LoadProgress { bytes: 4, rows: 1 })
+        );
+
+        cancelled.store(true, Ordering::Relaxed);
+        let control = LoadControl::new(&cancelled, &on_progress);
+        assert!(matches!(
+            Csv::from_csv_reader_with_control(reader(), ImportOptions::default(), None, &control),
+            Err(error::LoadCsv::Cancelled)
+        ));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn csv_head() {
+        let content = "time,conc\ns,mg/mL\n0,1.5\n1,2.5\n2,3.5\n";
+        let reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(content.as_bytes());
+        let options = ImportOptions {
+            metadata_rows: vec![(1, MetadataKind::Units)],
+            ..Default::default()
+        };
+
+        let csv = Csv::from_csv_reader_head(reader, options, 2).unwrap();
+        assert_eq!(csv.sheet.size(), (2, 2));
+        assert_eq!(csv.units(1), Some(&"mg/mL".to_string()));
+        let idx = core::data::CellIndex::new(1u8, 1u8);
+        assert!(matches!(
+            csv.sheet.get(&idx),
+            Some(Data::Float(value)) if *value == 1.5
+        ));
     }
 }
@@ -0,0 +1,320 @@
+//! Diagnostics collected for bug reports.
+//! Nothing is sent anywhere, bundles are saved locally for the user to attach to a report.
+//! Bundles describe the workspace without its data,
+//! e.g. datasets are described by their path and size, never their cells.
+use crate::settings;
+use hermes_core as core;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[cfg(feature = "fs")]
+use std::{fs, io, path::Path};
+
+/// Event emitted to the ui when the user asks for a diagnostic bundle,
+/// so the ui can describe its workspace.
+pub const DIAGNOSTICS_REQUESTED_EVENT: &str = "diagnostics-requested";
+
+/// Maximum number of recorded panics included in a bundle, most recent first.
+pub const MAX_PANICS: usize = 10;
+
+/// Maximum size of the log included in a bundle, in bytes.
+pub const MAX_LOG_BYTES: u64 = 256 * 1024;
+
+/// Workspace open when a bundle is created.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Workspace {
+    pub root: PathBuf,
+    pub datasets: Vec<Dataset>,
+
+    /// Number of formulas.
+    pub formulas: usize,
+
+    pub settings: settings::Settings,
+}
+
+/// Open dataset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Dataset {
+    /// Path relative to the project root.
+    pub path: PathBuf,
+
+    /// `(<rows>, <columns>)` of each sheet.
+    pub sheets: Vec<(core::data::IndexType, core::data::IndexType)>,
+
+    pub read_only: bool,
+}
+
+/// Version of the app and the system it runs on.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct System {
+    pub version: String,
+    pub os: String,
+    pub arch: String,
+}
+
+impl System {
+    /// # Arguments
+    /// + `version`: Version of the app.
+    pub fn current(version: impl Into<String>) -> Self {
+        Self {
+            version: version.into(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+        }
+    }
+}
+
+/// Panic recorded by the panic hook.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Panic {
+    pub time: chrono::DateTime<chrono::Utc>,
+
+    /// Name of the panicking thread.
+    pub thread: Option<String>,
+    pub message: String,
+
+    /// `<file>:<line>:<column>` the panic occurred at.
+    pub location: Option<String>,
+    pub backtrace: String,
+}
+
+impl Panic {
+    /// Describe a panic from within a panic hook.
+    pub fn from_hook(info: &std::panic::PanicHookInfo) -> Self {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown panic payload".to_string());
+
+        Self {
+            time: chrono::Utc::now(),
+            thread: std::thread::current().name().map(|name| name.to_string()),
+            message,
+            location: info.location().map(|location| location.to_string()),
+            backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        }
+    }
+
+    /// Save the panic in a folder of recorded panics.
+    #[cfg(feature = "fs")]
+    pub fn record(&self, dir: impl AsRef<Path>) -> io::Result<()> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let name = format!("panic-{}.json", self.time.format("%Y%m%dT%H%M%S%.3fZ"));
+        let content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        fs::write(dir.join(name), content)
+    }
+}
+
+/// # Returns
+/// Panics recorded in the folder, most recent first, up to [`MAX_PANICS`].
+/// Records that can not be read are skipped.
+#[cfg(feature = "fs")]
+pub fn recorded_panics(dir: impl AsRef<Path>) -> Vec<Panic> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+
+    let mut panics = entries
+        .filter_map(|entry| {
+            let content = fs::read_to_string(entry.ok()?.path()).ok()?;
+            serde_json::from_str::<Panic>(&content).ok()
+        })
+        .collect::<Vec<_>>();
+
+    panics.sort_by(|a, b| b.time.cmp(&a.time));
+    panics.truncate(MAX_PANICS);
+    panics
+}
+
+/// # Returns
+/// End of the most recently modified log file in the folder, up to [`MAX_LOG_BYTES`].
+/// If the log is cut, it starts at the first complete line.
+/// `None` if there is no log file.
+///
+/// # Arguments
+/// + `prefix`: Prefix of the log files' names.
+#[cfg(feature = "fs")]
+pub fn log_tail(dir: impl AsRef<Path>, prefix: &str) -> Option<String> {
+    use std::io::{Read, Seek};
+
+    let path = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(prefix))
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.path()))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)?;
+
+    let mut file = fs::File::open(path).ok()?;
+    let len = file.metadata().ok()?.len();
+    let start = len.saturating_sub(MAX_LOG_BYTES);
+    file.seek(io::SeekFrom::Start(start)).ok()?;
+    let mut content = vec![];
+    file.read_to_end(&mut content).ok()?;
+
+    let content = String::from_utf8_lossy(&content);
+    if start == 0 {
+        Some(content.to_string())
+    } else {
+        let line_start = content.find('\n').map(|idx| idx + 1).unwrap_or(0);
+        Some(content[line_start..].to_string())
+    }
+}
+
+/// Diagnostic bundle saved as a zip archive.
+#[cfg(feature = "fs")]
+#[derive(Clone, Debug)]
+pub struct Bundle {
+    pub system: System,
+    pub workspace: Option<Workspace>,
+    pub panics: Vec<Panic>,
+    pub log: Option<String>,
+}
+
+#[cfg(feature = "fs")]
+impl Bundle {
+    /// Write the bundle as a zip archive.
+    /// Contains `system.json`, `workspace.json`, `panics.json` and `log.txt`,
+    /// where `workspace.json` and `log.txt` are only included if present.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), error::Bundle> {
+        use std::io::Write;
+
+        let file = fs::File::create(path)?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default();
+
+        let mut add = |name: &str, content: &[u8]| -> Result<(), error::Bundle> {
+            zip.start_file(name, options)?;
+            zip.write_all(content)?;
+            Ok(())
+        };
+
+        add("system.json", &to_json(&self.system)?)?;
+        if let Some(workspace) = &self.workspace {
+            add("workspace.json", &to_json(workspace)?)?;
+        }
+        add("panics.json", &to_json(&self.panics)?)?;
+        if let Some(log) = &self.log {
+            add("log.txt", log.as_bytes())?;
+        }
+
+        zip.finish()?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "fs")]
+fn to_json(value: &impl Serialize) -> Result<Vec<u8>, error::Bundle> {
+    serde_json::to_vec_pretty(value).map_err(|err| error::Bundle::Serialize(err.to_string()))
+}
+
+pub mod error {
+    use serde::{Deserialize, Serialize};
+    use std::io;
+
+    #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    pub enum Bundle {
+        #[error("{0}")]
+        Io(
+            #[serde(with = "io_error_serde::ErrorKind")]
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
+            io::ErrorKind,
+        ),
+
+        #[error("could not write archive: {0}")]
+        Zip(String),
+
+        #[error("could not serialize diagnostics: {0}")]
+        Serialize(String),
+    }
+
+    impl From<io::Error> for Bundle {
+        fn from(err: io::Error) -> Self {
+            Self::Io(err.kind())
+        }
+    }
+
+    #[cfg(feature = "fs")]
+    impl From<zip::result::ZipError> for Bundle {
+        fn from(err: zip::result::ZipError) -> Self {
+            match err {
+                zip::result::ZipError::Io(err) => Self::Io(err.kind()),
+                err => Self::Zip(err.to_string()),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod test {
+    use super::*;
+
+    fn panic(secs: i64) -> Panic {
+        Panic {
+            time: chrono::DateTime::from_timestamp(secs, 0).unwrap(),
+            thread: Some("main".to_string()),
+            message: format!("panic {secs}"),
+            location: None,
+            backtrace: String::new(),
+        }
+    }
+
+    #[test]
+    fn recorded_panics_most_recent_first() {
+        let dir = tempfile::tempdir().unwrap();
+        panic(1).record(dir.path()).unwrap();
+        panic(3).record(dir.path()).unwrap();
+        panic(2).record(dir.path()).unwrap();
+        fs::write(dir.path().join("invalid.json"), "{").unwrap();
+
+        let panics = recorded_panics(dir.path());
+        let messages = panics
+            .iter()
+            .map(|panic| panic.message.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(messages, ["panic 3", "panic 2", "panic 1"]);
+    }
+
+    #[test]
+    fn log_tail_starts_at_line() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(log_tail(dir.path(), "hermes.log"), None);
+
+        let line = "x".repeat(99) + "\n";
+        let log = line.repeat((MAX_LOG_BYTES / 100 + 10) as usize);
+        fs::write(dir.path().join("hermes.log.2025-01-01"), &log).unwrap();
+        fs::write(dir.path().join("other.log"), "other").unwrap();
+
+        let tail = log_tail(dir.path(), "hermes.log").unwrap();
+        assert!(tail.len() as u64 <= MAX_LOG_BYTES);
+        assert!(tail.starts_with('x'));
+        assert!(log.ends_with(&tail));
+    }
+
+    #[test]
+    fn bundle_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bundle.zip");
+        let bundle = Bundle {
+            system: System::current("0.0.1"),
+            workspace: None,
+            panics: vec![panic(1)],
+            log: Some("log".to_string()),
+        };
+        bundle.save(&path).unwrap();
+
+        let archive = zip::ZipArchive::new(fs::File::open(&path).unwrap()).unwrap();
+        let mut names = archive.file_names().collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(names, ["log.txt", "panics.json", "system.json"]);
+    }
+}
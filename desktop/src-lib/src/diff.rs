@@ -0,0 +1,163 @@
+//! Cell level differences between a dataset on disk and its in-memory state.
+//! Values are compared by their written representation,
+//! so values which would be saved identically are not reported as changed.
+use crate::data::{CellMap, Data, Dataset};
+use hermes_core as core;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CellDiff {
+    /// Sheet index for workbooks.
+    /// `None` for csv files.
+    #[serde(default)]
+    pub sheet: Option<core::data::IndexType>,
+
+    pub cell: core::data::CellIndex,
+    pub kind: DiffKind,
+
+    /// Value of the cell in the file.
+    pub disk: Data,
+
+    /// Value of the cell in memory.
+    pub memory: Data,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum DiffKind {
+    /// Cell is empty on disk but set in memory.
+    Added,
+
+    /// Cell is set on disk but empty in memory.
+    Removed,
+
+    /// Cell is set in both, but with different values.
+    Modified,
+}
+
+/// Compare the cells of a sheet.
+///
+/// # Arguments
+/// + `sheet`: Sheet index to record in each diff.
+/// + `disk`: Cells of the sheet in the file.
+/// + `memory`: Cells of the sheet in memory.
+///
+/// # Returns
+/// Differing cells, ordered by index.
+pub fn sheet(
+    sheet: Option<core::data::IndexType>,
+    disk: &CellMap,
+    memory: &CellMap,
+) -> Vec<CellDiff> {
+    let mut cells = disk.keys().chain(memory.keys()).collect::<Vec<_>>();
+    cells.sort();
+    cells.dedup();
+
+    cells
+        .into_iter()
+        .filter_map(|cell| {
+            let disk = disk.get(cell).cloned().unwrap_or(Data::Empty);
+            let memory = memory.get(cell).cloned().unwrap_or(Data::Empty);
+            let kind = match (is_empty(&disk), is_empty(&memory)) {
+                (true, true) => return None,
+                (true, false) => DiffKind::Added,
+                (false, true) => DiffKind::Removed,
+                (false, false) => {
+                    if disk.to_string() == memory.to_string() {
+                        return None;
+                    }
+                    DiffKind::Modified
+                }
+            };
+
+            Some(CellDiff {
+                sheet,
+                cell: cell.clone(),
+                kind,
+                disk,
+                memory,
+            })
+        })
+        .collect()
+}
+
+/// Compare a dataset loaded from disk with its in-memory sheets.
+///
+/// # Arguments
+/// + `disk`: Dataset as loaded from the file.
+/// + `memory`: Cells of each sheet in memory.
+///     Csv files have a single sheet.
+///
+/// # Returns
+/// Differing cells, ordered by sheet then index.
+pub fn dataset(disk: &Dataset, memory: &[CellMap]) -> Vec<CellDiff> {
+    let empty = CellMap::new();
+    match disk {
+        Dataset::Csv(csv) => {
+            let memory = memory.first().unwrap_or(&empty);
+            self::sheet(None, csv.sheet.cells(), memory)
+        }
+
+        Dataset::Workbook(workbook) => {
+            let sheets = workbook.sheets().len().max(memory.len());
+            (0..sheets)
+                .flat_map(|idx| {
+                    let disk = workbook
+                        .get_sheet(idx)
+                        .map(|sheet| sheet.cells())
+                        .unwrap_or(&empty);
+                    let memory = memory.get(idx).unwrap_or(&empty);
+                    self::sheet(Some(idx as core::data::IndexType), disk, memory)
+                })
+                .collect()
+        }
+    }
+}
+
+fn is_empty(value: &Data) -> bool {
+    match value {
+        Data::Empty => true,
+        Data::String(value) => value.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sheet_diff() {
+        let cell = |row: u8, col: u8| core::data::CellIndex::new(row, col);
+        let disk = CellMap::from([
+            (cell(0, 0), Data::Int(1)),
+            (cell(0, 1), Data::String("a".to_string())),
+            (cell(1, 0), Data::Int(2)),
+            (cell(1, 1), Data::String(String::new())),
+        ]);
+        let memory = CellMap::from([
+            (cell(0, 0), Data::Float(1.0)),
+            (cell(0, 1), Data::String("b".to_string())),
+            (cell(1, 1), Data::Empty),
+            (cell(2, 0), Data::Bool(true)),
+        ]);
+
+        let diff = sheet(None, &disk, &memory);
+        let kinds = diff
+            .iter()
+            .map(|diff| (diff.cell.clone(), diff.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (cell(0, 1), DiffKind::Modified),
+                (cell(1, 0), DiffKind::Removed),
+                (cell(2, 0), DiffKind::Added),
+            ]
+        );
+        assert_eq!(diff[0].disk, Data::String("a".to_string()));
+        assert_eq!(diff[0].memory, Data::String("b".to_string()));
+        assert!(diff.iter().all(|diff| diff.sheet.is_none()));
+    }
+}
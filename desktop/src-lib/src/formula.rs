@@ -1,62 +1,878 @@
-use crate::data;
+use crate::{audit, clean, data};
 use hermes_core as core;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::{
+    cell::RefCell,
+    collections::{BTreeMap, HashMap, VecDeque},
+    path::{Path, PathBuf},
+};
 
 #[derive(Serialize, Deserialize, derive_more::From, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum WorkspaceOrder {
-    Create,
+    Create(Create),
     Update(Update),
+    Clean(Clean),
 }
 
+impl WorkspaceOrder {
+    /// # Returns
+    /// Path of the file the order writes to, if known.
+    pub fn path(&self) -> Option<&PathBuf> {
+        match self {
+            Self::Create(create) => Some(&create.path),
+            Self::Update(update) => Some(&update.path),
+            Self::Clean(clean) => Some(&clean.path),
+        }
+    }
+}
+
+/// Extension appended to files staged by a workspace run.
+pub const STAGED_EXTENSION: &str = "hermes-staged";
+
+/// # Returns
+/// Path of the staging file for `path`.
+/// Staging files are hidden and placed next to the file,
+/// so they can be renamed into place on the same file system.
+pub fn staged_path(path: impl AsRef<Path>) -> PathBuf {
+    hidden_sibling(path, STAGED_EXTENSION)
+}
+
+/// Extension appended to the originals of files replaced by a staged run,
+/// kept until every staged file is in place.
+pub const BACKUP_EXTENSION: &str = "hermes-backup";
+
+/// # Returns
+/// Path the original of `path` is kept at while staged files are moved into place.
+pub fn backup_path(path: impl AsRef<Path>) -> PathBuf {
+    hidden_sibling(path, BACKUP_EXTENSION)
+}
+
+/// # Returns
+/// Path of a hidden file next to `path`, named after it with `extension` appended.
+fn hidden_sibling(path: impl AsRef<Path>, extension: &str) -> PathBuf {
+    let path = path.as_ref();
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{file_name}.{extension}"))
+}
+
+/// Combine updates to the same file so each file is opened and saved once.
+/// An update is merged into the previous update to the same path if they are of the same kind,
+/// have the same import options, and no other order writes to the path in between.
+/// Order of the updates is preserved.
+///
+/// # Returns
+/// Coalesced orders, with the indices of the orders each was built from.
+pub fn coalesce(
+    orders: impl IntoIterator<Item = (usize, WorkspaceOrder)>,
+) -> Vec<(Vec<usize>, WorkspaceOrder)> {
+    let mut coalesced: Vec<(Vec<usize>, WorkspaceOrder)> = vec![];
+    let mut open_updates: HashMap<PathBuf, usize> = HashMap::new();
+    for (idx, order) in orders {
+        let Some(path) = order.path().cloned() else {
+            coalesced.push((vec![idx], order));
+            continue;
+        };
+
+        let WorkspaceOrder::Update(update) = order else {
+            open_updates.remove(&path);
+            coalesced.push((vec![idx], order));
+            continue;
+        };
+
+        let update = match open_updates.get(&path) {
+            Some(&group) => {
+                let (indices, WorkspaceOrder::Update(open)) = &mut coalesced[group] else {
+                    unreachable!("open updates only index updates");
+                };
+
+                match open.merge(update) {
+                    Ok(()) => {
+                        indices.push(idx);
+                        continue;
+                    }
+                    Err(update) => update,
+                }
+            }
+            None => update,
+        };
+
+        open_updates.insert(path, coalesced.len());
+        coalesced.push((vec![idx], update.into()));
+    }
+
+    coalesced
+}
+
+/// Cells referenced by a formula, as `(sheet, (min row, min col), (max row, max col))`.
+type Region = (
+    core::data::IndexType,
+    (core::data::IndexType, core::data::IndexType),
+    (core::data::IndexType, core::data::IndexType),
+);
+
+/// References between the formulas of a dataset,
+/// so only formulas affected by a change need to be re-evaluated.
+#[derive(Clone, Debug, Default)]
+pub struct Dependencies {
+    /// Cell of each formula.
+    cells: Vec<core::data::CellPath>,
+    /// Cells referenced by each formula.
+    references: Vec<Vec<Region>>,
+}
+
+impl Dependencies {
+    /// # Arguments
+    /// + `formulas`: Cell and source of each formula.
+    /// + `resolve_sheet`: Index of a referenced sheet, given the sheet of the referencing formula.
+    ///   References to sheets that can not be resolved are ignored.
+    pub fn new<'a>(
+        formulas: impl IntoIterator<Item = (core::data::CellPath, &'a str)>,
+        resolve_sheet: impl Fn(
+            &core::data::SheetRef,
+            core::data::IndexType,
+        ) -> Option<core::data::IndexType>,
+    ) -> Self {
+        let (cells, references) = formulas
+            .into_iter()
+            .map(|(cell, value)| {
+                let references = core::expr::references(value)
+                    .into_iter()
+                    .filter_map(|reference| {
+                        let sheet = resolve_sheet(&reference.start.sheet, cell.sheet)?;
+                        let (start, end) = reference.bounds();
+                        Some((sheet, start, end))
+                    })
+                    .collect();
+                (cell, references)
+            })
+            .unzip();
+
+        Self { cells, references }
+    }
+
+    /// # Returns
+    /// Cell of the formula at index `formula`.
+    pub fn cell(&self, formula: usize) -> &core::data::CellPath {
+        &self.cells[formula]
+    }
+
+    /// # Returns
+    /// Whether the formula at index `formula` references `cell`.
+    fn references(&self, formula: usize, cell: &core::data::CellPath) -> bool {
+        self.references[formula].iter().any(|(sheet, start, end)| {
+            *sheet == cell.sheet
+                && (start.0..=end.0).contains(&cell.row)
+                && (start.1..=end.1).contains(&cell.col)
+        })
+    }
+
+    /// # Returns
+    /// Indices of the formulas depending on any of the `changed` cells, directly or transitively,
+    /// in evaluation order.
+    /// See [`Self::order`].
+    pub fn dirty(&self, changed: &[core::data::CellPath]) -> Vec<usize> {
+        let mut dirty = vec![false; self.cells.len()];
+        let mut queue = changed.iter().cloned().collect::<VecDeque<_>>();
+        while let Some(cell) = queue.pop_front() {
+            for (idx, dirty) in dirty.iter_mut().enumerate() {
+                if !*dirty && self.references(idx, &cell) {
+                    *dirty = true;
+                    queue.push_back(self.cells[idx].clone());
+                }
+            }
+        }
+
+        self.order(dirty)
+    }
+
+    /// # Returns
+    /// Indices of all formulas in evaluation order.
+    /// See [`Self::order`].
+    pub fn all(&self) -> Vec<usize> {
+        self.order(vec![true; self.cells.len()])
+    }
+
+    /// Order the selected formulas so each is evaluated after the selected formulas it references.
+    /// Formulas in, or depending on, a reference cycle are ordered last, in their original order.
+    fn order(&self, selected: Vec<bool>) -> Vec<usize> {
+        let selected = selected
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, selected)| selected.then_some(idx))
+            .collect::<Vec<_>>();
+
+        let mut pending = selected
+            .iter()
+            .map(|&idx| {
+                selected
+                    .iter()
+                    .filter(|&&dependency| self.references(idx, &self.cells[dependency]))
+                    .count()
+            })
+            .collect::<Vec<_>>();
+
+        let mut ordered = Vec::with_capacity(selected.len());
+        let mut queue = (0..selected.len())
+            .filter(|&pos| pending[pos] == 0)
+            .collect::<VecDeque<_>>();
+        while let Some(pos) = queue.pop_front() {
+            ordered.push(selected[pos]);
+            let cell = &self.cells[selected[pos]];
+            for (dependent, &idx) in selected.iter().enumerate() {
+                if pending[dependent] > 0 && self.references(idx, cell) {
+                    pending[dependent] -= 1;
+                    if pending[dependent] == 0 {
+                        queue.push_back(dependent);
+                    }
+                }
+            }
+        }
+
+        if ordered.len() < selected.len() {
+            ordered.extend(
+                selected
+                    .iter()
+                    .enumerate()
+                    .filter(|(pos, _)| pending[*pos] > 0)
+                    .map(|(_, &idx)| idx),
+            );
+        }
+
+        ordered
+    }
+}
+
+/// Value of a formula.
+pub type FormulaResult = Result<data::Data, core::expr::Error>;
+
+/// Value of a formula with the cells read to compute it.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TracedResult {
+    pub result: FormulaResult,
+    pub inputs: Vec<core::data::CellRef>,
+}
+
+/// Formulas of a dataset to evaluate together, e.g. on a background thread.
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Evaluate {
+    /// Current values of each sheet of the dataset.
+    pub sheets: Vec<EvaluateSheet>,
+
+    /// Cell and source of each formula, in evaluation order.
+    pub formulas: Vec<(core::data::CellPath, String)>,
+
+    pub options: core::expr::Options,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EvaluateSheet {
+    /// Name of the sheet, used to resolve references by label.
+    pub name: String,
+    pub cells: data::CellMap,
+}
+
+impl Evaluate {
+    /// Evaluate the formulas in order.
+    /// The result of each formula is set as the value of its cell,
+    /// so later formulas may reference the results of earlier ones.
+    ///
+    /// # Returns
+    /// Result of each formula, in order.
+    pub fn run(self) -> Vec<FormulaResult> {
+        self.run_traced()
+            .into_iter()
+            .map(|traced| traced.result)
+            .collect()
+    }
+
+    /// Evaluate the formulas in order, recording the cells each reads.
+    /// See [`Self::run`].
+    pub fn run_traced(mut self) -> Vec<TracedResult> {
+        let mut errors = BTreeMap::new();
+        let mut results = Vec::with_capacity(self.formulas.len());
+        for (cell, value) in self.formulas.iter() {
+            let inputs = RefCell::new(vec![]);
+            let ctx = core::expr::Recording {
+                ctx: EvaluateContext {
+                    sheets: &self.sheets,
+                    errors: &errors,
+                    options: self.options,
+                },
+                reads: &inputs,
+            };
+            let result = core::expr::eval(value, ctx, cell);
+
+            let idx = core::data::CellIndex::new(cell.row, cell.col);
+            if let Some(sheet) = self.sheets.get_mut(cell.sheet as usize) {
+                match &result {
+                    Ok(value) => {
+                        sheet.cells.insert(idx.clone(), value.clone());
+                        errors.remove(&(cell.sheet, idx));
+                    }
+                    Err(err) => {
+                        sheet.cells.remove(&idx);
+                        errors.insert((cell.sheet, idx), err.clone());
+                    }
+                }
+            }
+            results.push(TracedResult {
+                result,
+                inputs: inputs.into_inner(),
+            });
+        }
+
+        results
+    }
+}
+
+/// Values of the sheets being evaluated.
+#[derive(Clone, Copy)]
+struct EvaluateContext<'a> {
+    sheets: &'a [EvaluateSheet],
+    /// Errors of the formulas evaluated so far, keyed by sheet and cell.
+    errors: &'a BTreeMap<(core::data::IndexType, core::data::CellIndex), core::expr::Error>,
+    options: core::expr::Options,
+}
+
+impl<'a> core::expr::Context for EvaluateContext<'a> {
+    fn cell_value(
+        self,
+        cell_ref: &core::data::CellRef,
+        origin: &core::data::CellPath,
+    ) -> Result<core::expr::Value, core::expr::ContextError> {
+        let sheet = cell_ref.sheet.resolve(
+            origin.sheet,
+            self.sheets.iter().map(|sheet| sheet.name.as_str()),
+        );
+        let Some((sheet_idx, sheet)) =
+            sheet.and_then(|idx| self.sheets.get(idx as usize).map(|sheet| (idx, sheet)))
+        else {
+            return Err(core::expr::ContextError::CellRefDoesNotExist);
+        };
+
+        let idx = core::data::CellIndex::new(cell_ref.row, cell_ref.col);
+        if let Some(err) = self.errors.get(&(sheet_idx, idx.clone())) {
+            return Err(core::expr::ContextError::CellRefValueError(err.clone()));
+        }
+        Ok(sheet
+            .cells
+            .get(&idx)
+            .cloned()
+            .unwrap_or(core::expr::Value::Empty))
+    }
+
+    fn options(self) -> core::expr::Options {
+        self.options
+    }
+}
+
+/// Write a new file from columns of values.
+/// An existing file at the path is replaced.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Create {
+    pub path: PathBuf,
+    pub format: OutputFormat,
+    pub columns: Vec<OutputColumn>,
+
+    /// Files the columns were computed from.
+    #[serde(default)]
+    pub inputs: Vec<PathBuf>,
+}
+
+impl Create {
+    /// # Returns
+    /// Records of the file, beginning with the header.
+    /// Shorter columns are padded with empty values.
+    pub fn records(&self) -> Vec<Vec<String>> {
+        let rows = self
+            .columns
+            .iter()
+            .map(|column| column.values.len())
+            .max()
+            .unwrap_or(0);
+
+        let header = self
+            .columns
+            .iter()
+            .map(|column| column.header.clone())
+            .collect();
+
+        let mut records = Vec::with_capacity(rows + 1);
+        records.push(header);
+        for row in 0..rows {
+            let record = self
+                .columns
+                .iter()
+                .map(|column| {
+                    column
+                        .values
+                        .get(row)
+                        .map(|value| value.to_string())
+                        .unwrap_or_default()
+                })
+                .collect();
+
+            records.push(record);
+        }
+
+        records
+    }
+
+    /// # Returns
+    /// Cells of the file, beginning with the header.
+    pub fn changes(&self) -> Vec<audit::CellChange> {
+        let mut changes = vec![];
+        for (col, column) in self.columns.iter().enumerate() {
+            let Ok(col) = core::data::IndexType::try_from(col) else {
+                break;
+            };
+
+            let header = core::expr::Value::String(column.header.clone());
+            let values = std::iter::once(&header).chain(column.values.iter());
+            for (row, value) in values.enumerate() {
+                let Ok(row) = core::data::IndexType::try_from(row) else {
+                    break;
+                };
+
+                changes.push(audit::CellChange {
+                    sheet: None,
+                    cell: core::data::CellIndex::new(row, col),
+                    old: core::expr::Value::Empty,
+                    new: value.clone(),
+                    formula: None,
+                    provenance: None,
+                    outcome: audit::CellOutcome::Written,
+                });
+            }
+        }
+
+        changes.sort_by(|a, b| a.cell.cmp(&b.cell));
+        changes
+    }
+}
+
+/// Copy of a sheet with its formulas replaced by their evaluated values,
+/// to be shared without hermes.
+/// Unlike workspace orders, the original file is not modified.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EvaluatedCopy {
+    /// Suggested path of the new file.
+    pub path: PathBuf,
+    pub format: OutputFormat,
+
+    /// Values of the sheet, with errors as their [code](core::expr::Error::code).
+    pub records: Vec<Vec<String>>,
+}
+
+impl EvaluatedCopy {
+    /// # Returns
+    /// Path of the copy of the file at `path`.
+    /// e.g. `data/results.xlsx` is copied to `data/results-evaluated.csv`.
+    pub fn path_for(path: impl AsRef<Path>, format: OutputFormat) -> PathBuf {
+        let path = path.as_ref();
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+        path.with_file_name(format!("{stem}-evaluated.{}", format.extension()))
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct OutputColumn {
+    pub header: String,
+    pub values: Vec<core::expr::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum OutputFormat {
+    #[default]
+    Csv,
+    Tsv,
+}
+
+impl OutputFormat {
+    pub const ALL: &[Self] = &[Self::Csv, Self::Tsv];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Tsv => "tsv",
+        }
+    }
+
+    pub fn delimiter(&self) -> u8 {
+        match self {
+            Self::Csv => b',',
+            Self::Tsv => b'\t',
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Update {
     pub path: PathBuf,
     pub updates: Updates,
+
+    /// Options the file was imported with.
+    /// Row indices of the updates are relative to these options.
+    #[serde(default)]
+    pub options: data::ImportOptions,
+}
+
+impl Update {
+    /// Append the updates of another update to the same file.
+    ///
+    /// # Returns
+    /// `Err` with the other update if it can not be merged.
+    /// i.e. It is for a different path, of a different kind, or has different import options.
+    pub fn merge(&mut self, other: Self) -> Result<(), Self> {
+        if self.path != other.path || self.options != other.options {
+            return Err(other);
+        }
+
+        match (&mut self.updates, other.updates) {
+            (Updates::Csv(updates), Updates::Csv(other)) => updates.extend(other),
+            (Updates::Workbook(updates), Updates::Workbook(other)) => updates.extend(other),
+            (_, updates) => {
+                return Err(Self { updates, ..other });
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Serialize, Deserialize, derive_more::From, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum Updates {
     Csv(Vec<UpdateCsv>),
     Workbook(Vec<UpdateWorkbook>),
 }
 
+/// How an update treats a cell that already contains a value.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum UpdateMode {
+    /// The order fails if the cell is not empty.
+    #[default]
+    InsertOnly,
+
+    /// The existing value is replaced.
+    Overwrite,
+
+    /// The existing value is kept and the cell is reported as skipped.
+    SkipIfNonEmpty,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct UpdateCsv {
     pub row: core::data::IndexType,
     pub col: core::data::IndexType,
     pub value: core::expr::Value,
+
+    /// Formula the value was computed from.
+    #[serde(default)]
+    pub formula: Option<String>,
+
+    /// How the value was computed.
+    #[serde(default)]
+    pub provenance: Option<audit::Provenance>,
+
+    #[serde(default)]
+    pub mode: UpdateMode,
+}
+
+impl UpdateCsv {
+    /// Apply the update to a sheet.
+    ///
+    /// # Returns
+    /// Change made to the cell.
+    pub fn apply(
+        self,
+        sheet: &mut data::Spreadsheet,
+    ) -> Result<audit::CellChange, error::WorkspaceOrder> {
+        let cell = core::data::CellIndex::new(self.row, self.col);
+        let (old, outcome) = apply_update(sheet, &cell, &self.value, self.mode)?;
+        Ok(audit::CellChange {
+            sheet: None,
+            cell,
+            old,
+            new: self.value,
+            formula: self.formula,
+            provenance: self.provenance,
+            outcome,
+        })
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct UpdateWorkbook {
     pub sheet: core::data::IndexType,
     pub row: core::data::IndexType,
     pub col: core::data::IndexType,
     pub value: core::expr::Value,
+
+    /// Formula the value was computed from.
+    #[serde(default)]
+    pub formula: Option<String>,
+
+    /// How the value was computed.
+    #[serde(default)]
+    pub provenance: Option<audit::Provenance>,
+
+    #[serde(default)]
+    pub mode: UpdateMode,
+}
+
+impl UpdateWorkbook {
+    /// Apply the update to a workbook.
+    ///
+    /// # Returns
+    /// Change made to the cell.
+    pub fn apply(
+        self,
+        workbook: &mut data::Workbook,
+    ) -> Result<audit::CellChange, error::WorkspaceOrder> {
+        let sheet = workbook
+            .get_sheet_mut(self.sheet as usize)
+            .ok_or(error::WorkspaceOrder::SheetNotFound(self.sheet))?;
+
+        let cell = core::data::CellIndex::new(self.row, self.col);
+        let (old, outcome) = apply_update(sheet, &cell, &self.value, self.mode)?;
+        Ok(audit::CellChange {
+            sheet: Some(self.sheet),
+            cell,
+            old,
+            new: self.value,
+            formula: self.formula,
+            provenance: self.provenance,
+            outcome,
+        })
+    }
+}
+
+/// Set the value of a cell according to the update mode.
+/// Cells holding [`Empty`](core::expr::Value::Empty) or an empty string are considered empty.
+/// Whitespace is data, so cells holding only whitespace are not empty.
+///
+/// # Returns
+/// `(<previous value>, <outcome>)`
+fn apply_update(
+    sheet: &mut data::Spreadsheet,
+    cell: &core::data::CellIndex,
+    value: &core::expr::Value,
+    mode: UpdateMode,
+) -> Result<(core::expr::Value, audit::CellOutcome), error::WorkspaceOrder> {
+    let old = match sheet.cells().get(cell) {
+        // Blank csv fields are loaded as empty strings.
+        Some(core::expr::Value::String(old)) if old.is_empty() => core::expr::Value::Empty,
+        Some(old) => old.clone(),
+        None => core::expr::Value::Empty,
+    };
+
+    let outcome = match (&old, mode) {
+        (core::expr::Value::Empty, _) | (_, UpdateMode::Overwrite) => {
+            sheet.set(cell.clone(), value.clone());
+            audit::CellOutcome::Written
+        }
+        (_, UpdateMode::InsertOnly) => {
+            return Err(error::WorkspaceOrder::CellOccupied(cell.clone()));
+        }
+        (_, UpdateMode::SkipIfNonEmpty) => audit::CellOutcome::Skipped,
+    };
+
+    Ok((old, outcome))
+}
+
+/// Formula cells of a file, used to [build](build_updates) the orders writing their values.
+#[derive(Clone, Debug)]
+pub struct FormulaFile {
+    pub path: PathBuf,
+
+    /// Options the file was imported with.
+    pub options: data::ImportOptions,
+
+    /// Whether the file is a workbook, otherwise a csv file.
+    pub workbook: bool,
+    pub cells: Vec<FormulaCell>,
+}
+
+/// Computed value of a formula cell.
+#[derive(Clone, Debug)]
+pub struct FormulaCell {
+    /// Sheet index, ignored for csv files.
+    pub sheet: core::data::IndexType,
+    pub cell: core::data::CellIndex,
+    pub value: core::expr::Value,
+
+    /// Value of the cell in the file, [`Empty`](core::expr::Value::Empty) if it has none.
+    pub on_disk: core::expr::Value,
+    pub formula: String,
+    pub provenance: Option<audit::Provenance>,
+}
+
+impl FormulaCell {
+    /// # Returns
+    /// Mode writing the value over the cell on disk.
+    /// Formulas are only created in empty cells,
+    /// so a value on disk is a previous result of the formula and is overwritten.
+    fn mode(&self) -> UpdateMode {
+        if self.on_disk == core::expr::Value::Empty {
+            UpdateMode::InsertOnly
+        } else {
+            UpdateMode::Overwrite
+        }
+    }
+}
+
+/// Build the orders writing formula values that differ from those on disk.
+/// A single update is built for each file, with its cells in order.
+/// Files without changes are omitted.
+/// Files are ordered by their first appearance.
+pub fn build_updates(files: impl IntoIterator<Item = FormulaFile>) -> Vec<WorkspaceOrder> {
+    let mut updates: Vec<Update> = vec![];
+    for file in files {
+        let mut cells = file
+            .cells
+            .into_iter()
+            .filter(|cell| cell.value != cell.on_disk)
+            .collect::<Vec<_>>();
+        if cells.is_empty() {
+            continue;
+        }
+        cells.sort_by(|a, b| a.sheet.cmp(&b.sheet).then_with(|| a.cell.cmp(&b.cell)));
+
+        let file_updates = if file.workbook {
+            Updates::Workbook(
+                cells
+                    .into_iter()
+                    .map(|cell| UpdateWorkbook {
+                        mode: cell.mode(),
+                        sheet: cell.sheet,
+                        row: cell.cell.row(),
+                        col: cell.cell.col(),
+                        value: cell.value,
+                        formula: Some(cell.formula),
+                        provenance: cell.provenance,
+                    })
+                    .collect(),
+            )
+        } else {
+            Updates::Csv(
+                cells
+                    .into_iter()
+                    .map(|cell| UpdateCsv {
+                        mode: cell.mode(),
+                        row: cell.cell.row(),
+                        col: cell.cell.col(),
+                        value: cell.value,
+                        formula: Some(cell.formula),
+                        provenance: cell.provenance,
+                    })
+                    .collect(),
+            )
+        };
+
+        let mut update = Update {
+            path: file.path,
+            updates: file_updates,
+            options: file.options,
+        };
+        if let Some(existing) = updates
+            .iter_mut()
+            .find(|existing| existing.path == update.path)
+        {
+            match existing.merge(update) {
+                Ok(()) => continue,
+                Err(other) => update = other,
+            }
+        }
+        updates.push(update);
+    }
+
+    updates.into_iter().map(WorkspaceOrder::Update).collect()
+}
+
+/// Apply cleaning operations to a csv file.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Clean {
+    pub path: PathBuf,
+
+    /// Options the file was imported with.
+    #[serde(default)]
+    pub options: data::ImportOptions,
+
+    /// Whether the first data row holds column headers.
+    #[serde(default)]
+    pub header: bool,
+    pub operations: Vec<clean::Operation>,
 }
 
 pub mod error {
     use crate::data;
+    use hermes_core as core;
     use serde::{Deserialize, Serialize};
     use std::io;
 
     #[derive(Serialize, Deserialize, Clone, Debug)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 
     pub enum WorkspaceOrder {
         /// The task could not be completed.
         TaskNotCompleted,
         /// File could not be opened.
-        OpenFile(#[serde(with = "io_error_serde::ErrorKind")] io::ErrorKind),
+        OpenFile(
+            #[serde(with = "io_error_serde::ErrorKind")]
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
+            io::ErrorKind,
+        ),
         /// File could not be saved.
-        Save(#[serde(with = "io_error_serde::ErrorKind")] io::ErrorKind),
+        Save(
+            #[serde(with = "io_error_serde::ErrorKind")]
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
+            io::ErrorKind,
+        ),
+        /// File exceeds the sheet size limits.
+        DataTooLarge,
+        /// File is protected from modification.
+        Protected,
+        /// File is read-only or locked by another process.
+        ReadOnly,
+        /// A value was to be written to a cell that already contains data.
+        CellOccupied(core::data::CellIndex),
+        /// The sheet does not exist in the workbook.
+        SheetNotFound(core::data::IndexType),
+        /// The order is not yet supported.
+        Unsupported,
     }
 
     impl From<data::error::LoadCsv> for WorkspaceOrder {
         fn from(value: data::error::LoadCsv) -> Self {
             match value {
                 data::error::LoadCsv::Io(err) => Self::OpenFile(err),
-                data::error::LoadCsv::DataTooLarge => todo!(),
+                data::error::LoadCsv::DataTooLarge { .. } | data::error::LoadCsv::SheetTooLarge => {
+                    Self::DataTooLarge
+                }
+                data::error::LoadCsv::Cancelled => Self::TaskNotCompleted,
+                data::error::LoadCsv::InvalidUtf8 { .. }
+                | data::error::LoadCsv::UnequalLengths { .. }
+                | data::error::LoadCsv::Read(_) => Self::OpenFile(io::ErrorKind::InvalidData),
             }
         }
     }
@@ -65,7 +881,561 @@ pub mod error {
         fn from(value: data::error::SaveCsv) -> Self {
             match value {
                 data::error::SaveCsv::Io(err) => Self::Save(err),
+                data::error::SaveCsv::UnequalLengths { .. } | data::error::SaveCsv::Write(_) => {
+                    Self::TaskNotCompleted
+                }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn evaluate_run() {
+        use hermes_core::expr::Value;
+
+        let cell = |sheet, row, col| core::data::CellPath { sheet, row, col };
+        let evaluate = Evaluate {
+            sheets: vec![
+                EvaluateSheet {
+                    name: "data".to_string(),
+                    cells: data::CellMap::from([(
+                        core::data::CellIndex::new(0u8, 0u8),
+                        Value::Int(2),
+                    )]),
+                },
+                EvaluateSheet {
+                    name: "results".to_string(),
+                    cells: data::CellMap::new(),
+                },
+            ],
+            formulas: vec![
+                (cell(0, 0, 1), "A1 * 2".to_string()),
+                (cell(1, 0, 0), "data!B1 + 1".to_string()),
+                (cell(1, 0, 1), "'a' + 1".to_string()),
+                (cell(1, 0, 2), "B1".to_string()),
+                (cell(1, 0, 3), "iserror(C1)".to_string()),
+                (cell(0, 0, 2), "results!A1".to_string()),
+            ],
+            options: Default::default(),
+        };
+
+        let results = evaluate.run();
+        assert_eq!(results.len(), 6);
+        assert!(matches!(results[0], Ok(Value::Int(4))));
+        assert!(matches!(results[1], Ok(Value::Int(5))));
+        assert!(matches!(
+            results[2],
+            Err(core::expr::Error::InvalidOperation(_))
+        ));
+        assert!(matches!(
+            results[3],
+            Err(core::expr::Error::InvalidOperation(_))
+        ));
+        assert!(matches!(results[4], Ok(Value::Bool(true))));
+        assert!(matches!(results[5], Ok(Value::Int(5))));
+    }
+
+    #[test]
+    fn evaluate_run_traced() {
+        use hermes_core::expr::Value;
+
+        let cell = |row, col| core::data::CellPath { sheet: 0, row, col };
+        let evaluate = Evaluate {
+            sheets: vec![EvaluateSheet {
+                name: "data".to_string(),
+                cells: data::CellMap::from([(core::data::CellIndex::new(0u8, 0u8), Value::Int(2))]),
+            }],
+            formulas: vec![
+                (cell(0, 1), "A1 * 2".to_string()),
+                (cell(0, 2), "A1 + B1 + A1".to_string()),
+                (cell(0, 3), "1".to_string()),
+            ],
+            options: Default::default(),
+        };
+
+        let results = evaluate.run_traced();
+        assert!(matches!(results[1].result, Ok(Value::Int(8))));
+        let a1 = core::data::CellRef::from_str("A1").unwrap();
+        let b1 = core::data::CellRef::from_str("B1").unwrap();
+        assert_eq!(results[0].inputs, vec![a1.clone()]);
+        assert_eq!(results[1].inputs, vec![a1, b1]);
+        assert!(results[2].inputs.is_empty());
+    }
+
+    #[test]
+    fn evaluate_unknown_sheet() {
+        use hermes_core::expr::Value;
+
+        let cell = |row, col| core::data::CellPath { sheet: 0, row, col };
+        let evaluate = Evaluate {
+            sheets: vec![EvaluateSheet {
+                name: "data".to_string(),
+                cells: data::CellMap::from([(core::data::CellIndex::new(0u8, 0u8), Value::Int(2))]),
+            }],
+            formulas: vec![
+                (cell(0, 1), "data!A1".to_string()),
+                (cell(0, 2), "other!A1".to_string()),
+                (cell(0, 3), "1!A1".to_string()),
+            ],
+            options: Default::default(),
+        };
+
+        let results = evaluate.run();
+        assert!(matches!(results[0], Ok(Value::Int(2))));
+        assert!(matches!(
+            results[1],
+            Err(core::expr::Error::InvalidCellRef(_))
+        ));
+        assert!(matches!(
+            results[2],
+            Err(core::expr::Error::InvalidCellRef(_))
+        ));
+    }
+
+    #[test]
+    fn dependencies_dirty() {
+        let cell = |row, col| core::data::CellPath { sheet: 0, row, col };
+        let formulas = [
+            (cell(0, 2), "B1 * 2"),
+            (cell(0, 1), "A1 + 1"),
+            (cell(0, 3), "5"),
+            (cell(1, 0), "sum!A1 + 1"),
+            (cell(0, 4), "A1:C1"),
+        ];
+        let dependencies = Dependencies::new(formulas, |sheet, origin| match sheet {
+            core::data::SheetRef::Relative => Some(origin),
+            core::data::SheetRef::Absolute(_) => None,
+        });
+
+        assert_eq!(dependencies.dirty(&[cell(0, 0)]), vec![1, 0, 4]);
+        assert_eq!(dependencies.dirty(&[cell(0, 2)]), vec![4]);
+        assert_eq!(dependencies.dirty(&[cell(0, 3)]), Vec::<usize>::new());
+        assert_eq!(dependencies.dirty(&[cell(5, 5)]), Vec::<usize>::new());
+        assert_eq!(dependencies.all(), vec![1, 2, 3, 0, 4]);
+    }
+
+    #[test]
+    fn dependencies_cycle() {
+        let cell = |row, col| core::data::CellPath { sheet: 0, row, col };
+        let formulas = [
+            (cell(0, 0), "B1 + 1"),
+            (cell(0, 1), "A1 + 1"),
+            (cell(0, 2), "A1"),
+            (cell(0, 3), "D2"),
+        ];
+        let dependencies = Dependencies::new(formulas, |_, origin| Some(origin));
+
+        assert_eq!(dependencies.all(), vec![3, 0, 1, 2]);
+        assert_eq!(dependencies.dirty(&[cell(0, 0)]), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn evaluated_copy_path() {
+        assert_eq!(
+            EvaluatedCopy::path_for("data/results.xlsx", OutputFormat::Csv),
+            PathBuf::from("data/results-evaluated.csv")
+        );
+        assert_eq!(
+            EvaluatedCopy::path_for("results.csv", OutputFormat::Tsv),
+            PathBuf::from("results-evaluated.tsv")
+        );
+    }
+
+    #[test]
+    fn create_records() {
+        use hermes_core::expr::Value;
+
+        let create = Create {
+            path: PathBuf::from("out.csv"),
+            format: OutputFormat::Csv,
+            columns: vec![
+                OutputColumn {
+                    header: "a".to_string(),
+                    values: vec![Value::Int(1), Value::Int(2)],
+                },
+                OutputColumn {
+                    header: "b".to_string(),
+                    values: vec![Value::String("x".to_string())],
+                },
+            ],
+            inputs: vec![],
+        };
+
+        assert_eq!(
+            create.records(),
+            vec![
+                vec!["a".to_string(), "b".to_string()],
+                vec!["1".to_string(), "x".to_string()],
+                vec!["2".to_string(), String::new()],
+            ]
+        );
+
+        let changes = create.changes();
+        assert_eq!(changes.len(), 5);
+        assert_eq!(changes[1].cell, core::data::CellIndex::new(0u8, 1u8));
+        assert_eq!(changes[1].new, Value::String("b".to_string()));
+        assert_eq!(changes[4].cell, core::data::CellIndex::new(2u8, 0u8));
+    }
+
+    #[test]
+    fn update_modes() {
+        use hermes_core::expr::Value;
+
+        let update = |col: u8, value: i64, mode| UpdateCsv {
+            row: 0u8.into(),
+            col: col.into(),
+            value: Value::Int(value),
+            formula: None,
+            provenance: None,
+            mode,
+        };
+
+        let mut sheet = data::Spreadsheet::new();
+        sheet.set(core::data::CellIndex::new(0u8, 0u8), Value::Int(1));
+        sheet.set(core::data::CellIndex::new(0u8, 1u8), Value::Empty);
+        sheet.set(
+            core::data::CellIndex::new(0u8, 2u8),
+            Value::String(String::new()),
+        );
+        sheet.set(
+            core::data::CellIndex::new(0u8, 3u8),
+            Value::String(" ".to_string()),
+        );
+
+        assert!(matches!(
+            update(0, 2, UpdateMode::InsertOnly).apply(&mut sheet),
+            Err(error::WorkspaceOrder::CellOccupied(_))
+        ));
+
+        let change = update(1, 2, UpdateMode::InsertOnly)
+            .apply(&mut sheet)
+            .unwrap();
+        assert_eq!(change.outcome, audit::CellOutcome::Written);
+        assert_eq!(change.old, Value::Empty);
+
+        let change = update(2, 2, UpdateMode::InsertOnly)
+            .apply(&mut sheet)
+            .unwrap();
+        assert_eq!(change.outcome, audit::CellOutcome::Written);
+        assert_eq!(change.old, Value::Empty);
+
+        assert!(matches!(
+            update(3, 2, UpdateMode::InsertOnly).apply(&mut sheet),
+            Err(error::WorkspaceOrder::CellOccupied(_))
+        ));
+
+        let change = update(3, 2, UpdateMode::SkipIfNonEmpty)
+            .apply(&mut sheet)
+            .unwrap();
+        assert_eq!(change.outcome, audit::CellOutcome::Skipped);
+        assert_eq!(
+            sheet.cells().get(&core::data::CellIndex::new(0u8, 3u8)),
+            Some(&Value::String(" ".to_string()))
+        );
+
+        let change = update(0, 3, UpdateMode::SkipIfNonEmpty)
+            .apply(&mut sheet)
+            .unwrap();
+        assert_eq!(change.outcome, audit::CellOutcome::Skipped);
+        assert_eq!(
+            sheet.cells().get(&core::data::CellIndex::new(0u8, 0u8)),
+            Some(&Value::Int(1))
+        );
+
+        let change = update(0, 4, UpdateMode::Overwrite)
+            .apply(&mut sheet)
+            .unwrap();
+        assert_eq!(change.outcome, audit::CellOutcome::Written);
+        assert_eq!(change.old, Value::Int(1));
+        assert_eq!(
+            sheet.cells().get(&core::data::CellIndex::new(0u8, 0u8)),
+            Some(&Value::Int(4))
+        );
+    }
+
+    #[test]
+    fn coalesce_updates() {
+        use hermes_core::expr::Value;
+
+        let csv = |path: &str, col: u8| -> WorkspaceOrder {
+            Update {
+                path: PathBuf::from(path),
+                updates: Updates::Csv(vec![UpdateCsv {
+                    row: 0u8.into(),
+                    col: col.into(),
+                    value: Value::Int(col.into()),
+                    formula: None,
+                    provenance: None,
+                    mode: UpdateMode::InsertOnly,
+                }]),
+                options: data::ImportOptions::default(),
+            }
+            .into()
+        };
+        let workbook = |path: &str, col: u8| -> WorkspaceOrder {
+            Update {
+                path: PathBuf::from(path),
+                updates: Updates::Workbook(vec![UpdateWorkbook {
+                    sheet: 0u8.into(),
+                    row: 0u8.into(),
+                    col: col.into(),
+                    value: Value::Int(col.into()),
+                    formula: None,
+                    provenance: None,
+                    mode: UpdateMode::InsertOnly,
+                }]),
+                options: data::ImportOptions::default(),
+            }
+            .into()
+        };
+        let create = |path: &str| -> WorkspaceOrder {
+            Create {
+                path: PathBuf::from(path),
+                format: OutputFormat::Csv,
+                columns: vec![],
+                inputs: vec![],
+            }
+            .into()
+        };
+
+        let orders = vec![
+            csv("a.csv", 0),
+            workbook("b.xlsx", 0),
+            csv("a.csv", 1),
+            workbook("b.xlsx", 1),
+            csv("b.xlsx", 2),
+            create("a.csv"),
+            csv("a.csv", 3),
+        ];
+        let coalesced = coalesce(orders.into_iter().enumerate());
+        let indices = coalesced
+            .iter()
+            .map(|(indices, _)| indices.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            indices,
+            vec![vec![0, 2], vec![1, 3], vec![4], vec![5], vec![6]]
+        );
+
+        let WorkspaceOrder::Update(Update {
+            updates: Updates::Csv(updates),
+            ..
+        }) = &coalesced[0].1
+        else {
+            panic!("expected csv update");
+        };
+        assert_eq!(
+            updates.iter().map(|update| update.col).collect::<Vec<_>>(),
+            vec![
+                core::data::IndexType::from(0u8),
+                core::data::IndexType::from(1u8)
+            ]
+        );
+
+        let WorkspaceOrder::Update(Update {
+            updates: Updates::Workbook(updates),
+            ..
+        }) = &coalesced[1].1
+        else {
+            panic!("expected workbook update");
+        };
+        assert_eq!(updates.len(), 2);
+    }
+
+    #[test]
+    fn coalesce_mixed_batch() {
+        use hermes_core::expr::Value;
+
+        let update = |path: &str, workbook: bool, options: data::ImportOptions| -> WorkspaceOrder {
+            let updates = if workbook {
+                Updates::Workbook(vec![UpdateWorkbook {
+                    sheet: 0u8.into(),
+                    row: 0u8.into(),
+                    col: 0u8.into(),
+                    value: Value::Int(1),
+                    formula: None,
+                    provenance: None,
+                    mode: UpdateMode::InsertOnly,
+                }])
+            } else {
+                Updates::Csv(vec![UpdateCsv {
+                    row: 0u8.into(),
+                    col: 0u8.into(),
+                    value: Value::Int(1),
+                    formula: None,
+                    provenance: None,
+                    mode: UpdateMode::InsertOnly,
+                }])
+            };
+
+            Update {
+                path: PathBuf::from(path),
+                updates,
+                options,
+            }
+            .into()
+        };
+        let semicolon = data::ImportOptions {
+            dialect: data::Dialect {
+                delimiter: b';',
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let orders = vec![
+            update("a.csv", false, Default::default()),
+            update("b.xlsx", true, Default::default()),
+            update("c.xlsx", true, Default::default()),
+            update("a.csv", false, semicolon.clone()),
+            update("b.xlsx", true, Default::default()),
+            update("a.csv", false, semicolon),
+            update("c.xlsx", true, Default::default()),
+        ];
+        let coalesced = coalesce(orders.into_iter().enumerate());
+        let groups = coalesced
+            .iter()
+            .map(|(indices, order)| {
+                let WorkspaceOrder::Update(update) = order else {
+                    panic!("expected update");
+                };
+                let count = match &update.updates {
+                    Updates::Csv(updates) => updates.len(),
+                    Updates::Workbook(updates) => updates.len(),
+                };
+                (indices.clone(), update.path.clone(), count)
+            })
+            .collect::<Vec<_>>();
+
+        // updates with different import options are not merged, and replace the open update
+        assert_eq!(
+            groups,
+            vec![
+                (vec![0], PathBuf::from("a.csv"), 1),
+                (vec![1, 4], PathBuf::from("b.xlsx"), 2),
+                (vec![2, 6], PathBuf::from("c.xlsx"), 2),
+                (vec![3, 5], PathBuf::from("a.csv"), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn staged_path_test() {
+        assert_eq!(
+            staged_path("data/out.csv"),
+            PathBuf::from("data/.out.csv.hermes-staged")
+        );
+        assert_eq!(
+            backup_path("data/out.csv"),
+            PathBuf::from("data/.out.csv.hermes-backup")
+        );
+    }
+
+    #[test]
+    fn build_updates_writes_changed_values() {
+        use hermes_core::expr::Value;
+
+        let cell = |sheet, row: u8, col: u8, value, on_disk| FormulaCell {
+            sheet,
+            cell: core::data::CellIndex::new(row, col),
+            value,
+            on_disk,
+            formula: "A1".to_string(),
+            provenance: None,
+        };
+        let csv = FormulaFile {
+            path: PathBuf::from("data.csv"),
+            options: data::ImportOptions::default(),
+            workbook: false,
+            cells: vec![
+                cell(0, 2, 1, Value::Int(3), Value::Empty),
+                cell(0, 0, 1, Value::Int(1), Value::Int(1)),
+                cell(0, 1, 1, Value::Int(2), Value::Int(4)),
+            ],
+        };
+        let unchanged = FormulaFile {
+            path: PathBuf::from("unchanged.csv"),
+            options: data::ImportOptions::default(),
+            workbook: false,
+            cells: vec![cell(0, 0, 0, Value::Bool(true), Value::Bool(true))],
+        };
+        let workbook = FormulaFile {
+            path: PathBuf::from("data.xlsx"),
+            options: data::ImportOptions::default(),
+            workbook: true,
+            cells: vec![
+                cell(1, 0, 0, Value::Int(1), Value::Empty),
+                cell(0, 3, 0, Value::Int(2), Value::Empty),
+            ],
+        };
+
+        let orders = build_updates([csv, unchanged, workbook]);
+        assert_eq!(orders.len(), 2);
+
+        let WorkspaceOrder::Update(Update {
+            path,
+            updates: Updates::Csv(updates),
+            ..
+        }) = &orders[0]
+        else {
+            panic!("expected csv update");
+        };
+        assert_eq!(path, &PathBuf::from("data.csv"));
+        let cells = updates
+            .iter()
+            .map(|update| (update.row, update.mode))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cells,
+            vec![(1, UpdateMode::Overwrite), (2, UpdateMode::InsertOnly)]
+        );
+
+        let WorkspaceOrder::Update(Update {
+            updates: Updates::Workbook(updates),
+            ..
+        }) = &orders[1]
+        else {
+            panic!("expected workbook update");
+        };
+        let cells = updates
+            .iter()
+            .map(|update| (update.sheet, update.row))
+            .collect::<Vec<_>>();
+        assert_eq!(cells, vec![(0, 3), (1, 0)]);
+    }
+
+    #[test]
+    fn build_updates_groups_files() {
+        use hermes_core::expr::Value;
+
+        let file = |row: u8| FormulaFile {
+            path: PathBuf::from("data.csv"),
+            options: data::ImportOptions::default(),
+            workbook: false,
+            cells: vec![FormulaCell {
+                sheet: 0,
+                cell: core::data::CellIndex::new(row, 0u8),
+                value: Value::Int(1),
+                on_disk: Value::Empty,
+                formula: "1".to_string(),
+                provenance: None,
+            }],
+        };
+
+        let orders = build_updates([file(0), file(1)]);
+        assert_eq!(orders.len(), 1);
+        let WorkspaceOrder::Update(Update {
+            updates: Updates::Csv(updates),
+            ..
+        }) = &orders[0]
+        else {
+            panic!("expected csv update");
+        };
+        assert_eq!(updates.len(), 2);
+        assert!(build_updates(Vec::<FormulaFile>::new()).is_empty());
+    }
+}
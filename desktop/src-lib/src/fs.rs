@@ -1,15 +1,38 @@
 use serde::{Deserialize, Serialize};
-use std::{collections::BTreeSet, ffi::OsString};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ffi::{OsStr, OsString},
+    path::PathBuf,
+};
 
 #[cfg(feature = "fs")]
-use std::{fs, path::Path};
+use std::{fs, io, path::Path};
+
+#[cfg(feature = "fs")]
+pub mod archive;
+#[cfg(feature = "fs")]
+pub mod ignore;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct Directory {
     #[serde(with = "serde_os_string")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
     pub name: OsString,
     #[serde(with = "serde_os_string_seq")]
+    #[cfg_attr(feature = "schema", schemars(with = "BTreeSet<String>"))]
     pub files: BTreeSet<OsString>,
+    #[serde(default)]
+    pub kind: DirectoryKind,
+
+    /// Metadata of files, keyed by file name.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, FileMetadata>,
+
+    /// The directory's contents have not been read yet.
+    /// See [`DirectoryTree::from_file_system_with_depth`].
+    #[serde(default)]
+    pub pending: bool,
 }
 
 impl Directory {
@@ -17,6 +40,9 @@ impl Directory {
         Self {
             name: name.into(),
             files: BTreeSet::new(),
+            kind: DirectoryKind::Folder,
+            metadata: BTreeMap::new(),
+            pending: false,
         }
     }
 
@@ -27,12 +53,213 @@ impl Directory {
         Self {
             name: name.into(),
             files: BTreeSet::from_iter(files),
+            kind: DirectoryKind::Folder,
+            metadata: BTreeMap::new(),
+            pending: false,
+        }
+    }
+
+    pub fn new_with_kind(name: impl Into<OsString>, kind: DirectoryKind) -> Self {
+        Self {
+            name: name.into(),
+            files: BTreeSet::new(),
+            kind,
+            metadata: BTreeMap::new(),
+            pending: false,
+        }
+    }
+
+    /// # Returns
+    /// Metadata of the file, if it was collected.
+    pub fn file_metadata(&self, file: impl AsRef<OsStr>) -> Option<&FileMetadata> {
+        self.metadata.get(file.as_ref().to_string_lossy().as_ref())
+    }
+
+    /// # Returns
+    /// If the directory's contents may not be modified.
+    pub fn is_read_only(&self) -> bool {
+        self.kind.is_archive()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum DirectoryKind {
+    /// A file system folder.
+    #[default]
+    Folder,
+
+    /// An archive file, browsed as a directory.
+    Archive,
+
+    /// A folder within an archive.
+    ArchiveFolder,
+
+    /// A symbolic link to a folder.
+    Link,
+}
+
+impl DirectoryKind {
+    /// If the directory is an archive or contained in one.
+    pub fn is_archive(&self) -> bool {
+        matches!(self, Self::Archive | Self::ArchiveFolder)
+    }
+}
+
+/// Metadata collected for a file.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FileMetadata {
+    /// Size in bytes.
+    pub size: u64,
+
+    /// The file is a symbolic link.
+    /// `size` is that of the link's target.
+    #[serde(default)]
+    pub is_link: bool,
+}
+
+/// How symbolic links are handled when reading a directory tree from the file system.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    /// Links are not included.
+    Skip,
+
+    /// Links are followed.
+    /// Each directory is read at most once, so links back into the tree are not descended into.
+    #[default]
+    Follow,
+}
+
+/// # Returns
+/// If the file exists but can not be written to.
+/// e.g. It is marked read-only, or is locked by another process.
+#[cfg(feature = "fs")]
+pub fn is_read_only(path: impl AsRef<Path>) -> bool {
+    let path = path.as_ref();
+    match fs::metadata(path) {
+        Err(_) => false,
+        Ok(metadata) if metadata.is_dir() => false,
+        Ok(metadata) if metadata.permissions().readonly() => true,
+        Ok(_) => match fs::OpenOptions::new().write(true).open(path) {
+            Ok(_) => false,
+            Err(err) => err.kind() != io::ErrorKind::NotFound,
+        },
+    }
+}
+
+/// Project opened when Hermes is launched with a path,
+/// e.g. from the command line or the system's "Open with".
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Launch {
+    pub root: PathBuf,
+
+    /// File to open, relative to the root.
+    pub file: Option<PathBuf>,
+}
+
+impl Launch {
+    /// A folder is opened as the root.
+    /// A file's parent folder is opened as the root, with the file opened in it.
+    #[cfg(feature = "fs")]
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, error::FromFileSystem> {
+        let path =
+            std::path::absolute(path.as_ref()).map_err(|err| error::FromFileSystem::ReadDir {
+                path: path.as_ref().to_path_buf(),
+                error: err.kind(),
+            })?;
+
+        if !path.exists() {
+            return Err(error::FromFileSystem::RootNotFound);
+        }
+        if path.is_dir() {
+            return Ok(Self {
+                root: path,
+                file: None,
+            });
+        }
+
+        let (Some(root), Some(file)) = (path.parent(), path.file_name()) else {
+            return Err(error::FromFileSystem::RootNotADirectory);
+        };
+        Ok(Self {
+            root: root.to_path_buf(),
+            file: Some(PathBuf::from(file)),
+        })
+    }
+}
+
+/// Options for reading a directory tree from the file system.
+#[cfg(feature = "fs")]
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    /// Maximum depth to read.
+    /// `None` to read the entire tree.
+    /// See [`DirectoryTree::from_file_system_with_depth`].
+    pub max_depth: Option<usize>,
+
+    pub symlinks: SymlinkPolicy,
+
+    /// Ignore rules to apply.
+    /// If `None`, the [ignore rules](ignore) of the root are used.
+    pub ignore: Option<::ignore::gitignore::Gitignore>,
+}
+
+/// Event emitted to the ui with the paths of files that were created or modified.
+pub const FILES_MODIFIED_EVENT: &str = "files-modified";
+
+/// File extensions of data files Hermes can work with.
+pub const SUPPORTED_EXTENSIONS: &[&str] = &["csv", "tsv", "xlsx", "xls", "ods", "parquet"];
+
+/// Kind of a data file, detected from its extension.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    Csv,
+    Tsv,
+    Xlsx,
+    Xls,
+    Ods,
+    Parquet,
+    Other,
+}
+
+impl FileKind {
+    pub fn from_path(path: impl AsRef<std::path::Path>) -> Self {
+        let Some(ext) = path.as_ref().extension() else {
+            return Self::Other;
+        };
+
+        match ext.to_string_lossy().to_ascii_lowercase().as_str() {
+            "csv" => Self::Csv,
+            "tsv" => Self::Tsv,
+            "xlsx" => Self::Xlsx,
+            "xls" => Self::Xls,
+            "ods" => Self::Ods,
+            "parquet" => Self::Parquet,
+            _ => Self::Other,
+        }
+    }
+
+    /// # Returns
+    /// Short label for the kind.
+    /// `None` for [`Self::Other`].
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            Self::Csv => Some("csv"),
+            Self::Tsv => Some("tsv"),
+            Self::Xlsx => Some("xlsx"),
+            Self::Xls => Some("xls"),
+            Self::Ods => Some("ods"),
+            Self::Parquet => Some("parquet"),
+            Self::Other => None,
         }
     }
 }
 
 /// Directory tree graph.
 #[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DirectoryTree {
     directories: Vec<Directory>,
 
@@ -260,13 +487,58 @@ impl DirectoryTree {
             .map(|ancestor| self.get(ancestor).unwrap().name.clone())
             .collect()
     }
+
+    /// # Returns
+    /// Paths of all files in the graph, relative to the graph root.
+    pub fn file_paths(&self) -> Vec<PathBuf> {
+        self.directories
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, directory)| {
+                let path = self.path(idx).into_iter().skip(1).collect::<PathBuf>();
+                directory
+                    .files
+                    .iter()
+                    .map(move |file| path.join(file))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
 }
 
 #[cfg(feature = "fs")]
 impl DirectoryTree {
     /// Create a `DirectoryTree` from a file system path.
+    /// Archives are included as directories.
     pub fn from_file_system(path: impl AsRef<Path>) -> Result<Self, error::FromFileSystem> {
-        use std::collections::VecDeque;
+        Self::from_file_system_with_depth(path, None)
+    }
+
+    /// Create a `DirectoryTree` from a file system path, reading at most `max_depth` levels.
+    /// The root is at depth `0`.
+    /// Directories below `max_depth` are included, but marked as `pending` without contents.
+    /// Archives are included as directories.
+    /// Paths matching the [ignore rules](ignore) of `path` are skipped.
+    pub fn from_file_system_with_depth(
+        path: impl AsRef<Path>,
+        max_depth: Option<usize>,
+    ) -> Result<Self, error::FromFileSystem> {
+        Self::from_file_system_with_options(
+            path,
+            ReadOptions {
+                max_depth,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Create a `DirectoryTree` from a file system path.
+    /// See [`ReadOptions`] and [`Self::from_file_system_with_depth`].
+    pub fn from_file_system_with_options(
+        path: impl AsRef<Path>,
+        options: ReadOptions,
+    ) -> Result<Self, error::FromFileSystem> {
+        use std::collections::{HashSet, VecDeque};
 
         let path = path.as_ref();
         if !path.exists() {
@@ -276,17 +548,69 @@ impl DirectoryTree {
             return Err(error::FromFileSystem::RootNotADirectory);
         }
 
+        let ReadOptions {
+            max_depth,
+            symlinks,
+            ignore: matcher,
+        } = options;
+        let matcher = matcher.unwrap_or_else(|| ignore::matcher(path));
+
+        // Classify an entry as `(is_dir, is_link)`.
+        // `None` if the entry should not be included.
+        let classify = |entry: &fs::DirEntry| {
+            let kind = entry.file_type().ok()?;
+            if kind.is_symlink() {
+                match symlinks {
+                    SymlinkPolicy::Skip => None,
+                    SymlinkPolicy::Follow => {
+                        // broken links are excluded
+                        let target = fs::metadata(entry.path()).ok()?;
+                        (target.is_dir() || target.is_file()).then_some((target.is_dir(), true))
+                    }
+                }
+            } else if kind.is_dir() || kind.is_file() {
+                Some((kind.is_dir(), false))
+            } else {
+                None
+            }
+        };
+
+        let mut visited = HashSet::new();
+        if let Ok(id) = directory_id(path) {
+            visited.insert(id);
+        }
+
         let mut directories = vec![];
         let mut parents = vec![];
         let mut parent_map = vec![];
+        let mut archives = vec![];
         let mut is_root = true;
         let mut unexplored = VecDeque::new();
-        unexplored.push_back(path.to_path_buf());
-        while let Some(active) = unexplored.pop_front() {
+        unexplored.push_back((path.to_path_buf(), 0, false));
+        while let Some((active, depth, is_link)) = unexplored.pop_front() {
             let name = active
                 .file_name()
                 .map(|name| name.to_os_string())
                 .unwrap_or("/".into());
+            let kind = if is_link {
+                DirectoryKind::Link
+            } else {
+                DirectoryKind::Folder
+            };
+
+            if max_depth.is_some_and(|max_depth| depth > max_depth) {
+                let mut directory = Directory::new_with_kind(name, kind);
+                directory.pending = true;
+                directories.push(directory);
+
+                let parent_map_idx = parent_map
+                    .iter()
+                    .position(|(child, _)| *child == active)
+                    .unwrap();
+                let (_, parent) = parent_map.remove(parent_map_idx);
+                parents.push(parent);
+                continue;
+            }
 
             let entries = fs::read_dir(&active)
                 .map_err(|err| error::FromFileSystem::ReadDir {
@@ -294,28 +618,74 @@ impl DirectoryTree {
                     error: err.kind(),
                 })?
                 .filter_map(|entry| entry.ok())
+                .filter_map(|entry| {
+                    let (is_dir, is_link) = classify(&entry)?;
+                    (!ignore::is_ignored(&matcher, entry.path(), is_dir))
+                        .then_some((entry, is_dir, is_link))
+                })
                 .collect::<Vec<_>>();
 
             let children = entries
                 .iter()
-                .filter_map(|entry| {
-                    entry
-                        .file_type()
-                        .ok()
-                        .map(|kind| kind.is_dir().then_some(entry.path()))
-                        .flatten()
+                .filter(|(_, is_dir, _)| *is_dir)
+                .filter_map(|(entry, _, is_link)| {
+                    let path = entry.path();
+                    // Directories without an id can not be tracked, but are still read.
+                    if let Ok(id) = directory_id(&path) {
+                        if !visited.insert(id) {
+                            #[cfg(feature = "tracing")]
+                            tracing::debug!(?path, "skipping previously read directory");
+                            return None;
+                        }
+                    }
+
+                    Some((path, *is_link))
                 })
                 .collect::<Vec<_>>();
-            let files = entries.iter().filter_map(|entry| {
-                entry
-                    .file_type()
-                    .ok()
-                    .map(|kind| kind.is_file().then_some(entry.file_name()))
-                    .flatten()
-            });
+            let (archive_files, files): (Vec<_>, Vec<_>) = entries
+                .iter()
+                .filter(|(_, is_dir, _)| !is_dir)
+                .partition(|(entry, _, _)| archive::is_archive(entry.path()));
 
-            unexplored.extend(children.iter().cloned());
-            directories.push(Directory::new_with_files(name, files));
+            let metadata = files
+                .iter()
+                .filter_map(|(entry, _, is_link)| {
+                    let size = fs::metadata(entry.path()).ok()?.len();
+                    Some((
+                        entry.file_name().to_string_lossy().to_string(),
+                        FileMetadata {
+                            size,
+                            is_link: *is_link,
+                        },
+                    ))
+                })
+                .collect::<BTreeMap<_, _>>();
+            let mut files = files
+                .into_iter()
+                .map(|(entry, _, _)| entry.file_name())
+                .collect::<Vec<_>>();
+            for (entry, _, _) in archive_files {
+                match archive::members(entry.path()) {
+                    Ok(members) => {
+                        archives.push((entry.file_name(), members, directories.len()));
+                    }
+                    Err(err) => {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(path = ?entry.path(), ?err, "could not read archive");
+                        files.push(entry.file_name());
+                    }
+                }
+            }
+
+            unexplored.extend(
+                children
+                    .iter()
+                    .map(|(child, is_link)| (child.clone(), depth + 1, *is_link)),
+            );
+            let mut directory = Directory::new_with_files(name, files);
+            directory.kind = kind;
+            directory.metadata = metadata;
+            directories.push(directory);
 
             if !is_root {
                 let parent_map_idx = parent_map
@@ -329,17 +699,93 @@ impl DirectoryTree {
             }
 
             let parent_idx = directories.len() - 1;
-            let children = children.into_iter().map(|child| (child, parent_idx));
+            let children = children.into_iter().map(|(child, _)| (child, parent_idx));
             parent_map.extend(children);
         }
 
-        Ok(Self {
+        let mut tree = Self {
             directories,
             parents,
-        })
+        };
+        for (name, members, parent) in archives {
+            tree.insert_archive(name, members, parent);
+        }
+
+        Ok(tree)
+    }
+
+    /// Insert an archive as a directory.
+    ///
+    /// # Arguments
+    /// + `name`: File name of the archive.
+    /// + `members`: Paths of the files in the archive.
+    /// + `parent`: Index of the directory containing the archive.
+    ///
+    /// # Returns
+    /// Index of the archive's directory.
+    fn insert_archive(
+        &mut self,
+        name: OsString,
+        members: Vec<std::path::PathBuf>,
+        parent: usize,
+    ) -> usize {
+        let root = self
+            .insert(
+                Directory::new_with_kind(name, DirectoryKind::Archive),
+                parent,
+            )
+            .expect("parent should exist");
+
+        for member in members {
+            let Some(file_name) = member.file_name() else {
+                continue;
+            };
+
+            let mut directory = root;
+            if let Some(folders) = member.parent() {
+                for folder in folders.iter() {
+                    let child = self
+                        .children(directory)
+                        .expect("directory should exist")
+                        .into_iter()
+                        .find(|child| self.directories[*child].name.as_os_str() == folder);
+
+                    directory = match child {
+                        Some(child) => child,
+                        None => self
+                            .insert(
+                                Directory::new_with_kind(folder, DirectoryKind::ArchiveFolder),
+                                directory,
+                            )
+                            .expect("directory should exist"),
+                    };
+                }
+            }
+
+            self.directories[directory]
+                .files
+                .insert(file_name.to_os_string());
+        }
+
+        root
     }
 }
 
+/// Identifies a directory independent of the path used to reach it.
+#[cfg(all(feature = "fs", unix))]
+fn directory_id(path: impl AsRef<Path>) -> std::io::Result<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.dev(), metadata.ino()))
+}
+
+/// Identifies a directory independent of the path used to reach it.
+#[cfg(all(feature = "fs", not(unix)))]
+fn directory_id(path: impl AsRef<Path>) -> std::io::Result<PathBuf> {
+    fs::canonicalize(path)
+}
+
 pub mod error {
     use serde::{Deserialize, Serialize};
     use std::{io, path::PathBuf};
@@ -370,6 +816,57 @@ pub mod error {
     }
 
     #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    pub enum FileOperation {
+        /// A resource already exists at the target path.
+        #[error("A file or folder with that name already exists.")]
+        AlreadyExists,
+
+        /// The resource does not exist.
+        #[error("The file or folder does not exist.")]
+        NotFound,
+
+        /// The resource, or one it contains, may not be modified.
+        /// e.g. It is protected or within an archive.
+        #[error("The file or folder can not be modified.")]
+        ReadOnly,
+
+        /// The resource could not be moved to the trash.
+        #[error("Could not move to the trash: {0}")]
+        Trash(String),
+
+        /// The resource could not be shown in the file manager.
+        #[error("Could not show in the file manager: {0}")]
+        Reveal(String),
+
+        #[error("{0}")]
+        Io(
+            #[serde(with = "io_error_serde::ErrorKind")]
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
+            io::ErrorKind,
+        ),
+    }
+
+    impl From<io::Error> for FileOperation {
+        fn from(value: io::Error) -> Self {
+            match value.kind() {
+                io::ErrorKind::AlreadyExists => Self::AlreadyExists,
+                io::ErrorKind::NotFound => Self::NotFound,
+                kind => Self::Io(kind),
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    pub enum SelectFolder {
+        /// The selected folder is not on the local file system.
+        #[error("The selected folder is not on the local file system.")]
+        NotAPath,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
     pub enum FromFileSystem {
         /// Root resource was not found.
         #[error("Could not find the project root.")]
@@ -384,6 +881,7 @@ pub mod error {
             path: PathBuf,
 
             #[serde(with = "io_error_serde::ErrorKind")]
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
             error: io::ErrorKind,
         },
     }
@@ -393,6 +891,27 @@ pub mod error {
 mod test {
     use super::*;
 
+    #[cfg(feature = "fs")]
+    #[test]
+    fn launch_from_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("data.csv");
+        std::fs::write(&file, "a\n1\n").unwrap();
+
+        let launch = Launch::from_path(&file).unwrap();
+        assert_eq!(launch.root, dir.path());
+        assert_eq!(launch.file, Some(PathBuf::from("data.csv")));
+
+        let launch = Launch::from_path(dir.path()).unwrap();
+        assert_eq!(launch.root, dir.path());
+        assert_eq!(launch.file, None);
+
+        assert!(matches!(
+            Launch::from_path(dir.path().join("missing.csv")),
+            Err(error::FromFileSystem::RootNotFound)
+        ));
+    }
+
     #[test]
     fn directory_tree() {
         let root_name = "0";
@@ -456,6 +975,144 @@ mod test {
         let c00_idx = c0_children[0];
         assert_eq!(tree.get(c00_idx).unwrap().name, c00_name);
     }
+
+    #[test]
+    fn file_kind_from_path() {
+        assert_eq!(FileKind::from_path("data.csv"), FileKind::Csv);
+        assert_eq!(FileKind::from_path("dir/data.XLSX"), FileKind::Xlsx);
+        assert_eq!(FileKind::from_path("data.parquet"), FileKind::Parquet);
+        assert_eq!(FileKind::from_path("notes.txt"), FileKind::Other);
+        assert_eq!(FileKind::from_path("Makefile"), FileKind::Other);
+        assert!(
+            SUPPORTED_EXTENSIONS
+                .iter()
+                .all(|ext| FileKind::from_path(format!("a.{ext}")).label() == Some(ext))
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn is_read_only_test() {
+        let root = tempfile::tempdir().unwrap();
+        let path = root.path().join("a.csv");
+        assert!(!is_read_only(&path));
+
+        fs::write(&path, "").unwrap();
+        assert!(!is_read_only(&path));
+
+        let mut permissions = fs::metadata(&path).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&path, permissions).unwrap();
+        assert!(is_read_only(&path));
+        assert!(!is_read_only(root.path()));
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn directory_tree_from_file_system_with_depth() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("a/b")).unwrap();
+        fs::write(root.path().join("a/a.csv"), "").unwrap();
+        fs::write(root.path().join("a/b/b.csv"), "").unwrap();
+
+        let tree = DirectoryTree::from_file_system_with_depth(root.path(), Some(1)).unwrap();
+        assert_eq!(tree.directories().len(), 3);
+        let a_idx = tree.children(DirectoryTree::ROOT).unwrap()[0];
+        let a = tree.get(a_idx).unwrap();
+        assert!(!a.pending);
+        assert_eq!(a.files.len(), 1);
+        let b_idx = tree.children(a_idx).unwrap()[0];
+        let b = tree.get(b_idx).unwrap();
+        assert!(b.pending);
+        assert!(b.files.is_empty());
+        assert!(tree.children(b_idx).unwrap().is_empty());
+
+        let tree = DirectoryTree::from_file_system(root.path()).unwrap();
+        assert!(tree.directories().iter().all(|dir| !dir.pending));
+        assert_eq!(tree.file_paths().len(), 2);
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn directory_tree_from_file_system_ignore() {
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join(".git/objects")).unwrap();
+        fs::create_dir_all(root.path().join("data")).unwrap();
+        fs::write(root.path().join("data/a.csv"), "").unwrap();
+        fs::write(root.path().join("data/a.bak"), "").unwrap();
+        fs::write(root.path().join(ignore::IGNORE_FILE), "*.bak\n").unwrap();
+
+        let tree = DirectoryTree::from_file_system(root.path()).unwrap();
+        assert_eq!(tree.directories().len(), 2);
+        let mut paths = tree.file_paths();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from(ignore::IGNORE_FILE),
+                PathBuf::from("data").join("a.csv")
+            ]
+        );
+    }
+
+    #[cfg(feature = "fs")]
+    #[test]
+    fn directory_tree_from_file_system_archives() {
+        use std::io::Write;
+
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("a")).unwrap();
+        let file = fs::File::create(root.path().join("a/data.zip")).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::SimpleFileOptions::default();
+        zip.start_file("b.csv", options).unwrap();
+        zip.write_all(b"1,2").unwrap();
+        zip.start_file("c/d.csv", options).unwrap();
+        zip.write_all(b"3,4").unwrap();
+        zip.finish().unwrap();
+
+        let tree = DirectoryTree::from_file_system(root.path()).unwrap();
+        let a_idx = tree.children(DirectoryTree::ROOT).unwrap()[0];
+        assert!(tree.get(a_idx).unwrap().files.is_empty());
+        let archive_idx = tree.children(a_idx).unwrap()[0];
+        let archive = tree.get(archive_idx).unwrap();
+        assert_eq!(archive.name, "data.zip");
+        assert_eq!(archive.kind, DirectoryKind::Archive);
+        assert!(archive.files.contains(OsStr::new("b.csv")));
+        let folder_idx = tree.children(archive_idx).unwrap()[0];
+        let folder = tree.get(folder_idx).unwrap();
+        assert_eq!(folder.kind, DirectoryKind::ArchiveFolder);
+        assert!(folder.files.contains(OsStr::new("d.csv")));
+    }
+
+    #[cfg(all(feature = "fs", unix))]
+    #[test]
+    fn directory_tree_from_file_system_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let root = tempfile::tempdir().unwrap();
+        fs::create_dir_all(root.path().join("a")).unwrap();
+        fs::write(root.path().join("a/a.csv"), "1,2").unwrap();
+        symlink(root.path(), root.path().join("a/root")).unwrap();
+        symlink(root.path().join("a/a.csv"), root.path().join("b.csv")).unwrap();
+
+        let tree = DirectoryTree::from_file_system(root.path()).unwrap();
+        assert_eq!(tree.directories().len(), 2);
+        let root_dir = tree.get(DirectoryTree::ROOT).unwrap();
+        let link = root_dir.file_metadata("b.csv").unwrap();
+        assert!(link.is_link);
+        assert_eq!(link.size, 3);
+
+        let tree = DirectoryTree::from_file_system_with_options(
+            root.path(),
+            ReadOptions {
+                symlinks: SymlinkPolicy::Skip,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(tree.file_paths(), vec![PathBuf::from("a").join("a.csv")]);
+    }
 }
 
 pub mod serde_os_string {
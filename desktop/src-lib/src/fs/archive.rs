@@ -0,0 +1,89 @@
+//! Read-only access to zip archives.
+//! Archives are treated as immutable directories.
+use std::{
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+/// File extensions recognized as archives.
+pub const ARCHIVE_EXTENSIONS: &[&str] = &["zip"];
+
+/// # Returns
+/// If the path has an archive file extension.
+pub fn is_archive(path: impl AsRef<Path>) -> bool {
+    path.as_ref()
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| {
+            ARCHIVE_EXTENSIONS
+                .iter()
+                .any(|archive_ext| ext.eq_ignore_ascii_case(archive_ext))
+        })
+        .unwrap_or(false)
+}
+
+/// Split a path pointing into an archive into
+/// the path of the archive and the path of the member within the archive.
+///
+/// # Returns
+/// `None` if no ancestor of `path` is an archive file.
+pub fn split_path(path: impl AsRef<Path>) -> Option<(PathBuf, PathBuf)> {
+    let path = path.as_ref();
+    path.ancestors()
+        .skip(1)
+        .find(|ancestor| is_archive(ancestor) && ancestor.is_file())
+        .map(|archive| {
+            let member = path
+                .strip_prefix(archive)
+                .expect("archive is an ancestor of path");
+            (archive.to_path_buf(), member.to_path_buf())
+        })
+}
+
+/// # Returns
+/// Paths of the files contained in the archive.
+/// Members with unsafe paths (e.g. absolute or containing `..`) are ignored.
+pub fn members(path: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+    let file = fs::File::open(path)?;
+    let mut archive = zip::ZipArchive::new(file)?;
+    let mut members = Vec::with_capacity(archive.len());
+    for idx in 0..archive.len() {
+        let member = archive.by_index_raw(idx)?;
+        if member.is_dir() {
+            continue;
+        }
+
+        if let Some(path) = member.enclosed_name() {
+            members.push(path);
+        }
+    }
+
+    Ok(members)
+}
+
+/// Read the content of an archive member.
+///
+/// # Arguments
+/// + `archive`: Path to the archive.
+/// + `member`: Path of the member within the archive.
+pub fn read_member(archive: impl AsRef<Path>, member: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let member = member.as_ref();
+    let file = fs::File::open(archive)?;
+    let mut archive = zip::ZipArchive::new(file)?;
+    let idx = (0..archive.len())
+        .find(|idx| {
+            archive
+                .by_index_raw(*idx)
+                .ok()
+                .and_then(|file| file.enclosed_name())
+                .map(|path| path == member)
+                .unwrap_or(false)
+        })
+        .ok_or(io::Error::from(io::ErrorKind::NotFound))?;
+
+    let mut member = archive.by_index(idx)?;
+    let mut content = Vec::with_capacity(member.size() as usize);
+    member.read_to_end(&mut content)?;
+    Ok(content)
+}
@@ -0,0 +1,94 @@
+//! Ignore rules for directory scanning.
+//! Rules are read from the project root's `.gitignore` and `.hermesignore` files,
+//! using gitignore syntax.
+//! Rules in `.hermesignore` take precedence.
+//!
+//! # Notes
+//! + Ignore files in subdirectories are not read.
+use ::ignore::gitignore::{Gitignore, GitignoreBuilder};
+use std::path::Path;
+
+/// Name of the project ignore file.
+pub const IGNORE_FILE: &str = ".hermesignore";
+
+/// Ignore files read from the project root, in order of increasing precedence.
+const IGNORE_FILES: &[&str] = &[".gitignore", IGNORE_FILE];
+
+/// Patterns ignored in every project.
+const DEFAULT_PATTERNS: &[&str] = &[".git/", "node_modules/", "*.tmp", "~$*"];
+
+/// Build the ignore matcher for a project.
+/// Invalid rules are skipped.
+pub fn matcher(root: impl AsRef<Path>) -> Gitignore {
+    let root = root.as_ref();
+    let mut builder = GitignoreBuilder::new(root);
+    for pattern in DEFAULT_PATTERNS {
+        builder
+            .add_line(None, pattern)
+            .expect("default patterns are valid");
+    }
+
+    for file in IGNORE_FILES {
+        let path = root.join(file);
+        if !path.is_file() {
+            continue;
+        }
+
+        if let Some(err) = builder.add(&path) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(?path, ?err, "invalid ignore rules");
+        }
+    }
+
+    match builder.build() {
+        Ok(matcher) => matcher,
+        Err(err) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(?err, "could not build ignore rules");
+            Gitignore::empty()
+        }
+    }
+}
+
+/// # Returns
+/// If the path is ignored.
+/// Paths outside of the matcher's root are never ignored.
+pub fn is_ignored(matcher: &Gitignore, path: impl AsRef<Path>, is_dir: bool) -> bool {
+    let path = path.as_ref();
+    if !path.starts_with(matcher.path()) {
+        return false;
+    }
+
+    matcher
+        .matched_path_or_any_parents(path, is_dir)
+        .is_ignore()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ignore_rules() {
+        let root = tempfile::tempdir().unwrap();
+        std::fs::write(root.path().join(".gitignore"), "*.log\nbuild/\n").unwrap();
+        std::fs::write(root.path().join(IGNORE_FILE), "!keep.log\n").unwrap();
+
+        let matcher = matcher(root.path());
+        assert!(is_ignored(&matcher, root.path().join(".git"), true));
+        assert!(is_ignored(
+            &matcher,
+            root.path().join("a/node_modules/b.csv"),
+            false
+        ));
+        assert!(is_ignored(&matcher, root.path().join("out.log"), false));
+        assert!(is_ignored(
+            &matcher,
+            root.path().join("build/data.csv"),
+            false
+        ));
+        assert!(!is_ignored(&matcher, root.path().join("keep.log"), false));
+        assert!(!is_ignored(&matcher, root.path().join("data.csv"), false));
+        assert!(!is_ignored(&matcher, "/elsewhere/out.log", false));
+    }
+}
@@ -0,0 +1,106 @@
+//! File content hashing.
+#[cfg(feature = "fs")]
+use sha2::{Digest as _, Sha256};
+#[cfg(feature = "fs")]
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+/// Number of bytes read at a time when hashing a file.
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// SHA-256 digest of a file's content.
+pub type Digest = [u8; 32];
+
+/// # Returns
+/// Lower case hex encoding of a digest.
+pub fn to_hex(digest: &Digest) -> String {
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Hash a file's content.
+/// The file is read in chunks of [`CHUNK_SIZE`] bytes.
+#[cfg(feature = "fs")]
+pub fn hash_file(path: impl AsRef<Path>) -> io::Result<Digest> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; CHUNK_SIZE];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+
+    Ok(hasher.finalize().into())
+}
+
+/// Find files with identical content.
+/// Files are grouped by size first, so only files which could be identical are hashed.
+///
+/// # Returns
+/// Sets of paths with identical content, each containing at least two paths.
+///
+/// # Notes
+/// + Files which can not be read are ignored.
+#[cfg(feature = "fs")]
+pub fn find_duplicates(paths: impl IntoIterator<Item = PathBuf>) -> Vec<Vec<PathBuf>> {
+    let mut sizes = HashMap::<u64, Vec<PathBuf>>::new();
+    for path in paths {
+        let Ok(metadata) = fs::metadata(&path) else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+
+        sizes.entry(metadata.len()).or_default().push(path);
+    }
+
+    let mut duplicates = vec![];
+    for (_, paths) in sizes.into_iter().filter(|(_, paths)| paths.len() > 1) {
+        let mut digests = HashMap::<Digest, Vec<PathBuf>>::new();
+        for path in paths {
+            match hash_file(&path) {
+                Ok(digest) => digests.entry(digest).or_default().push(path),
+                Err(err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(?path, ?err, "could not hash file");
+                }
+            }
+        }
+
+        duplicates.extend(digests.into_values().filter(|paths| paths.len() > 1));
+    }
+
+    for paths in duplicates.iter_mut() {
+        paths.sort();
+    }
+    duplicates.sort();
+    duplicates
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn find_duplicates_test() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a.csv");
+        let b = dir.path().join("b.csv");
+        let c = dir.path().join("c.csv");
+        let d = dir.path().join("d.csv");
+        fs::write(&a, "1,2\n3,4\n").unwrap();
+        fs::write(&b, "1,2\n3,4\n").unwrap();
+        fs::write(&c, "1,2\n3,5\n").unwrap();
+        fs::write(&d, "1\n").unwrap();
+
+        let duplicates = find_duplicates(vec![a.clone(), b.clone(), c, d]);
+        assert_eq!(duplicates, vec![vec![a, b]]);
+    }
+}
@@ -0,0 +1,715 @@
+//! Commands and events passed between the desktop ui and backend.
+//!
+//! Arguments and payloads are declared once, here, and used by both sides,
+//! so a change to one side does not compile until the other matches.
+//! With the `schema` feature, [`schema`] describes them as a JSON schema
+//! for tools outside of Rust.
+//!
+//! # Notes
+//! + Each backend command takes its [arguments](Command) as a single parameter named `args`,
+//!   with the fields of the arguments in camel case.
+use crate::{
+    audit, batch, data, diagnostics, diff, formula, fs, session, settings, snapshot, template,
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use std::path::PathBuf;
+
+/// Command invoked by the ui.
+/// Implemented by the command's arguments.
+pub trait Command: Serialize + DeserializeOwned {
+    /// Name the command is registered as.
+    const NAME: &'static str;
+
+    /// Value returned if the command succeeds.
+    type Output: Serialize + DeserializeOwned;
+
+    /// Value returned if the command fails.
+    type Error: Serialize + DeserializeOwned;
+}
+
+/// Result of a command.
+pub type Response<C> = Result<<C as Command>::Output, <C as Command>::Error>;
+
+/// [Error](Command::Error) of a command that can not fail.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Infallible {}
+
+/// Event emitted to the ui.
+pub trait Event {
+    /// Name the event is emitted as.
+    const NAME: &'static str;
+
+    type Payload: Serialize + DeserializeOwned;
+}
+
+/// Ask the user to choose a project folder.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SelectFolder;
+
+impl Command for SelectFolder {
+    const NAME: &'static str = "select_folder";
+
+    /// `None` if the user did not choose a folder.
+    type Output = Option<PathBuf>;
+    type Error = fs::error::SelectFolder;
+}
+
+/// List recently opened project roots.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ListRecentRoots;
+
+impl Command for ListRecentRoots {
+    const NAME: &'static str = "list_recent_roots";
+    type Output = Vec<PathBuf>;
+    type Error = Infallible;
+}
+
+/// Open a recently opened project root.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct OpenRecentRoot {
+    pub root: PathBuf,
+}
+
+impl Command for OpenRecentRoot {
+    const NAME: &'static str = "open_recent_root";
+    type Output = PathBuf;
+    type Error = fs::error::FromFileSystem;
+}
+
+/// Get the file or folder the app was launched with.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LaunchPath;
+
+impl Command for LaunchPath {
+    const NAME: &'static str = "launch_path";
+
+    /// `None` if the app was not launched with a path.
+    type Output = Option<fs::Launch>;
+    type Error = fs::error::FromFileSystem;
+}
+
+/// Load the directory tree of a project and watch it for changes.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct LoadDirectory {
+    pub root: PathBuf,
+}
+
+impl Command for LoadDirectory {
+    const NAME: &'static str = "load_directory";
+    type Output = fs::DirectoryTree;
+    type Error = fs::error::FromFileSystem;
+}
+
+/// Load the contents of a directory not yet loaded.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ExpandDirectory {
+    pub root: PathBuf,
+
+    /// Path to the directory relative to the root.
+    pub path: PathBuf,
+}
+
+impl Command for ExpandDirectory {
+    const NAME: &'static str = "expand_directory";
+    type Output = fs::DirectoryTree;
+    type Error = fs::error::FromFileSystem;
+}
+
+/// Find files with identical content.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct FindDuplicateFiles {
+    pub root: PathBuf,
+}
+
+impl Command for FindDuplicateFiles {
+    const NAME: &'static str = "find_duplicate_files";
+
+    /// Paths of each set of identical files.
+    type Output = Vec<Vec<PathBuf>>;
+    type Error = fs::error::FromFileSystem;
+}
+
+/// Create a folder.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFolder {
+    pub path: PathBuf,
+}
+
+impl Command for CreateFolder {
+    const NAME: &'static str = "create_folder";
+    type Output = ();
+    type Error = fs::error::FileOperation;
+}
+
+/// Create an empty file.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CreateFile {
+    pub path: PathBuf,
+}
+
+impl Command for CreateFile {
+    const NAME: &'static str = "create_file";
+    type Output = ();
+    type Error = fs::error::FileOperation;
+}
+
+/// Rename or move a file or folder.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct RenamePath {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+impl Command for RenamePath {
+    const NAME: &'static str = "rename_path";
+    type Output = ();
+    type Error = fs::error::FileOperation;
+}
+
+/// Move a file or folder to the trash.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct TrashPath {
+    pub path: PathBuf,
+}
+
+impl Command for TrashPath {
+    const NAME: &'static str = "trash_path";
+    type Output = ();
+    type Error = fs::error::FileOperation;
+}
+
+/// Show a file or folder in the system file manager.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct RevealPath {
+    pub path: PathBuf,
+}
+
+impl Command for RevealPath {
+    const NAME: &'static str = "reveal_path";
+    type Output = ();
+    type Error = fs::error::FileOperation;
+}
+
+/// Set whether a file is protected from modification.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SetFileProtected {
+    pub root: PathBuf,
+
+    /// Path to the file relative to the root.
+    pub path: PathBuf,
+    pub protected: bool,
+}
+
+impl Command for SetFileProtected {
+    const NAME: &'static str = "set_file_protected";
+    type Output = ();
+    type Error = fs::error::FileOperation;
+}
+
+/// Load the settings of a project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct GetSettings {
+    pub root: PathBuf,
+}
+
+impl Command for GetSettings {
+    const NAME: &'static str = "get_settings";
+    type Output = settings::ProjectSettings;
+    type Error = Infallible;
+}
+
+/// Save the settings of a project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SetSettings {
+    pub root: PathBuf,
+    pub scope: settings::Scope,
+    pub settings: settings::Settings,
+}
+
+impl Command for SetSettings {
+    const NAME: &'static str = "set_settings";
+    type Output = ();
+    type Error = fs::error::FileOperation;
+}
+
+/// Get the system theme.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SystemTheme;
+
+impl Command for SystemTheme {
+    const NAME: &'static str = "system_theme";
+    type Output = settings::Theme;
+    type Error = Infallible;
+}
+
+/// Notify the user an operation completed.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct NotifyComplete {
+    pub operation: settings::Operation,
+    pub title: String,
+    pub body: Option<String>,
+}
+
+impl Command for NotifyComplete {
+    const NAME: &'static str = "notify_complete";
+    type Output = ();
+    type Error = Infallible;
+}
+
+/// Load the saved formula templates.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LoadTemplates;
+
+impl Command for LoadTemplates {
+    const NAME: &'static str = "load_templates";
+    type Output = Vec<template::Template>;
+    type Error = Infallible;
+}
+
+/// Save the formula templates.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SaveTemplates {
+    pub templates: Vec<template::Template>,
+}
+
+impl Command for SaveTemplates {
+    const NAME: &'static str = "save_templates";
+    type Output = ();
+    type Error = fs::error::FileOperation;
+}
+
+/// Load the session of the window left by a crash.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LoadRecoveredSession;
+
+impl Command for LoadRecoveredSession {
+    const NAME: &'static str = "load_recovered_session";
+
+    /// `None` if there is no session to recover.
+    type Output = Option<session::Session>;
+    type Error = Infallible;
+}
+
+/// Save the session of the window.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SaveSession {
+    pub session: session::Session,
+}
+
+impl Command for SaveSession {
+    const NAME: &'static str = "save_session";
+    type Output = ();
+    type Error = fs::error::FileOperation;
+}
+
+/// Remove the saved session of the window.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ClearSession;
+
+impl Command for ClearSession {
+    const NAME: &'static str = "clear_session";
+    type Output = ();
+    type Error = fs::error::FileOperation;
+}
+
+/// Save a snapshot of formula results.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct SaveSnapshot {
+    pub root: PathBuf,
+    pub snapshot: snapshot::Snapshot,
+}
+
+impl Command for SaveSnapshot {
+    const NAME: &'static str = "save_snapshot";
+    type Output = ();
+    type Error = snapshot::error::Snapshot;
+}
+
+/// Load the saved snapshot of formula results.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct LoadSnapshot {
+    pub root: PathBuf,
+}
+
+impl Command for LoadSnapshot {
+    const NAME: &'static str = "load_snapshot";
+    type Output = snapshot::Snapshot;
+    type Error = snapshot::error::Snapshot;
+}
+
+/// Load a dataset.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct LoadDataset {
+    /// Handle of the load, used to follow its progress and cancel it.
+    pub id: data::LoadId,
+    pub path: PathBuf,
+    pub options: data::ImportOptions,
+}
+
+impl Command for LoadDataset {
+    const NAME: &'static str = "load_dataset";
+    type Output = data::Loaded;
+    type Error = data::error::Load;
+}
+
+/// Load the first rows of a csv dataset.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct LoadDatasetHead {
+    /// Handle of the load, used to follow its progress and cancel it.
+    pub id: data::LoadId,
+    pub path: PathBuf,
+    pub options: data::ImportOptions,
+}
+
+impl Command for LoadDatasetHead {
+    const NAME: &'static str = "load_dataset_head";
+    type Output = data::Loaded;
+    type Error = data::error::Load;
+}
+
+/// Cancel a dataset load.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CancelLoad {
+    pub id: data::LoadId,
+}
+
+impl Command for CancelLoad {
+    const NAME: &'static str = "cancel_load";
+    type Output = ();
+    type Error = Infallible;
+}
+
+/// Compare the in-memory cells of a dataset against its file.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct DiffDataset {
+    pub path: PathBuf,
+    pub options: data::ImportOptions,
+
+    /// Cells of each sheet in memory.
+    pub sheets: Vec<data::CellMap>,
+}
+
+impl Command for DiffDataset {
+    const NAME: &'static str = "diff_dataset";
+    type Output = Vec<diff::CellDiff>;
+    type Error = data::error::Load;
+}
+
+/// Run workspace orders.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct RunWorkspace {
+    pub root: PathBuf,
+    pub orders: Vec<formula::WorkspaceOrder>,
+
+    /// Report the changes that would be made without writing them.
+    pub dry_run: bool,
+
+    /// Only write files if every order succeeds.
+    pub atomic: bool,
+
+    /// Write a [manifest](crate::manifest) of the files written once the run finishes.
+    pub manifest: bool,
+}
+
+impl Command for RunWorkspace {
+    const NAME: &'static str = "run_workspace";
+    type Output = Vec<audit::Entry>;
+
+    /// `(<order index>, <error>)` of each failed order.
+    type Error = Vec<(usize, formula::error::WorkspaceOrder)>;
+}
+
+/// Save a copy of a dataset with its formulas evaluated, to a file chosen by the user.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct ExportEvaluatedCopy {
+    pub copy: formula::EvaluatedCopy,
+}
+
+impl Command for ExportEvaluatedCopy {
+    const NAME: &'static str = "export_evaluated_copy";
+
+    /// Path the copy was saved to,
+    /// or `None` if the user did not choose one.
+    type Output = Option<PathBuf>;
+    type Error = formula::error::WorkspaceOrder;
+}
+
+/// Evaluate the formulas of a dataset.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluateFormulas {
+    pub evaluate: formula::Evaluate,
+}
+
+impl Command for EvaluateFormulas {
+    const NAME: &'static str = "evaluate_formulas";
+
+    /// `None` if the evaluation did not complete.
+    type Output = Option<Vec<formula::TracedResult>>;
+    type Error = Infallible;
+}
+
+/// Build the orders applying a batch to each of its files.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct BatchOrders {
+    pub root: PathBuf,
+    pub batch: batch::Batch,
+}
+
+impl Command for BatchOrders {
+    const NAME: &'static str = "batch_orders";
+    type Output = Vec<batch::FileOrder>;
+    type Error = batch::error::Files;
+}
+
+/// Query the project's [audit log](crate::audit).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct QueryAuditLog {
+    pub root: PathBuf,
+    pub query: audit::Query,
+}
+
+impl Command for QueryAuditLog {
+    const NAME: &'static str = "query_audit_log";
+    type Output = Vec<audit::Entry>;
+    type Error = audit::error::Audit;
+}
+
+/// Save a diagnostic bundle chosen by the user.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub struct CreateDiagnostics {
+    /// Workspace open in the ui, if any.
+    pub workspace: Option<diagnostics::Workspace>,
+}
+
+impl Command for CreateDiagnostics {
+    const NAME: &'static str = "create_diagnostics";
+
+    /// Path the bundle was saved to,
+    /// or `None` if the user did not choose one.
+    type Output = Option<PathBuf>;
+    type Error = diagnostics::error::Bundle;
+}
+
+pub mod event {
+    use super::{Event, data, diagnostics, fs, settings};
+    use std::path::PathBuf;
+
+    /// Files were created or modified.
+    pub struct FilesModified;
+    impl Event for FilesModified {
+        const NAME: &'static str = fs::FILES_MODIFIED_EVENT;
+        type Payload = Vec<PathBuf>;
+    }
+
+    /// A dataset load progressed.
+    pub struct LoadProgress;
+    impl Event for LoadProgress {
+        const NAME: &'static str = data::LOAD_PROGRESS_EVENT;
+        type Payload = data::LoadProgressEvent;
+    }
+
+    /// The user asked for a diagnostic bundle.
+    pub struct DiagnosticsRequested;
+    impl Event for DiagnosticsRequested {
+        const NAME: &'static str = diagnostics::DIAGNOSTICS_REQUESTED_EVENT;
+        type Payload = ();
+    }
+
+    /// The system theme changed.
+    pub struct ThemeChanged;
+    impl Event for ThemeChanged {
+        const NAME: &'static str = settings::THEME_CHANGED_EVENT;
+        type Payload = settings::Theme;
+    }
+}
+
+/// # Returns
+/// JSON schema of the commands and events.
+/// Each command is described by its `args`, `output`, and `error`,
+/// and each event by its payload.
+#[cfg(feature = "schema")]
+pub fn schema() -> schemars::Schema {
+    use schemars::{JsonSchema, SchemaGenerator};
+
+    fn command<C>(generator: &mut SchemaGenerator) -> (String, serde_json::Value)
+    where
+        C: Command + JsonSchema,
+        C::Output: JsonSchema,
+        C::Error: JsonSchema,
+    {
+        let schema = serde_json::json!({
+            "args": generator.subschema_for::<C>(),
+            "output": generator.subschema_for::<C::Output>(),
+            "error": generator.subschema_for::<C::Error>(),
+        });
+        (C::NAME.to_string(), schema)
+    }
+
+    fn event<E>(generator: &mut SchemaGenerator) -> (String, serde_json::Value)
+    where
+        E: Event,
+        E::Payload: JsonSchema,
+    {
+        let schema = generator.subschema_for::<E::Payload>();
+        (E::NAME.to_string(), schema.to_value())
+    }
+
+    let mut generator = SchemaGenerator::default();
+    let commands = serde_json::Map::from_iter([
+        command::<SelectFolder>(&mut generator),
+        command::<ListRecentRoots>(&mut generator),
+        command::<OpenRecentRoot>(&mut generator),
+        command::<LaunchPath>(&mut generator),
+        command::<LoadDirectory>(&mut generator),
+        command::<ExpandDirectory>(&mut generator),
+        command::<FindDuplicateFiles>(&mut generator),
+        command::<CreateFolder>(&mut generator),
+        command::<CreateFile>(&mut generator),
+        command::<RenamePath>(&mut generator),
+        command::<TrashPath>(&mut generator),
+        command::<RevealPath>(&mut generator),
+        command::<SetFileProtected>(&mut generator),
+        command::<GetSettings>(&mut generator),
+        command::<SetSettings>(&mut generator),
+        command::<SystemTheme>(&mut generator),
+        command::<NotifyComplete>(&mut generator),
+        command::<LoadTemplates>(&mut generator),
+        command::<SaveTemplates>(&mut generator),
+        command::<LoadRecoveredSession>(&mut generator),
+        command::<SaveSession>(&mut generator),
+        command::<ClearSession>(&mut generator),
+        command::<SaveSnapshot>(&mut generator),
+        command::<LoadSnapshot>(&mut generator),
+        command::<LoadDataset>(&mut generator),
+        command::<LoadDatasetHead>(&mut generator),
+        command::<CancelLoad>(&mut generator),
+        command::<DiffDataset>(&mut generator),
+        command::<RunWorkspace>(&mut generator),
+        command::<ExportEvaluatedCopy>(&mut generator),
+        command::<EvaluateFormulas>(&mut generator),
+        command::<BatchOrders>(&mut generator),
+        command::<QueryAuditLog>(&mut generator),
+        command::<CreateDiagnostics>(&mut generator),
+    ]);
+    let events = serde_json::Map::from_iter([
+        event::<event::FilesModified>(&mut generator),
+        event::<event::LoadProgress>(&mut generator),
+        event::<event::DiagnosticsRequested>(&mut generator),
+        event::<event::ThemeChanged>(&mut generator),
+    ]);
+    let definitions = generator.take_definitions(true);
+
+    schemars::json_schema!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "hermes ipc",
+        "commands": commands,
+        "events": events,
+        "$defs": definitions,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn args_are_camel_case() {
+        let args = RunWorkspace {
+            root: PathBuf::from("/root"),
+            orders: vec![],
+            dry_run: true,
+            atomic: false,
+            manifest: false,
+        };
+        let args = serde_json::to_value(&args).unwrap();
+        assert_eq!(args["dryRun"], serde_json::Value::Bool(true));
+        assert!(args.get("dry_run").is_none());
+    }
+
+    #[test]
+    fn unit_args_are_null() {
+        let args = serde_json::to_value(SystemTheme).unwrap();
+        assert_eq!(args, serde_json::Value::Null);
+        serde_json::from_value::<SystemTheme>(args).unwrap();
+    }
+
+    #[cfg(feature = "schema")]
+    #[test]
+    fn schema_describes_commands_and_events() {
+        let schema = schema().to_value();
+        assert_eq!(
+            schema["commands"]["run_workspace"]["args"]["$ref"],
+            "#/$defs/RunWorkspace"
+        );
+        assert!(schema["events"][data::LOAD_PROGRESS_EVENT].is_object());
+        for name in ["Dataset", "WorkspaceOrder", "Load", "LoadProgressEvent"] {
+            assert!(schema["$defs"][name].is_object(), "missing `{name}`");
+        }
+        let required = schema["$defs"]["RunWorkspace"]["required"]
+            .as_array()
+            .unwrap();
+        assert!(required.contains(&serde_json::json!("dryRun")));
+    }
+}
@@ -1,3 +1,29 @@
+//! Dataset loading, formula evaluation, and order application for Hermes,
+//! independent of the desktop app.
+//!
+//! + [`data`]: Read datasets from files.
+//! + [`formula`]: Evaluate formulas and describe changes to files as workspace orders.
+//! + [`run`]: Run workspace orders against the file system.
+//! + [`apply`]: Apply formulas to a file in one call.
+//! + [`ipc`]: Commands and events shared by the desktop ui and backend.
 pub mod fs;
+pub mod apply;
+pub mod audit;
+pub mod batch;
+pub mod clean;
 pub mod data;
-pub mod formula;
\ No newline at end of file
+pub mod diagnostics;
+pub mod diff;
+pub mod formula;
+pub mod hash;
+pub mod ipc;
+pub mod manifest;
+pub mod pivot;
+pub mod run;
+pub mod session;
+pub mod settings;
+pub mod snapshot;
+pub mod template;
+
+#[cfg(feature = "fs")]
+pub use apply::{apply, apply_with_options};
\ No newline at end of file
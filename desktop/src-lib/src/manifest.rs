@@ -0,0 +1,195 @@
+//! Manifest of the files written by a workspace run, for reproducibility.
+//! Lists each output file with its SHA-256 digest, the input files it was computed from,
+//! and the formulas used.
+use hermes_core as core;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[cfg(feature = "fs")]
+use crate::{audit, formula};
+#[cfg(feature = "fs")]
+use std::{fs, io, path::Path};
+
+/// Name of the manifest file, relative to the project root.
+/// Replaced by each run.
+pub const MANIFEST_FILE: &str = ".hermes-manifest.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Manifest {
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+
+    /// Version of hermes the run was made with.
+    pub version: String,
+    pub outputs: Vec<File>,
+    pub inputs: Vec<File>,
+    pub formulas: Vec<FormulaUse>,
+}
+
+/// File with its content digest.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct File {
+    /// Path relative to the project root.
+    pub path: PathBuf,
+
+    /// Hex encoded SHA-256 digest of the file's content.
+    /// `None` if the file could not be read.
+    pub sha256: Option<String>,
+}
+
+/// Formula whose result was written to a file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FormulaUse {
+    /// Path of the file written to, relative to the project root.
+    pub path: PathBuf,
+
+    /// Sheet index for workbooks.
+    /// `None` for csv files.
+    pub sheet: Option<core::data::IndexType>,
+    pub cell: core::data::CellIndex,
+    pub formula: String,
+}
+
+#[cfg(feature = "fs")]
+impl Manifest {
+    /// Create the manifest of a run.
+    /// Files are hashed as they are when the manifest is created,
+    /// so it should be created once the run has finished.
+    ///
+    /// # Arguments
+    /// + `root`: Project root.
+    /// + `orders`: Orders of the run.
+    /// + `entries`: Entries of the orders that succeeded.
+    pub fn new(
+        root: impl AsRef<Path>,
+        orders: &[formula::WorkspaceOrder],
+        entries: &[audit::Entry],
+    ) -> Self {
+        let root = root.as_ref();
+        let file = |path: PathBuf| {
+            let sha256 = crate::hash::hash_file(root.join(&path))
+                .ok()
+                .map(|digest| crate::hash::to_hex(&digest));
+            File { path, sha256 }
+        };
+        let relative = |path: &PathBuf| {
+            path.strip_prefix(root)
+                .map(|path| path.to_path_buf())
+                .unwrap_or(path.clone())
+        };
+
+        let mut outputs = entries
+            .iter()
+            .map(|entry| entry.path.clone())
+            .collect::<Vec<_>>();
+        outputs.sort();
+        outputs.dedup();
+
+        let mut inputs = orders
+            .iter()
+            .flat_map(|order| match order {
+                formula::WorkspaceOrder::Create(create) => {
+                    create.inputs.iter().map(relative).collect()
+                }
+                formula::WorkspaceOrder::Update(update) => vec![relative(&update.path)],
+                formula::WorkspaceOrder::Clean(clean) => vec![relative(&clean.path)],
+            })
+            .collect::<Vec<_>>();
+        inputs.sort();
+        inputs.dedup();
+
+        let formulas = entries
+            .iter()
+            .flat_map(|entry| {
+                entry.changes.iter().filter_map(|change| {
+                    change.formula.as_ref().map(|formula| FormulaUse {
+                        path: entry.path.clone(),
+                        sheet: change.sheet,
+                        cell: change.cell.clone(),
+                        formula: formula.clone(),
+                    })
+                })
+            })
+            .collect();
+
+        Self {
+            timestamp: chrono::Utc::now(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            outputs: outputs.into_iter().map(file).collect(),
+            inputs: inputs.into_iter().map(file).collect(),
+            formulas,
+        }
+    }
+
+    /// Write the manifest to the project, replacing any previous manifest.
+    pub fn save(&self, root: impl AsRef<Path>) -> io::Result<()> {
+        let content = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        fs::write(root.as_ref().join(MANIFEST_FILE), content)
+    }
+}
+
+#[cfg(all(test, feature = "fs"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn manifest_new() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("in.csv"), "1,2\n").unwrap();
+        fs::write(root.join("out.csv"), "a\n3\n").unwrap();
+
+        let orders = vec![
+            formula::Create {
+                path: root.join("out.csv"),
+                format: formula::OutputFormat::Csv,
+                columns: vec![],
+                inputs: vec![root.join("in.csv"), root.join("missing.csv")],
+            }
+            .into(),
+        ];
+        let entries = vec![audit::Entry::now(
+            PathBuf::from("out.csv"),
+            audit::OrderKind::Create,
+            vec![audit::CellChange {
+                sheet: None,
+                cell: core::data::CellIndex::new(1u16, 0u16),
+                old: core::expr::Value::Empty,
+                new: core::expr::Value::Int(3),
+                formula: Some("1 + 2".to_string()),
+                provenance: None,
+                outcome: audit::CellOutcome::Written,
+            }],
+        )];
+
+        let manifest = Manifest::new(root, &orders, &entries);
+        assert_eq!(manifest.outputs.len(), 1);
+        assert_eq!(
+            manifest.outputs[0].sha256,
+            Some(crate::hash::to_hex(
+                &crate::hash::hash_file(root.join("out.csv")).unwrap()
+            ))
+        );
+        let inputs = manifest
+            .inputs
+            .iter()
+            .map(|file| (file.path.clone(), file.sha256.is_some()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            inputs,
+            vec![
+                (PathBuf::from("in.csv"), true),
+                (PathBuf::from("missing.csv"), false),
+            ]
+        );
+        assert_eq!(manifest.formulas.len(), 1);
+        assert_eq!(manifest.formulas[0].formula, "1 + 2");
+
+        manifest.save(root).unwrap();
+        let saved: Manifest =
+            serde_json::from_slice(&fs::read(root.join(MANIFEST_FILE)).unwrap()).unwrap();
+        assert_eq!(saved, manifest);
+    }
+}
@@ -0,0 +1,225 @@
+//! Group rows of a sheet and summarize a column of each group.
+use crate::{
+    data::{CellMap, Data},
+    formula,
+};
+use hermes_core as core;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Pivot {
+    /// Columns rows are grouped by.
+    pub group_by: Vec<core::data::IndexType>,
+
+    /// Column aggregated for each group.
+    pub value: core::data::IndexType,
+    pub aggregate: Aggregate,
+
+    /// Whether the first row holds column headers rather than data.
+    #[serde(default)]
+    pub header: bool,
+}
+
+impl Pivot {
+    /// Summarize the cells of a sheet.
+    /// Groups are ordered by their values.
+    /// Rows with no values in any of the group or value columns are ignored.
+    ///
+    /// # Returns
+    /// A column for each group column followed by the aggregate column.
+    pub fn apply(&self, cells: &CellMap) -> Vec<formula::OutputColumn> {
+        let rows = cells.keys().map(|idx| idx.row() + 1).max().unwrap_or(0);
+        let value = |row, col| {
+            cells
+                .get(&core::data::CellIndex::new(row, col))
+                .cloned()
+                .unwrap_or(Data::Empty)
+        };
+
+        let first_row = if self.header { 1 } else { 0 };
+        let mut groups = BTreeMap::<Vec<String>, (Vec<Data>, Accumulator)>::new();
+        for row in first_row..rows {
+            let keys = self
+                .group_by
+                .iter()
+                .map(|col| value(row, *col))
+                .collect::<Vec<_>>();
+            let data = value(row, self.value);
+            if keys.iter().chain([&data]).all(is_empty) {
+                continue;
+            }
+
+            let key = keys.iter().map(|key| key.to_string()).collect();
+            groups
+                .entry(key)
+                .or_insert_with(|| (keys, Accumulator::default()))
+                .1
+                .push(&data);
+        }
+
+        let header = |col: core::data::IndexType| {
+            if self.header {
+                let header = value(0, col);
+                if !is_empty(&header) {
+                    return header.to_string();
+                }
+            }
+
+            core::utils::index_to_col(col)
+        };
+
+        let mut columns = self
+            .group_by
+            .iter()
+            .map(|col| formula::OutputColumn {
+                header: header(*col),
+                values: Vec::with_capacity(groups.len()),
+            })
+            .collect::<Vec<_>>();
+        let mut aggregate = formula::OutputColumn {
+            header: format!("{}({})", self.aggregate.label(), header(self.value)),
+            values: Vec::with_capacity(groups.len()),
+        };
+
+        for (keys, accumulator) in groups.into_values() {
+            for (column, key) in columns.iter_mut().zip(keys) {
+                column.values.push(key);
+            }
+            aggregate.values.push(accumulator.value(self.aggregate));
+        }
+
+        columns.push(aggregate);
+        columns
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Aggregate {
+    /// Sum of numeric values.
+    #[default]
+    Sum,
+
+    /// Mean of numeric values.
+    Mean,
+
+    /// Number of non-empty values.
+    Count,
+}
+
+impl Aggregate {
+    pub const ALL: &[Self] = &[Self::Sum, Self::Mean, Self::Count];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Sum => "sum",
+            Self::Mean => "mean",
+            Self::Count => "count",
+        }
+    }
+}
+
+#[derive(Default)]
+struct Accumulator {
+    count: usize,
+    numeric: usize,
+    sum: f64,
+}
+
+impl Accumulator {
+    fn push(&mut self, value: &Data) {
+        if is_empty(value) {
+            return;
+        }
+
+        self.count += 1;
+        let number = match value {
+            Data::Int(value) => *value as f64,
+            Data::Float(value) if value.is_finite() => *value,
+            _ => return,
+        };
+        self.numeric += 1;
+        self.sum += number;
+    }
+
+    fn value(&self, aggregate: Aggregate) -> Data {
+        match aggregate {
+            Aggregate::Sum => Data::Float(self.sum),
+            Aggregate::Mean => {
+                if self.numeric == 0 {
+                    Data::Empty
+                } else {
+                    Data::Float(self.sum / self.numeric as f64)
+                }
+            }
+            Aggregate::Count => Data::Int(self.count as i64),
+        }
+    }
+}
+
+fn is_empty(value: &Data) -> bool {
+    match value {
+        Data::Empty => true,
+        Data::String(value) => value.is_empty(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn pivot_apply() {
+        let rows = [
+            ["site", "year", "count"],
+            ["b", "2024", "3"],
+            ["a", "2024", "1"],
+            ["b", "2024", "5"],
+            ["a", "2025", "x"],
+        ];
+        let mut cells = CellMap::new();
+        for (row, values) in rows.iter().enumerate() {
+            for (col, value) in values.iter().enumerate() {
+                let value = match value.parse::<i64>() {
+                    Ok(value) => Data::Int(value),
+                    Err(_) => Data::String(value.to_string()),
+                };
+                cells.insert(core::data::CellIndex::new(row as u16, col as u16), value);
+            }
+        }
+
+        let mut pivot = Pivot {
+            group_by: vec![0],
+            value: 2,
+            aggregate: Aggregate::Sum,
+            header: true,
+        };
+        let columns = pivot.apply(&cells);
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[0].header, "site");
+        assert_eq!(columns[1].header, "sum(count)");
+        assert_eq!(
+            columns[0].values,
+            vec![Data::String("a".to_string()), Data::String("b".to_string())]
+        );
+        assert_eq!(columns[1].values, vec![Data::Float(1.0), Data::Float(8.0)]);
+
+        pivot.group_by = vec![0, 1];
+        pivot.aggregate = Aggregate::Count;
+        let columns = pivot.apply(&cells);
+        assert_eq!(columns.len(), 3);
+        assert_eq!(
+            columns[2].values,
+            vec![Data::Int(1), Data::Int(1), Data::Int(2)]
+        );
+
+        pivot.aggregate = Aggregate::Mean;
+        pivot.header = false;
+        let columns = pivot.apply(&cells);
+        assert_eq!(columns[0].header, "A");
+        assert_eq!(columns[2].header, "mean(C)");
+        assert_eq!(columns[2].values.len(), 4);
+        assert_eq!(columns[2].values[1], Data::Empty);
+    }
+}
@@ -0,0 +1,309 @@
+//! Running workspace orders against the file system.
+#[cfg(feature = "fs")]
+use crate::{
+    audit, clean, data, diff,
+    formula::{self, WorkspaceOrder, error},
+};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "fs")]
+use std::{collections::HashSet, fs, io};
+
+/// Where an order writes its results.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Output {
+    /// Nothing is written.
+    None,
+
+    /// The order's file is written to directly.
+    InPlace,
+
+    /// A [staging file](crate::formula::staged_path) next to the order's file is written to.
+    Staged,
+
+    /// The staging file written by an earlier order of the run to the same file
+    /// is read and written to, so the changes of both orders are kept.
+    Restaged,
+}
+
+impl Output {
+    /// # Returns
+    /// Path to write the results for the file at `path` to,
+    /// or `None` if nothing should be written.
+    pub fn path(&self, path: &Path) -> Option<PathBuf> {
+        match self {
+            Self::None => None,
+            Self::InPlace => Some(path.to_path_buf()),
+            Self::Staged | Self::Restaged => Some(crate::formula::staged_path(path)),
+        }
+    }
+
+    /// # Returns
+    /// Path to read the current contents of the file at `path` from.
+    pub fn source(&self, path: &Path) -> PathBuf {
+        match self {
+            Self::None | Self::InPlace | Self::Staged => path.to_path_buf(),
+            Self::Restaged => crate::formula::staged_path(path),
+        }
+    }
+}
+
+/// Run orders one after another.
+/// Orders are [coalesced](crate::formula::coalesce) before being run.
+/// With [`Output::Staged`], files are only moved into place if every order succeeds,
+/// and orders on a file staged by an earlier order build on its [staged](Output::Restaged) changes.
+///
+/// # Returns
+/// Changes made by each coalesced order, with the indices of the orders it was built from.
+/// With [`Output::None`], the changes that would be made.
+/// `Err` with the index and error of each order that failed.
+#[cfg(feature = "fs")]
+pub fn run(
+    orders: Vec<WorkspaceOrder>,
+    output: Output,
+) -> Result<Vec<(Vec<usize>, Vec<audit::CellChange>)>, Vec<(usize, error::WorkspaceOrder)>> {
+    let mut results = vec![];
+    let mut errors = vec![];
+    let mut staged_files = HashSet::new();
+    for (indices, order) in formula::coalesce(orders.into_iter().enumerate()) {
+        let file = order.path().cloned();
+        let order_output = match &file {
+            Some(file) if output == Output::Staged && !staged_files.insert(file.clone()) => {
+                Output::Restaged
+            }
+            _ => output,
+        };
+        match run_order(order, order_output) {
+            Ok(changes) => results.push((indices, changes, file)),
+            Err(err) => errors.extend(indices.into_iter().map(|idx| (idx, err.clone()))),
+        }
+    }
+
+    if output == Output::Staged {
+        let staged = results
+            .iter()
+            .filter_map(|(indices, _, file)| Some((indices, file.as_deref()?)))
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            let files = staged.iter().map(|(_, file)| *file).collect::<Vec<_>>();
+            if let Err((idx, err)) = commit_all(&files) {
+                errors.extend(
+                    staged[idx]
+                        .0
+                        .iter()
+                        .map(|idx| (*idx, error::WorkspaceOrder::Save(err.kind()))),
+                );
+            }
+        } else {
+            for (_, file) in staged {
+                let _ = discard(file);
+            }
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(results
+            .into_iter()
+            .map(|(indices, changes, _)| (indices, changes))
+            .collect())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Run an order.
+///
+/// # Returns
+/// Changes made to the order's file.
+/// With [`Output::None`], the changes that would be made.
+#[cfg(feature = "fs")]
+pub fn run_order(
+    order: WorkspaceOrder,
+    output: Output,
+) -> Result<Vec<audit::CellChange>, error::WorkspaceOrder> {
+    match order {
+        WorkspaceOrder::Create(create) => match output.path(&create.path) {
+            None => Ok(create.changes()),
+            Some(path) => {
+                write_records(&path, create.format, create.records())?;
+                Ok(create.changes())
+            }
+        },
+        WorkspaceOrder::Update(update) => run_update(update, output),
+        WorkspaceOrder::Clean(clean) => run_clean(clean, output),
+    }
+}
+
+#[cfg(feature = "fs")]
+fn run_update(
+    update: formula::Update,
+    output: Output,
+) -> Result<Vec<audit::CellChange>, error::WorkspaceOrder> {
+    let formula::Update {
+        path,
+        updates,
+        options,
+    } = update;
+    match updates {
+        formula::Updates::Csv(updates) => {
+            let file = fs::File::open(output.source(&path))
+                .map_err(|err| error::WorkspaceOrder::OpenFile(err.kind()))?;
+            let rdr = csv::ReaderBuilder::new()
+                .has_headers(false)
+                .flexible(true)
+                .from_reader(file);
+            let mut csv = data::Csv::from_csv_reader_with_options(rdr, options)?;
+            let changes = updates
+                .into_iter()
+                .map(|update| update.apply(&mut csv.sheet))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if let Some(path) = output.path(&path) {
+                csv.save(path)?;
+            }
+            Ok(changes)
+        }
+        formula::Updates::Workbook(_) => Err(error::WorkspaceOrder::Unsupported),
+    }
+}
+
+#[cfg(feature = "fs")]
+fn run_clean(
+    clean: formula::Clean,
+    output: Output,
+) -> Result<Vec<audit::CellChange>, error::WorkspaceOrder> {
+    let mut csv =
+        data::Csv::load_from_path_with_options(output.source(&clean.path), clean.options)?;
+    let cells = clean::apply(csv.sheet.cells(), clean.header, &clean.operations);
+    let changes = diff::sheet(None, csv.sheet.cells(), &cells)
+        .into_iter()
+        .map(|diff| audit::CellChange {
+            sheet: diff.sheet,
+            cell: diff.cell,
+            old: diff.disk,
+            new: diff.memory,
+            formula: None,
+            provenance: None,
+            outcome: audit::CellOutcome::Written,
+        })
+        .collect();
+
+    if let Some(path) = output.path(&clean.path) {
+        csv.sheet = data::Spreadsheet::from_cells(cells);
+        csv.save(path)?;
+    }
+    Ok(changes)
+}
+
+/// Write records to a delimited file.
+/// An existing file at the path is replaced.
+#[cfg(feature = "fs")]
+pub fn write_records(
+    path: &Path,
+    format: formula::OutputFormat,
+    records: impl IntoIterator<Item = Vec<String>>,
+) -> Result<(), error::WorkspaceOrder> {
+    let mut wtr = csv::WriterBuilder::new()
+        .delimiter(format.delimiter())
+        .flexible(true)
+        .from_path(path)
+        .map_err(|err| match err.into_kind() {
+            csv::ErrorKind::Io(err) => error::WorkspaceOrder::Save(err.kind()),
+            _ => error::WorkspaceOrder::Save(io::ErrorKind::Other),
+        })?;
+
+    for record in records {
+        wtr.write_record(record)
+            .map_err(|_| error::WorkspaceOrder::Save(io::ErrorKind::Other))?;
+    }
+
+    wtr.flush()
+        .map_err(|err| error::WorkspaceOrder::Save(err.kind()))
+}
+
+/// Move the staged file of `path` into place.
+#[cfg(feature = "fs")]
+pub fn commit(path: impl AsRef<Path>) -> io::Result<()> {
+    let path = path.as_ref();
+    fs::rename(crate::formula::staged_path(path), path)
+}
+
+/// Move the staged files of `paths` into place, all or nothing.
+/// A path listed more than once is moved once.
+/// The original of each file is kept at its [backup path](crate::formula::backup_path)
+/// until every file is in place, so if a file can not be moved
+/// the files already moved are restored and the remaining staged files removed.
+///
+/// # Returns
+/// `Err` with the index of the file that could not be moved and the error.
+#[cfg(feature = "fs")]
+pub fn commit_all(paths: &[&Path]) -> Result<(), (usize, io::Error)> {
+    let mut committed = Vec::with_capacity(paths.len());
+    let mut unique = HashSet::with_capacity(paths.len());
+    for (idx, path) in paths.iter().enumerate() {
+        if !unique.insert(*path) {
+            continue;
+        }
+
+        match commit_with_backup(path) {
+            Ok(backed_up) => committed.push((path, backed_up)),
+            Err(err) => {
+                for (path, backed_up) in committed.into_iter().rev() {
+                    let restored = if backed_up {
+                        fs::rename(crate::formula::backup_path(path), path)
+                    } else {
+                        fs::remove_file(path)
+                    };
+                    if let Err(err) = restored {
+                        #[cfg(feature = "tracing")]
+                        tracing::error!(?err, ?path, "could not restore file");
+                    }
+                }
+                for path in &paths[idx..] {
+                    let _ = discard(path);
+                }
+                return Err((idx, err));
+            }
+        }
+    }
+
+    for (path, backed_up) in committed {
+        if backed_up {
+            let _ = fs::remove_file(crate::formula::backup_path(path));
+        }
+    }
+    Ok(())
+}
+
+/// Move the staged file of `path` into place,
+/// keeping the original at its [backup path](crate::formula::backup_path).
+///
+/// # Returns
+/// Whether the original existed, and so was backed up.
+#[cfg(feature = "fs")]
+fn commit_with_backup(path: &Path) -> io::Result<bool> {
+    let backup = crate::formula::backup_path(path);
+    let backed_up = match fs::rename(path, &backup) {
+        Ok(()) => true,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => false,
+        Err(err) => return Err(err),
+    };
+
+    if let Err(err) = commit(path) {
+        if backed_up {
+            let _ = fs::rename(&backup, path);
+        }
+        return Err(err);
+    }
+
+    Ok(backed_up)
+}
+
+/// Remove the staged file of `path`.
+/// A missing staged file is not an error.
+#[cfg(feature = "fs")]
+pub fn discard(path: impl AsRef<Path>) -> io::Result<()> {
+    match fs::remove_file(crate::formula::staged_path(path)) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
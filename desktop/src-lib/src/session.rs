@@ -0,0 +1,102 @@
+//! Workspace sessions.
+//! The session is saved periodically while a project is open,
+//! so it can be recovered if the app exits uncleanly.
+use hermes_core as core;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Session {
+    /// Project root.
+    pub root: PathBuf,
+
+    /// Open datasets, relative to the project root.
+    pub datasets: Vec<PathBuf>,
+
+    /// Active dataset, relative to the project root.
+    pub active: Option<PathBuf>,
+
+    pub formulas: Vec<SessionFormula>,
+
+    /// Formula being edited.
+    pub pending: Option<PendingEdit>,
+
+    /// [Version](core::expr::VERSION) of the expression language the formulas were written under.
+    #[serde(default = "unversioned")]
+    pub expr_version: u32,
+}
+
+impl Session {
+    /// # Returns
+    /// Whether there is any work to recover.
+    pub fn is_empty(&self) -> bool {
+        self.datasets.is_empty() && self.formulas.is_empty()
+    }
+}
+
+fn unversioned() -> u32 {
+    core::expr::UNVERSIONED
+}
+
+/// Formula of a session.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SessionFormula {
+    /// Path of the dataset, relative to the project root.
+    pub path: PathBuf,
+
+    /// Sheet index for workbooks.
+    /// `None` for csv files.
+    pub sheet: Option<core::data::IndexType>,
+
+    pub cell: core::data::CellIndex,
+    pub value: String,
+}
+
+/// Edit to a formula that has not been applied.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PendingEdit {
+    /// Index of the formula in the session's formulas.
+    pub formula: usize,
+    pub input: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn session_round_trip() {
+        let session = Session {
+            root: PathBuf::from("/project"),
+            datasets: vec![PathBuf::from("data.csv"), PathBuf::from("book.xlsx")],
+            active: Some(PathBuf::from("book.xlsx")),
+            formulas: vec![SessionFormula {
+                path: PathBuf::from("book.xlsx"),
+                sheet: Some(1),
+                cell: core::data::CellIndex::new(2u8, 3u8),
+                value: "A1 + 1".to_string(),
+            }],
+            pending: Some(PendingEdit {
+                formula: 0,
+                input: "A1 +".to_string(),
+            }),
+            expr_version: core::expr::VERSION,
+        };
+
+        let json = serde_json::to_string(&session).unwrap();
+        let parsed: Session = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, session);
+        assert!(!parsed.is_empty());
+    }
+
+    #[test]
+    fn session_without_version() {
+        let json =
+            r#"{"root":"/project","datasets":[],"active":null,"formulas":[],"pending":null}"#;
+        let session: Session = serde_json::from_str(json).unwrap();
+        assert_eq!(session.expr_version, core::expr::UNVERSIONED);
+    }
+}
@@ -0,0 +1,525 @@
+//! Application settings.
+//! Settings are saved globally, and may be overridden for a project.
+use crate::data;
+use hermes_core as core;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::PathBuf,
+    str::FromStr,
+};
+
+/// Event emitted to the ui with the new [`Theme`] when the system theme changes.
+pub const THEME_CHANGED_EVENT: &str = "theme-changed";
+
+/// Name of the project settings file, relative to the project root.
+/// If present, replaces the global settings for the project.
+pub const SETTINGS_FILE: &str = ".hermes-settings.json";
+
+/// Name of the file listing the project's protected files, relative to the project root.
+/// Kept apart from the [settings file](SETTINGS_FILE) so protection does not depend on the [`Scope`].
+pub const PROTECTED_FILE: &str = ".hermes-protected.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct Settings {
+    pub theme: Theme,
+
+    /// Language tag of the interface, e.g. `en-US`.
+    /// `None` to use the system language.
+    pub locale: Option<String>,
+
+    /// Separator used when displaying decimal numbers.
+    pub decimal: DecimalSeparator,
+
+    /// Dialect used to import csv files without import options.
+    pub csv: data::Dialect,
+
+    /// Seconds between automatic saves.
+    /// `None` to disable autosave.
+    pub autosave_interval: Option<u32>,
+
+    /// How formulas evaluate references to empty cells.
+    pub empty_cells: core::expr::EmptyCells,
+
+    /// What formulas compare empty values as.
+    pub empty_comparison: core::expr::EmptyComparison,
+
+    /// How formulas evaluate integer arithmetic that overflows.
+    pub overflow: core::expr::Overflow,
+
+    /// Write a [manifest](crate::manifest) of the files written each time the workspace is run.
+    pub manifest: bool,
+
+    pub notifications: Notifications,
+    pub limits: Limits,
+
+    /// Shortcuts replacing the [default](Action::default_shortcut) of their action.
+    pub keybindings: BTreeMap<Action, Shortcut>,
+}
+
+impl Settings {
+    /// # Returns
+    /// Options formulas are evaluated with.
+    pub fn eval_options(&self) -> core::expr::Options {
+        core::expr::Options {
+            empty_cells: self.empty_cells,
+            empty_comparison: self.empty_comparison,
+            overflow: self.overflow,
+            ..Default::default()
+        }
+    }
+
+    /// # Returns
+    /// Shortcut bound to the action.
+    pub fn shortcut(&self, action: Action) -> Shortcut {
+        self.keybindings
+            .get(&action)
+            .cloned()
+            .unwrap_or_else(|| action.default_shortcut())
+    }
+}
+
+/// Settings in effect for a project.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ProjectSettings {
+    pub settings: Settings,
+
+    /// Where the settings are saved.
+    pub scope: Scope,
+
+    /// Files protected from modification, relative to the project root.
+    #[serde(default)]
+    pub protected: BTreeSet<PathBuf>,
+}
+
+/// Where settings are saved.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Scope {
+    /// Settings used by all projects without their own settings.
+    Global,
+
+    /// Settings for a single project.
+    Project,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Theme {
+    /// Follow the system theme.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub const ALL: &[Self] = &[Self::System, Self::Light, Self::Dark];
+
+    /// # Arguments
+    /// + `system_dark`: Whether the system theme is dark.
+    pub fn is_dark(&self, system_dark: bool) -> bool {
+        match self {
+            Self::System => system_dark,
+            Self::Light => false,
+            Self::Dark => true,
+        }
+    }
+
+    /// # Returns
+    /// Theme following this one when toggling between themes.
+    pub fn next(&self) -> Self {
+        match self {
+            Self::System => Self::Light,
+            Self::Light => Self::Dark,
+            Self::Dark => Self::System,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum DecimalSeparator {
+    /// `1.5`
+    #[default]
+    Point,
+
+    /// `1,5`
+    Comma,
+}
+
+impl DecimalSeparator {
+    pub const ALL: &[Self] = &[Self::Point, Self::Comma];
+
+    pub fn as_char(&self) -> char {
+        match self {
+            Self::Point => '.',
+            Self::Comma => ',',
+        }
+    }
+
+    /// Format a number using the separator.
+    pub fn format(&self, value: f64) -> String {
+        let value = value.to_string();
+        match self {
+            Self::Point => value,
+            Self::Comma => value.replace('.', ","),
+        }
+    }
+}
+
+/// Limits on the size of loaded datasets.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct Limits {
+    /// Maximum size, in bytes, of a file loaded entirely into memory.
+    pub max_dataset_size: u64,
+
+    /// Number of rows loaded from a file larger than [`Self::max_dataset_size`].
+    pub preview_rows: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_dataset_size: 256 * 1024 * 1024,
+            preview_rows: 10_000,
+        }
+    }
+}
+
+/// Long running operations that may notify the user when they finish.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Operation {
+    /// A dataset was loaded.
+    Load,
+
+    /// The workspace was run.
+    Run,
+}
+
+/// Whether a system notification is shown when an operation finishes while the window is not focused.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default)]
+pub struct Notifications {
+    pub load: bool,
+    pub run: bool,
+}
+
+impl Notifications {
+    pub fn enabled(&self, operation: Operation) -> bool {
+        match operation {
+            Operation::Load => self.load,
+            Operation::Run => self.run,
+        }
+    }
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            load: true,
+            run: true,
+        }
+    }
+}
+
+/// Actions that can be triggered by a keyboard shortcut.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Action {
+    /// Save the session.
+    SaveWorkspace,
+    RunWorkspace,
+
+    /// Show or hide the side panels.
+    ToggleExplorer,
+    OpenFormulaEditor,
+
+    /// Show the shortcuts in effect.
+    ShowShortcuts,
+}
+
+impl Action {
+    pub const ALL: &[Self] = &[
+        Self::SaveWorkspace,
+        Self::RunWorkspace,
+        Self::ToggleExplorer,
+        Self::OpenFormulaEditor,
+        Self::ShowShortcuts,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::SaveWorkspace => "Save workspace",
+            Self::RunWorkspace => "Run workspace",
+            Self::ToggleExplorer => "Toggle explorer",
+            Self::OpenFormulaEditor => "Open formula editor",
+            Self::ShowShortcuts => "Show keyboard shortcuts",
+        }
+    }
+
+    pub fn default_shortcut(&self) -> Shortcut {
+        let (shift, key) = match self {
+            Self::SaveWorkspace => (false, "S"),
+            Self::RunWorkspace => (true, "Enter"),
+            Self::ToggleExplorer => (false, "B"),
+            Self::OpenFormulaEditor => (false, "E"),
+            Self::ShowShortcuts => (false, "/"),
+        };
+
+        Shortcut {
+            ctrl: true,
+            shift,
+            alt: false,
+            key: key.to_string(),
+        }
+    }
+}
+
+/// Key combination, e.g. `Ctrl+Shift+S`.
+/// `Ctrl` also matches the command key on macOS.
+/// Serialized as its display string.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+
+    /// Key as reported by `KeyboardEvent.key`.
+    /// Single characters are upper case.
+    pub key: String,
+}
+
+impl Shortcut {
+    /// Shortcut of a key press.
+    /// `None` if the key is a modifier.
+    ///
+    /// # Arguments
+    /// + `key`: Key as reported by `KeyboardEvent.key`.
+    /// + `ctrl`: Whether the control or command key was held.
+    pub fn from_key(key: &str, ctrl: bool, shift: bool, alt: bool) -> Option<Self> {
+        if key.is_empty() || Self::is_modifier(key) {
+            return None;
+        }
+
+        Some(Self {
+            ctrl,
+            shift,
+            alt,
+            key: normalize_key(key),
+        })
+    }
+
+    /// # Returns
+    /// Whether a key press triggers the shortcut.
+    ///
+    /// # Arguments
+    /// + `key`: Key as reported by `KeyboardEvent.key`.
+    /// + `ctrl`: Whether the control or command key was held.
+    pub fn matches(&self, key: &str, ctrl: bool, shift: bool, alt: bool) -> bool {
+        self.ctrl == ctrl
+            && self.shift == shift
+            && self.alt == alt
+            && self.key == normalize_key(key)
+    }
+
+    /// # Returns
+    /// `true` if the key is a modifier, so can not be bound on its own.
+    pub fn is_modifier(key: &str) -> bool {
+        matches!(key, "Control" | "Shift" | "Alt" | "Meta")
+    }
+}
+
+impl fmt::Display for Shortcut {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for Shortcut {
+    type Err = error::InvalidShortcut;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut shortcut = Self {
+            ctrl: false,
+            shift: false,
+            alt: false,
+            key: String::new(),
+        };
+
+        // `+` may be the key itself, e.g. `Ctrl++`.
+        let (modifiers, key) = match s.strip_suffix("++") {
+            Some(modifiers) => (modifiers, "+"),
+            None => s.rsplit_once('+').unwrap_or(("", s)),
+        };
+        for modifier in modifiers.split('+').filter(|modifier| !modifier.is_empty()) {
+            match modifier.trim().to_ascii_lowercase().as_str() {
+                "ctrl" | "cmd" => shortcut.ctrl = true,
+                "shift" => shortcut.shift = true,
+                "alt" => shortcut.alt = true,
+                _ => return Err(error::InvalidShortcut(s.to_string())),
+            }
+        }
+
+        let key = key.trim();
+        if key.is_empty() || Self::is_modifier(key) {
+            return Err(error::InvalidShortcut(s.to_string()));
+        }
+        shortcut.key = normalize_key(key);
+        Ok(shortcut)
+    }
+}
+
+impl Serialize for Shortcut {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Shortcut {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let value = String::deserialize(deserializer)?;
+        Self::from_str(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Shortcut {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Shortcut".into()
+    }
+
+    fn json_schema(_generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "string",
+            "description": "Key combination, e.g. `Ctrl+Shift+S`.",
+        })
+    }
+}
+
+/// Upper case single character keys so shortcuts match regardless of shift and caps lock.
+fn normalize_key(key: &str) -> String {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(ch), None) => ch.to_uppercase().collect(),
+        _ => key.to_string(),
+    }
+}
+
+pub mod error {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    #[error("invalid shortcut `{0}`")]
+    pub struct InvalidShortcut(pub String);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn settings_partial() {
+        let settings: Settings = serde_json::from_str(r#"{ "theme": "Dark" }"#).unwrap();
+        assert_eq!(settings.theme, Theme::Dark);
+        assert_eq!(settings.limits, Limits::default());
+        assert_eq!(settings.csv, data::Dialect::default());
+        assert_eq!(settings.empty_cells, core::expr::EmptyCells::Empty);
+        assert_eq!(
+            settings.empty_comparison,
+            core::expr::EmptyComparison::Blank
+        );
+        assert_eq!(settings.overflow, core::expr::Overflow::Promote);
+    }
+
+    #[test]
+    fn theme_toggle() {
+        let mut theme = Theme::default();
+        for _ in Theme::ALL {
+            theme = theme.next();
+        }
+        assert_eq!(theme, Theme::default());
+
+        assert!(Theme::System.is_dark(true));
+        assert!(!Theme::Light.is_dark(true));
+        assert!(Theme::Dark.is_dark(false));
+    }
+
+    #[test]
+    fn decimal_separator_format() {
+        assert_eq!(DecimalSeparator::Point.format(1.5), "1.5");
+        assert_eq!(DecimalSeparator::Comma.format(1.5), "1,5");
+        assert_eq!(DecimalSeparator::Comma.format(-2.0), "-2");
+    }
+
+    #[test]
+    fn shortcut_round_trip() {
+        let shortcut = Shortcut::from_str("ctrl+shift+s").unwrap();
+        assert!(shortcut.ctrl && shortcut.shift && !shortcut.alt);
+        assert_eq!(shortcut.key, "S");
+        assert_eq!(shortcut.to_string(), "Ctrl+Shift+S");
+
+        let plus = Shortcut::from_str("Ctrl++").unwrap();
+        assert_eq!(plus.key, "+");
+        assert_eq!(Shortcut::from_str(&plus.to_string()).unwrap(), plus);
+
+        assert!(Shortcut::from_str("Ctrl+").is_err());
+        assert!(Shortcut::from_str("Ctrl+Shift").is_err());
+        assert!(Shortcut::from_str("Hyper+S").is_err());
+    }
+
+    #[test]
+    fn shortcut_matches() {
+        let shortcut = Action::SaveWorkspace.default_shortcut();
+        assert!(shortcut.matches("s", true, false, false));
+        assert!(shortcut.matches("S", true, false, false));
+        assert!(!shortcut.matches("s", false, false, false));
+        assert!(!shortcut.matches("s", true, true, false));
+    }
+
+    #[test]
+    fn keybindings_override_defaults() {
+        let settings: Settings =
+            serde_json::from_str(r#"{ "keybindings": { "RunWorkspace": "Ctrl+R" } }"#).unwrap();
+        assert_eq!(
+            settings.shortcut(Action::RunWorkspace).to_string(),
+            "Ctrl+R"
+        );
+        assert_eq!(
+            settings.shortcut(Action::SaveWorkspace),
+            Action::SaveWorkspace.default_shortcut()
+        );
+
+        let mut defaults = Action::ALL
+            .iter()
+            .map(|action| action.default_shortcut())
+            .collect::<Vec<_>>();
+        defaults.sort_by_key(|shortcut| shortcut.to_string());
+        defaults.dedup();
+        assert_eq!(defaults.len(), Action::ALL.len());
+    }
+}
@@ -0,0 +1,185 @@
+//! Snapshots of evaluated cell values.
+//! A snapshot records the expected results of a project,
+//! so changes to input data or formulas that alter them can be detected.
+use hermes_core as core;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Name of the snapshot file, relative to the project root.
+pub const SNAPSHOT_FILE: &str = ".hermes-snapshot.json";
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Snapshot {
+    pub cells: Vec<CellSnapshot>,
+}
+
+impl Snapshot {
+    /// Compare the snapshot to current values.
+    ///
+    /// # Arguments
+    /// + `current`: Current value of a cell.
+    ///     `None` if the cell could not be evaluated.
+    ///
+    /// # Returns
+    /// Cells whose current value differs from the snapshot beyond their tolerance.
+    pub fn check(
+        &self,
+        mut current: impl FnMut(&CellSnapshot) -> Option<core::expr::Value>,
+    ) -> Vec<Drift> {
+        self.cells
+            .iter()
+            .filter_map(|cell| {
+                let actual = current(cell);
+                let matches = actual
+                    .as_ref()
+                    .map(|actual| cell.tolerance.accepts(&cell.value, actual))
+                    .unwrap_or(false);
+
+                (!matches).then(|| Drift {
+                    cell: cell.clone(),
+                    actual,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Expected value of a cell.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CellSnapshot {
+    /// Path of the dataset, relative to the project root.
+    pub path: PathBuf,
+
+    /// Sheet index for workbooks.
+    /// `None` for csv files.
+    #[serde(default)]
+    pub sheet: Option<core::data::IndexType>,
+
+    pub cell: core::data::CellIndex,
+    pub value: core::expr::Value,
+
+    #[serde(default)]
+    pub tolerance: Tolerance,
+}
+
+/// Allowed difference between an expected and actual numeric value.
+/// Non-numeric values must be equal.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Tolerance {
+    pub absolute: f64,
+
+    /// Fraction of the expected value.
+    pub relative: f64,
+}
+
+impl Tolerance {
+    /// # Returns
+    /// If `actual` is within tolerance of `expected`.
+    pub fn accepts(&self, expected: &core::expr::Value, actual: &core::expr::Value) -> bool {
+        match (expected.as_number(), actual.as_number()) {
+            (Some(expected), Some(actual)) => {
+                let allowed = self.absolute.max(self.relative * expected.abs());
+                (expected - actual).abs() <= allowed
+            }
+            _ => expected == actual,
+        }
+    }
+}
+
+/// A cell whose value changed from its snapshot.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Drift {
+    pub cell: CellSnapshot,
+
+    /// `None` if the cell could not be evaluated.
+    pub actual: Option<core::expr::Value>,
+}
+
+pub mod error {
+    use serde::{Deserialize, Serialize};
+    use std::io;
+
+    #[derive(Serialize, Deserialize, Debug, thiserror::Error, Clone)]
+    #[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+    pub enum Snapshot {
+        /// No snapshot has been saved for the project.
+        #[error("No snapshot has been saved.")]
+        NotFound,
+
+        /// The snapshot file could not be parsed.
+        #[error("The snapshot file is invalid: {0}")]
+        Invalid(String),
+
+        #[error("{0}")]
+        Io(
+            #[serde(with = "io_error_serde::ErrorKind")]
+            #[cfg_attr(feature = "schema", schemars(with = "String"))]
+            io::ErrorKind,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cell(value: core::expr::Value, tolerance: Tolerance) -> CellSnapshot {
+        CellSnapshot {
+            path: PathBuf::from("data.csv"),
+            sheet: None,
+            cell: core::data::CellIndex::new(0u8, 0u8),
+            value,
+            tolerance,
+        }
+    }
+
+    #[test]
+    fn snapshot_check() {
+        use hermes_core::expr::Value;
+
+        let snapshot = Snapshot {
+            cells: vec![
+                cell(Value::Float(1.0), Tolerance::default()),
+                cell(
+                    Value::Float(100.0),
+                    Tolerance {
+                        absolute: 0.0,
+                        relative: 0.01,
+                    },
+                ),
+                cell(Value::String("a".to_string()), Tolerance::default()),
+            ],
+        };
+
+        let current = [
+            Value::Int(1),
+            Value::Float(100.5),
+            Value::String("a".to_string()),
+        ];
+        let mut idx = 0;
+        let drift = snapshot.check(|_| {
+            idx += 1;
+            Some(current[idx - 1].clone())
+        });
+        assert!(drift.is_empty());
+
+        let current = [
+            Value::Float(1.1),
+            Value::Float(102.0),
+            Value::String("b".to_string()),
+        ];
+        let mut idx = 0;
+        let drift = snapshot.check(|_| {
+            idx += 1;
+            Some(current[idx - 1].clone())
+        });
+        assert_eq!(drift.len(), 3);
+
+        let drift = snapshot.check(|_| None);
+        assert_eq!(drift.len(), 3);
+        assert!(drift.iter().all(|drift| drift.actual.is_none()));
+    }
+}
@@ -0,0 +1,171 @@
+//! Reusable sets of formulas.
+//! A template records formulas by their position in a dataset,
+//! so they can be applied to other datasets with the same column structure.
+use hermes_core as core;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Template {
+    pub name: String,
+
+    /// Structure of the dataset the template was created from.
+    pub shape: Shape,
+    pub formulas: Vec<TemplateFormula>,
+
+    /// [Version](core::expr::VERSION) of the expression language the formulas were written under.
+    #[serde(default = "unversioned")]
+    pub expr_version: u32,
+}
+
+fn unversioned() -> u32 {
+    core::expr::UNVERSIONED
+}
+
+impl Template {
+    /// Check whether the template can be applied to a dataset.
+    ///
+    /// # Arguments
+    /// + `shape`: Structure of the target dataset.
+    ///
+    /// # Returns
+    /// `Err` describing the first mismatch.
+    pub fn check(&self, shape: &Shape) -> Result<(), Incompatible> {
+        match (&self.shape, shape) {
+            (Shape::Csv { cols: expected }, Shape::Csv { cols: found }) => {
+                if expected != found {
+                    return Err(Incompatible::Columns {
+                        sheet: None,
+                        expected: *expected,
+                        found: *found,
+                    });
+                }
+            }
+
+            (Shape::Workbook { cols: expected }, Shape::Workbook { cols: found }) => {
+                for formula in self.formulas.iter() {
+                    let Some(sheet) = formula.sheet else {
+                        continue;
+                    };
+                    let Some(found) = found.get(sheet as usize) else {
+                        return Err(Incompatible::SheetNotFound(sheet));
+                    };
+                    let expected = expected.get(sheet as usize).copied().unwrap_or(0);
+                    if expected != *found {
+                        return Err(Incompatible::Columns {
+                            sheet: Some(sheet),
+                            expected,
+                            found: *found,
+                        });
+                    }
+                }
+            }
+
+            _ => return Err(Incompatible::Kind),
+        }
+
+        Ok(())
+    }
+}
+
+/// Column structure of a dataset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Shape {
+    Csv {
+        cols: core::data::IndexType,
+    },
+
+    /// Number of columns of each sheet.
+    Workbook {
+        cols: Vec<core::data::IndexType>,
+    },
+}
+
+/// Formula bound to a cell position instead of a dataset.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TemplateFormula {
+    /// Sheet index for workbooks.
+    /// `None` for csv files.
+    #[serde(default)]
+    pub sheet: Option<core::data::IndexType>,
+
+    pub cell: core::data::CellIndex,
+    pub value: String,
+}
+
+/// Reason a template can not be applied to a dataset.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, thiserror::Error)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Incompatible {
+    #[error("template and dataset are different kinds")]
+    Kind,
+
+    #[error("sheet {0} does not exist")]
+    SheetNotFound(core::data::IndexType),
+
+    #[error("expected {expected} columns, found {found}")]
+    Columns {
+        sheet: Option<core::data::IndexType>,
+        expected: core::data::IndexType,
+        found: core::data::IndexType,
+    },
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn template_check() {
+        let formula = |sheet| TemplateFormula {
+            sheet,
+            cell: core::data::CellIndex::new(0u8, 3u8),
+            value: "A1 + B1".to_string(),
+        };
+
+        let csv = Template {
+            name: "csv".to_string(),
+            shape: Shape::Csv { cols: 3 },
+            formulas: vec![formula(None)],
+            expr_version: core::expr::VERSION,
+        };
+        assert_eq!(csv.check(&Shape::Csv { cols: 3 }), Ok(()));
+        assert_eq!(
+            csv.check(&Shape::Csv { cols: 4 }),
+            Err(Incompatible::Columns {
+                sheet: None,
+                expected: 3,
+                found: 4
+            })
+        );
+        assert_eq!(
+            csv.check(&Shape::Workbook { cols: vec![3] }),
+            Err(Incompatible::Kind)
+        );
+
+        let workbook = Template {
+            name: "workbook".to_string(),
+            shape: Shape::Workbook { cols: vec![2, 3] },
+            formulas: vec![formula(Some(1))],
+            expr_version: core::expr::VERSION,
+        };
+        assert_eq!(
+            workbook.check(&Shape::Workbook { cols: vec![5, 3] }),
+            Ok(())
+        );
+        assert_eq!(
+            workbook.check(&Shape::Workbook { cols: vec![2] }),
+            Err(Incompatible::SheetNotFound(1))
+        );
+        assert_eq!(
+            workbook.check(&Shape::Workbook { cols: vec![2, 2] }),
+            Err(Incompatible::Columns {
+                sheet: Some(1),
+                expected: 3,
+                found: 2
+            })
+        );
+    }
+}
@@ -0,0 +1,51 @@
+//! Append-only log of executed workspace orders.
+use hermes_desktop_lib as lib;
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::Path,
+};
+
+/// Append entries to the project's audit log, creating it if needed.
+pub fn append(root: impl AsRef<Path>, entries: &[lib::audit::Entry]) -> io::Result<()> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+
+    let mut content = Vec::new();
+    for entry in entries {
+        serde_json::to_writer(&mut content, entry).map_err(io::Error::other)?;
+        content.push(b'\n');
+    }
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(root.as_ref().join(lib::audit::AUDIT_FILE))?;
+    file.write_all(&content)
+}
+
+/// # Returns
+/// All entries of the project's audit log, oldest first.
+/// Empty if the log does not exist.
+pub fn read(root: impl AsRef<Path>) -> Result<Vec<lib::audit::Entry>, lib::audit::error::Audit> {
+    let file = match fs::File::open(root.as_ref().join(lib::audit::AUDIT_FILE)) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(err) => return Err(err.into()),
+    };
+
+    let mut entries = vec![];
+    for (idx, line) in io::BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry = serde_json::from_str(&line)
+            .map_err(|_| lib::audit::error::Audit::Invalid { line: idx + 1 })?;
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,55 @@
+//! Diagnostic bundles for bug reports.
+//! Panics of the backend, including the fs daemon, are recorded so they can be included.
+use hermes_desktop_lib as lib;
+use std::{io, path::PathBuf};
+use tauri::Manager;
+
+/// Folder panics are recorded in, relative to the app data folder.
+const PANICS_DIR: &str = "panics";
+
+/// Name suggested for saved bundles.
+pub const BUNDLE_FILE_NAME: &str = "hermes-diagnostics.zip";
+
+/// Menu item creating a diagnostic bundle.
+#[cfg(desktop)]
+pub const MENU_ID: &str = "create-diagnostics";
+
+fn panics_path(app: &tauri::AppHandle) -> io::Result<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(PANICS_DIR))
+        .map_err(io::Error::other)
+}
+
+/// Record panics, in addition to the default handling.
+/// Panics of every thread are recorded.
+pub fn install_panic_hook(app: &tauri::AppHandle) {
+    let Ok(dir) = panics_path(app) else {
+        return;
+    };
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        // Failing to record the panic must not cause another.
+        let _ = lib::diagnostics::Panic::from_hook(info).record(&dir);
+        default_hook(info);
+    }));
+}
+
+/// Collect the diagnostics of the app.
+///
+/// # Arguments
+/// + `workspace`: Workspace open in the ui, if any.
+pub fn bundle(
+    app: &tauri::AppHandle,
+    workspace: Option<lib::diagnostics::Workspace>,
+) -> lib::diagnostics::Bundle {
+    lib::diagnostics::Bundle {
+        system: lib::diagnostics::System::current(app.package_info().version.to_string()),
+        workspace,
+        panics: panics_path(app)
+            .map(lib::diagnostics::recorded_panics)
+            .unwrap_or_default(),
+        log: lib::diagnostics::log_tail(crate::LOG_DIR, crate::LOG_PREFIX),
+    }
+}
@@ -1,28 +1,177 @@
+use hermes_desktop_lib as lib;
 use hermes_fs_daemon as fs_daemon;
-use std::sync::Arc;
-use tauri::Manager;
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex, PoisonError,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+use tauri::{Emitter, Manager};
+
+mod audit;
+mod diagnostics;
+mod recent;
+mod session;
+mod settings;
+mod template;
+mod window;
+
+/// Folder log files are written to.
+pub const LOG_DIR: &str = ".";
+
+/// Prefix of the names of log files, followed by their date.
+pub const LOG_PREFIX: &str = "hermes.log";
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    run_with(None)
+}
+
+/// Run the app.
+///
+/// # Arguments
+/// + `path`: File or folder to open at launch.
+pub fn run_with(path: Option<PathBuf>) {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
+        .plugin(tauri_plugin_notification::init())
         .invoke_handler(tauri::generate_handler![
             commands::select_folder,
+            commands::list_recent_roots,
+            commands::open_recent_root,
+            commands::launch_path,
             commands::load_directory,
+            commands::expand_directory,
             commands::load_dataset,
+            commands::load_dataset_head,
+            commands::cancel_load,
+            commands::diff_dataset,
             commands::run_workspace,
+            commands::export_evaluated_copy,
+            commands::evaluate_formulas,
+            commands::batch_orders,
+            commands::query_audit_log,
+            commands::set_file_protected,
+            commands::find_duplicate_files,
+            commands::create_folder,
+            commands::create_file,
+            commands::rename_path,
+            commands::trash_path,
+            commands::reveal_path,
+            commands::save_snapshot,
+            commands::load_snapshot,
+            commands::get_settings,
+            commands::set_settings,
+            commands::notify_complete,
+            commands::create_diagnostics,
+            commands::system_theme,
+            commands::load_templates,
+            commands::save_templates,
+            commands::load_recovered_session,
+            commands::save_session,
+            commands::clear_session,
         ])
+        .manage(LaunchPath::new(path))
         .setup(setup)
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .on_window_event(|window, event| match event {
+            tauri::WindowEvent::ThemeChanged(theme) => {
+                if let Err(err) = emit::<lib::ipc::event::ThemeChanged, _>(window, to_theme(*theme))
+                {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(?err, "could not emit theme change");
+                }
+            }
+            tauri::WindowEvent::CloseRequested { .. } => {
+                if let Err(err) = crate::window::save(window) {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(?err, "could not save window state");
+                }
+
+                let app = window.app_handle();
+                if app.webview_windows().len() <= 1 {
+                    let command_tx = app.state::<FsDaemonCommandSender>();
+                    app.state::<FsDaemonHandle>().shutdown(&command_tx);
+                }
+            }
+            tauri::WindowEvent::Destroyed => {
+                let app = window.app_handle();
+                let root = app.state::<WatchedRoots>().close(window.label());
+                if let Some(root) = root.filter(|_| app.state::<FsDaemonHandle>().is_running()) {
+                    let command_tx = app.state::<FsDaemonCommandSender>();
+                    if let Err(err) = command_tx
+                        .blocking_lock()
+                        .send(fs_daemon::server::Command::Unwatch(root))
+                    {
+                        #[cfg(feature = "tracing")]
+                        tracing::error!(?err, "could not unwatch project root");
+                    }
+                }
+            }
+            _ => {}
+        })
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Files opened with Hermes from the system are passed as an event instead of an argument.
+            #[cfg(any(target_os = "macos", target_os = "ios"))]
+            if let tauri::RunEvent::Opened { urls } = &event {
+                if let Some(path) = urls.iter().find_map(|url| url.to_file_path().ok()) {
+                    let launch = app.state::<LaunchPath>();
+                    *launch.0.lock().unwrap_or_else(PoisonError::into_inner) = Some(path);
+                }
+            }
+
+            // The session is only kept for recovery after an unclean exit.
+            if let tauri::RunEvent::Exit = event {
+                if let Err(err) = session::clear(app) {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(?err, "could not clear session");
+                }
+            }
+        });
 }
 
-#[derive(derive_more::Deref, Clone)]
-struct FsDaemonEventReceiver(Arc<tokio::sync::Mutex<fs_daemon::server::EventReceiver>>);
-impl FsDaemonEventReceiver {
-    pub fn new(event_rx: fs_daemon::server::EventReceiver) -> Self {
-        Self(Arc::new(tokio::sync::Mutex::new(event_rx)))
+/// Convert a system theme into a settings theme.
+fn to_theme(theme: tauri::Theme) -> lib::settings::Theme {
+    match theme {
+        tauri::Theme::Dark => lib::settings::Theme::Dark,
+        _ => lib::settings::Theme::Light,
+    }
+}
+
+/// Number of event batches buffered for each subscriber of [`FsEventBroadcast`].
+/// Subscribers falling further behind skip the oldest batches.
+const FS_EVENT_CAPACITY: usize = 256;
+
+/// Batch of file system events shared between subscribers.
+type FsEvents = Arc<Vec<fs_daemon::Event>>;
+
+/// Fans file system events from the daemon out to any number of subscribers.
+#[derive(Clone)]
+struct FsEventBroadcast(tokio::sync::broadcast::Sender<FsEvents>);
+impl FsEventBroadcast {
+    pub fn new() -> Self {
+        let (event_tx, _) = tokio::sync::broadcast::channel(FS_EVENT_CAPACITY);
+        Self(event_tx)
+    }
+
+    /// Subscribe to file system events.
+    /// Only events sent after subscribing are received.
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<FsEvents> {
+        self.0.subscribe()
+    }
+
+    /// Forward events from the daemon to all current subscribers.
+    /// Runs until the daemon's event channel closes.
+    async fn dispatch(self, mut event_rx: fs_daemon::server::EventReceiver) {
+        while let Some(events) = event_rx.recv().await {
+            // Sending only fails if there are no subscribers, in which case the events are dropped.
+            let _ = self.0.send(Arc::new(events));
+        }
     }
 }
 
@@ -34,11 +183,216 @@ impl FsDaemonCommandSender {
     }
 }
 
+/// Thread running the fs daemon.
+#[derive(Clone)]
+struct FsDaemonHandle(Arc<Mutex<Option<std::thread::JoinHandle<()>>>>);
+impl FsDaemonHandle {
+    pub fn new(handle: std::thread::JoinHandle<()>) -> Self {
+        Self(Arc::new(Mutex::new(Some(handle))))
+    }
+
+    /// Whether the daemon has not been shut down.
+    pub fn is_running(&self) -> bool {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some()
+    }
+
+    /// Shut down the daemon and wait for its thread to finish.
+    /// Does nothing if the daemon was already shut down.
+    pub fn shutdown(&self, command_tx: &FsDaemonCommandSender) {
+        let Some(handle) = self.0.lock().unwrap_or_else(PoisonError::into_inner).take() else {
+            return;
+        };
+
+        if let Err(err) = command_tx
+            .blocking_lock()
+            .send(fs_daemon::server::Command::Shutdown)
+        {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?err, "could not shut down fs daemon");
+            return;
+        }
+
+        if handle.join().is_err() {
+            #[cfg(feature = "tracing")]
+            tracing::error!("fs daemon panicked");
+        }
+    }
+}
+
+/// Project root open in each window, keyed by window label.
+/// Roots open in several windows are watched once.
+#[derive(Clone)]
+struct WatchedRoots(Arc<Mutex<HashMap<String, PathBuf>>>);
+impl WatchedRoots {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(HashMap::new())))
+    }
+
+    /// Set the root open in a window.
+    ///
+    /// # Returns
+    /// `(<root must be watched>, <previous root of the window no longer open in any window>)`.
+    pub fn open(&self, window: &str, root: PathBuf) -> (bool, Option<PathBuf>) {
+        let mut roots = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let watched = roots.values().any(|open| *open == root);
+        let previous = roots
+            .insert(window.to_string(), root.clone())
+            .filter(|previous| *previous != root && !roots.values().any(|open| open == previous));
+
+        (!watched, previous)
+    }
+
+    /// Remove the root of a closed window.
+    ///
+    /// # Returns
+    /// Root of the window, if it is no longer open in any window.
+    pub fn close(&self, window: &str) -> Option<PathBuf> {
+        let mut roots = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let root = roots.remove(window)?;
+        (!roots.values().any(|open| *open == root)).then_some(root)
+    }
+}
+
+/// Files that may not be modified by any command.
+#[derive(derive_more::Deref, Clone)]
+struct ProtectedFiles(Arc<Mutex<HashSet<PathBuf>>>);
+impl ProtectedFiles {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(HashSet::new())))
+    }
+
+    /// Set whether a file is protected.
+    pub fn set(&self, path: impl AsRef<Path>, protected: bool) {
+        let path = Self::normalize(path);
+        let mut files = self.lock().unwrap_or_else(PoisonError::into_inner);
+        if protected {
+            files.insert(path);
+        } else {
+            files.remove(&path);
+        }
+    }
+
+    /// # Returns
+    /// If the file is protected.
+    pub fn contains(&self, path: impl AsRef<Path>) -> bool {
+        let path = Self::normalize(path);
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains(&path)
+    }
+
+    /// # Returns
+    /// If the path is protected or contains a protected file.
+    pub fn contains_within(&self, path: impl AsRef<Path>) -> bool {
+        let path = Self::normalize(path);
+        self.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .any(|file| file.starts_with(&path))
+    }
+
+    /// Canonicalize the path so different references to the same file match.
+    /// Falls back to the given path if it can not be canonicalized.
+    fn normalize(path: impl AsRef<Path>) -> PathBuf {
+        let path = path.as_ref();
+        fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+    }
+}
+
+/// Locks serializing writes to each file.
+/// Orders writing to the same file, e.g. from an automatic and a manual run,
+/// wait for each other rather than interleaving.
+#[derive(Clone)]
+struct FileLocks(Arc<Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>>);
+impl FileLocks {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(HashMap::new())))
+    }
+
+    /// Wait for exclusive access to a file.
+    /// Access is held until the guard is dropped.
+    pub async fn lock(&self, path: impl AsRef<Path>) -> tokio::sync::OwnedMutexGuard<()> {
+        let path = ProtectedFiles::normalize(path);
+        let lock = {
+            let mut locks = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+            // Locks only referenced by the map are not held or awaited.
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+            locks.entry(path).or_default().clone()
+        };
+
+        lock.lock_owned().await
+    }
+}
+
+/// File or folder Hermes was launched with, until the ui opens it.
+#[derive(Clone)]
+struct LaunchPath(Arc<Mutex<Option<PathBuf>>>);
+impl LaunchPath {
+    pub fn new(path: Option<PathBuf>) -> Self {
+        Self(Arc::new(Mutex::new(path)))
+    }
+
+    pub fn take(&self) -> Option<PathBuf> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner).take()
+    }
+}
+
+/// Dataset loads in progress, with the flag cancelling each.
+/// Loads are keyed by the label of the window that started them,
+/// as each window numbers its loads independently.
+#[derive(Clone)]
+struct Loads(Arc<Mutex<HashMap<(String, lib::data::LoadId), Arc<AtomicBool>>>>);
+impl Loads {
+    pub fn new() -> Self {
+        Self(Arc::new(Mutex::new(HashMap::new())))
+    }
+
+    /// Register a load.
+    /// Must be [finished](Self::finish) once complete.
+    ///
+    /// # Returns
+    /// Flag set when the load is cancelled.
+    pub fn start(&self, window: &str, id: lib::data::LoadId) -> Arc<AtomicBool> {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert((window.to_string(), id), cancelled.clone());
+
+        cancelled
+    }
+
+    pub fn finish(&self, window: &str, id: lib::data::LoadId) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&(window.to_string(), id));
+    }
+
+    /// Cancel a load.
+    /// Loads that already completed are ignored.
+    pub fn cancel(&self, window: &str, id: lib::data::LoadId) {
+        if let Some(cancelled) = self
+            .0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&(window.to_string(), id))
+        {
+            cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
 /// Runs setup tasks:
 /// 1. Launches `fs_daemon`.
 /// 2. Registers event listeners.
 #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
 fn setup(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
+    diagnostics::install_panic_hook(app.handle());
+
     let (command_tx, command_rx) = fs_daemon::server::command_channel();
     let (event_tx, event_rx) = fs_daemon::server::event_channel();
     let mut daemon = fs_daemon::server::Daemon::new(event_tx, command_rx);
@@ -47,202 +401,1303 @@ fn setup(app: &mut tauri::App) -> Result<(), Box<dyn std::error::Error>> {
         .name("hermes desktop fs daemon".to_string())
         .spawn(move || daemon.run())
         .expect("could not launch fs daemon");
+    app.manage(FsDaemonHandle::new(daemon_handle));
 
-    let event_rx = FsDaemonEventReceiver::new(event_rx);
-    app.manage(event_rx.clone());
+    let fs_events = FsEventBroadcast::new();
+    let events_rx = fs_events.subscribe();
+    app.manage(fs_events.clone());
     app.manage(FsDaemonCommandSender::new(command_tx));
-    tauri::async_runtime::spawn(handle_fs_events(app.handle().clone()));
+    app.manage(ProtectedFiles::new());
+    app.manage(FileLocks::new());
+    app.manage(Loads::new());
+    app.manage(WatchedRoots::new());
+    app.manage(settings::CurrentSettings::new(settings::load(app.handle())));
+    tauri::async_runtime::spawn(emit_modified_files(
+        app.handle().clone(),
+        fs_events.subscribe(),
+    ));
+    tauri::async_runtime::spawn(fs_events.dispatch(event_rx));
+    tauri::async_runtime::spawn(handle_fs_events(events_rx));
+
+    if let Some(main) = app.get_webview_window(window::MAIN) {
+        if let Err(err) = window::restore(&main) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(?err, "could not restore window state");
+        }
+    }
+
+    #[cfg(desktop)]
+    {
+        app.set_menu(window::menu(app.handle())?)?;
+        app.on_menu_event(|app, event| {
+            if event.id() == window::NEW_WINDOW_MENU_ID {
+                if let Err(err) = window::open(app) {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(?err, "could not open window");
+                }
+            } else if event.id() == diagnostics::MENU_ID {
+                let focused = app
+                    .webview_windows()
+                    .into_values()
+                    .find(|window| window.is_focused().unwrap_or(false));
+                let label = focused
+                    .as_ref()
+                    .map(|window| window.label())
+                    .unwrap_or(window::MAIN);
+                if let Err(err) =
+                    emit_to::<lib::ipc::event::DiagnosticsRequested, _>(app, label, ())
+                {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(?err, "could not request diagnostics");
+                }
+            }
+        });
+    }
+
     Ok(())
 }
 
+/// Emit a [shared event](lib::ipc::Event) to the ui.
+fn emit<E, R>(emitter: &impl Emitter<R>, payload: E::Payload) -> tauri::Result<()>
+where
+    E: lib::ipc::Event,
+    E::Payload: Clone,
+    R: tauri::Runtime,
+{
+    emitter.emit(E::NAME, payload)
+}
+
+/// Emit a [shared event](lib::ipc::Event) to a single window.
+fn emit_to<E, R>(emitter: &impl Emitter<R>, window: &str, payload: E::Payload) -> tauri::Result<()>
+where
+    E: lib::ipc::Event,
+    E::Payload: Clone,
+    R: tauri::Runtime,
+{
+    emitter.emit_to(window, E::NAME, payload)
+}
+
+/// Notify the ui of files that were created or modified,
+/// so workspaces depending on them can be reprocessed.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+async fn emit_modified_files(
+    app: tauri::AppHandle,
+    mut events_rx: tokio::sync::broadcast::Receiver<FsEvents>,
+) {
+    use tokio::sync::broadcast::error::RecvError;
+
+    loop {
+        let events = match events_rx.recv().await {
+            Ok(events) => events,
+            Err(RecvError::Lagged(skipped)) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(skipped, "modified file emitter lagged");
+                continue;
+            }
+            Err(RecvError::Closed) => break,
+        };
+
+        let paths = events
+            .iter()
+            .filter_map(|event| match event {
+                fs_daemon::Event::File(fs_daemon::event::File::Created(path))
+                | fs_daemon::Event::File(fs_daemon::event::File::Modified(path))
+                | fs_daemon::Event::File(fs_daemon::event::File::Renamed { to: path, .. })
+                | fs_daemon::Event::File(fs_daemon::event::File::Moved { to: path, .. }) => {
+                    Some(path.clone())
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        if paths.is_empty() {
+            continue;
+        }
+
+        if let Err(err) = emit::<lib::ipc::event::FilesModified, _>(&app, paths) {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?err, "could not emit modified files");
+        }
+    }
+}
+
 #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
-async fn handle_fs_events(app: tauri::AppHandle) {
-    let event_rx = app.state::<FsDaemonEventReceiver>();
-    while let Some(events) = event_rx.lock().await.recv().await {
-        tracing::trace!(?events);
+async fn handle_fs_events(mut events_rx: tokio::sync::broadcast::Receiver<FsEvents>) {
+    use tokio::sync::broadcast::error::RecvError;
+
+    loop {
+        match events_rx.recv().await {
+            Ok(events) => {
+                #[cfg(feature = "tracing")]
+                tracing::trace!(?events);
+            }
+            Err(RecvError::Lagged(skipped)) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(skipped, "file system event handler lagged");
+            }
+            Err(RecvError::Closed) => break,
+        }
     }
 }
 
 mod commands {
-    use hermes_core as core;
     use hermes_desktop_lib as lib;
     use hermes_fs_daemon as fs_daemon;
-    use std::path::PathBuf;
+    use lib::run::Output;
+    use std::{
+        collections::HashMap,
+        fs, io,
+        path::{Path, PathBuf},
+    };
     use tauri_plugin_dialog::{DialogExt, FilePath};
 
+    /// Select a project root.
+    /// The selected folder is added to the recent roots.
+    ///
+    /// # Returns
+    /// `None` if no folder was selected.
     #[tauri::command]
-    pub async fn select_folder(app: tauri::AppHandle) -> Option<PathBuf> {
-        app.dialog()
+    pub async fn select_folder(
+        app: tauri::AppHandle,
+        args: lib::ipc::SelectFolder,
+    ) -> lib::ipc::Response<lib::ipc::SelectFolder> {
+        let lib::ipc::SelectFolder = args;
+        let Some(path) = app
+            .dialog()
             .file()
             .set_title("Choose a folder")
             .blocking_pick_folder()
-            .map(|path| {
-                let FilePath::Path(path) = path else {
-                    panic!("invalid path kind");
-                };
-                path
-            })
+        else {
+            return Ok(None);
+        };
+
+        let path = match path {
+            FilePath::Path(path) => path,
+            FilePath::Url(url) => url
+                .to_file_path()
+                .map_err(|_| lib::fs::error::SelectFolder::NotAPath)?,
+        };
+
+        if let Err(err) = crate::recent::push(&app, path.clone()) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(?err, "could not save recent roots");
+        }
+
+        Ok(Some(path))
+    }
+
+    /// # Returns
+    /// Recently opened project roots, most recent first.
+    #[tauri::command]
+    pub fn list_recent_roots(
+        app: tauri::AppHandle,
+        args: lib::ipc::ListRecentRoots,
+    ) -> lib::ipc::Response<lib::ipc::ListRecentRoots> {
+        let lib::ipc::ListRecentRoots = args;
+        Ok(crate::recent::load(&app))
+    }
+
+    /// Open a recently used project root.
+    /// Moves the root to the front of the recent roots,
+    /// or removes it if it is no longer a valid root.
+    #[tauri::command]
+    pub fn open_recent_root(
+        app: tauri::AppHandle,
+        args: lib::ipc::OpenRecentRoot,
+    ) -> lib::ipc::Response<lib::ipc::OpenRecentRoot> {
+        let lib::ipc::OpenRecentRoot { root } = args;
+        let res = if !root.exists() {
+            Err(lib::fs::error::FromFileSystem::RootNotFound)
+        } else if !root.is_dir() {
+            Err(lib::fs::error::FromFileSystem::RootNotADirectory)
+        } else {
+            Ok(())
+        };
+
+        let update = match res {
+            Ok(_) => crate::recent::push(&app, root.clone()),
+            Err(_) => crate::recent::remove(&app, &root),
+        };
+        if let Err(err) = update {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(?err, "could not save recent roots");
+        }
+
+        res.map(|_| root)
+    }
+
+    /// Project Hermes was launched with.
+    /// The root is added to the recent roots.
+    /// Only returned once, so reloading the ui does not reopen it.
+    ///
+    /// # Returns
+    /// `None` if Hermes was not launched with a path.
+    #[tauri::command]
+    pub fn launch_path(
+        app: tauri::AppHandle,
+        launch: tauri::State<crate::LaunchPath>,
+        args: lib::ipc::LaunchPath,
+    ) -> lib::ipc::Response<lib::ipc::LaunchPath> {
+        let lib::ipc::LaunchPath = args;
+        let Some(path) = launch.take() else {
+            return Ok(None);
+        };
+
+        let launch = lib::fs::Launch::from_path(path)?;
+        if let Err(err) = crate::recent::push(&app, launch.root.clone()) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(?err, "could not save recent roots");
+        }
+
+        Ok(Some(launch))
     }
 
+    /// Number of directory levels below the root read when a project is loaded.
+    /// Deeper directories are read on demand with [`expand_directory`].
+    const LOAD_DIRECTORY_DEPTH: usize = 2;
+
+    /// Load the directory tree of a project.
+    /// Only the first [`LOAD_DIRECTORY_DEPTH`] levels are read,
+    /// deeper directories are returned as pending.
+    /// The root is watched for changes while it is open in any window.
     #[tauri::command]
     pub async fn load_directory(
+        window: tauri::Window,
         fs_command_tx: tauri::State<'_, crate::FsDaemonCommandSender>,
-        root: PathBuf,
-    ) -> Result<lib::fs::DirectoryTree, lib::fs::error::FromFileSystem> {
-        let res = lib::fs::DirectoryTree::from_file_system(&root);
+        watched_roots: tauri::State<'_, crate::WatchedRoots>,
+        args: lib::ipc::LoadDirectory,
+    ) -> lib::ipc::Response<lib::ipc::LoadDirectory> {
+        let lib::ipc::LoadDirectory { root } = args;
+        let res = tauri::async_runtime::spawn_blocking({
+            let root = root.clone();
+            move || {
+                lib::fs::DirectoryTree::from_file_system_with_depth(
+                    &root,
+                    Some(LOAD_DIRECTORY_DEPTH),
+                )
+            }
+        })
+        .await
+        .unwrap_or_else(|err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?err, "loading directory did not complete");
+            Err(lib::fs::error::FromFileSystem::ReadDir {
+                path: root.clone(),
+                error: std::io::ErrorKind::Interrupted,
+            })
+        });
+
         if res.is_ok() {
-            fs_command_tx
-                .lock()
-                .await
-                .send(fs_daemon::server::Command::Watch(root))
-                .unwrap();
+            let (watch, unwatch) = watched_roots.open(window.label(), root.clone());
+            let fs_command_tx = fs_command_tx.lock().await;
+            let mut sent = fs_command_tx.send(fs_daemon::server::Command::Ignore(
+                lib::fs::ignore::matcher(&root),
+            ));
+            if watch {
+                sent =
+                    sent.and_then(|_| fs_command_tx.send(fs_daemon::server::Command::Watch(root)));
+            }
+            if let Some(unwatch) = unwatch {
+                sent = sent
+                    .and_then(|_| fs_command_tx.send(fs_daemon::server::Command::Unwatch(unwatch)));
+            }
+            if let Err(err) = sent {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?err, "could not watch project root");
+            }
         }
         res
     }
 
+    /// Read the contents of a pending directory.
+    ///
+    /// # Arguments
+    /// + `path`: Path to the directory relative to `root`.
+    ///
+    /// # Returns
+    /// Tree rooted at the directory, with its child directories pending.
+    #[tauri::command]
+    pub async fn expand_directory(
+        args: lib::ipc::ExpandDirectory,
+    ) -> lib::ipc::Response<lib::ipc::ExpandDirectory> {
+        let lib::ipc::ExpandDirectory { root, path } = args;
+        let path = root.join(path);
+        tauri::async_runtime::spawn_blocking({
+            let path = path.clone();
+            move || {
+                lib::fs::DirectoryTree::from_file_system_with_options(
+                    &path,
+                    lib::fs::ReadOptions {
+                        max_depth: Some(0),
+                        ignore: Some(lib::fs::ignore::matcher(&root)),
+                        ..Default::default()
+                    },
+                )
+            }
+        })
+        .await
+        .unwrap_or_else(|err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?err, "expanding directory did not complete");
+            Err(lib::fs::error::FromFileSystem::ReadDir {
+                path,
+                error: std::io::ErrorKind::Interrupted,
+            })
+        })
+    }
+
+    /// Find sets of files with identical content in the project.
+    /// Runs on a background thread.
+    ///
+    /// # Returns
+    /// Sets of paths relative to `root`.
     #[tauri::command]
-    pub fn load_dataset(path: PathBuf) -> Result<lib::data::Dataset, lib::data::error::Load> {
-        use lib::data::Dataset;
+    pub async fn find_duplicate_files(
+        args: lib::ipc::FindDuplicateFiles,
+    ) -> lib::ipc::Response<lib::ipc::FindDuplicateFiles> {
+        let lib::ipc::FindDuplicateFiles { root } = args;
+        let res = tauri::async_runtime::spawn_blocking(move || {
+            let tree = lib::fs::DirectoryTree::from_file_system(&root)?;
+            let paths = tree.file_paths().into_iter().map(|path| root.join(path));
+            let duplicates = lib::hash::find_duplicates(paths)
+                .into_iter()
+                .map(|paths| {
+                    paths
+                        .into_iter()
+                        .map(|path| {
+                            path.strip_prefix(&root)
+                                .map(|path| path.to_path_buf())
+                                .unwrap_or(path)
+                        })
+                        .collect()
+                })
+                .collect();
+
+            Ok(duplicates)
+        })
+        .await;
 
-        let file_kind = if let Some(ext) = path.extension().map(|ext| ext.to_str()).flatten() {
-            match ext {
-                "csv" | "tsv" => FileKind::Csv,
-                "xlsx" | "xls" => FileKind::Excel,
-                _ => FileKind::Unknown,
+        match res {
+            Ok(res) => res,
+            Err(err) => {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?err, "duplicate file search did not complete");
+                Ok(vec![])
             }
-        } else {
-            FileKind::Unknown
-        };
-
-        match file_kind {
-            FileKind::Csv => lib::data::Csv::load_from_path(&path)
-                .map(|csv| csv.into())
-                .map_err(|err| err.into()),
-            FileKind::Excel => lib::data::Workbook::load_from_path(&path)
-                .map(|workbook| workbook.into())
-                .map_err(|err| err.into()),
-            FileKind::Unknown => match lib::data::Csv::load_from_path(&path) {
-                Ok(csv) => Ok(csv.into()),
-                Err(csv_err) => match csv_err {
-                    lib::data::error::LoadCsv::Io(_) => Err(csv_err.into()),
-                    _ => match lib::data::Workbook::load_from_path(&path) {
-                        Ok(workbook) => Ok(workbook.into()),
-                        Err(_) => Err(lib::data::error::Load::InvalidFileType),
-                    },
-                },
+        }
+    }
+
+    /// Load the settings of a project, falling back to the global settings.
+    /// The settings are used by other commands until changed.
+    /// The project's saved protected files are protected.
+    #[tauri::command]
+    pub fn get_settings(
+        app: tauri::AppHandle,
+        current: tauri::State<'_, crate::settings::CurrentSettings>,
+        protected_files: tauri::State<'_, crate::ProtectedFiles>,
+        args: lib::ipc::GetSettings,
+    ) -> lib::ipc::Response<lib::ipc::GetSettings> {
+        let lib::ipc::GetSettings { root } = args;
+        let protected = crate::settings::load_protected(&root);
+        for path in protected.iter() {
+            protected_files.set(root.join(path), true);
+        }
+
+        let settings = match crate::settings::load_project(&root) {
+            Some(settings) => lib::settings::ProjectSettings {
+                settings,
+                scope: lib::settings::Scope::Project,
+                protected,
+            },
+            None => lib::settings::ProjectSettings {
+                settings: crate::settings::load(&app),
+                scope: lib::settings::Scope::Global,
+                protected,
             },
+        };
+
+        current.set(settings.settings.clone());
+        Ok(settings)
+    }
+
+    /// Save the settings of a project.
+    ///
+    /// # Arguments
+    /// + `scope`: Where to save the settings.
+    ///     If [global](lib::settings::Scope::Global), the project's own settings are removed.
+    #[tauri::command]
+    pub fn set_settings(
+        app: tauri::AppHandle,
+        current: tauri::State<'_, crate::settings::CurrentSettings>,
+        args: lib::ipc::SetSettings,
+    ) -> lib::ipc::Response<lib::ipc::SetSettings> {
+        let lib::ipc::SetSettings {
+            root,
+            scope,
+            settings,
+        } = args;
+        match scope {
+            lib::settings::Scope::Global => {
+                crate::settings::save(&app, &settings)?;
+                match fs::remove_file(root.join(lib::settings::SETTINGS_FILE)) {
+                    Ok(()) => {}
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            lib::settings::Scope::Project => crate::settings::save_project(&root, &settings)?,
         }
+
+        current.set(settings);
+        Ok(())
     }
 
-    #[derive(Debug)]
-    enum FileKind {
-        Csv,
-        Excel,
-        Unknown,
+    #[tauri::command]
+    pub fn load_templates(
+        app: tauri::AppHandle,
+        args: lib::ipc::LoadTemplates,
+    ) -> lib::ipc::Response<lib::ipc::LoadTemplates> {
+        let lib::ipc::LoadTemplates = args;
+        Ok(crate::template::load(&app))
+    }
+
+    #[tauri::command]
+    pub fn save_templates(
+        app: tauri::AppHandle,
+        args: lib::ipc::SaveTemplates,
+    ) -> lib::ipc::Response<lib::ipc::SaveTemplates> {
+        let lib::ipc::SaveTemplates { templates } = args;
+        crate::template::save(&app, &templates)?;
+        Ok(())
+    }
+
+    /// # Returns
+    /// Session left by a previous run that exited uncleanly.
+    /// Only recovered in the main window.
+    #[tauri::command]
+    pub fn load_recovered_session(
+        app: tauri::AppHandle,
+        window: tauri::Window,
+        args: lib::ipc::LoadRecoveredSession,
+    ) -> lib::ipc::Response<lib::ipc::LoadRecoveredSession> {
+        let lib::ipc::LoadRecoveredSession = args;
+        if window.label() != crate::window::MAIN {
+            return Ok(None);
+        }
+
+        Ok(crate::session::load(&app))
+    }
+
+    /// Save the session for recovery.
+    /// Only the session of the main window is kept.
+    #[tauri::command]
+    pub fn save_session(
+        app: tauri::AppHandle,
+        window: tauri::Window,
+        args: lib::ipc::SaveSession,
+    ) -> lib::ipc::Response<lib::ipc::SaveSession> {
+        let lib::ipc::SaveSession { session } = args;
+        if window.label() != crate::window::MAIN {
+            return Ok(());
+        }
+
+        crate::session::save(&app, &session)?;
+        Ok(())
+    }
+
+    #[tauri::command]
+    pub fn clear_session(
+        app: tauri::AppHandle,
+        window: tauri::Window,
+        args: lib::ipc::ClearSession,
+    ) -> lib::ipc::Response<lib::ipc::ClearSession> {
+        let lib::ipc::ClearSession = args;
+        if window.label() != crate::window::MAIN {
+            return Ok(());
+        }
+
+        crate::session::clear(&app)?;
+        Ok(())
+    }
+
+    /// Save a diagnostic bundle to a file chosen by the user,
+    /// and reveal it in the file manager.
+    ///
+    /// # Arguments
+    /// + `workspace`: Workspace open in the ui, if any.
+    #[tauri::command]
+    pub async fn create_diagnostics(
+        app: tauri::AppHandle,
+        args: lib::ipc::CreateDiagnostics,
+    ) -> lib::ipc::Response<lib::ipc::CreateDiagnostics> {
+        use tauri_plugin_opener::OpenerExt;
+
+        let lib::ipc::CreateDiagnostics { workspace } = args;
+        let Some(path) = app
+            .dialog()
+            .file()
+            .set_title("Save diagnostic bundle")
+            .set_file_name(crate::diagnostics::BUNDLE_FILE_NAME)
+            .add_filter("Zip archive", &["zip"])
+            .blocking_save_file()
+        else {
+            return Ok(None);
+        };
+
+        let path = match path {
+            FilePath::Path(path) => path,
+            FilePath::Url(url) => url
+                .to_file_path()
+                .map_err(|_| lib::diagnostics::error::Bundle::Io(io::ErrorKind::InvalidInput))?,
+        };
+
+        crate::diagnostics::bundle(&app, workspace).save(&path)?;
+        if let Err(err) = app.opener().reveal_item_in_dir(&path) {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(?err, "could not reveal diagnostic bundle");
+        }
+
+        Ok(Some(path))
+    }
+
+    /// Show a system notification that an operation finished.
+    /// Only shown if the window is not focused and notifications are enabled for the operation.
+    #[tauri::command]
+    pub fn notify_complete(
+        app: tauri::AppHandle,
+        window: tauri::Window,
+        args: lib::ipc::NotifyComplete,
+    ) -> lib::ipc::Response<lib::ipc::NotifyComplete> {
+        use tauri_plugin_notification::NotificationExt;
+
+        let lib::ipc::NotifyComplete {
+            operation,
+            title,
+            body,
+        } = args;
+        if window.is_focused().unwrap_or(true) {
+            return Ok(());
+        }
+        if !crate::settings::current(&app)
+            .notifications
+            .enabled(operation)
+        {
+            return Ok(());
+        }
+
+        let mut notification = app.notification().builder().title(title);
+        if let Some(body) = body {
+            notification = notification.body(body);
+        }
+        if let Err(err) = notification.show() {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(?err, "could not show notification");
+        }
+
+        Ok(())
+    }
+
+    /// # Returns
+    /// Theme of the system, either light or dark.
+    #[tauri::command]
+    pub fn system_theme(
+        window: tauri::Window,
+        args: lib::ipc::SystemTheme,
+    ) -> lib::ipc::Response<lib::ipc::SystemTheme> {
+        let lib::ipc::SystemTheme = args;
+        match window.theme() {
+            Ok(theme) => Ok(crate::to_theme(theme)),
+            Err(err) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(?err, "could not get system theme");
+                Ok(lib::settings::Theme::Light)
+            }
+        }
+    }
+
+    /// Save the expected results of the project.
+    /// Replaces any existing snapshot.
+    #[tauri::command]
+    pub fn save_snapshot(
+        args: lib::ipc::SaveSnapshot,
+    ) -> lib::ipc::Response<lib::ipc::SaveSnapshot> {
+        let lib::ipc::SaveSnapshot { root, snapshot } = args;
+        let content = serde_json::to_string_pretty(&snapshot)
+            .map_err(|err| lib::snapshot::error::Snapshot::Invalid(err.to_string()))?;
+        fs::write(root.join(lib::snapshot::SNAPSHOT_FILE), content)
+            .map_err(|err| lib::snapshot::error::Snapshot::Io(err.kind()))
+    }
+
+    #[tauri::command]
+    pub fn load_snapshot(
+        args: lib::ipc::LoadSnapshot,
+    ) -> lib::ipc::Response<lib::ipc::LoadSnapshot> {
+        let lib::ipc::LoadSnapshot { root } = args;
+        let content = match fs::read_to_string(root.join(lib::snapshot::SNAPSHOT_FILE)) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(lib::snapshot::error::Snapshot::NotFound);
+            }
+            Err(err) => return Err(lib::snapshot::error::Snapshot::Io(err.kind())),
+        };
+
+        serde_json::from_str(&content)
+            .map_err(|err| lib::snapshot::error::Snapshot::Invalid(err.to_string()))
+    }
+
+    /// Ensure a path may be modified.
+    ///
+    /// # Returns
+    /// `Err` if the path is within an archive or is, or contains, a protected file.
+    fn check_writable(
+        protected_files: &crate::ProtectedFiles,
+        path: impl AsRef<Path>,
+    ) -> Result<(), lib::fs::error::FileOperation> {
+        let path = path.as_ref();
+        if lib::fs::archive::split_path(path).is_some() || protected_files.contains_within(path) {
+            Err(lib::fs::error::FileOperation::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
+    #[tauri::command]
+    pub fn create_folder(
+        protected_files: tauri::State<'_, crate::ProtectedFiles>,
+        args: lib::ipc::CreateFolder,
+    ) -> lib::ipc::Response<lib::ipc::CreateFolder> {
+        let lib::ipc::CreateFolder { path } = args;
+        check_writable(&protected_files, &path)?;
+        fs::create_dir(&path)?;
+        Ok(())
+    }
+
+    #[tauri::command]
+    pub fn create_file(
+        protected_files: tauri::State<'_, crate::ProtectedFiles>,
+        args: lib::ipc::CreateFile,
+    ) -> lib::ipc::Response<lib::ipc::CreateFile> {
+        let lib::ipc::CreateFile { path } = args;
+        check_writable(&protected_files, &path)?;
+        fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        Ok(())
+    }
+
+    /// Rename or move a file or folder.
+    #[tauri::command]
+    pub fn rename_path(
+        protected_files: tauri::State<'_, crate::ProtectedFiles>,
+        args: lib::ipc::RenamePath,
+    ) -> lib::ipc::Response<lib::ipc::RenamePath> {
+        let lib::ipc::RenamePath { from, to } = args;
+        check_writable(&protected_files, &from)?;
+        check_writable(&protected_files, &to)?;
+        if !from.exists() {
+            return Err(lib::fs::error::FileOperation::NotFound);
+        }
+        if to.exists() {
+            return Err(lib::fs::error::FileOperation::AlreadyExists);
+        }
+
+        fs::rename(&from, &to)?;
+        Ok(())
+    }
+
+    /// Move a file or folder to the trash.
+    #[tauri::command]
+    pub fn trash_path(
+        protected_files: tauri::State<'_, crate::ProtectedFiles>,
+        args: lib::ipc::TrashPath,
+    ) -> lib::ipc::Response<lib::ipc::TrashPath> {
+        let lib::ipc::TrashPath { path } = args;
+        check_writable(&protected_files, &path)?;
+        if !path.exists() {
+            return Err(lib::fs::error::FileOperation::NotFound);
+        }
+
+        trash::delete(&path).map_err(|err| lib::fs::error::FileOperation::Trash(err.to_string()))
+    }
+
+    /// Show a file or folder in the system file manager,
+    /// e.g. Finder or Explorer.
+    #[tauri::command]
+    pub fn reveal_path(
+        app: tauri::AppHandle,
+        args: lib::ipc::RevealPath,
+    ) -> lib::ipc::Response<lib::ipc::RevealPath> {
+        use tauri_plugin_opener::OpenerExt;
+
+        let lib::ipc::RevealPath { path } = args;
+        if !path.exists() {
+            return Err(lib::fs::error::FileOperation::NotFound);
+        }
+
+        app.opener()
+            .reveal_item_in_dir(&path)
+            .map_err(|err| lib::fs::error::FileOperation::Reveal(err.to_string()))
+    }
+
+    /// Load a dataset.
+    /// Parsing runs on a background thread, emitting its
+    /// [progress](lib::data::LOAD_PROGRESS_EVENT) until complete or [cancelled](cancel_load).
+    /// Paths into an archive load the archive member.
+    /// Archive members, and files that can not be written to, are marked read-only.
+    ///
+    /// # Arguments
+    /// + `id`: Handle of the load, used to follow its progress and cancel it.
+    ///
+    /// # Errors
+    /// + If the file is larger than the [maximum dataset size](lib::settings::Limits).
+    ///   Use [`load_dataset_head`] to load part of the file instead.
+    #[tauri::command]
+    pub async fn load_dataset(
+        app: tauri::AppHandle,
+        window: tauri::Window,
+        loads: tauri::State<'_, crate::Loads>,
+        fs_command_tx: tauri::State<'_, crate::FsDaemonCommandSender>,
+        args: lib::ipc::LoadDataset,
+    ) -> lib::ipc::Response<lib::ipc::LoadDataset> {
+        let lib::ipc::LoadDataset { id, path, options } = args;
+        let archived = lib::fs::archive::split_path(&path).is_some();
+        if !archived {
+            let limits = crate::settings::current(&app).limits;
+            lib::data::check_dataset_size(&path, limits.max_dataset_size)?;
+        }
+
+        let read_only = archived || lib::fs::is_read_only(&path);
+        let dataset =
+            read_dataset_in_background(window, &loads, id, path.clone(), options, None).await?;
+
+        // Only report modifications of the dataset's file if its content changed.
+        if !archived {
+            if let Err(err) = fs_command_tx
+                .lock()
+                .await
+                .send(fs_daemon::server::Command::TrackContent(path))
+            {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?err, "could not track dataset content");
+            }
+        }
+        Ok(lib::data::Loaded {
+            dataset,
+            read_only,
+            truncated: false,
+        })
+    }
+
+    /// Load the first [rows](lib::settings::Limits::preview_rows) of a csv dataset.
+    /// Runs as [`load_dataset`].
+    #[tauri::command]
+    pub async fn load_dataset_head(
+        app: tauri::AppHandle,
+        window: tauri::Window,
+        loads: tauri::State<'_, crate::Loads>,
+        args: lib::ipc::LoadDatasetHead,
+    ) -> lib::ipc::Response<lib::ipc::LoadDatasetHead> {
+        let lib::ipc::LoadDatasetHead { id, path, options } = args;
+        if lib::fs::archive::split_path(&path).is_some() {
+            return Err(lib::data::error::Load::InvalidFileType);
+        }
+        if lib::data::is_workbook_path(&path) {
+            return Err(lib::data::error::Load::InvalidFileType);
+        }
+
+        let read_only = lib::fs::is_read_only(&path);
+        let rows = crate::settings::current(&app).limits.preview_rows;
+        let dataset =
+            read_dataset_in_background(window, &loads, id, path, options, Some(rows)).await?;
+
+        Ok(lib::data::Loaded {
+            dataset,
+            read_only,
+            truncated: true,
+        })
+    }
+
+    /// Cancel a dataset load.
+    /// The load fails with [`Cancelled`](lib::data::error::LoadCsv::Cancelled).
+    #[tauri::command]
+    pub fn cancel_load(
+        window: tauri::Window,
+        loads: tauri::State<'_, crate::Loads>,
+        args: lib::ipc::CancelLoad,
+    ) -> lib::ipc::Response<lib::ipc::CancelLoad> {
+        let lib::ipc::CancelLoad { id } = args;
+        loads.cancel(window.label(), id);
+        Ok(())
+    }
+
+    /// Read a dataset on a background thread, emitting its progress to the window.
+    ///
+    /// # Arguments
+    /// + `max_rows`: Maximum number of data rows to read.
+    ///   `None` to read all rows.
+    async fn read_dataset_in_background(
+        window: tauri::Window,
+        loads: &crate::Loads,
+        id: lib::data::LoadId,
+        path: PathBuf,
+        options: lib::data::ImportOptions,
+        max_rows: Option<usize>,
+    ) -> Result<lib::data::Dataset, lib::data::error::Load> {
+        let cancelled = loads.start(window.label(), id);
+        let dataset = tauri::async_runtime::spawn_blocking({
+            let window = window.clone();
+            move || {
+                let on_progress = |progress: lib::data::LoadProgress| {
+                    let event = lib::data::LoadProgressEvent { id, progress };
+                    if let Err(err) = crate::emit_to::<lib::ipc::event::LoadProgress, _>(
+                        &window,
+                        window.label(),
+                        event,
+                    ) {
+                        #[cfg(feature = "tracing")]
+                        tracing::error!(?err, "could not emit load progress");
+                    }
+                };
+                let control = lib::data::LoadControl::new(&cancelled, &on_progress);
+                lib::data::read_dataset_with_control(path, options, max_rows, Some(&control))
+            }
+        })
+        .await
+        .unwrap_or_else(|err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?err, "loading dataset did not complete");
+            Err(lib::data::error::LoadCsv::Io(io::ErrorKind::Interrupted).into())
+        });
+
+        loads.finish(window.label(), id);
+        dataset
+    }
+
+    /// Compare the in-memory cells of a dataset against the file's current content.
+    ///
+    /// # Arguments
+    /// + `sheets`: Cells of each sheet in memory.
+    #[tauri::command]
+    pub fn diff_dataset(args: lib::ipc::DiffDataset) -> lib::ipc::Response<lib::ipc::DiffDataset> {
+        let lib::ipc::DiffDataset {
+            path,
+            options,
+            sheets,
+        } = args;
+        let disk = lib::data::read_dataset(path, options)?;
+        Ok(lib::diff::dataset(&disk, &sheets))
+    }
+
+    /// Set whether a file is protected from modification.
+    /// Protected files are never written to by workspace orders.
+    /// Protection is saved with the project.
+    ///
+    /// # Arguments
+    /// + `root`: Project root.
+    /// + `path`: Path to the file relative to the project root.
+    #[tauri::command]
+    pub fn set_file_protected(
+        protected_files: tauri::State<'_, crate::ProtectedFiles>,
+        args: lib::ipc::SetFileProtected,
+    ) -> lib::ipc::Response<lib::ipc::SetFileProtected> {
+        let lib::ipc::SetFileProtected {
+            root,
+            path,
+            protected,
+        } = args;
+        protected_files.set(root.join(&path), protected);
+
+        let mut saved = crate::settings::load_protected(&root);
+        if protected {
+            saved.insert(path);
+        } else {
+            saved.remove(&path);
+        }
+        crate::settings::save_protected(&root, &saved)?;
+        Ok(())
     }
 
     /// Run workspace orders.
+    /// Orders writing to a protected file or into an archive are rejected.
+    /// Updates to the same file are [coalesced](lib::formula::coalesce)
+    /// so the file is opened and saved once.
+    /// Orders writing to a file wait for any other order writing to it, from this or another run.
+    /// Successful orders are recorded in the project's [audit log](lib::audit).
+    ///
+    /// # Arguments
+    /// + `root`: Project root.
+    /// + `dry_run`: Evaluate the orders without writing to any file or the audit log.
+    /// + `atomic`: Only modify files if every order succeeds.
+    ///   Orders write to staging files which are moved into place once all orders complete,
+    ///   or are removed if any order fails.
     ///
     /// # Returns
+    /// Changes made, or that would be made for a dry run, by each order.
     /// If errors occur, returns a `Vec<(<order index>, <error>)>`.
     #[tauri::command]
     pub async fn run_workspace(
-        orders: Vec<lib::formula::WorkspaceOrder>,
-    ) -> Result<(), Vec<(usize, lib::formula::error::WorkspaceOrder)>> {
+        protected_files: tauri::State<'_, crate::ProtectedFiles>,
+        file_locks: tauri::State<'_, crate::FileLocks>,
+        args: lib::ipc::RunWorkspace,
+    ) -> lib::ipc::Response<lib::ipc::RunWorkspace> {
+        let lib::ipc::RunWorkspace {
+            root,
+            orders,
+            dry_run,
+            atomic,
+            manifest,
+        } = args;
+        let output = if dry_run {
+            Output::None
+        } else if atomic {
+            Output::Staged
+        } else {
+            Output::InPlace
+        };
+
         let mut tasks = tokio::task::JoinSet::new();
         let mut task_handles = Vec::with_capacity(orders.len());
-        for order in orders {
-            let handle = tasks.spawn(run_workspace_order(order));
-            task_handles.push(handle);
+        let mut errors = Vec::new();
+        let mut entries = Vec::new();
+        // Kept to list the inputs of the run in its manifest.
+        let manifest_orders = if manifest && !dry_run {
+            orders.clone()
+        } else {
+            vec![]
+        };
+        let orders = orders.into_iter().enumerate().filter_map(|(idx, order)| {
+            if order
+                .path()
+                .map(|path| {
+                    protected_files.contains(path) || lib::fs::archive::split_path(path).is_some()
+                })
+                .unwrap_or(false)
+            {
+                errors.push((idx, lib::formula::error::WorkspaceOrder::Protected));
+                None
+            } else if order.path().is_some_and(lib::fs::is_read_only) {
+                errors.push((idx, lib::formula::error::WorkspaceOrder::ReadOnly));
+                None
+            } else {
+                Some((idx, order))
+            }
+        });
+        let orders = lib::formula::coalesce(orders);
+
+        // Orders on the same file run one after another, in order,
+        // so each builds on the changes of the ones before it.
+        let mut previous = HashMap::new();
+        for (indices, order) in orders {
+            let kind = match &order {
+                lib::formula::WorkspaceOrder::Create(_) => lib::audit::OrderKind::Create,
+                lib::formula::WorkspaceOrder::Update(_)
+                | lib::formula::WorkspaceOrder::Clean(_) => lib::audit::OrderKind::Update,
+            };
+            let file = order.path().cloned().unwrap_or_default();
+            let path = file
+                .strip_prefix(&root)
+                .map(|path| path.to_path_buf())
+                .unwrap_or(file.clone());
+
+            let (done_tx, done_rx) = tokio::sync::oneshot::channel::<()>();
+            let after = previous.insert(file.clone(), done_rx);
+            let order_output = if output == Output::Staged && after.is_some() {
+                Output::Restaged
+            } else {
+                output
+            };
+
+            let handle = tasks.spawn({
+                let file_locks = file_locks.inner().clone();
+                let file = file.clone();
+                async move {
+                    if let Some(after) = after {
+                        // The previous order's task dropping the sender is not an error.
+                        let _ = after.await;
+                    }
+
+                    let _guard = match order_output {
+                        Output::None => None,
+                        Output::InPlace | Output::Staged | Output::Restaged => {
+                            Some(file_locks.lock(&file).await)
+                        }
+                    };
+                    let result = run_workspace_order(order, order_output).await;
+                    let _ = done_tx.send(());
+                    result
+                }
+            });
+            task_handles.push((indices, handle, path, file, kind));
         }
 
-        let mut errors = Vec::new();
+        let mut staged = Vec::new();
+
         while let Some(result) = tasks.join_next_with_id().await {
             match result {
                 Ok((id, result)) => {
-                    if let Err(err) = result {
-                        let idx = task_handles
-                            .iter()
-                            .position(|handle| handle.id() == id)
-                            .expect("task handle should exist");
+                    let (indices, _, path, file, kind) = task_handles
+                        .iter()
+                        .find(|(_, handle, _, _, _)| handle.id() == id)
+                        .expect("task handle should exist");
 
-                        errors.push((idx, err))
+                    match result {
+                        Ok(changes) => {
+                            entries.push((
+                                indices[0],
+                                lib::audit::Entry::now(path.clone(), *kind, changes),
+                            ));
+                            staged.push((indices.clone(), file.clone()));
+                        }
+                        Err(err) => {
+                            errors.extend(indices.iter().map(|idx| (*idx, err.clone())));
+                        }
                     }
                 }
 
                 Err(err) => {
-                    let idx = task_handles
+                    let indices = task_handles
                         .iter()
-                        .position(|handle| handle.id() == err.id())
+                        .find_map(|(indices, handle, _, _, _)| {
+                            (handle.id() == err.id()).then_some(indices)
+                        })
                         .expect("task handle should exist");
 
-                    errors.push((idx, lib::formula::error::WorkspaceOrder::TaskNotCompleted));
+                    errors.extend(
+                        indices.iter().map(|idx| {
+                            (*idx, lib::formula::error::WorkspaceOrder::TaskNotCompleted)
+                        }),
+                    );
+                }
+            }
+        }
+
+        if matches!(output, Output::Staged) {
+            if errors.is_empty() {
+                staged.sort_by_key(|(indices, _)| indices[0]);
+                if let Err((indices, err)) = commit_staged(&staged, &file_locks).await {
+                    errors.extend(indices.into_iter().map(|idx| (idx, err.clone())));
+                }
+            } else {
+                discard_staged(&staged);
+            }
+
+            if !errors.is_empty() {
+                entries.clear();
+            }
+        }
+
+        entries.sort_by_key(|(idx, _)| *idx);
+        let entries = entries
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect::<Vec<_>>();
+        if !dry_run {
+            if let Err(err) = crate::audit::append(&root, &entries) {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?err, "could not write audit log");
+            }
+
+            if manifest && !entries.is_empty() {
+                let root = root.clone();
+                let entries = entries.clone();
+                let result = tauri::async_runtime::spawn_blocking(move || {
+                    lib::manifest::Manifest::new(&root, &manifest_orders, &entries).save(&root)
+                })
+                .await;
+                if let Ok(Err(err)) = result {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!(?err, "could not write manifest");
                 }
             }
         }
 
         if errors.is_empty() {
-            return Ok(());
+            return Ok(entries);
         } else {
             return Err(errors);
         }
     }
 
-    async fn run_workspace_order(
-        order: lib::formula::WorkspaceOrder,
-    ) -> Result<(), lib::formula::error::WorkspaceOrder> {
-        match order {
-            lib::formula::WorkspaceOrder::Create => todo!(),
-            lib::formula::WorkspaceOrder::Update(update) => {
-                run_workspace_order_update(update).await
-            }
+    /// Write a copy of a sheet with its formulas replaced by their evaluated values.
+    /// The user chooses where the copy is saved, starting from the copy's suggested path.
+    /// Protected files and archives can not be chosen.
+    ///
+    /// # Returns
+    /// Path the copy was written to, or `None` if no path was chosen.
+    #[tauri::command]
+    pub async fn export_evaluated_copy(
+        app: tauri::AppHandle,
+        protected_files: tauri::State<'_, crate::ProtectedFiles>,
+        args: lib::ipc::ExportEvaluatedCopy,
+    ) -> lib::ipc::Response<lib::ipc::ExportEvaluatedCopy> {
+        let lib::ipc::ExportEvaluatedCopy { copy } = args;
+        let mut dialog = app
+            .dialog()
+            .file()
+            .set_title("Export evaluated copy")
+            .add_filter(copy.format.extension(), &[copy.format.extension()]);
+        if let Some(dir) = copy.path.parent() {
+            dialog = dialog.set_directory(dir);
         }
+        if let Some(name) = copy.path.file_name() {
+            dialog = dialog.set_file_name(name.to_string_lossy());
+        }
+
+        let Some(path) = dialog.blocking_save_file() else {
+            return Ok(None);
+        };
+        let path = match path {
+            FilePath::Path(path) => path,
+            FilePath::Url(url) => url.to_file_path().map_err(|_| {
+                lib::formula::error::WorkspaceOrder::Save(io::ErrorKind::InvalidInput)
+            })?,
+        };
+        check_writable(&protected_files, &path)
+            .map_err(|_| lib::formula::error::WorkspaceOrder::Protected)?;
+
+        tauri::async_runtime::spawn_blocking({
+            let path = path.clone();
+            move || lib::run::write_records(&path, copy.format, copy.records)
+        })
+        .await
+        .map_err(|_| lib::formula::error::WorkspaceOrder::TaskNotCompleted)??;
+
+        Ok(Some(path))
     }
 
-    async fn run_workspace_order_update(
-        update: lib::formula::Update,
-    ) -> Result<(), lib::formula::error::WorkspaceOrder> {
-        let lib::formula::Update { path, updates } = update;
-        match updates {
-            lib::formula::Updates::Csv(updates) => {
-                run_workspace_order_update_csv(path, updates).await
-            }
-            lib::formula::Updates::Workbook(updates) => {
-                run_workspace_order_update_workbook(path, updates).await
-            }
-        }
+    /// Evaluate formulas on a background thread, so large evaluations do not block the ui.
+    ///
+    /// # Returns
+    /// Result of each formula with the cells it read, in order,
+    /// or `None` if the evaluation did not complete.
+    #[tauri::command]
+    pub async fn evaluate_formulas(
+        args: lib::ipc::EvaluateFormulas,
+    ) -> lib::ipc::Response<lib::ipc::EvaluateFormulas> {
+        let lib::ipc::EvaluateFormulas { evaluate } = args;
+        let results = tauri::async_runtime::spawn_blocking(move || evaluate.run_traced())
+            .await
+            .inspect_err(|err| {
+                #[cfg(feature = "tracing")]
+                tracing::error!(?err, "evaluating formulas did not complete");
+            })
+            .ok();
+
+        Ok(results)
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace"))]
-    async fn run_workspace_order_update_csv(
-        path: PathBuf,
-        updates: Vec<lib::formula::UpdateCsv>,
-    ) -> Result<(), lib::formula::error::WorkspaceOrder> {
-        #[cfg(feature = "tracing")]
-        tracing::trace!("processing orders");
+    /// # Returns
+    /// Entries of the project's audit log matching the query, most recent first.
+    #[tauri::command]
+    pub async fn query_audit_log(
+        args: lib::ipc::QueryAuditLog,
+    ) -> lib::ipc::Response<lib::ipc::QueryAuditLog> {
+        let lib::ipc::QueryAuditLog { root, query } = args;
+        tauri::async_runtime::spawn_blocking(move || {
+            let entries = crate::audit::read(&root)?;
+            Ok(query.apply(entries.into_iter()))
+        })
+        .await
+        .unwrap_or_else(|err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?err, "reading audit log did not complete");
+            Err(lib::audit::error::Audit::Io(io::ErrorKind::Interrupted))
+        })
+    }
 
-        let file = tokio::fs::File::open(&path)
+    /// Create orders applying a template to each file matching a pattern.
+    /// No orders are created for protected files.
+    /// Runs on a background thread.
+    ///
+    /// # Returns
+    /// Order, or the reason it could not be created, for each matching file.
+    #[tauri::command]
+    pub async fn batch_orders(
+        protected_files: tauri::State<'_, crate::ProtectedFiles>,
+        args: lib::ipc::BatchOrders,
+    ) -> lib::ipc::Response<lib::ipc::BatchOrders> {
+        let lib::ipc::BatchOrders { root, batch } = args;
+        let protected_files = protected_files.inner().clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            let files = batch.files(&root)?;
+            let orders = files
+                .into_iter()
+                .map(|path| {
+                    let order = if protected_files.contains(root.join(&path)) {
+                        Err(lib::batch::error::File::Protected)
+                    } else {
+                        batch.order(&root, &path)
+                    };
+                    lib::batch::FileOrder { path, order }
+                })
+                .collect();
+
+            Ok(orders)
+        })
+        .await
+        .unwrap_or_else(|err| {
+            #[cfg(feature = "tracing")]
+            tracing::error!(?err, "batch did not complete");
+            Err(lib::batch::error::Files::Io(io::ErrorKind::Interrupted))
+        })
+    }
+
+    /// # Returns
+    /// Cells changed by the order.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(order)))]
+    async fn run_workspace_order(
+        order: lib::formula::WorkspaceOrder,
+        output: Output,
+    ) -> Result<Vec<lib::audit::CellChange>, lib::formula::error::WorkspaceOrder> {
+        tauri::async_runtime::spawn_blocking(move || lib::run::run_order(order, output))
             .await
-            .map_err(|err| lib::formula::error::WorkspaceOrder::OpenFile(err.kind()))?
-            .into_std()
-            .await;
-        let rdr = csv::Reader::from_reader(file);
-        let mut csv = lib::data::Csv::from_csv_reader(rdr)?;
-        for update in updates {
-            let idx = core::data::CellIndex::new(update.row, update.col);
-            csv.sheet
-                .insert(idx, update.value)
-                .expect("cell should be empty");
-        }
-
-        csv.save(&path)?;
-        Ok(())
+            .map_err(|_| lib::formula::error::WorkspaceOrder::TaskNotCompleted)?
     }
 
-    async fn run_workspace_order_update_workbook(
-        path: PathBuf,
-        updates: Vec<lib::formula::UpdateWorkbook>,
-    ) -> Result<(), lib::formula::error::WorkspaceOrder> {
-        todo!();
+    /// Move staged files into place, all or nothing.
+    /// A file staged by several orders is moved once.
+    /// If a file can not be moved, the files already moved are restored
+    /// and the remaining staged files removed.
+    /// See [`lib::run::commit_all`].
+    ///
+    /// # Arguments
+    /// + `staged`: `(<order indices>, <path>)` of the files to commit.
+    ///
+    /// # Returns
+    /// Order indices and error of the file that could not be moved.
+    async fn commit_staged(
+        staged: &[(Vec<usize>, PathBuf)],
+        file_locks: &crate::FileLocks,
+    ) -> Result<(), (Vec<usize>, lib::formula::error::WorkspaceOrder)> {
+        // Locks are taken in path order so concurrent commits can not deadlock.
+        let mut paths = staged.iter().map(|(_, path)| path).collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+        let mut guards = Vec::with_capacity(paths.len());
+        for path in paths {
+            guards.push(file_locks.lock(path).await);
+        }
+
+        let files = staged
+            .iter()
+            .map(|(_, path)| path.as_path())
+            .collect::<Vec<_>>();
+        lib::run::commit_all(&files).map_err(|(idx, err)| {
+            (
+                staged[idx].0.clone(),
+                lib::formula::error::WorkspaceOrder::Save(err.kind()),
+            )
+        })
+    }
+
+    /// Remove staged files.
+    fn discard_staged(staged: &[(Vec<usize>, PathBuf)]) {
+        for (_, path) in staged {
+            if let Err(err) = lib::run::discard(path) {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(?err, ?path, "could not remove staged file");
+            }
+        }
     }
 }
@@ -1,23 +1,33 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+use std::path::PathBuf;
+
 fn main() {
     #[cfg(feature = "tracing")]
     let _log_guard = logging::enable();
-    hermes_lib::run()
+    hermes_lib::run_with(launch_path())
+}
+
+/// # Returns
+/// File or folder passed as an argument, e.g. by the system's "Open with".
+/// Flags, such as those added by the system, are ignored.
+fn launch_path() -> Option<PathBuf> {
+    std::env::args_os()
+        .skip(1)
+        .find(|arg| !arg.to_string_lossy().starts_with('-'))
+        .map(PathBuf::from)
 }
 
 #[cfg(feature = "tracing")]
 mod logging {
+    use hermes_lib::{LOG_DIR, LOG_PREFIX};
     use tracing_subscriber::{
         EnvFilter, Registry, filter,
         fmt::{self, time},
         prelude::*,
     };
 
-    const LOG_DIR: &str = ".";
-    const LOG_PREFIX: &str = "hermes.log";
-
     #[cfg(debug_assertions)]
     const HERMES_LOG_LEVEL_FILE: tracing::Level = tracing::Level::DEBUG;
     #[cfg(not(debug_assertions))]
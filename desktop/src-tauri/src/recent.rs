@@ -0,0 +1,59 @@
+//! Recently opened project roots.
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+use tauri::Manager;
+
+const RECENT_ROOTS_FILE: &str = "recent_roots.json";
+
+/// Maximum number of recent roots to remember.
+const MAX_RECENT_ROOTS: usize = 10;
+
+fn recent_roots_path(app: &tauri::AppHandle) -> io::Result<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(RECENT_ROOTS_FILE))
+        .map_err(io::Error::other)
+}
+
+/// # Returns
+/// Recently opened roots, most recent first.
+/// Empty if the list does not exist or could not be read.
+pub fn load(app: &tauri::AppHandle) -> Vec<PathBuf> {
+    let Ok(path) = recent_roots_path(app) else {
+        return vec![];
+    };
+    let Ok(content) = fs::read_to_string(path) else {
+        return vec![];
+    };
+
+    serde_json::from_str(&content).unwrap_or_default()
+}
+
+fn save(app: &tauri::AppHandle, roots: &Vec<PathBuf>) -> io::Result<()> {
+    let path = recent_roots_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let content = serde_json::to_string_pretty(roots).map_err(io::Error::other)?;
+    fs::write(path, content)
+}
+
+/// Move `root` to the front of the recent roots.
+pub fn push(app: &tauri::AppHandle, root: PathBuf) -> io::Result<()> {
+    let mut roots = load(app);
+    roots.retain(|recent| *recent != root);
+    roots.insert(0, root);
+    roots.truncate(MAX_RECENT_ROOTS);
+    save(app, &roots)
+}
+
+/// Remove `root` from the recent roots.
+pub fn remove(app: &tauri::AppHandle, root: impl AsRef<Path>) -> io::Result<()> {
+    let root = root.as_ref();
+    let mut roots = load(app);
+    roots.retain(|recent| recent != root);
+    save(app, &roots)
+}
@@ -0,0 +1,42 @@
+//! Recovery of the workspace session.
+//! The session is saved by the ui while a project is open, and removed when the app exits.
+//! A session remaining when the app launches means the previous run exited uncleanly.
+use hermes_desktop_lib as lib;
+use std::{fs, io, path::PathBuf};
+use tauri::Manager;
+
+const RECOVERY_FILE: &str = "recovery.json";
+
+fn recovery_path(app: &tauri::AppHandle) -> io::Result<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(RECOVERY_FILE))
+        .map_err(io::Error::other)
+}
+
+/// # Returns
+/// Session left by a previous run.
+/// `None` if it does not exist or could not be read.
+pub fn load(app: &tauri::AppHandle) -> Option<lib::session::Session> {
+    let path = recovery_path(app).ok()?;
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+pub fn save(app: &tauri::AppHandle, session: &lib::session::Session) -> io::Result<()> {
+    let path = recovery_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let content = serde_json::to_string(session).map_err(io::Error::other)?;
+    fs::write(path, content)
+}
+
+/// Remove the saved session.
+pub fn clear(app: &tauri::AppHandle) -> io::Result<()> {
+    match fs::remove_file(recovery_path(app)?) {
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
@@ -0,0 +1,127 @@
+//! Application settings.
+//! Global settings are saved in the app config directory.
+//! Projects may override them with a [settings file](lib::settings::SETTINGS_FILE) in their root.
+//! A project's protected files are listed in its [protected file](lib::settings::PROTECTED_FILE).
+use hermes_desktop_lib as lib;
+use serde::Serialize;
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
+use tauri::Manager;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Settings in effect for the open project.
+#[derive(derive_more::Deref, Clone)]
+pub struct CurrentSettings(Arc<Mutex<lib::settings::Settings>>);
+impl CurrentSettings {
+    pub fn new(settings: lib::settings::Settings) -> Self {
+        Self(Arc::new(Mutex::new(settings)))
+    }
+
+    pub fn get(&self) -> lib::settings::Settings {
+        self.lock().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    pub fn set(&self, settings: lib::settings::Settings) {
+        *self.lock().unwrap_or_else(PoisonError::into_inner) = settings;
+    }
+}
+
+fn settings_path(app: &tauri::AppHandle) -> io::Result<PathBuf> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .map_err(io::Error::other)
+}
+
+/// Settings were previously saved in the app data directory.
+fn legacy_settings_path(app: &tauri::AppHandle) -> io::Result<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(SETTINGS_FILE))
+        .map_err(io::Error::other)
+}
+
+fn project_settings_path(root: impl AsRef<Path>) -> PathBuf {
+    root.as_ref().join(lib::settings::SETTINGS_FILE)
+}
+
+fn protected_path(root: impl AsRef<Path>) -> PathBuf {
+    root.as_ref().join(lib::settings::PROTECTED_FILE)
+}
+
+/// # Returns
+/// Settings in effect for the open project.
+pub fn current(app: &tauri::AppHandle) -> lib::settings::Settings {
+    app.state::<CurrentSettings>().get()
+}
+
+/// # Returns
+/// Saved global settings.
+/// Default settings if they do not exist or could not be read.
+pub fn load(app: &tauri::AppHandle) -> lib::settings::Settings {
+    let content = [settings_path(app), legacy_settings_path(app)]
+        .into_iter()
+        .filter_map(Result::ok)
+        .find_map(|path| fs::read_to_string(path).ok());
+
+    content
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// # Returns
+/// Settings of the project, or `None` if the project does not have its own settings.
+pub fn load_project(root: impl AsRef<Path>) -> Option<lib::settings::Settings> {
+    let content = fs::read_to_string(project_settings_path(root)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// # Returns
+/// Protected files of the project, relative to its root.
+/// Empty if the project has none or they could not be read.
+pub fn load_protected(root: impl AsRef<Path>) -> BTreeSet<PathBuf> {
+    fs::read_to_string(protected_path(root))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn save(app: &tauri::AppHandle, settings: &lib::settings::Settings) -> io::Result<()> {
+    write(settings_path(app)?, settings)
+}
+
+pub fn save_project(root: impl AsRef<Path>, settings: &lib::settings::Settings) -> io::Result<()> {
+    write(project_settings_path(root), settings)
+}
+
+/// Save the protected files of the project.
+/// Removes the file if no files are protected.
+///
+/// # Arguments
+/// + `files`: Paths relative to the project root.
+pub fn save_protected(root: impl AsRef<Path>, files: &BTreeSet<PathBuf>) -> io::Result<()> {
+    let path = protected_path(root);
+    if files.is_empty() {
+        return match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        };
+    }
+
+    write(path, files)
+}
+
+fn write(path: impl AsRef<Path>, value: &impl Serialize) -> io::Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let content = serde_json::to_string_pretty(value).map_err(io::Error::other)?;
+    fs::write(path, content)
+}
@@ -0,0 +1,37 @@
+//! Saved formula templates.
+use hermes_desktop_lib as lib;
+use std::{fs, io, path::PathBuf};
+use tauri::Manager;
+
+const TEMPLATES_FILE: &str = "templates.json";
+
+fn templates_path(app: &tauri::AppHandle) -> io::Result<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(TEMPLATES_FILE))
+        .map_err(io::Error::other)
+}
+
+/// # Returns
+/// Saved templates.
+/// Empty if they do not exist or could not be read.
+pub fn load(app: &tauri::AppHandle) -> Vec<lib::template::Template> {
+    let Ok(path) = templates_path(app) else {
+        return vec![];
+    };
+    let Ok(content) = fs::read_to_string(path) else {
+        return vec![];
+    };
+
+    serde_json::from_str(&content).unwrap_or_default()
+}
+
+pub fn save(app: &tauri::AppHandle, templates: &[lib::template::Template]) -> io::Result<()> {
+    let path = templates_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let content = serde_json::to_string_pretty(templates).map_err(io::Error::other)?;
+    fs::write(path, content)
+}
@@ -0,0 +1,164 @@
+//! Workspace windows.
+//! Each window runs its own workspace, sharing the backend.
+//! The size and position of the last window closed are restored to the main window on launch.
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::PathBuf};
+use tauri::Manager;
+
+/// Label of the window opened on launch.
+pub const MAIN: &str = "main";
+
+/// Prefix of the labels of windows opened with [`open`].
+/// Must match the capabilities' windows.
+#[cfg(desktop)]
+const WORKSPACE_PREFIX: &str = "workspace-";
+
+const WINDOW_STATE_FILE: &str = "window_state.json";
+
+/// Size of new windows, in logical pixels.
+#[cfg(desktop)]
+const DEFAULT_SIZE: (f64, f64) = (800.0, 600.0);
+
+/// Menu item opening a new window.
+#[cfg(desktop)]
+pub const NEW_WINDOW_MENU_ID: &str = "new-window";
+
+/// Size and position of a window, in physical pixels.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+struct WindowState {
+    width: u32,
+    height: u32,
+    x: i32,
+    y: i32,
+    maximized: bool,
+}
+
+fn window_state_path(app: &tauri::AppHandle) -> io::Result<PathBuf> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(WINDOW_STATE_FILE))
+        .map_err(io::Error::other)
+}
+
+/// # Returns
+/// `None` if the state does not exist or could not be read.
+fn load(app: &tauri::AppHandle) -> Option<WindowState> {
+    let path = window_state_path(app).ok()?;
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+/// Save the size and position of a window.
+/// While maximized only the maximized state is updated,
+/// so the window is restored to its previous size when unmaximized.
+pub fn save(window: &tauri::Window) -> io::Result<()> {
+    let app = window.app_handle();
+    let maximized = window.is_maximized().map_err(io::Error::other)?;
+    let state = match load(app) {
+        Some(state) if maximized => WindowState { maximized, ..state },
+        _ => {
+            let size = window.inner_size().map_err(io::Error::other)?;
+            let position = window.outer_position().map_err(io::Error::other)?;
+            WindowState {
+                width: size.width,
+                height: size.height,
+                x: position.x,
+                y: position.y,
+                maximized,
+            }
+        }
+    };
+
+    let path = window_state_path(app)?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let content = serde_json::to_string_pretty(&state).map_err(io::Error::other)?;
+    fs::write(path, content)
+}
+
+/// Restore the saved size and position to a window.
+/// The position is only restored if it is on an available monitor,
+/// e.g. not if the monitor it was on has been disconnected.
+pub fn restore(window: &tauri::WebviewWindow) -> tauri::Result<()> {
+    let Some(state) = load(window.app_handle()) else {
+        return Ok(());
+    };
+
+    window.set_size(tauri::PhysicalSize::new(state.width, state.height))?;
+    let on_monitor = window.available_monitors()?.iter().any(|monitor| {
+        let position = monitor.position();
+        let size = monitor.size();
+        (position.x..position.x + size.width as i32).contains(&state.x)
+            && (position.y..position.y + size.height as i32).contains(&state.y)
+    });
+    if on_monitor {
+        window.set_position(tauri::PhysicalPosition::new(state.x, state.y))?;
+    }
+    if state.maximized {
+        window.maximize()?;
+    }
+
+    Ok(())
+}
+
+/// Open a new workspace window.
+#[cfg(desktop)]
+pub fn open(app: &tauri::AppHandle) -> tauri::Result<tauri::WebviewWindow> {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static NEXT_WINDOW: AtomicUsize = AtomicUsize::new(1);
+
+    let label = format!(
+        "{WORKSPACE_PREFIX}{}",
+        NEXT_WINDOW.fetch_add(1, Ordering::Relaxed)
+    );
+    let (width, height) = DEFAULT_SIZE;
+    tauri::WebviewWindowBuilder::new(app, label, tauri::WebviewUrl::default())
+        .title("hermes")
+        .inner_size(width, height)
+        .build()
+}
+
+/// App menu, with items to open a new window in the File menu,
+/// and create a diagnostic bundle in the Help menu.
+#[cfg(desktop)]
+pub fn menu(app: &tauri::AppHandle) -> tauri::Result<tauri::menu::Menu<tauri::Wry>> {
+    use tauri::menu::{Menu, MenuItem, Submenu};
+
+    let menu = Menu::default(app)?;
+    let submenu = |text: &str| -> tauri::Result<Option<Submenu<tauri::Wry>>> {
+        Ok(menu.items()?.into_iter().find_map(|item| {
+            item.as_submenu()
+                .filter(|submenu| submenu.text().is_ok_and(|label| label == text))
+                .cloned()
+        }))
+    };
+
+    let new_window = MenuItem::with_id(
+        app,
+        NEW_WINDOW_MENU_ID,
+        "New Window",
+        true,
+        Some("CmdOrCtrl+Shift+N"),
+    )?;
+    match submenu("File")? {
+        Some(file) => file.prepend(&new_window)?,
+        None => menu.prepend(&Submenu::with_items(app, "File", true, &[&new_window])?)?,
+    }
+
+    let diagnostics = MenuItem::with_id(
+        app,
+        crate::diagnostics::MENU_ID,
+        "Create Diagnostic Bundle",
+        true,
+        None::<&str>,
+    )?;
+    match submenu("Help")? {
+        Some(help) => help.append(&diagnostics)?,
+        None => menu.append(&Submenu::with_items(app, "Help", true, &[&diagnostics])?)?,
+    }
+
+    Ok(menu)
+}
@@ -1,42 +1,140 @@
-use crate::{component, dataset, explorer, formula, icon, message, state, types};
+use crate::{
+    chart, component, dataset, diagnostics, explorer, formula, history, icon, keybinding, message,
+    session, settings, state, types,
+};
+use futures::StreamExt;
 use hermes_core as core;
 use hermes_desktop_lib as lib;
-use leptos::{either::Either, ev, prelude::*};
+use leptos::{
+    either::Either,
+    ev,
+    prelude::*,
+    task::{spawn_local, spawn_local_scoped},
+};
 use leptos_icons::Icon;
 use leptos_meta::*;
 use leptos_use::use_preferred_dark;
-use serde::Serialize;
+use lib::ipc::{
+    Event,
+    event::{LoadProgress, ThemeChanged},
+};
 use std::path::PathBuf;
 
 #[component]
 pub fn App() -> impl IntoView {
     leptos_meta::provide_meta_context();
-    let prefers_dark_mode = use_preferred_dark();
     let (root_path, set_root_path) = signal(None);
+    let settings = state::Settings::new();
+    provide_context(settings);
+    provide_context(session::Recovery::new());
+    provide_context(state::CompactLayout::new());
+    let launch_file = state::LaunchFile::new();
+    provide_context(launch_file);
+    let launching = RwSignal::new(true);
+    spawn_local_scoped(open_launch_path(set_root_path, launch_file, launching));
 
-    let html_class = move || if prefers_dark_mode() { "dark" } else { "" };
+    let system_dark = RwSignal::new(use_preferred_dark().get_untracked());
+    spawn_local_scoped(watch_system_theme(system_dark));
+
+    let html_class = move || {
+        let dark = settings.with(|settings| settings.theme.is_dark(system_dark.get()));
+        if dark { "dark" } else { "" }
+    };
+    let html_lang = move || settings.with(|settings| settings.locale.clone());
 
     view! {
         <Title formatter=|text| text text="Hermes" />
-        <Html attr:class=html_class />
+        <Html attr:class=html_class attr:lang=html_lang />
         <Body attr:class="h-screen font-secondary overflow-hidden dark:bg-secondary-800 dark:text-white select-none" />
 
         <div class="h-full">
             {move || match root_path.get() {
-                None => Either::Left(view! { <SelectRootPath set_root_path /> }),
+                None => Either::Left(
+                    (!launching.get()).then(|| view! { <SelectRootPath set_root_path /> }),
+                ),
                 Some(root_path) => Either::Right(view! { <Workspace root=root_path /> }),
             }}
         </div>
     }
 }
 
+/// Open the project Hermes was launched with, if any,
+/// instead of asking for a folder.
+async fn open_launch_path(
+    set_root_path: WriteSignal<Option<PathBuf>>,
+    launch_file: state::LaunchFile,
+    launching: RwSignal<bool>,
+) {
+    let launch: Result<Option<lib::fs::Launch>, lib::fs::error::FromFileSystem> =
+        crate::ipc::invoke(lib::ipc::LaunchPath).await;
+
+    match launch {
+        Ok(Some(launch)) => {
+            launch_file.set(launch.file);
+            set_root_path(Some(launch.root));
+        }
+        Ok(None) => {}
+        Err(err) => tracing::error!(?err, "could not open launch path"),
+    }
+    launching.set(false);
+}
+
+/// Track whether the system theme is dark.
+async fn watch_system_theme(system_dark: RwSignal<bool>) {
+    let theme: lib::settings::Theme = crate::ipc::invoke_infallible(lib::ipc::SystemTheme).await;
+    system_dark.set(theme == lib::settings::Theme::Dark);
+
+    let mut events = match tauri_sys::event::listen::<<ThemeChanged as Event>::Payload>(
+        ThemeChanged::NAME,
+    )
+    .await
+    {
+        Ok(events) => events,
+        Err(err) => {
+            tracing::error!(?err, "could not listen for theme changes");
+            return;
+        }
+    };
+
+    while let Some(event) = events.next().await {
+        system_dark.set(event.payload == lib::settings::Theme::Dark);
+    }
+}
+
+/// Track the progress of dataset loads.
+async fn watch_load_progress(progress: state::LoadProgress) {
+    let mut events = match tauri_sys::event::listen::<<LoadProgress as Event>::Payload>(
+        LoadProgress::NAME,
+    )
+    .await
+    {
+        Ok(events) => events,
+        Err(err) => {
+            tracing::error!(?err, "could not listen for load progress");
+            return;
+        }
+    };
+
+    while let Some(event) = events.next().await {
+        progress.set(Some(event.payload));
+    }
+}
+
 #[component]
 fn SelectRootPath(set_root_path: WriteSignal<Option<PathBuf>>) -> impl IntoView {
+    let (error, set_error) = signal::<Option<String>>(None);
     let select_folder_action = Action::new_local(move |_| async move {
-        let path = tauri_sys::core::invoke::<Option<PathBuf>>("select_folder", ()).await;
-        set_root_path(path);
+        match select_folder().await {
+            Ok(path) => {
+                set_error(None);
+                set_root_path(path);
+            }
+            Err(err) => set_error(Some(err.to_string())),
+        }
     });
 
+    spawn_local_scoped(diagnostics::watch_requests(None));
+
     let select_folder = move |e: ev::MouseEvent| {
         if e.button() != types::MouseButton::Primary {
             return;
@@ -53,10 +151,93 @@ fn SelectRootPath(set_root_path: WriteSignal<Option<PathBuf>>) -> impl IntoView
                     "Open a folder"
                 </button>
             </div>
+            <div class="text-center">
+                <small class="color-brand-red-600">{error}</small>
+            </div>
+            <session::RecoveryPrompt set_root_path />
+            <RecentRoots set_root_path />
         </main>
     }
 }
 
+/// Recently opened project roots.
+#[component]
+fn RecentRoots(set_root_path: WriteSignal<Option<PathBuf>>) -> impl IntoView {
+    let recent_roots = LocalResource::new(list_recent_roots);
+    let (error, set_error) = signal::<Option<String>>(None);
+
+    let open_recent_root_action = Action::new_local(move |root: &PathBuf| {
+        let root = root.clone();
+        async move {
+            match open_recent_root(root).await {
+                Ok(root) => set_root_path(Some(root)),
+                Err(err) => {
+                    set_error(Some(err.to_string()));
+                    recent_roots.refetch();
+                }
+            }
+        }
+    });
+
+    move || {
+        let roots = recent_roots.get().unwrap_or_default();
+        if roots.is_empty() {
+            return None;
+        }
+
+        let roots = roots
+            .into_iter()
+            .map(|root| {
+                let name = root
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_else(|| root.to_string_lossy().to_string());
+                let path = root.to_string_lossy().to_string();
+                let open = move |e: ev::MouseEvent| {
+                    if e.button() != types::MouseButton::Primary {
+                        return;
+                    }
+
+                    open_recent_root_action.dispatch(root.clone());
+                };
+
+                view! {
+                    <li
+                        class="flex gap-2 items-end px cursor-pointer hover:bg-secondary-50 dark:hover:bg-secondary-700"
+                        title=path.clone()
+                        on:mousedown=open
+                    >
+                        <div>{name}</div>
+                        <small class="truncate text-secondary-700 dark:text-secondary-200">
+                            {path}
+                        </small>
+                    </li>
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Some(view! {
+            <div class="max-w-md mx-auto">
+                <h2 class="font-bold uppercase pb">"Recent"</h2>
+                <ul>{roots}</ul>
+                <small class="color-brand-red-600">{error}</small>
+            </div>
+        })
+    }
+}
+
+async fn select_folder() -> Result<Option<PathBuf>, lib::fs::error::SelectFolder> {
+    crate::ipc::invoke(lib::ipc::SelectFolder).await
+}
+
+async fn list_recent_roots() -> Vec<PathBuf> {
+    crate::ipc::invoke_infallible(lib::ipc::ListRecentRoots).await
+}
+
+async fn open_recent_root(root: PathBuf) -> Result<PathBuf, lib::fs::error::FromFileSystem> {
+    crate::ipc::invoke(lib::ipc::OpenRecentRoot { root }).await
+}
+
 #[component]
 fn Workspace(root: PathBuf) -> impl IntoView {
     let load_directory_tree = LocalResource::new({
@@ -106,103 +287,386 @@ fn LoadError(errors: ArcRwSignal<Errors>) -> impl IntoView {
 
 #[component]
 fn WorkspaceView(root: PathBuf, graph: lib::fs::DirectoryTree) -> impl IntoView {
-    let state = state::State::new(root, graph);
+    let state = state::State::new(root, graph, expect_context::<state::Settings>());
     provide_context(state.clone());
+    settings::load(state.clone());
     provide_context(state::LoadWorkbookActionAbortHandle::new());
+    let load_progress = state::LoadProgress::new();
+    provide_context(load_progress);
+    spawn_local_scoped(watch_load_progress(load_progress));
     provide_context(state::WorkspaceOwner::with_current());
     provide_context(state::FormulaEditorVisibility::new());
+    provide_context(state::NewFormulaDialogVisibility::new());
+    provide_context(state::FormulaReferences::new());
+    let keybindings = keybinding::Keybindings::new();
+    provide_context(keybindings);
+    keybinding::listen(keybindings, state.settings);
+    session::autosave(state.clone());
+    session::restore(state.clone(), expect_context::<state::WorkspaceOwner>());
+    open_launch_file(state.clone(), expect_context::<state::WorkspaceOwner>());
+    spawn_local_scoped(diagnostics::watch_requests(Some(state.clone())));
+
+    let compact = expect_context::<state::CompactLayout>();
+    let panels_open = RwSignal::new(false);
+    let explorer_hidden = RwSignal::new(false);
+    let active_dataset = state.active_dataset;
+    Effect::watch(
+        move || active_dataset.read().as_ref().cloned(),
+        move |_, _, _| panels_open.set(false),
+        false,
+    );
+    let panels_class = move || {
+        if !compact.get() {
+            if explorer_hidden.get() {
+                "hidden"
+            } else {
+                "contents"
+            }
+        } else if panels_open.get() {
+            "absolute inset-y-0 right-0 z-30 w-full max-w-sm overflow-y-auto \
+            bg-white dark:bg-secondary-800 shadow-lg"
+        } else {
+            "hidden"
+        }
+    };
+
+    keybindings.on(lib::settings::Action::SaveWorkspace, {
+        let state = state.clone();
+        move || session::save(state.clone())
+    });
+    keybindings.on(lib::settings::Action::ToggleExplorer, move || {
+        if compact.get_untracked() {
+            panels_open.update(|open| *open = !*open);
+        } else {
+            explorer_hidden.update(|hidden| *hidden = !*hidden);
+        }
+    });
+    keybindings.on(lib::settings::Action::OpenFormulaEditor, {
+        let active_formula = state.active_formula;
+        let editor_vis = expect_context::<state::FormulaEditorVisibility>();
+        let new_formula_vis = expect_context::<state::NewFormulaDialogVisibility>();
+        move || {
+            if active_formula.read_untracked().is_some() {
+                editor_vis.set(true);
+            } else {
+                new_formula_vis.set(true);
+            }
+        }
+    });
 
     view! {
         <div class="flex flex-col h-full">
-            <div class="grow flex h-full">
+            <div class="relative grow flex h-full">
                 <div class="grow min-w-0 h-full">
                     <dataset::Workspace />
                 </div>
-                <component::ResizablePane>
-                    <run::Run />
-                    <formula::Workspace
-                        {..}
-                        class="border-l-secondary-50 dark:border-l-secondary-700 \
-                        border-b-secondary-50 dark:border-b-secondary-700"
-                    />
-                    <explorer::OutputFiles
-                        {..}
-                        class="border-l-secondary-50 dark:border-l-secondary-700 \
-                        border-b border-b-secondary-50 dark:border-b-secondary-700"
-                    />
-                    <explorer::ActiveFiles
-                        {..}
-                        class="border-l-secondary-50 dark:border-l-secondary-700 \
-                        border-b border-b-secondary-50 dark:border-b-secondary-700"
-                    />
-                    <explorer::FileTree class="border-l-secondary-50 dark:border-l-secondary-700" />
-                </component::ResizablePane>
+                <Show when=move || compact.get()>
+                    <button
+                        type="button"
+                        class="absolute bottom-2 right-2 z-40 p-3 rounded-full \
+                        bg-primary-600 text-white shadow-lg cursor-pointer"
+                        title=move || if panels_open.get() { "Hide panels" } else { "Show panels" }
+                        on:click=move |_| panels_open.update(|open| *open = !*open)
+                    >
+                        {move || {
+                            if panels_open.get() {
+                                view! { <Icon icon=icon::Close /> }
+                            } else {
+                                view! { <Icon icon=icon::Menu /> }
+                            }
+                        }}
+                    </button>
+                </Show>
+                <div class=panels_class>
+                    <component::ResizablePane compact>
+                        <run::Run />
+                        <formula::Workspace
+                            {..}
+                            class="border-l-secondary-50 dark:border-l-secondary-700 \
+                            border-b-secondary-50 dark:border-b-secondary-700"
+                        />
+                        <formula::Templates
+                            {..}
+                            class="border-l-secondary-50 dark:border-l-secondary-700 \
+                            border-b border-b-secondary-50 dark:border-b-secondary-700"
+                        />
+                        <explorer::OutputFiles
+                            {..}
+                            class="border-l-secondary-50 dark:border-l-secondary-700 \
+                            border-b border-b-secondary-50 dark:border-b-secondary-700"
+                        />
+                        <explorer::PivotTables
+                            {..}
+                            class="border-l-secondary-50 dark:border-l-secondary-700 \
+                            border-b border-b-secondary-50 dark:border-b-secondary-700"
+                        />
+                        <explorer::JoinTables
+                            {..}
+                            class="border-l-secondary-50 dark:border-l-secondary-700 \
+                            border-b border-b-secondary-50 dark:border-b-secondary-700"
+                        />
+                        <explorer::SubsetTables
+                            {..}
+                            class="border-l-secondary-50 dark:border-l-secondary-700 \
+                            border-b border-b-secondary-50 dark:border-b-secondary-700"
+                        />
+                        <explorer::ActiveFiles
+                            {..}
+                            class="border-l-secondary-50 dark:border-l-secondary-700 \
+                            border-b border-b-secondary-50 dark:border-b-secondary-700"
+                        />
+                        <dataset::ColumnStatistics
+                            {..}
+                            class="border-l-secondary-50 dark:border-l-secondary-700 \
+                            border-b border-b-secondary-50 dark:border-b-secondary-700"
+                        />
+                        <chart::Chart
+                            {..}
+                            class="border-l-secondary-50 dark:border-l-secondary-700 \
+                            border-b border-b-secondary-50 dark:border-b-secondary-700"
+                        />
+                        <history::History
+                            {..}
+                            class="border-l-secondary-50 dark:border-l-secondary-700 \
+                            border-b border-b-secondary-50 dark:border-b-secondary-700"
+                        />
+                        <settings::Settings
+                            {..}
+                            class="border-l-secondary-50 dark:border-l-secondary-700 \
+                            border-b border-b-secondary-50 dark:border-b-secondary-700"
+                        />
+                        <explorer::DuplicateFiles
+                            {..}
+                            class="border-l-secondary-50 dark:border-l-secondary-700 \
+                            border-b border-b-secondary-50 dark:border-b-secondary-700"
+                        />
+                        <explorer::FileTree class="border-l-secondary-50 dark:border-l-secondary-700" />
+                    </component::ResizablePane>
+                </div>
             </div>
 
         </div>
         <div class="absolute top-0">
             <message::Messages />
         </div>
+        <explorer::QuickOpen />
+        <formula::NewFormula />
+        <keybinding::CheatSheet />
     }
 }
 
+/// Open the file Hermes was launched with, if any.
+fn open_launch_file(state: state::State, owner: state::WorkspaceOwner) {
+    let launch_file = expect_context::<state::LaunchFile>();
+    let Some(path) = launch_file.write_untracked().take() else {
+        return;
+    };
+
+    let Some(file) = state.directory_tree.get_file_by_path(&path) else {
+        state.messages.push(message::Message::error_with_body(
+            "Could not open file.",
+            format!("`{}` is not in the project.", path.to_string_lossy()),
+        ));
+        return;
+    };
+
+    spawn_local(explorer::open_file(
+        state,
+        owner,
+        file.id().clone(),
+        lib::data::LoadId::next(),
+    ));
+}
+
 async fn load_directory(
     root: PathBuf,
 ) -> Result<lib::fs::DirectoryTree, lib::fs::error::FromFileSystem> {
-    #[derive(Serialize)]
-    struct Args {
-        root: PathBuf,
-    }
-
-    tauri_sys::core::invoke_result("load_directory", Args { root }).await
+    crate::ipc::invoke(lib::ipc::LoadDirectory { root }).await
 }
 
 mod run {
-    use crate::{state, state::FileResource, types};
+    use crate::{
+        dataset, explorer, formula, icon, keybinding, message, state, state::FileResource, types,
+    };
+    use futures::StreamExt;
     use hermes_core as core;
     use hermes_desktop_lib as lib;
-    use leptos::{ev, prelude::*};
-    use std::{collections::HashMap, path::PathBuf};
+    use leptos::{
+        ev, html,
+        prelude::*,
+        task::{spawn_local, spawn_local_scoped},
+    };
+    use leptos_icons::Icon;
+    use lib::ipc::{Event, event::FilesModified};
+    use std::{
+        collections::{BTreeMap, HashMap, HashSet},
+        path::PathBuf,
+        time::Duration,
+    };
+
+    /// Time to wait for further changes before automatically running the workspace.
+    const AUTO_RUN_DEBOUNCE: Duration = Duration::from_millis(1_000);
 
     #[component]
     pub fn Run() -> impl IntoView {
         let state = expect_context::<state::State>();
+        let atomic_run = state.atomic_run;
         let disabled = {
             let formulas = state.formulas.read_only();
-            move || formulas.read().is_empty()
+            let output_files = state.output_files.read_only();
+            let pivot_tables = state.pivot_tables.read_only();
+            let join_tables = state.join_tables.read_only();
+            let subset_tables = state.subset_tables.read_only();
+            move || {
+                formulas.read().is_empty()
+                    && output_files.read().is_empty()
+                    && pivot_tables.read().is_empty()
+                    && join_tables.read().is_empty()
+                    && subset_tables.read().is_empty()
+            }
         };
 
+        let results = RwSignal::new(None::<RunResults>);
+        let results_open = RwSignal::new(false);
         let run_workspace = Action::new_local({
+            let state = state.clone();
+            let messages = state.messages;
+            let auto_run = state.auto_run;
+            let atomic = state.atomic_run;
+            let settings = state.settings;
+            let root = state.root_path().clone();
             move |orders: &Vec<lib::formula::WorkspaceOrder>| {
                 let orders = orders.clone();
+                let root = root.clone();
+                let atomic = atomic.get_untracked();
+                let manifest = settings.with_untracked(|settings| settings.manifest);
+                let state = state.clone();
                 async move {
-                    if let Err(err) = run_workspace(&orders).await {
+                    auto_run.set_written(orders.iter().filter_map(|order| order.path()));
+                    let result = run_workspace(root, &orders, false, atomic, manifest).await;
+                    auto_run.set_written(orders.iter().filter_map(|order| order.path()));
+                    let run_results = RunResults::new(orders.clone(), &result, atomic);
+                    record_written_values(&state, run_results.succeeded());
+                    if run_results.failed() > 0 {
+                        results_open.set(true);
+                    }
+                    results.set(Some(run_results));
+                    if let Err(err) = result {
                         tracing::warn!(?err);
+                        let body = if atomic {
+                            format!(
+                                "{} of {} order(s) failed, no files were modified.",
+                                err.len(),
+                                orders.len()
+                            )
+                        } else {
+                            format!("{} of {} order(s) failed.", err.len(), orders.len())
+                        };
+                        message::notify_complete(
+                            lib::settings::Operation::Run,
+                            "Run finished with errors",
+                            Some(body),
+                        );
+                        let protected = err
+                            .iter()
+                            .filter(|(_, err)| {
+                                matches!(err, lib::formula::error::WorkspaceOrder::Protected)
+                            })
+                            .count();
+
+                        if protected > 0 {
+                            messages.push(message::Message::error_with_body(
+                                "Protected files were not modified.",
+                                format!("{protected} protected file(s) were skipped."),
+                            ));
+                        }
                     } else {
                         tracing::info!("workspace run complete");
+                        message::notify_complete(
+                            lib::settings::Operation::Run,
+                            "Run complete",
+                            Some(format!("{} order(s) completed.", orders.len())),
+                        );
                     };
                 }
             }
         });
 
-        let dispatch_run_workspace = move |e: ev::MouseEvent| {
-            if e.button() != types::MouseButton::Primary {
-                return;
+        let preview = RwSignal::new(None);
+        let preview_workspace = Action::new_local({
+            let root = state.root_path().clone();
+            move |orders: &Vec<lib::formula::WorkspaceOrder>| {
+                let orders = orders.clone();
+                let root = root.clone();
+                async move {
+                    let result = run_workspace(root, &orders, true, false, false).await;
+                    preview.set(Some(result));
+                }
             }
+        });
+
+        let dispatch_preview_workspace = {
+            let state = state.clone();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
 
-            match formulas_to_workspace_orders(
-                state.root_path().clone(),
-                state.formulas,
-                state.datasets,
-                state.directory_tree.clone(),
-            ) {
-                Ok(orders) => {
-                    run_workspace.dispatch(orders);
+                match workspace_orders(&state) {
+                    Ok(orders) => {
+                        preview_workspace.dispatch(orders);
+                    }
+                    Err(errors) => {
+                        tracing::warn!(?errors);
+                        state.messages.push(message::Message::error_with_body(
+                            "Preview skipped.",
+                            format!("{} formula(s) have errors.", errors.len()),
+                        ));
+                    }
                 }
-                Err(errors) => todo!(),
             }
         };
 
+        let dispatch_run_workspace = {
+            let state = state.clone();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+
+                match workspace_orders(&state) {
+                    Ok(orders) => {
+                        run_workspace.dispatch(orders);
+                    }
+                    Err(errors) => {
+                        tracing::warn!(?errors);
+                        state.messages.push(message::Message::error_with_body(
+                            "Run skipped.",
+                            format!("{} formula(s) have errors.", errors.len()),
+                        ));
+                    }
+                }
+            }
+        };
+
+        expect_context::<keybinding::Keybindings>().on(
+            lib::settings::Action::RunWorkspace,
+            move || {
+                if disabled() || run_workspace.pending().get_untracked() {
+                    return;
+                }
+
+                match workspace_orders(&state) {
+                    Ok(orders) => {
+                        run_workspace.dispatch(orders);
+                    }
+                    Err(errors) => {
+                        tracing::warn!(?errors);
+                    }
+                }
+            },
+        );
+
         view! {
             <div class="text-center">
                 <button
@@ -215,91 +679,1025 @@ mod run {
                 >
                     "Run"
                 </button>
+                <button
+                    type="button"
+                    class="btn"
+                    title="Show the changes running would make, without writing to files."
+                    on:mousedown=dispatch_preview_workspace
+                    disabled=move || disabled() || preview_workspace.pending().get()
+                >
+                    "Preview"
+                </button>
+                <label
+                    class="px-1"
+                    title="Only modify files if every order succeeds."
+                >
+                    <input
+                        type="checkbox"
+                        prop:checked=atomic_run
+                        on:change=move |e| atomic_run.set(event_target_checked(&e))
+                    />
+                    " All or nothing"
+                </label>
+                <Show when=move || results.read().is_some()>
+                    <button
+                        type="button"
+                        class="btn"
+                        title="Show the result of each order of the last run."
+                        on:mousedown=move |e: ev::MouseEvent| {
+                            if e.button() == types::MouseButton::Primary {
+                                results_open.set(true);
+                            }
+                        }
+                    >
+                        "Results"
+                    </button>
+                </Show>
+                <Preview preview />
+                <Results results open=results_open />
+                <Snapshot />
+                <AutoRun run_workspace />
+                <Batch />
             </div>
         }
     }
 
+    /// Outcome of an order of a run.
+    #[derive(Clone, Debug)]
+    enum OrderOutcome {
+        Succeeded,
+        Failed(lib::formula::error::WorkspaceOrder),
+
+        /// The order succeeded but was not written
+        /// because another order of an all or nothing run failed.
+        RolledBack,
+    }
+
+    /// Orders of the last run, with their outcome.
+    #[derive(Clone, Debug)]
+    struct RunResults {
+        orders: Vec<(lib::formula::WorkspaceOrder, OrderOutcome)>,
+    }
+
+    impl RunResults {
+        /// # Arguments
+        /// + `result`: Result of running `orders`.
+        /// + `atomic`: Whether the run was all or nothing.
+        fn new(
+            orders: Vec<lib::formula::WorkspaceOrder>,
+            result: &Result<
+                Vec<lib::audit::Entry>,
+                Vec<(usize, lib::formula::error::WorkspaceOrder)>,
+            >,
+            atomic: bool,
+        ) -> Self {
+            let errors = match result {
+                Ok(_) => HashMap::new(),
+                Err(errors) => errors.iter().cloned().collect::<HashMap<_, _>>(),
+            };
+            let succeeded = if errors.is_empty() || !atomic {
+                OrderOutcome::Succeeded
+            } else {
+                OrderOutcome::RolledBack
+            };
+
+            let orders = orders
+                .into_iter()
+                .enumerate()
+                .map(|(idx, order)| {
+                    let outcome = match errors.get(&idx) {
+                        Some(err) => OrderOutcome::Failed(err.clone()),
+                        None => succeeded.clone(),
+                    };
+                    (order, outcome)
+                })
+                .collect();
+
+            Self { orders }
+        }
+
+        /// # Returns
+        /// Orders that were written.
+        fn succeeded(&self) -> impl Iterator<Item = &lib::formula::WorkspaceOrder> {
+            self.orders
+                .iter()
+                .filter(|(_, outcome)| matches!(outcome, OrderOutcome::Succeeded))
+                .map(|(order, _)| order)
+        }
+
+        /// # Returns
+        /// Number of orders that failed.
+        fn failed(&self) -> usize {
+            self.orders
+                .iter()
+                .filter(|(_, outcome)| matches!(outcome, OrderOutcome::Failed(_)))
+                .count()
+        }
+    }
+
+    /// Drawer listing the orders of the last run with their outcome.
+    /// Orders that were not written may be retried individually.
+    #[component]
+    fn Results(results: RwSignal<Option<RunResults>>, open: RwSignal<bool>) -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let root = state.root_path().clone();
+
+        let retry = Action::new_local({
+            let state = state.clone();
+            let auto_run = state.auto_run;
+            let settings = state.settings;
+            let root = root.clone();
+            move |idx: &usize| {
+                let idx = *idx;
+                let order = results.with_untracked(|results| {
+                    results
+                        .as_ref()
+                        .and_then(|results| results.orders.get(idx))
+                        .map(|(order, _)| order.clone())
+                });
+                let root = root.clone();
+                let manifest = settings.with_untracked(|settings| settings.manifest);
+                let state = state.clone();
+                async move {
+                    let Some(order) = order else {
+                        return;
+                    };
+
+                    let orders = vec![order];
+                    auto_run.set_written(orders.iter().filter_map(|order| order.path()));
+                    let result = run_workspace(root, &orders, false, false, manifest).await;
+                    auto_run.set_written(orders.iter().filter_map(|order| order.path()));
+                    let outcome = match result {
+                        Ok(_) => {
+                            record_written_values(&state, &orders);
+                            OrderOutcome::Succeeded
+                        }
+                        Err(mut errors) => match errors.pop() {
+                            Some((_, err)) => OrderOutcome::Failed(err),
+                            None => OrderOutcome::Succeeded,
+                        },
+                    };
+                    results.update(|results| {
+                        if let Some((_, current)) = results
+                            .as_mut()
+                            .and_then(|results| results.orders.get_mut(idx))
+                        {
+                            *current = outcome;
+                        }
+                    });
+                }
+            }
+        });
+
+        let close = move |_: ev::MouseEvent| open.set(false);
+        let close_on_escape = move |e: ev::KeyboardEvent| {
+            if e.key() == "Escape" {
+                e.prevent_default();
+                open.set(false);
+            }
+        };
+
+        let summary = move || {
+            results.with(|results| {
+                results.as_ref().map(|results| {
+                    let failed = results.failed();
+                    if failed == 0 {
+                        format!("{} order(s) succeeded.", results.orders.len())
+                    } else {
+                        format!("{failed} of {} order(s) failed.", results.orders.len())
+                    }
+                })
+            })
+        };
+
+        let orders = move || {
+            results.with(|results| {
+                let Some(results) = results.as_ref() else {
+                    return vec![];
+                };
+
+                results
+                    .orders
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, (order, outcome))| {
+                        let path = order
+                            .path()
+                            .map(|path| {
+                                path.strip_prefix(&root)
+                                    .unwrap_or(path)
+                                    .to_string_lossy()
+                                    .to_string()
+                            })
+                            .unwrap_or_default();
+                        let label = format!("{} {path}", order_kind(order));
+                        let (status, class) = match outcome {
+                            OrderOutcome::Succeeded => ("Succeeded".to_string(), ""),
+                            OrderOutcome::Failed(err) => (
+                                format!("Failed: {}", message::order_error_message(err)),
+                                "text-brand-red-600",
+                            ),
+                            OrderOutcome::RolledBack => (
+                                "Not written, another order failed".to_string(),
+                                "text-secondary-700 dark:text-secondary-200",
+                            ),
+                        };
+                        let retryable = !matches!(outcome, OrderOutcome::Succeeded);
+                        let retry_order = move |e: ev::MouseEvent| {
+                            if e.button() != types::MouseButton::Primary {
+                                return;
+                            }
+
+                            retry.dispatch(idx);
+                        };
+
+                        view! {
+                            <li class="flex gap-2 items-center">
+                                <div class="grow min-w-0">
+                                    <div class="truncate" title=label.clone()>
+                                        {format!("{}. {label}", idx + 1)}
+                                    </div>
+                                    <small class=class>{status}</small>
+                                </div>
+                                {retryable
+                                    .then(|| {
+                                        view! {
+                                            <button
+                                                type="button"
+                                                class="btn-cmd btn-secondary"
+                                                title="Retry this order"
+                                                disabled=move || retry.pending().get()
+                                                on:mousedown=retry_order
+                                            >
+                                                <Icon icon=icon::Refresh />
+                                            </button>
+                                        }
+                                    })}
+                            </li>
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        view! {
+            <div
+                class="text-left"
+                class:hidden=move || !open.get() || results.read().is_none()
+                on:keydown=close_on_escape
+            >
+                <div class="fixed inset-0 z-30" on:mousedown=close></div>
+                <div class="fixed z-40 inset-y-0 right-0 w-96 max-w-full overflow-auto p-1 \
+                bg-white dark:bg-secondary-800 border-l border-secondary-100 dark:border-secondary-600">
+                    <div class="flex gap-2">
+                        <h2 class="grow font-bold uppercase">"Run results"</h2>
+                        <button type="button" class="btn-cmd cursor-pointer" on:mousedown=close>
+                            <Icon icon=icon::Close />
+                        </button>
+                    </div>
+                    <div class="pb text-sm">{summary}</div>
+                    <ul class="flex flex-col gap-1 text-sm">{orders}</ul>
+                </div>
+            </div>
+        }
+    }
+
+    fn order_kind(order: &lib::formula::WorkspaceOrder) -> &'static str {
+        match order {
+            lib::formula::WorkspaceOrder::Create(_) => "Create",
+            lib::formula::WorkspaceOrder::Update(_) => "Update",
+            lib::formula::WorkspaceOrder::Clean(_) => "Clean",
+        }
+    }
+
+    /// Changes a dry run of the workspace would make.
+    #[component]
+    fn Preview(
+        preview: RwSignal<
+            Option<
+                Result<Vec<lib::audit::Entry>, Vec<(usize, lib::formula::error::WorkspaceOrder)>>,
+            >,
+        >,
+    ) -> impl IntoView {
+        let close = move |_: ev::MouseEvent| preview.set(None);
+        let close_on_escape = move |e: ev::KeyboardEvent| {
+            if e.key() == "Escape" {
+                e.prevent_default();
+                preview.set(None);
+            }
+        };
+
+        let content = move || {
+            preview.get().map(|result| match result {
+                Ok(entries) if entries.iter().all(|entry| entry.changes.is_empty()) => {
+                    view! { <div>"No changes."</div> }.into_any()
+                }
+                Ok(entries) => entries
+                    .into_iter()
+                    .map(|entry| {
+                        view! {
+                            <div class="pb">
+                                <h3 class="font-bold">
+                                    {entry.path.to_string_lossy().to_string()}
+                                </h3>
+                                <ul class="text-sm">
+                                    <history::Changes changes=entry.changes />
+                                </ul>
+                            </div>
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .into_any(),
+                Err(errors) => {
+                    let errors = errors
+                        .iter()
+                        .map(|(idx, err)| {
+                            view! { <li>{format!("Order {}: {}", idx + 1, message::order_error_message(err))}</li> }
+                        })
+                        .collect::<Vec<_>>();
+                    view! { <ul class="text-brand-red-600">{errors}</ul> }.into_any()
+                }
+            })
+        };
+
+        view! {
+            <div
+                class="text-left"
+                class:hidden=move || preview.read().is_none()
+                on:keydown=close_on_escape
+            >
+                <div class="fixed inset-0 z-30" on:mousedown=close></div>
+                <div class="fixed z-40 top-12 left-1/2 -translate-x-1/2 w-1/2 max-h-3/4 overflow-auto p-1 \
+                bg-white dark:bg-secondary-800 border border-secondary-100 dark:border-secondary-600">
+                    <h2 class="font-bold uppercase">"Preview"</h2>
+                    {content}
+                </div>
+            </div>
+        }
+    }
+
+    /// Re-run the workspace when one of its datasets changes on disk.
+    #[component]
+    fn AutoRun(run_workspace: Action<Vec<lib::formula::WorkspaceOrder>, ()>) -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let owner = expect_context::<state::WorkspaceOwner>();
+        let enabled = state.auto_run.enabled;
+        let modified = StoredValue::new(HashSet::<state::ResourceId>::new());
+        let debounce = StoredValue::new(None::<TimeoutHandle>);
+
+        let reprocess = {
+            let state = state.clone();
+            move || {
+                let datasets = modified.with_value(|modified| modified.clone());
+                modified.set_value(HashSet::new());
+                spawn_local(reprocess_datasets(
+                    state.clone(),
+                    owner.clone(),
+                    datasets,
+                    run_workspace,
+                ));
+            }
+        };
+
+        spawn_local_scoped({
+            let state = state.clone();
+            async move {
+                let mut events =
+                    match tauri_sys::event::listen::<<FilesModified as Event>::Payload>(
+                        FilesModified::NAME,
+                    )
+                    .await
+                    {
+                        Ok(events) => events,
+                        Err(err) => {
+                            tracing::error!(?err, "could not listen for modified files");
+                            return;
+                        }
+                    };
+
+                while let Some(event) = events.next().await {
+                    if !enabled.get_untracked() {
+                        continue;
+                    }
+
+                    let datasets = modified_datasets(&state, &event.payload);
+                    if datasets.is_empty() {
+                        continue;
+                    }
+
+                    modified.update_value(|modified| modified.extend(datasets));
+                    if let Some(handle) = debounce.get_value() {
+                        handle.clear();
+                    }
+                    let handle = set_timeout_with_handle(reprocess.clone(), AUTO_RUN_DEBOUNCE)
+                        .inspect_err(|err| tracing::error!(?err))
+                        .ok();
+                    debounce.set_value(handle);
+                }
+            }
+        });
+
+        view! {
+            <label
+                class="px-1"
+                title="Re-run the workspace when one of its datasets changes on disk."
+            >
+                <input
+                    type="checkbox"
+                    prop:checked=enabled
+                    on:change=move |e| enabled.set(event_target_checked(&e))
+                />
+                " Auto"
+            </label>
+        }
+    }
+
+    /// # Returns
+    /// Loaded datasets whose files are in `paths`.
+    /// Files recently written by a run are excluded.
+    fn modified_datasets(state: &state::State, paths: &[PathBuf]) -> Vec<state::ResourceId> {
+        let paths = paths
+            .iter()
+            .filter(|path| !state.auto_run.was_written(path))
+            .collect::<Vec<_>>();
+        if paths.is_empty() {
+            return vec![];
+        }
+
+        state
+            .datasets
+            .read_untracked()
+            .iter()
+            .filter_map(|dataset| {
+                let path = state.directory_tree.get_file_path(dataset.id())?;
+                let path = state.root_path().join(path);
+                paths
+                    .iter()
+                    .any(|modified| **modified == path)
+                    .then(|| dataset.id().clone())
+            })
+            .collect()
+    }
+
+    /// Reload datasets, re-evaluate their formulas, and run the workspace.
+    /// The workspace is not run if any dataset could not be reloaded or evaluated.
+    async fn reprocess_datasets(
+        state: state::State,
+        owner: state::WorkspaceOwner,
+        datasets: HashSet<state::ResourceId>,
+        run_workspace: Action<Vec<lib::formula::WorkspaceOrder>, ()>,
+    ) {
+        let mut failed = vec![];
+        for id in datasets {
+            let Some(path) = state.directory_tree.get_file_path(&id) else {
+                continue;
+            };
+            let options = state.import_options(&id);
+
+            match dataset::load_dataset(state.root_path().join(&path), options).await {
+                Ok(dataset) => {
+                    state.datasets.update(|datasets| {
+                        if let Some(current) = datasets.iter_mut().find(|ds| *ds.id() == id) {
+                            *current = state::Dataset::new(id.clone(), dataset);
+                        }
+                    });
+                    state.refresh_panes();
+
+                    let errors = formula::eval_dataset_formulas(
+                        id.clone(),
+                        state.formulas,
+                        state.datasets,
+                        owner.clone(),
+                        state
+                            .settings
+                            .with_untracked(|settings| settings.eval_options()),
+                    )
+                    .await;
+                    if errors > 0 {
+                        failed.push(format!(
+                            "{}: {errors} formula(s) could not be evaluated",
+                            path.to_string_lossy()
+                        ));
+                    }
+                }
+                Err(err) => failed.push(format!("{}: {err}", path.to_string_lossy())),
+            }
+        }
+
+        if !failed.is_empty() {
+            state.messages.push(message::Message::error_with_body(
+                "Automatic run skipped.",
+                failed.join("\n"),
+            ));
+            return;
+        }
+
+        match workspace_orders(&state) {
+            Ok(orders) => {
+                run_workspace.dispatch(orders);
+            }
+            Err(errors) => {
+                tracing::warn!(?errors);
+                state.messages.push(message::Message::error_with_body(
+                    "Automatic run skipped.",
+                    format!("{} formula(s) have errors.", errors.len()),
+                ));
+            }
+        }
+    }
+
+    /// Apply the active dataset's formulas to every file matching a pattern.
+    #[component]
+    fn Batch() -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let pattern_node = NodeRef::<html::Input>::new();
+        let disabled = {
+            let formulas = state.formulas.read_only();
+            let active = state.active_dataset.read_only();
+            move || formulas.read().is_empty() || active.read().is_none()
+        };
+
+        let run_batch = Action::new_local({
+            let state = state.clone();
+            move |batch: &lib::batch::Batch| {
+                let state = state.clone();
+                let batch = batch.clone();
+                async move {
+                    let msg = match batch_orders(state.root_path().clone(), batch).await {
+                        Ok(files) => run_batch_files(state.root_path().clone(), files).await,
+                        Err(err) => message::Message::error_with_body(
+                            "Could not run batch.",
+                            err.to_string(),
+                        ),
+                    };
+                    state.messages.push(msg);
+                }
+            }
+        });
+
+        let dispatch_batch = {
+            let state = state.clone();
+            move |e: ev::SubmitEvent| {
+                e.prevent_default();
+                let Some(input) = pattern_node.get_untracked() else {
+                    return;
+                };
+
+                let Some(template) = formula::template_from_active(&state, "batch".to_string())
+                else {
+                    return;
+                };
+                let active = state
+                    .active_dataset
+                    .with_untracked(|active| active.as_ref().cloned());
+                let options = state.datasets.with_untracked(|datasets| {
+                    datasets.iter().find_map(|dataset| match dataset {
+                        state::Dataset::Csv(csv) if Some(csv.id()) == active.as_ref() => {
+                            Some(csv.options().clone())
+                        }
+                        _ => None,
+                    })
+                });
+                let Some(options) = options else {
+                    state.messages.push(message::Message::error(
+                        "Batches can only be run from a csv file.",
+                    ));
+                    return;
+                };
+
+                run_batch.dispatch(lib::batch::Batch {
+                    pattern: input.value(),
+                    template,
+                    options,
+                    empty_cells: state
+                        .settings
+                        .with_untracked(|settings| settings.empty_cells),
+                    empty_comparison: state
+                        .settings
+                        .with_untracked(|settings| settings.empty_comparison),
+                    overflow: state.settings.with_untracked(|settings| settings.overflow),
+                });
+            }
+        };
+
+        view! {
+            <form class="flex gap-1 pt" on:submit=dispatch_batch>
+                <input
+                    node_ref=pattern_node
+                    type="text"
+                    class="input-compact grow min-w-0"
+                    placeholder="Folder or glob, e.g. data/**/*.csv"
+                    title="Apply the active dataset's formulas to every matching file."
+                    disabled=disabled
+                />
+                <button
+                    type="submit"
+                    class="btn"
+                    disabled=move || disabled() || run_batch.pending().get()
+                >
+                    "Batch"
+                </button>
+            </form>
+        }
+    }
+
+    /// Run the orders of a batch.
+    ///
+    /// # Returns
+    /// Message reporting the status of each file.
+    async fn run_batch_files(root: PathBuf, files: Vec<lib::batch::FileOrder>) -> message::Message {
+        if files.is_empty() {
+            return message::Message::error("No files match the pattern.");
+        }
+
+        let mut status = files
+            .iter()
+            .map(|file| file.order.as_ref().err().map(|err| err.to_string()))
+            .collect::<Vec<_>>();
+        let (indices, orders): (Vec<_>, Vec<_>) = files
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, file)| file.order.clone().ok().map(|order| (idx, order)))
+            .unzip();
+
+        if let Err(errors) = run_workspace(root, &orders, false, false, false).await {
+            for (order_idx, err) in errors {
+                status[indices[order_idx]] = Some(message::order_error_message(&err).to_string());
+            }
+        }
+
+        let failed = status.iter().filter(|status| status.is_some()).count();
+        message::notify_complete(
+            lib::settings::Operation::Run,
+            "Batch complete",
+            Some(format!(
+                "{} of {} file(s) updated.",
+                files.len() - failed,
+                files.len()
+            )),
+        );
+
+        if failed == 0 {
+            return message::Message::success(format!("Updated {} file(s).", files.len()));
+        }
+
+        let body = files
+            .iter()
+            .zip(status)
+            .map(|(file, status)| {
+                let status = status.unwrap_or("ok".to_string());
+                format!("{}: {status}", file.path.to_string_lossy())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        message::Message::warning_with_body(
+            format!("{failed} of {} file(s) failed.", files.len()),
+            body,
+        )
+    }
+
+    /// Freeze the current results of formulas, and check results against them.
+    #[component]
+    fn Snapshot() -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let disabled = {
+            let formulas = state.formulas.read_only();
+            move || formulas.read().is_empty()
+        };
+
+        let freeze = Action::new_local({
+            let state = state.clone();
+            move |_| {
+                let state = state.clone();
+                async move {
+                    let snapshot = lib::snapshot::Snapshot {
+                        cells: snapshot_cells(&state),
+                    };
+                    let count = snapshot.cells.len();
+                    let msg = match save_snapshot(state.root_path().clone(), snapshot).await {
+                        Ok(_) => message::Message::success(format!("Froze {count} result(s).")),
+                        Err(err) => message::Message::error_with_body(
+                            "Could not save snapshot.",
+                            err.to_string(),
+                        ),
+                    };
+                    state.messages.push(msg);
+                }
+            }
+        });
+
+        let check = Action::new_local({
+            let state = state.clone();
+            move |_| {
+                let state = state.clone();
+                async move {
+                    let msg = match load_snapshot(state.root_path().clone()).await {
+                        Ok(snapshot) => {
+                            let drift = snapshot.check(|cell| snapshot_cell_value(&state, cell));
+                            if drift.is_empty() {
+                                message::Message::success("Results match the snapshot.")
+                            } else {
+                                message::Message::warning_with_body(
+                                    format!("{} result(s) changed.", drift.len()),
+                                    drift_message(&drift),
+                                )
+                            }
+                        }
+                        Err(err) => message::Message::error_with_body(
+                            "Could not check results.",
+                            err.to_string(),
+                        ),
+                    };
+                    state.messages.push(msg);
+                }
+            }
+        });
+
+        let dispatch_freeze = move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+            freeze.dispatch(());
+        };
+
+        let dispatch_check = move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+            check.dispatch(());
+        };
+
+        view! {
+            <button
+                type="button"
+                class="btn"
+                title="Save the current formula results as the expected results."
+                on:mousedown=dispatch_freeze
+                disabled=move || disabled() || freeze.pending().get()
+            >
+                "Freeze"
+            </button>
+            <button
+                type="button"
+                class="btn"
+                title="Compare formula results to the expected results."
+                on:mousedown=dispatch_check
+                disabled=move || check.pending().get()
+            >
+                "Check"
+            </button>
+        }
+    }
+
+    /// # Returns
+    /// Current values of all formula cells.
+    /// Cells with errors are excluded.
+    fn snapshot_cells(state: &state::State) -> Vec<lib::snapshot::CellSnapshot> {
+        state
+            .formulas
+            .read_untracked()
+            .iter()
+            .filter_map(|formula| {
+                let (dataset_id, sheet_id, cell) =
+                    formula.domain.with_untracked(|domain| match domain {
+                        state::FormulaDomain::CsvCell { dataset, cell } => {
+                            (dataset.clone(), None, cell.clone())
+                        }
+                        state::FormulaDomain::WorkbookCell {
+                            dataset,
+                            sheet,
+                            cell,
+                        } => (dataset.clone(), Some(sheet.clone()), cell.clone()),
+                    });
+
+                let dataset = state
+                    .datasets
+                    .read_untracked()
+                    .iter()
+                    .find(|dataset| *dataset.id() == dataset_id)
+                    .cloned()?;
+                let sheet = match (&dataset, sheet_id) {
+                    (state::Dataset::Workbook(workbook), Some(sheet_id)) => {
+                        let idx = workbook
+                            .sheets
+                            .read_untracked()
+                            .iter()
+                            .position(|sheet| *sheet.id() == sheet_id)?;
+                        Some(core::data::IndexType::try_from(idx).ok()?)
+                    }
+                    _ => None,
+                };
+
+                let path = state.directory_tree.get_file_path(&dataset_id)?;
+                let snapshot = lib::snapshot::CellSnapshot {
+                    path,
+                    sheet,
+                    cell,
+                    value: core::expr::Value::Empty,
+                    tolerance: lib::snapshot::Tolerance::default(),
+                };
+                let value = dataset_cell_value(&dataset, &snapshot).ok()?;
+                Some(lib::snapshot::CellSnapshot { value, ..snapshot })
+            })
+            .collect()
+    }
+
+    /// # Returns
+    /// Current value of a snapshot cell.
+    /// `None` if its dataset is not loaded or the cell has an error.
+    fn snapshot_cell_value(
+        state: &state::State,
+        cell: &lib::snapshot::CellSnapshot,
+    ) -> Option<core::expr::Value> {
+        let file = state.directory_tree.get_file_by_path(&cell.path)?;
+        let dataset = state
+            .datasets
+            .read_untracked()
+            .iter()
+            .find(|dataset| dataset.id() == file.id())
+            .cloned()?;
+
+        dataset_cell_value(&dataset, cell).ok()
+    }
+
+    fn dataset_cell_value(
+        dataset: &state::Dataset,
+        cell: &lib::snapshot::CellSnapshot,
+    ) -> Result<core::expr::Value, core::expr::ContextError> {
+        use core::expr::Context;
+
+        let (row, col) = (cell.cell.row(), cell.cell.col());
+        let cell_ref = match cell.sheet {
+            None => core::data::CellRef::dynamic(row, col),
+            Some(sheet) => core::data::CellRef::dynamic_with_sheet(
+                row,
+                col,
+                core::data::SheetIndex::Index(sheet),
+            ),
+        };
+        let origin = core::data::CellPath {
+            sheet: cell.sheet.unwrap_or(0),
+            row,
+            col,
+        };
+
+        dataset.cell_value(&cell_ref, &origin)
+    }
+
+    fn drift_message(drift: &[lib::snapshot::Drift]) -> String {
+        drift
+            .iter()
+            .map(|drift| {
+                let actual = drift
+                    .actual
+                    .as_ref()
+                    .map(|actual| actual.to_string())
+                    .unwrap_or("not available".to_string());
+                format!(
+                    "{} {}: expected {}, found {actual}",
+                    drift.cell.path.to_string_lossy(),
+                    drift.cell.cell,
+                    drift.cell.value,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    async fn batch_orders(
+        root: PathBuf,
+        batch: lib::batch::Batch,
+    ) -> Result<Vec<lib::batch::FileOrder>, lib::batch::error::Files> {
+        crate::ipc::invoke(lib::ipc::BatchOrders { root, batch }).await
+    }
+
+    async fn save_snapshot(
+        root: PathBuf,
+        snapshot: lib::snapshot::Snapshot,
+    ) -> Result<(), lib::snapshot::error::Snapshot> {
+        crate::ipc::invoke(lib::ipc::SaveSnapshot { root, snapshot }).await
+    }
+
+    async fn load_snapshot(
+        root: PathBuf,
+    ) -> Result<lib::snapshot::Snapshot, lib::snapshot::error::Snapshot> {
+        crate::ipc::invoke(lib::ipc::LoadSnapshot { root }).await
+    }
+
     /// # Returns
     /// If an error occurs, returns a `Vec<(<order index>, <error>)>`.
+    /// # Arguments
+    /// + `dry_run`: Only evaluate the orders, without writing to files.
+    /// + `atomic`: Only modify files if every order succeeds.
+    /// + `manifest`: Write a manifest of the files written.
+    ///
+    /// # Returns
+    /// Changes made, or that would be made for a dry run, by each order.
+    /// If an error occurs, returns a `Vec<(<order index>, <error>)>`.
     async fn run_workspace<'a>(
+        root: PathBuf,
         orders: &'a Vec<lib::formula::WorkspaceOrder>,
-    ) -> Result<(), Vec<(usize, lib::formula::error::WorkspaceOrder)>> {
-        #[derive(serde::Serialize)]
-        struct Args<'a> {
-            orders: &'a Vec<lib::formula::WorkspaceOrder>,
+        dry_run: bool,
+        atomic: bool,
+        manifest: bool,
+    ) -> Result<Vec<lib::audit::Entry>, Vec<(usize, lib::formula::error::WorkspaceOrder)>> {
+        crate::ipc::invoke(lib::ipc::RunWorkspace {
+            root,
+            orders: orders.clone(),
+            dry_run,
+            atomic,
+            manifest,
+        })
+        .await
+    }
+
+    /// # Returns
+    /// Orders for all formulas, output files, pivot tables, joins, and subsets of the workspace.
+    /// Formulas of protected datasets do not create orders.
+    fn workspace_orders(
+        state: &state::State,
+    ) -> Result<Vec<lib::formula::WorkspaceOrder>, Vec<error::InvalidCellValue>> {
+        let mut orders = formulas_to_workspace_orders(
+            state.root_path().clone(),
+            state.formulas,
+            state.datasets,
+            state.directory_tree.clone(),
+            &state.protected_files.get_untracked(),
+            &state.written_values.get_untracked(),
+        )?;
+        orders.extend(state.output_files.read_untracked().iter().map(|file| {
+            explorer::output_file_order(state, file, state.root_path().clone()).into()
+        }));
+        orders.extend(state.pivot_tables.read_untracked().iter().map(|table| {
+            explorer::pivot_table_order(state, table, state.root_path().clone()).into()
+        }));
+        orders.extend(state.join_tables.read_untracked().iter().map(|table| {
+            explorer::join_table_order(state, table, state.root_path().clone()).into()
+        }));
+        for table in state.subset_tables.read_untracked().iter() {
+            match explorer::subset_table_order(state, table, state.root_path().clone()) {
+                Ok(order) => orders.push(order.into()),
+                Err(err) => state.messages.push(message::Message::error_with_body(
+                    format!("Subset {} was skipped.", table.file_name()),
+                    err.to_string(),
+                )),
+            }
         }
 
-        tauri_sys::core::invoke_result("run_workspace", Args { orders }).await
+        Ok(orders)
     }
 
+    /// Build the orders writing formula values that changed since they were last written.
+    /// Formulas of protected files are skipped.
     fn formulas_to_workspace_orders(
         root_path: PathBuf,
         formulas: state::Formulas,
         datasets: state::Datasets,
         directory_tree: state::DirectoryTree,
+        protected_files: &HashSet<state::ResourceId>,
+        written_values: &HashMap<state::ResourceId, WrittenValues>,
     ) -> Result<Vec<lib::formula::WorkspaceOrder>, Vec<error::InvalidCellValue>> {
-        let (orders, errors) = sort_formulas_by_dataset(formulas.get_untracked())
-            .into_iter()
-            .map(|(ds_id, formulas)| {
-                let dataset = datasets
-                    .read_untracked()
-                    .iter()
-                    .find(|ds| *ds.id() == ds_id)
-                    .expect("dataset should exist")
-                    .clone();
-
-                match dataset {
-                    state::Dataset::Csv(csv) => {
-                        let (formulas, errors) = formulas
-                            .into_iter()
-                            .map(|formula| dataset_csv_formula_to_workspace_update(formula, &csv))
-                            .partition::<Vec<_>, _>(|res| res.is_ok());
-
-                        if errors.is_empty() {
-                            let formulas = formulas
-                                .into_iter()
-                                .map(|formula| formula.unwrap())
-                                .collect::<Vec<_>>();
-
-                            let path = directory_tree
-                                .get_file_path(csv.file())
-                                .expect("dataset file path should exist");
-                            let path = root_path.join(path);
-
-                            Ok(lib::formula::WorkspaceOrder::Update(lib::formula::Update {
-                                path,
-                                updates: lib::formula::Updates::Csv(formulas),
-                            }))
-                        } else {
-                            let errors = errors
-                                .into_iter()
-                                .map(|err| err.unwrap_err())
-                                .collect::<Vec<_>>();
+        let mut files = vec![];
+        let mut errors = vec![];
+        for (ds_id, formulas) in sort_formulas_by_dataset(formulas.get_untracked()) {
+            if protected_files.contains(&ds_id) {
+                continue;
+            }
 
-                            Err(errors)
-                        }
-                    }
+            let dataset = datasets
+                .read_untracked()
+                .iter()
+                .find(|ds| *ds.id() == ds_id)
+                .expect("dataset should exist")
+                .clone();
+            let path = directory_tree
+                .get_file_path(&ds_id)
+                .expect("dataset file path should exist");
+            let written = written_values.get(&ds_id);
 
-                    state::Dataset::Workbook(workbook) => {
-                        todo!();
-                    }
+            let mut cells = Vec::with_capacity(formulas.len());
+            for formula in formulas {
+                match formula_cell(&formula, &dataset, written) {
+                    Ok(cell) => cells.push(cell),
+                    Err(err) => errors.push(err),
                 }
-            })
-            .partition::<Vec<_>, _>(|res| res.is_ok());
+            }
 
-        if errors.is_empty() {
-            let updates = orders
-                .into_iter()
-                .map(|order| order.unwrap())
-                .collect::<Vec<_>>();
+            let (options, workbook) = match &dataset {
+                state::Dataset::Csv(csv) => (csv.options().clone(), false),
+                state::Dataset::Workbook(_) => (lib::data::ImportOptions::default(), true),
+            };
+            files.push(lib::formula::FormulaFile {
+                path: root_path.join(path),
+                options,
+                workbook,
+                cells,
+            });
+        }
 
-            Ok(updates)
+        if errors.is_empty() {
+            Ok(lib::formula::build_updates(files))
         } else {
-            // TODO: Need to indicate the workbook each set of errors comes from.
-            let errors = errors
-                .into_iter()
-                .flat_map(|err| err.unwrap_err())
-                .collect::<Vec<_>>();
             Err(errors)
         }
     }
@@ -320,42 +1718,118 @@ mod run {
         wb_formulas
     }
 
-    fn dataset_csv_formula_to_workspace_update(
-        formula: state::Formula,
-        csv: &state::Csv,
-    ) -> Result<lib::formula::UpdateCsv, error::InvalidCellValue> {
-        formula.domain.with_untracked(|domain| match domain {
-            state::FormulaDomain::CsvCell {
-                dataset: ds_id,
-                cell,
-            } => {
-                assert_eq!(ds_id, csv.id());
-                let state::CellValue::Variable(value) = csv
-                    .sheet()
-                    .cells
+    /// Values written to the formula cells of a file, keyed by sheet index and cell.
+    type WrittenValues = BTreeMap<(core::data::IndexType, core::data::CellIndex), lib::data::Data>;
+
+    /// # Arguments
+    /// + `written`: Values last written to the dataset's file.
+    fn formula_cell(
+        formula: &state::Formula,
+        dataset: &state::Dataset,
+        written: Option<&WrittenValues>,
+    ) -> Result<lib::formula::FormulaCell, error::InvalidCellValue> {
+        let domain = formula.domain.get_untracked();
+        let (sheet, cells, cell) = match (domain, dataset) {
+            (state::FormulaDomain::CsvCell { cell, .. }, state::Dataset::Csv(csv)) => {
+                (0, csv.sheet().cells, cell)
+            }
+
+            (
+                state::FormulaDomain::WorkbookCell { sheet, cell, .. },
+                state::Dataset::Workbook(workbook),
+            ) => {
+                let Some((idx, cells)) = workbook
+                    .sheets
                     .read_untracked()
-                    .get(cell)
-                    .expect("cell should exist")
-                    .clone()
+                    .iter()
+                    .enumerate()
+                    .find_map(|(idx, ws)| (*ws.id() == sheet).then_some((idx, ws.cells)))
                 else {
-                    panic!("invalid cell value type");
-                };
-
-                let Ok(value) = value.get_untracked().unwrap() else {
-                    return Err(error::InvalidCellValue(cell.clone()));
+                    return Err(error::InvalidCellValue(cell));
                 };
+                (idx as core::data::IndexType, cells, cell)
+            }
 
-                Ok(lib::formula::UpdateCsv {
-                    row: cell.row(),
-                    col: cell.col(),
-                    value,
-                })
+            (state::FormulaDomain::CsvCell { cell, .. }, _)
+            | (state::FormulaDomain::WorkbookCell { cell, .. }, _) => {
+                return Err(error::InvalidCellValue(cell));
             }
+        };
 
-            state::FormulaDomain::WorkbookCell { .. } => unreachable!(),
+        let Some(state::CellValue::Variable(value)) =
+            cells.with_untracked(|cells| cells.get(&cell).cloned())
+        else {
+            return Err(error::InvalidCellValue(cell));
+        };
+
+        let value = value.get_untracked();
+        let provenance = value.provenance().cloned();
+        let Ok(value) = value.unwrap() else {
+            return Err(error::InvalidCellValue(cell));
+        };
+
+        let on_disk = written
+            .and_then(|written| written.get(&(sheet, cell.clone())))
+            .cloned()
+            .unwrap_or(lib::data::Data::Empty);
+
+        Ok(lib::formula::FormulaCell {
+            sheet,
+            cell,
+            value,
+            on_disk,
+            formula: formula.value.get_untracked(),
+            provenance,
         })
     }
 
+    /// Record the formula values written by orders,
+    /// so they are not written again unless they change.
+    fn record_written_values<'a>(
+        state: &state::State,
+        orders: impl IntoIterator<Item = &'a lib::formula::WorkspaceOrder>,
+    ) {
+        let root = state.root_path();
+        state.written_values.update(|written_values| {
+            for order in orders {
+                let lib::formula::WorkspaceOrder::Update(update) = order else {
+                    continue;
+                };
+                let Some(file) = update
+                    .path
+                    .strip_prefix(root)
+                    .ok()
+                    .and_then(|path| state.directory_tree.get_file_by_path(path))
+                else {
+                    continue;
+                };
+
+                let written = written_values.entry(file.id().clone()).or_default();
+                match &update.updates {
+                    lib::formula::Updates::Csv(updates) => {
+                        for update in updates {
+                            written.insert(
+                                (0, core::data::CellIndex::new(update.row, update.col)),
+                                update.value.clone(),
+                            );
+                        }
+                    }
+                    lib::formula::Updates::Workbook(updates) => {
+                        for update in updates {
+                            written.insert(
+                                (
+                                    update.sheet,
+                                    core::data::CellIndex::new(update.row, update.col),
+                                ),
+                                update.value.clone(),
+                            );
+                        }
+                    }
+                }
+            }
+        });
+    }
+
     pub mod error {
         use hermes_core as core;
 
@@ -0,0 +1,15 @@
+//! Minimal page hosting the formula [`Playground`](hermes_ui::Playground).
+//! Build with `trunk build playground.html`.
+use leptos::prelude::*;
+
+fn main() {
+    console_error_panic_hook::set_once();
+    mount_to_body(|| {
+        view! {
+            <main class="p-4">
+                <h1 class="pb-2 font-bold">"Try hermes formulas"</h1>
+                <hermes_ui::Playground />
+            </main>
+        }
+    });
+}
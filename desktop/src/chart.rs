@@ -0,0 +1,317 @@
+//! Plot ranges of the active dataset.
+//!
+//! The first column of a multi-column range holds the x values,
+//! each remaining column is plotted as a series.
+//! Single column ranges are plotted against their row.
+use crate::{formula, state};
+use hermes_core as core;
+use leptos::{ev, prelude::*};
+
+const WIDTH: f64 = 300.0;
+const HEIGHT: f64 = 180.0;
+
+/// Space around the plot area for axis labels.
+const PADDING: f64 = 28.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ChartKind {
+    Line,
+    Scatter,
+    Bar,
+}
+
+impl ChartKind {
+    const ALL: &[Self] = &[Self::Line, Self::Scatter, Self::Bar];
+
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Line => "Line",
+            Self::Scatter => "Scatter",
+            Self::Bar => "Bar",
+        }
+    }
+}
+
+/// Points of a plotted column.
+type Series = Vec<(f64, f64)>;
+
+#[component]
+pub fn Chart() -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let range = RwSignal::new(String::new());
+    let kind = RwSignal::new(ChartKind::Line);
+
+    let series = move || {
+        let reference = range.with(|range| core::expr::references(range).into_iter().next())?;
+        let (start, end) = reference.bounds();
+        let id = state.active_dataset.read().as_ref().cloned()?;
+        let values = state.datasets.with(|datasets| {
+            datasets
+                .iter()
+                .find(|dataset| *dataset.id() == id)
+                .map(|dataset| dataset.range_values(start, end))
+        })?;
+
+        Some(range_series(values, start.0))
+    };
+
+    let set_kind = move |e: ev::Event| {
+        let idx = event_target_value(&e).parse::<usize>().unwrap_or(0);
+        if let Some(value) = ChartKind::ALL.get(idx) {
+            kind.set(*value);
+        }
+    };
+
+    view! {
+        <div>
+            <div class="pb flex gap-2">
+                <h2 class="grow font-bold uppercase">"Chart"</h2>
+            </div>
+            <div class="flex gap-1">
+                <input
+                    class="input-compact grow min-w-0"
+                    placeholder="a1:b20"
+                    prop:value=range
+                    on:change=move |e| range.set(event_target_value(&e))
+                />
+                <select class="input-compact" on:change=set_kind>
+                    {ChartKind::ALL
+                        .iter()
+                        .enumerate()
+                        .map(|(idx, value)| {
+                            let value = *value;
+                            view! {
+                                <option value=idx.to_string() selected=move || kind.get() == value>
+                                    {value.label()}
+                                </option>
+                            }
+                        })
+                        .collect::<Vec<_>>()}
+                </select>
+            </div>
+            {move || match series() {
+                Some(series) if series.iter().any(|series| !series.is_empty()) => {
+                    view! { <Plot series kind=kind.get() /> }.into_any()
+                }
+                Some(_) => view! { <div>"No numeric data in range."</div> }.into_any(),
+                None => view! { <div>"Enter a range to plot."</div> }.into_any(),
+            }}
+        </div>
+    }
+}
+
+#[component]
+fn Plot(series: Vec<Series>, kind: ChartKind) -> impl IntoView {
+    let (x_min, x_max) = extent(series.iter().flatten().map(|(x, _)| *x));
+    let (mut y_min, mut y_max) = extent(series.iter().flatten().map(|(_, y)| *y));
+    if kind == ChartKind::Bar {
+        y_min = y_min.min(0.0);
+        y_max = y_max.max(0.0);
+    }
+
+    let x_axis = Axis::new(x_min, x_max, PADDING, WIDTH - PADDING);
+    let y_axis = Axis::new(y_min, y_max, HEIGHT - PADDING, PADDING);
+    let marks = match kind {
+        ChartKind::Line => line_marks(&series, &x_axis, &y_axis).into_any(),
+        ChartKind::Scatter => scatter_marks(&series, &x_axis, &y_axis).into_any(),
+        ChartKind::Bar => bar_marks(&series, &y_axis).into_any(),
+    };
+
+    view! {
+        <svg
+            class="w-full text-xs"
+            viewBox=format!("0 0 {WIDTH} {HEIGHT}")
+            preserveAspectRatio="xMidYMid meet"
+        >
+            <g stroke="currentColor" stroke-width="1">
+                <line
+                    x1=PADDING
+                    y1=HEIGHT - PADDING
+                    x2=WIDTH - PADDING
+                    y2=HEIGHT - PADDING
+                ></line>
+                <line x1=PADDING y1=PADDING x2=PADDING y2=HEIGHT - PADDING></line>
+            </g>
+            <g fill="currentColor" font-size="8">
+                <text x=2 y=PADDING>
+                    {format_tick(y_max)}
+                </text>
+                <text x=2 y=HEIGHT - PADDING>
+                    {format_tick(y_min)}
+                </text>
+                {(kind != ChartKind::Bar)
+                    .then(|| {
+                        view! {
+                            <text x=PADDING y=HEIGHT - PADDING / 2.0>
+                                {format_tick(x_min)}
+                            </text>
+                            <text x=WIDTH - PADDING y=HEIGHT - PADDING / 2.0 text-anchor="end">
+                                {format_tick(x_max)}
+                            </text>
+                        }
+                    })}
+            </g>
+            {marks}
+        </svg>
+    }
+}
+
+fn line_marks(series: &[Series], x_axis: &Axis, y_axis: &Axis) -> impl IntoView {
+    series
+        .iter()
+        .enumerate()
+        .map(|(idx, series)| {
+            let points = series
+                .iter()
+                .map(|(x, y)| format!("{},{}", x_axis.scale(*x), y_axis.scale(*y)))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            view! {
+                <polyline
+                    points=points
+                    fill="none"
+                    stroke=formula::reference_color(idx)
+                    stroke-width="1.5"
+                ></polyline>
+            }
+        })
+        .collect::<Vec<_>>()
+}
+
+fn scatter_marks(series: &[Series], x_axis: &Axis, y_axis: &Axis) -> impl IntoView {
+    series
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, series)| {
+            series.iter().map(move |(x, y)| {
+                view! {
+                    <circle
+                        cx=x_axis.scale(*x)
+                        cy=y_axis.scale(*y)
+                        r="2"
+                        fill=formula::reference_color(idx)
+                    ></circle>
+                }
+            })
+        })
+        .collect::<Vec<_>>()
+}
+
+/// Bars are placed by position in their series rather than by x value.
+fn bar_marks(series: &[Series], y_axis: &Axis) -> impl IntoView {
+    let points = series.iter().map(|series| series.len()).max().unwrap_or(0);
+    let band = (WIDTH - 2.0 * PADDING) / points.max(1) as f64;
+    let width = band / series.len().max(1) as f64;
+    let zero = y_axis.scale(0.0);
+
+    series
+        .iter()
+        .enumerate()
+        .flat_map(|(idx, series)| {
+            series.iter().enumerate().map(move |(position, (_, y))| {
+                let y = y_axis.scale(*y);
+                view! {
+                    <rect
+                        x=PADDING + band * position as f64 + width * idx as f64
+                        y=y.min(zero)
+                        width=width * 0.9
+                        height=(y - zero).abs()
+                        fill=formula::reference_color(idx)
+                    ></rect>
+                }
+            })
+        })
+        .collect::<Vec<_>>()
+}
+
+/// Maps data values onto plot coordinates.
+struct Axis {
+    min: f64,
+    span: f64,
+    start: f64,
+    end: f64,
+}
+
+impl Axis {
+    /// # Arguments
+    /// + `min`, `max`: Range of data values.
+    /// + `start`, `end`: Plot coordinates `min` and `max` map to.
+    fn new(min: f64, max: f64, start: f64, end: f64) -> Self {
+        let span = if max > min { max - min } else { 1.0 };
+        Self {
+            min,
+            span,
+            start,
+            end,
+        }
+    }
+
+    fn scale(&self, value: f64) -> f64 {
+        self.start + (value - self.min) / self.span * (self.end - self.start)
+    }
+}
+
+/// Convert range values into plotted series.
+/// Non-numeric values, e.g. headers, are skipped.
+///
+/// # Arguments
+/// + `values`: Values of the range by row.
+/// + `first_row`: Row index of the first row of the range.
+fn range_series(
+    values: Vec<Vec<Option<core::expr::Value>>>,
+    first_row: core::data::IndexType,
+) -> Vec<Series> {
+    let cols = values.first().map(|row| row.len()).unwrap_or(0);
+    if cols == 0 {
+        return vec![];
+    }
+
+    let row_x = |row: usize| (first_row as usize + row + 1) as f64;
+    if cols == 1 {
+        let series = values
+            .iter()
+            .enumerate()
+            .filter_map(|(row, values)| Some((row_x(row), number(values[0].as_ref())?)))
+            .collect();
+        return vec![series];
+    }
+
+    (1..cols)
+        .map(|col| {
+            values
+                .iter()
+                .enumerate()
+                .filter_map(|(row, values)| {
+                    let y = number(values[col].as_ref())?;
+                    let x = number(values[0].as_ref()).unwrap_or_else(|| row_x(row));
+                    Some((x, y))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn number(value: Option<&core::expr::Value>) -> Option<f64> {
+    match value? {
+        core::expr::Value::Int(value) => Some(*value as f64),
+        core::expr::Value::Float(value) if value.is_finite() => Some(*value),
+        _ => None,
+    }
+}
+
+/// # Returns
+/// `(min, max)` of the values.
+fn extent(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+        (min.min(value), max.max(value))
+    })
+}
+
+fn format_tick(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e9 {
+        format!("{value:.0}")
+    } else {
+        format!("{value:.2}")
+    }
+}
@@ -1,13 +1,24 @@
 //! Generic components.
-use leptos::prelude::*;
+use crate::types;
+use leptos::{ev, prelude::*};
 
 #[component]
 pub fn ResizablePane(
     #[prop(optional)] class: Option<String>,
     #[prop(optional, default = 200)] width: usize,
+    /// Fill the available width without a resize handle,
+    /// e.g. when shown in the compact layout.
+    #[prop(optional, into)]
+    compact: Signal<bool>,
     children: Children,
 ) -> impl IntoView {
-    let width = format!("{width}px");
+    let width = move || {
+        if compact.get() {
+            "100%".to_string()
+        } else {
+            format!("{width}px")
+        }
+    };
     let wrapper_class = if let Some(class) = class {
         format!("grow {class}")
     } else {
@@ -16,10 +27,72 @@ pub fn ResizablePane(
 
     view! {
         <div class="flex h-full">
-            <div class="h-full w-[2px] border-l-4 border-transparent hover:border-primary-600 cursor-ew-resize"></div>
+            <div
+                class="h-full w-[2px] border-l-4 border-transparent hover:border-primary-600 cursor-ew-resize"
+                class:hidden=compact
+            ></div>
             <div style:width=width class=wrapper_class>
                 {children()}
             </div>
         </div>
     }
 }
+
+/// Menu opened at a point, e.g. by right clicking.
+/// Clicking outside of the menu closes it.
+///
+/// # Arguments
+/// + `x`, `y`: Client coordinates of the menu's top left corner.
+/// + `on_close`: Called when the menu should close.
+#[component]
+pub fn ContextMenu<F>(x: i32, y: i32, on_close: F, children: Children) -> impl IntoView
+where
+    F: Fn() + Clone + 'static,
+{
+    let close = move |e: ev::MouseEvent| {
+        e.prevent_default();
+        on_close();
+    };
+
+    view! {
+        <div class="fixed inset-0 z-10" on:mousedown=close.clone() on:contextmenu=close></div>
+        <div
+            class="fixed z-20 p-1 font-normal normal-case bg-white dark:bg-secondary-800 \
+            border border-secondary-100 dark:border-secondary-600"
+            style:left=format!("{x}px")
+            style:top=format!("{y}px")
+        >
+            {children()}
+        </div>
+    }
+}
+
+/// Command in a [`ContextMenu`].
+/// `on_select` is only called for the primary mouse button.
+#[component]
+pub fn ContextMenuItem<F>(
+    on_select: F,
+    #[prop(optional, into)] disabled: Signal<bool>,
+    children: Children,
+) -> impl IntoView
+where
+    F: Fn() + 'static,
+{
+    let select = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary || disabled.get_untracked() {
+            return;
+        }
+        on_select();
+    };
+
+    view! {
+        <li
+            class="px-1 hover:bg-secondary-100 dark:hover:bg-secondary-700"
+            class:cursor-pointer=move || !disabled.get()
+            class:opacity-50=disabled
+            on:mousedown=select
+        >
+            {children()}
+        </li>
+    }
+}
@@ -1,13 +1,25 @@
-use crate::{formula, icon, state, state::FileResource, types};
+use crate::{
+    component::{self, ContextMenuItem},
+    explorer, formula, history, icon, message, state,
+    state::FileResource,
+    types,
+};
 use hermes_core as core;
 use hermes_desktop_lib as lib;
 use leptos::{
     either::{Either, EitherOf3, either},
-    ev,
+    ev, html,
     prelude::*,
+    task::spawn_local,
 };
 use leptos_icons::Icon;
-use std::{collections::btree_map::Values, path::PathBuf};
+use std::{
+    collections::{HashMap, btree_map::Values},
+    path::PathBuf,
+};
+
+/// Number of bins in column histograms.
+const HISTOGRAM_BINS: usize = 10;
 
 #[derive(Copy, Clone, derive_more::Deref)]
 struct ActiveDatasetId(Signal<Option<state::ResourceId>>);
@@ -25,42 +37,522 @@ impl ActiveSpreadsheetId {
     }
 }
 
+/// Differences between the active dataset on disk and in memory.
+/// `None` if no diff is shown.
+#[derive(Copy, Clone, derive_more::Deref)]
+struct DatasetDiff(RwSignal<Option<Vec<lib::diff::CellDiff>>>);
+impl DatasetDiff {
+    pub fn new() -> Self {
+        Self(RwSignal::new(None))
+    }
+}
+
+/// Cell the pane's canvas should scroll into view.
+#[derive(Copy, Clone, derive_more::Deref)]
+struct ScrollTarget(RwSignal<Option<core::data::CellIndex>>);
+impl ScrollTarget {
+    pub fn new() -> Self {
+        Self(RwSignal::new(None))
+    }
+}
+
+/// Row or column header of the canvas.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum Header {
+    Row(core::data::IndexType),
+    Col(core::data::IndexType),
+}
+
+impl Header {
+    /// # Returns
+    /// Selection of every cell in the rows or columns from `self` to `other`,
+    /// or `None` if the headers are not on the same axis.
+    fn span(
+        &self,
+        other: &Self,
+        rows: core::data::IndexType,
+        cols: core::data::IndexType,
+    ) -> Option<state::ActiveCell> {
+        let (start, end) = match (*self, *other) {
+            (Self::Row(a), Self::Row(b)) => ((a.min(b), 0), (a.max(b), cols.checked_sub(1)?)),
+            (Self::Col(a), Self::Col(b)) => ((0, a.min(b)), (rows.checked_sub(1)?, a.max(b))),
+            _ => return None,
+        };
+
+        Some(state::ActiveCell::Range {
+            start: start.into(),
+            end: end.into(),
+        })
+    }
+}
+
+/// Width of the row header column of the canvas, in pixels.
+const ROW_HEADER_WIDTH: i32 = 48;
+
+/// Default width of canvas columns, in pixels.
+const COL_WIDTH: i32 = 96;
+
+/// Narrowest a canvas column can be resized to, in pixels.
+const MIN_COL_WIDTH: i32 = 24;
+
+/// Space added around a column's content when fitting its width, in pixels.
+const COL_FIT_PADDING: i32 = 12;
+
+/// Height of canvas rows, in pixels.
+const ROW_HEIGHT: i32 = 24;
+
+/// Widths of the columns of the sheet shown in the canvas.
+#[derive(Copy, Clone)]
+struct ColumnWidths {
+    sheet: Memo<Option<(state::ResourceId, usize)>>,
+    widths: RwSignal<HashMap<(state::ResourceId, usize), HashMap<core::data::IndexType, i32>>>,
+}
+
+impl ColumnWidths {
+    /// # Returns
+    /// Width of the column, in pixels.
+    fn width(&self, col: core::data::IndexType) -> i32 {
+        self.sheet.with(|sheet| {
+            sheet
+                .as_ref()
+                .and_then(|sheet| {
+                    self.widths
+                        .with(|widths| widths.get(sheet)?.get(&col).copied())
+                })
+                .unwrap_or(COL_WIDTH)
+        })
+    }
+
+    /// # Returns
+    /// Total width of the columns before `col`, in pixels.
+    fn offset(&self, col: core::data::IndexType) -> i32 {
+        (0..col).map(|col| self.width(col)).sum()
+    }
+
+    fn set(&self, col: core::data::IndexType, width: i32) {
+        let Some(sheet) = self.sheet.get_untracked() else {
+            return;
+        };
+        self.widths.update(|widths| {
+            widths
+                .entry(sheet)
+                .or_default()
+                .insert(col, width.max(MIN_COL_WIDTH));
+        });
+    }
+}
+
+/// Column being resized by dragging its edge.
+#[derive(Clone, Copy)]
+struct ColumnResize {
+    col: core::data::IndexType,
+    start_x: i32,
+    start_width: i32,
+}
+
+/// Time a touch must be held on a cell to start selecting a range, in milliseconds.
+const LONG_PRESS_MS: u64 = 400;
+
+/// Distance a touch may move before it is treated as scrolling, in pixels.
+const TAP_SLOP: i32 = 8;
+
+/// Rows added to the canvas at a time.
+const GROW_ROWS: core::data::IndexType = 100;
+
+/// Columns added to the canvas at a time.
+const GROW_COLS: core::data::IndexType = 10;
+
+/// Date format used when converting to dates without a format.
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Number of rows shown when previewing extracted columns.
+const EXTRACT_PREVIEW_ROWS: usize = 10;
+
+/// Touch on a canvas cell, either a tap or, once held, a range selection.
+#[derive(Clone)]
+struct CellTouch {
+    /// Cell first touched.
+    anchor: core::data::CellIndex,
+    start_x: i32,
+    start_y: i32,
+
+    /// Touch was held long enough to select a range.
+    selecting: bool,
+}
+
+/// Context menu opened on a canvas cell.
+#[derive(Clone)]
+struct CellMenu {
+    cell: core::data::CellIndex,
+    x: i32,
+    y: i32,
+}
+
+/// Rows and columns of the canvas kept visible while scrolling.
+#[derive(Copy, Clone)]
+struct FrozenCells {
+    freeze: Signal<state::Freeze>,
+    widths: ColumnWidths,
+
+    /// Height of the canvas header, in pixels.
+    header_height: RwSignal<i32>,
+}
+
+impl FrozenCells {
+    fn is_frozen(&self, row: core::data::IndexType, col: core::data::IndexType) -> bool {
+        self.freeze
+            .with(|freeze| row < freeze.rows || col < freeze.cols)
+    }
+
+    /// # Returns
+    /// Sticky offset from the top of the canvas if the row is frozen.
+    fn top(&self, row: core::data::IndexType) -> Option<String> {
+        (row < self.freeze.with(|freeze| freeze.rows))
+            .then(|| format!("{}px", self.header_height.get() + row as i32 * ROW_HEIGHT))
+    }
+
+    /// # Returns
+    /// Sticky offset from the left of the canvas if the column is frozen.
+    fn left(&self, col: core::data::IndexType) -> Option<String> {
+        (col < self.freeze.with(|freeze| freeze.cols))
+            .then(|| format!("{}px", ROW_HEADER_WIDTH + self.widths.offset(col)))
+    }
+
+    /// # Returns
+    /// Inline style positioning a cell, empty if it is not frozen.
+    fn style(&self, row: core::data::IndexType, col: core::data::IndexType) -> String {
+        let top = self.top(row);
+        let left = self.left(col);
+        // Cells frozen in both directions stay above those frozen in one.
+        let z_index = match (&top, &left) {
+            (None, None) => return String::new(),
+            (Some(_), Some(_)) => 2,
+            _ => 1,
+        };
+
+        let mut style = format!("position: sticky; z-index: {z_index};");
+        if let Some(top) = top {
+            style.push_str(&format!("top: {top};"));
+        }
+        if let Some(left) = left {
+            style.push_str(&format!("left: {left};"));
+        }
+        style
+    }
+}
+
 #[component]
 pub fn Workspace() -> impl IntoView {
     let state = expect_context::<state::State>();
-    provide_context(ActiveDatasetId::from_active_dataset(
-        state.active_dataset.read_only(),
-    ));
+    let owner = expect_context::<state::WorkspaceOwner>();
+    let active = state.active_dataset.read_only();
+    Effect::watch(
+        move || active.read().as_ref().cloned(),
+        {
+            let selected_column = state.selected_column;
+            move |_, _, _| selected_column.set(None)
+        },
+        false,
+    );
+
+    let editor_dock = expect_context::<state::FormulaEditorVisibility>().dock;
+
+    let split = state.split;
+    let toggle_split = {
+        let active = state.active_dataset;
+        move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+
+            if split.read_untracked().is_some() {
+                split.set(None);
+            } else {
+                let dataset = active.with_untracked(|active| active.as_ref().cloned());
+                split.set(Some(owner.with(|| state::Pane::new(dataset))));
+            }
+        }
+    };
+
+    view! {
+        <div class="h-full flex flex-col">
+            <div class="flex justify-end px-1">
+                <button
+                    type="button"
+                    class="btn-cmd cursor-pointer"
+                    class=("bg-secondary-100", move || split.read().is_some())
+                    class=("dark:bg-secondary-700", move || split.read().is_some())
+                    title="View two datasets side by side"
+                    on:mousedown=toggle_split
+                >
+                    <Icon icon=icon::Split />
+                </button>
+            </div>
+            <div
+                class="grow min-h-0 flex"
+                class:flex-col=move || editor_dock.get() == state::EditorDock::Bottom
+            >
+                <div class="grow min-h-0 min-w-0 flex">
+                    <DatasetPane pane=state.pane() {..} class="grow basis-0 min-w-0" />
+                    {move || {
+                        split
+                            .get()
+                            .map(|pane| {
+                                view! {
+                                    <div class="grow basis-0 min-w-0 flex flex-col border-l border-secondary-200 dark:border-secondary-600">
+                                        <SplitPaneHeader pane />
+                                        <DatasetPane pane {..} class="grow min-h-0" />
+                                    </div>
+                                }
+                            })
+                    }}
+                </div>
+                <Show when=move || active.read().is_some()>
+                    <FormulaEditor />
+                </Show>
+            </div>
+        </div>
+    }
+}
+
+/// Select the dataset shown in a secondary pane.
+#[component]
+fn SplitPaneHeader(pane: state::Pane) -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let datasets = state.datasets.read_only();
+    let directory_tree = state.directory_tree.clone();
+    let options = move || {
+        datasets.with(|datasets| {
+            datasets
+                .iter()
+                .map(|dataset| {
+                    let id = dataset.id().clone();
+                    let label = directory_tree
+                        .get_file_path(&id)
+                        .map(|path| path.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    (id, label)
+                })
+                .collect::<Vec<_>>()
+        })
+    };
+
+    let select_dataset = {
+        let options = options.clone();
+        move |e: ev::Event| {
+            let idx = event_target_value(&e).parse::<usize>().ok();
+            match idx.and_then(|idx| options().into_iter().nth(idx)) {
+                Some((id, _)) => pane.dataset.update(|dataset| dataset.insert(id)),
+                None => pane.dataset.update(|dataset| dataset.take()),
+            }
+        }
+    };
+
+    let close = {
+        let split = state.split;
+        move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+
+            split.set(None);
+        }
+    };
+
+    view! {
+        <div class="flex gap-1 px-1 text-sm">
+            <select class="input-compact grow min-w-0" on:change=select_dataset>
+                <option value="">"Select a file"</option>
+                {move || {
+                    let active = pane.dataset.with(|dataset| dataset.as_ref().cloned());
+                    options()
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, (id, label))| {
+                            view! {
+                                <option value=idx.to_string() selected=active.as_ref() == Some(&id)>
+                                    {label}
+                                </option>
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                }}
+            </select>
+            <button type="button" class="cursor-pointer" title="Close pane" on:mousedown=close>
+                <Icon icon=icon::Close />
+            </button>
+        </div>
+    }
+}
+
+/// Jump to, and select, a cell or range of the pane's dataset.
+/// e.g. `BF300`, `Sheet2!C10`, `A1:B3`.
+#[component]
+fn NameBox() -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let pane = expect_context::<state::Pane>();
+    let messages = state.messages;
+    let datasets = state.datasets;
+    let scroll_target = expect_context::<ScrollTarget>();
+    let input_node = NodeRef::<html::Input>::new();
+
+    let label = move || {
+        pane.dataset.with(|dataset| {
+            let state::ActiveDataset::Some { active_cell, .. } = dataset else {
+                return String::new();
+            };
+            active_cell.with(|active_cell| match active_cell.bounds() {
+                None => String::new(),
+                Some((start, end)) if start == end => start.to_string(),
+                Some((start, end)) => format!("{start}:{end}"),
+            })
+        })
+    };
+
+    let submit = move |e: ev::SubmitEvent| {
+        e.prevent_default();
+        let Some(input) = input_node.get_untracked() else {
+            return;
+        };
+        let value = input.value();
+        let Some(range) = core::data::RangeRef::from_str(&value) else {
+            messages.push(message::Message::warning_with_body(
+                "Could not go to cell.",
+                format!("`{value}` is not a cell or range."),
+            ));
+            return;
+        };
+        let Some(id) = pane
+            .dataset
+            .with_untracked(|dataset| dataset.as_ref().cloned())
+        else {
+            return;
+        };
+
+        let (rows, cols) = (
+            pane.canvas.rows().get_untracked(),
+            pane.canvas.cols().get_untracked(),
+        );
+        if range.start.row() >= rows || range.start.col() >= cols {
+            messages.push(message::Message::warning_with_body(
+                "Could not go to cell.",
+                format!("`{value}` is outside of the canvas."),
+            ));
+            return;
+        }
+
+        if let core::data::SheetRef::Absolute(sheet) = &range.sheet {
+            let sheet_idx = datasets.with_untracked(|datasets| {
+                let dataset = datasets.iter().find(|dataset| *dataset.id() == id)?;
+                let idx = sheet_position(dataset, sheet)?;
+                if let state::Dataset::Workbook(workbook) = dataset {
+                    workbook.active_sheet.set(idx);
+                }
+                Some(idx)
+            });
+            if sheet_idx.is_none() {
+                messages.push(message::Message::warning_with_body(
+                    "Could not go to cell.",
+                    format!("Sheet `{sheet}` does not exist."),
+                ));
+                return;
+            }
+        }
+
+        scroll_target.set(Some(range.start.clone()));
+        pane.dataset.with_untracked(|dataset| {
+            if let state::ActiveDataset::Some { active_cell, .. } = dataset {
+                active_cell.set(range.into());
+            }
+        });
+    };
+
+    view! {
+        <form class="px-1" on:submit=submit>
+            <input
+                node_ref=input_node
+                class="input-compact w-32 text-sm"
+                placeholder="Go to cell"
+                title="Go to a cell or range, e.g. BF300, Sheet2!C10, A1:B3"
+                prop:value=label
+            />
+        </form>
+    }
+}
+
+/// # Returns
+/// Index of the sheet in the dataset, or `None` if it does not exist.
+/// Labels are matched case insensitively.
+fn sheet_position(dataset: &state::Dataset, sheet: &core::data::SheetIndex) -> Option<usize> {
+    match (dataset, sheet) {
+        (state::Dataset::Csv(_), core::data::SheetIndex::Index(0)) => Some(0),
+        (state::Dataset::Csv(_), _) => None,
+        (state::Dataset::Workbook(workbook), core::data::SheetIndex::Index(idx)) => {
+            let idx = *idx as usize;
+            (idx < workbook.sheets.read_untracked().len()).then_some(idx)
+        }
+        (state::Dataset::Workbook(workbook), core::data::SheetIndex::Label(label)) => {
+            workbook.sheets.read_untracked().iter().position(|sheet| {
+                sheet
+                    .name
+                    .with_untracked(|name| name.eq_ignore_ascii_case(label))
+            })
+        }
+    }
+}
+
+/// Dataset of a pane, drawn on the pane's canvas.
+#[component]
+fn DatasetPane(pane: state::Pane) -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let active_dataset = ActiveDatasetId::from_active_dataset(pane.dataset.read_only());
+    let diff = DatasetDiff::new();
+    provide_context(pane);
+    provide_context(active_dataset);
     provide_context(ActiveSpreadsheetId::new());
+    provide_context(ScrollTarget::new());
+    provide_context(diff);
+    Effect::watch(
+        move || active_dataset.get(),
+        move |_, _, _| diff.set(None),
+        false,
+    );
 
-    let active = state.active_dataset.read_only();
+    let active = pane.dataset.read_only();
     let datasets = state.datasets.read_only();
-    let mut canvas = state.canvas.clone();
+    let canvas = pane.canvas;
     view! {
-        <div class="h-full flex flex-col">
+        <div class="flex flex-col min-h-0">
             <NoActiveFile {..} class:hidden=move || active.read().is_some() />
+            <Show when=move || active.read().is_some()>
+                <NameBox />
+            </Show>
             <Canvas class="grow" class:hidden=move || active.read().is_none() />
             {move || {
                 active
                     .with(|active| {
-                        if let state::ActiveDataset::Some { id, .. } = active {
-                            let dataset = datasets
-                                .read_untracked()
-                                .iter()
-                                .find(|dataset| dataset.file() == id)
-                                .expect("workbook to exist")
-                                .clone();
-                            Some(
-                                either!(
-                                    dataset,
-                                    state::Dataset::Csv(csv) => view! { <Csv csv /> },
-                                    state::Dataset::Workbook(workbook) => view! { <Workbook workbook /> },
-                                ),
-                            )
-                        } else {
+                        let state::ActiveDataset::Some { id, .. } = active else {
                             canvas.cells().clear();
-                            None
-                        }
+                            return None;
+                        };
+                        // The dataset may have been unloaded while shown in a secondary pane.
+                        let Some(dataset) = datasets
+                            .read_untracked()
+                            .iter()
+                            .find(|dataset| dataset.file() == id)
+                            .cloned()
+                        else {
+                            canvas.cells().clear();
+                            return None;
+                        };
+
+                        Some(
+                            either!(
+                                dataset,
+                                state::Dataset::Csv(csv) => view! { <Csv csv /> },
+                                state::Dataset::Workbook(workbook) => view! { <Workbook workbook /> },
+                            ),
+                        )
                     })
             }}
         </div>
@@ -74,10 +566,35 @@ fn NoActiveFile() -> impl IntoView {
 
 #[component]
 fn Canvas(#[prop(optional, into)] class: Option<String>) -> impl IntoView {
-    const WRAPPER_CLASS: &'static str = "overflow-auto scrollbar-thin";
+    const WRAPPER_CLASS: &'static str =
+        "overflow-auto scrollbar-thin touch-manipulation overscroll-contain";
 
     let state = expect_context::<state::State>();
-    let canvas = state.canvas;
+    let pane = expect_context::<state::Pane>();
+    let canvas = pane.canvas;
+    let column_metadata = {
+        let active = pane.dataset.read_only();
+        let datasets = state.datasets;
+        Memo::new(move |_| {
+            active.with(|active| {
+                let state::ActiveDataset::Some { id, .. } = active else {
+                    return lib::data::ColumnMetadataMap::new();
+                };
+
+                datasets.with(|datasets| {
+                    datasets
+                        .iter()
+                        .find_map(|dataset| match dataset {
+                            state::Dataset::Csv(csv) if csv.id() == id => {
+                                Some(csv.columns().clone())
+                            }
+                            _ => None,
+                        })
+                        .unwrap_or_default()
+                })
+            })
+        })
+    };
 
     let wrapper_class = if let Some(class) = class {
         format!("{class} {WRAPPER_CLASS}")
@@ -85,21 +602,390 @@ fn Canvas(#[prop(optional, into)] class: Option<String>) -> impl IntoView {
         WRAPPER_CLASS.to_string()
     };
 
+    let sheet = {
+        let datasets = state.datasets;
+        Memo::new(move |_| pane_sheet(pane, &datasets))
+    };
+    let freeze = {
+        let frozen = state.frozen;
+        Signal::derive(move || {
+            sheet
+                .get()
+                .and_then(|sheet| frozen.with(|frozen| frozen.get(&sheet).copied()))
+                .unwrap_or_default()
+        })
+    };
+    let widths = ColumnWidths {
+        sheet,
+        widths: state.column_widths,
+    };
+    let frozen = FrozenCells {
+        freeze,
+        widths,
+        header_height: RwSignal::new(0),
+    };
+    provide_context(frozen);
+
+    let resizing = RwSignal::new(None::<ColumnResize>);
+    let resize_move = window_event_listener(ev::mousemove, move |e| {
+        if let Some(resize) = resizing.get_untracked() {
+            widths.set(
+                resize.col,
+                resize.start_width + e.client_x() - resize.start_x,
+            );
+        }
+    });
+    let resize_end = window_event_listener(ev::mouseup, move |_| {
+        if resizing.get_untracked().is_some() {
+            resizing.set(None);
+        }
+    });
+    on_cleanup(move || {
+        resize_move.remove();
+        resize_end.remove();
+    });
+
+    let table_node = NodeRef::<html::Table>::new();
+    let thead_node = NodeRef::<html::Thead>::new();
+    Effect::new(move || {
+        column_metadata.track();
+        if let Some(thead) = thead_node.get() {
+            frozen.header_height.set(thead.offset_height());
+        }
+    });
+
+    let scroll_target = expect_context::<ScrollTarget>();
+    Effect::new(move || {
+        let Some(target) = scroll_target.get() else {
+            return;
+        };
+        let Some(table) = table_node.get() else {
+            return;
+        };
+
+        if let Some(cell) = cell_element(&table, target.row(), target.col()) {
+            cell.scroll_into_view();
+        }
+        scroll_target.set(None);
+    });
+
+    let header_anchor = StoredValue::new(None::<Header>);
+    let select_header = move |header: Header, extend: bool| {
+        let state::ActiveDataset::Some { active_cell, .. } = pane.dataset.get_untracked() else {
+            return;
+        };
+        let (rows, cols) = (canvas.rows().get_untracked(), canvas.cols().get_untracked());
+        let anchor = header_anchor
+            .get_value()
+            .filter(|anchor| extend && anchor.span(&header, rows, cols).is_some())
+            .unwrap_or(header);
+
+        header_anchor.set_value(Some(anchor));
+        if let Some(selection) = anchor.span(&header, rows, cols) {
+            active_cell.set(selection);
+        }
+    };
+
+    let touch = StoredValue::new(None::<CellTouch>);
+    let long_press = StoredValue::new(None::<TimeoutHandle>);
+    let cancel_long_press = move || {
+        if let Some(handle) = long_press.get_value() {
+            handle.clear();
+            long_press.set_value(None);
+        }
+    };
+    let select_cells = move |selection: state::ActiveCell| {
+        if let state::ActiveDataset::Some { active_cell, .. } = pane.dataset.get_untracked() {
+            active_cell.set(selection);
+        }
+    };
+    let touch_start = move |e: ev::TouchEvent| {
+        cancel_long_press();
+        touch.set_value(None);
+        let touches = e.touches();
+        if touches.length() != 1 {
+            return;
+        }
+        let Some(point) = touches.get(0) else {
+            return;
+        };
+        let Some(anchor) = cell_at_point(point.client_x(), point.client_y()) else {
+            return;
+        };
+
+        touch.set_value(Some(CellTouch {
+            anchor,
+            start_x: point.client_x(),
+            start_y: point.client_y(),
+            selecting: false,
+        }));
+        let handle = set_timeout_with_handle(
+            move || {
+                long_press.set_value(None);
+                touch.update_value(|touch| {
+                    if let Some(touch) = touch {
+                        touch.selecting = true;
+                        select_cells(state::ActiveCell::Some(touch.anchor.clone()));
+                    }
+                });
+            },
+            std::time::Duration::from_millis(LONG_PRESS_MS),
+        );
+        long_press.set_value(handle.ok());
+    };
+    let touch_move = move |e: ev::TouchEvent| {
+        let Some(current) = touch.get_value() else {
+            return;
+        };
+        let Some(point) = e.touches().get(0) else {
+            return;
+        };
+
+        if current.selecting {
+            e.prevent_default();
+            if let Some(cell) = cell_at_point(point.client_x(), point.client_y()) {
+                select_cells(cell_span(&current.anchor, &cell));
+            }
+        } else if (point.client_x() - current.start_x).abs() > TAP_SLOP
+            || (point.client_y() - current.start_y).abs() > TAP_SLOP
+        {
+            // Scrolling.
+            cancel_long_press();
+            touch.set_value(None);
+        }
+    };
+    let touch_end = move |_: ev::TouchEvent| {
+        cancel_long_press();
+        if let Some(current) = touch.get_value() {
+            if !current.selecting {
+                select_cells(state::ActiveCell::Some(current.anchor));
+            }
+        }
+        touch.set_value(None);
+    };
+    let touch_cancel = move |_: ev::TouchEvent| {
+        cancel_long_press();
+        touch.set_value(None);
+    };
+    on_cleanup(cancel_long_press);
+
+    let navigate = move |e: ev::KeyboardEvent| {
+        let (d_row, d_col) = match e.key().as_str() {
+            "ArrowUp" => (-1, 0),
+            "ArrowDown" => (1, 0),
+            "ArrowLeft" => (0, -1),
+            "ArrowRight" => (0, 1),
+            _ => return,
+        };
+        if is_editable(&e) {
+            return;
+        }
+        let state::ActiveDataset::Some { active_cell, .. } = pane.dataset.get_untracked() else {
+            return;
+        };
+        let Some(current) = active_cell.with_untracked(|cell| match cell {
+            state::ActiveCell::None => None,
+            state::ActiveCell::Some(cell) => Some(cell.clone()),
+            state::ActiveCell::Range { end, .. } => Some(end.clone()),
+        }) else {
+            return;
+        };
+        e.prevent_default();
+
+        let step = |idx: core::data::IndexType, delta: i64| {
+            core::data::IndexType::try_from(i64::from(idx) + delta).ok()
+        };
+        let (Some(row), Some(col)) = (step(current.row(), d_row), step(current.col(), d_col))
+        else {
+            return;
+        };
+
+        // Moving past the edge of the canvas extends it.
+        canvas.grow_to(row.saturating_add(1), col.saturating_add(1));
+        let cell = core::data::CellIndex::new(row, col);
+        active_cell.set(state::ActiveCell::Some(cell.clone()));
+        scroll_target.set(Some(cell));
+    };
+
+    let cell_menu = RwSignal::new(None::<CellMenu>);
+    let open_cell_menu = move |e: ev::MouseEvent| {
+        let Some(cell) = cell_at_point(e.client_x(), e.client_y()) else {
+            return;
+        };
+        let state::ActiveDataset::Some { active_cell, .. } = pane.dataset.get_untracked() else {
+            return;
+        };
+        e.prevent_default();
+
+        // Commands apply to the selection, so keep it if the cell is part of it.
+        let selected = active_cell.with_untracked(|active| {
+            active.bounds().is_some_and(|(start, end)| {
+                (start.row()..=end.row()).contains(&cell.row())
+                    && (start.col()..=end.col()).contains(&cell.col())
+            })
+        });
+        if !selected {
+            active_cell.set(state::ActiveCell::Some(cell.clone()));
+        }
+
+        cell_menu.set(Some(CellMenu {
+            cell,
+            x: e.client_x(),
+            y: e.client_y(),
+        }));
+    };
+
+    let add_rows = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+
+        canvas.add_rows(GROW_ROWS);
+    };
+
+    let add_cols = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+
+        canvas.add_cols(GROW_COLS);
+    };
+
+    let selected_column = state.selected_column;
+    let reference_style = state.reference_style;
+    let toggle_reference_style = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+
+        reference_style.update(|style| {
+            *style = match style {
+                core::data::ReferenceStyle::A1 => core::data::ReferenceStyle::R1C1,
+                core::data::ReferenceStyle::R1C1 => core::data::ReferenceStyle::A1,
+            };
+        });
+    };
+
     view! {
-        <div class=wrapper_class>
-            <table class="table-fixed">
-                <thead class="bg-white dark:bg-secondary-800 sticky top-0">
+        <div class=wrapper_class tabindex="0" on:keydown=navigate>
+            <div class="relative">
+            <table
+                class="table-fixed [&_td]:truncate"
+                style:width=move || {
+                    format!("{}px", ROW_HEADER_WIDTH + widths.offset(canvas.cols().get()))
+                }
+                node_ref=table_node
+            >
+                <colgroup>
+                    <col style:width=format!("{ROW_HEADER_WIDTH}px") />
+                    {
+                        let cols = canvas.cols();
+                        move || {
+                            (0..cols.get())
+                                .map(|col| {
+                                    view! { <col style:width=move || format!("{}px", widths.width(col)) /> }
+                                })
+                                .collect::<Vec<_>>()
+                        }
+                    }
+                </colgroup>
+                <thead class="bg-white dark:bg-secondary-800 sticky top-0 z-20" node_ref=thead_node>
                     <tr>
-                        <th></th>
+                        <th
+                            class="sticky left-0 cursor-pointer text-xs font-normal bg-white dark:bg-secondary-800"
+                            title="Toggle between A1 and R1C1 reference styles"
+                            on:mousedown=toggle_reference_style
+                        >
+                            {move || match reference_style.get() {
+                                core::data::ReferenceStyle::A1 => "A1",
+                                core::data::ReferenceStyle::R1C1 => "R1C1",
+                            }}
+                        </th>
                         {
                             let cols = canvas.cols();
                             move || {
                                 (0..cols.get())
                                     .into_iter()
                                     .map(|idx| {
+                                        let metadata = move || {
+                                            column_metadata
+                                                .with(|columns| columns.get(&idx).cloned())
+                                        };
+                                        let select_column = move |e: ev::MouseEvent| {
+                                            if e.button() != types::MouseButton::Primary {
+                                                return;
+                                            }
+                                            if e.shift_key() {
+                                                e.prevent_default();
+                                            }
+                                            select_header(Header::Col(idx), e.shift_key());
+                                            if !pane.primary || e.shift_key() {
+                                                return;
+                                            }
+
+                                            selected_column.update(|selected| {
+                                                if *selected == Some(idx) {
+                                                    *selected = None;
+                                                } else {
+                                                    *selected = Some(idx);
+                                                }
+                                            });
+                                        };
+                                        let start_resize = move |e: ev::MouseEvent| {
+                                            if e.button() != types::MouseButton::Primary {
+                                                return;
+                                            }
+                                            e.stop_propagation();
+                                            e.prevent_default();
+                                            resizing.set(Some(ColumnResize {
+                                                col: idx,
+                                                start_x: e.client_x(),
+                                                start_width: untrack(|| widths.width(idx)),
+                                            }));
+                                        };
+                                        let fit_width = move |e: ev::MouseEvent| {
+                                            e.stop_propagation();
+                                            let Some(table) = table_node.get_untracked() else {
+                                                return;
+                                            };
+                                            if let Some(width) =
+                                                content_width(&table, canvas.rows().get_untracked(), idx)
+                                            {
+                                                widths.set(idx, width + COL_FIT_PADDING);
+                                            }
+                                        };
                                         view! {
-                                            <th class="cursor-pointer">
-                                                {core::utils::index_to_col(idx)}
+                                            <th
+                                                class="sticky cursor-pointer bg-white dark:bg-secondary-800"
+                                                class=(
+                                                    ["bg-secondary-100", "dark:bg-secondary-700"],
+                                                    move || pane.primary && selected_column.get() == Some(idx),
+                                                )
+                                                data-col=idx
+                                                style:left=move || frozen.left(idx).unwrap_or_default()
+                                                on:mousedown=select_column
+                                                title=move || {
+                                                    metadata().map(|metadata| column_title(&metadata))
+                                                }
+                                            >
+                                                <div>
+                                                    {move || reference_style.with(|style| style.col_label(idx))}
+                                                </div>
+                                                {move || {
+                                                    metadata()
+                                                        .and_then(|metadata| metadata.units)
+                                                        .map(|units| {
+                                                            view! {
+                                                                <div class="text-xs font-normal">{units}</div>
+                                                            }
+                                                        })
+                                                }}
+                                                <div
+                                                    class="absolute top-0 right-0 h-full w-1 cursor-col-resize hover:bg-primary-400"
+                                                    title="Drag to resize, double-click to fit content"
+                                                    on:mousedown=start_resize
+                                                    on:dblclick=fit_width
+                                                ></div>
                                             </th>
                                         }
                                     })
@@ -108,27 +994,37 @@ fn Canvas(#[prop(optional, into)] class: Option<String>) -> impl IntoView {
                         }
                     </tr>
                 </thead>
-                <tbody>
+                <tbody
+                    on:contextmenu=open_cell_menu
+                    on:touchstart=touch_start
+                    on:touchmove=touch_move
+                    on:touchend=touch_end
+                    on:touchcancel=touch_cancel
+                >
                     {
-                        let cells = canvas.cells();
                         let rows = canvas.rows();
                         let cols = canvas.cols();
                         move || {
                             view! {
                                 <For each=move || 0..rows.get() key=|row| *row let:row_idx>
-                                    <tr>
-                                        <th class="sticky left-0 cursor-pointer bg-white dark:bg-secondary-800">
+                                    <tr style:height=format!("{ROW_HEIGHT}px")>
+                                        <th
+                                            class="sticky left-0 z-10 cursor-pointer bg-white dark:bg-secondary-800"
+                                            style:top=move || frozen.top(row_idx).unwrap_or_default()
+                                            on:mousedown=move |e: ev::MouseEvent| {
+                                                if e.button() != types::MouseButton::Primary {
+                                                    return;
+                                                }
+                                                if e.shift_key() {
+                                                    e.prevent_default();
+                                                }
+                                                select_header(Header::Row(row_idx), e.shift_key());
+                                            }
+                                        >
                                             {core::utils::index_to_row(row_idx)}
                                         </th>
                                         <For each=move || 0..cols.get() key=|col| *col let:col_idx>
-                                            {
-                                                let idx: core::data::CellIndex = (row_idx, col_idx).into();
-                                                let cell = cells
-                                                    .get_cell(&idx)
-                                                    .expect("cell to exist")
-                                                    .read_only();
-                                                view! { <CanvasCellValue idx cell /> }
-                                            }
+                                            <CanvasCell idx=(row_idx, col_idx).into() />
                                         </For>
                                     </tr>
                                 </For>
@@ -137,17 +1033,576 @@ fn Canvas(#[prop(optional, into)] class: Option<String>) -> impl IntoView {
                     }
                 </tbody>
             </table>
+            <ReferenceOverlay table=table_node />
+            <DiffOverlay table=table_node />
+            <SelectionOverlay table=table_node />
+            </div>
+            <div class="flex gap-2 p-1" class:hidden=move || pane.dataset.read().is_none()>
+                <button type="button" class="btn-cmd cursor-pointer" on:mousedown=add_rows>
+                    {format!("Add {GROW_ROWS} rows")}
+                </button>
+                <button type="button" class="btn-cmd cursor-pointer" on:mousedown=add_cols>
+                    {format!("Add {GROW_COLS} columns")}
+                </button>
+            </div>
+            {move || {
+                cell_menu
+                    .get()
+                    .map(|menu| view! { <CellContextMenu menu on_close=move || cell_menu.set(None) /> })
+            }}
         </div>
     }
 }
 
+/// Commands for the selected canvas cells.
+#[component]
+fn CellContextMenu<F>(menu: CellMenu, on_close: F) -> impl IntoView
+where
+    F: Fn() + Clone + Send + Sync + 'static,
+{
+    let state = expect_context::<state::State>();
+    let owner = expect_context::<state::WorkspaceOwner>();
+    let pane = expect_context::<state::Pane>();
+    let active_dataset = expect_context::<ActiveDatasetId>();
+    let active_sheet = expect_context::<ActiveSpreadsheetId>();
+    let editor_vis = expect_context::<state::FormulaEditorVisibility>();
+    let CellMenu { cell, x, y } = menu;
+    let cells = pane.canvas.cells();
+
+    let (start, end) = match pane.dataset.get_untracked() {
+        state::ActiveDataset::Some { active_cell, .. } => active_cell.with_untracked(|active| {
+            active
+                .bounds()
+                .map(|(start, end)| (start.clone(), end.clone()))
+        }),
+        state::ActiveDataset::None => None,
+    }
+    .unwrap_or_else(|| (cell.clone(), cell.clone()));
+
+    let domain = cell_domain(cell.clone(), &state.datasets, active_dataset, active_sheet);
+    let cell_formula = domain
+        .as_ref()
+        .and_then(|domain| state.formulas.get_by_containing_domain(domain));
+    let selected_formulas = domain
+        .as_ref()
+        .map(|domain| {
+            state.formulas.with_untracked(|formulas| {
+                formulas
+                    .iter()
+                    .filter(|formula| {
+                        formula
+                            .domain
+                            .with_untracked(|f_domain| in_selection(f_domain, domain, &start, &end))
+                    })
+                    .cloned()
+                    .collect::<Vec<_>>()
+            })
+        })
+        .unwrap_or_default();
+
+    // Formulas can only be set on cells without fixed data.
+    let is_variable = match cells.get_cell(&cell) {
+        Some(value) => value.with_untracked(|value| {
+            matches!(
+                value,
+                state::CanvasCellValue::Set(state::CellValue::Variable(_))
+            )
+        }),
+        None => cells.default().get_untracked() == state::CanvasCellDefault::Empty,
+    };
+
+    let copy = {
+        let on_close = on_close.clone();
+        let settings = state.settings;
+        let messages = state.messages;
+        move || {
+            on_close();
+            let decimal = settings.with_untracked(|settings| settings.decimal);
+            let text = (start.row()..=end.row())
+                .map(|row| {
+                    (start.col()..=end.col())
+                        .map(|col| canvas_cell_text(&cells, &(row, col).into(), decimal))
+                        .collect::<Vec<_>>()
+                        .join("\t")
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            spawn_local(async move {
+                if let Err(err) = copy_to_clipboard(&text).await {
+                    tracing::error!(?err);
+                    messages.push(message::Message::error("Could not copy to the clipboard."));
+                }
+            });
+        }
+    };
+
+    let clear = {
+        let on_close = on_close.clone();
+        let state = state.clone();
+        let owner = owner.clone();
+        let selected_formulas = selected_formulas.clone();
+        move || {
+            on_close();
+            let active_cleared = state.active_formula.with_untracked(|active| {
+                active.as_ref().is_some_and(|active| {
+                    selected_formulas
+                        .iter()
+                        .any(|formula| formula.id() == active)
+                })
+            });
+            if active_cleared {
+                editor_vis.set(false);
+                state.active_formula.set(None);
+            }
+
+            let options = state
+                .settings
+                .with_untracked(|settings| settings.eval_options());
+            let errors = formula::clear_formulas(
+                &selected_formulas,
+                state.formulas,
+                &state.datasets,
+                &owner,
+                options,
+            );
+            if errors > 0 {
+                tracing::warn!("{errors} formula(s) could not be synced");
+            }
+        }
+    };
+
+    let edit_formula = {
+        let on_close = on_close.clone();
+        let state = state.clone();
+        let domain = domain.clone();
+        move || {
+            let Some(domain) = domain.clone() else {
+                return;
+            };
+
+            on_close();
+            formula::open_formula(
+                domain,
+                state.formulas,
+                state.active_formula,
+                &owner,
+                editor_vis,
+            );
+        }
+    };
+
+    let format_formula = {
+        let on_close = on_close.clone();
+        let formula = cell_formula.clone();
+        move || {
+            let Some(formula) = formula.as_ref() else {
+                return;
+            };
+
+            on_close();
+            formula
+                .value
+                .update(|value| *value = core::expr::normalize(&*value));
+        }
+    };
+
+    let has_formula = cell_formula.is_some();
+    let is_protected = domain
+        .as_ref()
+        .is_some_and(|domain| state.is_protected(domain.dataset()));
+    let can_clear = !is_protected && !selected_formulas.is_empty();
+    let can_edit = !is_protected && domain.is_some() && (has_formula || is_variable);
+    let can_format = !is_protected && has_formula;
+    view! {
+        <component::ContextMenu x y on_close>
+            <ul>
+                <ContextMenuItem on_select=copy>"Copy"</ContextMenuItem>
+                <ContextMenuItem on_select=clear disabled=!can_clear>
+                    "Clear"
+                </ContextMenuItem>
+                <ContextMenuItem on_select=edit_formula disabled=!can_edit>
+                    {if has_formula { "Edit formula" } else { "Add formula" }}
+                </ContextMenuItem>
+                <ContextMenuItem on_select=format_formula disabled=!can_format>
+                    "Format formula"
+                </ContextMenuItem>
+            </ul>
+        </component::ContextMenu>
+    }
+}
+
+/// # Returns
+/// Whether `domain` is a cell within the selection from `start` to `end`,
+/// in the dataset and sheet of `target`.
+fn in_selection(
+    domain: &state::FormulaDomain,
+    target: &state::FormulaDomain,
+    start: &core::data::CellIndex,
+    end: &core::data::CellIndex,
+) -> bool {
+    let cell = match (domain, target) {
+        (
+            state::FormulaDomain::CsvCell { dataset, cell },
+            state::FormulaDomain::CsvCell {
+                dataset: target, ..
+            },
+        ) if dataset == target => cell,
+
+        (
+            state::FormulaDomain::WorkbookCell {
+                dataset,
+                sheet,
+                cell,
+            },
+            state::FormulaDomain::WorkbookCell {
+                dataset: target,
+                sheet: target_sheet,
+                ..
+            },
+        ) if dataset == target && sheet == target_sheet => cell,
+
+        _ => return false,
+    };
+
+    (start.row()..=end.row()).contains(&cell.row())
+        && (start.col()..=end.col()).contains(&cell.col())
+}
+
+/// # Returns
+/// Text shown in a canvas cell.
+fn canvas_cell_text(
+    cells: &state::CanvasCells,
+    idx: &core::data::CellIndex,
+    decimal: lib::settings::DecimalSeparator,
+) -> String {
+    let Some(cell) = cells.get_cell(idx) else {
+        return String::new();
+    };
+
+    cell.with_untracked(|cell| match cell {
+        state::CanvasCellValue::Unset => String::new(),
+        state::CanvasCellValue::Set(state::CellValue::Fixed(data)) => display_data(data, decimal),
+        state::CanvasCellValue::Set(state::CellValue::Variable(value)) => {
+            value.with_untracked(|value| match value {
+                state::VariableCellValue::Empty => String::new(),
+                state::VariableCellValue::Formula(Ok(data), _) => display_data(data, decimal),
+                state::VariableCellValue::Formula(Err(err), _) => expr_error_to_string(err),
+            })
+        }
+    })
+}
+
+/// Write text to the system clipboard.
+async fn copy_to_clipboard(text: &str) -> Result<(), wasm_bindgen::JsValue> {
+    let promise = window().navigator().clipboard().write_text(text);
+    wasm_bindgen_futures::JsFuture::from(promise)
+        .await
+        .map(|_| ())
+}
+
+/// # Returns
+/// Whether the event originated from an element accepting text input, e.g. a cell being edited.
+fn is_editable(e: &ev::KeyboardEvent) -> bool {
+    use wasm_bindgen::JsCast;
+
+    e.target()
+        .and_then(|target| target.dyn_into::<web_sys::Element>().ok())
+        .map(|target| {
+            target
+                .matches("input, textarea, select, [contenteditable]")
+                .unwrap_or(false)
+        })
+        .unwrap_or(false)
+}
+
+/// Outlines the cells referenced by the formula being edited.
+#[component]
+fn ReferenceOverlay(table: NodeRef<html::Table>) -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let pane = expect_context::<state::Pane>();
+    let references = expect_context::<state::FormulaReferences>();
+    let rows = pane.canvas.rows();
+    let cols = pane.canvas.cols();
+
+    let is_visible = {
+        let state = state.clone();
+        move |sheet: &core::data::SheetRef| {
+            let core::data::SheetRef::Absolute(sheet) = sheet else {
+                return true;
+            };
+
+            let Some(id) = pane.dataset.read().as_ref().cloned() else {
+                return false;
+            };
+            state.datasets.with(|datasets| {
+                let Some(dataset) = datasets.iter().find(|dataset| *dataset.id() == id) else {
+                    return false;
+                };
+
+                match (dataset, sheet) {
+                    (state::Dataset::Csv(_), core::data::SheetIndex::Index(idx)) => *idx == 0,
+                    (state::Dataset::Csv(_), core::data::SheetIndex::Label(_)) => false,
+                    (state::Dataset::Workbook(workbook), core::data::SheetIndex::Index(idx)) => {
+                        workbook.active_sheet.get() == *idx as usize
+                    }
+                    (state::Dataset::Workbook(workbook), core::data::SheetIndex::Label(label)) => {
+                        workbook
+                            .sheets
+                            .read()
+                            .get(workbook.active_sheet.get())
+                            .map(|sheet| sheet.name.with(|name| name.eq_ignore_ascii_case(label)))
+                            .unwrap_or(false)
+                    }
+                }
+            })
+        }
+    };
+
+    move || {
+        let (rows, cols) = (rows.get(), cols.get());
+        let Some(table) = table.get() else {
+            return vec![];
+        };
+        if rows == 0 || cols == 0 {
+            return vec![];
+        }
+
+        references
+            .get()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, reference)| is_visible(&reference.start.sheet))
+            .filter_map(|(idx, reference)| {
+                let ((top, left), (bottom, right)) = reference.bounds();
+                if top >= rows || left >= cols {
+                    return None;
+                }
+
+                let start = cell_element(&table, top, left)?;
+                let end = cell_element(&table, bottom.min(rows - 1), right.min(cols - 1))?;
+                let x = start.offset_left();
+                let y = start.offset_top();
+                let width = end.offset_left() + end.offset_width() - x;
+                let height = end.offset_top() + end.offset_height() - y;
+                Some(view! {
+                    <div
+                        class="absolute pointer-events-none border-2"
+                        style:left=format!("{x}px")
+                        style:top=format!("{y}px")
+                        style:width=format!("{width}px")
+                        style:height=format!("{height}px")
+                        style:border-color=formula::reference_color(idx)
+                    ></div>
+                })
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+/// Outlines the active cell or range of the pane.
+#[component]
+fn SelectionOverlay(table: NodeRef<html::Table>) -> impl IntoView {
+    let pane = expect_context::<state::Pane>();
+    let rows = pane.canvas.rows();
+    let cols = pane.canvas.cols();
+
+    move || {
+        let (rows, cols) = (rows.get(), cols.get());
+        let table = table.get()?;
+        let state::ActiveDataset::Some { active_cell, .. } = pane.dataset.get() else {
+            return None;
+        };
+        let (top, left, bottom, right) = active_cell.with(|active_cell| {
+            active_cell
+                .bounds()
+                .map(|(start, end)| (start.row(), start.col(), end.row(), end.col()))
+        })?;
+        if top >= rows || left >= cols {
+            return None;
+        }
+
+        let start = cell_element(&table, top, left)?;
+        let end = cell_element(&table, bottom.min(rows - 1), right.min(cols - 1))?;
+        let x = start.offset_left();
+        let y = start.offset_top();
+        let width = end.offset_left() + end.offset_width() - x;
+        let height = end.offset_top() + end.offset_height() - y;
+        Some(view! {
+            <div
+                class="absolute pointer-events-none border-2 border-primary-600 bg-primary-600/10"
+                style:left=format!("{x}px")
+                style:top=format!("{y}px")
+                style:width=format!("{width}px")
+                style:height=format!("{height}px")
+            ></div>
+        })
+    }
+}
+
+/// Outlines the cells of the active sheet whose values differ from the file.
+#[component]
+fn DiffOverlay(table: NodeRef<html::Table>) -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let pane = expect_context::<state::Pane>();
+    let diff = expect_context::<DatasetDiff>();
+    let rows = pane.canvas.rows();
+    let cols = pane.canvas.cols();
+
+    let active_sheet = {
+        let state = state.clone();
+        move || {
+            let id = pane.dataset.read().as_ref().cloned()?;
+            state.datasets.with(|datasets| {
+                match datasets.iter().find(|dataset| *dataset.id() == id)? {
+                    state::Dataset::Csv(_) => Some(None),
+                    state::Dataset::Workbook(workbook) => {
+                        Some(Some(workbook.active_sheet.get() as core::data::IndexType))
+                    }
+                }
+            })
+        }
+    };
+
+    move || {
+        let (rows, cols) = (rows.get(), cols.get());
+        let Some(table) = table.get() else {
+            return vec![];
+        };
+        let Some(sheet) = active_sheet() else {
+            return vec![];
+        };
+
+        diff.with(|diff| {
+            let Some(diff) = diff else {
+                return vec![];
+            };
+
+            diff.iter()
+                .filter(|diff| diff.sheet == sheet)
+                .filter(|diff| diff.cell.row() < rows && diff.cell.col() < cols)
+                .filter_map(|diff| {
+                    let cell = cell_element(&table, diff.cell.row(), diff.cell.col())?;
+                    let class = match diff.kind {
+                        lib::diff::DiffKind::Added => "border-brand-green-600",
+                        lib::diff::DiffKind::Removed => "border-brand-red-600",
+                        lib::diff::DiffKind::Modified => "border-brand-yellow-600",
+                    };
+
+                    Some(view! {
+                        <div
+                            class=format!("absolute pointer-events-none border-2 border-dashed {class}")
+                            style:left=format!("{}px", cell.offset_left())
+                            style:top=format!("{}px", cell.offset_top())
+                            style:width=format!("{}px", cell.offset_width())
+                            style:height=format!("{}px", cell.offset_height())
+                        ></div>
+                    })
+                })
+                .collect::<Vec<_>>()
+        })
+    }
+}
+
+/// # Returns
+/// Element of the canvas cell at the given position.
+fn cell_element(
+    table: &web_sys::HtmlTableElement,
+    row: core::data::IndexType,
+    col: core::data::IndexType,
+) -> Option<web_sys::HtmlElement> {
+    use wasm_bindgen::JsCast;
+
+    table
+        .query_selector(&format!("[data-row=\"{row}\"][data-col=\"{col}\"]"))
+        .ok()
+        .flatten()?
+        .dyn_into::<web_sys::HtmlElement>()
+        .ok()
+}
+
+/// # Returns
+/// Index of the canvas cell at the given point of the viewport,
+/// or `None` if the point is not over a cell.
+fn cell_at_point(x: i32, y: i32) -> Option<core::data::CellIndex> {
+    let cell = document()
+        .element_from_point(x as f32, y as f32)?
+        .closest("td[data-row][data-col]")
+        .ok()
+        .flatten()?;
+
+    let row = cell.get_attribute("data-row")?;
+    let col = cell.get_attribute("data-col")?;
+    Some(core::data::CellIndex::new(
+        row.parse::<core::data::IndexType>().ok()?,
+        col.parse::<core::data::IndexType>().ok()?,
+    ))
+}
+
+/// # Returns
+/// Selection of the cells between `a` and `b`, inclusive.
+fn cell_span(a: &core::data::CellIndex, b: &core::data::CellIndex) -> state::ActiveCell {
+    if a == b {
+        return state::ActiveCell::Some(a.clone());
+    }
+
+    state::ActiveCell::Range {
+        start: (a.row().min(b.row()), a.col().min(b.col())).into(),
+        end: (a.row().max(b.row()), a.col().max(b.col())).into(),
+    }
+}
+
+/// # Returns
+/// Width of the widest content in the column, including its header, in pixels.
+/// `None` if the column has no elements.
+fn content_width(
+    table: &web_sys::HtmlTableElement,
+    rows: core::data::IndexType,
+    col: core::data::IndexType,
+) -> Option<i32> {
+    use wasm_bindgen::JsCast;
+
+    let header = table
+        .query_selector(&format!("th[data-col=\"{col}\"]"))
+        .ok()
+        .flatten()
+        .and_then(|header| header.dyn_into::<web_sys::HtmlElement>().ok());
+
+    header
+        .into_iter()
+        .chain((0..rows).filter_map(|row| cell_element(table, row, col)))
+        .map(|element| element.scroll_width())
+        .max()
+}
+
+/// Canvas cell, taking the canvas' default value if it has not been set.
+#[component]
+fn CanvasCell(idx: core::data::CellIndex) -> impl IntoView {
+    let pane = expect_context::<state::Pane>();
+    let cells = pane.canvas.cells();
+    let default = cells.default();
+    move || match cells.track_cell(&idx) {
+        Some(cell) => {
+            EitherOf3::A(view! { <CanvasCellValue idx=idx.clone() cell=cell.read_only() /> })
+        }
+        None => match default.get() {
+            state::CanvasCellDefault::Unset => {
+                EitherOf3::B(view! { <CellValueUnset idx=idx.clone() /> })
+            }
+            state::CanvasCellDefault::Empty => {
+                EitherOf3::C(view! { <CellEmpty idx=idx.clone() /> })
+            }
+        },
+    }
+}
+
 #[component]
 fn CanvasCellValue(
     idx: core::data::CellIndex,
     cell: ReadSignal<state::CanvasCellValue>,
 ) -> impl IntoView {
     move || match cell.get() {
-        state::CanvasCellValue::Unset => EitherOf3::A(view! { <CellValueUnset /> }),
+        state::CanvasCellValue::Unset => EitherOf3::A(view! { <CellValueUnset idx=idx.clone() /> }),
         state::CanvasCellValue::Set(value) => match value {
             state::CellValue::Fixed(data) => {
                 EitherOf3::B(view! { <CellValueFixed data=data.clone() idx=idx.clone() /> })
@@ -160,8 +1615,18 @@ fn CanvasCellValue(
 }
 
 #[component]
-fn CellValueUnset() -> impl IntoView {
-    view! { <td class="cursor-not-allowed"></td> }
+fn CellValueUnset(idx: core::data::CellIndex) -> impl IntoView {
+    let frozen = expect_context::<FrozenCells>();
+    let (row, col) = (idx.row(), idx.col());
+    view! {
+        <td
+            class="cursor-not-allowed"
+            class=(["bg-white", "dark:bg-secondary-800"], move || frozen.is_frozen(row, col))
+            style=move || frozen.style(row, col)
+            data-row=row
+            data-col=col
+        ></td>
+    }
 }
 
 const STATIC_CELL_DATA_CLASS: &'static str =
@@ -170,171 +1635,1489 @@ const STATIC_CELL_DATA_CLASS: &'static str =
 /// Cell data for static data.
 #[component]
 fn CellValueFixed(data: lib::data::Data, idx: core::data::CellIndex) -> impl IntoView {
+    let settings = expect_context::<state::Settings>();
+    let frozen = expect_context::<FrozenCells>();
+    let (row, col) = (idx.row(), idx.col());
+    view! {
+        <td
+            class=STATIC_CELL_DATA_CLASS
+            class=(["bg-white", "dark:bg-secondary-800"], move || frozen.is_frozen(row, col))
+            style=move || frozen.style(row, col)
+            data-row=row
+            data-col=col
+        >
+            {move || display_data(&data, settings.with(|settings| settings.decimal))}
+        </td>
+    }
+}
+
+/// # Returns
+/// Text of cell data, with numbers formatted using the decimal separator.
+fn display_data(data: &lib::data::Data, decimal: lib::settings::DecimalSeparator) -> String {
+    match data {
+        lib::data::Data::Float(value) => decimal.format(*value),
+        data => data.to_string(),
+    }
+}
+
+#[component]
+fn CellValueVariable(
+    data: ReadSignal<state::VariableCellValue>,
+    idx: core::data::CellIndex,
+) -> impl IntoView {
+    move || match data.get() {
+        state::VariableCellValue::Empty => Either::Left(view! { <CellEmpty idx=idx.clone() /> }),
+        state::VariableCellValue::Formula(data, provenance) => {
+            Either::Right(view! { <CellValueFormula data provenance idx=idx.clone() /> })
+        }
+    }
+}
+
+/// Cell data for dynamic data with a formula.
+#[component]
+fn CellValueFormula(
+    data: Result<lib::data::Data, core::expr::Error>,
+    provenance: lib::audit::Provenance,
+    idx: core::data::CellIndex,
+) -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let settings = state.settings;
+    let frozen = expect_context::<FrozenCells>();
+    let (row, col) = (idx.row(), idx.col());
+    let is_err = data.is_err();
+    let inspect = RwSignal::new(false);
+
+    let select_formula = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+    };
+    let text = {
+        let data = data.clone();
+        move || match data.as_ref() {
+            Ok(data) => display_data(data, settings.with(|settings| settings.decimal)),
+            Err(err) => expr_error_to_string(err),
+        }
+    };
+
     view! {
-        <td class=STATIC_CELL_DATA_CLASS data-row=idx.row() data-col=idx.col()>
-            // {calamine_data_to_string(&data)}
-            {data.to_string()}
+        <td
+            class="relative cursor-pointer hover:bg-secondary-50 dark:hover:bg-secondary-700 border border-primary-600"
+            class:bg-brand-red-500=is_err
+            class=(
+                ["bg-white", "dark:bg-secondary-800"],
+                move || !is_err && frozen.is_frozen(row, col),
+            )
+            style=move || frozen.style(row, col)
+            title=data.as_ref().err().map(|err| err.to_string())
+            data-row=row
+            data-col=col
+            on:mousedown=select_formula
+            on:mouseenter=move |_| inspect.set(true)
+            on:mouseleave=move |_| inspect.set(false)
+        >
+            {text}
+            <Show when=move || inspect.get()>
+                <ProvenanceInspector provenance=provenance.clone() />
+            </Show>
         </td>
     }
 }
 
+/// How a formula cell's value was computed, shown while hovering the cell.
+#[component]
+fn ProvenanceInspector(provenance: lib::audit::Provenance) -> impl IntoView {
+    let inputs = if provenance.inputs.is_empty() {
+        "none".to_string()
+    } else {
+        provenance
+            .inputs
+            .iter()
+            .map(|input| input.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    view! {
+        <div class="absolute z-20 top-full left-0 p-2 min-w-48 text-xs font-normal text-left \
+        whitespace-nowrap pointer-events-none bg-white dark:bg-secondary-800 \
+        border border-secondary-100 dark:border-secondary-600">
+            <div class="font-mono">"=" {provenance.formula}</div>
+            <div>"Computed " {provenance.computed.format("%Y-%m-%d %H:%M:%S UTC").to_string()}</div>
+            <div>"Expression version " {provenance.expr_version}</div>
+            <div>"Inputs: " {inputs}</div>
+        </div>
+    }
+}
+
+/// Cell data for an empty cell.
+#[component]
+fn CellEmpty(idx: core::data::CellIndex) -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let workspace_owner = expect_context::<state::WorkspaceOwner>();
+    let active_dataset = expect_context::<ActiveDatasetId>();
+    let active_sheet = expect_context::<ActiveSpreadsheetId>();
+    let formula_editor_vis = expect_context::<state::FormulaEditorVisibility>();
+    let frozen = expect_context::<FrozenCells>();
+    let (row, col) = (idx.row(), idx.col());
+
+    let create_cell_data = {
+        let datasets = state.datasets;
+        let formulas = state.formulas;
+        let active_formula = state.active_formula;
+        let idx = idx.clone();
+        move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+
+            let Some(domain) = cell_domain(idx.clone(), &datasets, active_dataset, active_sheet)
+            else {
+                return;
+            };
+            if state.is_protected(domain.dataset()) {
+                return;
+            }
+
+            formula::open_formula(
+                domain,
+                formulas,
+                active_formula,
+                &workspace_owner,
+                formula_editor_vis,
+            );
+        }
+    };
+
+    view! {
+        <td
+            class=STATIC_CELL_DATA_CLASS
+            class=(["bg-white", "dark:bg-secondary-800"], move || frozen.is_frozen(row, col))
+            style=move || frozen.style(row, col)
+            on:click=create_cell_data
+            data-row=row
+            data-col=col
+        ></td>
+    }
+}
+
+/// # Returns
+/// Formula domain of a cell of the active dataset and sheet,
+/// or `None` if no dataset is active.
+fn cell_domain(
+    cell: core::data::CellIndex,
+    datasets: &state::Datasets,
+    active_dataset: ActiveDatasetId,
+    active_sheet: ActiveSpreadsheetId,
+) -> Option<state::FormulaDomain> {
+    let dataset = active_dataset.get_untracked()?;
+    let is_csv = datasets.with_untracked(|datasets| {
+        datasets
+            .iter()
+            .find(|ds| *ds.id() == dataset)
+            .map(|ds| matches!(ds, state::Dataset::Csv(_)))
+    })?;
+
+    if is_csv {
+        Some(state::FormulaDomain::CsvCell { dataset, cell })
+    } else {
+        Some(state::FormulaDomain::WorkbookCell {
+            dataset,
+            sheet: active_sheet.get_untracked()?,
+            cell,
+        })
+    }
+}
+
+#[component]
+fn Csv(csv: state::Csv) -> impl IntoView {
+    view! {
+        <ReadOnlyBanner read_only=csv.read_only() truncated=csv.truncated() />
+        <Protection dataset=csv.id().clone() />
+        <FreezeControls dataset=csv.id().clone() sheet=0 />
+        <ExportEvaluated dataset=csv.id().clone() sheet=0 />
+        <ImportSettings csv=csv.clone() />
+        <Diff dataset=csv.id().clone() />
+        <Clean dataset=csv.id().clone() />
+        <ExtractColumns csv=csv.clone() />
+        <Spreadsheet sheet=csv.sheet().clone() />
+    }
+}
+
+/// Notice shown when the dataset's file can not be written to, or was only partially loaded.
+#[component]
+fn ReadOnlyBanner(read_only: bool, #[prop(optional)] truncated: bool) -> impl IntoView {
+    let notice = match (read_only, truncated) {
+        (false, false) => return None,
+        (true, false) => "This file is read-only. Running the workspace will not modify it.",
+        (false, true) => "Only the first rows of this file were loaded.",
+        (true, true) => {
+            "Only the first rows of this file were loaded. \
+            It is read-only, so running the workspace will not modify it."
+        }
+    };
+
+    Some(view! { <div class="px-1 text-sm bg-secondary-100 dark:bg-secondary-700">{notice}</div> })
+}
+
+/// Save a copy of a sheet with its formulas replaced by their evaluated values,
+/// to share with collaborators who do not use hermes.
+#[component]
+fn ExportEvaluated(
+    dataset: state::ResourceId,
+    #[prop(into)] sheet: Signal<usize>,
+) -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let messages = state.messages;
+
+    let export = Action::new_local({
+        let state = state.clone();
+        move |_: &()| {
+            let copy = state
+                .directory_tree
+                .get_file_path(&dataset)
+                .and_then(|path| {
+                    let path = state.root_path().join(path);
+                    let records = state.datasets.with_untracked(|datasets| {
+                        datasets
+                            .iter()
+                            .find(|ds| *ds.id() == dataset)?
+                            .evaluated_records(sheet.get_untracked())
+                    })?;
+
+                    let format = lib::formula::OutputFormat::Csv;
+                    Some(lib::formula::EvaluatedCopy {
+                        path: lib::formula::EvaluatedCopy::path_for(path, format),
+                        format,
+                        records,
+                    })
+                });
+
+            async move {
+                let Some(copy) = copy else {
+                    messages.push(message::Message::error(
+                        "Could not find the dataset to export.",
+                    ));
+                    return;
+                };
+
+                match export_evaluated_copy(copy).await {
+                    Ok(Some(path)) => messages.push(message::Message::success(format!(
+                        "Exported evaluated copy to {}.",
+                        path.to_string_lossy()
+                    ))),
+                    Ok(None) => {}
+                    Err(err) => messages.push(message::Message::error_with_body(
+                        "Could not export evaluated copy.",
+                        message::order_error_message(&err),
+                    )),
+                }
+            }
+        }
+    });
+
+    let trigger_export = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+
+        export.dispatch(());
+    };
+
+    view! {
+        <div class="flex justify-end px-2">
+            <button
+                type="button"
+                class="btn-cmd cursor-pointer"
+                title="Export evaluated copy, with formulas replaced by their values"
+                disabled=move || export.pending().get()
+                on:mousedown=trigger_export
+            >
+                <Icon icon=icon::Export />
+            </button>
+        </div>
+    }
+}
+
+/// # Returns
+/// Dataset and index of the sheet shown in the pane.
+fn pane_sheet(pane: state::Pane, datasets: &state::Datasets) -> Option<(state::ResourceId, usize)> {
+    let id = pane.dataset.with(|dataset| dataset.as_ref().cloned())?;
+    datasets.with(|datasets| {
+        datasets
+            .iter()
+            .find(|dataset| *dataset.id() == id)
+            .map(|dataset| match dataset {
+                state::Dataset::Csv(_) => (id.clone(), 0),
+                state::Dataset::Workbook(workbook) => (id.clone(), workbook.active_sheet.get()),
+            })
+    })
+}
+
+/// Set the number of leading rows and columns of a sheet kept visible while scrolling.
+/// Toggle protecting the dataset's file from modification.
+/// Cells of a protected dataset can not be edited,
+/// and running the workspace does not modify its file.
+#[component]
+fn Protection(dataset: state::ResourceId) -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let is_protected = {
+        let protected_files = state.protected_files;
+        let dataset = dataset.clone();
+        Memo::new(move |_| protected_files.with(|files| files.contains(&dataset)))
+    };
+
+    let set_protected = Action::new_local(move |protected: &bool| {
+        explorer::set_protected(state.clone(), dataset.clone(), *protected)
+    });
+
+    view! {
+        <div
+            class="flex gap-2 px-2 text-sm"
+            class=(["bg-secondary-100", "dark:bg-secondary-700"], move || is_protected.get())
+        >
+            <label title="Block cell editing and workspace runs for this file.">
+                <input
+                    type="checkbox"
+                    prop:checked=is_protected
+                    disabled=move || set_protected.pending().get()
+                    on:change=move |e| {
+                        set_protected.dispatch(event_target_checked(&e));
+                    }
+                />
+                " Protected"
+            </label>
+            <Show when=move || is_protected.get()>
+                <span>"Cells can not be edited, and running the workspace will not modify this file."</span>
+            </Show>
+        </div>
+    }
+}
+
+#[component]
+fn FreezeControls(dataset: state::ResourceId, #[prop(into)] sheet: Signal<usize>) -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let frozen = state.frozen;
+
+    let freeze = {
+        let dataset = dataset.clone();
+        move || {
+            frozen.with(|frozen| {
+                frozen
+                    .get(&(dataset.clone(), sheet.get()))
+                    .copied()
+                    .unwrap_or_default()
+            })
+        }
+    };
+
+    let set_freeze = move |update: fn(&mut state::Freeze, core::data::IndexType), value: String| {
+        let Ok(value) = value.trim().parse::<core::data::IndexType>() else {
+            return;
+        };
+        frozen.update(|frozen| {
+            let key = (dataset.clone(), sheet.get_untracked());
+            let freeze = frozen.entry(key.clone()).or_default();
+            update(freeze, value);
+            if *freeze == state::Freeze::default() {
+                frozen.remove(&key);
+            }
+        });
+    };
+
+    view! {
+        <div class="flex gap-2 px-2 text-sm">
+            <label>
+                "Freeze rows" <input
+                    type="number"
+                    min="0"
+                    class="input-compact w-16"
+                    prop:value={
+                        let freeze = freeze.clone();
+                        move || freeze().rows.to_string()
+                    }
+                    on:change={
+                        let set_freeze = set_freeze.clone();
+                        move |e| set_freeze(|freeze, rows| freeze.rows = rows, event_target_value(&e))
+                    }
+                />
+            </label>
+            <label>
+                "Freeze columns" <input
+                    type="number"
+                    min="0"
+                    class="input-compact w-16"
+                    prop:value=move || freeze().cols.to_string()
+                    on:change=move |e| {
+                        set_freeze(|freeze, cols| freeze.cols = cols, event_target_value(&e))
+                    }
+                />
+            </label>
+        </div>
+    }
+}
+
+/// Designate rows of the file as column metadata rather than data.
+#[component]
+fn ImportSettings(csv: state::Csv) -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let units_row = NodeRef::<html::Input>::new();
+    let description_row = NodeRef::<html::Input>::new();
+
+    let initial_row = {
+        let options = csv.options().clone();
+        move |kind: lib::data::MetadataKind| {
+            options
+                .metadata_rows
+                .iter()
+                .find_map(|(row, k)| (*k == kind).then(|| (row + 1).to_string()))
+                .unwrap_or_default()
+        }
+    };
+
+    let reload = Action::new_local({
+        let state = state.clone();
+        let file_id = csv.id().clone();
+        move |options: &lib::data::ImportOptions| {
+            let state = state.clone();
+            let file_id = file_id.clone();
+            let options = options.clone();
+            async move {
+                let path = state
+                    .directory_tree
+                    .get_file_path(&file_id)
+                    .expect("file exists");
+                let path = state.root_path().join(path);
+                state.import_options.update(|import_options| {
+                    import_options.insert(file_id.clone(), options.clone());
+                });
+
+                match load_dataset(path, options).await {
+                    Ok(dataset) => {
+                        state.datasets.update(|datasets| {
+                            if let Some(current) =
+                                datasets.iter_mut().find(|dataset| *dataset.id() == file_id)
+                            {
+                                *current = state::Dataset::new(file_id.clone(), dataset);
+                            }
+                        });
+                        state.refresh_panes();
+                    }
+                    Err(err) => {
+                        state.messages.push(message::Message::error_with_body(
+                            "Could not load file.",
+                            err.to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+    });
+
+    let dialect = csv.options().dialect.clone();
+    let submit = move |e: ev::SubmitEvent| {
+        e.prevent_default();
+        let mut metadata_rows = vec![];
+        for (input, kind) in [
+            (units_row, lib::data::MetadataKind::Units),
+            (description_row, lib::data::MetadataKind::Description),
+        ] {
+            let Some(input) = input.get_untracked() else {
+                continue;
+            };
+            if let Ok(row) = input.value().trim().parse::<usize>() {
+                if row > 0 {
+                    metadata_rows.push((row - 1, kind));
+                }
+            }
+        }
+
+        reload.dispatch(lib::data::ImportOptions {
+            metadata_rows,
+            dialect: dialect.clone(),
+        });
+    };
+
+    view! {
+        <form class="flex gap-2 px-2 text-sm" on:submit=submit>
+            <label>
+                "Units row" <input
+                    node_ref=units_row
+                    type="number"
+                    min="1"
+                    class="input-compact w-16"
+                    prop:value=initial_row(lib::data::MetadataKind::Units)
+                />
+            </label>
+            <label>
+                "Description row" <input
+                    node_ref=description_row
+                    type="number"
+                    min="1"
+                    class="input-compact w-16"
+                    prop:value=initial_row(lib::data::MetadataKind::Description)
+                />
+            </label>
+            <button type="submit" class="btn-cmd btn-secondary" disabled=reload.pending()>
+                "Apply"
+            </button>
+        </form>
+    }
+}
+
+/// Summary statistics of the selected column.
+#[component]
+pub fn ColumnStatistics() -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let refresh = RwSignal::new(());
+
+    let stats = {
+        let state = state.clone();
+        move || {
+            refresh.track();
+            let col = state.selected_column.get()?;
+            let id = state.active_dataset.read().as_ref().cloned()?;
+            state.datasets.with(|datasets| {
+                datasets
+                    .iter()
+                    .find(|dataset| *dataset.id() == id)
+                    .map(|dataset| (col, dataset.column_stats(col, HISTOGRAM_BINS)))
+            })
+        }
+    };
+
+    let trigger_refresh = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+
+        refresh.notify();
+    };
+
+    view! {
+        <div>
+            <div class="pb flex gap-2">
+                <h2 class="grow font-bold uppercase">"Column"</h2>
+                <div>
+                    <button
+                        type="button"
+                        class="btn-cmd cursor-pointer"
+                        title="Refresh"
+                        on:mousedown=trigger_refresh
+                    >
+                        <Icon icon=icon::Refresh />
+                    </button>
+                </div>
+            </div>
+            {move || match stats() {
+                None => Either::Left(view! { <div>"Select a column"</div> }),
+                Some((col, stats)) => {
+                    Either::Right(view! { <ColumnStatisticsSummary col stats /> })
+                }
+            }}
+        </div>
+    }
+}
+
 #[component]
-fn CellValueVariable(
-    data: ReadSignal<state::VariableCellValue>,
-    idx: core::data::CellIndex,
+fn ColumnStatisticsSummary(
+    col: core::data::IndexType,
+    stats: lib::data::ColumnStats,
 ) -> impl IntoView {
-    move || match data.get() {
-        state::VariableCellValue::Empty => Either::Left(view! { <CellEmpty idx=idx.clone() /> }),
-        state::VariableCellValue::Formula(data) => {
-            Either::Right(view! { <CellValueFormula data idx=idx.clone() /> })
+    let reference_style = expect_context::<state::State>().reference_style;
+    let format_number = |value: Option<f64>| {
+        value
+            .map(|value| format!("{value:.4}"))
+            .unwrap_or_else(|| "-".to_string())
+    };
+
+    let rows = [
+        ("Count", stats.count.to_string()),
+        ("Missing", stats.missing.to_string()),
+        ("Errors", stats.skipped.to_string()),
+        ("Numeric", stats.numeric.to_string()),
+        ("Mean", format_number(stats.mean)),
+        ("Std. dev.", format_number(stats.std_dev)),
+        ("Min", format_number(stats.min)),
+        ("Max", format_number(stats.max)),
+    ];
+
+    let peak = stats.histogram.iter().copied().max().unwrap_or(0).max(1);
+    let bin_width = match (stats.min, stats.max) {
+        (Some(min), Some(max)) if !stats.histogram.is_empty() => {
+            Some((min, (max - min) / stats.histogram.len() as f64))
         }
+        _ => None,
+    };
+    let histogram = stats
+        .histogram
+        .iter()
+        .enumerate()
+        .map(|(bin, count)| {
+            let title = match bin_width {
+                Some((min, width)) => {
+                    let start = min + width * bin as f64;
+                    format!("{start:.4} to {:.4}: {count}", start + width)
+                }
+                None => count.to_string(),
+            };
+
+            view! {
+                <div
+                    class="grow bg-primary-600"
+                    style:height=format!("{}%", count * 100 / peak)
+                    title=title
+                ></div>
+            }
+        })
+        .collect::<Vec<_>>();
+
+    view! {
+        <div class="text-sm">
+            <div class="font-bold">
+                {move || reference_style.with(|style| style.col_label(col))}
+            </div>
+            <table>
+                {rows
+                    .into_iter()
+                    .map(|(label, value)| {
+                        view! {
+                            <tr>
+                                <th class="pr-2 text-left font-normal">{label}</th>
+                                <td>{value}</td>
+                            </tr>
+                        }
+                    })
+                    .collect::<Vec<_>>()}
+            </table>
+            <div class="flex items-end gap-px h-16 pt-1">{histogram}</div>
+        </div>
     }
 }
 
-/// Cell data for dynamic data with a formula.
+/// Compare the dataset's in-memory values against the file on disk.
 #[component]
-fn CellValueFormula(
-    data: Result<lib::data::Data, core::expr::Error>,
-    idx: core::data::CellIndex,
-) -> impl IntoView {
+fn Diff(dataset: state::ResourceId) -> impl IntoView {
     let state = expect_context::<state::State>();
+    let diff = expect_context::<DatasetDiff>();
 
-    let select_formula = move |e: ev::MouseEvent| {
+    let compare = Action::new_local({
+        let state = state.clone();
+        move |_: &()| {
+            let state = state.clone();
+            let id = dataset.clone();
+            async move {
+                let Some(path) = state.directory_tree.get_file_path(&id) else {
+                    return;
+                };
+                let options = state.import_options(&id);
+                let Some(sheets) = state.datasets.with_untracked(|datasets| {
+                    datasets
+                        .iter()
+                        .find(|dataset| *dataset.id() == id)
+                        .map(|dataset| dataset.values())
+                }) else {
+                    return;
+                };
+
+                match diff_dataset(state.root_path().join(path), options, sheets).await {
+                    Ok(cells) => diff.set(Some(cells)),
+                    Err(err) => {
+                        state.messages.push(message::Message::error_with_body(
+                            "Could not compare with file.",
+                            err.to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+    });
+
+    let toggle = move |e: ev::MouseEvent| {
         if e.button() != types::MouseButton::Primary {
             return;
         }
+
+        if diff.read_untracked().is_some() {
+            diff.set(None);
+        } else {
+            compare.dispatch(());
+        }
+    };
+
+    let summary = move || {
+        diff.with(|diff| {
+            diff.as_ref().map(|diff| {
+                let count = |kind| diff.iter().filter(|diff| diff.kind == kind).count();
+                format!(
+                    "{} added, {} removed, {} modified",
+                    count(lib::diff::DiffKind::Added),
+                    count(lib::diff::DiffKind::Removed),
+                    count(lib::diff::DiffKind::Modified),
+                )
+            })
+        })
     };
 
     view! {
-        <td
-            class="cursor-pointer hover:bg-secondary-50 dark:hover:bg-secondary-700 border border-primary-600"
-            class:bg-brand-red-500=data.is_err()
-            data-row=idx.row()
-            data-col=idx.col()
-            on:mousedown=select_formula
-        >
-            {match data.as_ref() {
-                Ok(data) => data.to_string(),
-                Err(err) => todo!(),
-            }}
+        <div class="flex gap-2 px-2 text-sm items-center">
+            <button
+                type="button"
+                class="btn-cmd btn-secondary"
+                disabled=compare.pending()
+                on:mousedown=toggle
+            >
+                {move || if diff.read().is_some() { "Hide changes" } else { "Compare with file" }}
+            </button>
+            <span>{summary}</span>
+        </div>
+        <ul class="px-2 text-sm max-h-32 overflow-y-auto scrollbar-thin">
+            {move || {
+                diff.get()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|diff| {
+                        let sheet = diff
+                            .sheet
+                            .map(|sheet| format!("{sheet}!"))
+                            .unwrap_or_default();
 
-        </td>
+                        view! {
+                            <li class="flex gap-2">
+                                <span class="font-mono">{format!("{sheet}{}", diff.cell)}</span>
+                                <del class="text-brand-red-600">{diff.disk.to_string()}</del>
+                                <ins class="text-brand-green-600 no-underline">
+                                    {diff.memory.to_string()}
+                                </ins>
+                            </li>
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            }}
+        </ul>
     }
 }
 
-/// Cell data for an empty cell.
+/// Stage dataset level cleaning operations, then preview or apply them.
 #[component]
-fn CellEmpty(idx: core::data::CellIndex) -> impl IntoView {
+fn Clean(dataset: state::ResourceId) -> impl IntoView {
     let state = expect_context::<state::State>();
-    let workspace_owner = expect_context::<state::WorkspaceOwner>();
-    let active_dataset = expect_context::<ActiveDatasetId>();
-    let active_sheet = expect_context::<ActiveSpreadsheetId>();
-    let formula_editor_vis = expect_context::<state::FormulaEditorVisibility>();
+    let operation_node = NodeRef::<html::Select>::new();
+    let columns_node = NodeRef::<html::Input>::new();
+    let constant_node = NodeRef::<html::Input>::new();
+    let header = RwSignal::new(true);
+    let changes = RwSignal::new(None);
+    let failures = RwSignal::new(vec![]);
 
-    let create_cell_data = {
-        let datasets = state.datasets;
-        let formulas = state.formulas;
-        let active_formula = state.active_formula;
-        let idx = idx.clone();
-        move |e: ev::MouseEvent| {
-            if e.button() != types::MouseButton::Primary {
+    let staged = {
+        let cleaning = state.cleaning;
+        let dataset = dataset.clone();
+        Memo::new(move |_| {
+            cleaning.with(|cleaning| cleaning.get(&dataset).cloned().unwrap_or_default())
+        })
+    };
+
+    let stage = {
+        let cleaning = state.cleaning;
+        let settings = state.settings;
+        let dataset = dataset.clone();
+        move |e: ev::SubmitEvent| {
+            e.prevent_default();
+            let (Some(operation), Some(columns), Some(constant)) = (
+                operation_node.get_untracked(),
+                columns_node.get_untracked(),
+                constant_node.get_untracked(),
+            ) else {
                 return;
-            }
+            };
 
-            let dataset = active_dataset.with_untracked(|active_dataset| {
-                let active_dataset = active_dataset.as_ref().unwrap();
-                datasets
-                    .read_untracked()
-                    .iter()
-                    .find(|ds| ds.id() == active_dataset)
-                    .expect("dataset to exist")
-                    .clone()
-            });
-            let domain = match dataset {
-                state::Dataset::Csv(csv) => state::FormulaDomain::CsvCell {
-                    dataset: active_dataset
-                        .get_untracked()
-                        .expect("dataset id to be set"),
-                    cell: idx.clone(),
+            let columns = columns
+                .value()
+                .split(',')
+                .filter_map(|col| core::utils::col_to_index(col.trim()))
+                .collect::<Vec<_>>();
+            let operation = match operation.value().as_str() {
+                "drop-duplicates" => lib::clean::Operation::DropDuplicates { columns },
+                "drop-missing" => lib::clean::Operation::DropMissing { columns },
+                "fill-constant" => lib::clean::Operation::FillMissing {
+                    columns,
+                    fill: lib::clean::Fill::Constant(constant_to_data(constant.value())),
                 },
-                state::Dataset::Workbook(workbook) => state::FormulaDomain::WorkbookCell {
-                    dataset: active_dataset
-                        .get_untracked()
-                        .expect("dataset id to be set"),
-                    sheet: active_sheet
-                        .get_untracked()
-                        .expect("spreadsheet id to be set"),
-                    cell: idx.clone(),
+                "fill-forward" => lib::clean::Operation::FillMissing {
+                    columns,
+                    fill: lib::clean::Fill::Forward,
                 },
+                "fill-mean" => lib::clean::Operation::FillMissing {
+                    columns,
+                    fill: lib::clean::Fill::Mean,
+                },
+                "convert-number" => lib::clean::Operation::Convert {
+                    columns,
+                    to: lib::clean::Conversion::Number {
+                        decimal: settings.with_untracked(|settings| settings.decimal),
+                    },
+                },
+                "convert-date" => {
+                    let format = constant.value().trim().to_string();
+                    let format = if format.is_empty() {
+                        DEFAULT_DATE_FORMAT.to_string()
+                    } else {
+                        format
+                    };
+                    lib::clean::Operation::Convert {
+                        columns,
+                        to: lib::clean::Conversion::Date { format },
+                    }
+                }
+                "convert-text" => lib::clean::Operation::Convert {
+                    columns,
+                    to: lib::clean::Conversion::Text,
+                },
+                _ => return,
             };
 
-            let formula_id = if let Some(formula) = formulas.get_by_containing_domain(&domain) {
-                formula.id().clone()
-            } else {
-                let formula = workspace_owner.with(|| state::Formula::new(domain));
-                let id = formula.id().clone();
-                formulas.write().push(formula);
-                id
-            };
+            cleaning
+                .update(|cleaning| cleaning.entry(dataset.clone()).or_default().push(operation));
+            changes.set(None);
+            failures.set(vec![]);
+        }
+    };
+
+    let is_protected = {
+        let protected_files = state.protected_files;
+        let dataset = dataset.clone();
+        Memo::new(move |_| protected_files.with(|files| files.contains(&dataset)))
+    };
+
+    let clean_order = {
+        let state = state.clone();
+        let dataset = dataset.clone();
+        move || {
+            if state.is_protected(&dataset) {
+                return None;
+            }
+            let path = state.directory_tree.get_file_path(&dataset)?;
+            let operations = state
+                .cleaning
+                .with_untracked(|cleaning| cleaning.get(&dataset).cloned())
+                .filter(|operations| !operations.is_empty())?;
+            let options = state.import_options(&dataset);
+
+            Some(lib::formula::Clean {
+                path: state.root_path().join(path),
+                options,
+                header: header.get_untracked(),
+                operations,
+            })
+        }
+    };
+
+    let preview = Action::new_local({
+        let state = state.clone();
+        let dataset = dataset.clone();
+        let clean_order = clean_order.clone();
+        move |_: &()| {
+            let state = state.clone();
+            let dataset = dataset.clone();
+            let root = state.root_path().clone();
+            let order = clean_order();
+            async move {
+                let Some(order) = order else {
+                    return;
+                };
+
+                // Failures are reported from the loaded values,
+                // which match the file unless the dataset has unsaved edits.
+                let cells = state.datasets.with_untracked(|datasets| {
+                    datasets.iter().find_map(|current| match current {
+                        state::Dataset::Csv(csv) if *csv.id() == dataset => {
+                            Some(csv.sheet().values())
+                        }
+                        _ => None,
+                    })
+                });
+                failures.set(
+                    cells
+                        .map(|cells| {
+                            lib::clean::clean(&cells, order.header, &order.operations).failures
+                        })
+                        .unwrap_or_default(),
+                );
+
+                match run_workspace(root, vec![order.into()], true).await {
+                    Ok(entries) => changes.set(Some(
+                        entries
+                            .into_iter()
+                            .flat_map(|entry| entry.changes)
+                            .collect::<Vec<_>>(),
+                    )),
+                    Err(errors) => {
+                        changes.set(None);
+                        let body = errors
+                            .iter()
+                            .map(|(_, err)| message::order_error_message(err))
+                            .collect::<Vec<_>>()
+                            .join("\n");
+                        state.messages.push(message::Message::error_with_body(
+                            "Could not preview cleaning operations.",
+                            body,
+                        ));
+                    }
+                }
+            }
+        }
+    });
+
+    let apply = Action::new_local({
+        let state = state.clone();
+        let dataset = dataset.clone();
+        move |_: &()| {
+            let state = state.clone();
+            let dataset = dataset.clone();
+            let order = clean_order();
+            async move {
+                let Some(order) = order else {
+                    return;
+                };
+
+                let path = order.path.clone();
+                let options = order.options.clone();
+                state.auto_run.set_written([&path]);
+                let result =
+                    run_workspace(state.root_path().clone(), vec![order.into()], false).await;
+                state.auto_run.set_written([&path]);
+                if let Err(errors) = result {
+                    let body = errors
+                        .iter()
+                        .map(|(_, err)| message::order_error_message(err))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    state.messages.push(message::Message::error_with_body(
+                        "Could not apply cleaning operations.",
+                        body,
+                    ));
+                    return;
+                }
+
+                state.cleaning.update(|cleaning| {
+                    cleaning.remove(&dataset);
+                });
+                changes.set(None);
+                failures.set(vec![]);
+                match load_dataset(path, options).await {
+                    Ok(data) => {
+                        state.datasets.update(|datasets| {
+                            if let Some(current) =
+                                datasets.iter_mut().find(|current| *current.id() == dataset)
+                            {
+                                *current = state::Dataset::new(dataset.clone(), data);
+                            }
+                        });
+                        state.refresh_panes();
+                    }
+                    Err(err) => {
+                        state.messages.push(message::Message::error_with_body(
+                            "Could not reload file.",
+                            err.to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+    });
+
+    let remove_operation = {
+        let cleaning = state.cleaning;
+        let dataset = dataset.clone();
+        move |idx: usize| {
+            cleaning.update(|cleaning| {
+                if let Some(operations) = cleaning.get_mut(&dataset) {
+                    operations.remove(idx);
+                }
+            });
+            changes.set(None);
+            failures.set(vec![]);
+        }
+    };
 
-            let _ = active_formula.write().insert(formula_id);
-            formula_editor_vis.set(true);
+    let dispatch = move |action: Action<(), ()>| {
+        move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+
+            action.dispatch(());
         }
     };
 
     view! {
-        <td
-            class=STATIC_CELL_DATA_CLASS
-            on:click=create_cell_data
-            data-row=idx.row()
-            data-col=idx.col()
-        ></td>
+        <form class="flex gap-2 px-2 text-sm" on:submit=stage>
+            <select node_ref=operation_node class="input-compact">
+                <option value="drop-duplicates">"Drop duplicate rows"</option>
+                <option value="drop-missing">"Drop rows with missing values"</option>
+                <option value="fill-constant">"Fill missing with value"</option>
+                <option value="fill-forward">"Fill missing forward"</option>
+                <option value="fill-mean">"Fill missing with mean"</option>
+                <option value="convert-number">"Convert to number"</option>
+                <option value="convert-date">"Convert to date"</option>
+                <option value="convert-text">"Convert to text"</option>
+            </select>
+            <input
+                node_ref=columns_node
+                class="input-compact w-24"
+                placeholder="Columns"
+                title="Comma separated columns, e.g. A, C. All columns if empty."
+            />
+            <input
+                node_ref=constant_node
+                class="input-compact w-16"
+                placeholder="Value"
+                title="Value used by \"Fill missing with value\", or date format used by \"Convert to date\", e.g. %d/%m/%Y."
+            />
+            <label title="First row holds column headers.">
+                <input
+                    type="checkbox"
+                    prop:checked=header
+                    on:change=move |e| header.set(event_target_checked(&e))
+                />
+                " Header row"
+            </label>
+            <button type="submit" class="btn-cmd btn-secondary">
+                "Stage"
+            </button>
+        </form>
+        <div class="px-2 text-sm" class:hidden=move || staged.with(|staged| staged.is_empty())>
+            <ol class="list-decimal list-inside">
+                {move || {
+                    staged
+                        .get()
+                        .into_iter()
+                        .enumerate()
+                        .map(|(idx, operation)| {
+                            let remove_operation = remove_operation.clone();
+                            view! {
+                                <li>
+                                    {clean_operation_label(&operation)}
+                                    <button
+                                        type="button"
+                                        class="cursor-pointer pl-1"
+                                        title="Remove operation"
+                                        on:mousedown=move |e: ev::MouseEvent| {
+                                            if e.button() == types::MouseButton::Primary {
+                                                remove_operation(idx);
+                                            }
+                                        }
+                                    >
+                                        <Icon icon=icon::Close />
+                                    </button>
+                                </li>
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                }}
+            </ol>
+            <div class="flex gap-2">
+                <button
+                    type="button"
+                    class="btn-cmd btn-secondary"
+                    disabled=move || preview.pending().get() || is_protected.get()
+                    on:mousedown=dispatch(preview)
+                >
+                    "Preview"
+                </button>
+                <button
+                    type="button"
+                    class="btn-cmd btn-primary"
+                    disabled=move || apply.pending().get() || is_protected.get()
+                    on:mousedown=dispatch(apply)
+                >
+                    "Apply"
+                </button>
+            </div>
+            <ul class="max-h-32 overflow-y-auto scrollbar-thin">
+                {move || {
+                    changes
+                        .get()
+                        .map(|changes: Vec<lib::audit::CellChange>| {
+                            if changes.is_empty() {
+                                Either::Left(view! { <li>"No changes."</li> })
+                            } else {
+                                Either::Right(view! { <history::Changes changes /> })
+                            }
+                        })
+                }}
+            </ul>
+            <ul
+                class="max-h-32 overflow-y-auto scrollbar-thin text-brand-red-600"
+                class:hidden=move || failures.with(|failures| failures.is_empty())
+            >
+                {move || {
+                    failures
+                        .get()
+                        .into_iter()
+                        .map(|failure| {
+                            view! {
+                                <li>
+                                    <span class="font-mono pr-2">{failure.cell.to_string()}</span>
+                                    {format!(
+                                        "\"{}\" could not be converted to {}.",
+                                        failure.value,
+                                        conversion_label(&failure.to),
+                                    )}
+                                </li>
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                }}
+            </ul>
+        </div>
     }
 }
 
+/// Preview columns derived from a column by a regex capture or delimiter split,
+/// then stage them as a cleaning operation.
 #[component]
-fn Csv(csv: state::Csv) -> impl IntoView {
+fn ExtractColumns(csv: state::Csv) -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let visible = RwSignal::new(false);
+    let col_node = NodeRef::<html::Input>::new();
+    let method_node = NodeRef::<html::Select>::new();
+    let pattern_node = NodeRef::<html::Input>::new();
+    let header = RwSignal::new(true);
+    let (error, set_error) = signal(None);
+    let preview = RwSignal::new(None::<(lib::data::Extract, Vec<lib::formula::OutputColumn>)>);
+
+    let extract = move || {
+        let (Some(col), Some(method), Some(pattern)) = (
+            col_node.get_untracked(),
+            method_node.get_untracked(),
+            pattern_node.get_untracked(),
+        ) else {
+            return None;
+        };
+
+        let Some(col) = core::utils::col_to_index(col.value().trim()) else {
+            set_error(Some("Invalid column.".to_string()));
+            return None;
+        };
+        let method = match method.value().as_str() {
+            "regex" => lib::data::ExtractMethod::Regex(pattern.value()),
+            "split" => lib::data::ExtractMethod::Split {
+                delimiter: pattern.value(),
+            },
+            _ => return None,
+        };
+
+        Some(lib::data::Extract {
+            col,
+            method,
+            header: header.get_untracked(),
+        })
+    };
+
+    let show_preview = {
+        let sheet = csv.sheet().clone();
+        move |e: ev::SubmitEvent| {
+            e.prevent_default();
+            let Some(extract) = extract() else {
+                preview.set(None);
+                return;
+            };
+
+            match extract.apply(&sheet.values()) {
+                Ok(columns) => {
+                    set_error(None);
+                    preview.set(Some((extract, columns)));
+                }
+                Err(err) => {
+                    set_error(Some(err.to_string()));
+                    preview.set(None);
+                }
+            }
+        }
+    };
+
+    let stage = {
+        let cleaning = state.cleaning;
+        let dataset = csv.id().clone();
+        move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+            let Some((extract, _)) = preview.get_untracked() else {
+                return;
+            };
+
+            let operation = lib::clean::Operation::Extract {
+                col: extract.col,
+                method: extract.method,
+            };
+            cleaning
+                .update(|cleaning| cleaning.entry(dataset.clone()).or_default().push(operation));
+            preview.set(None);
+            visible.set(false);
+        }
+    };
+
+    let close_on_escape = move |e: ev::KeyboardEvent| {
+        if e.key() == "Escape" {
+            e.prevent_default();
+            visible.set(false);
+        }
+    };
+
+    let close = move |_: ev::MouseEvent| visible.set(false);
+
     view! {
-        <Spreadsheet sheet=csv.sheet().clone() />
-        <FormulaEditor />
+        <div class="px-2 text-sm">
+            <button
+                type="button"
+                class="btn-cmd btn-secondary"
+                on:mousedown=move |e: ev::MouseEvent| {
+                    if e.button() == types::MouseButton::Primary {
+                        visible.set(true);
+                    }
+                }
+            >
+                "Extract columns"
+            </button>
+        </div>
+        <div class:hidden=move || !visible.get() on:keydown=close_on_escape>
+            <div class="fixed inset-0 z-30" on:mousedown=close></div>
+            <form
+                class="fixed z-40 top-12 left-1/2 -translate-x-1/2 w-1/2 p-1 flex flex-col gap-1 text-sm \
+                bg-white dark:bg-secondary-800 border border-secondary-100 dark:border-secondary-600"
+                on:submit=show_preview
+            >
+                <h2 class="font-bold uppercase">"Extract columns"</h2>
+                <div class="flex gap-1">
+                    <input
+                        node_ref=col_node
+                        type="text"
+                        class="input-compact w-16"
+                        placeholder="Column"
+                    />
+                    <select node_ref=method_node class="input-compact">
+                        <option value="split">"Split on delimiter"</option>
+                        <option value="regex">"Regex captures"</option>
+                    </select>
+                    <input
+                        node_ref=pattern_node
+                        type="text"
+                        class="input-compact grow min-w-0"
+                        placeholder="Delimiter or pattern, e.g. _ or (\\w+)_t(\\d+)"
+                    />
+                </div>
+                <div class="flex gap-2">
+                    <label title="First row holds column headers.">
+                        <input
+                            type="checkbox"
+                            prop:checked=header
+                            on:change=move |e| header.set(event_target_checked(&e))
+                        />
+                        " Header row"
+                    </label>
+                    <button type="submit" class="btn-cmd btn-secondary">
+                        "Preview"
+                    </button>
+                    <button
+                        type="button"
+                        class="btn-cmd btn-primary"
+                        disabled=move || preview.with(|preview| preview.is_none())
+                        on:mousedown=stage
+                    >
+                        "Stage"
+                    </button>
+                </div>
+                <small class="color-brand-red-600">{error}</small>
+                <div class="max-h-64 overflow-auto scrollbar-thin">
+                    {move || {
+                        preview
+                            .get()
+                            .map(|(_, columns)| {
+                                let rows = columns
+                                    .iter()
+                                    .map(|column| column.values.len())
+                                    .max()
+                                    .unwrap_or(0)
+                                    .min(EXTRACT_PREVIEW_ROWS);
+                                view! {
+                                    <table>
+                                        <thead>
+                                            <tr>
+                                                {columns
+                                                    .iter()
+                                                    .map(|column| {
+                                                        view! {
+                                                            <th class="pr-2 text-left">
+                                                                {column.header.clone()}
+                                                            </th>
+                                                        }
+                                                    })
+                                                    .collect::<Vec<_>>()}
+                                            </tr>
+                                        </thead>
+                                        <tbody>
+                                            {(0..rows)
+                                                .map(|row| {
+                                                    view! {
+                                                        <tr>
+                                                            {columns
+                                                                .iter()
+                                                                .map(|column| {
+                                                                    view! {
+                                                                        <td class="pr-2">
+                                                                            {column.values[row].to_string()}
+                                                                        </td>
+                                                                    }
+                                                                })
+                                                                .collect::<Vec<_>>()}
+                                                        </tr>
+                                                    }
+                                                })
+                                                .collect::<Vec<_>>()}
+                                        </tbody>
+                                    </table>
+                                }
+                            })
+                    }}
+                </div>
+            </form>
+        </div>
+    }
+}
+
+/// Interpret a fill value as a number if possible, otherwise as text.
+fn constant_to_data(value: String) -> lib::data::Data {
+    let trimmed = value.trim();
+    if let Ok(value) = trimmed.parse::<i64>() {
+        lib::data::Data::Int(value)
+    } else if let Ok(value) = trimmed.parse::<f64>() {
+        lib::data::Data::Float(value)
+    } else {
+        lib::data::Data::String(value)
+    }
+}
+
+/// Short description of a cleaning operation.
+fn clean_operation_label(operation: &lib::clean::Operation) -> String {
+    let columns = |columns: &Vec<core::data::IndexType>| {
+        if columns.is_empty() {
+            "all columns".to_string()
+        } else {
+            columns
+                .iter()
+                .map(|col| core::utils::index_to_col(*col))
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    };
+
+    match operation {
+        lib::clean::Operation::DropDuplicates { columns: cols } => {
+            format!("Drop duplicate rows ({})", columns(cols))
+        }
+        lib::clean::Operation::DropMissing { columns: cols } => {
+            format!("Drop rows with missing values ({})", columns(cols))
+        }
+        lib::clean::Operation::FillMissing {
+            columns: cols,
+            fill,
+        } => {
+            let fill = match fill {
+                lib::clean::Fill::Constant(value) => format!("\"{value}\""),
+                lib::clean::Fill::Forward => "previous value".to_string(),
+                lib::clean::Fill::Mean => "mean".to_string(),
+            };
+            format!("Fill missing values with {fill} ({})", columns(cols))
+        }
+        lib::clean::Operation::Convert { columns: cols, to } => {
+            format!("Convert to {} ({})", conversion_label(to), columns(cols))
+        }
+        lib::clean::Operation::Extract { col, method } => {
+            let col = core::utils::index_to_col(*col);
+            match method {
+                lib::data::ExtractMethod::Regex(pattern) => {
+                    format!("Extract captures of \"{pattern}\" from {col}")
+                }
+                lib::data::ExtractMethod::Split { delimiter } => {
+                    format!("Split {col} on \"{delimiter}\"")
+                }
+            }
+        }
+    }
+}
+
+fn conversion_label(conversion: &lib::clean::Conversion) -> String {
+    match conversion {
+        lib::clean::Conversion::Number { .. } => "number".to_string(),
+        lib::clean::Conversion::Date { format } => format!("date \"{format}\""),
+        lib::clean::Conversion::Text => "text".to_string(),
     }
 }
 
 #[component]
 fn Workbook(workbook: state::Workbook) -> impl IntoView {
-    let active_sheet = expect_context::<ActiveSpreadsheetId>();
-    let formula_editor_vis = expect_context::<state::FormulaEditorVisibility>();
-
-    let sheet_names = workbook
-        .sheets
-        .read()
-        .iter()
-        .map(|sheet| sheet.name.get())
-        .collect::<Vec<_>>();
+    let state = expect_context::<state::State>();
+    let owner = expect_context::<state::WorkspaceOwner>();
 
     let active_sheet = workbook.active_sheet.read_only();
-    let workbook = workbook.clone();
-    let sheet = workbook
-        .sheets
-        .read()
-        .get(active_sheet.get())
-        .expect("sheet exists")
-        .clone();
+    let sheets = workbook.sheets.read_only();
+
+    // Formulas of the sheet may reference cells of other sheets that changed while it was hidden.
+    Effect::watch(
+        move || active_sheet.get(),
+        {
+            let id = workbook.id().clone();
+            move |sheet, _, _| {
+                let Ok(sheet) = core::data::IndexType::try_from(*sheet) else {
+                    return;
+                };
+                let options = state
+                    .settings
+                    .with_untracked(|settings| settings.eval_options());
+                let failed = formula::sync_sheet_formulas(
+                    &id,
+                    sheet,
+                    state.formulas,
+                    &state.datasets,
+                    &owner,
+                    options,
+                );
+                if failed > 0 {
+                    tracing::warn!("{failed} formula(s) could not be synced");
+                }
+            }
+        },
+        false,
+    );
 
     view! {
-        <Spreadsheet sheet />
+        <ReadOnlyBanner read_only=workbook.read_only() />
+        <Protection dataset=workbook.id().clone() />
+        <FreezeControls dataset=workbook.id().clone() sheet=active_sheet />
+        <ExportEvaluated dataset=workbook.id().clone() sheet=active_sheet />
+        <Diff dataset=workbook.id().clone() />
+        {move || {
+            sheets
+                .with(|sheets| sheets.get(active_sheet.get()).cloned())
+                .map(|sheet| view! { <Spreadsheet sheet /> })
+        }}
         <div>
-            <SheetList sheets=sheet_names />
+            <SheetList workbook />
         </div>
-        <FormulaEditor />
     }
 }
 
+/// Formula editor, docked to the bottom or right of the workspace.
+/// Its size is changed by dragging its inner edge.
 #[component]
 fn FormulaEditor() -> impl IntoView {
     let state = expect_context::<state::State>();
     let formula_editor_vis = expect_context::<state::FormulaEditorVisibility>();
     let active_formula = state.active_formula.read_only();
+    let dock = formula_editor_vis.dock;
+    let size = formula_editor_vis.size();
 
     let close_formula_editor = move |e: ev::MouseEvent| {
         if e.button() != types::MouseButton::Primary {
@@ -344,6 +3127,57 @@ fn FormulaEditor() -> impl IntoView {
         formula_editor_vis.set(false);
     };
 
+    let toggle_dock = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+
+        dock.update(|dock| {
+            *dock = match dock {
+                state::EditorDock::Bottom => state::EditorDock::Right,
+                state::EditorDock::Right => state::EditorDock::Bottom,
+            }
+        });
+    };
+
+    let resizing = RwSignal::new(None::<EditorResize>);
+    let resize_move = window_event_listener(ev::mousemove, move |e| {
+        if let Some(resize) = resizing.get_untracked() {
+            let offset = match dock.get_untracked() {
+                state::EditorDock::Bottom => resize.start - e.client_y(),
+                state::EditorDock::Right => resize.start - e.client_x(),
+            };
+            formula_editor_vis.set_size(resize.start_size + offset);
+        }
+    });
+    let resize_end = window_event_listener(ev::mouseup, move |_| {
+        if resizing.get_untracked().is_some() {
+            resizing.set(None);
+        }
+    });
+    on_cleanup(move || {
+        resize_move.remove();
+        resize_end.remove();
+    });
+
+    let start_resize = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+        e.prevent_default();
+
+        let start = match dock.get_untracked() {
+            state::EditorDock::Bottom => e.client_y(),
+            state::EditorDock::Right => e.client_x(),
+        };
+        resizing.set(Some(EditorResize {
+            start,
+            start_size: size.get_untracked(),
+        }));
+    };
+
+    let is_bottom = move || dock.get() == state::EditorDock::Bottom;
+
     Effect::watch(
         active_formula,
         {
@@ -363,52 +3197,193 @@ fn FormulaEditor() -> impl IntoView {
 
     view! {
         <div
-            class="flex bg-white dark:bg-secondary-800"
+            class="shrink-0 flex bg-white dark:bg-secondary-800"
+            class:flex-col=is_bottom
             class:hidden=move || !formula_editor_vis.get()
+            style:height=move || is_bottom().then(|| format!("{}px", size.get()))
+            style:width=move || (!is_bottom()).then(|| format!("{}px", size.get()))
         >
-            <formula::Editor />
-            <div>
-                <button type="button" class="cursor-pointer" on:mousedown=close_formula_editor>
-                    <Icon icon=icon::Close />
-                </button>
+            <div
+                class="shrink-0 hover:bg-primary-400"
+                class=(["h-1", "cursor-row-resize"], is_bottom)
+                class=(["w-1", "cursor-col-resize"], move || !is_bottom())
+                title="Drag to resize"
+                on:mousedown=start_resize
+            ></div>
+            <div class="grow min-h-0 min-w-0 flex gap-1 p-1">
+                <div class="grow min-w-0">
+                    <formula::Editor />
+                </div>
+                <div class="flex flex-col">
+                    <button
+                        type="button"
+                        class="cursor-pointer"
+                        title=move || {
+                            if is_bottom() { "Dock to the right" } else { "Dock to the bottom" }
+                        }
+                        on:mousedown=toggle_dock
+                    >
+                        <Icon icon=icon::Split />
+                    </button>
+                    <button type="button" class="cursor-pointer" on:mousedown=close_formula_editor>
+                        <Icon icon=icon::Close />
+                    </button>
+                </div>
             </div>
         </div>
     }
 }
 
+/// Formula editor being resized by dragging its edge.
+#[derive(Clone, Copy)]
+struct EditorResize {
+    /// Client coordinate along the resized axis where the drag started.
+    start: i32,
+    start_size: i32,
+}
+
 #[component]
 fn Spreadsheet(sheet: state::Spreadsheet) -> impl IntoView {
-    let state = expect_context::<state::State>();
+    let pane = expect_context::<state::Pane>();
     let owner = expect_context::<state::WorkspaceOwner>();
 
-    let canvas = state.canvas.cells();
-    owner.with(|| canvas.empty());
+    let canvas = pane.canvas;
     let size = sheet.size;
+    owner.with(|| {
+        let (rows, cols) = size.get_untracked();
+        canvas.fit(rows, cols);
+        canvas.cells().empty();
+    });
     move || {
-        for row_idx in 0..size.get().0 {
-            for col_idx in 0..size.get().1 {
-                let idx: core::data::CellIndex = (row_idx, col_idx).into();
-                let cell = canvas.get_cell(&idx).expect("canvas cell to exist");
-                if let Some(data) = sheet.cells.read().get(&idx) {
-                    cell.update(|cell| cell.insert(data.clone()))
-                }
-            }
-        }
+        let (rows, cols) = size.get();
+        canvas.grow_to(rows, cols);
+        let values = sheet.cells.with(|cells| {
+            cells
+                .iter()
+                .map(|(idx, value)| (idx.clone(), value.clone()))
+                .collect::<Vec<_>>()
+        });
+        owner.with(|| canvas.cells().set_all(values));
     }
 }
 
+/// Tabs switching the active sheet of a workbook.
 #[component]
-fn SheetList(sheets: Vec<String>) -> impl IntoView {
+fn SheetList(workbook: state::Workbook) -> impl IntoView {
+    let pane = expect_context::<state::Pane>();
+    let sheets = workbook.sheets.read_only();
+    let active_sheet = workbook.active_sheet;
+
+    let select = move |idx: usize| {
+        if active_sheet.get_untracked() == idx {
+            return;
+        }
+
+        // The selection refers to cells of the previous sheet.
+        pane.dataset.with_untracked(|dataset| {
+            if let state::ActiveDataset::Some { active_cell, .. } = dataset {
+                active_cell.set(state::ActiveCell::None);
+            }
+        });
+        active_sheet.set(idx);
+    };
+
     view! {
         <div class="flex">
-            {sheets
-                .into_iter()
-                .map(|name| view! { <div class="pl-2 pr-8">{name}</div> })
-                .collect::<Vec<_>>()}
+            {move || {
+                sheets
+                    .with(|sheets| {
+                        sheets
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, sheet)| {
+                                let name = sheet.name.read_only();
+                                view! {
+                                    <button
+                                        type="button"
+                                        class="pl-2 pr-8 cursor-pointer hover:bg-secondary-50 dark:hover:bg-secondary-700"
+                                        class=(
+                                            ["font-bold", "bg-secondary-50", "dark:bg-secondary-700"],
+                                            move || active_sheet.get() == idx,
+                                        )
+                                        on:mousedown=move |e| {
+                                            if e.button() == types::MouseButton::Primary {
+                                                select(idx);
+                                            }
+                                        }
+                                    >
+                                        {name}
+                                    </button>
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    })
+            }}
         </div>
     }
 }
 
+/// Tooltip describing a column.
+fn column_title(metadata: &lib::data::ColumnMetadata) -> String {
+    let mut lines = vec![];
+    if let Some(units) = metadata.units.as_ref() {
+        lines.push(format!("Units: {units}"));
+    }
+    if let Some(description) = metadata.description.as_ref() {
+        lines.push(description.clone());
+    }
+
+    lines.join("\n")
+}
+
+/// Load a dataset.
+/// The load can not be cancelled.
+pub async fn load_dataset(
+    path: PathBuf,
+    options: lib::data::ImportOptions,
+) -> Result<lib::data::Loaded, lib::data::error::Load> {
+    let id = lib::data::LoadId::next();
+    crate::ipc::invoke(lib::ipc::LoadDataset { id, path, options }).await
+}
+
+/// Write a copy of a sheet with its formulas replaced by their evaluated values.
+///
+/// # Returns
+/// Path the copy was written to, or `None` if no path was chosen.
+async fn export_evaluated_copy(
+    copy: lib::formula::EvaluatedCopy,
+) -> Result<Option<PathBuf>, lib::formula::error::WorkspaceOrder> {
+    crate::ipc::invoke(lib::ipc::ExportEvaluatedCopy { copy }).await
+}
+
+async fn run_workspace(
+    root: PathBuf,
+    orders: Vec<lib::formula::WorkspaceOrder>,
+    dry_run: bool,
+) -> Result<Vec<lib::audit::Entry>, Vec<(usize, lib::formula::error::WorkspaceOrder)>> {
+    crate::ipc::invoke(lib::ipc::RunWorkspace {
+        root,
+        orders,
+        dry_run,
+        atomic: false,
+        manifest: false,
+    })
+    .await
+}
+
+async fn diff_dataset(
+    path: PathBuf,
+    options: lib::data::ImportOptions,
+    sheets: Vec<lib::data::CellMap>,
+) -> Result<Vec<lib::diff::CellDiff>, lib::data::error::Load> {
+    crate::ipc::invoke(lib::ipc::DiffDataset {
+        path,
+        options,
+        sheets,
+    })
+    .await
+}
+
 fn expr_value_to_string(value: &core::expr::Value) -> String {
     match value {
         core::expr::Value::Empty => "".to_string(),
@@ -416,19 +3391,21 @@ fn expr_value_to_string(value: &core::expr::Value) -> String {
         core::expr::Value::Int(value) => value.to_string(),
         core::expr::Value::Float(value) => value.to_string(),
         core::expr::Value::Bool(value) => value.to_string(),
-        core::expr::Value::DateTime(date_time) => todo!(),
-        core::expr::Value::Duration(duration) => todo!(),
+        core::expr::Value::DateTime(date_time) => date_time.to_rfc3339(),
+        core::expr::Value::Duration(duration) => format!("{duration:?}"),
     }
 }
 
 fn expr_error_to_string(error: &core::expr::Error) -> String {
     match error {
-        core::expr::Error::Tokenize(kind) => todo!(),
-        core::expr::Error::Parse(kind) => todo!(),
+        core::expr::Error::Tokenize(_) => "#Syntax".to_string(),
+        core::expr::Error::Parse(_) => "#Parse".to_string(),
         core::expr::Error::Div0 => "#Div0".to_string(),
         core::expr::Error::InvalidNumber => "#NaN".to_string(),
         core::expr::Error::InvalidOperation(_) => "#BadOp".to_string(),
         core::expr::Error::Overflow => "#Overflow".to_string(),
         core::expr::Error::InvalidCellRef(cell_ref) => "#CellRef".to_string(),
+        core::expr::Error::EmptyCell(_) => "#Empty".to_string(),
+        core::expr::Error::Timeout => "#Timeout".to_string(),
     }
 }
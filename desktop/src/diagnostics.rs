@@ -0,0 +1,70 @@
+//! Diagnostic bundles for bug reports.
+use crate::{ipc, message, state};
+use futures::StreamExt;
+use hermes_desktop_lib as lib;
+use leptos::prelude::*;
+use lib::ipc::{Event, event::DiagnosticsRequested};
+
+/// Create a diagnostic bundle each time the user asks for one.
+///
+/// # Arguments
+/// + `state`: Workspace to describe in the bundle, if one is open.
+///   Its messages report the outcome.
+pub async fn watch_requests(state: Option<state::State>) {
+    let mut events = match tauri_sys::event::listen::<<DiagnosticsRequested as Event>::Payload>(
+        DiagnosticsRequested::NAME,
+    )
+    .await
+    {
+        Ok(events) => events,
+        Err(err) => {
+            tracing::error!(?err, "could not listen for diagnostic requests");
+            return;
+        }
+    };
+
+    while events.next().await.is_some() {
+        let workspace = state.as_ref().map(describe);
+        let created = ipc::invoke(lib::ipc::CreateDiagnostics { workspace }).await;
+        let Some(state) = state.as_ref() else {
+            if let Err(err) = created {
+                tracing::error!(?err, "could not create diagnostic bundle");
+            }
+            continue;
+        };
+
+        match created {
+            Ok(None) => {}
+            Ok(Some(_)) => state
+                .messages
+                .push(message::Message::success("Diagnostic bundle saved.")),
+            Err(err) => state.messages.push(message::Message::error_with_body(
+                "Could not create diagnostic bundle.",
+                err.to_string(),
+            )),
+        }
+    }
+}
+
+/// Describe the workspace without its data.
+fn describe(state: &state::State) -> lib::diagnostics::Workspace {
+    let datasets = state.datasets.with_untracked(|datasets| {
+        datasets
+            .iter()
+            .filter_map(|dataset| {
+                Some(lib::diagnostics::Dataset {
+                    path: state.directory_tree.get_file_path(dataset.id())?,
+                    sheets: dataset.sizes(),
+                    read_only: dataset.read_only(),
+                })
+            })
+            .collect()
+    });
+
+    lib::diagnostics::Workspace {
+        root: state.root_path().clone(),
+        datasets,
+        formulas: state.formulas.with_untracked(|formulas| formulas.len()),
+        settings: state.settings.get_untracked(),
+    }
+}
@@ -1,19 +1,37 @@
 //! File explorer.
-pub use active::ActiveFiles;
-pub use nav::FileTree;
-pub use output::OutputFiles;
+pub use active::{ActiveFiles, set_protected};
+pub use duplicates::DuplicateFiles;
+pub use join::{JoinTables, join_table_order};
+pub use nav::{FileTree, open_file};
+pub use output::{OutputFiles, output_file_order};
+pub use pivot::{PivotTables, pivot_table_order};
+pub use search::QuickOpen;
+pub use subset::{SubsetTables, subset_table_order};
 
 mod output {
-    use crate::{icon, types};
+    use crate::{icon, state, types};
+    use hermes_core as core;
+    use hermes_desktop_lib as lib;
     use leptos::{ev, prelude::*};
     use leptos_icons::Icon;
 
+    /// Number of rows shown in an output file preview.
+    const PREVIEW_ROWS: usize = 10;
+
     #[component]
     pub fn OutputFiles() -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let output_files = state.output_files;
+
         let add_output_file = move |e: ev::MouseEvent| {
             if e.button() != types::MouseButton::Primary {
                 return;
             }
+
+            output_files.update(|files| {
+                let name = format!("output-{}", files.len() + 1);
+                files.push(state::OutputFile::new(name));
+            });
         };
 
         view! {
@@ -21,164 +39,2750 @@ mod output {
                 <div class="pb flex gap-2">
                     <h2 class="grow font-bold uppercase">"Output files"</h2>
                     <div>
-                        <button type="button" class="btn-cmd cursor-pointer">
+                        <button
+                            type="button"
+                            class="btn-cmd cursor-pointer"
+                            on:mousedown=add_output_file
+                        >
                             <Icon icon=icon::Add />
                         </button>
                     </div>
                 </div>
-                <div></div>
+                <div>
+                    <For
+                        each=output_files.read_only()
+                        key=|file| file.id().clone()
+                        let:file
+                    >
+                        <OutputFile file />
+                    </For>
+                </div>
             </div>
         }
     }
-}
-
-mod active {
-    use crate::{LEVEL_PAD, LEVEL_PAD_UNIT, icon, state, state::FileResource, types};
-    use hermes_desktop_lib as lib;
-    use leptos::{ev, prelude::*};
-    use leptos_icons::Icon;
 
     #[component]
-    pub fn ActiveFiles() -> impl IntoView {
+    fn OutputFile(file: state::OutputFile) -> impl IntoView {
         let state = expect_context::<state::State>();
-        let directory_tree = state.directory_tree.clone();
+        let preview = RwSignal::new(false);
+
+        let remove = {
+            let output_files = state.output_files;
+            let id = file.id().clone();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+
+                output_files.update(|files| files.retain(|file| *file.id() != id));
+            }
+        };
+
+        let add_column = {
+            let columns = file.columns;
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+
+                columns.update(|columns| columns.push(state::OutputColumn::new()));
+            }
+        };
+
+        let toggle_preview = move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+
+            preview.update(|preview| *preview = !*preview);
+        };
 
+        let format = file.format;
+        let set_format = move |e: ev::Event| {
+            let idx = event_target_value(&e).parse::<usize>().unwrap_or(0);
+            if let Some(value) = lib::formula::OutputFormat::ALL.get(idx) {
+                format.set(*value);
+            }
+        };
+
+        let columns = file.columns;
         view! {
-            <div>
-                <div class="pb">
-                    <h2 class="font-bold uppercase">"Input files"</h2>
+            <div class="pb">
+                <div class="flex gap-1">
+                    <input
+                        class="input-compact grow min-w-0"
+                        prop:value=file.name
+                        on:change=move |e| file.name.set(event_target_value(&e))
+                    />
+                    <select class="input-compact" on:change=set_format>
+                        {lib::formula::OutputFormat::ALL
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, value)| {
+                                let value = *value;
+                                view! {
+                                    <option
+                                        value=idx.to_string()
+                                        selected=move || format.get() == value
+                                    >
+                                        {value.extension()}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </select>
+                    <button
+                        type="button"
+                        class="btn-cmd cursor-pointer"
+                        title="Remove output file"
+                        on:mousedown=remove
+                    >
+                        <Icon icon=icon::Close />
+                    </button>
                 </div>
-                <div>
-                    <For each=state.selected_files.read_only() key=|id| id.clone() let:id>
-                        {
-                            let file = directory_tree.get_file_by_id(&id).expect("file exists");
-                            view! { <File file /> }
-                        }
+                <div class="pl-2">
+                    <For each=columns.read_only() key=|column| column.id().clone() let:column>
+                        <OutputColumn columns column />
                     </For>
+                    <div class="flex gap-2 text-sm">
+                        <button type="button" class="cursor-pointer" on:mousedown=add_column>
+                            "+ Column"
+                        </button>
+                        <button type="button" class="cursor-pointer" on:mousedown=toggle_preview>
+                            {move || if preview.get() { "Hide preview" } else { "Preview" }}
+                        </button>
+                    </div>
+                    <Show when=move || preview.get()>
+                        <Preview file=file.clone() />
+                    </Show>
                 </div>
             </div>
         }
     }
 
     #[component]
-    fn File(file: state::File) -> impl IntoView {
-        let state = expect_context::<state::State>();
+    fn OutputColumn(
+        columns: RwSignal<Vec<state::OutputColumn>>,
+        column: state::OutputColumn,
+    ) -> impl IntoView {
+        let sources = column_sources();
+        let source = column.source;
+        let set_source = move |e: ev::Event| {
+            let value = event_target_value(&e)
+                .parse::<usize>()
+                .ok()
+                .and_then(|idx| sources.with_untracked(|sources| sources.get(idx).cloned()))
+                .map(|(source, _)| source);
 
-        let name = {
-            let name = file.name.read_only();
-            move || name.with(|name| name.to_string_lossy().to_string())
+            source.set(value);
         };
 
-        let path = {
-            state
-                .directory_tree
-                .get_file_path(file.id())
-                .expect("file exists")
-                .to_string_lossy()
-                .to_string()
-        };
+        let remove = {
+            let id = column.id().clone();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
 
-        let is_active = {
-            let active = state.active_dataset.read_only();
-            let id = file.id().clone();
-            move || {
-                active
-                    .read()
-                    .as_ref()
-                    .map(|active| *active == id)
-                    .unwrap_or(false)
+                columns.update(|columns| columns.retain(|column| *column.id() != id));
             }
         };
 
-        let activate = {
-            let id = file.id().clone();
-            let active = state.active_dataset;
-            move |e: ev::MouseEvent| {
-                if e.button() != types::MouseButton::Primary {
-                    return;
+        view! {
+            <div class="flex gap-1 pt-1">
+                <input
+                    class="input-compact w-1/3 min-w-0"
+                    placeholder="Header"
+                    prop:value=column.header
+                    on:change=move |e| column.header.set(event_target_value(&e))
+                />
+                <select class="input-compact grow min-w-0" on:change=set_source>
+                    <option value="" selected=move || source.read().is_none()>
+                        "(empty)"
+                    </option>
+                    {move || {
+                        sources
+                            .get()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(idx, (value, label))| {
+                                view! {
+                                    <option
+                                        value=idx.to_string()
+                                        selected=move || {
+                                            source.read().as_ref().is_some_and(|source| *source == value)
+                                        }
+                                    >
+                                        {label}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </select>
+                <button
+                    type="button"
+                    class="btn-cmd cursor-pointer"
+                    title="Remove column"
+                    on:mousedown=remove
+                >
+                    <Icon icon=icon::Close />
+                </button>
+            </div>
+        }
+    }
+
+    /// First rows of an output file, as they will be written.
+    #[component]
+    fn Preview(file: state::OutputFile) -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let records = move || {
+            file.columns.with(|columns| {
+                for column in columns {
+                    column.header.track();
+                    column.source.track();
                 }
+            });
+            state.datasets.track();
+            let create = output_file_order(&state, &file, std::path::PathBuf::new());
+            create
+                .records()
+                .into_iter()
+                .take(PREVIEW_ROWS + 1)
+                .collect::<Vec<_>>()
+        };
 
-                if !active
+        view! {
+            <div class="overflow-auto scrollbar-thin pt-1">
+                <table class="text-xs">
+                    {move || {
+                        records()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(idx, record)| {
+                                let cells = record
+                                    .into_iter()
+                                    .map(|value| {
+                                        view! { <td class="px-1 border border-secondary-100 dark:border-secondary-600">{value}</td> }
+                                    })
+                                    .collect::<Vec<_>>();
+                                view! { <tr class:font-bold=idx == 0>{cells}</tr> }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </table>
+            </div>
+        }
+    }
+
+    /// # Returns
+    /// Columns of loaded datasets available as output columns, with their labels.
+    fn column_sources() -> Memo<Vec<(state::ColumnSource, String)>> {
+        let state = expect_context::<state::State>();
+        Memo::new(move |_| {
+            state
+                .datasets
+                .read()
+                .iter()
+                .flat_map(|dataset| {
+                    let path = state
+                        .directory_tree
+                        .get_file_path(dataset.id())
+                        .map(|path| path.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let id = dataset.id().clone();
+                    (0..dataset.cols()).map(move |col| {
+                        let label = format!("{path} {}", core::utils::index_to_col(col));
+                        let source = state::ColumnSource {
+                            dataset: id.clone(),
+                            col,
+                        };
+                        (source, label)
+                    })
+                })
+                .collect()
+        })
+    }
+
+    /// Create the order to write an output file from the current dataset values.
+    /// Columns whose source is not set, or whose dataset is not loaded, are empty.
+    ///
+    /// # Arguments
+    /// + `root`: Project root the file is created in.
+    pub fn output_file_order(
+        state: &state::State,
+        file: &state::OutputFile,
+        root: std::path::PathBuf,
+    ) -> lib::formula::Create {
+        let columns = file
+            .columns
+            .read_untracked()
+            .iter()
+            .map(|column| {
+                let values = column
+                    .source
                     .read_untracked()
                     .as_ref()
-                    .map(|active| *active == id)
-                    .unwrap_or(false)
-                {
-                    let _ = active.write().insert(id.clone());
+                    .and_then(|source| {
+                        state
+                            .datasets
+                            .read_untracked()
+                            .iter()
+                            .find(|dataset| *dataset.id() == source.dataset)
+                            .map(|dataset| dataset.column_values(source.col))
+                    })
+                    .unwrap_or_default();
+
+                lib::formula::OutputColumn {
+                    header: column.header.get_untracked(),
+                    values,
                 }
+            })
+            .collect();
+
+        let mut inputs = file
+            .columns
+            .read_untracked()
+            .iter()
+            .filter_map(|column| {
+                column
+                    .source
+                    .read_untracked()
+                    .as_ref()
+                    .and_then(|source| state.file_path(&source.dataset))
+            })
+            .collect::<Vec<_>>();
+        inputs.sort();
+        inputs.dedup();
+
+        lib::formula::Create {
+            path: root.join(file.file_name()),
+            format: file.format.get_untracked(),
+            columns,
+            inputs,
+        }
+    }
+}
+
+mod pivot {
+    use crate::{icon, state, types};
+    use hermes_core as core;
+    use hermes_desktop_lib as lib;
+    use leptos::{ev, prelude::*};
+    use leptos_icons::Icon;
+
+    /// Number of groups shown in a pivot table preview.
+    const PREVIEW_ROWS: usize = 10;
+
+    #[component]
+    pub fn PivotTables() -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let pivot_tables = state.pivot_tables;
+
+        let add_pivot_table = move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
             }
+
+            pivot_tables.update(|tables| {
+                let name = format!("pivot-{}", tables.len() + 1);
+                tables.push(state::PivotTable::new(name));
+            });
         };
 
+        view! {
+            <div>
+                <div class="pb flex gap-2">
+                    <h2 class="grow font-bold uppercase">"Pivot tables"</h2>
+                    <div>
+                        <button
+                            type="button"
+                            class="btn-cmd cursor-pointer"
+                            on:mousedown=add_pivot_table
+                        >
+                            <Icon icon=icon::Add />
+                        </button>
+                    </div>
+                </div>
+                <div>
+                    <For
+                        each=pivot_tables.read_only()
+                        key=|table| table.id().clone()
+                        let:table
+                    >
+                        <PivotTable table />
+                    </For>
+                </div>
+            </div>
+        }
+    }
+
+    #[component]
+    fn PivotTable(table: state::PivotTable) -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let preview = RwSignal::new(false);
+
         let remove = {
-            let workbooks = state.datasets;
-            let selected = state.selected_files;
-            let active = state.active_dataset;
-            let id = file.id().clone();
+            let pivot_tables = state.pivot_tables;
+            let id = table.id().clone();
             move |e: ev::MouseEvent| {
                 if e.button() != types::MouseButton::Primary {
                     return;
                 }
-                e.stop_propagation();
 
-                if active.with_untracked(|active| {
-                    active.as_ref().map(|active| *active == id).unwrap_or(false)
-                }) {
-                    let idx = selected
-                        .read_untracked()
+                pivot_tables.update(|tables| tables.retain(|table| *table.id() != id));
+            }
+        };
+
+        let toggle_preview = move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+
+            preview.update(|preview| *preview = !*preview);
+        };
+
+        let datasets = {
+            let datasets = state.datasets;
+            let directory_tree = state.directory_tree.clone();
+            move || {
+                datasets.with(|datasets| {
+                    datasets
                         .iter()
-                        .position(|selected| *selected == id)
-                        .expect("file is selected");
-                    let remaining_len = selected.read_untracked().len() - 1;
-                    if remaining_len == 0 {
-                        active.write().take();
-                    } else if idx == remaining_len {
-                        let next = selected
-                            .read_untracked()
-                            .get(remaining_len - 1)
-                            .expect("file is last element")
-                            .clone();
-                        active.write().insert(next);
-                    } else {
-                        let next = selected
-                            .read_untracked()
-                            .get(idx + 1)
-                            .expect("file is not last element")
-                            .clone();
-                        active.write().insert(next);
-                    }
-                }
+                        .map(|dataset| {
+                            let path = directory_tree
+                                .get_file_path(dataset.id())
+                                .map(|path| path.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            (dataset.id().clone(), path)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            }
+        };
 
-                selected.update(|selected| {
-                    selected.retain(|rid| *rid != id);
+        let dataset = table.dataset;
+        let set_dataset = {
+            let datasets = state.datasets;
+            move |e: ev::Event| {
+                let value = event_target_value(&e);
+                let id = datasets.with_untracked(|datasets| {
+                    datasets
+                        .iter()
+                        .find(|dataset| dataset.id().to_string() == value)
+                        .map(|dataset| dataset.id().clone())
                 });
-                workbooks.update(|datasets| datasets.retain(|dataset| *dataset.file() != id));
+                dataset.set(id);
+            }
+        };
+
+        let format = table.format;
+        let set_format = move |e: ev::Event| {
+            let idx = event_target_value(&e).parse::<usize>().unwrap_or(0);
+            if let Some(value) = lib::formula::OutputFormat::ALL.get(idx) {
+                format.set(*value);
+            }
+        };
+
+        let pivot = table.pivot;
+        let set_group_by = move |e: ev::Event| {
+            let group_by = event_target_value(&e)
+                .split(',')
+                .filter_map(|col| core::utils::col_to_index(col.trim()))
+                .collect();
+            pivot.update(|pivot| pivot.group_by = group_by);
+        };
+
+        let set_value = move |e: ev::Event| {
+            if let Some(col) = core::utils::col_to_index(event_target_value(&e).trim()) {
+                pivot.update(|pivot| pivot.value = col);
+            }
+        };
+
+        let set_aggregate = move |e: ev::Event| {
+            let idx = event_target_value(&e).parse::<usize>().unwrap_or(0);
+            if let Some(value) = lib::pivot::Aggregate::ALL.get(idx) {
+                pivot.update(|pivot| pivot.aggregate = *value);
             }
         };
 
+        let group_by = move || {
+            pivot.with(|pivot| {
+                pivot
+                    .group_by
+                    .iter()
+                    .map(|col| core::utils::index_to_col(*col))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            })
+        };
+
         view! {
-            <div
-                class="flex gap-2 items-end px cursor-pointer group/file text-nowrap"
-                class=(["bg-secondary-50", "dark:bg-secondary-700"], is_active.clone())
-                style:padding-left=format!("{LEVEL_PAD}{LEVEL_PAD_UNIT}")
-                on:mousedown=activate
-            >
-                <div>{name}</div>
-                <small
-                    class="truncate text-secondary-700 dark:text-secondary-200"
-                    title=path.clone()
-                >
-                    {path.clone()}
-                </small>
-                <button class="hidden group-hover/file:block btn-cmd btn-secondary">
-                    <Icon icon=icon::Close on:mousedown=remove />
-                </button>
+            <div class="pb">
+                <div class="flex gap-1">
+                    <input
+                        class="input-compact grow min-w-0"
+                        prop:value=table.name
+                        on:change=move |e| table.name.set(event_target_value(&e))
+                    />
+                    <select class="input-compact" on:change=set_format>
+                        {lib::formula::OutputFormat::ALL
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, value)| {
+                                let value = *value;
+                                view! {
+                                    <option
+                                        value=idx.to_string()
+                                        selected=move || format.get() == value
+                                    >
+                                        {value.extension()}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </select>
+                    <button
+                        type="button"
+                        class="btn-cmd cursor-pointer"
+                        title="Remove pivot table"
+                        on:mousedown=remove
+                    >
+                        <Icon icon=icon::Close />
+                    </button>
+                </div>
+                <div class="pl-2 flex flex-col gap-1 pt-1 text-sm">
+                    <select class="input-compact min-w-0" on:change=set_dataset>
+                        <option value="" selected=move || dataset.read().is_none()>
+                            "(dataset)"
+                        </option>
+                        {move || {
+                            datasets()
+                                .into_iter()
+                                .map(|(id, path)| {
+                                    view! {
+                                        <option
+                                            value=id.to_string()
+                                            selected=move || {
+                                                dataset.read().as_ref().is_some_and(|dataset| *dataset == id)
+                                            }
+                                        >
+                                            {path}
+                                        </option>
+                                    }
+                                })
+                                .collect::<Vec<_>>()
+                        }}
+                    </select>
+                    <div class="flex gap-1">
+                        <input
+                            class="input-compact w-1/3 min-w-0"
+                            placeholder="Group by, e.g. A, B"
+                            title="Columns to group by"
+                            prop:value=group_by
+                            on:change=set_group_by
+                        />
+                        <select class="input-compact" on:change=set_aggregate>
+                            {lib::pivot::Aggregate::ALL
+                                .iter()
+                                .enumerate()
+                                .map(|(idx, value)| {
+                                    let value = *value;
+                                    view! {
+                                        <option
+                                            value=idx.to_string()
+                                            selected=move || pivot.read().aggregate == value
+                                        >
+                                            {value.label()}
+                                        </option>
+                                    }
+                                })
+                                .collect::<Vec<_>>()}
+                        </select>
+                        <input
+                            class="input-compact w-16 min-w-0"
+                            placeholder="Column"
+                            title="Column to aggregate"
+                            prop:value=move || core::utils::index_to_col(pivot.read().value)
+                            on:change=set_value
+                        />
+                    </div>
+                    <div class="flex gap-2">
+                        <label title="First row of the dataset holds column headers.">
+                            <input
+                                type="checkbox"
+                                prop:checked=move || pivot.read().header
+                                on:change=move |e| {
+                                    pivot.update(|pivot| pivot.header = event_target_checked(&e))
+                                }
+                            />
+                            " Header row"
+                        </label>
+                        <button type="button" class="cursor-pointer" on:mousedown=toggle_preview>
+                            {move || if preview.get() { "Hide preview" } else { "Preview" }}
+                        </button>
+                    </div>
+                    <Show when=move || preview.get()>
+                        <Preview table=table.clone() />
+                    </Show>
+                </div>
+            </div>
+        }
+    }
+
+    /// First groups of a pivot table, as they will be written.
+    #[component]
+    fn Preview(table: state::PivotTable) -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let records = move || {
+            table.pivot.track();
+            table.dataset.track();
+            state.datasets.track();
+            let create = pivot_table_order(&state, &table, std::path::PathBuf::new());
+            create
+                .records()
+                .into_iter()
+                .take(PREVIEW_ROWS + 1)
+                .collect::<Vec<_>>()
+        };
+
+        view! {
+            <div class="overflow-auto scrollbar-thin">
+                <table class="text-xs">
+                    {move || {
+                        records()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(idx, record)| {
+                                let cells = record
+                                    .into_iter()
+                                    .map(|value| {
+                                        view! { <td class="px-1 border border-secondary-100 dark:border-secondary-600">{value}</td> }
+                                    })
+                                    .collect::<Vec<_>>();
+                                view! { <tr class:font-bold=idx == 0>{cells}</tr> }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </table>
             </div>
         }
     }
+
+    /// Create the order to write a pivot table from the current dataset values.
+    /// The table is empty if its dataset is not set or not loaded.
+    ///
+    /// # Arguments
+    /// + `root`: Project root the file is created in.
+    pub fn pivot_table_order(
+        state: &state::State,
+        table: &state::PivotTable,
+        root: std::path::PathBuf,
+    ) -> lib::formula::Create {
+        let cells = table
+            .dataset
+            .read_untracked()
+            .as_ref()
+            .and_then(|id| {
+                state
+                    .datasets
+                    .read_untracked()
+                    .iter()
+                    .find(|dataset| dataset.id() == id)
+                    .and_then(|dataset| dataset.values().into_iter().next())
+            })
+            .unwrap_or_default();
+
+        lib::formula::Create {
+            path: root.join(table.file_name()),
+            format: table.format.get_untracked(),
+            columns: table.pivot.with_untracked(|pivot| pivot.apply(&cells)),
+            inputs: table
+                .dataset
+                .read_untracked()
+                .as_ref()
+                .and_then(|id| state.file_path(id))
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+mod join {
+    use crate::{icon, state, types};
+    use hermes_core as core;
+    use hermes_desktop_lib as lib;
+    use leptos::{ev, html, prelude::*};
+    use leptos_icons::Icon;
+
+    /// Number of rows shown in a join preview.
+    const PREVIEW_ROWS: usize = 10;
+
+    #[component]
+    pub fn JoinTables() -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let join_tables = state.join_tables;
+        let dialog_visible = RwSignal::new(false);
+
+        let show_dialog = move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+
+            dialog_visible.set(true);
+        };
+
+        view! {
+            <div>
+                <div class="pb flex gap-2">
+                    <h2 class="grow font-bold uppercase">"Joins"</h2>
+                    <div>
+                        <button
+                            type="button"
+                            class="btn-cmd cursor-pointer"
+                            on:mousedown=show_dialog
+                        >
+                            <Icon icon=icon::Add />
+                        </button>
+                    </div>
+                </div>
+                <div>
+                    <For each=join_tables.read_only() key=|table| table.id().clone() let:table>
+                        <JoinTable table />
+                    </For>
+                </div>
+                <NewJoin visible=dialog_visible />
+            </div>
+        }
+    }
+
+    /// Configure a join of two datasets.
+    #[component]
+    fn NewJoin(visible: RwSignal<bool>) -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let left_node = NodeRef::<html::Select>::new();
+        let right_node = NodeRef::<html::Select>::new();
+        let left_keys_node = NodeRef::<html::Input>::new();
+        let right_keys_node = NodeRef::<html::Input>::new();
+        let kind_node = NodeRef::<html::Select>::new();
+        let header_node = NodeRef::<html::Input>::new();
+        let (error, set_error) = signal(None);
+
+        let datasets = {
+            let datasets = state.datasets;
+            let directory_tree = state.directory_tree.clone();
+            move || {
+                datasets.with(|datasets| {
+                    datasets
+                        .iter()
+                        .map(|dataset| {
+                            let path = directory_tree
+                                .get_file_path(dataset.id())
+                                .map(|path| path.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            (dataset.id().clone(), path)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            }
+        };
+
+        let create = {
+            let datasets = state.datasets;
+            let join_tables = state.join_tables;
+            move |e: ev::SubmitEvent| {
+                e.prevent_default();
+                let (
+                    Some(left),
+                    Some(right),
+                    Some(left_keys),
+                    Some(right_keys),
+                    Some(kind),
+                    Some(header),
+                ) = (
+                    left_node.get_untracked(),
+                    right_node.get_untracked(),
+                    left_keys_node.get_untracked(),
+                    right_keys_node.get_untracked(),
+                    kind_node.get_untracked(),
+                    header_node.get_untracked(),
+                )
+                else {
+                    return;
+                };
+
+                let dataset = |value: String| {
+                    datasets.with_untracked(|datasets| {
+                        datasets
+                            .iter()
+                            .find(|dataset| dataset.id().to_string() == value)
+                            .map(|dataset| dataset.id().clone())
+                    })
+                };
+                let kind = kind
+                    .value()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|idx| lib::data::JoinKind::ALL.get(idx).copied())
+                    .unwrap_or_default();
+
+                let join = new_join(
+                    dataset(left.value()),
+                    dataset(right.value()),
+                    &left_keys.value(),
+                    &right_keys.value(),
+                    kind,
+                    header.checked(),
+                );
+                match join {
+                    Ok((left, right, join)) => {
+                        join_tables.update(|tables| {
+                            let name = format!("join-{}", tables.len() + 1);
+                            tables.push(state::JoinTable::new(name, left, right, join));
+                        });
+                        set_error(None);
+                        visible.set(false);
+                    }
+                    Err(err) => set_error(Some(new_join_error_message(err))),
+                }
+            }
+        };
+
+        let close_on_escape = move |e: ev::KeyboardEvent| {
+            if e.key() == "Escape" {
+                e.prevent_default();
+                visible.set(false);
+            }
+        };
+
+        let close = move |_: ev::MouseEvent| visible.set(false);
+
+        let dataset_options = move || {
+            datasets()
+                .into_iter()
+                .map(|(id, path)| view! { <option value=id.to_string()>{path}</option> })
+                .collect::<Vec<_>>()
+        };
+
+        view! {
+            <div class:hidden=move || !visible.get() on:keydown=close_on_escape>
+                <div class="fixed inset-0 z-30" on:mousedown=close></div>
+                <form
+                    class="fixed z-40 top-12 left-1/2 -translate-x-1/2 w-1/2 p-1 flex flex-col gap-1 \
+                    bg-white dark:bg-secondary-800 border border-secondary-100 dark:border-secondary-600"
+                    on:submit=create
+                >
+                    <h2 class="font-bold uppercase">"New join"</h2>
+                    <div class="flex gap-1">
+                        <select node_ref=left_node class="input-compact grow min-w-0">
+                            {dataset_options.clone()}
+                        </select>
+                        <input
+                            node_ref=left_keys_node
+                            type="text"
+                            class="input-compact w-1/3 min-w-0"
+                            placeholder="Left keys, e.g. A, B"
+                        />
+                    </div>
+                    <div class="flex gap-1">
+                        <select node_ref=right_node class="input-compact grow min-w-0">
+                            {dataset_options}
+                        </select>
+                        <input
+                            node_ref=right_keys_node
+                            type="text"
+                            class="input-compact w-1/3 min-w-0"
+                            placeholder="Right keys, e.g. C, A"
+                        />
+                    </div>
+                    <div class="flex gap-2">
+                        <select node_ref=kind_node class="input-compact">
+                            {lib::data::JoinKind::ALL
+                                .iter()
+                                .enumerate()
+                                .map(|(idx, kind)| {
+                                    view! { <option value=idx.to_string()>{kind.label()}</option> }
+                                })
+                                .collect::<Vec<_>>()}
+                        </select>
+                        <label title="First row of each dataset holds column headers.">
+                            <input node_ref=header_node type="checkbox" checked=true />
+                            " Header row"
+                        </label>
+                        <button type="submit" class="btn-cmd btn-primary">
+                            "Create"
+                        </button>
+                    </div>
+                    <small class="color-brand-red-600">{error}</small>
+                </form>
+            </div>
+        }
+    }
+
+    #[component]
+    fn JoinTable(table: state::JoinTable) -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let preview = RwSignal::new(false);
+
+        let remove = {
+            let join_tables = state.join_tables;
+            let id = table.id().clone();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+
+                join_tables.update(|tables| tables.retain(|table| *table.id() != id));
+            }
+        };
+
+        let toggle_preview = move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+
+            preview.update(|preview| *preview = !*preview);
+        };
+
+        let format = table.format;
+        let set_format = move |e: ev::Event| {
+            let idx = event_target_value(&e).parse::<usize>().unwrap_or(0);
+            if let Some(value) = lib::formula::OutputFormat::ALL.get(idx) {
+                format.set(*value);
+            }
+        };
+
+        let summary = {
+            let path = |id: &state::ResourceId| {
+                state
+                    .directory_tree
+                    .get_file_path(id)
+                    .map(|path| path.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            };
+            let keys = |keys: &Vec<core::data::IndexType>| {
+                keys.iter()
+                    .map(|col| core::utils::index_to_col(*col))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+
+            format!(
+                "{} {} join {} on {} = {}",
+                path(&table.left),
+                table.join.kind.label(),
+                path(&table.right),
+                keys(&table.join.left_keys),
+                keys(&table.join.right_keys),
+            )
+        };
+
+        view! {
+            <div class="pb">
+                <div class="flex gap-1">
+                    <input
+                        class="input-compact grow min-w-0"
+                        prop:value=table.name
+                        on:change=move |e| table.name.set(event_target_value(&e))
+                    />
+                    <select class="input-compact" on:change=set_format>
+                        {lib::formula::OutputFormat::ALL
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, value)| {
+                                let value = *value;
+                                view! {
+                                    <option
+                                        value=idx.to_string()
+                                        selected=move || format.get() == value
+                                    >
+                                        {value.extension()}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </select>
+                    <button
+                        type="button"
+                        class="btn-cmd cursor-pointer"
+                        title="Remove join"
+                        on:mousedown=remove
+                    >
+                        <Icon icon=icon::Close />
+                    </button>
+                </div>
+                <div class="pl-2 text-sm">
+                    <div class="truncate" title=summary.clone()>
+                        {summary.clone()}
+                    </div>
+                    <button type="button" class="cursor-pointer" on:mousedown=toggle_preview>
+                        {move || if preview.get() { "Hide preview" } else { "Preview" }}
+                    </button>
+                    <Show when=move || preview.get()>
+                        <Preview table=table.clone() />
+                    </Show>
+                </div>
+            </div>
+        }
+    }
+
+    /// First rows of a join, as they will be written.
+    #[component]
+    fn Preview(table: state::JoinTable) -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let records = move || {
+            state.datasets.track();
+            let create = join_table_order(&state, &table, std::path::PathBuf::new());
+            create
+                .records()
+                .into_iter()
+                .take(PREVIEW_ROWS + 1)
+                .collect::<Vec<_>>()
+        };
+
+        view! {
+            <div class="overflow-auto scrollbar-thin">
+                <table class="text-xs">
+                    {move || {
+                        records()
+                            .into_iter()
+                            .enumerate()
+                            .map(|(idx, record)| {
+                                let cells = record
+                                    .into_iter()
+                                    .map(|value| {
+                                        view! { <td class="px-1 border border-secondary-100 dark:border-secondary-600">{value}</td> }
+                                    })
+                                    .collect::<Vec<_>>();
+                                view! { <tr class:font-bold=idx == 0>{cells}</tr> }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </table>
+            </div>
+        }
+    }
+
+    /// Create the order to write a join from the current dataset values.
+    /// Datasets which are not loaded are treated as empty.
+    ///
+    /// # Arguments
+    /// + `root`: Project root the file is created in.
+    pub fn join_table_order(
+        state: &state::State,
+        table: &state::JoinTable,
+        root: std::path::PathBuf,
+    ) -> lib::formula::Create {
+        let values = |id: &state::ResourceId| {
+            state
+                .datasets
+                .read_untracked()
+                .iter()
+                .find(|dataset| dataset.id() == id)
+                .and_then(|dataset| dataset.values().into_iter().next())
+                .unwrap_or_default()
+        };
+
+        lib::formula::Create {
+            path: root.join(table.file_name()),
+            format: table.format.get_untracked(),
+            columns: table
+                .join
+                .apply(&values(&table.left), &values(&table.right)),
+            inputs: [&table.left, &table.right]
+                .into_iter()
+                .filter_map(|id| state.file_path(id))
+                .collect(),
+        }
+    }
+
+    /// Validate the configuration of a join.
+    ///
+    /// # Arguments
+    /// + `left_keys`, `right_keys`: Comma separated column labels.
+    fn new_join(
+        left: Option<state::ResourceId>,
+        right: Option<state::ResourceId>,
+        left_keys: &str,
+        right_keys: &str,
+        kind: lib::data::JoinKind,
+        header: bool,
+    ) -> Result<(state::ResourceId, state::ResourceId, lib::data::Join), error::NewJoin> {
+        let (Some(left), Some(right)) = (left, right) else {
+            return Err(error::NewJoin::DatasetNotSelected);
+        };
+
+        let parse_keys = |keys: &str| {
+            keys.split(',')
+                .map(|col| core::utils::col_to_index(col.trim()))
+                .collect::<Option<Vec<_>>>()
+                .filter(|keys| !keys.is_empty())
+                .ok_or(error::NewJoin::InvalidKeys)
+        };
+        let left_keys = parse_keys(left_keys)?;
+        let right_keys = parse_keys(right_keys)?;
+        if left_keys.len() != right_keys.len() {
+            return Err(error::NewJoin::KeyCountMismatch);
+        }
+
+        Ok((
+            left,
+            right,
+            lib::data::Join {
+                kind,
+                left_keys,
+                right_keys,
+                header,
+            },
+        ))
+    }
+
+    fn new_join_error_message(err: error::NewJoin) -> &'static str {
+        match err {
+            error::NewJoin::DatasetNotSelected => "Select both datasets.",
+            error::NewJoin::InvalidKeys => "Keys must be column labels, e.g. A, B.",
+            error::NewJoin::KeyCountMismatch => "Both datasets need the same number of keys.",
+        }
+    }
+
+    mod error {
+        #[derive(Debug)]
+        pub enum NewJoin {
+            /// One of the datasets was not selected.
+            DatasetNotSelected,
+
+            /// Key columns could not be parsed.
+            InvalidKeys,
+
+            /// The datasets have a different number of key columns.
+            KeyCountMismatch,
+        }
+    }
+}
+
+mod subset {
+    use crate::{icon, state, types};
+    use hermes_desktop_lib as lib;
+    use leptos::{ev, html, prelude::*};
+    use leptos_icons::Icon;
+
+    /// Number of rows shown in a subset preview.
+    const PREVIEW_ROWS: usize = 10;
+
+    #[component]
+    pub fn SubsetTables() -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let subset_tables = state.subset_tables;
+        let dialog_visible = RwSignal::new(false);
+
+        let show_dialog = move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+
+            dialog_visible.set(true);
+        };
+
+        view! {
+            <div>
+                <div class="pb flex gap-2">
+                    <h2 class="grow font-bold uppercase">"Subsets"</h2>
+                    <div>
+                        <button
+                            type="button"
+                            class="btn-cmd cursor-pointer"
+                            on:mousedown=show_dialog
+                        >
+                            <Icon icon=icon::Add />
+                        </button>
+                    </div>
+                </div>
+                <div>
+                    <For each=subset_tables.read_only() key=|table| table.id().clone() let:table>
+                        <SubsetTable table />
+                    </For>
+                </div>
+                <NewSubset visible=dialog_visible />
+            </div>
+        }
+    }
+
+    /// Configure a subset of a dataset's rows.
+    #[component]
+    fn NewSubset(visible: RwSignal<bool>) -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let dataset_node = NodeRef::<html::Select>::new();
+        let kind_node = NodeRef::<html::Select>::new();
+        let count_node = NodeRef::<html::Input>::new();
+        let seed_node = NodeRef::<html::Input>::new();
+        let expression_node = NodeRef::<html::Input>::new();
+        let header_node = NodeRef::<html::Input>::new();
+        let (kind, set_kind) = signal("sample".to_string());
+        let (error, set_error) = signal(None);
+
+        let datasets = {
+            let datasets = state.datasets;
+            let directory_tree = state.directory_tree.clone();
+            move || {
+                datasets.with(|datasets| {
+                    datasets
+                        .iter()
+                        .map(|dataset| {
+                            let path = directory_tree
+                                .get_file_path(dataset.id())
+                                .map(|path| path.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            (dataset.id().clone(), path)
+                        })
+                        .collect::<Vec<_>>()
+                })
+            }
+        };
+
+        let create = {
+            let state = state.clone();
+            move |e: ev::SubmitEvent| {
+                e.prevent_default();
+                let (
+                    Some(dataset),
+                    Some(kind),
+                    Some(count),
+                    Some(seed),
+                    Some(expression),
+                    Some(header),
+                ) = (
+                    dataset_node.get_untracked(),
+                    kind_node.get_untracked(),
+                    count_node.get_untracked(),
+                    seed_node.get_untracked(),
+                    expression_node.get_untracked(),
+                    header_node.get_untracked(),
+                )
+                else {
+                    return;
+                };
+
+                let dataset = state.datasets.with_untracked(|datasets| {
+                    datasets
+                        .iter()
+                        .find(|current| current.id().to_string() == dataset.value())
+                        .cloned()
+                });
+                let Some(dataset) = dataset else {
+                    set_error(Some("Select a dataset.".to_string()));
+                    return;
+                };
+
+                let subset = new_subset(
+                    &kind.value(),
+                    &count.value(),
+                    &seed.value(),
+                    &expression.value(),
+                    header.checked(),
+                );
+                let subset = match subset {
+                    Ok(subset) => subset,
+                    Err(err) => {
+                        set_error(Some(new_subset_error_message(err).to_string()));
+                        return;
+                    }
+                };
+
+                // Check the expression against the current values before accepting it.
+                let values = dataset.values().into_iter().next().unwrap_or_default();
+                if let Err(err) = subset.apply(&values) {
+                    set_error(Some(err.to_string()));
+                    return;
+                }
+
+                state.subset_tables.update(|tables| {
+                    let name = format!("subset-{}", tables.len() + 1);
+                    tables.push(state::SubsetTable::new(name, dataset.id().clone(), subset));
+                });
+                set_error(None);
+                visible.set(false);
+            }
+        };
+
+        let close_on_escape = move |e: ev::KeyboardEvent| {
+            if e.key() == "Escape" {
+                e.prevent_default();
+                visible.set(false);
+            }
+        };
+
+        let close = move |_: ev::MouseEvent| visible.set(false);
+
+        view! {
+            <div class:hidden=move || !visible.get() on:keydown=close_on_escape>
+                <div class="fixed inset-0 z-30" on:mousedown=close></div>
+                <form
+                    class="fixed z-40 top-12 left-1/2 -translate-x-1/2 w-1/2 p-1 flex flex-col gap-1 \
+                    bg-white dark:bg-secondary-800 border border-secondary-100 dark:border-secondary-600"
+                    on:submit=create
+                >
+                    <h2 class="font-bold uppercase">"New subset"</h2>
+                    <select node_ref=dataset_node class="input-compact min-w-0">
+                        {move || {
+                            datasets()
+                                .into_iter()
+                                .map(|(id, path)| {
+                                    view! { <option value=id.to_string()>{path}</option> }
+                                })
+                                .collect::<Vec<_>>()
+                        }}
+                    </select>
+                    <div class="flex gap-1">
+                        <select
+                            node_ref=kind_node
+                            class="input-compact"
+                            on:change=move |e| set_kind(event_target_value(&e))
+                        >
+                            <option value="sample">"Random sample"</option>
+                            <option value="first">"First rows"</option>
+                            <option value="last">"Last rows"</option>
+                            <option value="matching">"Rows matching"</option>
+                        </select>
+                        <input
+                            node_ref=count_node
+                            type="number"
+                            min="0"
+                            class="input-compact w-20"
+                            placeholder="Rows"
+                            class:hidden=move || kind.with(|kind| kind == "matching")
+                        />
+                        <input
+                            node_ref=seed_node
+                            type="number"
+                            min="0"
+                            class="input-compact w-20"
+                            placeholder="Seed"
+                            title="Samples with the same seed select the same rows."
+                            class:hidden=move || kind.with(|kind| kind != "sample")
+                        />
+                        <input
+                            node_ref=expression_node
+                            type="text"
+                            class="input-compact grow min-w-0"
+                            placeholder="Condition for the first data row, e.g. A2 > 10"
+                            class:hidden=move || kind.with(|kind| kind != "matching")
+                        />
+                    </div>
+                    <div class="flex gap-2">
+                        <label title="First row holds column headers.">
+                            <input node_ref=header_node type="checkbox" checked=true />
+                            " Header row"
+                        </label>
+                        <button type="submit" class="btn-cmd btn-primary">
+                            "Create"
+                        </button>
+                    </div>
+                    <small class="color-brand-red-600">{error}</small>
+                </form>
+            </div>
+        }
+    }
+
+    #[component]
+    fn SubsetTable(table: state::SubsetTable) -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let preview = RwSignal::new(false);
+
+        let remove = {
+            let subset_tables = state.subset_tables;
+            let id = table.id().clone();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+
+                subset_tables.update(|tables| tables.retain(|table| *table.id() != id));
+            }
+        };
+
+        let toggle_preview = move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+
+            preview.update(|preview| *preview = !*preview);
+        };
+
+        let format = table.format;
+        let set_format = move |e: ev::Event| {
+            let idx = event_target_value(&e).parse::<usize>().unwrap_or(0);
+            if let Some(value) = lib::formula::OutputFormat::ALL.get(idx) {
+                format.set(*value);
+            }
+        };
+
+        let summary = {
+            let path = state
+                .directory_tree
+                .get_file_path(&table.dataset)
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let rows = match &table.subset.rows {
+                lib::data::SubsetRows::Sample { count, seed } => {
+                    format!("{count} random rows (seed {seed})")
+                }
+                lib::data::SubsetRows::First(count) => format!("first {count} rows"),
+                lib::data::SubsetRows::Last(count) => format!("last {count} rows"),
+                lib::data::SubsetRows::Matching(expression) => {
+                    format!("rows matching {expression}")
+                }
+            };
+
+            format!("{rows} of {path}")
+        };
+
+        view! {
+            <div class="pb">
+                <div class="flex gap-1">
+                    <input
+                        class="input-compact grow min-w-0"
+                        prop:value=table.name
+                        on:change=move |e| table.name.set(event_target_value(&e))
+                    />
+                    <select class="input-compact" on:change=set_format>
+                        {lib::formula::OutputFormat::ALL
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, value)| {
+                                let value = *value;
+                                view! {
+                                    <option
+                                        value=idx.to_string()
+                                        selected=move || format.get() == value
+                                    >
+                                        {value.extension()}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </select>
+                    <button
+                        type="button"
+                        class="btn-cmd cursor-pointer"
+                        title="Remove subset"
+                        on:mousedown=remove
+                    >
+                        <Icon icon=icon::Close />
+                    </button>
+                </div>
+                <div class="pl-2 text-sm">
+                    <div class="truncate" title=summary.clone()>
+                        {summary.clone()}
+                    </div>
+                    <button type="button" class="cursor-pointer" on:mousedown=toggle_preview>
+                        {move || if preview.get() { "Hide preview" } else { "Preview" }}
+                    </button>
+                    <Show when=move || preview.get()>
+                        <Preview table=table.clone() />
+                    </Show>
+                </div>
+            </div>
+        }
+    }
+
+    /// First rows of a subset, as they will be written.
+    #[component]
+    fn Preview(table: state::SubsetTable) -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let records = move || {
+            state.datasets.track();
+            subset_table_order(&state, &table, std::path::PathBuf::new()).map(|create| {
+                create
+                    .records()
+                    .into_iter()
+                    .take(PREVIEW_ROWS + 1)
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        view! {
+            <div class="overflow-auto scrollbar-thin">
+                {move || match records() {
+                    Ok(records) => {
+                        let rows = records
+                            .into_iter()
+                            .enumerate()
+                            .map(|(idx, record)| {
+                                let cells = record
+                                    .into_iter()
+                                    .map(|value| {
+                                        view! { <td class="px-1 border border-secondary-100 dark:border-secondary-600">{value}</td> }
+                                    })
+                                    .collect::<Vec<_>>();
+                                view! { <tr class:font-bold=idx == 0>{cells}</tr> }
+                            })
+                            .collect::<Vec<_>>();
+                        view! { <table class="text-xs">{rows}</table> }.into_any()
+                    }
+                    Err(err) => {
+                        view! { <small class="color-brand-red-600">{err.to_string()}</small> }
+                            .into_any()
+                    }
+                }}
+            </div>
+        }
+    }
+
+    /// Create the order to write a subset from the current dataset values.
+    /// Datasets which are not loaded are treated as empty.
+    ///
+    /// # Arguments
+    /// + `root`: Project root the file is created in.
+    pub fn subset_table_order(
+        state: &state::State,
+        table: &state::SubsetTable,
+        root: std::path::PathBuf,
+    ) -> Result<lib::formula::Create, lib::data::error::Subset> {
+        let values = state
+            .datasets
+            .read_untracked()
+            .iter()
+            .find(|dataset| *dataset.id() == table.dataset)
+            .and_then(|dataset| dataset.values().into_iter().next())
+            .unwrap_or_default();
+
+        Ok(lib::formula::Create {
+            path: root.join(table.file_name()),
+            format: table.format.get_untracked(),
+            columns: table.subset.apply(&values)?,
+            inputs: state.file_path(&table.dataset).into_iter().collect(),
+        })
+    }
+
+    /// Validate the configuration of a subset.
+    ///
+    /// # Arguments
+    /// + `kind`: `sample`, `first`, `last`, or `matching`.
+    fn new_subset(
+        kind: &str,
+        count: &str,
+        seed: &str,
+        expression: &str,
+        header: bool,
+    ) -> Result<lib::data::Subset, error::NewSubset> {
+        let count = || {
+            count
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| error::NewSubset::InvalidCount)
+        };
+        let rows = match kind {
+            "sample" => {
+                let seed = seed.trim();
+                let seed = if seed.is_empty() {
+                    0
+                } else {
+                    seed.parse::<u64>()
+                        .map_err(|_| error::NewSubset::InvalidSeed)?
+                };
+                lib::data::SubsetRows::Sample {
+                    count: count()?,
+                    seed,
+                }
+            }
+            "first" => lib::data::SubsetRows::First(count()?),
+            "last" => lib::data::SubsetRows::Last(count()?),
+            "matching" if expression.trim().is_empty() => {
+                return Err(error::NewSubset::EmptyExpression);
+            }
+            "matching" => lib::data::SubsetRows::Matching(expression.trim().to_string()),
+            _ => return Err(error::NewSubset::InvalidKind),
+        };
+
+        Ok(lib::data::Subset { rows, header })
+    }
+
+    fn new_subset_error_message(err: error::NewSubset) -> &'static str {
+        match err {
+            error::NewSubset::InvalidKind => "Select how rows are chosen.",
+            error::NewSubset::InvalidCount => "Number of rows must be a whole number.",
+            error::NewSubset::InvalidSeed => "Seed must be a whole number.",
+            error::NewSubset::EmptyExpression => "Enter a condition, e.g. A2 > 10.",
+        }
+    }
+
+    mod error {
+        #[derive(Debug)]
+        pub enum NewSubset {
+            /// How rows are chosen is not known.
+            InvalidKind,
+
+            /// Number of rows could not be parsed.
+            InvalidCount,
+
+            /// Seed could not be parsed.
+            InvalidSeed,
+
+            /// No expression was given to match rows.
+            EmptyExpression,
+        }
+    }
+}
+
+mod active {
+    use crate::{
+        LEVEL_PAD, LEVEL_PAD_UNIT, explorer::nav, formula, icon, message, state,
+        state::FileResource, types,
+    };
+    use hermes_desktop_lib as lib;
+    use leptos::{ev, prelude::*, task::spawn_local};
+    use leptos_icons::Icon;
+    use std::{collections::HashSet, path::PathBuf};
+
+    /// Files selected for group operations.
+    #[derive(Clone, Copy)]
+    struct Selection {
+        files: RwSignal<HashSet<state::ResourceId>>,
+
+        /// File ranges are selected from.
+        anchor: RwSignal<Option<state::ResourceId>>,
+    }
+
+    impl Selection {
+        fn new() -> Self {
+            Self {
+                files: RwSignal::new(HashSet::new()),
+                anchor: RwSignal::new(None),
+            }
+        }
+
+        /// # Returns
+        /// Selected files in the order they are listed.
+        fn ordered(&self, state: &state::State) -> Vec<state::ResourceId> {
+            let files = self.files.get_untracked();
+            state
+                .selected_files
+                .read_untracked()
+                .iter()
+                .filter(|file| files.contains(*file))
+                .cloned()
+                .collect()
+        }
+    }
+
+    /// File being dragged to reorder the list.
+    #[derive(Clone, Copy, derive_more::Deref)]
+    struct DragFile(RwSignal<Option<state::ResourceId>>);
+
+    #[component]
+    pub fn ActiveFiles() -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let directory_tree = state.directory_tree.clone();
+        let selection = Selection::new();
+        provide_context(selection);
+        provide_context(DragFile(RwSignal::new(None)));
+
+        view! {
+            <div>
+                <div class="pb">
+                    <h2 class="font-bold uppercase">"Input files"</h2>
+                </div>
+                <Show when=move || selection.files.with(|files| files.len() > 1)>
+                    <GroupActions />
+                </Show>
+                <div>
+                    <For each=state.selected_files.read_only() key=|id| id.clone() let:id>
+                        {
+                            let file = directory_tree.get_file_by_id(&id).expect("file exists");
+                            view! { <File file /> }
+                        }
+                    </For>
+                </div>
+            </div>
+        }
+    }
+
+    /// Operations on all selected files.
+    #[component]
+    fn GroupActions() -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let owner = expect_context::<state::WorkspaceOwner>();
+        let selection = expect_context::<Selection>();
+        let (template, set_template) = signal(String::new());
+
+        let count = move || selection.files.with(|files| files.len());
+
+        let remove_all = {
+            let state = state.clone();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+
+                for id in selection.ordered(&state) {
+                    remove_file(&state, &id);
+                }
+                selection.files.update(|files| files.clear());
+                selection.anchor.set(None);
+            }
+        };
+
+        let reload_all = {
+            let state = state.clone();
+            let owner = owner.clone();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+
+                let files = selection.ordered(&state);
+                spawn_local(reload_files(state.clone(), owner.clone(), files));
+            }
+        };
+
+        let apply_template = {
+            let state = state.clone();
+            let owner = owner.clone();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+
+                let name = template.get_untracked();
+                let Some(template) = state.templates.with_untracked(|templates| {
+                    templates
+                        .iter()
+                        .find(|template| template.name == name)
+                        .cloned()
+                }) else {
+                    return;
+                };
+
+                let loaded = state.datasets.with_untracked(|datasets| {
+                    datasets
+                        .iter()
+                        .map(|dataset| dataset.id().clone())
+                        .collect::<HashSet<_>>()
+                });
+                let files = selection
+                    .ordered(&state)
+                    .into_iter()
+                    .filter(|file| loaded.contains(file))
+                    .collect::<Vec<_>>();
+                formula::apply_template_to_all(&template, &files, &state, &owner);
+            }
+        };
+
+        let clear = move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+
+            selection.files.update(|files| files.clear());
+        };
+
+        view! {
+            <div class="flex flex-wrap gap-1 items-center pb-1 text-sm">
+                <span class="grow">{move || format!("{} selected", count())}</span>
+                <button
+                    type="button"
+                    class="btn-cmd btn-secondary"
+                    title="Reload selected files"
+                    on:mousedown=reload_all
+                >
+                    <Icon icon=icon::Refresh />
+                </button>
+                <button
+                    type="button"
+                    class="btn-cmd btn-secondary"
+                    title="Remove selected files"
+                    on:mousedown=remove_all
+                >
+                    <Icon icon=icon::Close />
+                </button>
+                <button
+                    type="button"
+                    class="btn-cmd btn-secondary"
+                    title="Clear selection"
+                    on:mousedown=clear
+                >
+                    <Icon icon=icon::Remove />
+                </button>
+                <div class="flex gap-1 w-full">
+                    <select
+                        class="input-compact grow"
+                        prop:value=template
+                        on:change=move |e| set_template(event_target_value(&e))
+                    >
+                        <option value="">"Template"</option>
+                        {move || {
+                            state
+                                .templates
+                                .with(|templates| {
+                                    templates
+                                        .iter()
+                                        .map(|template| {
+                                            let name = template.name.clone();
+                                            view! { <option value=name.clone()>{name}</option> }
+                                        })
+                                        .collect::<Vec<_>>()
+                                })
+                        }}
+                    </select>
+                    <button
+                        type="button"
+                        class="btn-cmd btn-secondary"
+                        title="Apply the template to the selected files"
+                        disabled=move || template.with(|template| template.is_empty())
+                        on:mousedown=apply_template
+                    >
+                        "Apply"
+                    </button>
+                </div>
+            </div>
+        }
+    }
+
+    #[component]
+    fn File(file: state::File) -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let selection = expect_context::<Selection>();
+        let drag = expect_context::<DragFile>();
+
+        let name = {
+            let name = file.name.read_only();
+            move || name.with(|name| name.to_string_lossy().to_string())
+        };
+
+        let path = {
+            state
+                .directory_tree
+                .get_file_path(file.id())
+                .expect("file exists")
+                .to_string_lossy()
+                .to_string()
+        };
+
+        let is_active = {
+            let active = state.active_dataset.read_only();
+            let id = file.id().clone();
+            move || {
+                active
+                    .read()
+                    .as_ref()
+                    .map(|active| *active == id)
+                    .unwrap_or(false)
+            }
+        };
+
+        let is_selected = {
+            let id = file.id().clone();
+            move || {
+                selection
+                    .files
+                    .with(|files| files.len() > 1 && files.contains(&id))
+            }
+        };
+
+        let is_loaded = {
+            let datasets = state.datasets.read_only();
+            let id = file.id().clone();
+            Memo::new(move |_| datasets.read().iter().any(|dataset| *dataset.id() == id))
+        };
+
+        let activate = {
+            let state = state.clone();
+            let owner = expect_context::<state::WorkspaceOwner>();
+            let id = file.id().clone();
+            let active = state.active_dataset;
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+
+                if e.ctrl_key() || e.meta_key() {
+                    selection.files.update(|files| {
+                        if !files.remove(&id) {
+                            files.insert(id.clone());
+                        }
+                    });
+                    selection.anchor.set(Some(id.clone()));
+                    return;
+                }
+
+                if e.shift_key() {
+                    let anchor = selection.anchor.get_untracked();
+                    let range = state.selected_files.with_untracked(|selected| {
+                        let end = selected.iter().position(|file| *file == id)?;
+                        let start = anchor
+                            .and_then(|anchor| selected.iter().position(|file| *file == anchor))
+                            .unwrap_or(end);
+                        let (start, end) = (start.min(end), start.max(end));
+                        Some(selected[start..=end].to_vec())
+                    });
+                    if let Some(range) = range {
+                        selection.files.set(range.into_iter().collect());
+                    }
+                    return;
+                }
+
+                selection.files.set(HashSet::from([id.clone()]));
+                selection.anchor.set(Some(id.clone()));
+
+                if !is_loaded.get_untracked() {
+                    spawn_local(nav::open_file(
+                        state.clone(),
+                        owner.clone(),
+                        id.clone(),
+                        lib::data::LoadId::next(),
+                    ));
+                    return;
+                }
+
+                if !active
+                    .read_untracked()
+                    .as_ref()
+                    .map(|active| *active == id)
+                    .unwrap_or(false)
+                {
+                    let _ = active.write().insert(id.clone());
+                }
+            }
+        };
+
+        let unload = {
+            let state = state.clone();
+            let id = file.id().clone();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+                e.stop_propagation();
+
+                deactivate(&state, &id);
+                state
+                    .datasets
+                    .update(|datasets| datasets.retain(|dataset| *dataset.file() != id));
+            }
+        };
+
+        let remove = {
+            let state = state.clone();
+            let id = file.id().clone();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+                e.stop_propagation();
+
+                remove_file(&state, &id);
+                selection.files.update(|files| {
+                    files.remove(&id);
+                });
+            }
+        };
+
+        let drag_start = {
+            let id = file.id().clone();
+            move |e: ev::DragEvent| {
+                e.stop_propagation();
+                drag.set(Some(id.clone()));
+            }
+        };
+
+        let drop = {
+            let state = state.clone();
+            let id = file.id().clone();
+            move |e: ev::DragEvent| {
+                e.prevent_default();
+                let Some(dragged) = drag.write().take() else {
+                    return;
+                };
+
+                state.selected_files.update(|selected| {
+                    move_file(selected, &dragged, &id);
+                });
+            }
+        };
+
+        let is_protected = {
+            let protected = state.protected_files.read_only();
+            let id = file.id().clone();
+            Memo::new(move |_| protected.read().contains(&id))
+        };
+
+        let set_protected = Action::new_local({
+            let state = state.clone();
+            let id = file.id().clone();
+            move |protected: &bool| set_protected(state.clone(), id.clone(), *protected)
+        });
+
+        let toggle_protected = move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+            e.stop_propagation();
+
+            set_protected.dispatch(!is_protected.get_untracked());
+        };
+
+        view! {
+            <div
+                class="flex gap-2 items-end px cursor-pointer group/file text-nowrap"
+                class=(["bg-secondary-50", "dark:bg-secondary-700"], is_active.clone())
+                class=(["outline", "outline-1", "outline-primary-600"], is_selected)
+                class=("text-secondary-400", move || !is_loaded.get())
+                style:padding-left=format!("{LEVEL_PAD}{LEVEL_PAD_UNIT}")
+                title=move || {
+                    (!is_loaded.get()).then_some("Data unloaded, click to load it again")
+                }
+                on:mousedown=activate
+                on:dragover=|e: ev::DragEvent| e.prevent_default()
+                on:drop=drop
+            >
+                <span
+                    class="self-center cursor-grab text-secondary-400"
+                    title="Drag to reorder"
+                    draggable="true"
+                    on:mousedown=|e: ev::MouseEvent| e.stop_propagation()
+                    on:dragstart=drag_start
+                    on:dragend=move |_| drag.set(None)
+                >
+                    <Icon icon=icon::DragHandle />
+                </span>
+                <div>{name}</div>
+                <small
+                    class="truncate text-secondary-700 dark:text-secondary-200"
+                    title=path.clone()
+                >
+                    {path.clone()}
+                </small>
+                <button
+                    class="group-hover/file:block btn-cmd btn-secondary"
+                    class:hidden=move || !is_protected.get()
+                    title=move || {
+                        if is_protected.get() {
+                            "Protected, click to allow modification"
+                        } else {
+                            "Protect from modification"
+                        }
+                    }
+                    on:mousedown=toggle_protected
+                >
+                    {move || {
+                        if is_protected.get() {
+                            view! { <Icon icon=icon::Lock /> }
+                        } else {
+                            view! { <Icon icon=icon::Unlock /> }
+                        }
+                    }}
+                </button>
+                <button
+                    class="hidden btn-cmd btn-secondary"
+                    class=("group-hover/file:block", move || is_loaded.get())
+                    title="Unload data, keep formulas"
+                    on:mousedown=unload
+                >
+                    <Icon icon=icon::Unload />
+                </button>
+                <button class="hidden group-hover/file:block btn-cmd btn-secondary">
+                    <Icon icon=icon::Close on:mousedown=remove />
+                </button>
+            </div>
+        }
+    }
+
+    /// Move a file to the position of another in the list.
+    fn move_file(
+        files: &mut Vec<state::ResourceId>,
+        file: &state::ResourceId,
+        to: &state::ResourceId,
+    ) {
+        if file == to {
+            return;
+        }
+        let Some(from) = files.iter().position(|id| id == file) else {
+            return;
+        };
+        let Some(to) = files.iter().position(|id| id == to) else {
+            return;
+        };
+
+        let file = files.remove(from);
+        files.insert(to, file);
+    }
+
+    /// Remove a file from the input files, unloading its data.
+    fn remove_file(state: &state::State, id: &state::ResourceId) {
+        deactivate(state, id);
+        state.selected_files.update(|selected| {
+            selected.retain(|rid| rid != id);
+        });
+        state
+            .datasets
+            .update(|datasets| datasets.retain(|dataset| dataset.file() != id));
+    }
+
+    /// Load files again from disk, keeping their formulas.
+    /// The active file is restored afterwards.
+    async fn reload_files(
+        state: state::State,
+        owner: state::WorkspaceOwner,
+        files: Vec<state::ResourceId>,
+    ) {
+        let active = state
+            .active_dataset
+            .with_untracked(|active| active.as_ref().cloned());
+        for id in files {
+            state
+                .datasets
+                .update(|datasets| datasets.retain(|dataset| *dataset.file() != id));
+            nav::open_file(state.clone(), owner.clone(), id, lib::data::LoadId::next()).await;
+        }
+
+        if let Some(active) = active {
+            let is_loaded = state
+                .datasets
+                .read_untracked()
+                .iter()
+                .any(|dataset| *dataset.id() == active);
+            if is_loaded {
+                state.active_dataset.write().insert(active);
+            }
+        }
+    }
+
+    /// If the file is active, make the next loaded file active instead,
+    /// or the previous if there are none after it.
+    fn deactivate(state: &state::State, id: &state::ResourceId) {
+        let active = state.active_dataset;
+        if !active
+            .with_untracked(|active| active.as_ref().map(|active| active == id).unwrap_or(false))
+        {
+            return;
+        }
+
+        let loaded = state.datasets.with_untracked(|datasets| {
+            datasets
+                .iter()
+                .map(|dataset| dataset.id().clone())
+                .collect::<HashSet<_>>()
+        });
+        let next = state.selected_files.with_untracked(|selected| {
+            let idx = selected
+                .iter()
+                .position(|selected| selected == id)
+                .expect("file is selected");
+            selected[idx + 1..]
+                .iter()
+                .chain(selected[..idx].iter().rev())
+                .find(|file| loaded.contains(*file))
+                .cloned()
+        });
+
+        match next {
+            Some(next) => active.write().insert(next),
+            None => active.write().take(),
+        }
+    }
+
+    /// Set whether a file is protected from modification.
+    /// Cells of protected datasets can not be edited,
+    /// and running the workspace does not modify them.
+    pub async fn set_protected(state: state::State, file: state::ResourceId, protected: bool) {
+        let Some(path) = state.directory_tree.get_file_path(&file) else {
+            return;
+        };
+
+        if let Err(err) = set_file_protected(state.root_path().clone(), path, protected).await {
+            tracing::error!(?err);
+            state.messages.push(message::Message::error_with_body(
+                "Could not save file protection.",
+                err.to_string(),
+            ));
+        }
+
+        state.protected_files.update(|files| {
+            if protected {
+                files.insert(file);
+            } else {
+                files.remove(&file);
+            }
+        });
+    }
+
+    async fn set_file_protected(
+        root: PathBuf,
+        path: PathBuf,
+        protected: bool,
+    ) -> Result<(), lib::fs::error::FileOperation> {
+        crate::ipc::invoke(lib::ipc::SetFileProtected {
+            root,
+            path,
+            protected,
+        })
+        .await
+    }
+}
+
+mod duplicates {
+    use crate::{LEVEL_PAD, LEVEL_PAD_UNIT, state};
+    use hermes_desktop_lib as lib;
+    use leptos::prelude::*;
+    use std::{collections::HashSet, path::PathBuf};
+
+    /// Sets of files with identical content.
+    /// Hidden if there are no duplicates.
+    #[component]
+    pub fn DuplicateFiles() -> impl IntoView {
+        let state = expect_context::<state::State>();
+
+        let find_duplicates = Action::new_local({
+            let root_path = state.root_path().clone();
+            let directory_tree = state.directory_tree.clone();
+            let duplicate_files = state.duplicate_files;
+            move |_| {
+                let root_path = root_path.clone();
+                let directory_tree = directory_tree.clone();
+                async move {
+                    match find_duplicate_files(root_path).await {
+                        Ok(duplicates) => {
+                            let duplicates = duplicates
+                                .into_iter()
+                                .map(|paths| {
+                                    paths
+                                        .into_iter()
+                                        .filter_map(|path| {
+                                            directory_tree
+                                                .get_file_by_path(path)
+                                                .map(|file| file.id().clone())
+                                        })
+                                        .collect::<Vec<_>>()
+                                })
+                                .filter(|files| files.len() > 1)
+                                .collect();
+
+                            duplicate_files.set(duplicates);
+                        }
+                        Err(err) => tracing::warn!(?err),
+                    }
+                }
+            }
+        });
+        find_duplicates.dispatch(());
+
+        let is_empty = {
+            let duplicate_files = state.duplicate_files.read_only();
+            move || duplicate_files.read().is_empty()
+        };
+
+        view! {
+            <div class:hidden=is_empty>
+                <div class="pb">
+                    <h2 class="font-bold uppercase">"Duplicate files"</h2>
+                </div>
+                <div>
+                    <For
+                        each=state.duplicate_files.read_only()
+                        key=|files| files.clone()
+                        let:files
+                    >
+                        <DuplicateSet files />
+                    </For>
+                </div>
+            </div>
+        }
+    }
+
+    #[component]
+    fn DuplicateSet(files: Vec<state::ResourceId>) -> impl IntoView {
+        let state = expect_context::<state::State>();
+
+        let paths = files
+            .iter()
+            .filter_map(|file| state.directory_tree.get_file_path(file))
+            .map(|path| path.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+
+        let has_conflict = {
+            let formulas = state.formulas.read_only();
+            move || {
+                formulas.with(|formulas| {
+                    formulas
+                        .iter()
+                        .map(|formula| {
+                            formula.domain.with(|domain| match domain {
+                                state::FormulaDomain::CsvCell { dataset, .. } => dataset.clone(),
+                                state::FormulaDomain::WorkbookCell { dataset, .. } => {
+                                    dataset.clone()
+                                }
+                            })
+                        })
+                        .filter(|dataset| files.contains(dataset))
+                        .collect::<HashSet<_>>()
+                        .len()
+                        > 1
+                })
+            }
+        };
+
+        view! {
+            <div class="pb">
+                {paths
+                    .into_iter()
+                    .map(|path| {
+                        view! {
+                            <div
+                                class="truncate text-nowrap"
+                                style:padding-left=format!("{LEVEL_PAD}{LEVEL_PAD_UNIT}")
+                                title=path.clone()
+                            >
+                                {path}
+                            </div>
+                        }
+                    })
+                    .collect::<Vec<_>>()}
+                <Show when=has_conflict>
+                    <small
+                        class="color-brand-red-600"
+                        style:padding-left=format!("{LEVEL_PAD}{LEVEL_PAD_UNIT}")
+                    >
+                        "Formulas are set on more than one copy of this data."
+                    </small>
+                </Show>
+            </div>
+        }
+    }
+
+    async fn find_duplicate_files(
+        root: PathBuf,
+    ) -> Result<Vec<Vec<PathBuf>>, lib::fs::error::FromFileSystem> {
+        crate::ipc::invoke(lib::ipc::FindDuplicateFiles { root }).await
+    }
+}
+
+mod search {
+    use crate::{explorer::nav, state};
+    use hermes_desktop_lib as lib;
+    use leptos::{ev, html, prelude::*, task::spawn_local};
+    use std::collections::HashSet;
+
+    /// Maximum number of results listed by the quick open palette.
+    const QUICK_OPEN_MAX_RESULTS: usize = 50;
+
+    /// Files and directories matching the explorer search.
+    #[derive(Clone, PartialEq, Default)]
+    struct Matches {
+        files: HashSet<state::ResourceId>,
+        /// `None` if all directories are shown.
+        directories: Option<HashSet<state::ResourceId>>,
+    }
+
+    /// Filters the file tree by a search query and file extension.
+    #[derive(Clone, Copy)]
+    pub struct TreeFilter {
+        query: RwSignal<String>,
+        /// Extensions of files shown in the tree.
+        extensions: RwSignal<Vec<String>>,
+        /// Show files regardless of their extension.
+        show_all: RwSignal<bool>,
+        /// `None` if no filter is applied.
+        matches: Memo<Option<Matches>>,
+    }
+
+    impl TreeFilter {
+        pub fn new(index: Memo<Vec<state::IndexedFile>>) -> Self {
+            let query = RwSignal::new(String::new());
+            let extensions = RwSignal::new(
+                lib::fs::SUPPORTED_EXTENSIONS
+                    .iter()
+                    .map(|ext| ext.to_string())
+                    .collect::<Vec<_>>(),
+            );
+            let show_all = RwSignal::new(false);
+            let matches = Memo::new(move |_| {
+                let query = query.read();
+                let query = query.trim();
+                let show_all = show_all.get();
+                if query.is_empty() && show_all {
+                    return None;
+                }
+
+                let mut files = HashSet::new();
+                let mut directories = (!query.is_empty()).then(HashSet::new);
+                extensions.with(|extensions| {
+                    index.with(|index| {
+                        for file in index {
+                            if !show_all && !has_extension(&file.path, extensions) {
+                                continue;
+                            }
+                            if !query.is_empty()
+                                && fuzzy_score(query, &file.path.to_string_lossy()).is_none()
+                            {
+                                continue;
+                            }
+
+                            files.insert(file.id.clone());
+                            if let Some(directories) = directories.as_mut() {
+                                directories.extend(file.ancestors.iter().cloned());
+                            }
+                        }
+                    })
+                });
+
+                Some(Matches { files, directories })
+            });
+
+            Self {
+                query,
+                extensions,
+                show_all,
+                matches,
+            }
+        }
+
+        pub fn shows_file(&self, id: &state::ResourceId) -> bool {
+            self.matches.with(|matches| {
+                matches
+                    .as_ref()
+                    .map(|matches| matches.files.contains(id))
+                    .unwrap_or(true)
+            })
+        }
+
+        pub fn shows_directory(&self, id: &state::ResourceId) -> bool {
+            self.matches.with(|matches| {
+                matches
+                    .as_ref()
+                    .and_then(|matches| matches.directories.as_ref())
+                    .map(|directories| directories.contains(id))
+                    .unwrap_or(true)
+            })
+        }
+    }
+
+    /// # Returns
+    /// If the path's extension is one of `extensions`, ignoring case.
+    fn has_extension(path: &std::path::Path, extensions: &[String]) -> bool {
+        let Some(ext) = path.extension() else {
+            return false;
+        };
+        let ext = ext.to_string_lossy();
+        extensions
+            .iter()
+            .any(|allowed| allowed.eq_ignore_ascii_case(&ext))
+    }
+
+    /// Parse a comma or whitespace separated list of extensions.
+    fn parse_extensions(input: &str) -> Vec<String> {
+        input
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .map(|ext| ext.trim().trim_start_matches('.').to_ascii_lowercase())
+            .filter(|ext| !ext.is_empty())
+            .collect()
+    }
+
+    /// Search box filtering the file tree.
+    #[component]
+    pub fn FileSearch(filter: TreeFilter) -> impl IntoView {
+        let clear = move |e: ev::KeyboardEvent| {
+            if e.key() == "Escape" {
+                filter.query.set(String::new());
+            }
+        };
+
+        view! {
+            <div class="pb">
+                <input
+                    type="search"
+                    class="input-compact w-full"
+                    placeholder="Search files"
+                    prop:value=filter.query
+                    on:input=move |e| filter.query.set(event_target_value(&e))
+                    on:keydown=clear
+                />
+                <div class="flex gap-1 items-center text-xs pt-1">
+                    <label class="flex gap-1 items-center text-nowrap">
+                        <input
+                            type="checkbox"
+                            prop:checked=filter.show_all
+                            on:change=move |e| filter.show_all.set(event_target_checked(&e))
+                        />
+                        "All files"
+                    </label>
+                    <input
+                        class="input-compact grow min-w-0"
+                        title="File extensions shown in the tree"
+                        prop:value=move || filter.extensions.with(|extensions| extensions.join(", "))
+                        on:change=move |e| {
+                            filter.extensions.set(parse_extensions(&event_target_value(&e)))
+                        }
+                        disabled=filter.show_all
+                    />
+                </div>
+            </div>
+        }
+    }
+
+    /// Prefix of quick open queries that list commands instead of files.
+    const COMMAND_PREFIX: char = '>';
+
+    /// Commands available from the quick open palette.
+    #[derive(Clone, Copy, PartialEq, Debug)]
+    enum Command {
+        NewFormula,
+    }
+
+    impl Command {
+        const ALL: &'static [Self] = &[Self::NewFormula];
+
+        fn label(&self) -> &'static str {
+            match self {
+                Self::NewFormula => "New formula",
+            }
+        }
+    }
+
+    #[derive(Clone, PartialEq, Debug)]
+    enum Entry {
+        File(state::ResourceId),
+        Command(Command),
+    }
+
+    /// Palette to quickly open any file under the root.
+    /// Queries starting with `>` list commands.
+    /// Toggled with `Ctrl+P`.
+    #[component]
+    pub fn QuickOpen() -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let new_formula_vis = expect_context::<state::NewFormulaDialogVisibility>();
+        let visible = RwSignal::new(false);
+        let query = RwSignal::new(String::new());
+        let highlighted = RwSignal::new(0_usize);
+        let input_node = NodeRef::<html::Input>::new();
+
+        let toggle = window_event_listener(ev::keydown, move |e| {
+            if (e.ctrl_key() || e.meta_key()) && e.key().eq_ignore_ascii_case("p") {
+                e.prevent_default();
+                query.set(String::new());
+                highlighted.set(0);
+                visible.update(|visible| *visible = !*visible);
+            }
+        });
+        on_cleanup(move || toggle.remove());
+
+        Effect::new(move || {
+            if visible.get() {
+                if let Some(input) = input_node.get() {
+                    if let Err(err) = input.focus() {
+                        tracing::warn!(?err);
+                    }
+                }
+            }
+        });
+
+        let index = state.directory_tree.file_index();
+        let results = Memo::new(move |_| {
+            let query = query.read();
+            let query = query.trim();
+            let mut results = if let Some(query) = query.strip_prefix(COMMAND_PREFIX) {
+                let query = query.trim();
+                Command::ALL
+                    .iter()
+                    .filter_map(|command| {
+                        let label = command.label().to_string();
+                        fuzzy_score(query, &label)
+                            .map(|score| (score, Entry::Command(*command), label))
+                    })
+                    .collect::<Vec<_>>()
+            } else {
+                index.with(|index| {
+                    index
+                        .iter()
+                        .filter_map(|file| {
+                            let path = file.path.to_string_lossy().to_string();
+                            fuzzy_score(query, &path)
+                                .map(|score| (score, Entry::File(file.id.clone()), path))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            };
+
+            results.sort_by(|(a_score, _, a_label), (b_score, _, b_label)| {
+                b_score.cmp(a_score).then_with(|| a_label.cmp(b_label))
+            });
+            results.truncate(QUICK_OPEN_MAX_RESULTS);
+            results
+                .into_iter()
+                .map(|(_, entry, label)| (entry, label))
+                .collect::<Vec<_>>()
+        });
+
+        let owner = expect_context::<state::WorkspaceOwner>();
+        let open = {
+            let state = state.clone();
+            move |entry: Entry| {
+                visible.set(false);
+                let id = match entry {
+                    Entry::File(id) => id,
+                    Entry::Command(Command::NewFormula) => {
+                        new_formula_vis.set(true);
+                        return;
+                    }
+                };
+
+                let is_loaded = state
+                    .datasets
+                    .read_untracked()
+                    .iter()
+                    .any(|dataset| *dataset.id() == id);
+
+                if is_loaded {
+                    if !state.selected_files.read_untracked().contains(&id) {
+                        state.selected_files.write().push(id.clone());
+                    }
+                    let _ = state.active_dataset.write().insert(id);
+                } else {
+                    spawn_local(nav::open_file(
+                        state.clone(),
+                        owner.clone(),
+                        id,
+                        lib::data::LoadId::next(),
+                    ));
+                }
+            }
+        };
+
+        let navigate = {
+            let open = open.clone();
+            move |e: ev::KeyboardEvent| match e.key().as_str() {
+                "ArrowDown" => {
+                    e.prevent_default();
+                    let count = results.with_untracked(|results| results.len());
+                    highlighted.update(|idx| {
+                        if *idx + 1 < count {
+                            *idx += 1;
+                        }
+                    });
+                }
+                "ArrowUp" => {
+                    e.prevent_default();
+                    highlighted.update(|idx| *idx = idx.saturating_sub(1));
+                }
+                "Enter" => {
+                    e.prevent_default();
+                    let entry = results.with_untracked(|results| {
+                        results
+                            .get(highlighted.get_untracked())
+                            .map(|(entry, _)| entry.clone())
+                    });
+                    if let Some(entry) = entry {
+                        open(entry);
+                    }
+                }
+                "Escape" => {
+                    e.prevent_default();
+                    visible.set(false);
+                }
+                _ => {}
+            }
+        };
+
+        let close = move |_: ev::MouseEvent| visible.set(false);
+
+        view! {
+            <div class:hidden=move || !visible.get()>
+                <div class="fixed inset-0 z-30" on:mousedown=close></div>
+                <div class="fixed z-40 top-12 left-1/2 -translate-x-1/2 w-1/2 p-1 bg-white dark:bg-secondary-800 \
+                border border-secondary-100 dark:border-secondary-600">
+                    <input
+                        node_ref=input_node
+                        type="text"
+                        class="input-compact w-full"
+                        placeholder="Open file, or type > for commands"
+                        prop:value=query
+                        on:input=move |e| {
+                            query.set(event_target_value(&e));
+                            highlighted.set(0);
+                        }
+                        on:keydown=navigate
+                    />
+                    <ul class="max-h-96 overflow-auto scrollbar-thin">
+                        {move || {
+                            results
+                                .get()
+                                .into_iter()
+                                .enumerate()
+                                .map(|(idx, (entry, label))| {
+                                    let open = open.clone();
+                                    let is_highlighted = move || highlighted.get() == idx;
+                                    view! {
+                                        <li
+                                            class="px truncate cursor-pointer hover:bg-secondary-50 dark:hover:bg-secondary-700"
+                                            class=("bg-secondary-50", is_highlighted)
+                                            class=("dark:bg-secondary-700", is_highlighted)
+                                            title=label.clone()
+                                            on:mousedown=move |_| open(entry.clone())
+                                        >
+                                            {label}
+                                        </li>
+                                    }
+                                })
+                                .collect::<Vec<_>>()
+                        }}
+                    </ul>
+                </div>
+            </div>
+        }
+    }
+
+    /// Score how well `query` fuzzy matches `candidate`.
+    /// Matching is case insensitive and requires every character of the query
+    /// to appear in the candidate in order.
+    ///
+    /// # Returns
+    /// `None` if `candidate` does not match, otherwise the match score,
+    /// with higher scores being better matches.
+    fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+        const MATCH: i64 = 1;
+        const CONSECUTIVE_BONUS: i64 = 4;
+        const BOUNDARY_BONUS: i64 = 6;
+
+        let mut query = query.chars().flat_map(char::to_lowercase).peekable();
+        let mut score = 0;
+        let mut prev: Option<char> = None;
+        let mut prev_matched = false;
+        for c in candidate.chars() {
+            let Some(q) = query.peek() else {
+                break;
+            };
+
+            let is_match = c.to_lowercase().eq(std::iter::once(*q));
+            if is_match {
+                score += MATCH;
+                if prev_matched {
+                    score += CONSECUTIVE_BONUS;
+                }
+                if prev
+                    .map(|prev| matches!(prev, '/' | '\\' | '_' | '-' | '.' | ' '))
+                    .unwrap_or(true)
+                {
+                    score += BOUNDARY_BONUS;
+                }
+
+                query.next();
+            }
+
+            prev_matched = is_match;
+            prev = Some(c);
+        }
+
+        if query.peek().is_some() {
+            return None;
+        }
+
+        // prefer shorter candidates
+        Some(score * 100 - candidate.len() as i64)
+    }
 }
 
 mod nav {
-    use crate::{LEVEL_PAD, LEVEL_PAD_UNIT, icon, message, state, types};
+    use crate::{LEVEL_PAD, LEVEL_PAD_UNIT, formula, icon, message, state, types};
     use hermes_desktop_lib as lib;
     use leptos::{ev, html, prelude::*};
     use leptos_icons::Icon;
@@ -196,9 +2800,15 @@ mod nav {
             Some(class) => format!("group/level-0 overflow-auto scrollbar-thin h-full {class}"),
             None => "group/level-0 overflow-auto scrollbar-thin h-full".to_string(),
         };
+        provide_context(ops::ActiveMenu::new());
+        provide_context(ops::DragNode::new());
+        let filter = search::TreeFilter::new(state.directory_tree.file_index());
+        provide_context(filter);
 
         view! {
             <div class=root_class>
+                <ops::ContextMenu />
+                <search::FileSearch filter />
                 <ProjectRoot {..} class="font-bold pb" />
                 <div>
                     <div>
@@ -228,8 +2838,15 @@ mod nav {
             move || name.with(|name| name.to_string_lossy().to_string())
         };
 
+        let node = ops::Node::Directory(root.clone());
         view! {
-            <div class="font-bold uppercase" title=root_path>
+            <div
+                class="font-bold uppercase"
+                title=root_path
+                on:contextmenu=ops::open_menu(node)
+                on:dragover=ops::allow_drop
+                on:drop=ops::drop_on(root)
+            >
                 {name}
             </div>
         }
@@ -245,8 +2862,14 @@ mod nav {
             move || children.with(|children| children.as_ref().expect("directory exists").clone())
         };
 
+        let filter = expect_context::<search::TreeFilter>();
+        let is_hidden = {
+            let id = directory.id().clone();
+            move || !filter.shows_directory(&id)
+        };
+
         view! {
-            <div class=format!("group/level-{level}")>
+            <div class=format!("group/level-{level}") class:hidden=is_hidden>
                 <Directory directory=directory.clone() level />
                 <div>
                     <div>
@@ -302,12 +2925,102 @@ mod nav {
 
     #[component]
     fn DirectoryContent(directory: state::Directory) -> impl IntoView {
+        let state = expect_context::<state::State>();
         let name = {
             let name = directory.name.read_only();
             move || name.with(|name| name.to_string_lossy().to_string())
         };
 
-        view! { {name} }
+        let expand = Action::new_local({
+            let directory = directory.clone();
+            move |_| expand_directory(state.clone(), directory.clone())
+        });
+        let dispatch_expand = {
+            let pending = directory.pending.read_only();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+                if !pending.get_untracked() || expand.pending().get_untracked() {
+                    return;
+                }
+
+                expand.dispatch(());
+            }
+        };
+
+        let is_archive = matches!(directory.kind(), lib::fs::DirectoryKind::Archive);
+        let is_link = matches!(directory.kind(), lib::fs::DirectoryKind::Link);
+        let is_pending = directory.pending.read_only();
+        let node = ops::Node::Directory(directory.clone());
+
+        view! {
+            <div
+                class="flex gap-2 items-end"
+                on:mousedown=dispatch_expand
+                draggable="true"
+                on:dragstart=ops::drag_start(node.clone())
+                on:dragend=ops::drag_end
+                on:dragover=ops::allow_drop
+                on:drop=ops::drop_on(directory)
+                on:contextmenu=ops::open_menu(node)
+            >
+                <div>{name}</div>
+                <Show when=move || is_archive>
+                    <small
+                        class="text-secondary-700 dark:text-secondary-200"
+                        title="Archive contents are read-only."
+                    >
+                        "archive"
+                    </small>
+                </Show>
+                <Show when=move || is_link>
+                    <small
+                        class="text-secondary-700 dark:text-secondary-200"
+                        title="Symbolic link."
+                    >
+                        "link"
+                    </small>
+                </Show>
+                <Show when=move || is_pending.get()>
+                    <small
+                        class="text-secondary-700 dark:text-secondary-200"
+                        title="Click to load contents."
+                    >
+                        {move || if expand.pending().get() { "loading" } else { "..." }}
+                    </small>
+                </Show>
+            </div>
+        }
+    }
+
+    /// Load the contents of a pending directory.
+    /// Errors are reported as messages.
+    async fn expand_directory(state: state::State, directory: state::Directory) {
+        let Some(path) = state.directory_tree.get_directory_path(directory.id()) else {
+            return;
+        };
+
+        match load_directory_contents(state.root_path().clone(), path).await {
+            Ok(graph) => {
+                if let Err(err) = state.directory_tree.expand_directory(directory.id(), graph) {
+                    tracing::error!(?err);
+                }
+            }
+            Err(err) => {
+                state.messages.push(message::Message::error_with_body(
+                    "Could not load folder.",
+                    err.to_string(),
+                ));
+            }
+        }
+    }
+
+    async fn load_directory_contents(
+        root: PathBuf,
+        path: PathBuf,
+    ) -> Result<lib::fs::DirectoryTree, lib::fs::error::FromFileSystem> {
+        crate::ipc::invoke(lib::ipc::ExpandDirectory { root, path }).await
     }
 
     #[component]
@@ -320,6 +3033,12 @@ mod nav {
             move || selected.read().contains(&id)
         };
 
+        let is_hidden = {
+            let filter = expect_context::<search::TreeFilter>();
+            let id = file.id().clone();
+            move || !filter.shows_file(&id)
+        };
+
         let ancestors = (0..level)
             .map(|level| {
                 html::div()
@@ -350,6 +3069,7 @@ mod nav {
             )
             .class(("bg-secondary-50", is_selected.clone()))
             .class(("dark:bg-secondary-700", is_selected.clone()))
+            .class(("hidden", is_hidden))
     }
 
     #[component]
@@ -358,68 +3078,15 @@ mod nav {
         let load_dataset_action_abort_handle =
             expect_context::<state::LoadWorkbookActionAbortHandle>();
 
+        let load_progress = expect_context::<state::LoadProgress>();
+        let load = RwSignal::new(None);
+
         let try_load_dataset = Action::new_local({
-            let directory_tree = state.directory_tree.clone();
-            let root_path = state.root_path().clone();
-            let datasets = state.datasets;
-            let selected = state.selected_files;
-            let active = state.active_dataset;
-            let messages = state.messages;
+            let state = state.clone();
+            let owner = expect_context::<state::WorkspaceOwner>();
             let file_id = file.id().clone();
-            move |_| {
-                let directory_tree = directory_tree.clone();
-                let root_path = root_path.clone();
-                let file_id = file_id.clone();
-                async move {
-                    let path = directory_tree.get_file_path(&file_id).expect("file exists");
-                    let path = root_path.join(path);
-                    match load_dataset(path).await {
-                        Ok(dataset) => {
-                            datasets
-                                .write()
-                                .push(state::Dataset::new(file_id.clone(), dataset));
-
-                            if !selected.read_untracked().contains(&file_id) {
-                                selected.write().push(file_id.clone());
-                            }
-                            if active
-                                .read_untracked()
-                                .as_ref()
-                                .map(|active| *active != file_id)
-                                .unwrap_or(true)
-                            {
-                                active.write().insert(file_id.clone());
-                            }
-                        }
-                        Err(err) => {
-                            messages.update(|messages| {
-                                let body = match err {
-                                    hermes_desktop_lib::data::error::Load::InvalidFileType => {
-                                        "Invalid file type"
-                                    }
-                                    hermes_desktop_lib::data::error::Load::Csv(err) => match err {
-                                        hermes_desktop_lib::data::error::LoadCsv::Io(err) => {
-                                            io_error_message(err)
-                                        }
-                                        hermes_desktop_lib::data::error::LoadCsv::DataTooLarge => {
-                                            "File too large."
-                                        }
-                                    },
-                                    hermes_desktop_lib::data::error::Load::Excel(err) => {
-                                        match err {
-                                            hermes_desktop_lib::data::error::LoadExcel::Io(err) => {
-                                                io_error_message(err)
-                                            }
-                                        }
-                                    }
-                                };
-                                let msg =
-                                    message::Message::error_with_body("Could not load file.", body);
-                                messages.push(msg);
-                            });
-                        }
-                    }
-                }
+            move |load: &lib::data::LoadId| {
+                open_file(state.clone(), owner.clone(), file_id.clone(), *load)
             }
         });
 
@@ -434,11 +3101,14 @@ mod nav {
                     return;
                 }
 
-                if let Some(other_pending) = dataset_abort_handle.take() {
+                if let Some((other_pending, other_load)) = dataset_abort_handle.take() {
                     other_pending.abort();
+                    spawn_local(cancel_load(other_load));
                 }
-                let abort_handle = try_load_dataset.dispatch(());
-                dataset_abort_handle.insert(abort_handle);
+                let id = lib::data::LoadId::next();
+                load.set(Some(id));
+                let abort_handle = try_load_dataset.dispatch(id);
+                dataset_abort_handle.insert(abort_handle, id);
             }
         };
 
@@ -452,20 +3122,56 @@ mod nav {
                 if !pending.get_untracked() {
                     return;
                 }
-                if let Some(abort_handle) = abort_handle.take() {
+                if let Some((abort_handle, load)) = abort_handle.take() {
                     abort_handle.abort();
+                    spawn_local(cancel_load(load));
                 }
             }
         };
 
+        let progress = move || {
+            load.with(|load| load.as_ref().and_then(|load| load_progress.of(load)))
+                .map(format_progress)
+        };
+
         let name = {
             let name = file.name.read_only();
             move || name.with(|name| name.to_string_lossy().to_string())
         };
 
+        let kind = {
+            let name = file.name.read_only();
+            move || name.with(|name| lib::fs::FileKind::from_path(name).label())
+        };
+        let size = file.size().map(format_size);
+        let is_link = file.is_link();
+
+        let node = ops::Node::File(file.clone());
+
         view! {
-            <div on:mousedown=dispatch_load_dataset class="flex">
+            <div
+                on:mousedown=dispatch_load_dataset
+                class="flex"
+                draggable="true"
+                on:dragstart=ops::drag_start(node.clone())
+                on:dragend=ops::drag_end
+                on:contextmenu=ops::open_menu(node)
+            >
                 <div class="grow">{name}</div>
+                <div class="flex gap-1 items-center px-1 text-xs text-secondary-500">
+                    {move || {
+                        kind()
+                            .map(|kind| {
+                                view! {
+                                    <span class="px-1 rounded bg-secondary-100 dark:bg-secondary-600 uppercase">
+                                        {kind}
+                                    </span>
+                                }
+                            })
+                    }}
+                    {size.map(|size| view! { <span>{size}</span> })}
+                    {is_link.then(|| view! { <span title="Symbolic link.">"link"</span> })}
+                </div>
                 {
                     let wb_load_pending = try_load_dataset.pending();
                     let abort_load_dataset = abort_load_dataset.clone();
@@ -474,10 +3180,12 @@ mod nav {
                             .get()
                             .then_some(
                                 view! {
-                                    <div>
+                                    <div class="flex gap-1 items-center">
+                                        <span class="text-xs text-secondary-500">{progress}</span>
                                         <button
                                             on:mousedown=abort_load_dataset.clone()
                                             class="cursor-pointer"
+                                            title="Cancel loading"
                                         >
                                             <span class="block animate-spin">
                                                 <Icon icon=icon::LoadingSpinner />
@@ -492,13 +3200,671 @@ mod nav {
         }
     }
 
-    async fn load_dataset(path: PathBuf) -> Result<lib::data::Dataset, lib::data::error::Load> {
-        #[derive(serde::Serialize)]
-        struct Args {
-            path: PathBuf,
+    /// Format a size in bytes for display.
+    fn format_size(size: u64) -> String {
+        const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+
+        let mut value = size as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+
+        if unit == 0 {
+            format!("{size} {}", UNITS[unit])
+        } else {
+            format!("{value:.1} {}", UNITS[unit])
+        }
+    }
+
+    /// Format the progress of a load for display.
+    fn format_progress(progress: lib::data::LoadProgress) -> String {
+        format!("{} rows, {}", progress.rows, format_size(progress.bytes))
+    }
+
+    /// Load a file as a dataset and make it active.
+    /// Formulas of the dataset, e.g. kept when it was unloaded, are evaluated.
+    /// Errors are reported as messages.
+    ///
+    /// # Arguments
+    /// + `load`: Handle of the load, used to follow its progress and cancel it.
+    pub async fn open_file(
+        state: state::State,
+        owner: state::WorkspaceOwner,
+        file_id: state::ResourceId,
+        load: lib::data::LoadId,
+    ) {
+        let state::State {
+            directory_tree,
+            datasets,
+            selected_files: selected,
+            active_dataset: active,
+            messages,
+            ..
+        } = state.clone();
+        let root_path = state.root_path().clone();
+
+        let path = directory_tree.get_file_path(&file_id).expect("file exists");
+        let name = path.to_string_lossy().to_string();
+        let path = root_path.join(path);
+        let options = state.import_options(&file_id);
+        let loaded = match load_dataset(load, path.clone(), options.clone()).await {
+            Err(lib::data::error::Load::Csv(lib::data::error::LoadCsv::DataTooLarge {
+                size,
+                limit,
+            })) => {
+                messages.push(message::Message::warning_with_body(
+                    "File is too large to load entirely.",
+                    format!(
+                        "{name} is {}, larger than the {} limit. \
+                        Only the first rows were loaded.",
+                        format_size(size),
+                        format_size(limit)
+                    ),
+                ));
+                load_dataset_head(load, path, options).await
+            }
+            loaded => loaded,
+        };
+
+        match loaded {
+            Ok(dataset) => {
+                state.written_values.update(|written| {
+                    written.remove(&file_id);
+                });
+                datasets
+                    .write()
+                    .push(state::Dataset::new(file_id.clone(), dataset));
+                let errors = formula::eval_dataset_formulas(
+                    file_id.clone(),
+                    state.formulas,
+                    datasets,
+                    owner.clone(),
+                    state
+                        .settings
+                        .with_untracked(|settings| settings.eval_options()),
+                )
+                .await;
+                if errors > 0 {
+                    messages.push(message::Message::warning_with_body(
+                        "Some formulas could not be evaluated.",
+                        format!("{name}: {errors} formula(s) could not be evaluated"),
+                    ));
+                }
+
+                if !selected.read_untracked().contains(&file_id) {
+                    selected.write().push(file_id.clone());
+                }
+                if active
+                    .read_untracked()
+                    .as_ref()
+                    .map(|active| *active != file_id)
+                    .unwrap_or(true)
+                {
+                    active.write().insert(file_id.clone());
+                }
+
+                message::notify_complete(lib::settings::Operation::Load, "Loaded file", Some(name));
+            }
+            Err(err) => {
+                message::notify_complete(
+                    lib::settings::Operation::Load,
+                    "Could not load file",
+                    Some(name),
+                );
+                let body = match err {
+                    hermes_desktop_lib::data::error::Load::InvalidFileType => {
+                        "Invalid file type".to_string()
+                    }
+                    hermes_desktop_lib::data::error::Load::Csv(err) => match err {
+                        hermes_desktop_lib::data::error::LoadCsv::Io(err) => {
+                            io_error_message(err).to_string()
+                        }
+                        hermes_desktop_lib::data::error::LoadCsv::DataTooLarge { size, limit } => {
+                            too_large_message(size, limit)
+                        }
+                        hermes_desktop_lib::data::error::LoadCsv::SheetTooLarge => {
+                            "File has too many rows or columns.".to_string()
+                        }
+                        hermes_desktop_lib::data::error::LoadCsv::Cancelled => {
+                            "Loading was cancelled.".to_string()
+                        }
+                        err @ (hermes_desktop_lib::data::error::LoadCsv::InvalidUtf8 { .. }
+                        | hermes_desktop_lib::data::error::LoadCsv::UnequalLengths {
+                            ..
+                        }
+                        | hermes_desktop_lib::data::error::LoadCsv::Read(_)) => {
+                            format!("File is not a valid csv: {err}")
+                        }
+                    },
+                    hermes_desktop_lib::data::error::Load::Excel(err) => match err {
+                        hermes_desktop_lib::data::error::LoadExcel::Io(err) => {
+                            io_error_message(err).to_string()
+                        }
+                        hermes_desktop_lib::data::error::LoadExcel::DataTooLarge {
+                            size,
+                            limit,
+                        } => too_large_message(size, limit),
+                        hermes_desktop_lib::data::error::LoadExcel::SheetTooLarge => {
+                            "File has too many rows or columns.".to_string()
+                        }
+                        hermes_desktop_lib::data::error::LoadExcel::Invalid(err) => {
+                            format!("File is not a valid workbook: {err}")
+                        }
+                    },
+                };
+                let msg = message::Message::error_with_body("Could not load file.", body);
+                messages.push(msg);
+            }
+        }
+    }
+
+    fn too_large_message(size: u64, limit: u64) -> String {
+        format!(
+            "File is {}, larger than the {} limit.",
+            format_size(size),
+            format_size(limit)
+        )
+    }
+
+    async fn load_dataset(
+        id: lib::data::LoadId,
+        path: PathBuf,
+        options: lib::data::ImportOptions,
+    ) -> Result<lib::data::Loaded, lib::data::error::Load> {
+        crate::ipc::invoke(lib::ipc::LoadDataset { id, path, options }).await
+    }
+
+    /// Load the first rows of a dataset too large to load entirely.
+    async fn load_dataset_head(
+        id: lib::data::LoadId,
+        path: PathBuf,
+        options: lib::data::ImportOptions,
+    ) -> Result<lib::data::Loaded, lib::data::error::Load> {
+        crate::ipc::invoke(lib::ipc::LoadDatasetHead { id, path, options }).await
+    }
+
+    /// Stop a dataset load on the backend.
+    async fn cancel_load(id: lib::data::LoadId) {
+        crate::ipc::invoke_infallible(lib::ipc::CancelLoad { id }).await
+    }
+
+    /// File system operations on the file tree.
+    mod ops {
+        use crate::{
+            component::{self, ContextMenuItem},
+            message, state,
+        };
+        use hermes_desktop_lib as lib;
+        use leptos::{either::Either, ev, html, prelude::*, task::spawn_local};
+        use std::{ffi::OsString, path::PathBuf};
+
+        /// File tree node targeted by an operation.
+        #[derive(Clone)]
+        pub enum Node {
+            Directory(state::Directory),
+            File(state::File),
+        }
+
+        impl Node {
+            /// # Returns
+            /// Absolute path to the node.
+            fn path(&self, state: &state::State) -> Option<PathBuf> {
+                let path = match self {
+                    Self::Directory(directory) => {
+                        state.directory_tree.get_directory_path(directory.id())
+                    }
+                    Self::File(file) => state.directory_tree.get_file_path(file.id()),
+                }?;
+
+                Some(state.root_path().join(path))
+            }
+
+            fn name(&self) -> OsString {
+                match self {
+                    Self::Directory(directory) => directory.name.get_untracked(),
+                    Self::File(file) => file.name.get_untracked(),
+                }
+            }
+
+            fn is_root(&self, state: &state::State) -> bool {
+                match self {
+                    Self::Directory(directory) => {
+                        directory.id() == state.directory_tree.root().id()
+                    }
+                    Self::File(_) => false,
+                }
+            }
+        }
+
+        #[derive(Clone)]
+        pub struct Menu {
+            node: Node,
+            x: i32,
+            y: i32,
+        }
+
+        /// Currently open context menu.
+        #[derive(Clone, Copy, derive_more::Deref)]
+        pub struct ActiveMenu(RwSignal<Option<Menu>>);
+        impl ActiveMenu {
+            pub fn new() -> Self {
+                Self(RwSignal::new(None))
+            }
+        }
+
+        /// Node currently being dragged.
+        #[derive(Clone, Copy, derive_more::Deref)]
+        pub struct DragNode(RwSignal<Option<Node>>);
+        impl DragNode {
+            pub fn new() -> Self {
+                Self(RwSignal::new(None))
+            }
+        }
+
+        #[derive(Clone, Copy, PartialEq)]
+        enum NameOperation {
+            NewFile,
+            NewFolder,
+            Rename,
+        }
+
+        pub fn open_menu(node: Node) -> impl Fn(ev::MouseEvent) + 'static {
+            let menu = expect_context::<ActiveMenu>();
+            move |e: ev::MouseEvent| {
+                e.prevent_default();
+                e.stop_propagation();
+                menu.set(Some(Menu {
+                    node: node.clone(),
+                    x: e.client_x(),
+                    y: e.client_y(),
+                }));
+            }
+        }
+
+        pub fn drag_start(node: Node) -> impl Fn(ev::DragEvent) + 'static {
+            let drag = expect_context::<DragNode>();
+            move |e: ev::DragEvent| {
+                e.stop_propagation();
+                drag.set(Some(node.clone()));
+            }
+        }
+
+        pub fn drag_end(_: ev::DragEvent) {
+            expect_context::<DragNode>().set(None);
+        }
+
+        pub fn allow_drop(e: ev::DragEvent) {
+            e.prevent_default();
+        }
+
+        pub fn drop_on(directory: state::Directory) -> impl Fn(ev::DragEvent) + 'static {
+            let state = expect_context::<state::State>();
+            let drag = expect_context::<DragNode>();
+            move |e: ev::DragEvent| {
+                e.prevent_default();
+                e.stop_propagation();
+                let Some(node) = drag.write().take() else {
+                    return;
+                };
+
+                spawn_local(move_node(state.clone(), node, directory.clone()));
+            }
+        }
+
+        #[component]
+        pub fn ContextMenu() -> impl IntoView {
+            let menu = expect_context::<ActiveMenu>();
+            move || menu.get().map(|menu| view! { <ContextMenuContent menu /> })
+        }
+
+        #[component]
+        fn ContextMenuContent(menu: Menu) -> impl IntoView {
+            let state = expect_context::<state::State>();
+            let active_menu = expect_context::<ActiveMenu>();
+            let (operation, set_operation) = signal::<Option<NameOperation>>(None);
+            let (error, set_error) = signal::<Option<String>>(None);
+            let Menu { node, x, y } = menu;
+            let is_directory = matches!(node, Node::Directory(_));
+            let is_root = node.is_root(&state);
+
+            let close = move || active_menu.set(None);
+            let select = move |op: NameOperation| move || set_operation(Some(op));
+
+            let load = {
+                let state = state.clone();
+                let owner = expect_context::<state::WorkspaceOwner>();
+                let node = node.clone();
+                move || {
+                    let Node::File(file) = &node else {
+                        return;
+                    };
+
+                    active_menu.set(None);
+                    let id = file.id().clone();
+                    let is_loaded = state
+                        .datasets
+                        .read_untracked()
+                        .iter()
+                        .any(|dataset| *dataset.id() == id);
+
+                    if is_loaded {
+                        let _ = state.active_dataset.write().insert(id);
+                    } else {
+                        spawn_local(super::open_file(
+                            state.clone(),
+                            owner.clone(),
+                            id,
+                            lib::data::LoadId::next(),
+                        ));
+                    }
+                }
+            };
+
+            let reveal = {
+                let state = state.clone();
+                let node = node.clone();
+                move || {
+                    let Some(path) = node.path(&state) else {
+                        set_error(Some(lib::fs::error::FileOperation::NotFound.to_string()));
+                        return;
+                    };
+
+                    spawn_local(async move {
+                        match reveal_path(path).await {
+                            Ok(()) => active_menu.set(None),
+                            Err(err) => set_error(Some(err.to_string())),
+                        }
+                    });
+                }
+            };
+
+            let delete = {
+                let state = state.clone();
+                let node = node.clone();
+                move || {
+                    let state = state.clone();
+                    let node = node.clone();
+                    spawn_local(async move {
+                        match delete_node(&state, &node).await {
+                            Ok(()) => active_menu.set(None),
+                            Err(err) => set_error(Some(err.to_string())),
+                        }
+                    });
+                }
+            };
+
+            let commands = move || {
+                view! {
+                    <ul>
+                        <Show when=move || !is_directory>
+                            <ContextMenuItem on_select=load.clone()>"Load"</ContextMenuItem>
+                        </Show>
+                        <Show when=move || is_directory>
+                            <ContextMenuItem on_select=select(NameOperation::NewFile)>
+                                "New file"
+                            </ContextMenuItem>
+                            <ContextMenuItem on_select=select(NameOperation::NewFolder)>
+                                "New folder"
+                            </ContextMenuItem>
+                        </Show>
+                        <ContextMenuItem on_select=reveal.clone()>
+                            "Reveal in file manager"
+                        </ContextMenuItem>
+                        <Show when=move || !is_root>
+                            <ContextMenuItem on_select=select(NameOperation::Rename)>
+                                "Rename"
+                            </ContextMenuItem>
+                            <ContextMenuItem on_select=delete.clone()>
+                                "Move to trash"
+                            </ContextMenuItem>
+                        </Show>
+                    </ul>
+                }
+            };
+
+            let input_node = NodeRef::<html::Input>::new();
+            Effect::new(move || {
+                if let Some(input) = input_node.get() {
+                    if let Err(err) = input.focus() {
+                        tracing::warn!(?err);
+                    }
+                }
+            });
+
+            let submit_name = {
+                let state = state.clone();
+                let node = node.clone();
+                move |e: ev::SubmitEvent| {
+                    e.prevent_default();
+                    let Some(op) = operation.get_untracked() else {
+                        return;
+                    };
+                    let Some(input) = input_node.get_untracked() else {
+                        return;
+                    };
+                    let name = input.value().trim().to_string();
+                    if name.is_empty() {
+                        return;
+                    }
+
+                    let state = state.clone();
+                    let node = node.clone();
+                    spawn_local(async move {
+                        match name_node(&state, &node, op, name.into()).await {
+                            Ok(()) => active_menu.set(None),
+                            Err(err) => set_error(Some(err.to_string())),
+                        }
+                    });
+                }
+            };
+
+            let initial_name = {
+                let node = node.clone();
+                move || match operation.get() {
+                    Some(NameOperation::Rename) => node.name().to_string_lossy().to_string(),
+                    _ => String::new(),
+                }
+            };
+
+            view! {
+                <component::ContextMenu x y on_close=close>
+                    {move || match operation.get() {
+                        None => Either::Left(commands()),
+                        Some(_) => {
+                            Either::Right(
+                                view! {
+                                    <form on:submit=submit_name.clone()>
+                                        <input
+                                            node_ref=input_node
+                                            type="text"
+                                            class="input-compact"
+                                            prop:value=initial_name.clone()
+                                        />
+                                    </form>
+                                },
+                            )
+                        }
+                    }}
+                    <small class="color-brand-red-600">{error}</small>
+                </component::ContextMenu>
+            }
+        }
+
+        /// Create a new node in, or rename, `node`.
+        async fn name_node(
+            state: &state::State,
+            node: &Node,
+            op: NameOperation,
+            name: OsString,
+        ) -> Result<(), lib::fs::error::FileOperation> {
+            let path = node
+                .path(state)
+                .ok_or(lib::fs::error::FileOperation::NotFound)?;
+
+            match (op, node) {
+                (NameOperation::NewFile, Node::Directory(directory)) => {
+                    create_file(path.join(&name)).await?;
+                    state
+                        .directory_tree
+                        .insert_file(directory.id(), name)
+                        .map_err(|_| lib::fs::error::FileOperation::NotFound)?;
+                }
+                (NameOperation::NewFolder, Node::Directory(directory)) => {
+                    create_folder(path.join(&name)).await?;
+                    state
+                        .directory_tree
+                        .insert_directory(directory.id(), name)
+                        .map_err(|_| lib::fs::error::FileOperation::NotFound)?;
+                }
+                (NameOperation::Rename, node) => {
+                    let to = path.with_file_name(&name);
+                    rename_path(path, to).await?;
+                    match node {
+                        Node::Directory(directory) => directory.name.set(name),
+                        Node::File(file) => file.name.set(name),
+                    }
+                }
+                (NameOperation::NewFile | NameOperation::NewFolder, Node::File(_)) => {
+                    return Err(lib::fs::error::FileOperation::NotFound);
+                }
+            }
+
+            Ok(())
+        }
+
+        /// Move `node` to the trash.
+        async fn delete_node(
+            state: &state::State,
+            node: &Node,
+        ) -> Result<(), lib::fs::error::FileOperation> {
+            let path = node
+                .path(state)
+                .ok_or(lib::fs::error::FileOperation::NotFound)?;
+            trash_path(path).await?;
+
+            let removed = match node {
+                Node::Directory(directory) => state
+                    .directory_tree
+                    .remove_directory(directory.id())
+                    .unwrap_or_default(),
+                Node::File(file) => {
+                    state.directory_tree.remove_file(file.id());
+                    vec![file.id().clone()]
+                }
+            };
+
+            forget_files(state, &removed);
+            Ok(())
+        }
+
+        /// Move `node` into `parent`.
+        /// Errors are reported as messages.
+        async fn move_node(state: state::State, node: Node, parent: state::Directory) {
+            let res = async {
+                let from = node
+                    .path(&state)
+                    .ok_or(lib::fs::error::FileOperation::NotFound)?;
+                let to = Node::Directory(parent.clone())
+                    .path(&state)
+                    .ok_or(lib::fs::error::FileOperation::NotFound)?
+                    .join(node.name());
+                if from == to {
+                    return Ok(());
+                }
+                if to.starts_with(&from) {
+                    return Err(lib::fs::error::FileOperation::AlreadyExists);
+                }
+
+                rename_path(from, to).await?;
+                match &node {
+                    Node::Directory(directory) => {
+                        if let Err(err) = state
+                            .directory_tree
+                            .move_directory(directory.id(), parent.id())
+                        {
+                            tracing::error!(?err);
+                        }
+                    }
+                    Node::File(file) => {
+                        if let Err(err) = state.directory_tree.move_file(file.id(), parent.id()) {
+                            tracing::error!(?err);
+                        }
+                    }
+                }
+
+                Ok(())
+            }
+            .await;
+
+            if let Err(err) = res {
+                state.messages.push(message::Message::error_with_body(
+                    "Could not move.",
+                    err.to_string(),
+                ));
+            }
+        }
+
+        /// Remove references to files which no longer exist.
+        fn forget_files(state: &state::State, files: &Vec<state::ResourceId>) {
+            if files.is_empty() {
+                return;
+            }
+
+            state
+                .selected_files
+                .update(|selected| selected.retain(|id| !files.contains(id)));
+            state
+                .protected_files
+                .update(|protected| protected.retain(|id| !files.contains(id)));
+            state
+                .import_options
+                .update(|options| options.retain(|id, _| !files.contains(id)));
+            state.formulas.update(|formulas| {
+                formulas.retain(|formula| {
+                    formula.domain.with_untracked(|domain| match domain {
+                        state::FormulaDomain::CsvCell { dataset, .. }
+                        | state::FormulaDomain::WorkbookCell { dataset, .. } => {
+                            !files.contains(dataset)
+                        }
+                    })
+                })
+            });
+            state
+                .datasets
+                .update(|datasets| datasets.retain(|dataset| !files.contains(dataset.id())));
+            let active_removed = state.active_dataset.with_untracked(|active| match active {
+                state::ActiveDataset::None => false,
+                state::ActiveDataset::Some { id, .. } => files.contains(id),
+            });
+            if active_removed {
+                state.active_dataset.set(state::ActiveDataset::None);
+            }
+        }
+
+        async fn create_folder(path: PathBuf) -> Result<(), lib::fs::error::FileOperation> {
+            crate::ipc::invoke(lib::ipc::CreateFolder { path }).await
+        }
+
+        async fn create_file(path: PathBuf) -> Result<(), lib::fs::error::FileOperation> {
+            crate::ipc::invoke(lib::ipc::CreateFile { path }).await
+        }
+
+        async fn rename_path(
+            from: PathBuf,
+            to: PathBuf,
+        ) -> Result<(), lib::fs::error::FileOperation> {
+            crate::ipc::invoke(lib::ipc::RenamePath { from, to }).await
+        }
+
+        async fn trash_path(path: PathBuf) -> Result<(), lib::fs::error::FileOperation> {
+            crate::ipc::invoke(lib::ipc::TrashPath { path }).await
         }
 
-        tauri_sys::core::invoke_result("load_dataset", Args { path }).await
+        async fn reveal_path(path: PathBuf) -> Result<(), lib::fs::error::FileOperation> {
+            crate::ipc::invoke(lib::ipc::RevealPath { path }).await
+        }
     }
 
     fn io_error_message(err: io::ErrorKind) -> &'static str {
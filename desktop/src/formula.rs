@@ -3,19 +3,67 @@ use hermes_core as core;
 use hermes_desktop_lib as lib;
 use leptos::{either::Either, ev, html, prelude::*};
 use leptos_icons::Icon;
+use std::{cell::RefCell, collections::HashSet};
+
+pub use template::{Templates, apply_template_to_all, template_from_active};
+
+/// Colors distinguishing the references of a formula.
+const REFERENCE_COLORS: &[&str] = &[
+    "#2563eb", "#dc2626", "#16a34a", "#d97706", "#9333ea", "#db2777", "#0891b2",
+];
+
+/// # Returns
+/// Color of the `idx`th reference of a formula.
+pub fn reference_color(idx: usize) -> &'static str {
+    REFERENCE_COLORS[idx % REFERENCE_COLORS.len()]
+}
 
 #[component]
 pub fn Workspace() -> impl IntoView {
     let state = expect_context::<state::State>();
 
+    let new_formula_vis = expect_context::<state::NewFormulaDialogVisibility>();
+    let show_new_formula = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+
+        new_formula_vis.set(true);
+    };
+
+    let expressions = {
+        let formulas = state.formulas.read_only();
+        move || {
+            formulas.with(|formulas| {
+                let mut expressions = formulas
+                    .iter()
+                    .map(|formula| formula.value.with(core::expr::normalize))
+                    .collect::<Vec<_>>();
+                expressions.sort();
+                expressions.dedup();
+                expressions
+            })
+        }
+    };
+
     view! {
         <div>
-            <div class="pb">
-                <h2 class="font-bold uppercase">"Formulas"</h2>
+            <div class="pb flex gap-2">
+                <h2 class="grow font-bold uppercase">"Formulas"</h2>
+                <div>
+                    <button
+                        type="button"
+                        class="btn-cmd cursor-pointer"
+                        title="New formula"
+                        on:mousedown=show_new_formula
+                    >
+                        <Icon icon=icon::Add />
+                    </button>
+                </div>
             </div>
             <div>
-                <For each=state.formulas.read_only() key=|formula| formula.id().clone() let:formula>
-                    <Formula formula />
+                <For each=expressions key=|expression| expression.clone() let:expression>
+                    <library::Expression expression />
                 </For>
             </div>
         </div>
@@ -30,9 +78,12 @@ fn Formula(formula: state::Formula) -> impl IntoView {
     let domain = formula.domain.read_only();
     let title = {
         let datasets = state.datasets;
+        let reference_style = state.reference_style;
         move || {
             domain.with(|domain| match domain {
-                state::FormulaDomain::CsvCell { dataset, cell } => cell.to_string(),
+                state::FormulaDomain::CsvCell { dataset, cell } => {
+                    reference_style.with(|style| style.cell_label(&cell))
+                }
 
                 state::FormulaDomain::WorkbookCell {
                     dataset,
@@ -47,7 +98,10 @@ fn Formula(formula: state::Formula) -> impl IntoView {
                         .clone();
 
                     match dataset {
-                        state::Dataset::Csv(_) => unreachable!(),
+                        state::Dataset::Csv(_) => {
+                            tracing::warn!(err = ?error::SyncFormula::DomainMismatch);
+                            reference_style.with(|style| style.cell_label(&cell))
+                        }
                         state::Dataset::Workbook(workbook) => {
                             let sheet_name = workbook
                                 .sheets
@@ -58,7 +112,11 @@ fn Formula(formula: state::Formula) -> impl IntoView {
                                 })
                                 .expect("sheet to exist");
 
-                            format!("{}!{cell}", sheet_name.get())
+                            format!(
+                                "{}!{}",
+                                core::data::SheetIndex::Label(sheet_name.get()),
+                                reference_style.with(|style| style.cell_label(&cell))
+                            )
                         }
                     }
                 }
@@ -149,6 +207,268 @@ fn Formula(formula: state::Formula) -> impl IntoView {
     }
 }
 
+/// Dialog to create a formula by typing its target cell.
+#[component]
+pub fn NewFormula() -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let workspace_owner = expect_context::<state::WorkspaceOwner>();
+    let editor_vis = expect_context::<state::FormulaEditorVisibility>();
+    let visible = expect_context::<state::NewFormulaDialogVisibility>();
+    let dataset_node = NodeRef::<html::Select>::new();
+    let target_node = NodeRef::<html::Input>::new();
+    let (error, set_error) = signal::<Option<&'static str>>(None);
+
+    Effect::new(move || {
+        if !visible.get() {
+            return;
+        }
+        set_error(None);
+        if let Some(input) = target_node.get() {
+            input.set_value("");
+            if let Err(err) = input.focus() {
+                tracing::warn!(?err);
+            }
+        }
+    });
+
+    let datasets = {
+        let datasets = state.datasets;
+        let directory_tree = state.directory_tree.clone();
+        move || {
+            datasets.with(|datasets| {
+                datasets
+                    .iter()
+                    .map(|dataset| {
+                        let path = directory_tree
+                            .get_file_path(dataset.id())
+                            .map(|path| path.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        (dataset.id().clone(), path)
+                    })
+                    .collect::<Vec<_>>()
+            })
+        }
+    };
+
+    let is_active = {
+        let active = state.active_dataset.read_only();
+        move |id: &state::ResourceId| {
+            active
+                .read()
+                .as_ref()
+                .map(|active| active == id)
+                .unwrap_or(false)
+        }
+    };
+
+    let create = {
+        let datasets = state.datasets;
+        let formulas = state.formulas;
+        let active_formula = state.active_formula;
+        move |e: ev::SubmitEvent| {
+            e.prevent_default();
+            let (Some(dataset_input), Some(target_input)) =
+                (dataset_node.get_untracked(), target_node.get_untracked())
+            else {
+                return;
+            };
+
+            let dataset_id = dataset_input.value();
+            let dataset = datasets.with_untracked(|datasets| {
+                datasets
+                    .iter()
+                    .find(|dataset| dataset.id().to_string() == dataset_id)
+                    .cloned()
+            });
+            let domain = match dataset {
+                Some(dataset) if state.is_protected(dataset.id()) => {
+                    Err(error::NewFormula::Protected)
+                }
+                Some(dataset) => formula_domain(&dataset, target_input.value().trim()),
+                None => Err(error::NewFormula::DatasetNotSelected),
+            };
+
+            match domain {
+                Ok(domain) => {
+                    open_formula(
+                        domain,
+                        formulas,
+                        active_formula,
+                        &workspace_owner,
+                        editor_vis,
+                    );
+                    visible.set(false);
+                }
+                Err(err) => set_error(Some(new_formula_error_message(err))),
+            }
+        }
+    };
+
+    let close_on_escape = move |e: ev::KeyboardEvent| {
+        if e.key() == "Escape" {
+            e.prevent_default();
+            visible.set(false);
+        }
+    };
+
+    let close = move |_: ev::MouseEvent| visible.set(false);
+
+    view! {
+        <div class:hidden=move || !visible.get() on:keydown=close_on_escape>
+            <div class="fixed inset-0 z-30" on:mousedown=close></div>
+            <form
+                class="fixed z-40 top-12 left-1/2 -translate-x-1/2 w-1/2 p-1 flex flex-col gap-1 \
+                bg-white dark:bg-secondary-800 border border-secondary-100 dark:border-secondary-600"
+                on:submit=create
+            >
+                <h2 class="font-bold uppercase">"New formula"</h2>
+                <select node_ref=dataset_node class="input-compact">
+                    {move || {
+                        datasets()
+                            .into_iter()
+                            .map(|(id, path)| {
+                                let selected = is_active(&id);
+                                view! {
+                                    <option value=id.to_string() selected=selected>
+                                        {path}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()
+                    }}
+                </select>
+                <input
+                    node_ref=target_node
+                    type="text"
+                    class="input-compact"
+                    placeholder="Target cell, e.g. B7 or Sheet1!B7"
+                />
+                <small class="color-brand-red-600">{error}</small>
+            </form>
+        </div>
+    }
+}
+
+/// Open the formula for a domain, creating it if needed.
+pub fn open_formula(
+    domain: state::FormulaDomain,
+    formulas: state::Formulas,
+    active_formula: RwSignal<Option<state::ResourceId>>,
+    owner: &state::WorkspaceOwner,
+    editor_vis: state::FormulaEditorVisibility,
+) {
+    let formula_id = if let Some(formula) = formulas.get_by_containing_domain(&domain) {
+        formula.id().clone()
+    } else {
+        let formula = owner.with(|| state::Formula::new(domain));
+        let id = formula.id().clone();
+        formulas.write().push(formula);
+        id
+    };
+
+    let _ = active_formula.write().insert(formula_id);
+    editor_vis.set(true);
+}
+
+/// Resolve a typed target cell into a formula domain.
+///
+/// # Arguments
+/// + `target`: Cell reference of the form `[<sheet>!]<cell>`.
+///     If the sheet is omitted, a workbook's active sheet is used.
+fn formula_domain(
+    dataset: &state::Dataset,
+    target: &str,
+) -> Result<state::FormulaDomain, error::NewFormula> {
+    let cell_ref =
+        core::data::CellRef::from_str(target).ok_or(error::NewFormula::InvalidReference)?;
+    let cell = core::data::CellIndex::new(cell_ref.row, cell_ref.col);
+
+    let (domain, cells) = match dataset {
+        state::Dataset::Csv(csv) => {
+            match cell_ref.sheet {
+                core::data::SheetRef::Relative
+                | core::data::SheetRef::Absolute(core::data::SheetIndex::Index(0)) => {}
+                core::data::SheetRef::Absolute(_) => {
+                    return Err(error::NewFormula::SheetNotFound);
+                }
+            }
+
+            let domain = state::FormulaDomain::CsvCell {
+                dataset: csv.id().clone(),
+                cell: cell.clone(),
+            };
+            (domain, csv.sheet().cells)
+        }
+
+        state::Dataset::Workbook(workbook) => {
+            let sheet = workbook.sheets.with_untracked(|sheets| {
+                match &cell_ref.sheet {
+                    core::data::SheetRef::Relative => {
+                        sheets.get(workbook.active_sheet.get_untracked())
+                    }
+                    core::data::SheetRef::Absolute(core::data::SheetIndex::Index(idx)) => {
+                        sheets.get(*idx as usize)
+                    }
+                    core::data::SheetRef::Absolute(core::data::SheetIndex::Label(label)) => {
+                        sheets.iter().find(|sheet| {
+                            sheet
+                                .name
+                                .with_untracked(|name| name.eq_ignore_ascii_case(label))
+                        })
+                    }
+                }
+                .map(|sheet| (sheet.id().clone(), sheet.cells))
+            });
+            let (sheet, cells) = sheet.ok_or(error::NewFormula::SheetNotFound)?;
+
+            let domain = state::FormulaDomain::WorkbookCell {
+                dataset: workbook.id().clone(),
+                sheet,
+                cell: cell.clone(),
+            };
+            (domain, cells)
+        }
+    };
+
+    if cells.with_untracked(|cells| matches!(cells.get(&cell), Some(state::CellValue::Fixed(_)))) {
+        return Err(error::NewFormula::CellNotEmpty);
+    }
+
+    Ok(domain)
+}
+
+fn new_formula_error_message(err: error::NewFormula) -> &'static str {
+    match err {
+        error::NewFormula::DatasetNotSelected => "Select a dataset.",
+        error::NewFormula::InvalidReference => "Invalid cell reference.",
+        error::NewFormula::SheetNotFound => "Sheet does not exist.",
+        error::NewFormula::CellNotEmpty => "Cell contains data.",
+        error::NewFormula::Protected => "Dataset is protected from modification.",
+    }
+}
+
+/// Describe how formulas written under another version of the expression language
+/// may behave differently.
+pub fn compatibility_message(compatibility: &core::expr::Compatibility) -> String {
+    match compatibility {
+        core::expr::Compatibility::Current => String::new(),
+        core::expr::Compatibility::Older(changes) => {
+            let mut message =
+                "Formulas were written for an earlier version of hermes. Results may differ:"
+                    .to_string();
+            for change in changes {
+                message.push_str("\n+ ");
+                message.push_str(change);
+            }
+            message
+        }
+        core::expr::Compatibility::Newer => {
+            "Formulas were written for a newer version of hermes and may not be supported."
+                .to_string()
+        }
+    }
+}
+
 /// Group consecutive indices into groups returning a list of `(start, end)` indexes for each consecutive group.
 ///
 /// # Examples
@@ -210,7 +530,8 @@ fn EditorEnabled(formula: state::Formula) -> impl IntoView {
     let state = expect_context::<state::State>();
     let workspace_owner = expect_context::<state::WorkspaceOwner>();
     let editor_vis = expect_context::<state::FormulaEditorVisibility>();
-    let input_node = NodeRef::<html::Input>::new();
+    let input_node = NodeRef::<html::Textarea>::new();
+    let highlight_node = NodeRef::<html::Div>::new();
 
     Effect::new(move || {
         let Some(input) = input_node.get() else {
@@ -223,15 +544,54 @@ fn EditorEnabled(formula: state::Formula) -> impl IntoView {
         }
     });
 
-    let (input, set_input) = signal(formula.value.get_untracked());
-    let (error, set_error) = signal::<Option<&'static str>>(None);
+    let (input, set_input) = signal(
+        state
+            .formula_draft
+            .get_untracked()
+            .unwrap_or_else(|| formula.value.get_untracked()),
+    );
+    let (error, set_error) = signal::<Option<String>>(None);
+
+    let formula_draft = state.formula_draft;
+    Effect::new({
+        let value = formula.value;
+        move || {
+            let draft = input.get();
+            if value.with(|value| *value == draft) {
+                formula_draft.set(None);
+            } else {
+                formula_draft.set(Some(draft));
+            }
+        }
+    });
+    on_cleanup(move || formula_draft.set(None));
+
+    let references = expect_context::<state::FormulaReferences>();
+    Effect::new(move || {
+        references.set(input.with(|input| core::expr::references(input)));
+    });
+    on_cleanup(move || references.set(vec![]));
+
+    let is_protected = {
+        let protected_files = state.protected_files;
+        let domain = formula.domain;
+        Memo::new(move |_| {
+            domain.with(|domain| protected_files.with(|files| files.contains(domain.dataset())))
+        })
+    };
 
     let save_formula = {
         let datasets = state.datasets;
         let formulas = state.formulas;
         let active_formula = state.active_formula;
+        let settings = state.settings;
         let formula = formula.clone();
         move || {
+            if is_protected.get_untracked() {
+                set_error(Some("dataset is protected from modification".to_string()));
+                return;
+            }
+
             input.with_untracked(|input| {
                 let input = input.trim();
                 if input.is_empty() {
@@ -243,18 +603,30 @@ fn EditorEnabled(formula: state::Formula) -> impl IntoView {
                 } else {
                     match core::expr::parse(input) {
                         Ok(_expr) => {
-                            set_error(None);
-                            formula.value.set(input.to_string());
-                            sync_formula(&formula, &datasets, &workspace_owner);
-                        }
-                        Err(err) => {
-                            let msg = match err {
-                                core::expr::Error::Tokenize(_kind) => "syntax error",
-                                core::expr::Error::Parse(_kind) => "parse error",
-                                _ => unreachable!("invalid error kind"),
-                            };
-                            set_error(Some(msg));
+                            formula.set_value(input.to_string());
+                            let options =
+                                settings.with_untracked(|settings| settings.eval_options());
+                            match sync_formula(&formula, &datasets, &workspace_owner, options) {
+                                Ok(()) => set_error(None),
+                                Err(err) => {
+                                    tracing::error!(?err);
+                                    set_error(Some("could not evaluate formula".to_string()));
+                                    return;
+                                }
+                            }
+
+                            let errors = sync_dependent_formulas(
+                                &formula,
+                                formulas,
+                                &datasets,
+                                &workspace_owner,
+                                options,
+                            );
+                            if errors > 0 {
+                                tracing::warn!("{errors} dependent formula(s) could not be synced");
+                            }
                         }
+                        Err(err) => set_error(Some(err.to_string())),
                     }
                 }
             })
@@ -269,12 +641,91 @@ fn EditorEnabled(formula: state::Formula) -> impl IntoView {
         }
     };
 
+    let save_on_ctrl_enter = {
+        let save_formula = save_formula.clone();
+        move |e: ev::KeyboardEvent| {
+            if e.key() == "Enter" && (e.ctrl_key() || e.meta_key()) && !e.shift_key() {
+                e.prevent_default();
+                save_formula()
+            }
+        }
+    };
+
+    let sync_scroll = move |_: ev::Event| {
+        if let (Some(input), Some(highlight)) =
+            (input_node.get_untracked(), highlight_node.get_untracked())
+        {
+            highlight.set_scroll_top(input.scroll_top());
+            highlight.set_scroll_left(input.scroll_left());
+        }
+    };
+
+    let history = {
+        let history = formula.history;
+        let save_formula = save_formula.clone();
+        move || {
+            history.with(|history| {
+                if history.is_empty() {
+                    return Either::Left(view! {
+                        <li class="text-sm text-secondary-700 dark:text-secondary-200">
+                            "No previous formulas."
+                        </li>
+                    });
+                }
+
+                let revisions = history
+                    .iter()
+                    .rev()
+                    .map(|revision| {
+                        let value = revision.value.clone();
+                        let revert = {
+                            let value = value.clone();
+                            let save_formula = save_formula.clone();
+                            move |e: ev::MouseEvent| {
+                                if e.button() != types::MouseButton::Primary {
+                                    return;
+                                }
+
+                                set_input(value.clone());
+                                save_formula();
+                            }
+                        };
+
+                        view! {
+                            <li class="flex gap-2 items-center group/revision">
+                                <small class="text-secondary-700 dark:text-secondary-200">
+                                    {format_time(revision.timestamp)}
+                                </small>
+                                <span class="grow truncate font-mono" title=value.clone()>
+                                    {value.clone()}
+                                </span>
+                                <button
+                                    type="button"
+                                    class="hidden group-hover/revision:block btn-cmd btn-secondary"
+                                    title="Revert to this formula"
+                                    disabled=is_protected
+                                    on:mousedown=revert
+                                >
+                                    <Icon icon=icon::Revert />
+                                </button>
+                            </li>
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                Either::Right(revisions)
+            })
+        }
+    };
+
     let title = {
         let domain = formula.domain.read_only();
         let directory_tree = state.directory_tree.clone();
         let datasets = state.datasets;
+        let reference_style = state.reference_style;
         move || match domain.get() {
-            state::FormulaDomain::CsvCell { dataset, cell } => cell.to_string(),
+            state::FormulaDomain::CsvCell { dataset, cell } => {
+                reference_style.with(|style| style.cell_label(&cell))
+            }
 
             state::FormulaDomain::WorkbookCell {
                 dataset,
@@ -291,7 +742,10 @@ fn EditorEnabled(formula: state::Formula) -> impl IntoView {
                     .expect("dataset to exist")
                     .clone();
                 match dataset {
-                    state::Dataset::Csv(_) => unreachable!(),
+                    state::Dataset::Csv(_) => {
+                        tracing::warn!(err = ?error::SyncFormula::DomainMismatch);
+                        reference_style.with(|style| style.cell_label(&cell))
+                    }
                     state::Dataset::Workbook(workbook) => {
                         let sheet_name = workbook
                             .sheets
@@ -299,7 +753,11 @@ fn EditorEnabled(formula: state::Formula) -> impl IntoView {
                             .iter()
                             .find_map(|s| (*s.id() == sheet).then_some(s.name.read_only()))
                             .expect("sheet to exist");
-                        format!("{}!{cell}", sheet_name.get())
+                        format!(
+                            "{}!{}",
+                            core::data::SheetIndex::Label(sheet_name.get()),
+                            reference_style.with(|style| style.cell_label(&cell))
+                        )
                     }
                 }
             }
@@ -307,146 +765,1285 @@ fn EditorEnabled(formula: state::Formula) -> impl IntoView {
     };
 
     view! {
-        <div class="flex">
-            <div>{title}</div>
-            <form class="grow" on:submit=save_formula_trigger>
-                <div>
-                    <label
-                        class="flex border border-transparent"
-                        class:border-color-brand-red-600=move || error.read().is_some()
+        <div class="h-full flex gap-2 min-h-0">
+            <form class="grow min-w-0 flex flex-col" on:submit=save_formula_trigger>
+                <div class="flex gap-2 items-center">
+                    <div class="grow font-bold">{title}</div>
+                    <small class="text-secondary-700 dark:text-secondary-200">
+                        "Ctrl+Enter to apply"
+                    </small>
+                    <button
+                        type="submit"
+                        class="btn-cmd btn-secondary"
+                        title="Apply formula"
+                        disabled=is_protected
                     >
                         <Icon icon=icon::Equal />
-                        <input
-                            node_ref=input_node
-                            name="formula"
-                            type="text"
-                            class="grow input-compact"
-                            bind:value=(input, set_input)
-                        />
-                    </label>
-                    <div>
-                        <small class="color-brand-red-600">{error}</small>
+                    </button>
+                </div>
+                <div
+                    class="grow min-h-0 relative border border-transparent"
+                    class:border-color-brand-red-600=move || error.read().is_some()
+                >
+                    <div
+                        node_ref=highlight_node
+                        class="absolute inset-0 input-compact pointer-events-none whitespace-pre-wrap break-words overflow-hidden font-mono"
+                        aria-hidden="true"
+                    >
+                        {move || {
+                            input.with(|input| highlight_references(input, &references.read()))
+                        }}
                     </div>
+                    <textarea
+                        node_ref=input_node
+                        name="formula"
+                        class="relative w-full h-full resize-none input-compact bg-transparent text-transparent font-mono caret-black dark:caret-white"
+                        spellcheck="false"
+                        prop:value=input
+                        on:input=move |e| set_input(event_target_value(&e))
+                        on:keydown=save_on_ctrl_enter
+                        on:scroll=sync_scroll
+                        disabled=is_protected
+                        title=move || {
+                            is_protected
+                                .get()
+                                .then_some("The dataset is protected from modification.")
+                        }
+                    ></textarea>
+                </div>
+                <div>
+                    <small class="color-brand-red-600">{error}</small>
                 </div>
             </form>
+            <div class="w-1/3 min-w-0 flex flex-col">
+                <h3 class="font-bold">"History"</h3>
+                <ul class="grow min-h-0 overflow-y-auto">{history}</ul>
+            </div>
         </div>
     }
 }
 
+/// # Returns
+/// Local time of a timestamp, in milliseconds since the Unix epoch.
+fn format_time(timestamp: f64) -> String {
+    let date = js_sys::Date::new(&wasm_bindgen::JsValue::from_f64(timestamp));
+    String::from(date.to_locale_time_string("default"))
+}
+
+/// Split formula text into segments, coloring references.
+fn highlight_references(input: &str, references: &Vec<core::expr::Reference>) -> Vec<AnyView> {
+    let chars = input.chars().collect::<Vec<_>>();
+    let text = |start: usize, end: usize| chars[start..end].iter().collect::<String>();
+
+    let mut segments = vec![];
+    let mut pos = 0;
+    for (idx, reference) in references.iter().enumerate() {
+        let span = reference.span.chars();
+        let start = span.start.min(chars.len());
+        let end = span.end.min(chars.len());
+        if start < pos {
+            continue;
+        }
+        if start > pos {
+            segments.push(view! { <span>{text(pos, start)}</span> }.into_any());
+        }
+        segments.push(
+            view! { <span style:color=reference_color(idx)>{text(start, end)}</span> }.into_any(),
+        );
+        pos = end;
+    }
+    if pos < chars.len() {
+        segments.push(view! { <span>{text(pos, chars.len())}</span> }.into_any());
+    }
+
+    segments
+}
+
+/// Re-evaluate the formulas of a dataset, e.g. after it was reloaded.
+/// Formulas are evaluated after the formulas they reference.
+///
+/// # Returns
+/// Number of formulas that could not be synced.
+fn sync_dataset_formulas(
+    dataset: &state::ResourceId,
+    formulas: state::Formulas,
+    datasets: &state::Datasets,
+    owner: &state::WorkspaceOwner,
+    options: core::expr::Options,
+) -> usize {
+    let (resolved, unresolved, dependencies) = dataset_dependencies(dataset, formulas, datasets);
+    let ordered = dependencies
+        .all()
+        .into_iter()
+        .map(|idx| &resolved[idx])
+        .chain(unresolved.iter());
+    sync_formulas(ordered, datasets, owner, options)
+}
+
+/// Re-evaluate the formulas of a sheet of a dataset, e.g. after it became the active sheet.
+/// Formulas are evaluated after the formulas they reference.
+/// Formulas of other sheets are not evaluated, so their current values are used.
+///
+/// # Arguments
+/// + `sheet`: Index of the sheet.
+///
+/// # Returns
+/// Number of formulas that could not be synced.
+pub fn sync_sheet_formulas(
+    dataset: &state::ResourceId,
+    sheet: core::data::IndexType,
+    formulas: state::Formulas,
+    datasets: &state::Datasets,
+    owner: &state::WorkspaceOwner,
+    options: core::expr::Options,
+) -> usize {
+    let (resolved, _, dependencies) = dataset_dependencies(dataset, formulas, datasets);
+    let ordered = dependencies
+        .all()
+        .into_iter()
+        .filter(|&idx| dependencies.cell(idx).sheet == sheet)
+        .map(|idx| &resolved[idx]);
+    sync_formulas(ordered, datasets, owner, options)
+}
+
+/// Re-evaluate the formulas of a dataset on a background thread, e.g. after it was loaded.
+/// Formulas are evaluated after the formulas they reference,
+/// and their cells are set once all results arrive.
+/// Falls back to evaluating on the ui thread if the background evaluation does not complete.
+///
+/// # Returns
+/// Number of formulas that could not be synced.
+pub async fn eval_dataset_formulas(
+    dataset: state::ResourceId,
+    formulas: state::Formulas,
+    datasets: state::Datasets,
+    owner: state::WorkspaceOwner,
+    options: core::expr::Options,
+) -> usize {
+    let (resolved, unresolved, dependencies) = dataset_dependencies(&dataset, formulas, &datasets);
+    if resolved.is_empty() {
+        return unresolved.len();
+    }
+
+    let order = dependencies.all();
+    let sheets = datasets.with_untracked(|datasets| {
+        let dataset = datasets.iter().find(|ds| *ds.id() == dataset)?;
+        let names = match dataset {
+            state::Dataset::Csv(csv) => vec![csv.sheet().name.get_untracked()],
+            state::Dataset::Workbook(workbook) => workbook
+                .sheets
+                .read_untracked()
+                .iter()
+                .map(|sheet| sheet.name.get_untracked())
+                .collect(),
+        };
+        Some(
+            names
+                .into_iter()
+                .zip(dataset.values())
+                .map(|(name, cells)| lib::formula::EvaluateSheet { name, cells })
+                .collect::<Vec<_>>(),
+        )
+    });
+    let Some(sheets) = sheets else {
+        return resolved.len() + unresolved.len();
+    };
+
+    let evaluate = lib::formula::Evaluate {
+        sheets,
+        formulas: order
+            .iter()
+            .map(|&idx| {
+                (
+                    dependencies.cell(idx).clone(),
+                    resolved[idx].value.get_untracked(),
+                )
+            })
+            .collect(),
+        options,
+    };
+    let Some(results) = evaluate_formulas(evaluate).await else {
+        tracing::warn!("background evaluation did not complete");
+        return sync_dataset_formulas(&dataset, formulas, &datasets, &owner, options);
+    };
+
+    let mut failed = unresolved.len();
+    for (idx, traced) in order.into_iter().zip(results) {
+        let provenance =
+            lib::audit::Provenance::now(resolved[idx].value.get_untracked(), traced.inputs);
+        if let Err(err) = set_formula_value(&resolved[idx], &datasets, &owner, |_, _| {
+            (traced.result, provenance)
+        }) {
+            tracing::warn!(?err);
+            failed += 1;
+        }
+    }
+    failed
+}
+
+async fn evaluate_formulas(
+    evaluate: lib::formula::Evaluate,
+) -> Option<Vec<lib::formula::TracedResult>> {
+    crate::ipc::invoke_infallible(lib::ipc::EvaluateFormulas { evaluate }).await
+}
+
+/// Remove formulas, emptying their cells.
+/// The remaining formulas of their datasets are re-evaluated,
+/// so formulas referencing the cleared cells are updated.
+///
+/// # Returns
+/// Number of formulas that could not be synced.
+pub fn clear_formulas(
+    cleared: &[state::Formula],
+    formulas: state::Formulas,
+    datasets: &state::Datasets,
+    owner: &state::WorkspaceOwner,
+    options: core::expr::Options,
+) -> usize {
+    for formula in cleared {
+        if let Err(err) = set_cell_value(formula, datasets, owner, |_, _| {
+            state::VariableCellValue::Empty
+        }) {
+            tracing::warn!(?err);
+        }
+    }
+
+    formulas.update(|formulas| {
+        formulas.retain(|formula| !cleared.iter().any(|cleared| cleared.id() == formula.id()))
+    });
+
+    let cleared_datasets = cleared
+        .iter()
+        .map(|formula| {
+            formula.domain.with_untracked(|domain| match domain {
+                state::FormulaDomain::CsvCell { dataset, .. }
+                | state::FormulaDomain::WorkbookCell { dataset, .. } => dataset.clone(),
+            })
+        })
+        .collect::<HashSet<_>>();
+    cleared_datasets
+        .iter()
+        .map(|dataset| sync_dataset_formulas(dataset, formulas, datasets, owner, options))
+        .sum()
+}
+
+/// Re-evaluate the formulas that depend on a formula's cell, directly or transitively,
+/// e.g. after the formula changed.
+/// Formulas are evaluated after the formulas they reference.
+///
+/// # Returns
+/// Number of formulas that could not be synced.
+fn sync_dependent_formulas(
+    formula: &state::Formula,
+    formulas: state::Formulas,
+    datasets: &state::Datasets,
+    owner: &state::WorkspaceOwner,
+    options: core::expr::Options,
+) -> usize {
+    let dataset = formula.domain.with_untracked(|domain| match domain {
+        state::FormulaDomain::CsvCell { dataset, .. }
+        | state::FormulaDomain::WorkbookCell { dataset, .. } => dataset.clone(),
+    });
+    let (resolved, _, dependencies) = dataset_dependencies(&dataset, formulas, datasets);
+    let Some(changed) = resolved
+        .iter()
+        .position(|resolved| resolved.id() == formula.id())
+    else {
+        return 0;
+    };
+
+    let ordered = dependencies
+        .dirty(&[dependencies.cell(changed).clone()])
+        .into_iter()
+        .filter(|&idx| idx != changed)
+        .map(|idx| &resolved[idx]);
+    sync_formulas(ordered, datasets, owner, options)
+}
+
+/// # Returns
+/// Number of formulas that could not be synced.
+fn sync_formulas<'a>(
+    formulas: impl IntoIterator<Item = &'a state::Formula>,
+    datasets: &state::Datasets,
+    owner: &state::WorkspaceOwner,
+    options: core::expr::Options,
+) -> usize {
+    formulas
+        .into_iter()
+        .filter(
+            |formula| match sync_formula(formula, datasets, owner, options) {
+                Ok(()) => false,
+                Err(err) => {
+                    tracing::warn!(?err);
+                    true
+                }
+            },
+        )
+        .count()
+}
+
+/// # Returns
+/// `(resolved, unresolved, dependencies)` where
+/// `resolved` are the formulas of the dataset whose cells could be found, indexed by `dependencies`,
+/// and `unresolved` are the remaining formulas of the dataset.
+fn dataset_dependencies(
+    dataset: &state::ResourceId,
+    formulas: state::Formulas,
+    datasets: &state::Datasets,
+) -> (
+    Vec<state::Formula>,
+    Vec<state::Formula>,
+    lib::formula::Dependencies,
+) {
+    let formulas = formulas
+        .read_untracked()
+        .iter()
+        .filter(|formula| {
+            formula.domain.with_untracked(|domain| match domain {
+                state::FormulaDomain::CsvCell { dataset: id, .. }
+                | state::FormulaDomain::WorkbookCell { dataset: id, .. } => id == dataset,
+            })
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+
+    datasets.with_untracked(|datasets| {
+        let Some(dataset) = datasets.iter().find(|ds| ds.id() == dataset) else {
+            return (vec![], formulas, lib::formula::Dependencies::default());
+        };
+
+        let mut resolved = vec![];
+        let mut unresolved = vec![];
+        let mut cells = vec![];
+        for formula in formulas {
+            match formula
+                .domain
+                .with_untracked(|domain| formula_cell(domain, dataset))
+            {
+                Some(cell) => {
+                    cells.push((cell, formula.value.get_untracked()));
+                    resolved.push(formula);
+                }
+                None => unresolved.push(formula),
+            }
+        }
+
+        let sheets = match dataset {
+            state::Dataset::Csv(csv) => vec![csv.sheet().name.get_untracked()],
+            state::Dataset::Workbook(workbook) => workbook
+                .sheets
+                .read_untracked()
+                .iter()
+                .map(|sheet| sheet.name.get_untracked())
+                .collect::<Vec<_>>(),
+        };
+        let dependencies = lib::formula::Dependencies::new(
+            cells
+                .iter()
+                .map(|(cell, value)| (cell.clone(), value.as_str())),
+            |sheet, origin| sheet.resolve(origin, &sheets),
+        );
+
+        (resolved, unresolved, dependencies)
+    })
+}
+
+/// # Returns
+/// Path of the formula's cell, or `None` if the cell is not in the dataset.
+fn formula_cell(
+    domain: &state::FormulaDomain,
+    dataset: &state::Dataset,
+) -> Option<core::data::CellPath> {
+    match (domain, dataset) {
+        (state::FormulaDomain::CsvCell { cell, .. }, state::Dataset::Csv(_)) => {
+            Some(core::data::CellPath {
+                sheet: 0,
+                row: cell.row(),
+                col: cell.col(),
+            })
+        }
+
+        (
+            state::FormulaDomain::WorkbookCell { sheet, cell, .. },
+            state::Dataset::Workbook(workbook),
+        ) => {
+            let sheet = workbook
+                .sheets
+                .read_untracked()
+                .iter()
+                .position(|s| s.id() == sheet)?;
+            Some(core::data::CellPath {
+                sheet: sheet as core::data::IndexType,
+                row: cell.row(),
+                col: cell.col(),
+            })
+        }
+
+        _ => None,
+    }
+}
+
 /// Update workbook data for formula.
 /// Creates a new cell if needed.
+///
+/// # Returns
+/// `Err` if the formula's cell could not be resolved or set.
+/// Evaluation errors are stored as the cell's value.
 fn sync_formula(
     formula: &state::Formula,
     datasets: &state::Datasets,
     owner: &state::WorkspaceOwner,
-) {
-    formula.domain.with_untracked(|domain| match domain {
-        state::FormulaDomain::CsvCell { dataset, cell } => datasets.with_untracked(|datasets| {
-            let dataset = datasets
-                .iter()
-                .find(|ds| ds.id() == dataset)
-                .expect("dataset should exist");
+    options: core::expr::Options,
+) -> Result<(), error::SyncFormula> {
+    set_formula_value(formula, datasets, owner, |dataset, origin| {
+        let expression = formula.value.get_untracked();
+        let inputs = RefCell::new(vec![]);
+        let ctx = core::expr::WithOptions {
+            ctx: core::expr::Recording {
+                ctx: dataset,
+                reads: &inputs,
+            },
+            options,
+        };
+        let value = core::expr::eval(&expression, ctx, origin).map(|value| value.into());
+        let provenance = lib::audit::Provenance::now(expression, inputs.into_inner());
+        (value, provenance)
+    })
+}
 
-            let (cells, origin) = match dataset {
-                state::Dataset::Csv(csv) => (
-                    csv.sheet().cells,
+/// Set the value of a formula's cell.
+/// Creates a new cell if needed.
+///
+/// # Arguments
+/// + `value`: Value of the formula and how it was computed, given its dataset and cell.
+///
+/// # Returns
+/// `Err` if the formula's cell could not be resolved or set.
+fn set_formula_value(
+    formula: &state::Formula,
+    datasets: &state::Datasets,
+    owner: &state::WorkspaceOwner,
+    value: impl FnOnce(
+        &state::Dataset,
+        &core::data::CellPath,
+    ) -> (state::FormulaCellValue, lib::audit::Provenance),
+) -> Result<(), error::SyncFormula> {
+    set_cell_value(formula, datasets, owner, |dataset, origin| {
+        let (value, provenance) = value(dataset, origin);
+        state::VariableCellValue::Formula(value, provenance)
+    })
+}
+
+/// Set the value of a formula's cell.
+/// Creates a new cell if needed.
+///
+/// # Returns
+/// `Err` if the formula's cell could not be resolved or set.
+fn set_cell_value(
+    formula: &state::Formula,
+    datasets: &state::Datasets,
+    owner: &state::WorkspaceOwner,
+    value: impl FnOnce(&state::Dataset, &core::data::CellPath) -> state::VariableCellValue,
+) -> Result<(), error::SyncFormula> {
+    let domain = formula.domain.get_untracked();
+    datasets.with_untracked(|datasets| {
+        let (dataset_id, cell) = match &domain {
+            state::FormulaDomain::CsvCell { dataset, cell } => (dataset, cell),
+            state::FormulaDomain::WorkbookCell { dataset, cell, .. } => (dataset, cell),
+        };
+
+        let dataset = datasets
+            .iter()
+            .find(|ds| ds.id() == dataset_id)
+            .ok_or(error::SyncFormula::DatasetNotFound)?;
+
+        let (cells, origin) = match (&domain, dataset) {
+            (state::FormulaDomain::CsvCell { cell, .. }, state::Dataset::Csv(csv)) => (
+                csv.sheet().cells,
+                core::data::CellPath {
+                    sheet: 0,
+                    row: cell.row(),
+                    col: cell.col(),
+                },
+            ),
+
+            (
+                state::FormulaDomain::WorkbookCell { sheet, cell, .. },
+                state::Dataset::Workbook(workbook),
+            ) => {
+                let (sheet_idx, cells) = workbook
+                    .sheets
+                    .read_untracked()
+                    .iter()
+                    .enumerate()
+                    .find_map(|(idx, s)| (s.id() == sheet).then_some((idx, s.cells)))
+                    .ok_or(error::SyncFormula::SheetNotFound)?;
+
+                (
+                    cells,
                     core::data::CellPath {
-                        sheet: 0,
+                        sheet: sheet_idx as core::data::IndexType,
                         row: cell.row(),
                         col: cell.col(),
                     },
-                ),
-                state::Dataset::Workbook(workbook) => unreachable!(),
-            };
+                )
+            }
 
-            let value = core::expr::eval(formula.value.get_untracked(), dataset, &origin);
-            if cells.with_untracked(|cells| cells.contains_key(cell)) {
-                cells.with_untracked(|cells| {
-                    let state::CellValue::Variable(cell) = cells.get(cell).expect("cell to exist")
-                    else {
-                        panic!("expected a variable cell");
-                    };
-                    cell.set(state::VariableCellValue::Formula(
-                        value.map(|value| value.into()),
-                    ));
-                });
-            } else {
+            _ => return Err(error::SyncFormula::DomainMismatch),
+        };
+
+        let value = value(dataset, &origin);
+
+        let cell_value = match cells.with_untracked(|cells| cells.get(cell).cloned()) {
+            Some(state::CellValue::Variable(cell_value)) => cell_value,
+            Some(state::CellValue::Fixed(_)) => return Err(error::SyncFormula::CellNotVariable),
+            None => {
+                let cell_value = owner.with(|| RwSignal::new(state::VariableCellValue::Empty));
                 cells.update(|cells| {
-                    let state::CellValue::Variable(cell) =
-                        cells
-                            .entry(cell.clone())
-                            .or_insert(state::CellValue::Variable(
-                                owner.with(|| RwSignal::new(state::VariableCellValue::Empty)),
-                            ))
-                    else {
-                        panic!("expected a formula cell");
-                    };
-                    cell.set(state::VariableCellValue::Formula(
-                        value.map(|value| value.into()),
-                    ));
+                    cells.insert(cell.clone(), state::CellValue::Variable(cell_value));
                 });
+                cell_value
             }
-        }),
-
-        state::FormulaDomain::WorkbookCell {
-            dataset,
-            sheet,
-            cell,
-        } => datasets.with_untracked(|datasets| {
-            let dataset = datasets
-                .iter()
-                .find(|ds| ds.id() == dataset)
-                .expect("dataset should exist");
+        };
+        cell_value.set(value);
+        Ok(())
+    })
+}
+
+/// Formulas grouped by expression, so an expression can be reused across cells.
+mod library {
+    use super::{Formula, sync_formula};
+    use crate::{message, state, types};
+    use hermes_core as core;
+    use leptos::{ev, prelude::*};
+
+    /// Cells whose formulas normalize to the same expression.
+    ///
+    /// # Arguments
+    /// + `expression`: Normalized expression.
+    #[component]
+    pub fn Expression(expression: String) -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let owner = expect_context::<state::WorkspaceOwner>();
+        let expanded = RwSignal::new(false);
+
+        let formulas = {
+            let formulas = state.formulas.read_only();
+            let expression = expression.clone();
+            move || {
+                formulas.with(|formulas| {
+                    formulas
+                        .iter()
+                        .filter(|formula| formula.value.with(core::expr::normalize) == expression)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                })
+            }
+        };
+
+        let count = {
+            let formulas = formulas.clone();
+            move || formulas().len()
+        };
+
+        let disabled = {
+            let active = state.active_dataset.read_only();
+            move || {
+                active.with(|active| match active {
+                    state::ActiveDataset::None => true,
+                    state::ActiveDataset::Some { active_cell, .. } => {
+                        !active_cell.with(|cell| cell.is_some())
+                    }
+                })
+            }
+        };
 
-            let (cells, origin) = match dataset {
-                state::Dataset::Csv(csv) => unreachable!(),
-                state::Dataset::Workbook(workbook) => {
-                    let (sheet_idx, cells) = workbook
+        let toggle = move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+
+            expanded.update(|expanded| *expanded = !*expanded);
+        };
+
+        let apply = {
+            let state = state.clone();
+            let expression = expression.clone();
+            let formulas = formulas.clone();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+                e.stop_propagation();
+
+                // Apply the source of a formula, rather than its normalized form,
+                // to preserve the author's formatting.
+                let Some(value) = formulas()
+                    .first()
+                    .map(|formula| formula.value.get_untracked())
+                else {
+                    return;
+                };
+
+                let msg = match apply_to_selection(&value, &state, &owner) {
+                    Ok(Applied { applied, skipped }) if skipped == 0 => {
+                        message::Message::success(format!("Applied to {applied} cell(s)."))
+                    }
+                    Ok(Applied { applied, skipped }) => message::Message::warning_with_body(
+                        format!("Applied to {applied} cell(s)."),
+                        format!("{skipped} cell(s) already contained data."),
+                    ),
+                    Err(err) => message::Message::error_with_body(
+                        format!("Could not apply {expression}."),
+                        apply_error_message(err),
+                    ),
+                };
+                state.messages.push(msg);
+            }
+        };
+
+        let label = if expression.is_empty() {
+            "(empty)".to_string()
+        } else {
+            expression.clone()
+        };
+
+        view! {
+            <div>
+                <div
+                    class="flex gap-2 items-center cursor-pointer hover:bg-secondary-50 dark:hover:bg-secondary-700"
+                    on:mousedown=toggle
+                >
+                    <code class="grow truncate" title=label.clone()>
+                        {label.clone()}
+                    </code>
+                    <small>{move || format!("{} cell(s)", count())}</small>
+                    <button
+                        type="button"
+                        class="btn-cmd cursor-pointer"
+                        title="Apply to the selected cells"
+                        on:mousedown=apply
+                        disabled=disabled
+                    >
+                        "Apply"
+                    </button>
+                </div>
+                <Show when=move || expanded.get()>
+                    <div class="pl-2">
+                        <For each=formulas.clone() key=|formula| formula.id().clone() let:formula>
+                            <Formula formula />
+                        </For>
+                    </div>
+                </Show>
+            </div>
+        }
+    }
+
+    /// Number of cells an expression was applied to.
+    struct Applied {
+        applied: usize,
+        /// Cells that contained data.
+        skipped: usize,
+    }
+
+    /// Set the formula of each selected cell of the active dataset to an expression.
+    /// Existing formulas are replaced, cells that contain data are skipped.
+    fn apply_to_selection(
+        value: &str,
+        state: &state::State,
+        owner: &state::WorkspaceOwner,
+    ) -> Result<Applied, error::Apply> {
+        let (active, active_cell) = state
+            .active_dataset
+            .with_untracked(|active| match active {
+                state::ActiveDataset::None => None,
+                state::ActiveDataset::Some { id, active_cell } => Some((id.clone(), *active_cell)),
+            })
+            .ok_or(error::Apply::DatasetNotSelected)?;
+        if state.is_protected(&active) {
+            return Err(error::Apply::Protected);
+        }
+        let (start, end) = active_cell
+            .with_untracked(|cell| {
+                cell.bounds()
+                    .map(|(start, end)| (start.clone(), end.clone()))
+            })
+            .ok_or(error::Apply::CellsNotSelected)?;
+        let dataset = state
+            .datasets
+            .read_untracked()
+            .iter()
+            .find(|dataset| *dataset.id() == active)
+            .cloned()
+            .ok_or(error::Apply::DatasetNotSelected)?;
+
+        let (sheet, cells) = match &dataset {
+            state::Dataset::Csv(csv) => (None, csv.sheet().cells),
+            state::Dataset::Workbook(workbook) => workbook
+                .sheets
+                .with_untracked(|sheets| {
+                    sheets
+                        .get(workbook.active_sheet.get_untracked())
+                        .map(|sheet| (Some(sheet.id().clone()), sheet.cells))
+                })
+                .ok_or(error::Apply::SheetNotFound)?,
+        };
+
+        let options = state
+            .settings
+            .with_untracked(|settings| settings.eval_options());
+        let mut applied = Applied {
+            applied: 0,
+            skipped: 0,
+        };
+        for row in start.row()..=end.row() {
+            for col in start.col()..=end.col() {
+                let cell = core::data::CellIndex::new(row, col);
+                let occupied = cells.with_untracked(|cells| {
+                    matches!(cells.get(&cell), Some(state::CellValue::Fixed(_)))
+                });
+                if occupied {
+                    applied.skipped += 1;
+                    continue;
+                }
+
+                let domain = match &sheet {
+                    None => state::FormulaDomain::CsvCell {
+                        dataset: active.clone(),
+                        cell,
+                    },
+                    Some(sheet) => state::FormulaDomain::WorkbookCell {
+                        dataset: active.clone(),
+                        sheet: sheet.clone(),
+                        cell,
+                    },
+                };
+
+                let formula = match state.formulas.get_by_containing_domain(&domain) {
+                    Some(formula) => formula,
+                    None => {
+                        let formula = owner.with(|| state::Formula::new(domain));
+                        state.formulas.write().push(formula.clone());
+                        formula
+                    }
+                };
+                formula.set_value(value.to_string());
+                if let Err(err) = sync_formula(&formula, &state.datasets, owner, options) {
+                    tracing::error!(?err);
+                }
+                applied.applied += 1;
+            }
+        }
+
+        Ok(applied)
+    }
+
+    fn apply_error_message(err: error::Apply) -> &'static str {
+        match err {
+            error::Apply::DatasetNotSelected => "Select a dataset.",
+            error::Apply::CellsNotSelected => "Select the cells to apply the formula to.",
+            error::Apply::SheetNotFound => "The active sheet could not be found.",
+            error::Apply::Protected => "The dataset is protected from modification.",
+        }
+    }
+
+    mod error {
+        #[derive(Debug)]
+        pub enum Apply {
+            /// No dataset is active.
+            DatasetNotSelected,
+
+            /// No cells of the active dataset are selected.
+            CellsNotSelected,
+
+            /// The active sheet of the workbook does not exist.
+            SheetNotFound,
+
+            /// The active dataset is protected from modification.
+            Protected,
+        }
+    }
+}
+
+mod template {
+    use super::{compatibility_message, sync_formula};
+    use crate::{icon, message, state, types};
+    use hermes_core as core;
+    use hermes_desktop_lib as lib;
+    use leptos::{ev, html, prelude::*, task::spawn_local};
+    use leptos_icons::Icon;
+
+    #[component]
+    pub fn Templates() -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let name_node = NodeRef::<html::Input>::new();
+
+        spawn_local({
+            let templates = state.templates;
+            async move {
+                let saved = load_templates().await;
+                templates.set(saved);
+            }
+        });
+
+        let disabled = {
+            let active = state.active_dataset.read_only();
+            let formulas = state.formulas.read_only();
+            move || {
+                let Some(active) = active.with(|active| active.as_ref().cloned()) else {
+                    return true;
+                };
+                !formulas.with(|formulas| {
+                    formulas.iter().any(|formula| {
+                        formula
+                            .domain
+                            .with(|domain| domain_dataset(domain) == &active)
+                    })
+                })
+            }
+        };
+
+        let create = {
+            let state = state.clone();
+            move |e: ev::SubmitEvent| {
+                e.prevent_default();
+                let Some(input) = name_node.get_untracked() else {
+                    return;
+                };
+                let name = input.value().trim().to_string();
+                if name.is_empty() {
+                    return;
+                }
+
+                let Some(template) = template_from_active(&state, name) else {
+                    return;
+                };
+                state.templates.update(|templates| {
+                    templates.retain(|saved| saved.name != template.name);
+                    templates.push(template);
+                });
+                input.set_value("");
+                save(&state);
+            }
+        };
+
+        view! {
+            <div>
+                <div class="pb flex gap-2">
+                    <h2 class="grow font-bold uppercase">"Templates"</h2>
+                </div>
+                <form class="flex gap-1" on:submit=create>
+                    <input
+                        node_ref=name_node
+                        type="text"
+                        class="input-compact grow"
+                        placeholder="Template name"
+                        title="Save the active dataset's formulas as a template."
+                        disabled=disabled
+                    />
+                </form>
+                <div>
+                    <For
+                        each=move || state.templates.get()
+                        key=|template| template.name.clone()
+                        let:template
+                    >
+                        <Template template />
+                    </For>
+                </div>
+            </div>
+        }
+    }
+
+    #[component]
+    fn Template(template: lib::template::Template) -> impl IntoView {
+        let state = expect_context::<state::State>();
+        let owner = expect_context::<state::WorkspaceOwner>();
+        let disabled = {
+            let active = state.active_dataset.read_only();
+            move || active.read().is_none()
+        };
+
+        let apply = {
+            let state = state.clone();
+            let template = template.clone();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+
+                let Some(active) = state
+                    .active_dataset
+                    .with_untracked(|active| active.as_ref().cloned())
+                else {
+                    return;
+                };
+
+                let compatibility = core::expr::compatibility(template.expr_version);
+                let msg = match apply_template(&template, &active, &state, &owner) {
+                    Ok(Applied { created, .. }) if !compatibility.is_compatible() => {
+                        message::Message::warning_with_body(
+                            format!("Applied {created} formula(s)."),
+                            compatibility_message(&compatibility),
+                        )
+                    }
+                    Ok(Applied { created, skipped }) if skipped == 0 => {
+                        message::Message::success(format!("Applied {created} formula(s)."))
+                    }
+                    Ok(Applied { created, skipped }) => message::Message::warning_with_body(
+                        format!("Applied {created} formula(s)."),
+                        format!("{skipped} cell(s) already contained data or a formula."),
+                    ),
+                    Err(err) => message::Message::error_with_body(
+                        format!("Could not apply {}.", template.name),
+                        apply_error_message(err),
+                    ),
+                };
+                state.messages.push(msg);
+            }
+        };
+
+        let remove = {
+            let state = state.clone();
+            let name = template.name.clone();
+            move |e: ev::MouseEvent| {
+                if e.button() != types::MouseButton::Primary {
+                    return;
+                }
+
+                state
+                    .templates
+                    .update(|templates| templates.retain(|template| template.name != name));
+                save(&state);
+            }
+        };
+
+        view! {
+            <div class="flex gap-2 items-center">
+                <span class="grow truncate" title=template.name.clone()>
+                    {template.name.clone()}
+                </span>
+                <small>{format!("{} formula(s)", template.formulas.len())}</small>
+                <button
+                    type="button"
+                    class="btn-cmd cursor-pointer"
+                    title="Apply to the active dataset"
+                    on:mousedown=apply
+                    disabled=disabled
+                >
+                    "Apply"
+                </button>
+                <button
+                    type="button"
+                    class="btn-cmd cursor-pointer"
+                    title="Delete template"
+                    on:mousedown=remove
+                >
+                    <Icon icon=icon::Close />
+                </button>
+            </div>
+        }
+    }
+
+    /// Number of formulas created by applying a template.
+    struct Applied {
+        created: usize,
+        /// Cells that were not empty.
+        skipped: usize,
+    }
+
+    /// Create a template from the formulas of the active dataset.
+    ///
+    /// # Returns
+    /// `None` if there is no active dataset.
+    pub fn template_from_active(
+        state: &state::State,
+        name: String,
+    ) -> Option<lib::template::Template> {
+        let active = state
+            .active_dataset
+            .with_untracked(|active| active.as_ref().cloned())?;
+        let dataset = state
+            .datasets
+            .read_untracked()
+            .iter()
+            .find(|dataset| *dataset.id() == active)
+            .cloned()?;
+
+        let formulas = state
+            .formulas
+            .read_untracked()
+            .iter()
+            .filter_map(|formula| {
+                let domain = formula.domain.get_untracked();
+                let (sheet, cell) = match (&domain, &dataset) {
+                    (state::FormulaDomain::CsvCell { dataset, cell }, _) if *dataset == active => {
+                        (None, cell.clone())
+                    }
+                    (
+                        state::FormulaDomain::WorkbookCell {
+                            dataset,
+                            sheet,
+                            cell,
+                        },
+                        state::Dataset::Workbook(workbook),
+                    ) if *dataset == active => {
+                        let idx = workbook
+                            .sheets
+                            .read_untracked()
+                            .iter()
+                            .position(|s| s.id() == sheet)?;
+                        (
+                            Some(core::data::IndexType::try_from(idx).ok()?),
+                            cell.clone(),
+                        )
+                    }
+                    _ => return None,
+                };
+
+                Some(lib::template::TemplateFormula {
+                    sheet,
+                    cell,
+                    value: formula.value.get_untracked(),
+                })
+            })
+            .collect();
+
+        Some(lib::template::Template {
+            name,
+            shape: dataset.shape(),
+            formulas,
+            expr_version: core::expr::VERSION,
+        })
+    }
+
+    /// Apply a template to several datasets,
+    /// reporting the outcome as a single message.
+    pub fn apply_template_to_all(
+        template: &lib::template::Template,
+        datasets: &[state::ResourceId],
+        state: &state::State,
+        owner: &state::WorkspaceOwner,
+    ) {
+        let mut created = 0;
+        let mut skipped = 0;
+        let mut failed = vec![];
+        for dataset in datasets {
+            match apply_template(template, dataset, state, owner) {
+                Ok(applied) => {
+                    created += applied.created;
+                    skipped += applied.skipped;
+                }
+                Err(err) => {
+                    let name = state
+                        .directory_tree
+                        .get_file_path(dataset)
+                        .map(|path| path.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    failed.push(format!("{name}: {}", apply_error_message(err)));
+                }
+            }
+        }
+
+        let title = format!(
+            "Applied {created} formula(s) to {} file(s).",
+            datasets.len() - failed.len()
+        );
+        let compatibility = core::expr::compatibility(template.expr_version);
+        let mut notes = vec![];
+        if !failed.is_empty() {
+            notes.push(failed.join("\n"));
+        }
+        if skipped > 0 {
+            notes.push(format!(
+                "{skipped} cell(s) already contained data or a formula."
+            ));
+        }
+        if !compatibility.is_compatible() {
+            notes.push(compatibility_message(&compatibility));
+        }
+
+        let msg = if !failed.is_empty() {
+            message::Message::error_with_body(title, notes.join("\n"))
+        } else if !notes.is_empty() {
+            message::Message::warning_with_body(title, notes.join("\n"))
+        } else {
+            message::Message::success(title)
+        };
+        state.messages.push(msg);
+    }
+
+    /// Bind a template's formulas to a dataset.
+    /// Cells that already contain data or a formula are skipped.
+    fn apply_template(
+        template: &lib::template::Template,
+        active: &state::ResourceId,
+        state: &state::State,
+        owner: &state::WorkspaceOwner,
+    ) -> Result<Applied, error::Apply> {
+        let active = active.clone();
+        if state.is_protected(&active) {
+            return Err(error::Apply::Protected);
+        }
+        let dataset = state
+            .datasets
+            .read_untracked()
+            .iter()
+            .find(|dataset| *dataset.id() == active)
+            .cloned()
+            .ok_or(error::Apply::DatasetNotSelected)?;
+        template.check(&dataset.shape())?;
+
+        let mut applied = Applied {
+            created: 0,
+            skipped: 0,
+        };
+        for template_formula in template.formulas.iter() {
+            let (domain, cells) = match (&dataset, template_formula.sheet) {
+                (state::Dataset::Csv(csv), _) => (
+                    state::FormulaDomain::CsvCell {
+                        dataset: active.clone(),
+                        cell: template_formula.cell.clone(),
+                    },
+                    csv.sheet().cells,
+                ),
+                (state::Dataset::Workbook(workbook), sheet) => {
+                    let sheet = workbook
                         .sheets
                         .read_untracked()
-                        .iter()
-                        .enumerate()
-                        .find_map(|(idx, s)| (s.id() == sheet).then_some((idx, s.cells)))
-                        .expect("sheet should exist");
+                        .get(sheet.unwrap_or(0) as usize)
+                        .map(|sheet| (sheet.id().clone(), sheet.cells));
+                    let Some((sheet, cells)) = sheet else {
+                        applied.skipped += 1;
+                        continue;
+                    };
 
                     (
-                        cells,
-                        core::data::CellPath {
-                            sheet: sheet_idx as core::data::IndexType,
-                            row: cell.row(),
-                            col: cell.col(),
+                        state::FormulaDomain::WorkbookCell {
+                            dataset: active.clone(),
+                            sheet,
+                            cell: template_formula.cell.clone(),
                         },
+                        cells,
                     )
                 }
             };
 
-            let value = core::expr::eval(formula.value.get_untracked(), dataset, &origin);
-            if cells.with_untracked(|cells| cells.contains_key(cell)) {
-                cells.with_untracked(|cells| {
-                    let state::CellValue::Variable(cell) = cells.get(cell).expect("cell to exist")
-                    else {
-                        panic!("expected a variable cell");
-                    };
-                    cell.set(state::VariableCellValue::Formula(
-                        value.map(|value| value.into()),
-                    ));
-                });
-            } else {
-                cells.update(|cells| {
-                    let state::CellValue::Variable(cell) =
-                        cells
-                            .entry(cell.clone())
-                            .or_insert(state::CellValue::Variable(
-                                owner.with(|| RwSignal::new(state::VariableCellValue::Empty)),
-                            ))
-                    else {
-                        panic!("expected a formula cell");
-                    };
-                    cell.set(state::VariableCellValue::Formula(
-                        value.map(|value| value.into()),
-                    ));
-                });
+            let occupied = cells.with_untracked(|cells| {
+                matches!(
+                    cells.get(&template_formula.cell),
+                    Some(state::CellValue::Fixed(_))
+                )
+            });
+            if occupied || state.formulas.get_by_containing_domain(&domain).is_some() {
+                applied.skipped += 1;
+                continue;
             }
-        }),
-    })
+
+            let formula = owner.with(|| state::Formula::new(domain));
+            formula.value.set(template_formula.value.clone());
+            state.formulas.write().push(formula.clone());
+            let options = state
+                .settings
+                .with_untracked(|settings| settings.eval_options());
+            if let Err(err) = sync_formula(&formula, &state.datasets, owner, options) {
+                tracing::error!(?err);
+            }
+            applied.created += 1;
+        }
+
+        Ok(applied)
+    }
+
+    fn domain_dataset(domain: &state::FormulaDomain) -> &state::ResourceId {
+        match domain {
+            state::FormulaDomain::CsvCell { dataset, .. } => dataset,
+            state::FormulaDomain::WorkbookCell { dataset, .. } => dataset,
+        }
+    }
+
+    /// Save all templates, reporting failures as a message.
+    fn save(state: &state::State) {
+        let templates = state.templates.get_untracked();
+        let messages = state.messages;
+        spawn_local(async move {
+            if let Err(err) = save_templates(templates).await {
+                messages.push(message::Message::error_with_body(
+                    "Could not save templates.",
+                    err.to_string(),
+                ));
+            }
+        });
+    }
+
+    async fn load_templates() -> Vec<lib::template::Template> {
+        crate::ipc::invoke_infallible(lib::ipc::LoadTemplates).await
+    }
+
+    async fn save_templates(
+        templates: Vec<lib::template::Template>,
+    ) -> Result<(), lib::fs::error::FileOperation> {
+        crate::ipc::invoke(lib::ipc::SaveTemplates { templates }).await
+    }
+
+    fn apply_error_message(err: error::Apply) -> String {
+        match err {
+            error::Apply::DatasetNotSelected => "Select a dataset.".to_string(),
+            error::Apply::Protected => "The dataset is protected from modification.".to_string(),
+            error::Apply::Incompatible(err) => {
+                format!("Dataset does not match the template: {err}.")
+            }
+        }
+    }
+
+    mod error {
+        use hermes_desktop_lib as lib;
+
+        #[derive(Debug, derive_more::From)]
+        pub enum Apply {
+            /// The dataset is not loaded.
+            DatasetNotSelected,
+
+            /// The active dataset is protected from modification.
+            Protected,
+
+            /// The active dataset does not match the template's structure.
+            Incompatible(lib::template::Incompatible),
+        }
+    }
+}
+
+pub mod error {
+    #[derive(Debug)]
+    pub enum SyncFormula {
+        /// The formula's dataset does not exist.
+        DatasetNotFound,
+
+        /// The formula's sheet does not exist.
+        SheetNotFound,
+
+        /// The formula's domain does not match its dataset's kind.
+        DomainMismatch,
+
+        /// The formula's cell contains fixed data.
+        CellNotVariable,
+    }
+
+    #[derive(Debug)]
+    pub enum NewFormula {
+        /// No dataset was selected.
+        DatasetNotSelected,
+
+        /// The target is not a valid cell reference.
+        InvalidReference,
+
+        /// The target's sheet does not exist.
+        SheetNotFound,
+
+        /// The target cell contains fixed data.
+        CellNotEmpty,
+
+        /// The target dataset is protected from modification.
+        Protected,
+    }
 }
@@ -0,0 +1,165 @@
+//! History of changes made to files by running the workspace.
+use crate::{icon, state, types};
+use hermes_core as core;
+use hermes_desktop_lib as lib;
+use leptos::{either::Either, ev, prelude::*};
+use leptos_icons::Icon;
+use std::path::PathBuf;
+
+/// Maximum number of audit log entries shown.
+const HISTORY_LIMIT: usize = 50;
+
+#[component]
+pub fn History() -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let entries = LocalResource::new({
+        let root = state.root_path().clone();
+        move || {
+            query_audit_log(
+                root.clone(),
+                lib::audit::Query {
+                    path: None,
+                    limit: Some(HISTORY_LIMIT),
+                },
+            )
+        }
+    });
+
+    let refresh = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+
+        entries.refetch();
+    };
+
+    view! {
+        <div>
+            <div class="pb flex gap-2">
+                <h2 class="grow font-bold uppercase">"History"</h2>
+                <div>
+                    <button
+                        type="button"
+                        class="btn-cmd cursor-pointer"
+                        title="Refresh"
+                        on:mousedown=refresh
+                    >
+                        <Icon icon=icon::Refresh />
+                    </button>
+                </div>
+            </div>
+            <div>
+                {move || match entries.get() {
+                    None => view! { <div>"Loading"</div> }.into_any(),
+                    Some(Err(err)) => {
+                        view! { <small class="color-brand-red-600">{err.to_string()}</small> }
+                            .into_any()
+                    }
+                    Some(Ok(entries)) if entries.is_empty() => {
+                        view! { <div>"No changes recorded."</div> }.into_any()
+                    }
+                    Some(Ok(entries)) => {
+                        entries
+                            .into_iter()
+                            .map(|entry| view! { <Entry entry /> })
+                            .collect::<Vec<_>>()
+                            .into_any()
+                    }
+                }}
+            </div>
+        </div>
+    }
+}
+
+#[component]
+fn Entry(entry: lib::audit::Entry) -> impl IntoView {
+    let expanded = RwSignal::new(false);
+    let toggle = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+
+        expanded.update(|expanded| *expanded = !*expanded);
+    };
+
+    let kind = match entry.kind {
+        lib::audit::OrderKind::Create => "created",
+        lib::audit::OrderKind::Update => "updated",
+    };
+    let timestamp = entry.timestamp.format("%Y-%m-%d %H:%M:%S UTC").to_string();
+    let count = entry.changes.len();
+
+    view! {
+        <div class="text-sm">
+            <div
+                class="flex gap-2 cursor-pointer hover:bg-secondary-50 dark:hover:bg-secondary-700"
+                title=timestamp.clone()
+                on:mousedown=toggle
+            >
+                <span class="grow truncate">{entry.path.to_string_lossy().to_string()}</span>
+                <small>{format!("{kind}, {count} cell(s)")}</small>
+            </div>
+            <ul class="pl-2" class:hidden=move || !expanded.get()>
+                <li>
+                    <small>{timestamp}</small>
+                </li>
+                <Changes changes=entry.changes />
+            </ul>
+        </div>
+    }
+}
+
+/// Diff of cell values.
+#[component]
+pub fn Changes(changes: Vec<lib::audit::CellChange>) -> impl IntoView {
+    changes
+        .into_iter()
+        .map(|change| {
+            let sheet = change
+                .sheet
+                .map(|sheet| format!("{sheet}!"))
+                .unwrap_or_default();
+            let formula = change.formula.map(|formula| format!("={formula}"));
+            let inputs = change.provenance.and_then(|provenance| {
+                (!provenance.inputs.is_empty()).then(|| {
+                    provenance
+                        .inputs
+                        .iter()
+                        .map(|input| input.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+            });
+            let old =
+                (!matches!(change.old, core::expr::Value::Empty)).then(|| change.old.to_string());
+
+            let value = match change.outcome {
+                lib::audit::CellOutcome::Written => Either::Left(view! {
+                    {old.map(|old| view! { <del class="text-brand-red-600">{old}</del> })}
+                    <ins class="text-brand-green-600 no-underline">{change.new.to_string()}</ins>
+                }),
+                lib::audit::CellOutcome::Skipped => Either::Right(view! {
+                    <span>{old}</span>
+                    <small class="text-secondary-500">"skipped"</small>
+                }),
+            };
+
+            view! {
+                <li class="flex gap-2">
+                    <span class="font-mono">{format!("{sheet}{}", change.cell)}</span>
+                    {value}
+                    <small class="truncate text-secondary-500" title=inputs>
+                        {formula}
+                    </small>
+                </li>
+            }
+        })
+        .collect::<Vec<_>>()
+}
+
+async fn query_audit_log(
+    root: PathBuf,
+    query: lib::audit::Query,
+) -> Result<Vec<lib::audit::Entry>, lib::audit::error::Audit> {
+    crate::ipc::invoke(lib::ipc::QueryAuditLog { root, query }).await
+}
@@ -0,0 +1,28 @@
+//! Invoke [commands](lib::ipc::Command) shared with the backend.
+use hermes_desktop_lib as lib;
+use serde::Serialize;
+
+/// Arguments of a command, passed as its single `args` parameter.
+#[derive(Serialize)]
+struct Args<C> {
+    args: C,
+}
+
+/// Invoke a command with its arguments.
+pub async fn invoke<C>(args: C) -> lib::ipc::Response<C>
+where
+    C: lib::ipc::Command,
+{
+    tauri_sys::core::invoke_result(C::NAME, Args { args }).await
+}
+
+/// Invoke a command that can not fail with its arguments.
+pub async fn invoke_infallible<C>(args: C) -> C::Output
+where
+    C: lib::ipc::Command<Error = lib::ipc::Infallible>,
+{
+    match invoke(args).await {
+        Ok(output) => output,
+        Err(err) => match err {},
+    }
+}
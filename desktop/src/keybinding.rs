@@ -0,0 +1,140 @@
+//! Keyboard shortcuts.
+//! Shortcuts are bound to actions in the settings,
+//! components handle the actions they own.
+use crate::{icon, state, types};
+use hermes_desktop_lib as lib;
+use leptos::{ev, prelude::*};
+use leptos_icons::Icon;
+
+/// Shortcuts that can not be configured, with their description.
+const FIXED_SHORTCUTS: &[(&str, &str)] = &[
+    ("Ctrl+P", "Quick open"),
+    ("Ctrl+Enter", "Apply formula, in the formula editor"),
+];
+
+/// Action triggered by its shortcut.
+#[derive(Clone, Copy)]
+pub struct Keybindings(RwSignal<Option<lib::settings::Action>>);
+impl Keybindings {
+    pub fn new() -> Self {
+        Self(RwSignal::new(None))
+    }
+
+    /// Call `handler` each time the action is triggered.
+    /// The handler is removed with the current owner.
+    pub fn on(&self, action: lib::settings::Action, handler: impl Fn() + 'static) {
+        let triggered = self.0;
+        Effect::watch(
+            move || triggered.get(),
+            move |triggered, _, _| {
+                if *triggered == Some(action) {
+                    handler();
+                }
+            },
+            false,
+        );
+    }
+
+    /// Trigger the action bound to the key press, if any.
+    fn dispatch(&self, e: &ev::KeyboardEvent, settings: &lib::settings::Settings) {
+        let ctrl = e.ctrl_key() || e.meta_key();
+        let action = lib::settings::Action::ALL.iter().find(|action| {
+            settings
+                .shortcut(**action)
+                .matches(&e.key(), ctrl, e.shift_key(), e.alt_key())
+        });
+
+        if let Some(action) = action {
+            e.prevent_default();
+            self.0.set(Some(*action));
+        }
+    }
+}
+
+/// Trigger actions when their shortcut is pressed.
+pub fn listen(keybindings: Keybindings, settings: state::Settings) {
+    let listener = window_event_listener(ev::keydown, move |e| {
+        if e.repeat() {
+            return;
+        }
+        settings.with_untracked(|settings| keybindings.dispatch(&e, settings));
+    });
+    on_cleanup(move || listener.remove());
+}
+
+/// Overlay listing the shortcuts in effect.
+/// Toggled by its action.
+#[component]
+pub fn CheatSheet() -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let keybindings = expect_context::<Keybindings>();
+    let settings = state.settings;
+    let visible = RwSignal::new(false);
+    keybindings.on(lib::settings::Action::ShowShortcuts, move || {
+        visible.update(|visible| *visible = !*visible)
+    });
+
+    let close = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+
+        visible.set(false);
+    };
+
+    let close_on_escape = move |e: ev::KeyboardEvent| {
+        if e.key() == "Escape" {
+            e.prevent_default();
+            visible.set(false);
+        }
+    };
+
+    let bindings = move || {
+        settings.with(|settings| {
+            lib::settings::Action::ALL
+                .iter()
+                .map(|action| (settings.shortcut(*action).to_string(), action.label()))
+                .chain(
+                    FIXED_SHORTCUTS
+                        .iter()
+                        .map(|(shortcut, label)| (shortcut.to_string(), *label)),
+                )
+                .map(|(shortcut, label)| {
+                    view! {
+                        <tr>
+                            <td class="pr-4">{label}</td>
+                            <td>
+                                <kbd class="font-mono">{shortcut}</kbd>
+                            </td>
+                        </tr>
+                    }
+                })
+                .collect::<Vec<_>>()
+        })
+    };
+
+    view! {
+        <Show when=move || visible.get()>
+            <div class="fixed inset-0 z-30" on:mousedown=close></div>
+            <div
+                class="fixed z-40 top-12 left-1/2 -translate-x-1/2 p-2 flex flex-col gap-1 \
+                bg-white dark:bg-secondary-800 border border-secondary-100 dark:border-secondary-600"
+                tabindex="-1"
+                on:keydown=close_on_escape
+            >
+                <div class="flex gap-2">
+                    <h2 class="grow font-bold uppercase">"Keyboard shortcuts"</h2>
+                    <button type="button" class="btn-cmd cursor-pointer" on:mousedown=close>
+                        <Icon icon=icon::Close />
+                    </button>
+                </div>
+                <table class="text-sm">
+                    <tbody>{bindings}</tbody>
+                </table>
+                <small class="text-secondary-700 dark:text-secondary-200">
+                    "Shortcuts can be changed in the settings."
+                </small>
+            </div>
+        </Show>
+    }
+}
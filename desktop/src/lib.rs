@@ -1,12 +1,21 @@
 mod app;
+mod chart;
 mod component;
 mod dataset;
+mod diagnostics;
 mod explorer;
 mod formula;
+mod history;
+mod ipc;
+mod keybinding;
 mod message;
+mod playground;
+mod session;
+mod settings;
 mod state;
 
 pub use app::App;
+pub use playground::Playground;
 
 const LEVEL_PAD: usize = 6;
 const LEVEL_PAD_UNIT: &str = "px";
@@ -61,9 +70,12 @@ static _TAILWIND_CLASSES: &'static [&'static str] = &[
 
 mod icon {
     pub use icondata::{
-        AiCloseOutlined as Close, AiLoading3QuartersOutlined as LoadingSpinner,
-        AiMinusOutlined as Remove, AiPlusOutlined as Add, FaEqualsSolid as Equal,
-        MdiFunction as Function,
+        AiBulbOutlined as Theme, AiClearOutlined as Unload, AiCloseOutlined as Close,
+        AiExportOutlined as Export, AiHolderOutlined as DragHandle,
+        AiLoading3QuartersOutlined as LoadingSpinner, AiLockOutlined as Lock,
+        AiMenuOutlined as Menu, AiMinusOutlined as Remove, AiPlusOutlined as Add,
+        AiReloadOutlined as Refresh, AiSplitCellsOutlined as Split, AiUndoOutlined as Revert,
+        AiUnlockOutlined as Unlock, FaEqualsSolid as Equal, MdiFunction as Function,
     };
 }
 
@@ -1,36 +1,220 @@
+//! Messages shown to the user.
+//! Informational messages are dismissed automatically, errors remain until dismissed.
+//! Dismissed messages are kept in a history.
 use crate::{
     icon,
     state::{self, ResourceId},
+    types,
 };
-use leptos::prelude::*;
+use hermes_desktop_lib as lib;
+use leptos::{ev, prelude::*, task::spawn_local};
 use leptos_icons::Icon;
+use std::time::Duration;
+
+/// Maximum number of dismissed messages kept in the history.
+const HISTORY_LIMIT: usize = 100;
+
+/// Messages shown to the user, and those previously dismissed.
+#[derive(Clone, Copy)]
+pub struct Messages {
+    active: RwSignal<Vec<Message>>,
+    history: RwSignal<Vec<Message>>,
+}
+
+impl Messages {
+    pub fn new() -> Self {
+        Self {
+            active: RwSignal::new(vec![]),
+            history: RwSignal::new(vec![]),
+        }
+    }
+
+    /// Show a message.
+    /// If an identical message is already shown, its count is increased instead.
+    pub fn push(&self, message: Message) {
+        let timeout = message.kind.timeout();
+        let now = js_sys::Date::now();
+        let existing = self.active.with_untracked(|active| {
+            active
+                .iter()
+                .find(|active| active.is_repeat_of(&message))
+                .map(|active| active.id.clone())
+        });
+
+        let id = match existing {
+            Some(id) => {
+                self.active.update(|active| {
+                    if let Some(active) = active.iter_mut().find(|active| active.id == id) {
+                        active.count += 1;
+                        active.timestamp = now;
+                    }
+                });
+                id
+            }
+            None => {
+                let id = message.id.clone();
+                self.active.update(|active| {
+                    active.push(Message {
+                        timestamp: now,
+                        ..message
+                    })
+                });
+                id
+            }
+        };
+
+        if let Some(timeout) = timeout {
+            let messages = *self;
+            set_timeout(move || messages.expire(&id, timeout), timeout);
+        }
+    }
+
+    /// Dismiss a message if it was not repeated within `timeout`.
+    fn expire(&self, id: &ResourceId, timeout: Duration) {
+        let now = js_sys::Date::now();
+        let expired = self.active.with_untracked(|active| {
+            active.iter().any(|message| {
+                message.id == *id && now - message.timestamp >= timeout.as_millis() as f64
+            })
+        });
+
+        if expired {
+            self.dismiss(id);
+        }
+    }
+
+    /// Move a message to the history.
+    pub fn dismiss(&self, id: &ResourceId) {
+        let mut dismissed = None;
+        self.active.update(|active| {
+            if let Some(idx) = active.iter().position(|message| message.id == *id) {
+                dismissed = Some(active.remove(idx));
+            }
+        });
+
+        if let Some(message) = dismissed {
+            self.history.update(|history| {
+                history.insert(0, message);
+                history.truncate(HISTORY_LIMIT);
+            });
+        }
+    }
+
+    pub fn clear_history(&self) {
+        self.history.update(|history| history.clear());
+    }
+
+    /// Messages currently shown.
+    pub fn active(&self) -> ReadSignal<Vec<Message>> {
+        self.active.read_only()
+    }
+
+    /// Dismissed messages, most recent first.
+    pub fn history(&self) -> ReadSignal<Vec<Message>> {
+        self.history.read_only()
+    }
+}
 
 #[component]
 pub fn Messages() -> impl IntoView {
-    let state = expect_context::<state::State>();
+    let messages = expect_context::<state::State>().messages;
+    let history_visible = RwSignal::new(false);
+    let history_len = move || messages.history().with(|history| history.len());
+
+    let toggle_history = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+
+        history_visible.update(|visible| *visible = !*visible);
+    };
 
     view! {
-        <div>
-            <For each=state.messages.read_only() key=|message| message.id().clone() let:message>
+        <div class="flex flex-col gap-1 w-md max-w-full">
+            <For each=messages.active() key=|message| message.id().clone() let:message>
                 <Message message />
             </For>
+            <div class="flex justify-end" class:hidden=move || history_len() == 0>
+                <button
+                    type="button"
+                    class="btn-cmd cursor-pointer text-sm"
+                    title="Dismissed messages"
+                    on:mousedown=toggle_history
+                >
+                    {move || format!("History ({})", history_len())}
+                </button>
+            </div>
+            <Show when=move || history_visible.get() && history_len() > 0>
+                <History />
+            </Show>
+        </div>
+    }
+}
+
+/// Previously dismissed messages.
+#[component]
+fn History() -> impl IntoView {
+    let messages = expect_context::<state::State>().messages;
+    let clear = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+
+        messages.clear_history();
+    };
+
+    view! {
+        <div class="max-h-96 overflow-y-auto bg-white dark:bg-secondary-800 border border-secondary-200 dark:border-secondary-600">
+            <div class="flex px-1">
+                <h2 class="grow font-bold uppercase">"History"</h2>
+                <button type="button" class="btn-cmd cursor-pointer text-sm" on:mousedown=clear>
+                    "Clear"
+                </button>
+            </div>
+            <ul>
+                {move || {
+                    messages
+                        .history()
+                        .get()
+                        .into_iter()
+                        .map(|message| {
+                            view! {
+                                <li class=format!("px-1 text-sm {}", message.kind.class())>
+                                    <div>{message.heading()}</div>
+                                    {message.body.map(|body| view! { <div>{body}</div> })}
+                                </li>
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                }}
+            </ul>
         </div>
     }
 }
 
 #[component]
 pub fn Message(message: Message) -> impl IntoView {
+    let messages = expect_context::<state::State>().messages;
+    let id = message.id.clone();
+    let dismiss = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+
+        messages.dismiss(&id);
+    };
+
     view! {
-        <div>
+        <div class=format!("px-1 {}", message.kind.class())>
             <div class="flex">
-                <div class="grow">{message.title}</div>
+                <div class="grow">{message.heading()}</div>
                 <div>
-                    <button type="button" class="pointer-cursor">
+                    <button type="button" class="cursor-pointer" title="Dismiss" on:mousedown=dismiss>
                         <Icon icon=icon::Close />
                     </button>
                 </div>
             </div>
-            {message.body.map(|body| view! { <div>{body}</div> })}
+            {message.body.map(|body| view! { <div class="text-sm">{body}</div> })}
         </div>
     }
 }
@@ -41,36 +225,121 @@ pub struct Message {
     kind: Kind,
     title: String,
     body: Option<String>,
+
+    /// Number of times the message was shown.
+    count: usize,
+
+    /// Time the message was last shown, in milliseconds since the epoch.
+    timestamp: f64,
 }
 
 impl Message {
-    pub fn error(title: impl Into<String>) -> Self {
+    fn new(kind: Kind, title: impl Into<String>, body: Option<String>) -> Self {
         Self {
             id: ResourceId::new(),
-            kind: Kind::Error,
+            kind,
             title: title.into(),
-            body: None,
+            body,
+            count: 1,
+            timestamp: 0.0,
         }
     }
 
+    pub fn error(title: impl Into<String>) -> Self {
+        Self::new(Kind::Error, title, None)
+    }
+
     pub fn error_with_body(title: impl Into<String>, body: impl Into<String>) -> Self {
-        Self {
-            id: ResourceId::new(),
-            kind: Kind::Error,
-            title: title.into(),
-            body: Some(body.into()),
-        }
+        Self::new(Kind::Error, title, Some(body.into()))
+    }
+
+    pub fn success(title: impl Into<String>) -> Self {
+        Self::new(Kind::Success, title, None)
+    }
+
+    pub fn warning_with_body(title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self::new(Kind::Warning, title, Some(body.into()))
     }
 
     pub fn id(&self) -> &ResourceId {
         &self.id
     }
+
+    /// # Returns
+    /// Title, with the number of times the message was shown if repeated.
+    fn heading(&self) -> String {
+        if self.count > 1 {
+            format!("{} (x{})", self.title, self.count)
+        } else {
+            self.title.clone()
+        }
+    }
+
+    /// Whether the messages have the same content.
+    fn is_repeat_of(&self, other: &Self) -> bool {
+        self.kind == other.kind && self.title == other.title && self.body == other.body
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Kind {
     Success,
     Info,
     Warning,
     Error,
 }
+
+impl Kind {
+    /// # Returns
+    /// Time after which messages are dismissed automatically.
+    /// `None` if they must be dismissed manually.
+    fn timeout(&self) -> Option<Duration> {
+        match self {
+            Self::Success | Self::Info => Some(Duration::from_secs(5)),
+            Self::Warning => Some(Duration::from_secs(10)),
+            Self::Error => None,
+        }
+    }
+
+    fn class(&self) -> &'static str {
+        match self {
+            Self::Success => "bg-brand-green-100 dark:bg-brand-green-800",
+            Self::Info => "bg-secondary-100 dark:bg-secondary-700",
+            Self::Warning => "bg-brand-yellow-100 dark:bg-brand-yellow-800",
+            Self::Error => "bg-brand-red-100 dark:bg-brand-red-800",
+        }
+    }
+}
+
+/// Show a system notification that an operation finished.
+/// The notification is only shown if the window is not focused,
+/// and notifications are enabled for the operation in the settings.
+pub fn notify_complete(
+    operation: lib::settings::Operation,
+    title: impl Into<String>,
+    body: Option<String>,
+) {
+    let args = lib::ipc::NotifyComplete {
+        operation,
+        title: title.into(),
+        body,
+    };
+    spawn_local(async move {
+        crate::ipc::invoke_infallible(args).await;
+    });
+}
+
+/// Short description of why a workspace order failed.
+pub fn order_error_message(err: &lib::formula::error::WorkspaceOrder) -> &'static str {
+    match err {
+        lib::formula::error::WorkspaceOrder::TaskNotCompleted => "did not complete",
+        lib::formula::error::WorkspaceOrder::OpenFile(_) => "could not open file",
+        lib::formula::error::WorkspaceOrder::Save(_) => "could not save file",
+        lib::formula::error::WorkspaceOrder::DataTooLarge => "file is too large",
+        lib::formula::error::WorkspaceOrder::Protected => "file is protected",
+        lib::formula::error::WorkspaceOrder::ReadOnly => "file is read-only",
+        lib::formula::error::WorkspaceOrder::CellOccupied(_) => "cell already contains data",
+        lib::formula::error::WorkspaceOrder::SheetNotFound(_) => "sheet does not exist",
+        lib::formula::error::WorkspaceOrder::Unsupported => "not supported yet",
+    }
+}
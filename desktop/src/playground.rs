@@ -0,0 +1,186 @@
+//! Standalone expression playground.
+//!
+//! Evaluates formulas entirely client-side against a small editable grid,
+//! so it can be embedded outside of the desktop app, e.g. in documentation.
+//! Only depends on `hermes_core`, never on the Tauri backend.
+use hermes_core as core;
+use leptos::{either::Either, prelude::*};
+
+/// Label of the playground's only sheet.
+const SHEET: &str = "Sheet1";
+const DEFAULT_ROWS: usize = 5;
+const DEFAULT_COLS: usize = 3;
+const DEFAULT_FORMULA: &str = "A1 * 2";
+
+/// Grid of literal values formulas are evaluated against.
+#[derive(Clone, Debug, PartialEq)]
+struct Grid {
+    rows: Vec<Vec<String>>,
+}
+
+impl Grid {
+    fn new(rows: usize, cols: usize) -> Self {
+        Self {
+            rows: (0..rows)
+                .map(|row| {
+                    (0..cols)
+                        .map(|col| {
+                            if col == 0 {
+                                (row + 1).to_string()
+                            } else {
+                                String::new()
+                            }
+                        })
+                        .collect()
+                })
+                .collect(),
+        }
+    }
+
+    fn cols(&self) -> usize {
+        self.rows.first().map(|row| row.len()).unwrap_or(0)
+    }
+}
+
+impl core::expr::Context for &Grid {
+    fn cell_value(
+        self,
+        cell_ref: &core::data::CellRef,
+        origin: &core::data::CellPath,
+    ) -> Result<core::expr::Value, core::expr::ContextError> {
+        if cell_ref.sheet.resolve(origin.sheet, [SHEET]).is_none() {
+            return Err(core::expr::ContextError::CellRefDoesNotExist);
+        }
+
+        self.rows
+            .get(cell_ref.row as usize)
+            .and_then(|row| row.get(cell_ref.col as usize))
+            .map(|value| str_to_value(value))
+            .ok_or(core::expr::ContextError::CellRefDoesNotExist)
+    }
+}
+
+/// Formula playground.
+/// The formula is evaluated once per row of the grid,
+/// with its result shown in the last column.
+/// Relative references are resolved from the row being evaluated.
+#[component]
+pub fn Playground(
+    #[prop(optional, default = DEFAULT_ROWS)] rows: usize,
+    #[prop(optional, default = DEFAULT_COLS)] cols: usize,
+    #[prop(optional, into, default = DEFAULT_FORMULA.to_string())] formula: String,
+) -> impl IntoView {
+    let grid = RwSignal::new(Grid::new(rows.max(1), cols.max(1)));
+    let formula = RwSignal::new(formula);
+
+    let parse_error = move || {
+        formula.with(|formula| {
+            core::expr::parse(formula)
+                .err()
+                .map(|err| format!("{}: {}", err.span.start, err.value))
+        })
+    };
+
+    view! {
+        <div class="flex flex-col gap-2">
+            <label class="flex gap-2 items-center">
+                <span class="font-mono">"="</span>
+                <input
+                    class="grow px-1 font-mono border border-secondary-200 dark:border-secondary-600"
+                    prop:value=formula
+                    on:input=move |e| formula.set(event_target_value(&e))
+                    spellcheck="false"
+                />
+            </label>
+            {move || {
+                parse_error().map(|error| view! { <div class="text-sm text-red-600">{error}</div> })
+            }}
+            <table class="border-collapse font-mono text-sm">
+                <thead>
+                    <tr>
+                        <th></th>
+                        {(0..grid.with_untracked(|grid| grid.cols()))
+                            .map(|col| {
+                                view! { <th>{core::utils::index_to_col(col as core::data::IndexType)}</th> }
+                            })
+                            .collect::<Vec<_>>()}
+                        <th>"Result"</th>
+                    </tr>
+                </thead>
+                <tbody>
+                    {(0..grid.with_untracked(|grid| grid.rows.len()))
+                        .map(|row| {
+                            let result = move || {
+                                let origin = core::data::CellPath {
+                                    sheet: 0,
+                                    row: row as core::data::IndexType,
+                                    col: grid.with(|grid| grid.cols()) as core::data::IndexType,
+                                };
+                                grid.with(|grid| {
+                                    formula.with(|formula| core::expr::eval(formula, grid, &origin))
+                                })
+                            };
+
+                            view! {
+                                <tr>
+                                    <th class="px-1">{row + 1}</th>
+                                    {(0..grid.with_untracked(|grid| grid.cols()))
+                                        .map(|col| {
+                                            let value = move || {
+                                                grid.with(|grid| grid.rows[row][col].clone())
+                                            };
+                                            view! {
+                                                <td class="border border-secondary-200 dark:border-secondary-600">
+                                                    <input
+                                                        class="w-20 px-1"
+                                                        prop:value=value
+                                                        on:input=move |e| {
+                                                            let value = event_target_value(&e);
+                                                            grid.update(|grid| grid.rows[row][col] = value);
+                                                        }
+                                                    />
+                                                </td>
+                                            }
+                                        })
+                                        .collect::<Vec<_>>()}
+                                    <td class="px-1 border border-secondary-200 dark:border-secondary-600">
+                                        {move || match result() {
+                                            Ok(value) => Either::Left(value.to_string()),
+                                            Err(err) => {
+                                                Either::Right(
+                                                    view! {
+                                                        <span class="text-red-600" title=err.to_string()>
+                                                            {err.code()}
+                                                        </span>
+                                                    },
+                                                )
+                                            }
+                                        }}
+                                    </td>
+                                </tr>
+                            }
+                        })
+                        .collect::<Vec<_>>()}
+                </tbody>
+            </table>
+        </div>
+    }
+}
+
+/// Interpret a cell's text as a number or boolean if possible, otherwise as text.
+fn str_to_value(value: &str) -> core::expr::Value {
+    let trimmed = value.trim();
+    if trimmed.is_empty() {
+        core::expr::Value::Empty
+    } else if let Ok(value) = trimmed.parse::<i64>() {
+        core::expr::Value::Int(value)
+    } else if let Ok(value) = trimmed.parse::<f64>() {
+        core::expr::Value::Float(value)
+    } else if trimmed.eq_ignore_ascii_case("true") {
+        core::expr::Value::Bool(true)
+    } else if trimmed.eq_ignore_ascii_case("false") {
+        core::expr::Value::Bool(false)
+    } else {
+        core::expr::Value::String(value.to_string())
+    }
+}
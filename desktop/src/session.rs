@@ -0,0 +1,365 @@
+//! Session autosave and recovery.
+use crate::{explorer, formula, message, state, types};
+use hermes_core as core;
+use hermes_desktop_lib as lib;
+use leptos::{ev, prelude::*, task::spawn_local};
+use std::time::Duration;
+
+/// Session recovered from an unclean exit, to be restored when its workspace opens.
+#[derive(Clone, Copy, derive_more::Deref)]
+pub struct Recovery(RwSignal<Option<lib::session::Session>>);
+impl Recovery {
+    pub fn new() -> Self {
+        Self(RwSignal::new(None))
+    }
+}
+
+/// Save the session at the autosave interval of the settings.
+/// Autosave is disabled while the interval is `None`.
+pub fn autosave(state: state::State) {
+    let settings = state.settings;
+    let interval = Memo::new(move |_| settings.with(|settings| settings.autosave_interval));
+    let handle = StoredValue::new(None::<IntervalHandle>);
+
+    Effect::new(move || {
+        if let Some(handle) = handle.get_value() {
+            handle.clear();
+        }
+
+        let Some(interval) = interval.get() else {
+            handle.set_value(None);
+            return;
+        };
+
+        let save = {
+            let state = state.clone();
+            move || {
+                let session = current(&state);
+                spawn_local(async move {
+                    if let Err(err) = save_session(session).await {
+                        tracing::error!(?err, "could not save session");
+                    }
+                });
+            }
+        };
+
+        match set_interval_with_handle(save, Duration::from_secs(interval.into())) {
+            Ok(interval) => handle.set_value(Some(interval)),
+            Err(err) => {
+                tracing::error!(?err);
+                handle.set_value(None);
+            }
+        }
+    });
+
+    on_cleanup(move || {
+        if let Some(handle) = handle.get_value() {
+            handle.clear();
+        }
+    });
+}
+
+/// Save the session now, reporting the result as a message.
+pub fn save(state: state::State) {
+    let session = current(&state);
+    spawn_local(async move {
+        match save_session(session).await {
+            Ok(()) => state
+                .messages
+                .push(message::Message::success("Workspace saved.")),
+            Err(err) => {
+                tracing::error!(?err, "could not save session");
+                state.messages.push(message::Message::error_with_body(
+                    "Could not save workspace.",
+                    err.to_string(),
+                ));
+            }
+        }
+    });
+}
+
+/// Restore the recovered session if it belongs to the workspace.
+pub fn restore(state: state::State, owner: state::WorkspaceOwner) {
+    let recovery = expect_context::<Recovery>();
+    let Some(session) = recovery.write_untracked().take() else {
+        return;
+    };
+    if session.root != *state.root_path() {
+        return;
+    }
+
+    let editor_vis = expect_context::<state::FormulaEditorVisibility>();
+    spawn_local(restore_session(state, owner, editor_vis, session));
+}
+
+async fn restore_session(
+    state: state::State,
+    owner: state::WorkspaceOwner,
+    editor_vis: state::FormulaEditorVisibility,
+    session: lib::session::Session,
+) {
+    let mut missing = vec![];
+    let mut opened = vec![];
+    for path in session.datasets.iter() {
+        let Some(file) = state.directory_tree.get_file_by_path(path) else {
+            missing.push(path.to_string_lossy().to_string());
+            continue;
+        };
+
+        explorer::open_file(
+            state.clone(),
+            owner.clone(),
+            file.id().clone(),
+            lib::data::LoadId::next(),
+        )
+        .await;
+        opened.push(file.id().clone());
+    }
+
+    let mut formulas = Vec::with_capacity(session.formulas.len());
+    for session_formula in session.formulas.iter() {
+        let Some(domain) = formula_domain(&state, session_formula) else {
+            missing.push(format!(
+                "{} ({})",
+                session_formula.path.to_string_lossy(),
+                session_formula.value
+            ));
+            formulas.push(None);
+            continue;
+        };
+
+        let formula = owner.with(|| state::Formula::new(domain));
+        formula.value.set(session_formula.value.clone());
+        formulas.push(Some(formula.id().clone()));
+        state.formulas.write().push(formula);
+    }
+
+    let options = state
+        .settings
+        .with_untracked(|settings| settings.eval_options());
+    for dataset in opened {
+        formula::eval_dataset_formulas(
+            dataset,
+            state.formulas,
+            state.datasets,
+            owner.clone(),
+            options.clone(),
+        )
+        .await;
+    }
+
+    if let Some(active) = session
+        .active
+        .as_ref()
+        .and_then(|path| state.directory_tree.get_file_by_path(path))
+    {
+        state.active_dataset.write().insert(active.id().clone());
+    }
+
+    if let Some(pending) = session.pending {
+        if let Some(Some(id)) = formulas.get(pending.formula) {
+            state.formula_draft.set(Some(pending.input));
+            state.active_formula.set(Some(id.clone()));
+            editor_vis.set(true);
+        }
+    }
+
+    let compatibility = core::expr::compatibility(session.expr_version);
+    if !compatibility.is_compatible() {
+        state.messages.push(message::Message::warning_with_body(
+            "Session formulas were written for another version of hermes.",
+            formula::compatibility_message(&compatibility),
+        ));
+    }
+
+    if missing.is_empty() {
+        state
+            .messages
+            .push(message::Message::success("Session restored."));
+    } else {
+        state.messages.push(message::Message::warning_with_body(
+            "Some of the session could not be restored.",
+            missing.join("\n"),
+        ));
+    }
+}
+
+/// # Returns
+/// Domain of the formula in the open datasets,
+/// or `None` if its dataset or sheet is not open.
+fn formula_domain(
+    state: &state::State,
+    formula: &lib::session::SessionFormula,
+) -> Option<state::FormulaDomain> {
+    let file = state.directory_tree.get_file_by_path(&formula.path)?;
+    let datasets = state.datasets.read_untracked();
+    let dataset = datasets.iter().find(|dataset| dataset.id() == file.id())?;
+    match (dataset, formula.sheet) {
+        (state::Dataset::Csv(_), None) => Some(state::FormulaDomain::CsvCell {
+            dataset: dataset.id().clone(),
+            cell: formula.cell.clone(),
+        }),
+        (state::Dataset::Workbook(workbook), Some(sheet)) => {
+            let sheet = workbook
+                .sheets
+                .read_untracked()
+                .get(sheet as usize)?
+                .id()
+                .clone();
+            Some(state::FormulaDomain::WorkbookCell {
+                dataset: dataset.id().clone(),
+                sheet,
+                cell: formula.cell.clone(),
+            })
+        }
+        _ => None,
+    }
+}
+
+/// # Returns
+/// Current session of the workspace.
+fn current(state: &state::State) -> lib::session::Session {
+    let directory_tree = &state.directory_tree;
+    let datasets = state.datasets.read_untracked();
+    let active_formula = state.active_formula.get_untracked();
+
+    let mut formulas = vec![];
+    let mut pending = None;
+    for formula in state.formulas.read_untracked().iter() {
+        let Some(session_formula) = formula.domain.with_untracked(|domain| {
+            let (dataset, sheet, cell) = match domain {
+                state::FormulaDomain::CsvCell { dataset, cell } => (dataset, None, cell),
+                state::FormulaDomain::WorkbookCell {
+                    dataset,
+                    sheet,
+                    cell,
+                } => {
+                    let Some(state::Dataset::Workbook(workbook)) =
+                        datasets.iter().find(|ds| ds.id() == dataset)
+                    else {
+                        return None;
+                    };
+                    let idx = workbook
+                        .sheets
+                        .read_untracked()
+                        .iter()
+                        .position(|ws| ws.id() == sheet)?;
+                    (dataset, Some(idx as core::data::IndexType), cell)
+                }
+            };
+
+            Some(lib::session::SessionFormula {
+                path: directory_tree.get_file_path(dataset)?,
+                sheet,
+                cell: cell.clone(),
+                value: formula.value.get_untracked(),
+            })
+        }) else {
+            continue;
+        };
+
+        if active_formula.as_ref() == Some(formula.id()) {
+            pending = state
+                .formula_draft
+                .get_untracked()
+                .map(|input| lib::session::PendingEdit {
+                    formula: formulas.len(),
+                    input,
+                });
+        }
+        formulas.push(session_formula);
+    }
+
+    lib::session::Session {
+        root: state.root_path().clone(),
+        datasets: state
+            .selected_files
+            .read_untracked()
+            .iter()
+            .filter(|file| datasets.iter().any(|dataset| dataset.id() == *file))
+            .filter_map(|file| directory_tree.get_file_path(file))
+            .collect(),
+        active: state.active_dataset.with_untracked(|active| {
+            active
+                .as_ref()
+                .and_then(|id| directory_tree.get_file_path(id))
+        }),
+        formulas,
+        pending,
+        expr_version: core::expr::VERSION,
+    }
+}
+
+/// Prompt to restore the session recovered from an unclean exit.
+#[component]
+pub fn RecoveryPrompt(set_root_path: WriteSignal<Option<std::path::PathBuf>>) -> impl IntoView {
+    let recovery = expect_context::<Recovery>();
+    let recovered = LocalResource::new(load_recovered_session);
+    let (dismissed, set_dismissed) = signal(false);
+
+    let discard_action = Action::new_local(move |_| async move {
+        if let Err(err) = clear_session().await {
+            tracing::error!(?err, "could not clear session");
+        }
+    });
+
+    move || {
+        if dismissed.get() {
+            return None;
+        }
+        let session = recovered.get().flatten()?;
+        if session.is_empty() {
+            return None;
+        }
+
+        let root = session.root.to_string_lossy().to_string();
+        let restore = move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+
+            let root = session.root.clone();
+            recovery.set(Some(session.clone()));
+            set_dismissed(true);
+            set_root_path(Some(root));
+        };
+
+        let discard = move |e: ev::MouseEvent| {
+            if e.button() != types::MouseButton::Primary {
+                return;
+            }
+
+            set_dismissed(true);
+            discard_action.dispatch(());
+        };
+
+        Some(view! {
+            <div class="max-w-md mx-auto pb-4">
+                <div>"Hermes did not close properly. Restore the previous session?"</div>
+                <small class="block truncate text-secondary-700 dark:text-secondary-200">
+                    {root}
+                </small>
+                <div class="flex gap-2 pt-2">
+                    <button on:mousedown=restore class="btn btn-primary cursor-pointer">
+                        "Restore"
+                    </button>
+                    <button on:mousedown=discard class="btn btn-secondary cursor-pointer">
+                        "Discard"
+                    </button>
+                </div>
+            </div>
+        })
+    }
+}
+
+async fn load_recovered_session() -> Option<lib::session::Session> {
+    crate::ipc::invoke_infallible(lib::ipc::LoadRecoveredSession).await
+}
+
+async fn save_session(session: lib::session::Session) -> Result<(), lib::fs::error::FileOperation> {
+    crate::ipc::invoke(lib::ipc::SaveSession { session }).await
+}
+
+async fn clear_session() -> Result<(), lib::fs::error::FileOperation> {
+    crate::ipc::invoke(lib::ipc::ClearSession).await
+}
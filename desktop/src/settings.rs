@@ -0,0 +1,620 @@
+//! Application and project settings.
+use crate::{icon, message, state, types};
+use hermes_core as core;
+use hermes_desktop_lib as lib;
+use leptos::{ev, prelude::*, task::spawn_local};
+use leptos_icons::Icon;
+use std::path::PathBuf;
+
+const MIB: u64 = 1024 * 1024;
+
+/// Csv delimiters that may be selected.
+const DELIMITERS: &[(u8, &str)] = &[
+    (b',', "Comma"),
+    (b';', "Semicolon"),
+    (b'\t', "Tab"),
+    (b'|', "Pipe"),
+];
+
+/// Evaluations of empty cells that may be selected.
+const EMPTY_CELLS: &[(core::expr::EmptyCells, &str)] = &[
+    (core::expr::EmptyCells::Empty, "Empty"),
+    (core::expr::EmptyCells::Zero, "Zero"),
+    (core::expr::EmptyCells::Error, "Error"),
+];
+
+/// Comparisons of empty values that may be selected.
+const EMPTY_COMPARISONS: &[(core::expr::EmptyComparison, &str)] = &[
+    (core::expr::EmptyComparison::Blank, "Blank text or zero"),
+    (core::expr::EmptyComparison::String, "Blank text"),
+    (core::expr::EmptyComparison::Zero, "Zero"),
+    (core::expr::EmptyComparison::Strict, "Only empty"),
+];
+
+/// Evaluations of integer overflow that may be selected.
+const OVERFLOWS: &[(core::expr::Overflow, &str)] = &[
+    (core::expr::Overflow::Promote, "Decimal"),
+    (core::expr::Overflow::Error, "Error"),
+];
+
+/// Csv quote characters that may be selected.
+const QUOTES: &[(u8, &str)] = &[(b'"', "Double quote"), (b'\'', "Single quote")];
+
+/// Load the settings and protected files of the project into the state.
+pub fn load(state: state::State) {
+    spawn_local(async move {
+        let loaded = get_settings(state.root_path().clone()).await;
+        state.settings.set(loaded.settings);
+        state.settings_scope.set(loaded.scope);
+
+        let protected = loaded
+            .protected
+            .iter()
+            .filter_map(|path| state.directory_tree.get_file_by_path(path))
+            .map(|file| file.id().clone())
+            .collect::<Vec<_>>();
+        state
+            .protected_files
+            .update(|files| files.extend(protected));
+    });
+}
+
+/// Edit the settings.
+/// Changes take effect immediately and are saved.
+#[component]
+pub fn Settings() -> impl IntoView {
+    let state = expect_context::<state::State>();
+    let settings = state.settings;
+    let scope = state.settings_scope;
+
+    let save = Action::new_local({
+        let root = state.root_path().clone();
+        let messages = state.messages;
+        move |_: &()| {
+            let root = root.clone();
+            let scope = scope.get_untracked();
+            let settings = settings.get_untracked();
+            async move {
+                if let Err(err) = set_settings(root, scope, settings).await {
+                    tracing::error!(?err);
+                    messages.push(message::Message::error_with_body(
+                        "Could not save settings.",
+                        err.to_string(),
+                    ));
+                }
+            }
+        }
+    });
+
+    let set_scope = move |e: ev::Event| {
+        let scope_value = match event_target_value(&e).as_str() {
+            "project" => lib::settings::Scope::Project,
+            _ => lib::settings::Scope::Global,
+        };
+        scope.set(scope_value);
+        save.dispatch(());
+    };
+
+    let toggle_theme = move |e: ev::MouseEvent| {
+        if e.button() != types::MouseButton::Primary {
+            return;
+        }
+
+        settings.update(|settings| settings.theme = settings.theme.next());
+        save.dispatch(());
+    };
+
+    let set_theme = move |e: ev::Event| {
+        let idx = event_target_value(&e).parse::<usize>().unwrap_or(0);
+        if let Some(theme) = lib::settings::Theme::ALL.get(idx) {
+            settings.update(|settings| settings.theme = *theme);
+            save.dispatch(());
+        }
+    };
+
+    let set_locale = move |e: ev::Event| {
+        let locale = event_target_value(&e);
+        let locale = locale.trim();
+        settings.update(|settings| {
+            settings.locale = (!locale.is_empty()).then(|| locale.to_string());
+        });
+        save.dispatch(());
+    };
+
+    let set_decimal = move |e: ev::Event| {
+        let idx = event_target_value(&e).parse::<usize>().unwrap_or(0);
+        if let Some(decimal) = lib::settings::DecimalSeparator::ALL.get(idx) {
+            settings.update(|settings| settings.decimal = *decimal);
+            save.dispatch(());
+        }
+    };
+
+    let set_empty_cells = move |e: ev::Event| {
+        let idx = event_target_value(&e).parse::<usize>().unwrap_or(0);
+        if let Some((empty_cells, _)) = EMPTY_CELLS.get(idx) {
+            settings.update(|settings| settings.empty_cells = *empty_cells);
+            save.dispatch(());
+        }
+    };
+
+    let set_empty_comparison = move |e: ev::Event| {
+        let idx = event_target_value(&e).parse::<usize>().unwrap_or(0);
+        if let Some((empty_comparison, _)) = EMPTY_COMPARISONS.get(idx) {
+            settings.update(|settings| settings.empty_comparison = *empty_comparison);
+            save.dispatch(());
+        }
+    };
+
+    let set_overflow = move |e: ev::Event| {
+        let idx = event_target_value(&e).parse::<usize>().unwrap_or(0);
+        if let Some((overflow, _)) = OVERFLOWS.get(idx) {
+            settings.update(|settings| settings.overflow = *overflow);
+            save.dispatch(());
+        }
+    };
+
+    let set_delimiter = move |e: ev::Event| {
+        let idx = event_target_value(&e).parse::<usize>().unwrap_or(0);
+        if let Some((delimiter, _)) = DELIMITERS.get(idx) {
+            settings.update(|settings| settings.csv.delimiter = *delimiter);
+            save.dispatch(());
+        }
+    };
+
+    let set_quote = move |e: ev::Event| {
+        let idx = event_target_value(&e).parse::<usize>().unwrap_or(0);
+        if let Some((quote, _)) = QUOTES.get(idx) {
+            settings.update(|settings| settings.csv.quote = *quote);
+            save.dispatch(());
+        }
+    };
+
+    let set_autosave_interval = move |e: ev::Event| {
+        let interval = event_target_value(&e)
+            .trim()
+            .parse::<u32>()
+            .ok()
+            .filter(|interval| *interval > 0);
+        settings.update(|settings| settings.autosave_interval = interval);
+        save.dispatch(());
+    };
+
+    let set_max_dataset_size = move |e: ev::Event| {
+        if let Ok(size) = event_target_value(&e).trim().parse::<u64>() {
+            settings.update(|settings| settings.limits.max_dataset_size = size * MIB);
+            save.dispatch(());
+        }
+    };
+
+    let set_preview_rows = move |e: ev::Event| {
+        if let Ok(rows) = event_target_value(&e).trim().parse::<usize>() {
+            settings.update(|settings| settings.limits.preview_rows = rows);
+            save.dispatch(());
+        }
+    };
+
+    let set_notify_load = move |e: ev::Event| {
+        settings.update(|settings| settings.notifications.load = event_target_checked(&e));
+        save.dispatch(());
+    };
+
+    let set_notify_run = move |e: ev::Event| {
+        settings.update(|settings| settings.notifications.run = event_target_checked(&e));
+        save.dispatch(());
+    };
+
+    let set_manifest = move |e: ev::Event| {
+        settings.update(|settings| settings.manifest = event_target_checked(&e));
+        save.dispatch(());
+    };
+
+    let shortcuts = {
+        let messages = state.messages;
+        move || {
+            lib::settings::Action::ALL
+                .iter()
+                .map(|action| {
+                    let action = *action;
+                    let is_default = move || {
+                        settings.with(|settings| !settings.keybindings.contains_key(&action))
+                    };
+
+                    let set_shortcut = move |e: ev::KeyboardEvent| {
+                        if e.key() == "Tab" {
+                            return;
+                        }
+                        e.prevent_default();
+                        e.stop_propagation();
+
+                        let Some(shortcut) = lib::settings::Shortcut::from_key(
+                            &e.key(),
+                            e.ctrl_key() || e.meta_key(),
+                            e.shift_key(),
+                            e.alt_key(),
+                        ) else {
+                            return;
+                        };
+
+                        let conflict = settings.with_untracked(|settings| {
+                            lib::settings::Action::ALL.iter().copied().find(|other| {
+                                *other != action && settings.shortcut(*other) == shortcut
+                            })
+                        });
+                        if let Some(conflict) = conflict {
+                            messages.push(message::Message::warning_with_body(
+                                "Shortcut already in use.",
+                                format!("{shortcut} is bound to {}.", conflict.label()),
+                            ));
+                            return;
+                        }
+
+                        settings.update(|settings| {
+                            if shortcut == action.default_shortcut() {
+                                settings.keybindings.remove(&action);
+                            } else {
+                                settings.keybindings.insert(action, shortcut);
+                            }
+                        });
+                        save.dispatch(());
+                    };
+
+                    let reset = move |e: ev::MouseEvent| {
+                        if e.button() != types::MouseButton::Primary {
+                            return;
+                        }
+
+                        settings.update(|settings| {
+                            settings.keybindings.remove(&action);
+                        });
+                        save.dispatch(());
+                    };
+
+                    view! {
+                        <label class="flex gap-2 items-center">
+                            <span class="grow">{action.label()}</span>
+                            <input
+                                class="input-compact w-32 font-mono"
+                                title="Press the new shortcut."
+                                readonly
+                                prop:value=move || {
+                                    settings.with(|settings| settings.shortcut(action).to_string())
+                                }
+                                on:keydown=set_shortcut
+                            />
+                            <button
+                                type="button"
+                                class="btn-cmd btn-secondary"
+                                class:invisible=is_default
+                                title="Reset to default"
+                                on:mousedown=reset
+                            >
+                                <Icon icon=icon::Revert />
+                            </button>
+                        </label>
+                    }
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+
+    view! {
+        <div>
+            <div class="pb flex gap-2">
+                <h2 class="grow font-bold uppercase">"Settings"</h2>
+                <div>
+                    <button
+                        type="button"
+                        class="btn-cmd cursor-pointer"
+                        title=move || {
+                            format!(
+                                "Theme: {}",
+                                settings.with(|settings| theme_label(settings.theme)),
+                            )
+                        }
+                        on:mousedown=toggle_theme
+                    >
+                        <Icon icon=icon::Theme />
+                    </button>
+                </div>
+            </div>
+            <div class="flex flex-col gap-1 text-sm">
+                <label class="flex gap-2 items-center">
+                    <span class="grow">"Save for"</span>
+                    <select class="input-compact" on:change=set_scope>
+                        <option
+                            value="global"
+                            selected=move || scope.get() == lib::settings::Scope::Global
+                        >
+                            "All projects"
+                        </option>
+                        <option
+                            value="project"
+                            selected=move || scope.get() == lib::settings::Scope::Project
+                        >
+                            "This project"
+                        </option>
+                    </select>
+                </label>
+                <label class="flex gap-2 items-center">
+                    <span class="grow">"Theme"</span>
+                    <select class="input-compact" on:change=set_theme>
+                        {lib::settings::Theme::ALL
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, value)| {
+                                let value = *value;
+                                view! {
+                                    <option
+                                        value=idx.to_string()
+                                        selected=move || {
+                                            settings.with(|settings| settings.theme == value)
+                                        }
+                                    >
+                                        {theme_label(value)}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label class="flex gap-2 items-center">
+                    <span class="grow">"Language"</span>
+                    <input
+                        class="input-compact w-24"
+                        placeholder="System"
+                        title="Language tag, e.g. en-US."
+                        prop:value=move || {
+                            settings.with(|settings| settings.locale.clone().unwrap_or_default())
+                        }
+                        on:change=set_locale
+                    />
+                </label>
+                <label class="flex gap-2 items-center">
+                    <span class="grow">"Decimal separator"</span>
+                    <select class="input-compact" on:change=set_decimal>
+                        {lib::settings::DecimalSeparator::ALL
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, value)| {
+                                let value = *value;
+                                view! {
+                                    <option
+                                        value=idx.to_string()
+                                        selected=move || {
+                                            settings.with(|settings| settings.decimal == value)
+                                        }
+                                    >
+                                        {value.as_char().to_string()}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label
+                    class="flex gap-2 items-center"
+                    title="How formulas evaluate references to empty cells."
+                >
+                    <span class="grow">"Empty cells in formulas"</span>
+                    <select class="input-compact" on:change=set_empty_cells>
+                        {EMPTY_CELLS
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, (value, label))| {
+                                let value = *value;
+                                view! {
+                                    <option
+                                        value=idx.to_string()
+                                        selected=move || {
+                                            settings.with(|settings| settings.empty_cells == value)
+                                        }
+                                    >
+                                        {*label}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label
+                    class="flex gap-2 items-center"
+                    title="What empty values equal when compared in formulas."
+                >
+                    <span class="grow">"Empty values in comparisons"</span>
+                    <select class="input-compact" on:change=set_empty_comparison>
+                        {EMPTY_COMPARISONS
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, (value, label))| {
+                                let value = *value;
+                                view! {
+                                    <option
+                                        value=idx.to_string()
+                                        selected=move || {
+                                            settings
+                                                .with(|settings| settings.empty_comparison == value)
+                                        }
+                                    >
+                                        {*label}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label
+                    class="flex gap-2 items-center"
+                    title="How formulas evaluate integer arithmetic too large for an integer."
+                >
+                    <span class="grow">"Integer overflow in formulas"</span>
+                    <select class="input-compact" on:change=set_overflow>
+                        {OVERFLOWS
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, (value, label))| {
+                                let value = *value;
+                                view! {
+                                    <option
+                                        value=idx.to_string()
+                                        selected=move || {
+                                            settings.with(|settings| settings.overflow == value)
+                                        }
+                                    >
+                                        {*label}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label class="flex gap-2 items-center">
+                    <span class="grow">"Csv delimiter"</span>
+                    <select class="input-compact" on:change=set_delimiter>
+                        {DELIMITERS
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, (value, label))| {
+                                let value = *value;
+                                view! {
+                                    <option
+                                        value=idx.to_string()
+                                        selected=move || {
+                                            settings.with(|settings| settings.csv.delimiter == value)
+                                        }
+                                    >
+                                        {*label}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label class="flex gap-2 items-center">
+                    <span class="grow">"Csv quote"</span>
+                    <select class="input-compact" on:change=set_quote>
+                        {QUOTES
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, (value, label))| {
+                                let value = *value;
+                                view! {
+                                    <option
+                                        value=idx.to_string()
+                                        selected=move || {
+                                            settings.with(|settings| settings.csv.quote == value)
+                                        }
+                                    >
+                                        {*label}
+                                    </option>
+                                }
+                            })
+                            .collect::<Vec<_>>()}
+                    </select>
+                </label>
+                <label class="flex gap-2 items-center">
+                    <span class="grow">"Autosave (seconds)"</span>
+                    <input
+                        type="number"
+                        min="0"
+                        class="input-compact w-24"
+                        placeholder="Off"
+                        prop:value=move || {
+                            settings
+                                .with(|settings| {
+                                    settings
+                                        .autosave_interval
+                                        .map(|interval| interval.to_string())
+                                        .unwrap_or_default()
+                                })
+                        }
+                        on:change=set_autosave_interval
+                    />
+                </label>
+                <label class="flex gap-2 items-center">
+                    <span class="grow">"Maximum file size (MiB)"</span>
+                    <input
+                        type="number"
+                        min="1"
+                        class="input-compact w-24"
+                        prop:value=move || {
+                            settings
+                                .with(|settings| {
+                                    (settings.limits.max_dataset_size / MIB).to_string()
+                                })
+                        }
+                        on:change=set_max_dataset_size
+                    />
+                </label>
+                <label
+                    class="flex gap-2 items-center"
+                    title="Rows loaded from files larger than the maximum size."
+                >
+                    <span class="grow">"Preview rows"</span>
+                    <input
+                        type="number"
+                        min="1"
+                        class="input-compact w-24"
+                        prop:value=move || {
+                            settings.with(|settings| settings.limits.preview_rows.to_string())
+                        }
+                        on:change=set_preview_rows
+                    />
+                </label>
+                <label class="flex gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || settings.with(|settings| settings.notifications.load)
+                        on:change=set_notify_load
+                    />
+                    "Notify when files finish loading"
+                </label>
+                <label class="flex gap-2 items-center">
+                    <input
+                        type="checkbox"
+                        prop:checked=move || settings.with(|settings| settings.notifications.run)
+                        on:change=set_notify_run
+                    />
+                    "Notify when runs finish"
+                </label>
+                <label
+                    class="flex gap-2 items-center"
+                    title="List each file written with its checksum, the input files, and the formulas used."
+                >
+                    <input
+                        type="checkbox"
+                        prop:checked=move || settings.with(|settings| settings.manifest)
+                        on:change=set_manifest
+                    />
+                    "Write a manifest after each run"
+                </label>
+                <h3 class="pt-1 font-bold">"Keyboard shortcuts"</h3>
+                {shortcuts}
+            </div>
+        </div>
+    }
+}
+
+fn theme_label(theme: lib::settings::Theme) -> &'static str {
+    match theme {
+        lib::settings::Theme::System => "System",
+        lib::settings::Theme::Light => "Light",
+        lib::settings::Theme::Dark => "Dark",
+    }
+}
+
+async fn get_settings(root: PathBuf) -> lib::settings::ProjectSettings {
+    crate::ipc::invoke_infallible(lib::ipc::GetSettings { root }).await
+}
+
+async fn set_settings(
+    root: PathBuf,
+    scope: lib::settings::Scope,
+    settings: lib::settings::Settings,
+) -> Result<(), lib::fs::error::FileOperation> {
+    crate::ipc::invoke(lib::ipc::SetSettings {
+        root,
+        scope,
+        settings,
+    })
+    .await
+}
@@ -2,11 +2,19 @@ use crate::{formula, message};
 use hermes_core as core;
 use hermes_desktop_lib as lib;
 use leptos::prelude::*;
-use std::{collections::BTreeMap, ffi::OsString, path::PathBuf, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    ffi::OsString,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 const CANVAS_ROWS_DEFAULT: core::data::IndexType = 100;
 const CANVAS_COLS_DEFAULT: core::data::IndexType = 26;
 
+/// Time, in milliseconds, during which changes to a file written by a run are attributed to the run.
+const AUTO_RUN_WRITE_GRACE_MS: f64 = 3_000.0;
+
 pub trait FileResource {
     fn file(&self) -> &ResourceId;
 }
@@ -19,25 +27,82 @@ impl ResourceId {
     }
 }
 
-/// Abort handle used to cancel loading a workbook.
+/// Abort handle used to cancel loading a workbook,
+/// with the handle of the backend load it awaits.
 #[derive(Clone)]
-pub struct LoadWorkbookActionAbortHandle(Option<Arc<ActionAbortHandle>>);
+pub struct LoadWorkbookActionAbortHandle(Option<Arc<(ActionAbortHandle, lib::data::LoadId)>>);
 impl LoadWorkbookActionAbortHandle {
     pub fn new() -> Self {
         Self(None)
     }
 
-    pub fn insert(&mut self, handle: ActionAbortHandle) {
-        let _ = self.0.insert(Arc::new(handle));
+    pub fn insert(&mut self, handle: ActionAbortHandle, load: lib::data::LoadId) {
+        let _ = self.0.insert(Arc::new((handle, load)));
     }
 
-    pub fn take(&mut self) -> Option<ActionAbortHandle> {
+    pub fn take(&mut self) -> Option<(ActionAbortHandle, lib::data::LoadId)> {
         self.0
             .take()
             .map(|handle| Arc::into_inner(handle).expect("single owner"))
     }
 }
 
+/// Latest progress reported by a dataset load.
+#[derive(Clone, Copy, Debug, derive_more::Deref)]
+pub struct LoadProgress(RwSignal<Option<lib::data::LoadProgressEvent>>);
+impl LoadProgress {
+    pub fn new() -> Self {
+        Self(RwSignal::new(None))
+    }
+
+    /// # Returns
+    /// Progress of the load, if any was reported.
+    pub fn of(&self, load: &lib::data::LoadId) -> Option<lib::data::LoadProgress> {
+        self.0.with(|event| {
+            event
+                .as_ref()
+                .filter(|event| event.id == *load)
+                .map(|event| event.progress)
+        })
+    }
+}
+
+/// Number of leading rows and columns of a sheet kept visible while scrolling.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Freeze {
+    pub rows: core::data::IndexType,
+    pub cols: core::data::IndexType,
+}
+
+/// Dataset shown in the workspace, with the canvas it is drawn on.
+#[derive(Clone, Copy)]
+pub struct Pane {
+    pub dataset: RwSignal<ActiveDataset>,
+    pub canvas: Canvas,
+
+    /// Whether the pane shows the [active dataset](State::active_dataset).
+    pub primary: bool,
+}
+
+impl Pane {
+    /// Create a secondary pane.
+    /// The pane tracks its own active cell.
+    pub fn new(dataset: Option<ResourceId>) -> Self {
+        let mut active = ActiveDataset::None;
+        if let Some(dataset) = dataset {
+            active.insert(dataset);
+        }
+
+        Self {
+            dataset: RwSignal::new(active),
+            canvas: Canvas::new(CANVAS_ROWS_DEFAULT, CANVAS_COLS_DEFAULT),
+            frozen: RwSignal::new(HashMap::new()),
+            column_widths: RwSignal::new(HashMap::new()),
+            primary: false,
+        }
+    }
+}
+
 /// Reactive owner for the workspace.
 /// Use to hoist ownership when creating signals.
 #[derive(Clone, derive_more::Deref)]
@@ -100,18 +165,133 @@ impl ActiveDataset {
 pub enum ActiveCell {
     None,
     Some(core::data::CellIndex),
+
+    /// Rectangular range of cells, from the top left to the bottom right cell.
+    Range {
+        start: core::data::CellIndex,
+        end: core::data::CellIndex,
+    },
 }
 
 impl ActiveCell {
     pub fn is_some(&self) -> bool {
-        matches!(self, Self::Some(_))
+        !matches!(self, Self::None)
+    }
+
+    /// # Returns
+    /// Top left and bottom right cells of the selection.
+    pub fn bounds(&self) -> Option<(&core::data::CellIndex, &core::data::CellIndex)> {
+        match self {
+            Self::None => None,
+            Self::Some(cell) => Some((cell, cell)),
+            Self::Range { start, end } => Some((start, end)),
+        }
+    }
+}
+
+impl From<core::data::RangeRef> for ActiveCell {
+    fn from(value: core::data::RangeRef) -> Self {
+        if value.is_cell() {
+            Self::Some(value.start)
+        } else {
+            Self::Range {
+                start: value.start,
+                end: value.end,
+            }
+        }
     }
 }
 
-/// `true` indicates the formula editor should be visible.
+/// Default size of the formula editor panel, in pixels.
+const FORMULA_EDITOR_SIZE_DEFAULT: i32 = 160;
+
+/// Smallest the formula editor panel can be resized to, in pixels.
+const FORMULA_EDITOR_SIZE_MIN: i32 = 80;
+
+/// Edge of the workspace the formula editor panel is docked to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum EditorDock {
+    #[default]
+    Bottom,
+    Right,
+}
+
+/// Layout of the formula editor panel.
+/// Derefs to its visibility, `true` indicating the editor should be visible.
 #[derive(Clone, Copy, Debug, derive_more::Deref)]
-pub struct FormulaEditorVisibility(RwSignal<bool>);
+pub struct FormulaEditorVisibility {
+    #[deref]
+    visible: RwSignal<bool>,
+    pub dock: RwSignal<EditorDock>,
+
+    /// Height of the panel when docked to the bottom,
+    /// or width when docked to the right, in pixels.
+    size: RwSignal<i32>,
+}
+
 impl FormulaEditorVisibility {
+    pub fn new() -> Self {
+        Self {
+            visible: RwSignal::new(false),
+            dock: RwSignal::new(EditorDock::default()),
+            size: RwSignal::new(FORMULA_EDITOR_SIZE_DEFAULT),
+        }
+    }
+
+    pub fn size(&self) -> ReadSignal<i32> {
+        self.size.read_only()
+    }
+
+    /// Set the size of the panel, limited to its minimum.
+    pub fn set_size(&self, size: i32) {
+        self.size.set(size.max(FORMULA_EDITOR_SIZE_MIN));
+    }
+}
+
+/// File Hermes was launched with, relative to the root,
+/// to be opened when its workspace loads.
+#[derive(Clone, Copy, Debug, derive_more::Deref)]
+pub struct LaunchFile(RwSignal<Option<PathBuf>>);
+impl LaunchFile {
+    pub fn new() -> Self {
+        Self(RwSignal::new(None))
+    }
+}
+
+/// Media query matching small or touch screens.
+const COMPACT_LAYOUT_QUERY: &str = "(max-width: 767px), (pointer: coarse)";
+
+/// `true` indicates the compact layout, for small or touch screens, is in use.
+#[derive(Clone, Copy, Debug, derive_more::Deref)]
+pub struct CompactLayout(Signal<bool>);
+impl CompactLayout {
+    pub fn new() -> Self {
+        Self(leptos_use::use_media_query(COMPACT_LAYOUT_QUERY))
+    }
+}
+
+/// Settings in effect for the open project.
+#[derive(Clone, Copy, Debug, derive_more::Deref)]
+pub struct Settings(RwSignal<lib::settings::Settings>);
+impl Settings {
+    pub fn new() -> Self {
+        Self(RwSignal::new(lib::settings::Settings::default()))
+    }
+}
+
+/// References of the formula being edited.
+#[derive(Clone, Copy, Debug, derive_more::Deref)]
+pub struct FormulaReferences(RwSignal<Vec<core::expr::Reference>>);
+impl FormulaReferences {
+    pub fn new() -> Self {
+        Self(RwSignal::new(vec![]))
+    }
+}
+
+/// `true` indicates the new formula dialog should be visible.
+#[derive(Clone, Copy, Debug, derive_more::Deref)]
+pub struct NewFormulaDialogVisibility(RwSignal<bool>);
+impl NewFormulaDialogVisibility {
     pub fn new() -> Self {
         Self(RwSignal::new(false))
     }
@@ -120,35 +300,153 @@ impl FormulaEditorVisibility {
 #[derive(Clone)]
 pub struct State {
     root_path: PathBuf,
-    pub messages: RwSignal<Vec<message::Message>>,
+    pub messages: message::Messages,
     pub directory_tree: DirectoryTree,
     /// Active resources.
     pub selected_files: RwSignal<Vec<ResourceId>>,
+    /// Files protected from modification.
+    pub protected_files: RwSignal<HashSet<ResourceId>>,
+    /// Sets of files with identical content.
+    pub duplicate_files: RwSignal<Vec<Vec<ResourceId>>>,
+    /// Notation used to label cells in the workspace.
+    pub reference_style: RwSignal<core::data::ReferenceStyle>,
+    /// Cleaning operations staged for each dataset, applied in order.
+    pub cleaning: RwSignal<HashMap<ResourceId, Vec<lib::clean::Operation>>>,
+    /// Import options used when loading a file.
+    pub import_options: RwSignal<HashMap<ResourceId, lib::data::ImportOptions>>,
     pub active_dataset: RwSignal<ActiveDataset>,
     pub datasets: Datasets,
     pub formulas: Formulas,
     pub active_formula: RwSignal<Option<ResourceId>>,
+    /// Input of the formula being edited that has not been applied.
+    pub formula_draft: RwSignal<Option<String>>,
+    /// Column of the active dataset selected in the canvas.
+    pub selected_column: RwSignal<Option<core::data::IndexType>>,
+    /// Files created from formula results when the workspace is run.
+    pub output_files: RwSignal<Vec<OutputFile>>,
+    /// Summaries of datasets written to files when the workspace is run.
+    pub pivot_tables: RwSignal<Vec<PivotTable>>,
+    /// Datasets joined on key columns, written to files when the workspace is run.
+    pub join_tables: RwSignal<Vec<JoinTable>>,
+    /// Subsets of dataset rows, written to files when the workspace is run.
+    pub subset_tables: RwSignal<Vec<SubsetTable>>,
+    /// Saved formula templates.
+    pub templates: RwSignal<Vec<lib::template::Template>>,
+    pub auto_run: AutoRun,
+    /// Only modify files when running the workspace if every order succeeds.
+    pub atomic_run: RwSignal<bool>,
     pub canvas: Canvas,
+    /// Rows and columns frozen in each sheet, keyed by dataset and sheet index.
+    /// Kept for the session.
+    pub frozen: RwSignal<HashMap<(ResourceId, usize), Freeze>>,
+    /// Widths of resized columns in each sheet, in pixels, keyed by dataset and sheet index.
+    /// Kept for the session.
+    pub column_widths: RwSignal<HashMap<(ResourceId, usize), HashMap<core::data::IndexType, i32>>>,
+    /// Pane shown beside the active dataset, e.g. to view a dataset referenced by a formula.
+    pub split: RwSignal<Option<Pane>>,
+    /// Formula values written to each dataset's file by runs, keyed by sheet index and cell.
+    /// Used to only write values that changed since.
+    pub written_values: RwSignal<
+        HashMap<
+            ResourceId,
+            BTreeMap<(core::data::IndexType, core::data::CellIndex), lib::data::Data>,
+        >,
+    >,
+    pub settings: Settings,
+    /// Where the project's settings are saved.
+    pub settings_scope: RwSignal<lib::settings::Scope>,
 }
 
 impl State {
-    pub fn new(root_path: PathBuf, directory_tree: lib::fs::DirectoryTree) -> Self {
+    pub fn new(
+        root_path: PathBuf,
+        directory_tree: lib::fs::DirectoryTree,
+        settings: Settings,
+    ) -> Self {
         Self {
             root_path,
-            messages: RwSignal::new(vec![]),
+            messages: message::Messages::new(),
             directory_tree: DirectoryTree::from_graph(directory_tree),
             selected_files: RwSignal::new(vec![]),
+            protected_files: RwSignal::new(HashSet::new()),
+            duplicate_files: RwSignal::new(vec![]),
+            reference_style: RwSignal::new(core::data::ReferenceStyle::default()),
+            cleaning: RwSignal::new(HashMap::new()),
+            import_options: RwSignal::new(HashMap::new()),
             active_dataset: RwSignal::new(ActiveDataset::None),
             datasets: Datasets::new(),
             formulas: Formulas::new(),
             active_formula: RwSignal::new(None),
+            formula_draft: RwSignal::new(None),
+            selected_column: RwSignal::new(None),
+            output_files: RwSignal::new(vec![]),
+            pivot_tables: RwSignal::new(vec![]),
+            join_tables: RwSignal::new(vec![]),
+            subset_tables: RwSignal::new(vec![]),
+            templates: RwSignal::new(vec![]),
+            auto_run: AutoRun::new(),
+            atomic_run: RwSignal::new(false),
             canvas: Canvas::new(CANVAS_ROWS_DEFAULT, CANVAS_COLS_DEFAULT),
+            frozen: RwSignal::new(HashMap::new()),
+            column_widths: RwSignal::new(HashMap::new()),
+            split: RwSignal::new(None),
+            written_values: RwSignal::new(HashMap::new()),
+            settings,
+            settings_scope: RwSignal::new(lib::settings::Scope::Global),
         }
     }
 
     pub fn root_path(&self) -> &PathBuf {
         &self.root_path
     }
+
+    /// # Returns
+    /// Pane showing the active dataset.
+    pub fn pane(&self) -> Pane {
+        Pane {
+            dataset: self.active_dataset,
+            canvas: self.canvas,
+            primary: true,
+        }
+    }
+
+    /// # Returns
+    /// Whether the file is protected from modification,
+    /// in which case the cells of its dataset may not be edited.
+    pub fn is_protected(&self, file: &ResourceId) -> bool {
+        self.protected_files
+            .with_untracked(|files| files.contains(file))
+    }
+
+    /// Rerender the datasets shown in all panes, e.g. after one was reloaded.
+    pub fn refresh_panes(&self) {
+        self.active_dataset.notify();
+        if let Some(split) = self.split.get_untracked() {
+            split.dataset.notify();
+        }
+    }
+
+    /// # Returns
+    /// Absolute path of the file, if it is in the project.
+    pub fn file_path(&self, file: &ResourceId) -> Option<PathBuf> {
+        self.directory_tree
+            .get_file_path(file)
+            .map(|path| self.root_path.join(path))
+    }
+
+    /// # Returns
+    /// Options to import the file with.
+    /// Files without options use the csv dialect of the settings.
+    pub fn import_options(&self, file: &ResourceId) -> lib::data::ImportOptions {
+        self.import_options
+            .with_untracked(|options| options.get(file).cloned())
+            .unwrap_or_else(|| lib::data::ImportOptions {
+                dialect: self
+                    .settings
+                    .with_untracked(|settings| settings.csv.clone()),
+                ..Default::default()
+            })
+    }
 }
 
 #[derive(Clone, Copy, derive_more::Deref)]
@@ -195,36 +493,32 @@ impl Datasets {
     pub fn get_variable_cells_by_domain(
         &self,
         domain: &FormulaDomain,
-    ) -> Vec<RwSignal<VariableCellValue>> {
+    ) -> Result<Vec<RwSignal<VariableCellValue>>, formula::error::SyncFormula> {
         match domain {
             FormulaDomain::CsvCell { dataset, cell } => {
-                let Some(dataset) = self
+                let dataset = self
                     .0
                     .read_untracked()
                     .iter()
                     .find(|ds| ds.id() == dataset)
                     .cloned()
-                else {
-                    return vec![];
-                };
-
-                let cells = match dataset {
-                    Dataset::Csv(csv) => Some(csv.sheet.cells.read_only()),
+                    .ok_or(formula::error::SyncFormula::DatasetNotFound)?;
 
-                    Dataset::Workbook(workbook) => unreachable!(),
-                };
-                let Some(cells) = cells else {
-                    return vec![];
+                let Dataset::Csv(csv) = dataset else {
+                    return Err(formula::error::SyncFormula::DomainMismatch);
                 };
 
-                cells
+                let cells = csv
+                    .sheet
+                    .cells
                     .read_untracked()
                     .get(cell)
                     .map(|cell| match cell {
                         CellValue::Fixed(_) => vec![],
                         CellValue::Variable(value) => vec![value.clone()],
                     })
-                    .unwrap_or(vec![])
+                    .unwrap_or(vec![]);
+                Ok(cells)
             }
 
             FormulaDomain::WorkbookCell {
@@ -232,36 +526,34 @@ impl Datasets {
                 sheet,
                 cell,
             } => {
-                let Some(dataset) = self
+                let dataset = self
                     .0
                     .read_untracked()
                     .iter()
                     .find(|ds| ds.id() == dataset)
                     .cloned()
-                else {
-                    return vec![];
-                };
+                    .ok_or(formula::error::SyncFormula::DatasetNotFound)?;
 
-                let cells = match dataset {
-                    Dataset::Csv(csv) => unreachable!(),
-                    Dataset::Workbook(workbook) => workbook
-                        .sheets
-                        .read_untracked()
-                        .iter()
-                        .find_map(|s| (s.id() == sheet).then_some(s.cells.read_only())),
-                };
-                let Some(cells) = cells else {
-                    return vec![];
+                let Dataset::Workbook(workbook) = dataset else {
+                    return Err(formula::error::SyncFormula::DomainMismatch);
                 };
 
-                cells
+                let cells = workbook
+                    .sheets
+                    .read_untracked()
+                    .iter()
+                    .find_map(|s| (s.id() == sheet).then_some(s.cells.read_only()))
+                    .ok_or(formula::error::SyncFormula::SheetNotFound)?;
+
+                let cells = cells
                     .read_untracked()
                     .get(cell)
                     .map(|cell| match cell {
                         CellValue::Fixed(_) => vec![],
                         CellValue::Variable(value) => vec![value.clone()],
                     })
-                    .unwrap_or(vec![])
+                    .unwrap_or(vec![]);
+                Ok(cells)
             }
         }
     }
@@ -274,10 +566,21 @@ pub enum Dataset {
 }
 
 impl Dataset {
-    pub fn new(file: ResourceId, dataset: lib::data::Dataset) -> Self {
+    pub fn new(file: ResourceId, loaded: lib::data::Loaded) -> Self {
+        let lib::data::Loaded {
+            dataset,
+            read_only,
+            truncated,
+        } = loaded;
         match dataset {
-            lib::data::Dataset::Csv(csv) => Self::Csv(Csv::new(file, csv)),
-            lib::data::Dataset::Workbook(workbook) => Self::Workbook(Workbook::new(file, workbook)),
+            lib::data::Dataset::Csv(csv) => {
+                let mut csv = Csv::new(file, csv, read_only);
+                csv.truncated = truncated;
+                Self::Csv(csv)
+            }
+            lib::data::Dataset::Workbook(workbook) => {
+                Self::Workbook(Workbook::new(file, workbook, read_only))
+            }
         }
     }
 
@@ -295,6 +598,202 @@ impl Dataset {
     pub fn is_workbook(&self) -> bool {
         matches!(self, Self::Workbook(_))
     }
+
+    /// Whether the dataset's file can not be written to.
+    pub fn read_only(&self) -> bool {
+        match self {
+            Self::Csv(csv) => csv.read_only(),
+            Self::Workbook(workbook) => workbook.read_only(),
+        }
+    }
+
+    /// Sheet values are read from.
+    /// For workbooks this is the active sheet.
+    fn value_sheet(&self) -> Option<Spreadsheet> {
+        match self {
+            Self::Csv(csv) => Some(csv.sheet().clone()),
+            Self::Workbook(workbook) => workbook
+                .sheets
+                .read_untracked()
+                .get(workbook.active_sheet.get_untracked())
+                .cloned(),
+        }
+    }
+
+    /// # Returns
+    /// Column structure of the fixed data.
+    pub fn shape(&self) -> lib::template::Shape {
+        match self {
+            Self::Csv(csv) => lib::template::Shape::Csv {
+                cols: csv.sheet().size_fixed().1,
+            },
+            Self::Workbook(workbook) => lib::template::Shape::Workbook {
+                cols: workbook
+                    .sheets
+                    .read_untracked()
+                    .iter()
+                    .map(|sheet| sheet.size_fixed().1)
+                    .collect(),
+            },
+        }
+    }
+
+    /// # Returns
+    /// `(<rows>, <columns>)` of the fixed data of each sheet.
+    pub fn sizes(&self) -> Vec<(core::data::IndexType, core::data::IndexType)> {
+        match self {
+            Self::Csv(csv) => vec![csv.sheet().size_fixed()],
+            Self::Workbook(workbook) => workbook
+                .sheets
+                .read_untracked()
+                .iter()
+                .map(|sheet| sheet.size_fixed())
+                .collect(),
+        }
+    }
+
+    /// # Returns
+    /// Number of columns containing data.
+    pub fn cols(&self) -> core::data::IndexType {
+        self.value_sheet()
+            .map(|sheet| sheet.size.get_untracked().1)
+            .unwrap_or(0)
+    }
+
+    /// # Returns
+    /// Current values of a column, including formula results.
+    /// Cells with errors are empty.
+    pub fn column_values(&self, col: core::data::IndexType) -> Vec<core::expr::Value> {
+        let Some(sheet) = self.value_sheet() else {
+            return vec![];
+        };
+
+        let rows = sheet.size.get_untracked().0;
+        sheet.cells.with_untracked(|cells| {
+            (0..rows)
+                .map(|row| {
+                    let idx = core::data::CellIndex::new(row, col);
+                    match cells.get(&idx) {
+                        None => core::expr::Value::Empty,
+                        Some(CellValue::Fixed(value)) => value.clone(),
+                        Some(CellValue::Variable(value)) => match value.get_untracked() {
+                            VariableCellValue::Formula(Ok(value), _) => value,
+                            VariableCellValue::Formula(Err(_), _) | VariableCellValue::Empty => {
+                                core::expr::Value::Empty
+                            }
+                        },
+                    }
+                })
+                .collect()
+        })
+    }
+
+    /// # Returns
+    /// Current values of the cells in a rectangle, by row, including formula results.
+    /// Cells with errors are `None`.
+    /// The rectangle is clipped to the sheet.
+    /// Reads are tracked, so callers rerun when formulas are recalculated.
+    pub fn range_values(
+        &self,
+        start: (core::data::IndexType, core::data::IndexType),
+        end: (core::data::IndexType, core::data::IndexType),
+    ) -> Vec<Vec<Option<core::expr::Value>>> {
+        if let Self::Workbook(workbook) = self {
+            workbook.active_sheet.track();
+        }
+        let Some(sheet) = self.value_sheet() else {
+            return vec![];
+        };
+
+        let (rows, cols) = sheet.size.get();
+        if rows == 0 || cols == 0 || start.0 >= rows || start.1 >= cols {
+            return vec![];
+        }
+        let end = (end.0.min(rows - 1), end.1.min(cols - 1));
+
+        sheet.cells.with(|cells| {
+            (start.0..=end.0)
+                .map(|row| {
+                    (start.1..=end.1)
+                        .map(|col| {
+                            let idx = core::data::CellIndex::new(row, col);
+                            match cells.get(&idx) {
+                                None => Some(core::expr::Value::Empty),
+                                Some(CellValue::Fixed(value)) => Some(value.clone()),
+                                Some(CellValue::Variable(value)) => match value.get() {
+                                    VariableCellValue::Formula(Ok(value), _) => Some(value),
+                                    VariableCellValue::Formula(Err(_), _) => None,
+                                    VariableCellValue::Empty => Some(core::expr::Value::Empty),
+                                },
+                            }
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+    }
+
+    /// # Returns
+    /// Summary statistics of a column, including formula results.
+    /// Formulas with errors are skipped.
+    pub fn column_stats(&self, col: core::data::IndexType, bins: usize) -> lib::data::ColumnStats {
+        let Some(sheet) = self.value_sheet() else {
+            return lib::data::ColumnStats::default();
+        };
+
+        let rows = sheet.size.get_untracked().0;
+        sheet.cells.with_untracked(|cells| {
+            let values = (0..rows)
+                .map(|row| {
+                    let idx = core::data::CellIndex::new(row, col);
+                    match cells.get(&idx) {
+                        None => Some(core::expr::Value::Empty),
+                        Some(CellValue::Fixed(value)) => Some(value.clone()),
+                        Some(CellValue::Variable(value)) => match value.get_untracked() {
+                            VariableCellValue::Formula(Ok(value), _) => Some(value),
+                            VariableCellValue::Formula(Err(_), _) => None,
+                            VariableCellValue::Empty => Some(core::expr::Value::Empty),
+                        },
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            lib::data::ColumnStats::compute(values.iter().map(|value| value.as_ref()), bins)
+        })
+    }
+
+    /// # Returns
+    /// Records of the sheet's current values, as they would be laid out in a file,
+    /// or `None` if the sheet does not exist.
+    /// See [`Spreadsheet::evaluated_records`].
+    pub fn evaluated_records(&self, sheet: usize) -> Option<Vec<Vec<String>>> {
+        match self {
+            Self::Csv(csv) => (sheet == 0).then(|| {
+                csv.inner
+                    .restore_metadata_rows(csv.sheet().evaluated_records())
+            }),
+            Self::Workbook(workbook) => workbook
+                .sheets
+                .read_untracked()
+                .get(sheet)
+                .map(|sheet| sheet.evaluated_records()),
+        }
+    }
+
+    /// # Returns
+    /// Current values of each sheet, including formula results.
+    /// Cells with errors or without a value are omitted.
+    pub fn values(&self) -> Vec<lib::data::CellMap> {
+        match self {
+            Self::Csv(csv) => vec![csv.sheet().values()],
+            Self::Workbook(workbook) => workbook
+                .sheets
+                .read_untracked()
+                .iter()
+                .map(|sheet| sheet.values())
+                .collect(),
+        }
+    }
 }
 
 impl FileResource for Dataset {
@@ -324,15 +823,20 @@ pub struct Csv {
     file: ResourceId,
     inner: lib::data::Csv,
     sheet: Spreadsheet,
+    read_only: bool,
+    /// Only the first rows of the file were loaded.
+    truncated: bool,
 }
 
 impl Csv {
-    pub fn new(file: ResourceId, csv: lib::data::Csv) -> Self {
+    pub fn new(file: ResourceId, csv: lib::data::Csv, read_only: bool) -> Self {
         let cells = csv.sheet.cells().clone();
         Self {
             file,
             inner: csv,
             sheet: Spreadsheet::with_fixed_values("data", cells),
+            read_only,
+            truncated: false,
         }
     }
 
@@ -343,6 +847,26 @@ impl Csv {
     pub fn sheet(&self) -> &Spreadsheet {
         &self.sheet
     }
+
+    /// Options the csv was imported with.
+    pub fn options(&self) -> &lib::data::ImportOptions {
+        &self.inner.options
+    }
+
+    /// Metadata of each column.
+    pub fn columns(&self) -> &lib::data::ColumnMetadataMap {
+        &self.inner.columns
+    }
+
+    /// Whether the file can not be written to.
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Whether only the first rows of the file were loaded.
+    pub fn truncated(&self) -> bool {
+        self.truncated
+    }
 }
 
 impl FileResource for Csv {
@@ -358,6 +882,14 @@ impl core::expr::Context for &Csv {
         cell_ref: &core::data::CellRef,
         origin: &core::data::CellPath,
     ) -> Result<core::expr::Value, core::expr::ContextError> {
+        let sheet = self
+            .sheet
+            .name
+            .with_untracked(|name| cell_ref.sheet.resolve(origin.sheet, [name]));
+        if sheet.is_none() {
+            return Err(core::expr::ContextError::CellRefDoesNotExist);
+        }
+
         let idx = core::data::CellIndex::new(cell_ref.row, cell_ref.col);
         match self
             .sheet
@@ -373,7 +905,7 @@ impl core::expr::Context for &Csv {
                 }
                 CellValue::Variable(data) => match data.get_untracked() {
                     VariableCellValue::Empty => return Ok(core::expr::Value::Empty),
-                    VariableCellValue::Formula(data) => match data {
+                    VariableCellValue::Formula(data, _) => match data {
                         Err(err) => return Err(core::expr::ContextError::CellRefValueError(err)),
                         Ok(data) => {
                             return Ok(data);
@@ -392,10 +924,11 @@ pub struct Workbook {
     inner: RwSignal<lib::data::Workbook>,
     pub sheets: RwSignal<Vec<Spreadsheet>>,
     pub active_sheet: RwSignal<usize>,
+    read_only: bool,
 }
 
 impl Workbook {
-    pub fn new(file: ResourceId, workbook: lib::data::Workbook) -> Self {
+    pub fn new(file: ResourceId, workbook: lib::data::Workbook, read_only: bool) -> Self {
         let sheets = workbook
             .sheets()
             .iter()
@@ -407,6 +940,7 @@ impl Workbook {
             inner: RwSignal::new(workbook),
             sheets: RwSignal::new(sheets),
             active_sheet: RwSignal::new(0),
+            read_only,
         }
     }
 
@@ -417,6 +951,11 @@ impl Workbook {
     pub fn id(&self) -> &ResourceId {
         &self.file
     }
+
+    /// Whether the file can not be written to.
+    pub fn read_only(&self) -> bool {
+        self.read_only
+    }
 }
 
 impl FileResource for Workbook {
@@ -432,25 +971,14 @@ impl core::expr::Context for &Workbook {
         cell_ref: &core::data::CellRef,
         origin: &core::data::CellPath,
     ) -> Result<core::expr::Value, core::expr::ContextError> {
-        let sheet = match cell_ref.sheet {
-            core::data::SheetRef::Relative => self
-                .sheets
-                .read_untracked()
-                .get(origin.sheet as usize)
-                .cloned(),
-            core::data::SheetRef::Absolute(ref sheet) => match sheet {
-                core::data::SheetIndex::Label(label) => self
-                    .sheets
-                    .read_untracked()
-                    .iter()
-                    .find(|sheet| sheet.name.with_untracked(|name| name == label))
-                    .cloned(),
-
-                core::data::SheetIndex::Index(idx) => {
-                    self.sheets.read_untracked().get(*idx as usize).cloned()
-                }
-            },
-        };
+        let sheet = self.sheets.with_untracked(|sheets| {
+            let labels = sheets
+                .iter()
+                .map(|sheet| sheet.name.get_untracked())
+                .collect::<Vec<_>>();
+            let idx = cell_ref.sheet.resolve(origin.sheet, labels)?;
+            sheets.get(idx as usize).cloned()
+        });
         let Some(sheet) = sheet else {
             return Err(core::expr::ContextError::CellRefDoesNotExist);
         };
@@ -466,7 +994,7 @@ impl core::expr::Context for &Workbook {
                 }
                 CellValue::Variable(data) => match data.get_untracked() {
                     VariableCellValue::Empty => return Ok(core::expr::Value::Empty),
-                    VariableCellValue::Formula(data) => match data {
+                    VariableCellValue::Formula(data, _) => match data {
                         Err(err) => return Err(core::expr::ContextError::CellRefValueError(err)),
                         Ok(data) => {
                             return Ok(data);
@@ -496,15 +1024,16 @@ impl CellValue {
         Self::Variable(RwSignal::new(VariableCellValue::Empty))
     }
 
-    pub fn formula(value: FormulaCellValue) -> Self {
-        Self::Variable(RwSignal::new(VariableCellValue::Formula(value)))
+    pub fn formula(value: FormulaCellValue, provenance: lib::audit::Provenance) -> Self {
+        Self::Variable(RwSignal::new(VariableCellValue::Formula(value, provenance)))
     }
 }
 
 #[derive(Clone, derive_more::From)]
 pub enum VariableCellValue {
     Empty,
-    Formula(FormulaCellValue),
+    /// Value computed by a formula, and how it was computed.
+    Formula(FormulaCellValue, lib::audit::Provenance),
 }
 
 impl VariableCellValue {
@@ -515,7 +1044,14 @@ impl VariableCellValue {
     pub fn unwrap(self) -> FormulaCellValue {
         match self {
             Self::Empty => panic!("called `VariableCellValue::unwrap()` on an `Empty` value"),
-            Self::Formula(formula) => formula,
+            Self::Formula(formula, _) => formula,
+        }
+    }
+
+    pub fn provenance(&self) -> Option<&lib::audit::Provenance> {
+        match self {
+            Self::Empty => None,
+            Self::Formula(_, provenance) => Some(provenance),
         }
     }
 }
@@ -596,6 +1132,57 @@ impl Spreadsheet {
     pub fn size_fixed(&self) -> (core::data::IndexType, core::data::IndexType) {
         self.size_fixed
     }
+
+    /// # Returns
+    /// Current values of the sheet, including formula results.
+    /// Cells with errors or without a value are omitted.
+    pub fn values(&self) -> lib::data::CellMap {
+        self.cells.with_untracked(|cells| {
+            cells
+                .iter()
+                .filter_map(|(idx, cell)| {
+                    let value = match cell {
+                        CellValue::Fixed(value) => value.clone(),
+                        CellValue::Variable(value) => match value.get_untracked() {
+                            VariableCellValue::Formula(Ok(value), _) => value,
+                            VariableCellValue::Formula(Err(_), _) | VariableCellValue::Empty => {
+                                return None;
+                            }
+                        },
+                    };
+
+                    Some((idx.clone(), value))
+                })
+                .collect()
+        })
+    }
+
+    /// # Returns
+    /// Records of the sheet's current values, including formula results.
+    /// Errors are written as their [code](core::expr::Error::code).
+    pub fn evaluated_records(&self) -> Vec<Vec<String>> {
+        self.cells.with_untracked(|cells| {
+            let (rows, cols) = cells.keys().fold((0, 0), |(rows, cols), idx| {
+                (rows.max(idx.row() + 1), cols.max(idx.col() + 1))
+            });
+
+            let mut records = vec![vec![String::new(); cols as usize]; rows as usize];
+            for (idx, cell) in cells {
+                let value = match cell {
+                    CellValue::Fixed(value) => value.to_string(),
+                    CellValue::Variable(value) => match value.get_untracked() {
+                        VariableCellValue::Formula(Ok(value), _) => value.to_string(),
+                        VariableCellValue::Formula(Err(err), _) => err.code().to_string(),
+                        VariableCellValue::Empty => continue,
+                    },
+                };
+
+                records[idx.row() as usize][idx.col() as usize] = value;
+            }
+
+            records
+        })
+    }
 }
 
 #[derive(Clone, Copy, derive_more::Deref)]
@@ -629,6 +1216,9 @@ pub struct Formula {
     id: ResourceId,
     pub domain: RwSignal<FormulaDomain>,
     pub value: RwSignal<String>,
+
+    /// Previous values of the formula, oldest first.
+    pub history: RwSignal<Vec<FormulaRevision>>,
 }
 
 impl Formula {
@@ -637,6 +1227,269 @@ impl Formula {
             id: ResourceId::new(),
             domain: RwSignal::new(domain),
             value: RwSignal::new("".to_string()),
+            history: RwSignal::new(vec![]),
+        }
+    }
+
+    pub fn id(&self) -> &ResourceId {
+        &self.id
+    }
+
+    /// Set the formula's value, recording its current value in its history.
+    /// Nothing is recorded if the value is unchanged or was empty.
+    pub fn set_value(&self, value: String) {
+        let previous = self.value.get_untracked();
+        if previous == value {
+            return;
+        }
+
+        if !previous.trim().is_empty() {
+            self.history.update(|history| {
+                history.push(FormulaRevision::new(previous));
+                if history.len() > FORMULA_HISTORY_LIMIT {
+                    history.remove(0);
+                }
+            });
+        }
+        self.value.set(value);
+    }
+}
+
+/// Maximum number of previous values kept for a formula.
+const FORMULA_HISTORY_LIMIT: usize = 20;
+
+/// Previous value of a formula.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormulaRevision {
+    pub value: String,
+
+    /// When the value was replaced, in milliseconds since the Unix epoch.
+    pub timestamp: f64,
+}
+
+impl FormulaRevision {
+    pub fn new(value: String) -> Self {
+        Self {
+            value,
+            timestamp: js_sys::Date::now(),
+        }
+    }
+}
+
+/// Re-run the workspace when one of its datasets changes.
+#[derive(Clone, Copy)]
+pub struct AutoRun {
+    pub enabled: RwSignal<bool>,
+
+    /// Files written by runs, with the time they were written.
+    written: StoredValue<HashMap<PathBuf, f64>>,
+}
+
+impl AutoRun {
+    pub fn new() -> Self {
+        Self {
+            enabled: RwSignal::new(false),
+            written: StoredValue::new(HashMap::new()),
+        }
+    }
+
+    /// Record that a run wrote to files.
+    pub fn set_written<'a>(&self, paths: impl IntoIterator<Item = &'a PathBuf>) {
+        let now = js_sys::Date::now();
+        self.written.update_value(|written| {
+            written.retain(|_, time| now - *time < AUTO_RUN_WRITE_GRACE_MS);
+            for path in paths {
+                written.insert(path.clone(), now);
+            }
+        });
+    }
+
+    /// # Returns
+    /// If a run recently wrote to the file,
+    /// in which case changes to it should not trigger another run.
+    pub fn was_written(&self, path: impl AsRef<Path>) -> bool {
+        let now = js_sys::Date::now();
+        self.written.with_value(|written| {
+            written
+                .get(path.as_ref())
+                .is_some_and(|time| now - *time < AUTO_RUN_WRITE_GRACE_MS)
+        })
+    }
+}
+
+/// File created from columns of datasets when the workspace is run.
+#[derive(Clone)]
+pub struct OutputFile {
+    id: ResourceId,
+    /// File name, relative to the project root.
+    pub name: RwSignal<String>,
+    pub format: RwSignal<lib::formula::OutputFormat>,
+    pub columns: RwSignal<Vec<OutputColumn>>,
+}
+
+impl OutputFile {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            id: ResourceId::new(),
+            name: RwSignal::new(name.into()),
+            format: RwSignal::new(lib::formula::OutputFormat::default()),
+            columns: RwSignal::new(vec![OutputColumn::new()]),
+        }
+    }
+
+    pub fn id(&self) -> &ResourceId {
+        &self.id
+    }
+
+    /// # Returns
+    /// File name with the format's extension.
+    pub fn file_name(&self) -> String {
+        file_name_with_extension(self.name.get_untracked(), self.format.get_untracked())
+    }
+}
+
+/// Rows of a dataset grouped and summarized, written to a file when the workspace is run.
+#[derive(Clone)]
+pub struct PivotTable {
+    id: ResourceId,
+    /// File name, relative to the project root.
+    pub name: RwSignal<String>,
+    pub format: RwSignal<lib::formula::OutputFormat>,
+    /// Dataset summarized.
+    /// Only the first sheet of workbooks is used.
+    pub dataset: RwSignal<Option<ResourceId>>,
+    pub pivot: RwSignal<lib::pivot::Pivot>,
+}
+
+impl PivotTable {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            id: ResourceId::new(),
+            name: RwSignal::new(name.into()),
+            format: RwSignal::new(lib::formula::OutputFormat::default()),
+            dataset: RwSignal::new(None),
+            pivot: RwSignal::new(lib::pivot::Pivot {
+                group_by: vec![0],
+                value: 1,
+                aggregate: lib::pivot::Aggregate::default(),
+                header: true,
+            }),
+        }
+    }
+
+    pub fn id(&self) -> &ResourceId {
+        &self.id
+    }
+
+    /// # Returns
+    /// File name with the format's extension.
+    pub fn file_name(&self) -> String {
+        file_name_with_extension(self.name.get_untracked(), self.format.get_untracked())
+    }
+}
+
+/// Two datasets joined on key columns, written to a file when the workspace is run.
+/// Only the first sheet of workbooks is used.
+#[derive(Clone)]
+pub struct JoinTable {
+    id: ResourceId,
+    /// File name, relative to the project root.
+    pub name: RwSignal<String>,
+    pub format: RwSignal<lib::formula::OutputFormat>,
+    pub left: ResourceId,
+    pub right: ResourceId,
+    pub join: lib::data::Join,
+}
+
+impl JoinTable {
+    pub fn new(
+        name: impl Into<String>,
+        left: ResourceId,
+        right: ResourceId,
+        join: lib::data::Join,
+    ) -> Self {
+        Self {
+            id: ResourceId::new(),
+            name: RwSignal::new(name.into()),
+            format: RwSignal::new(lib::formula::OutputFormat::default()),
+            left,
+            right,
+            join,
+        }
+    }
+
+    pub fn id(&self) -> &ResourceId {
+        &self.id
+    }
+
+    /// # Returns
+    /// File name with the format's extension.
+    pub fn file_name(&self) -> String {
+        file_name_with_extension(self.name.get_untracked(), self.format.get_untracked())
+    }
+}
+
+/// Subset of a dataset's rows, written to a file when the workspace is run.
+/// Only the first sheet of workbooks is used.
+#[derive(Clone)]
+pub struct SubsetTable {
+    id: ResourceId,
+    /// File name, relative to the project root.
+    pub name: RwSignal<String>,
+    pub format: RwSignal<lib::formula::OutputFormat>,
+    pub dataset: ResourceId,
+    pub subset: lib::data::Subset,
+}
+
+impl SubsetTable {
+    pub fn new(name: impl Into<String>, dataset: ResourceId, subset: lib::data::Subset) -> Self {
+        Self {
+            id: ResourceId::new(),
+            name: RwSignal::new(name.into()),
+            format: RwSignal::new(lib::formula::OutputFormat::default()),
+            dataset,
+            subset,
+        }
+    }
+
+    pub fn id(&self) -> &ResourceId {
+        &self.id
+    }
+
+    /// # Returns
+    /// File name with the format's extension.
+    pub fn file_name(&self) -> String {
+        file_name_with_extension(self.name.get_untracked(), self.format.get_untracked())
+    }
+}
+
+/// # Returns
+/// `name` with the format's extension, if it does not already have it.
+fn file_name_with_extension(name: String, format: lib::formula::OutputFormat) -> String {
+    let extension = format.extension();
+    if Path::new(&name)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+    {
+        name
+    } else {
+        format!("{name}.{extension}")
+    }
+}
+
+#[derive(Clone)]
+pub struct OutputColumn {
+    id: ResourceId,
+    pub header: RwSignal<String>,
+    pub source: RwSignal<Option<ColumnSource>>,
+}
+
+impl OutputColumn {
+    pub fn new() -> Self {
+        Self {
+            id: ResourceId::new(),
+            header: RwSignal::new(String::new()),
+            source: RwSignal::new(None),
         }
     }
 
@@ -645,6 +1498,13 @@ impl Formula {
     }
 }
 
+/// Column of a dataset used as an output column.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ColumnSource {
+    pub dataset: ResourceId,
+    pub col: core::data::IndexType,
+}
+
 #[derive(Clone, PartialEq)]
 pub enum FormulaDomain {
     /// A single cell in a csv.
@@ -662,6 +1522,14 @@ pub enum FormulaDomain {
 }
 
 impl FormulaDomain {
+    /// # Returns
+    /// Dataset the domain is in.
+    pub fn dataset(&self) -> &ResourceId {
+        match self {
+            Self::CsvCell { dataset, .. } | Self::WorkbookCell { dataset, .. } => dataset,
+        }
+    }
+
     /// Test if the domain intersects with the given domain.
     pub fn intersects(&self, domain: &Self) -> bool {
         match (self, domain) {
@@ -687,12 +1555,26 @@ impl FormulaDomain {
 pub struct File {
     id: ResourceId,
     pub name: RwSignal<OsString>,
+
+    /// Size in bytes, if known.
+    size: Option<u64>,
+
+    /// The file is a symbolic link.
+    is_link: bool,
 }
 
 impl File {
     pub fn id(&self) -> &ResourceId {
         &self.id
     }
+
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    pub fn is_link(&self) -> bool {
+        self.is_link
+    }
 }
 
 impl From<OsString> for File {
@@ -700,6 +1582,8 @@ impl From<OsString> for File {
         Self {
             id: ResourceId::new(),
             name: RwSignal::new(value),
+            size: None,
+            is_link: false,
         }
     }
 }
@@ -736,27 +1620,62 @@ pub struct Directory {
     id: ResourceId,
     pub name: RwSignal<OsString>,
     pub files: FileList,
+    kind: lib::fs::DirectoryKind,
+
+    /// The directory's contents have not been loaded yet.
+    pub pending: RwSignal<bool>,
 }
 
 impl Directory {
     pub fn id(&self) -> &ResourceId {
         &self.id
     }
+
+    pub fn kind(&self) -> &lib::fs::DirectoryKind {
+        &self.kind
+    }
 }
 
 impl From<lib::fs::Directory> for Directory {
     fn from(value: lib::fs::Directory) -> Self {
-        let lib::fs::Directory { name, files } = value;
-        let files = files.into_iter().map(|file| file.into()).collect();
+        let lib::fs::Directory {
+            name,
+            files,
+            kind,
+            metadata,
+            pending,
+        } = value;
+        let files = files
+            .into_iter()
+            .map(|file| {
+                let metadata = metadata.get(file.to_string_lossy().as_ref()).copied();
+                let mut file = File::from(file);
+                file.size = metadata.map(|metadata| metadata.size);
+                file.is_link = metadata.is_some_and(|metadata| metadata.is_link);
+                file
+            })
+            .collect();
 
         Self {
             id: ResourceId::new(),
             name: RwSignal::new(name),
             files: FileList::with_files(files),
+            kind,
+            pending: RwSignal::new(pending),
         }
     }
 }
 
+/// Entry of the flat file index of a [`DirectoryTree`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct IndexedFile {
+    pub id: ResourceId,
+    /// Path relative to the directory tree root.
+    pub path: PathBuf,
+    /// Directories containing the file, ordered from the root.
+    pub ancestors: Vec<ResourceId>,
+}
+
 #[derive(Clone)]
 pub struct DirectoryTree {
     directories: RwSignal<Vec<Directory>>,
@@ -834,6 +1753,21 @@ impl DirectoryTree {
             })
     }
 
+    /// # Arguments
+    /// + `path`: Path relative to the directory tree root.
+    pub fn get_file_by_path(&self, path: impl AsRef<Path>) -> Option<File> {
+        let path = path.as_ref();
+        self.directories
+            .read_untracked()
+            .iter()
+            .flat_map(|directory| directory.files.get_untracked())
+            .find(|file| {
+                self.get_file_path(file.id())
+                    .map(|file_path| file_path == path)
+                    .unwrap_or(false)
+            })
+    }
+
     /// Gets the current path to the file relative to the directory tree root.
     pub fn get_file_path(&self, id: &ResourceId) -> Option<PathBuf> {
         let (parent_idx, filename) =
@@ -913,10 +1847,275 @@ impl DirectoryTree {
             .read_untracked()
             .iter()
             .enumerate()
-            .filter_map(|(child, c_parent)| (*c_parent == parent).then_some(child))
+            .filter_map(|(child, c_parent)| (*c_parent == parent).then_some(child + 1))
             .collect())
     }
 
+    /// # Returns
+    /// List of descendants starting with `root`.
+    fn descendants_idx(&self, root: usize) -> Vec<usize> {
+        let mut descendants = vec![root];
+        let mut idx = 0;
+        while let Some(parent) = descendants.get(idx).copied() {
+            descendants.extend(self.children_idx(parent).unwrap_or_default());
+            idx += 1;
+        }
+
+        descendants
+    }
+
+    pub fn get_directory_by_id(&self, id: &ResourceId) -> Option<Directory> {
+        self.directories
+            .read_untracked()
+            .iter()
+            .find(|directory| directory.id() == id)
+            .cloned()
+    }
+
+    /// Gets the current path to the directory relative to the directory tree root.
+    /// The root's path is empty.
+    pub fn get_directory_path(&self, id: &ResourceId) -> Option<PathBuf> {
+        let idx = self.index(id)?;
+        let ancestors = self.ancestors_idx(idx).ok()?;
+        let path = self.directories.with_untracked(move |directories| {
+            ancestors
+                .into_iter()
+                .rev()
+                .skip(1)
+                .map(|idx| directories[idx].name.get_untracked())
+                .collect::<PathBuf>()
+        });
+
+        Some(path)
+    }
+
+    /// Insert a new directory.
+    ///
+    /// # Returns
+    /// Id of the new directory.
+    pub fn insert_directory(
+        &self,
+        parent: &ResourceId,
+        name: OsString,
+    ) -> Result<ResourceId, lib::fs::error::NodeDoesNotExist> {
+        let parent = self.index(parent).ok_or(lib::fs::error::NodeDoesNotExist)?;
+
+        let directory: Directory = lib::fs::Directory::new(name).into();
+        let id = directory.id().clone();
+        self.directories.write().push(directory);
+        self.parents.write().push(parent);
+        Ok(id)
+    }
+
+    /// Fill a pending directory with its loaded contents.
+    ///
+    /// # Arguments
+    /// + `graph`: Tree rooted at the directory.
+    pub fn expand_directory(
+        &self,
+        id: &ResourceId,
+        graph: lib::fs::DirectoryTree,
+    ) -> Result<(), lib::fs::error::NodeDoesNotExist> {
+        let root = self.index(id).ok_or(lib::fs::error::NodeDoesNotExist)?;
+        let directory = self.get_idx(root)?;
+
+        let mut graph_directories = graph
+            .directories()
+            .iter()
+            .map(|dir| Directory::from(dir.clone()));
+        let graph_root = graph_directories.next().expect("graph has a root");
+        let offset = self.directories.read_untracked().len();
+        let graph_directories = graph_directories.collect::<Vec<_>>();
+        let graph_parents = graph.parents().iter().map(|parent| {
+            if *parent == lib::fs::DirectoryTree::ROOT {
+                root
+            } else {
+                offset + parent - 1
+            }
+        });
+
+        self.directories.write().extend(graph_directories);
+        self.parents.write().extend(graph_parents);
+        directory.files.set(graph_root.files.get_untracked());
+        directory.pending.set(false);
+        Ok(())
+    }
+
+    /// Insert a new file.
+    ///
+    /// # Returns
+    /// Id of the new file.
+    pub fn insert_file(
+        &self,
+        parent: &ResourceId,
+        name: OsString,
+    ) -> Result<ResourceId, lib::fs::error::NodeDoesNotExist> {
+        let parent = self
+            .get_directory_by_id(parent)
+            .ok_or(lib::fs::error::NodeDoesNotExist)?;
+
+        let file = File::from(name);
+        let id = file.id().clone();
+        parent.files.write().push(file);
+        Ok(id)
+    }
+
+    /// Remove a file.
+    ///
+    /// # Returns
+    /// The removed file.
+    pub fn remove_file(&self, id: &ResourceId) -> Option<File> {
+        self.directories
+            .read_untracked()
+            .iter()
+            .find_map(|directory| {
+                let idx = directory
+                    .files
+                    .read_untracked()
+                    .iter()
+                    .position(|file| file.id() == id)?;
+
+                Some(directory.files.write().remove(idx))
+            })
+    }
+
+    /// Remove a directory and its descendants.
+    ///
+    /// # Returns
+    /// Ids of the files contained in the removed directories.
+    pub fn remove_directory(
+        &self,
+        id: &ResourceId,
+    ) -> Result<Vec<ResourceId>, lib::fs::error::Remove> {
+        let root = self.index(id).ok_or(lib::fs::error::Remove::InvalidRoot)?;
+        if root == Self::ROOT {
+            return Err(lib::fs::error::Remove::GraphRoot);
+        }
+
+        let removed = self.descendants_idx(root);
+        let directories = self.directories.get_untracked();
+        let parents = self.parents.get_untracked();
+        let mut index_map = Vec::with_capacity(directories.len());
+        let mut next = 0;
+        for idx in 0..directories.len() {
+            if removed.contains(&idx) {
+                index_map.push(None);
+            } else {
+                index_map.push(Some(next));
+                next += 1;
+            }
+        }
+
+        let files = removed
+            .iter()
+            .flat_map(|idx| {
+                directories[*idx]
+                    .files
+                    .read_untracked()
+                    .iter()
+                    .map(|file| file.id().clone())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let parents = parents
+            .into_iter()
+            .enumerate()
+            .filter(|(child, _)| !removed.contains(&(child + 1)))
+            .map(|(_, parent)| index_map[parent].expect("parent should not be removed"))
+            .collect();
+
+        let directories = directories
+            .into_iter()
+            .enumerate()
+            .filter_map(|(idx, directory)| (!removed.contains(&idx)).then_some(directory))
+            .collect();
+
+        self.directories.set(directories);
+        self.parents.set(parents);
+        Ok(files)
+    }
+
+    /// Move a file to a different directory.
+    pub fn move_file(
+        &self,
+        id: &ResourceId,
+        parent: &ResourceId,
+    ) -> Result<(), lib::fs::error::NodeDoesNotExist> {
+        let parent = self
+            .get_directory_by_id(parent)
+            .ok_or(lib::fs::error::NodeDoesNotExist)?;
+        let file = self
+            .remove_file(id)
+            .ok_or(lib::fs::error::NodeDoesNotExist)?;
+
+        parent.files.write().push(file);
+        Ok(())
+    }
+
+    /// Move a directory to a different parent.
+    pub fn move_directory(
+        &self,
+        id: &ResourceId,
+        parent: &ResourceId,
+    ) -> Result<(), lib::fs::error::Shift> {
+        let root = self.index(id).ok_or(lib::fs::error::Shift::InvalidRoot)?;
+        let parent = self
+            .index(parent)
+            .ok_or(lib::fs::error::Shift::InvalidParent)?;
+        if root == Self::ROOT || self.descendants_idx(root).contains(&parent) {
+            return Err(lib::fs::error::Shift::CanNotShiftToDescendant);
+        }
+
+        self.parents.update(|parents| parents[root - 1] = parent);
+        Ok(())
+    }
+
+    /// Flat index of every file in the tree.
+    /// Updates as directories and files are added, removed, renamed, or moved.
+    pub fn file_index(&self) -> Memo<Vec<IndexedFile>> {
+        let directories = self.directories.read_only();
+        let parents = self.parents.read_only();
+        Memo::new(move |_| {
+            parents.with(|parents| {
+                directories.with(|directories| {
+                    let mut index = vec![];
+                    for (idx, directory) in directories.iter().enumerate() {
+                        let mut lineage = vec![idx];
+                        let mut child = idx;
+                        while child != Self::ROOT {
+                            child = parents[child - 1];
+                            lineage.push(child);
+                        }
+                        lineage.reverse();
+
+                        let ancestors = lineage
+                            .iter()
+                            .map(|idx| directories[*idx].id().clone())
+                            .collect::<Vec<_>>();
+                        let path = lineage
+                            .iter()
+                            .skip(1)
+                            .map(|idx| directories[*idx].name.get())
+                            .collect::<PathBuf>();
+
+                        directory.files.with(|files| {
+                            for file in files {
+                                index.push(IndexedFile {
+                                    id: file.id().clone(),
+                                    path: file.name.with(|name| path.join(name)),
+                                    ancestors: ancestors.clone(),
+                                });
+                            }
+                        });
+                    }
+
+                    index
+                })
+            })
+        })
+    }
+
     pub fn children(
         &self,
         parent: ResourceId,
@@ -952,7 +2151,7 @@ impl DirectoryTree {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy)]
 pub struct Canvas {
     cells: CanvasCells,
     rows: RwSignal<core::data::IndexType>,
@@ -961,7 +2160,7 @@ pub struct Canvas {
 impl Canvas {
     pub fn new(rows: core::data::IndexType, cols: core::data::IndexType) -> Self {
         Self {
-            cells: CanvasCells::new(rows, cols),
+            cells: CanvasCells::new(),
             rows: RwSignal::new(rows),
             cols: RwSignal::new(cols),
         }
@@ -978,6 +2177,46 @@ impl Canvas {
     pub fn cols(&self) -> ReadSignal<core::data::IndexType> {
         self.cols.read_only()
     }
+
+    /// Size the canvas to fit `(rows, cols)`, but no smaller than the default size.
+    pub fn fit(&self, rows: core::data::IndexType, cols: core::data::IndexType) {
+        self.resize(rows.max(CANVAS_ROWS_DEFAULT), cols.max(CANVAS_COLS_DEFAULT));
+    }
+
+    /// Grow the canvas to contain at least `(rows, cols)`.
+    pub fn grow_to(&self, rows: core::data::IndexType, cols: core::data::IndexType) {
+        self.resize(
+            rows.max(self.rows.get_untracked()),
+            cols.max(self.cols.get_untracked()),
+        );
+    }
+
+    pub fn add_rows(&self, rows: core::data::IndexType) {
+        self.resize(
+            self.rows.get_untracked().saturating_add(rows),
+            self.cols.get_untracked(),
+        );
+    }
+
+    pub fn add_cols(&self, cols: core::data::IndexType) {
+        self.resize(
+            self.rows.get_untracked(),
+            self.cols.get_untracked().saturating_add(cols),
+        );
+    }
+
+    fn resize(&self, rows: core::data::IndexType, cols: core::data::IndexType) {
+        let (current_rows, current_cols) = (self.rows.get_untracked(), self.cols.get_untracked());
+        if (rows, cols) == (current_rows, current_cols) {
+            return;
+        }
+
+        self.rows.set(rows);
+        self.cols.set(cols);
+        if rows < current_rows || cols < current_cols {
+            self.cells.truncate(rows, cols);
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -1013,48 +2252,95 @@ impl CanvasCellValue {
     }
 }
 
+/// Value of canvas cells that have not been set.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CanvasCellDefault {
+    Unset,
+    Empty,
+}
+
+/// Cells of a canvas.
+/// Only cells that have been set are stored, other cells take the default value,
+/// so the canvas can grow without allocating a signal per cell.
 #[derive(Clone, Copy)]
 pub struct CanvasCells {
-    inner: RwSignal<Vec<Vec<RwSignal<CanvasCellValue>>>>,
+    inner: StoredValue<BTreeMap<core::data::CellIndex, RwSignal<CanvasCellValue>>>,
+
+    /// Notified when cells are added.
+    added: Trigger,
+    default: RwSignal<CanvasCellDefault>,
 }
 impl CanvasCells {
-    pub fn new(rows: core::data::IndexType, cols: core::data::IndexType) -> Self {
-        let mut cells = Vec::with_capacity(rows as usize);
-        for _ in 0..rows {
-            let mut row = Vec::with_capacity(cols as usize);
-            for _ in 0..cols {
-                row.push(RwSignal::new(CanvasCellValue::Unset));
-            }
-            cells.push(row);
-        }
-
+    pub fn new() -> Self {
         Self {
-            inner: RwSignal::new(cells),
+            inner: StoredValue::new(BTreeMap::new()),
+            added: Trigger::new(),
+            default: RwSignal::new(CanvasCellDefault::Unset),
         }
     }
 
+    /// # Returns
+    /// The cell, if it has been set.
     pub fn get_cell(&self, idx: &core::data::CellIndex) -> Option<RwSignal<CanvasCellValue>> {
-        let row = idx.row() as usize;
-        let col = idx.col() as usize;
-        let rows = self.inner.read_untracked().len();
-        if row >= rows {
-            return None;
+        self.inner.with_value(|cells| cells.get(idx).copied())
+    }
+
+    /// Like [`Self::get_cell`], but reruns the caller when the cell is added if it does not exist.
+    pub fn track_cell(&self, idx: &core::data::CellIndex) -> Option<RwSignal<CanvasCellValue>> {
+        let cell = self.get_cell(idx);
+        if cell.is_none() {
+            self.added.track();
+        }
+        cell
+    }
+
+    /// Value of cells that have not been set.
+    pub fn default(&self) -> ReadSignal<CanvasCellDefault> {
+        self.default.read_only()
+    }
+
+    /// Set the value of cells.
+    /// Must be called within the workspace owner, as new cells are created.
+    pub fn set_all(&self, values: impl IntoIterator<Item = (core::data::CellIndex, CellValue)>) {
+        let mut added = false;
+        for (idx, value) in values {
+            match self.get_cell(&idx) {
+                Some(cell) => cell.update(|cell| cell.insert(value)),
+                None => {
+                    let cell = RwSignal::new(CanvasCellValue::Set(value));
+                    self.inner.update_value(|cells| {
+                        cells.insert(idx, cell);
+                    });
+                    added = true;
+                }
+            }
         }
-        let cols = self.inner.read_untracked()[0].len();
-        if col >= cols {
-            return None;
+
+        if added {
+            self.added.notify();
         }
-        Some(self.inner.read_untracked()[row][col].clone())
+    }
+
+    /// Remove cells outside of `(rows, cols)`.
+    fn truncate(&self, rows: core::data::IndexType, cols: core::data::IndexType) {
+        self.inner.update_value(|cells| {
+            cells.retain(|idx, cell| {
+                let keep = idx.row() < rows && idx.col() < cols;
+                if !keep {
+                    cell.dispose();
+                }
+                keep
+            });
+        });
     }
 
     /// Unset all cells.
     pub fn clear(&self) {
-        self.inner.with_untracked(|cells| {
-            for row in cells.iter() {
-                for cell in row.iter() {
-                    if cell.read_untracked().is_set() {
-                        cell.update(|cell| cell.take());
-                    }
+        self.default.set(CanvasCellDefault::Unset);
+        self.inner.with_value(|cells| {
+            for cell in cells.values() {
+                if cell.read_untracked().is_set() {
+                    cell.update(|cell| cell.take());
                 }
             }
         });
@@ -1062,12 +2348,11 @@ impl CanvasCells {
 
     /// Set all cells to empty.
     pub fn empty(&self) {
-        self.inner.with_untracked(|cells| {
-            for row in cells.iter() {
-                for cell in row.iter() {
-                    if !cell.read_untracked().is_empty() {
-                        cell.update(|cell| cell.insert(CellValue::empty()));
-                    }
+        self.default.set(CanvasCellDefault::Empty);
+        self.inner.with_value(|cells| {
+            for cell in cells.values() {
+                if !cell.read_untracked().is_empty() {
+                    cell.update(|cell| cell.insert(CellValue::empty()));
                 }
             }
         });
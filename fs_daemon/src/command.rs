@@ -0,0 +1,25 @@
+use std::path::PathBuf;
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Command {
+    Watch(PathBuf),
+    Unwatch(PathBuf),
+
+    /// Set the ignore rules for the matcher's root, replacing any previous rules for it.
+    /// Events for ignored paths are not reported.
+    ///
+    /// # Notes
+    /// + Only available in-process as the matcher can not be serialized.
+    #[cfg(feature = "server")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Ignore(ignore::gitignore::Gitignore),
+
+    /// Track the content of a file by its hash.
+    /// Modify events for tracked files are only reported if their content changed.
+    TrackContent(PathBuf),
+    UntrackContent(PathBuf),
+
+    /// Stop watching all paths, flush pending events, and stop the daemon.
+    Shutdown,
+}
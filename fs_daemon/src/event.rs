@@ -8,6 +8,12 @@ pub enum Event {
 
     /// Could not determine if the event affects a file, folder, or other resource.
     Any(Any),
+
+    /// Events under the watched root may have been missed,
+    /// e.g. because the event queue overflowed or the watch was re-established.
+    /// Its contents should be read again.
+    #[from(ignore)]
+    Rescan(PathBuf),
 }
 
 #[derive(Debug)]
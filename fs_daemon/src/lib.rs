@@ -1,7 +1,11 @@
 #![feature(assert_matches)]
 
+pub mod command;
 pub mod event;
+pub use command::Command;
 pub use event::Event;
 
+#[cfg(feature = "serde")]
+pub mod protocol;
 #[cfg(feature = "server")]
 pub mod server;
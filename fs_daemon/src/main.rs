@@ -1,6 +1,14 @@
 //! Runs an fs daemon.
 //!
 //! Must be run with the `server` feature enabled.
+//!
+//! # Modes
+//! + No arguments: Reads text commands from stdin, e.g. `watch <path>`.
+//! + `--json`: Speaks the JSON lines protocol over stdin and stdout.
+//! + `--listen <address>`: Speaks the JSON lines protocol over the first TCP connection to `address`.
+//!
+//! The JSON lines protocol requires the `serde` feature.
+//! See [`hermes_fs_daemon::protocol`].
 
 fn main() {
     #[cfg(feature = "server")]
@@ -38,14 +46,46 @@ mod server {
             .spawn(move || daemon.run())
             .expect("could not launch daemon");
 
+        let mut args = std::env::args().skip(1);
+        match args.next().as_deref() {
+            None => {}
+            #[cfg(feature = "serde")]
+            Some("--json") => {
+                let stdin = std::io::stdin().lock();
+                hermes_fs_daemon::protocol::serve(stdin, std::io::stdout(), command_tx, event_rx)
+                    .expect("could not serve protocol");
+                return;
+            }
+            #[cfg(feature = "serde")]
+            Some("--listen") => {
+                let address = args.next().expect("`--listen` requires an address");
+                let listener =
+                    std::net::TcpListener::bind(&address).expect("could not bind address");
+                let (stream, _) = listener.accept().expect("could not accept connection");
+                let reader = std::io::BufReader::new(
+                    stream.try_clone().expect("could not clone connection"),
+                );
+                hermes_fs_daemon::protocol::serve(reader, stream, command_tx, event_rx)
+                    .expect("could not serve protocol");
+                return;
+            }
+            Some(arg) => panic!("invalid argument `{arg}`"),
+        }
+
         let mut user_in = String::new();
         loop {
+            user_in.clear();
             std::io::stdin().read_line(&mut user_in).unwrap();
             let Some(cmd) = parse_user_input_to_command(&user_in) else {
                 std::io::stdout().write_all(b"invalid command").unwrap();
                 continue;
             };
+            let shutdown = matches!(cmd, server::Command::Shutdown);
             command_tx.send(cmd).unwrap();
+            if shutdown {
+                daemon_handle.join().expect("daemon panicked");
+                return;
+            }
         }
     }
 
@@ -67,6 +107,7 @@ mod server {
                 };
                 Some(server::Command::Unwatch(PathBuf::from(path)))
             }
+            "shutdown" => Some(server::Command::Shutdown),
             _ => None,
         }
     }
@@ -96,9 +137,10 @@ mod server {
         };
 
         /// Enable logging.
+        /// Logs are written to stderr so they do not interfere with the protocol on stdout.
         pub fn enable() {
             let console_logger = fmt::layer()
-                .with_writer(io::stdout)
+                .with_writer(io::stderr)
                 .with_timer(UtcTime::rfc_3339())
                 .pretty();
 
@@ -0,0 +1,147 @@
+//! JSON lines protocol to drive the daemon from another process.
+//!
+//! Each line sent to the daemon is a JSON encoded [`Command`],
+//! each line sent by the daemon a JSON encoded [`Message`].
+//!
+//! # Examples
+//! ```text
+//! > {"Watch":"/path/to/root"}
+//! < {"Events":[{"File":{"Modified":"/path/to/root/data.csv"}}]}
+//! ```
+use crate::{Command, Event};
+use std::io::{self, Write};
+
+/// Message sent by the daemon.
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub enum Message {
+    /// Batch of debounced file system events.
+    Events(Vec<Event>),
+
+    /// A line could not be parsed as a command.
+    InvalidCommand { line: String, error: String },
+}
+
+/// Parse a line as a command.
+pub fn decode(line: &str) -> serde_json::Result<Command> {
+    serde_json::from_str(line.trim())
+}
+
+/// Write a message as a single line.
+pub fn write_message(mut writer: impl Write, message: &Message) -> io::Result<()> {
+    serde_json::to_writer(&mut writer, message)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+#[cfg(feature = "server")]
+pub use serve::serve;
+
+#[cfg(feature = "server")]
+mod serve {
+    use super::{Message, decode, write_message};
+    use crate::server::{CommandSender, EventReceiver};
+    use std::{
+        io::{self, BufRead, Write},
+        sync::{Arc, Mutex},
+    };
+
+    /// Forward commands read from `reader` to the daemon
+    /// and write its events to `writer`.
+    /// Returns once `reader` is closed or the daemon stops.
+    ///
+    /// # Notes
+    /// + Events are written from a separate thread,
+    /// which runs until the daemon stops or `writer` fails.
+    pub fn serve(
+        reader: impl BufRead,
+        writer: impl Write + Send + 'static,
+        command_tx: CommandSender,
+        mut event_rx: EventReceiver,
+    ) -> io::Result<()> {
+        let writer = Arc::new(Mutex::new(writer));
+        std::thread::Builder::new()
+            .name("daemon events".to_string())
+            .spawn({
+                let writer = writer.clone();
+                move || {
+                    while let Some(events) = event_rx.blocking_recv() {
+                        let mut writer = writer.lock().expect("writer lock poisoned");
+                        if let Err(err) = write_message(&mut *writer, &Message::Events(events)) {
+                            #[cfg(feature = "tracing")]
+                            tracing::error!(?err, "could not write events");
+                            break;
+                        }
+                    }
+                }
+            })?;
+
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match decode(&line) {
+                Ok(cmd) => {
+                    if command_tx.send(cmd).is_err() {
+                        break;
+                    }
+                }
+                Err(err) => {
+                    let mut writer = writer.lock().expect("writer lock poisoned");
+                    write_message(
+                        &mut *writer,
+                        &Message::InvalidCommand {
+                            line,
+                            error: err.to_string(),
+                        },
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::event;
+    use std::{assert_matches::assert_matches, path::PathBuf};
+
+    #[test]
+    fn decode_command() {
+        assert_matches!(
+            decode(r#"{"Watch":"/root"}"#),
+            Ok(Command::Watch(path)) if path == PathBuf::from("/root")
+        );
+        assert_matches!(
+            decode(" {\"Unwatch\":\"/root\"}\n"),
+            Ok(Command::Unwatch(path)) if path == PathBuf::from("/root")
+        );
+        assert!(decode("watch /root").is_err());
+        assert!(decode(r#"{"Ignore":null}"#).is_err());
+    }
+
+    #[test]
+    fn write_message_line() {
+        let mut out = vec![];
+        let message = Message::Events(vec![
+            event::File::Modified(PathBuf::from("/root/data.csv")).into(),
+        ]);
+        write_message(&mut out, &message).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), 1);
+        assert!(out.ends_with('\n'));
+
+        let message: Message = serde_json::from_str(&out).unwrap();
+        let Message::Events(events) = message else {
+            panic!("expected events");
+        };
+        assert_matches!(
+            &events[..],
+            [Event::File(event::File::Modified(path))] if path == &PathBuf::from("/root/data.csv")
+        );
+    }
+}
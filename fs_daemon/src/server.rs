@@ -1,13 +1,34 @@
+pub use crate::command::Command;
 use crate::event;
 use notify_debouncer_full::{DebounceEventResult, DebouncedEvent, Debouncer, FileIdMap};
-use std::{assert_matches::assert_matches, path::PathBuf};
+use std::{
+    assert_matches::assert_matches,
+    collections::HashMap,
+    fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
 
-const DEBOUNCE_TIMEOUT: std::time::Duration = std::time::Duration::from_millis(100);
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Number of bytes read at a time when hashing a file.
+const HASH_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Delay before the first attempt to re-establish a failed watch.
+/// Doubles with each failed attempt.
+const RETRY_DELAY_MIN: Duration = Duration::from_millis(250);
+
+/// Longest delay between attempts to re-establish a failed watch.
+const RETRY_DELAY_MAX: Duration = Duration::from_secs(30);
 
 pub type EventSender = tokio::sync::mpsc::UnboundedSender<Vec<event::Event>>;
 pub type EventReceiver = tokio::sync::mpsc::UnboundedReceiver<Vec<event::Event>>;
 pub type CommandSender = crossbeam::channel::Sender<Command>;
 pub type CommandReceiver = crossbeam::channel::Receiver<Command>;
+pub type ErrorSender = tokio::sync::mpsc::UnboundedSender<Error>;
+pub type ErrorReceiver = tokio::sync::mpsc::UnboundedReceiver<Error>;
 type FsEventReceiver = crossbeam::channel::Receiver<DebounceEventResult>;
 
 pub fn event_channel() -> (EventSender, EventReceiver) {
@@ -18,10 +39,62 @@ pub fn command_channel() -> (CommandSender, CommandReceiver) {
     crossbeam::channel::unbounded()
 }
 
+pub fn error_channel() -> (ErrorSender, ErrorReceiver) {
+    tokio::sync::mpsc::unbounded_channel()
+}
+
+/// Error encountered by the daemon.
+#[derive(Debug, Clone)]
+pub enum Error {
+    /// A path could not be watched.
+    Watch { path: PathBuf, error: String },
+
+    /// The file system watcher reported an error.
+    /// `paths` is empty if the error is not specific to any path.
+    Watcher { paths: Vec<PathBuf>, error: String },
+}
+
+/// Health of the daemon.
+#[derive(Debug, Clone, Default)]
+pub struct Health {
+    /// Watched roots being re-established after an error,
+    /// with the number of failed attempts.
+    pub retrying: Vec<(PathBuf, u32)>,
+
+    /// Number of errors encountered since the daemon started.
+    pub errors: usize,
+    pub last_error: Option<Error>,
+}
+
+impl Health {
+    /// # Returns
+    /// If every watched root is being watched.
+    pub fn is_healthy(&self) -> bool {
+        self.retrying.is_empty()
+    }
+}
+
+/// Shared handle to the health of a daemon.
+pub type HealthStatus = Arc<RwLock<Health>>;
+
+/// Pending attempt to re-establish a watch.
 #[derive(Debug)]
-pub enum Command {
-    Watch(PathBuf),
-    Unwatch(PathBuf),
+struct Retry {
+    root: PathBuf,
+
+    /// Number of failed attempts.
+    attempts: u32,
+    at: Instant,
+}
+
+/// # Returns
+/// Delay before the next attempt to re-establish a watch
+/// after `attempts` failed attempts.
+fn retry_delay(attempts: u32) -> Duration {
+    let exponent = attempts.saturating_sub(1).min(16);
+    RETRY_DELAY_MIN
+        .saturating_mul(1 << exponent)
+        .min(RETRY_DELAY_MAX)
 }
 
 type FileSystemWatcher = notify::RecommendedWatcher;
@@ -30,6 +103,17 @@ pub struct Daemon {
     fs_event_rx: FsEventReceiver,
     command_rx: CommandReceiver,
     event_tx: EventSender,
+    error_tx: Option<ErrorSender>,
+    ignore: Vec<ignore::gitignore::Gitignore>,
+
+    /// Watched roots.
+    roots: Vec<PathBuf>,
+
+    /// Content hashes of tracked files.
+    /// `None` if the file could not be read, e.g. because it was removed.
+    content: HashMap<PathBuf, Option<u64>>,
+    retries: Vec<Retry>,
+    health: HealthStatus,
 }
 
 impl Daemon {
@@ -45,30 +129,84 @@ impl Daemon {
             fs_watcher,
             fs_event_rx,
             event_tx,
+            error_tx: None,
             command_rx,
+            ignore: vec![],
+            roots: vec![],
+            content: HashMap::new(),
+            retries: vec![],
+            health: HealthStatus::default(),
         }
     }
 
+    /// Report errors to `error_tx`.
+    pub fn with_errors(mut self, error_tx: ErrorSender) -> Self {
+        self.error_tx = Some(error_tx);
+        self
+    }
+
+    /// # Returns
+    /// Handle to the daemon's health, which can be read from other threads.
+    pub fn health(&self) -> HealthStatus {
+        self.health.clone()
+    }
+
     /// Begin responding to events.
+    /// Returns once the daemon is shut down,
+    /// either by [`Command::Shutdown`] or by all command senders being dropped.
     pub fn run(&mut self) {
         self.listen_for_events();
+        self.shutdown();
     }
 
     /// Listen for events coming from child actors.
+    /// Returns when the daemon should shut down.
     fn listen_for_events(&mut self) {
         loop {
+            let retry = match self.retries.iter().map(|retry| retry.at).min() {
+                Some(at) => crossbeam::channel::at(at),
+                None => crossbeam::channel::never(),
+            };
+
             crossbeam::select! {
                 recv(self.command_rx) -> cmd => match cmd {
+                    Ok(Command::Shutdown) | Err(_) => return,
                     Ok(cmd) => self.handle_command(cmd),
-                    Err(err) => panic!("{err:?}"),
                 },
                 recv(self.fs_event_rx) -> events => match events {
                     Ok(events) => self.handle_file_system_events(events),
                     Err(err) => panic!("{err:?}"),
                 },
+                recv(retry) -> _ => self.retry_watches(),
             }
         }
     }
+
+    /// Stop watching all roots and flush pending events.
+    fn shutdown(&mut self) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!("shutting down");
+
+        self.retries.clear();
+        self.update_health();
+        for root in std::mem::take(&mut self.roots) {
+            if let Err(err) = self.fs_watcher.unwatch(&root) {
+                self.report(Error::Watch {
+                    path: root,
+                    error: err.to_string(),
+                });
+            }
+        }
+
+        // Debounced events are emitted after the debounce timeout.
+        // Events can not be flushed if no one is listening.
+        while !self.event_tx.is_closed() {
+            let Ok(events) = self.fs_event_rx.recv_timeout(DEBOUNCE_TIMEOUT * 2) else {
+                break;
+            };
+            self.handle_file_system_events(events);
+        }
+    }
 }
 
 impl Daemon {
@@ -80,42 +218,281 @@ impl Daemon {
         match cmd {
             Command::Watch(path) => self.watch_path(path),
             Command::Unwatch(path) => self.unwatch_path(path),
+            Command::Ignore(matcher) => self.set_ignore(matcher),
+            Command::TrackContent(path) => self.track_content(path),
+            Command::UntrackContent(path) => {
+                self.content.remove(&path);
+            }
+            Command::Shutdown => unreachable!("handled by the event loop"),
         }
     }
 
+    fn track_content(&mut self, path: PathBuf) {
+        let hash = hash_content(&path).ok();
+        self.content.insert(path, hash);
+    }
+
+    /// Update the hashes of tracked files affected by the events.
+    ///
+    /// # Returns
+    /// `events` without modifications of tracked files whose content did not change.
+    fn filter_unchanged_content(&mut self, events: Vec<event::Event>) -> Vec<event::Event> {
+        if self.content.is_empty() {
+            return events;
+        }
+
+        events
+            .into_iter()
+            .filter(|event| match event {
+                event::Event::File(event::File::Modified(path) | event::File::Created(path)) => {
+                    let Some(hash) = self.content.get_mut(path) else {
+                        return true;
+                    };
+
+                    let current = hash_content(path).ok();
+                    if current.is_some() && current == *hash {
+                        return false;
+                    }
+                    *hash = current;
+                    true
+                }
+
+                event::Event::File(event::File::Removed(path))
+                | event::Event::Any(event::Any::Removed(path)) => {
+                    if let Some(hash) = self.content.get_mut(path) {
+                        *hash = None;
+                    }
+                    true
+                }
+
+                event::Event::File(
+                    event::File::Renamed { from, to } | event::File::Moved { from, to },
+                ) => {
+                    if let Some(hash) = self.content.remove(from) {
+                        self.content.insert(to.clone(), hash);
+                    }
+                    true
+                }
+
+                event::Event::Folder(_) | event::Event::Rescan(_) => true,
+            })
+            .collect()
+    }
+
+    fn set_ignore(&mut self, matcher: ignore::gitignore::Gitignore) {
+        self.ignore.retain(|ignore| ignore.path() != matcher.path());
+        self.ignore.push(matcher);
+    }
+
+    /// # Returns
+    /// If the path is matched by the ignore rules of a root containing it.
+    fn is_ignored(&self, path: &Path) -> bool {
+        self.ignore.iter().any(|matcher| {
+            path.starts_with(matcher.path())
+                && matcher
+                    .matched_path_or_any_parents(path, path.is_dir())
+                    .is_ignore()
+        })
+    }
+
     /// Add a path to watch for file system changes.
+    /// If the path can not be watched, watching it is retried.
     fn watch_path(&mut self, path: impl Into<PathBuf>) {
         let path: PathBuf = path.into();
         assert!(path.is_absolute());
-        self.fs_watcher
-            .watch(path, notify::RecursiveMode::Recursive)
-            .unwrap();
+        if !self.roots.contains(&path) {
+            self.roots.push(path.clone());
+        }
+
+        if let Err(err) = self
+            .fs_watcher
+            .watch(&path, notify::RecursiveMode::Recursive)
+        {
+            self.report(Error::Watch {
+                path: path.clone(),
+                error: err.to_string(),
+            });
+            self.schedule_retry(path);
+        }
     }
 
     /// Remove a path from watching file system changes.
     fn unwatch_path(&mut self, path: impl Into<PathBuf>) {
         let path: PathBuf = path.into();
         assert!(path.is_absolute());
-        self.fs_watcher.unwatch(path).unwrap();
+        self.roots.retain(|root| *root != path);
+        self.retries.retain(|retry| retry.root != path);
+        self.update_health();
+
+        if let Err(err) = self.fs_watcher.unwatch(&path) {
+            self.report(Error::Watch {
+                path,
+                error: err.to_string(),
+            });
+        }
+    }
+
+    /// Re-establish the watch of a root.
+    /// On success a rescan of the root is reported, as events may have been missed.
+    /// On failure another attempt is scheduled.
+    fn rewatch(&mut self, root: PathBuf) {
+        // The watch may already be gone, in which case unwatching fails.
+        let _ = self.fs_watcher.unwatch(&root);
+        match self
+            .fs_watcher
+            .watch(&root, notify::RecursiveMode::Recursive)
+        {
+            Ok(()) => {
+                self.retries.retain(|retry| retry.root != root);
+                self.update_health();
+                self.event_tx
+                    .send(vec![event::Event::Rescan(root)])
+                    .unwrap();
+            }
+            Err(err) => {
+                self.report(Error::Watch {
+                    path: root.clone(),
+                    error: err.to_string(),
+                });
+                self.schedule_retry(root);
+            }
+        }
+    }
+
+    /// Schedule an attempt to re-establish the watch of a root,
+    /// backing off with each failed attempt.
+    fn schedule_retry(&mut self, root: PathBuf) {
+        match self.retries.iter_mut().find(|retry| retry.root == root) {
+            Some(retry) => {
+                retry.attempts += 1;
+                retry.at = Instant::now() + retry_delay(retry.attempts);
+            }
+            None => self.retries.push(Retry {
+                root,
+                attempts: 1,
+                at: Instant::now() + retry_delay(1),
+            }),
+        }
+
+        self.update_health();
+    }
+
+    /// Attempt to re-establish watches that are due.
+    fn retry_watches(&mut self) {
+        let now = Instant::now();
+        let due = self
+            .retries
+            .iter()
+            .filter(|retry| retry.at <= now)
+            .map(|retry| retry.root.clone())
+            .collect::<Vec<_>>();
+
+        for root in due {
+            self.rewatch(root);
+        }
+    }
+
+    /// # Returns
+    /// Watched roots containing any of the paths.
+    /// All watched roots if `paths` is empty or none are contained in a root.
+    fn affected_roots(&self, paths: &[PathBuf]) -> Vec<PathBuf> {
+        let roots = self
+            .roots
+            .iter()
+            .filter(|root| paths.iter().any(|path| path.starts_with(root)))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if roots.is_empty() {
+            self.roots.clone()
+        } else {
+            roots
+        }
+    }
+
+    fn report(&self, error: Error) {
+        #[cfg(feature = "tracing")]
+        tracing::error!(?error);
+
+        {
+            let mut health = self.health.write().expect("health lock poisoned");
+            health.errors += 1;
+            health.last_error = Some(error.clone());
+        }
+
+        if let Some(error_tx) = self.error_tx.as_ref() {
+            // Errors are dropped if no one is listening.
+            let _ = error_tx.send(error);
+        }
+    }
+
+    fn update_health(&self) {
+        let mut health = self.health.write().expect("health lock poisoned");
+        health.retrying = self
+            .retries
+            .iter()
+            .map(|retry| (retry.root.clone(), retry.attempts))
+            .collect();
     }
 }
 
 impl Daemon {
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
-    fn handle_file_system_events(&self, events: DebounceEventResult) {
+    fn handle_file_system_events(&mut self, events: DebounceEventResult) {
         #[cfg(feature = "tracing")]
         tracing::trace!(?events);
 
         match events {
             Ok(events) => {
+                // The event queue overflowed, so events were dropped.
+                let (rescan, events): (Vec<_>, Vec<_>) =
+                    events.into_iter().partition(|event| event.need_rescan());
+                if !rescan.is_empty() {
+                    let paths = rescan
+                        .into_iter()
+                        .flat_map(|event| event.event.paths)
+                        .collect::<Vec<_>>();
+                    let rescan = self
+                        .affected_roots(&paths)
+                        .into_iter()
+                        .map(event::Event::Rescan)
+                        .collect::<Vec<_>>();
+                    if !rescan.is_empty() {
+                        self.event_tx.send(rescan).unwrap();
+                    }
+                }
+
+                let events = events
+                    .into_iter()
+                    .filter(|event| {
+                        event.paths.is_empty()
+                            || !event.paths.iter().all(|path| self.is_ignored(path))
+                    })
+                    .collect();
                 let events = Self::filter_fs_events(events);
                 #[cfg(feature = "tracing")]
                 tracing::trace!("filtered events\n{events:?}");
 
                 self.process_events(events)
             }
-            Err(err) => {
-                todo!("{err:?}")
+            Err(errors) => {
+                let mut roots = vec![];
+                for error in errors {
+                    for root in self.affected_roots(&error.paths) {
+                        if !roots.contains(&root) {
+                            roots.push(root);
+                        }
+                    }
+
+                    self.report(Error::Watcher {
+                        paths: error.paths.clone(),
+                        error: error.to_string(),
+                    });
+                }
+
+                for root in roots {
+                    self.rewatch(root);
+                }
             }
         }
     }
@@ -247,11 +624,12 @@ impl Daemon {
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip(self)))]
-    fn process_events(&self, events: Vec<DebouncedEvent>) {
+    fn process_events(&mut self, events: Vec<DebouncedEvent>) {
         let events = events
             .into_iter()
             .flat_map(|event| self.process_event(event))
             .collect::<Vec<_>>();
+        let events = self.filter_unchanged_content(events);
         #[cfg(feature = "tracing")]
         tracing::trace!(?events);
 
@@ -412,3 +790,50 @@ impl Daemon {
         }
     }
 }
+
+/// Hash a file's content.
+fn hash_content(path: impl AsRef<Path>) -> io::Result<u64> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = xxhash_rust::xxh3::Xxh3::new();
+    let mut buf = vec![0; HASH_CHUNK_SIZE];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+
+    Ok(hasher.digest())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn retry_delay_backs_off() {
+        assert_eq!(retry_delay(1), RETRY_DELAY_MIN);
+        assert_eq!(retry_delay(2), RETRY_DELAY_MIN * 2);
+        assert_eq!(retry_delay(3), RETRY_DELAY_MIN * 4);
+        assert_eq!(retry_delay(100), RETRY_DELAY_MAX);
+        assert!(retry_delay(100) >= retry_delay(99));
+    }
+
+    #[test]
+    fn hash_content_of_file() {
+        let dir = std::env::temp_dir().join("hermes_fs_daemon_hash_content");
+        fs::create_dir_all(&dir).unwrap();
+        let a = dir.join("a.csv");
+        let b = dir.join("b.csv");
+        fs::write(&a, "a,b\n1,2\n").unwrap();
+        fs::write(&b, "a,b\n1,2\n").unwrap();
+        assert_eq!(hash_content(&a).unwrap(), hash_content(&b).unwrap());
+
+        fs::write(&b, "a,b\n1,3\n").unwrap();
+        assert_ne!(hash_content(&a).unwrap(), hash_content(&b).unwrap());
+        assert!(hash_content(dir.join("missing.csv")).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
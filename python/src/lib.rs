@@ -0,0 +1,335 @@
+//! Python bindings for the expression engine,
+//! so scripts validate and evaluate formulas exactly as the desktop app does.
+//!
+//! Build with [maturin](https://www.maturin.rs), e.g. `maturin develop -m python/Cargo.toml`.
+//!
+//! # Examples
+//! ```python
+//! import hermes
+//!
+//! cells = {"A1": 2, "B1": 3}
+//! def context(ref, origin):
+//!     return cells[str(ref)]
+//!
+//! hermes.parse("A1 * B1")
+//! assert hermes.eval("A1 * B1", context) == 6
+//! ```
+use hermes_core::{data, expr, utils};
+use pyo3::{
+    IntoPyObjectExt, create_exception,
+    exceptions::{PyException, PyLookupError, PyTypeError, PyValueError},
+    prelude::*,
+    types::{PyBool, PyFloat, PyInt, PyString},
+};
+use std::{cell::RefCell, time};
+
+create_exception!(
+    hermes,
+    FormulaError,
+    PyException,
+    "Formula could not be evaluated.\nArguments are `(message, code)`, e.g. `('division by zero', '#DIV/0!')`."
+);
+create_exception!(
+    hermes,
+    ParseError,
+    FormulaError,
+    "Formula is not valid.\nArguments are `(message, start, end)`, where `start` and `end` are character offsets."
+);
+
+#[pymodule]
+fn hermes(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("FormulaError", m.py().get_type::<FormulaError>())?;
+    m.add("ParseError", m.py().get_type::<ParseError>())?;
+    m.add_class::<CellRef>()?;
+    m.add_class::<Reference>()?;
+    m.add_function(wrap_pyfunction!(parse, m)?)?;
+    m.add_function(wrap_pyfunction!(eval, m)?)?;
+    m.add_function(wrap_pyfunction!(references, m)?)?;
+    m.add_function(wrap_pyfunction!(index_to_col, m)?)?;
+    m.add_function(wrap_pyfunction!(col_to_index, m)?)?;
+    m.add_function(wrap_pyfunction!(index_to_row, m)?)?;
+    m.add_function(wrap_pyfunction!(row_to_index, m)?)?;
+    Ok(())
+}
+
+/// Validate a formula can be parsed.
+///
+/// Raises `ParseError` at the first error.
+#[pyfunction]
+fn parse(formula: &str) -> PyResult<()> {
+    expr::parse(formula).map_err(parse_error)
+}
+
+/// Evaluate a formula.
+///
+/// `context` is called as `context(ref, origin)` to get the value of each referenced cell,
+/// where `ref` is a `CellRef` and `origin` is `(sheet, row, col)`.
+/// It returns `None`, a `str`, `int`, `float`, `bool`,
+/// timezone aware `datetime` or `timedelta`,
+/// and raises `LookupError` if the cell does not exist.
+/// Without a context, referencing a cell is an error.
+///
+/// `empty_cells` is how references to empty cells evaluate,
+/// one of `"empty"`, `"zero"` or `"error"`.
+///
+/// Raises `ParseError` if the formula is not valid, and `FormulaError` if it fails.
+/// Errors raised by `context`, other than `LookupError`, are propagated.
+#[pyfunction]
+#[pyo3(signature = (formula, context = None, origin = (0, 0, 0), *, empty_cells = "empty"))]
+fn eval<'py>(
+    py: Python<'py>,
+    formula: &str,
+    context: Option<Bound<'py, PyAny>>,
+    origin: (data::IndexType, data::IndexType, data::IndexType),
+    empty_cells: &str,
+) -> PyResult<Bound<'py, PyAny>> {
+    expr::parse(formula).map_err(parse_error)?;
+
+    let empty_cells = match empty_cells {
+        "empty" => expr::EmptyCells::Empty,
+        "zero" => expr::EmptyCells::Zero,
+        "error" => expr::EmptyCells::Error,
+        _ => {
+            return Err(PyValueError::new_err(format!(
+                "invalid `empty_cells` `{empty_cells}`, expected one of `empty`, `zero`, `error`"
+            )));
+        }
+    };
+
+    let (sheet, row, col) = origin;
+    let origin = data::CellPath { sheet, row, col };
+    let context = Callback {
+        func: context,
+        options: expr::Options {
+            empty_cells,
+            ..Default::default()
+        },
+        error: RefCell::new(None),
+    };
+    let value = expr::eval(formula, &context, &origin);
+    if let Some(err) = context.error.into_inner() {
+        return Err(err);
+    }
+
+    let value = value.map_err(formula_error)?;
+    value_into_py(py, value)
+}
+
+/// Extract the cell and range references of a formula,
+/// in the order they appear.
+/// The formula does not need to be valid.
+#[pyfunction]
+fn references(formula: &str) -> Vec<Reference> {
+    expr::references(formula)
+        .into_iter()
+        .map(|reference| Reference {
+            start: CellRef(reference.start),
+            end: reference.end.map(CellRef),
+            span: (reference.span.start.char, reference.span.end.char),
+        })
+        .collect()
+}
+
+/// Column label of an index, e.g. `0` -> `"A"`, `26` -> `"AA"`.
+#[pyfunction]
+fn index_to_col(idx: data::IndexType) -> String {
+    utils::index_to_col(idx)
+}
+
+/// Index of a column label, e.g. `"A"` -> `0`, `"aa"` -> `26`.
+///
+/// Raises `ValueError` if the label is not valid.
+#[pyfunction]
+fn col_to_index(col: &str) -> PyResult<data::IndexType> {
+    utils::col_to_index(col).ok_or_else(|| PyValueError::new_err(format!("invalid column `{col}`")))
+}
+
+/// Row label of an index, e.g. `0` -> `"1"`.
+#[pyfunction]
+fn index_to_row(idx: data::IndexType) -> String {
+    utils::index_to_row(idx)
+}
+
+/// Index of a row label, e.g. `1` -> `0`.
+///
+/// Raises `ValueError` if the row is `0`.
+#[pyfunction]
+fn row_to_index(row: data::IndexType) -> PyResult<data::IndexType> {
+    utils::row_to_index(row).ok_or_else(|| PyValueError::new_err("rows start at `1`"))
+}
+
+/// Reference to a cell, e.g. `A1`, `$B$2`, `'My Sheet'!C3`.
+#[pyclass(frozen, eq, module = "hermes")]
+#[derive(Clone, PartialEq)]
+struct CellRef(data::CellRef);
+
+#[pymethods]
+impl CellRef {
+    /// Parse a cell reference.
+    ///
+    /// Raises `ValueError` if the reference is not valid.
+    #[new]
+    fn new(value: &str) -> PyResult<Self> {
+        data::CellRef::from_str(value)
+            .map(Self)
+            .ok_or_else(|| PyValueError::new_err(format!("invalid cell reference `{value}`")))
+    }
+
+    /// Index or label of the referenced sheet,
+    /// or `None` if the reference is to the sheet of the formula.
+    #[getter]
+    fn sheet<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        match &self.0.sheet {
+            data::SheetRef::Relative => Ok(None),
+            data::SheetRef::Absolute(data::SheetIndex::Index(idx)) => {
+                idx.into_bound_py_any(py).map(Some)
+            }
+            data::SheetRef::Absolute(data::SheetIndex::Label(label)) => {
+                label.into_bound_py_any(py).map(Some)
+            }
+        }
+    }
+
+    #[getter]
+    fn row(&self) -> data::IndexType {
+        self.0.row
+    }
+
+    #[getter]
+    fn col(&self) -> data::IndexType {
+        self.0.col
+    }
+
+    #[getter]
+    fn row_absolute(&self) -> bool {
+        self.0.row_mode == data::RefMode::Absolute
+    }
+
+    #[getter]
+    fn col_absolute(&self) -> bool {
+        self.0.col_mode == data::RefMode::Absolute
+    }
+
+    fn __str__(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("CellRef({:?})", self.0.to_string())
+    }
+}
+
+/// Cell or range referenced by a formula.
+#[pyclass(frozen, get_all, module = "hermes")]
+struct Reference {
+    start: CellRef,
+
+    /// End of the range, or `None` if the reference is a cell.
+    end: Option<CellRef>,
+
+    /// `(start, end)` character offsets of the reference in the formula.
+    span: (usize, usize),
+}
+
+#[pymethods]
+impl Reference {
+    fn __repr__(&self) -> String {
+        match &self.end {
+            None => format!("Reference({:?})", self.start.0.to_string()),
+            Some(end) => {
+                let range = format!("{}:{}", self.start.0, end.0);
+                format!("Reference({range:?})")
+            }
+        }
+    }
+}
+
+/// Context calling a Python function for cell values.
+struct Callback<'py> {
+    func: Option<Bound<'py, PyAny>>,
+    options: expr::Options,
+
+    /// First error raised by `func`, other than a `LookupError`.
+    error: RefCell<Option<PyErr>>,
+}
+
+impl expr::Context for &Callback<'_> {
+    fn cell_value(
+        self,
+        cell_ref: &data::CellRef,
+        origin: &data::CellPath,
+    ) -> Result<expr::Value, expr::ContextError> {
+        let Some(func) = &self.func else {
+            return Err(expr::ContextError::CellRefDoesNotExist);
+        };
+
+        let value = func
+            .call1((
+                CellRef(cell_ref.clone()),
+                (origin.sheet, origin.row, origin.col),
+            ))
+            .and_then(|value| value_from_py(&value));
+
+        match value {
+            Ok(value) => Ok(value),
+            Err(err) => {
+                if !err.is_instance_of::<PyLookupError>(func.py()) {
+                    self.error.borrow_mut().get_or_insert(err);
+                }
+                Err(expr::ContextError::CellRefDoesNotExist)
+            }
+        }
+    }
+
+    fn options(self) -> expr::Options {
+        self.options
+    }
+}
+
+fn value_into_py(py: Python<'_>, value: expr::Value) -> PyResult<Bound<'_, PyAny>> {
+    match value {
+        expr::Value::Empty => Ok(py.None().into_bound(py)),
+        expr::Value::String(value) => value.into_bound_py_any(py),
+        expr::Value::Int(value) => value.into_bound_py_any(py),
+        expr::Value::Float(value) => value.into_bound_py_any(py),
+        expr::Value::Bool(value) => value.into_bound_py_any(py),
+        expr::Value::DateTime(value) => value.into_bound_py_any(py),
+        expr::Value::Duration(value) => value.into_bound_py_any(py),
+    }
+}
+
+fn value_from_py(value: &Bound<'_, PyAny>) -> PyResult<expr::Value> {
+    // `bool` is a subclass of `int`, so must be checked first.
+    if value.is_none() {
+        Ok(expr::Value::Empty)
+    } else if value.is_instance_of::<PyBool>() {
+        Ok(expr::Value::Bool(value.extract()?))
+    } else if value.is_instance_of::<PyInt>() {
+        Ok(expr::Value::Int(value.extract()?))
+    } else if value.is_instance_of::<PyFloat>() {
+        Ok(expr::Value::Float(value.extract()?))
+    } else if value.is_instance_of::<PyString>() {
+        Ok(expr::Value::String(value.extract()?))
+    } else if let Ok(datetime) = value.extract::<chrono::DateTime<chrono::Utc>>() {
+        Ok(expr::Value::DateTime(datetime))
+    } else if let Ok(duration) = value.extract::<time::Duration>() {
+        Ok(expr::Value::Duration(duration))
+    } else {
+        Err(PyTypeError::new_err(format!(
+            "cell values must be `None`, `str`, `int`, `float`, `bool`, a timezone aware `datetime` or `timedelta`, found `{}`",
+            value.get_type().name()?
+        )))
+    }
+}
+
+fn parse_error(err: expr::WithSpan<expr::Error>) -> PyErr {
+    ParseError::new_err((
+        err.value.to_string(),
+        err.span.start.char,
+        err.span.end.char,
+    ))
+}
+
+fn formula_error(err: expr::Error) -> PyErr {
+    FormulaError::new_err((err.to_string(), err.code()))
+}
@@ -0,0 +1,155 @@
+//! Fixtures for end-to-end tests.
+//!
+//! A [`Fixture`] is a temporary project root, removed when it is dropped,
+//! populated with generated csv and xlsx files.
+use std::path::{Path, PathBuf};
+
+/// Temporary project root.
+pub struct Fixture {
+    root: tempfile::TempDir,
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        Self {
+            root: tempfile::tempdir().expect("could not create fixture root"),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        self.root.path()
+    }
+
+    /// # Returns
+    /// Absolute path of `path` relative to the root.
+    pub fn path(&self, path: impl AsRef<Path>) -> PathBuf {
+        self.root.path().join(path)
+    }
+
+    /// Write a csv file.
+    /// Parent folders are created as needed.
+    ///
+    /// # Returns
+    /// Absolute path of the file.
+    pub fn csv(&self, path: impl AsRef<Path>, records: &[&[&str]]) -> PathBuf {
+        let path = self.path(path);
+        create_parent(&path);
+        let mut wtr = csv::WriterBuilder::new()
+            .flexible(true)
+            .from_path(&path)
+            .expect("could not create csv fixture");
+        for record in records {
+            wtr.write_record(*record)
+                .expect("could not write csv fixture");
+        }
+        wtr.flush().expect("could not write csv fixture");
+        path
+    }
+
+    /// Write an xlsx file.
+    /// Values that parse as numbers are written as numbers, others as strings.
+    /// Parent folders are created as needed.
+    ///
+    /// # Arguments
+    /// + `sheets`: `(<name>, <records>)` of each sheet.
+    ///
+    /// # Returns
+    /// Absolute path of the file.
+    pub fn xlsx(&self, path: impl AsRef<Path>, sheets: &[(&str, &[&[&str]])]) -> PathBuf {
+        let path = self.path(path);
+        create_parent(&path);
+        let mut workbook = rust_xlsxwriter::Workbook::new();
+        for (name, records) in sheets {
+            let worksheet = workbook.add_worksheet();
+            worksheet
+                .set_name(*name)
+                .expect("invalid xlsx fixture sheet name");
+            for (row, record) in records.iter().enumerate() {
+                for (col, value) in record.iter().enumerate() {
+                    let row = row as u32;
+                    let col = col as u16;
+                    match value.parse::<f64>() {
+                        Ok(value) => worksheet.write_number(row, col, value),
+                        Err(_) => worksheet.write_string(row, col, *value),
+                    }
+                    .expect("could not write xlsx fixture");
+                }
+            }
+        }
+
+        workbook.save(&path).expect("could not save xlsx fixture");
+        path
+    }
+
+    /// Read a csv file as records.
+    pub fn read_csv(&self, path: impl AsRef<Path>) -> Vec<Vec<String>> {
+        read_delimited(self.path(path), b',')
+    }
+
+    /// Read a tsv file as records.
+    pub fn read_tsv(&self, path: impl AsRef<Path>) -> Vec<Vec<String>> {
+        read_delimited(self.path(path), b'\t')
+    }
+
+    /// # Returns
+    /// Paths of the files under the root, relative to it, sorted.
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files = vec![];
+        let mut dirs = vec![self.root().to_path_buf()];
+        while let Some(dir) = dirs.pop() {
+            for entry in std::fs::read_dir(&dir).expect("could not read fixture folder") {
+                let path = entry.expect("could not read fixture folder").path();
+                if path.is_dir() {
+                    dirs.push(path);
+                } else {
+                    files.push(
+                        path.strip_prefix(self.root())
+                            .expect("path is in root")
+                            .to_path_buf(),
+                    );
+                }
+            }
+        }
+
+        files.sort();
+        files
+    }
+}
+
+impl Default for Fixture {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Convert string records into owned records, e.g. to compare against a file's contents.
+pub fn records(records: &[&[&str]]) -> Vec<Vec<String>> {
+    records
+        .iter()
+        .map(|record| record.iter().map(|value| value.to_string()).collect())
+        .collect()
+}
+
+fn read_delimited(path: impl AsRef<Path>, delimiter: u8) -> Vec<Vec<String>> {
+    csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .delimiter(delimiter)
+        .from_path(path)
+        .expect("could not open file")
+        .records()
+        .map(|record| {
+            record
+                .expect("invalid record")
+                .iter()
+                .map(|value| value.to_string())
+                .collect()
+        })
+        .collect()
+}
+
+fn create_parent(path: &Path) {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).expect("could not create fixture folder");
+    }
+}
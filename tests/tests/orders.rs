@@ -0,0 +1,468 @@
+//! Running workspace orders against files on disk.
+use hermes_core as core;
+use hermes_desktop_lib as lib;
+use hermes_tests::{Fixture, records};
+use lib::{
+    formula::{self, WorkspaceOrder, error},
+    run::{self, Output},
+};
+use std::{fs, path::PathBuf};
+
+fn create(path: PathBuf, format: formula::OutputFormat) -> WorkspaceOrder {
+    formula::Create {
+        path,
+        format,
+        columns: vec![
+            formula::OutputColumn {
+                header: "a".to_string(),
+                values: vec![core::expr::Value::Int(1), core::expr::Value::Int(2)],
+            },
+            formula::OutputColumn {
+                header: "b".to_string(),
+                values: vec![core::expr::Value::String("x".to_string())],
+            },
+        ],
+        inputs: vec![],
+    }
+    .into()
+}
+
+fn update_csv(
+    path: PathBuf,
+    row: core::data::IndexType,
+    col: core::data::IndexType,
+    value: core::expr::Value,
+    mode: formula::UpdateMode,
+) -> WorkspaceOrder {
+    formula::Update {
+        path,
+        updates: formula::Updates::Csv(vec![formula::UpdateCsv {
+            row,
+            col,
+            value,
+            formula: None,
+            provenance: None,
+            mode,
+        }]),
+        options: lib::data::ImportOptions::default(),
+    }
+    .into()
+}
+
+#[test]
+fn create_writes_records() {
+    let fixture = Fixture::new();
+    let orders = vec![
+        create(fixture.path("out.csv"), formula::OutputFormat::Csv),
+        create(fixture.path("out.tsv"), formula::OutputFormat::Tsv),
+    ];
+
+    let results = run::run(orders, Output::InPlace).unwrap();
+    assert_eq!(results.len(), 2);
+
+    let expected = records(&[&["a", "b"], &["1", "x"], &["2", ""]]);
+    assert_eq!(fixture.read_csv("out.csv"), expected);
+    assert_eq!(fixture.read_tsv("out.tsv"), expected);
+}
+
+#[test]
+fn update_csv_in_place() {
+    let fixture = Fixture::new();
+    let path = fixture.csv("data.csv", &[&["a", "b"], &["1", ""]]);
+    let orders = vec![update_csv(
+        path,
+        1,
+        1,
+        core::expr::Value::Int(3),
+        formula::UpdateMode::InsertOnly,
+    )];
+
+    let results = run::run(orders, Output::InPlace).unwrap();
+    let [(indices, changes)] = &results[..] else {
+        panic!("expected one result");
+    };
+    assert_eq!(indices, &vec![0]);
+    let [change] = &changes[..] else {
+        panic!("expected one change");
+    };
+    assert_eq!(change.old, core::expr::Value::Empty);
+    assert_eq!(change.new, core::expr::Value::Int(3));
+
+    assert_eq!(
+        fixture.read_csv("data.csv"),
+        records(&[&["a", "b"], &["1", "3"]])
+    );
+}
+
+#[test]
+fn update_csv_blank_fields() {
+    let fixture = Fixture::new();
+    let path = fixture.csv("data.csv", &[&["a", "b", "c"], &["", " ", "1"]]);
+    let orders = vec![
+        update_csv(
+            path.clone(),
+            1,
+            0,
+            core::expr::Value::Int(2),
+            formula::UpdateMode::InsertOnly,
+        ),
+        update_csv(
+            path,
+            1,
+            1,
+            core::expr::Value::Int(3),
+            formula::UpdateMode::SkipIfNonEmpty,
+        ),
+    ];
+
+    // whitespace is data, so only the blank field is written
+    let results = run::run(orders, Output::InPlace).unwrap();
+    let changes = &results[0].1;
+    assert_eq!(changes[0].old, core::expr::Value::Empty);
+    assert_eq!(changes[0].outcome, lib::audit::CellOutcome::Written);
+    assert_eq!(changes[1].outcome, lib::audit::CellOutcome::Skipped);
+    assert_eq!(
+        fixture.read_csv("data.csv"),
+        records(&[&["a", "b", "c"], &["2", " ", "1"]])
+    );
+}
+
+#[test]
+fn update_csv_occupied_cell() {
+    let fixture = Fixture::new();
+    let path = fixture.csv("data.csv", &[&["a"], &["1"]]);
+    let original = fs::read(&path).unwrap();
+
+    let insert = vec![update_csv(
+        path.clone(),
+        1,
+        0,
+        core::expr::Value::Int(2),
+        formula::UpdateMode::InsertOnly,
+    )];
+    let errors = run::run(insert, Output::InPlace).unwrap_err();
+    assert!(matches!(
+        &errors[..],
+        [(0, error::WorkspaceOrder::CellOccupied(_))]
+    ));
+    assert_eq!(fs::read(&path).unwrap(), original);
+
+    let skip = vec![update_csv(
+        path.clone(),
+        1,
+        0,
+        core::expr::Value::Int(2),
+        formula::UpdateMode::SkipIfNonEmpty,
+    )];
+    let results = run::run(skip, Output::InPlace).unwrap();
+    assert_eq!(results[0].1[0].outcome, lib::audit::CellOutcome::Skipped);
+    assert_eq!(fixture.read_csv("data.csv"), records(&[&["a"], &["1"]]));
+
+    let overwrite = vec![update_csv(
+        path,
+        1,
+        0,
+        core::expr::Value::Int(2),
+        formula::UpdateMode::Overwrite,
+    )];
+    run::run(overwrite, Output::InPlace).unwrap();
+    assert_eq!(fixture.read_csv("data.csv"), records(&[&["a"], &["2"]]));
+}
+
+#[test]
+fn dry_run_writes_nothing() {
+    let fixture = Fixture::new();
+    let path = fixture.csv("data.csv", &[&["a"], &[""]]);
+    let original = fs::read(&path).unwrap();
+    let orders = vec![
+        update_csv(
+            path.clone(),
+            1,
+            0,
+            core::expr::Value::Int(1),
+            formula::UpdateMode::InsertOnly,
+        ),
+        create(fixture.path("out.csv"), formula::OutputFormat::Csv),
+    ];
+
+    let results = run::run(orders, Output::None).unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(!results[1].1.is_empty());
+    assert_eq!(fs::read(&path).unwrap(), original);
+    assert_eq!(fixture.files(), vec![PathBuf::from("data.csv")]);
+}
+
+#[test]
+fn staged_run_is_atomic() {
+    let fixture = Fixture::new();
+    let path = fixture.csv("data.csv", &[&["a"], &["1"]]);
+    let original = fs::read(&path).unwrap();
+    let orders = vec![
+        create(fixture.path("out.csv"), formula::OutputFormat::Csv),
+        update_csv(
+            path.clone(),
+            1,
+            0,
+            core::expr::Value::Int(2),
+            formula::UpdateMode::InsertOnly,
+        ),
+    ];
+
+    let errors = run::run(orders, Output::Staged).unwrap_err();
+    assert!(matches!(
+        &errors[..],
+        [(1, error::WorkspaceOrder::CellOccupied(_))]
+    ));
+    assert_eq!(fs::read(&path).unwrap(), original);
+    assert_eq!(fixture.files(), vec![PathBuf::from("data.csv")]);
+}
+
+#[test]
+fn staged_commit_is_atomic() {
+    let fixture = Fixture::new();
+    let a = fixture.csv("a.csv", &[&["old"]]);
+    let b = fixture.path("b.csv");
+    let c = fixture.path("c.csv");
+    fs::write(formula::staged_path(&a), "new\n").unwrap();
+    fs::write(formula::staged_path(&b), "new\n").unwrap();
+
+    // `c.csv` has no staged file, so can not be moved into place.
+    let err = run::commit_all(&[&a, &b, &c]).unwrap_err();
+    assert_eq!(err.0, 2);
+    assert_eq!(fixture.read_csv("a.csv"), records(&[&["old"]]));
+    assert_eq!(fixture.files(), vec![PathBuf::from("a.csv")]);
+
+    fs::write(formula::staged_path(&a), "new\n").unwrap();
+    fs::write(formula::staged_path(&b), "new\n").unwrap();
+    run::commit_all(&[&a, &b]).unwrap();
+    assert_eq!(fixture.read_csv("a.csv"), records(&[&["new"]]));
+    assert_eq!(fixture.read_csv("b.csv"), records(&[&["new"]]));
+    assert_eq!(
+        fixture.files(),
+        vec![PathBuf::from("a.csv"), PathBuf::from("b.csv")]
+    );
+}
+
+#[test]
+fn staged_commit_of_a_repeated_file() {
+    let fixture = Fixture::new();
+    let a = fixture.csv("a.csv", &[&["old"]]);
+    fs::write(formula::staged_path(&a), "new\n").unwrap();
+
+    run::commit_all(&[&a, &a]).unwrap();
+    assert_eq!(fixture.read_csv("a.csv"), records(&[&["new"]]));
+    assert_eq!(fixture.files(), vec![PathBuf::from("a.csv")]);
+}
+
+#[test]
+fn staged_run_chains_orders_on_a_file() {
+    let fixture = Fixture::new();
+    let path = fixture.csv("data.csv", &[&["a", "b"], &["1", ""], &["1", ""]]);
+    let orders = vec![
+        formula::Clean {
+            path: path.clone(),
+            options: lib::data::ImportOptions::default(),
+            header: true,
+            operations: vec![lib::clean::Operation::DropDuplicates { columns: vec![] }],
+        }
+        .into(),
+        update_csv(
+            path,
+            1,
+            1,
+            core::expr::Value::Int(2),
+            formula::UpdateMode::InsertOnly,
+        ),
+    ];
+
+    // the update is applied to the cleaned file rather than the original
+    run::run(orders, Output::Staged).unwrap();
+    assert_eq!(fixture.files(), vec![PathBuf::from("data.csv")]);
+    assert_eq!(
+        fixture.read_csv("data.csv"),
+        records(&[&["a", "b"], &["1", "2"]])
+    );
+}
+
+#[test]
+fn staged_run_commits() {
+    let fixture = Fixture::new();
+    let path = fixture.csv("data/values.csv", &[&["a"], &[""]]);
+    let orders = vec![
+        create(fixture.path("out.csv"), formula::OutputFormat::Csv),
+        update_csv(
+            path,
+            1,
+            0,
+            core::expr::Value::Int(2),
+            formula::UpdateMode::InsertOnly,
+        ),
+    ];
+
+    run::run(orders, Output::Staged).unwrap();
+    assert_eq!(
+        fixture.files(),
+        vec![PathBuf::from("data/values.csv"), PathBuf::from("out.csv")]
+    );
+    assert_eq!(
+        fixture.read_csv("data/values.csv"),
+        records(&[&["a"], &["2"]])
+    );
+}
+
+#[test]
+fn updates_to_a_file_are_coalesced() {
+    let fixture = Fixture::new();
+    let path = fixture.csv("data.csv", &[&["a", "b"], &["", ""]]);
+    let orders = vec![
+        update_csv(
+            path.clone(),
+            1,
+            0,
+            core::expr::Value::Int(1),
+            formula::UpdateMode::InsertOnly,
+        ),
+        update_csv(
+            path,
+            1,
+            1,
+            core::expr::Value::Int(2),
+            formula::UpdateMode::InsertOnly,
+        ),
+    ];
+
+    let results = run::run(orders, Output::InPlace).unwrap();
+    let [(indices, changes)] = &results[..] else {
+        panic!("expected one coalesced result");
+    };
+    assert_eq!(indices, &vec![0, 1]);
+    assert_eq!(changes.len(), 2);
+    assert_eq!(
+        fixture.read_csv("data.csv"),
+        records(&[&["a", "b"], &["1", "2"]])
+    );
+}
+
+#[test]
+fn clean_drops_duplicates() {
+    let fixture = Fixture::new();
+    let path = fixture.csv("data.csv", &[&["a"], &["1"], &["1"], &["2"]]);
+    let orders = vec![
+        formula::Clean {
+            path,
+            options: lib::data::ImportOptions::default(),
+            header: true,
+            operations: vec![lib::clean::Operation::DropDuplicates { columns: vec![] }],
+        }
+        .into(),
+    ];
+
+    run::run(orders, Output::InPlace).unwrap();
+    assert_eq!(
+        fixture.read_csv("data.csv"),
+        records(&[&["a"], &["1"], &["2"]])
+    );
+}
+
+#[test]
+fn update_workbook_is_unsupported() {
+    let fixture = Fixture::new();
+    let path = fixture.xlsx(
+        "data.xlsx",
+        &[("values", &[&["a", "b"], &["1", ""]]), ("notes", &[&["n"]])],
+    );
+    let original = fs::read(&path).unwrap();
+    let orders = vec![
+        formula::Update {
+            path: path.clone(),
+            updates: formula::Updates::Workbook(vec![formula::UpdateWorkbook {
+                sheet: 0,
+                row: 1,
+                col: 1,
+                value: core::expr::Value::Int(2),
+                formula: None,
+                provenance: None,
+                mode: formula::UpdateMode::InsertOnly,
+            }]),
+            options: lib::data::ImportOptions::default(),
+        }
+        .into(),
+    ];
+
+    let errors = run::run(orders, Output::Staged).unwrap_err();
+    assert!(matches!(
+        &errors[..],
+        [(0, error::WorkspaceOrder::Unsupported)]
+    ));
+    assert_eq!(fs::read(&path).unwrap(), original);
+    assert_eq!(fixture.files(), vec![PathBuf::from("data.xlsx")]);
+}
+
+#[test]
+fn mixed_batch_is_coalesced_per_file() {
+    let fixture = Fixture::new();
+    let csv = fixture.csv("data.csv", &[&["a", "b"], &["1", "2"]]);
+    let xlsx = fixture.xlsx("data.xlsx", &[("values", &[&["a"], &["1"]])]);
+    let original = fs::read(&xlsx).unwrap();
+    let update_workbook = |col| -> WorkspaceOrder {
+        formula::Update {
+            path: xlsx.clone(),
+            updates: formula::Updates::Workbook(vec![formula::UpdateWorkbook {
+                sheet: 0,
+                row: 1,
+                col,
+                value: core::expr::Value::Int(3),
+                formula: None,
+                provenance: None,
+                mode: formula::UpdateMode::Overwrite,
+            }]),
+            options: lib::data::ImportOptions::default(),
+        }
+        .into()
+    };
+    let orders = vec![
+        update_csv(
+            csv.clone(),
+            1,
+            0,
+            core::expr::Value::Int(3),
+            formula::UpdateMode::Overwrite,
+        ),
+        update_workbook(0),
+        update_csv(
+            csv.clone(),
+            1,
+            1,
+            core::expr::Value::Int(4),
+            formula::UpdateMode::Overwrite,
+        ),
+        update_workbook(1),
+    ];
+
+    // each workbook order reports the coalesced error
+    let errors = run::run(orders.clone(), Output::InPlace).unwrap_err();
+    assert!(matches!(
+        &errors[..],
+        [
+            (1, error::WorkspaceOrder::Unsupported),
+            (3, error::WorkspaceOrder::Unsupported)
+        ]
+    ));
+    assert_eq!(
+        fixture.read_csv("data.csv"),
+        records(&[&["a", "b"], &["3", "4"]])
+    );
+    assert_eq!(fs::read(&xlsx).unwrap(), original);
+
+    // nothing is committed when staged
+    fixture.csv("data.csv", &[&["a", "b"], &["1", "2"]]);
+    run::run(orders, Output::Staged).unwrap_err();
+    assert_eq!(
+        fixture.read_csv("data.csv"),
+        records(&[&["a", "b"], &["1", "2"]])
+    );
+    assert_eq!(
+        fixture.files(),
+        vec![PathBuf::from("data.csv"), PathBuf::from("data.xlsx")]
+    );
+}